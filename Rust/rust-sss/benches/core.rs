@@ -0,0 +1,70 @@
+//! `cargo bench`: the KDF, AES-256-GCM, share generation and reconstruction, the last three
+//! across payload sizes so a slowdown in the GF(2^8) arithmetic shows up as lost throughput.
+
+use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+use shamir_cli::bench::DEFAULT_SIZES;
+use shamir_cli::crypto::{self, NONCE_LEN, PBKDF2_ROUNDS, SALT_LEN};
+use shamir_cli::shamir;
+
+fn payload(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i * 31 + 7) as u8).collect()
+}
+
+fn kdf(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kdf");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("pbkdf2-sha256", PBKDF2_ROUNDS), |b| {
+        b.iter(|| crypto::derive_key(black_box("benchmark"), &[0u8; SALT_LEN], PBKDF2_ROUNDS).unwrap())
+    });
+    group.finish();
+}
+
+fn aead(c: &mut Criterion) {
+    let cipher = Aes256Gcm::new(&[7u8; 32].into());
+    let nonce = [0u8; NONCE_LEN];
+    let mut group = c.benchmark_group("aead");
+    for size in DEFAULT_SIZES {
+        let data = payload(size);
+        let sealed = cipher.encrypt(&nonce.into(), data.as_slice()).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("seal", size), &data, |b, data| {
+            b.iter(|| cipher.encrypt(&nonce.into(), black_box(data.as_slice())).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("open", size), &sealed, |b, sealed| {
+            b.iter(|| cipher.decrypt(&nonce.into(), black_box(sealed.as_slice())).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn split(c: &mut Criterion) {
+    let mut rng = crypto::seeded_rng("bench");
+    let mut group = c.benchmark_group("split");
+    for size in DEFAULT_SIZES {
+        let data = payload(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("3-of-5", size), &data, |b, data| {
+            b.iter(|| shamir::split_with_rng(black_box(data), 3, 5, &mut rng).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn reconstruct(c: &mut Criterion) {
+    let mut rng = crypto::seeded_rng("bench");
+    let mut group = c.benchmark_group("reconstruct");
+    for size in DEFAULT_SIZES {
+        let shares = shamir::split_with_rng(&payload(size), 3, 5, &mut rng).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("3-of-5", size), &shares[..3], |b, quorum| {
+            b.iter(|| shamir::reconstruct(black_box(quorum)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, kdf, aead, split, reconstruct);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shamir_cli::secret::{deserialize_secret, pad, serialize_secret, Padding};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(secret) = deserialize_secret(data) {
+        let mut bytes = serialize_secret(&secret);
+        let len = data.len().max(bytes.len());
+        bytes.resize(len, 0);
+        assert!(deserialize_secret(&bytes).is_ok());
+        let padded = pad(&serialize_secret(&secret), Padding::PowerOfTwo).unwrap();
+        assert!(deserialize_secret(&padded).is_ok());
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shamir_cli::crypto::split_payload;
+use shamir_cli::format::validate_shares;
+use shamir_cli::input::{normalize, parse_share};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = normalize(data) {
+        let shares: Vec<Vec<u8>> = text.split(';').filter_map(|s| parse_share(s).ok()).collect();
+        if validate_shares(&shares).is_ok() {
+            let _ = split_payload(&shares[0]);
+        }
+    }
+});
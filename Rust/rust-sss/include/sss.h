@@ -0,0 +1,114 @@
+#ifndef SSS_H
+#define SSS_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The call succeeded.
+#define SSS_OK 0
+
+// Reading or writing failed.
+#define SSS_ERR_IO 1
+
+// An argument was invalid, such as a null pointer or a threshold above the share count.
+#define SSS_ERR_INPUT 2
+
+// A share or payload is malformed or failed its checksum.
+#define SSS_ERR_PARSE 3
+
+// The shares could not be combined: too few, or from different sets.
+#define SSS_ERR_SHARE 4
+
+// Deriving the key from the password failed.
+#define SSS_ERR_KDF 5
+
+// Decryption failed: a wrong password, or shares that do not rebuild the set.
+#define SSS_ERR_DECRYPT 6
+
+// Memory for the secret could not be locked.
+#define SSS_ERR_SECURE_MEMORY 7
+
+// A share store failed.
+#define SSS_ERR_STORE 8
+
+// Recovery was not authorized.
+#define SSS_ERR_UNAUTHORIZED 9
+
+// The request violates a policy, such as a time lock that has not opened.
+#define SSS_ERR_POLICY 10
+
+// Signing or checking a signature failed.
+#define SSS_ERR_SIGNING 11
+
+// The library panicked.
+#define SSS_ERR_PANIC -1
+
+// Bytes owned by whoever the library handed them to.
+typedef struct SssBuffer {
+  uint8_t *data;
+  size_t len;
+} SssBuffer;
+
+// `count` shares, each an `SssBuffer` starting with its index byte.
+typedef struct SssShares {
+  struct SssBuffer *shares;
+  size_t count;
+} SssShares;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Encrypts `secret` under `password` and splits it into `total` shares, any `threshold` of
+// which recover it, written to `out`.
+//
+// # Safety
+//
+// `secret` must point to `secret_len` readable bytes, `password` to a NUL-terminated string,
+// and `out` to writable memory for one `SssShares`.
+int32_t sss_split(const uint8_t *secret,
+                  size_t secret_len,
+                  const char *password,
+                  uint8_t threshold,
+                  uint8_t total,
+                  struct SssShares *out);
+
+// Recovers the secret from `count` shares and writes it to `out`: the bytes given to
+// `sss_split`, or for secrets split by `sss encrypt` the text `sss decrypt` prints.
+//
+// # Safety
+//
+// `shares` must point to `count` buffers, each pointing to `len` readable bytes, `password`
+// to a NUL-terminated string, and `out` to writable memory for one `SssBuffer`.
+int32_t sss_combine(const struct SssBuffer *shares,
+                    size_t count,
+                    const char *password,
+                    struct SssBuffer *out);
+
+// Zeroes and frees a buffer filled by the library, leaving it empty.
+//
+// # Safety
+//
+// `buffer` must be null or point to an `SssBuffer` that is empty or was filled by this
+// library and not freed since.
+void sss_buffer_free(struct SssBuffer *buffer);
+
+// Zeroes and frees every share and the list itself, leaving it empty.
+//
+// # Safety
+//
+// `shares` must be null or point to an `SssShares` that is empty or was filled by
+// `sss_split` and not freed since.
+void sss_shares_free(struct SssShares *shares);
+
+// The message of the last failed call on this thread, or null. It stays valid until the next
+// call into the library on the same thread.
+const char *sss_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SSS_H */
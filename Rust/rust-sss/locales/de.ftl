@@ -0,0 +1,60 @@
+# Deutsch
+
+## Aufteilen
+
+prompt-secret = Geheimnis eingeben (Präfix hex:/base64: für Binärdaten, json: für ein JSON-Objekt)
+prompt-enter-encryption-password = Verschlüsselungspasswort eingeben
+prompt-confirm-encryption-password = Verschlüsselungspasswort bestätigen
+prompt-enter-new-encryption-password = Neues Verschlüsselungspasswort eingeben
+prompt-confirm-new-encryption-password = Neues Verschlüsselungspasswort bestätigen
+prompt-enter-decoy-password = Täuschungspasswort eingeben
+prompt-confirm-decoy-password = Täuschungspasswort bestätigen
+password-empty = Das Passwort darf nicht leer sein
+password-mismatch = Die Passwörter stimmen nicht überein; bitte erneut eingeben
+prompt-total-shares = Gesamtzahl der Anteile
+prompt-threshold = Mindestens benötigte Anteile
+prompt-make-cards = Druckbare Wiederherstellungskarten für die Verwahrer erstellen?
+prompt-cards-dir = Verzeichnis für die Karten
+
+## Wiederherstellen
+
+prompt-share = Anteil Nr. { $number } (einfügen oder @Pfad zu einer Anteilsdatei; leer zum Beenden)
+prompt-password = Verschlüsselungspasswort eingeben
+share-rejected = Anteil abgelehnt: { $reason }
+too-few-shares = nur { $given } der erforderlichen { $threshold } Anteile wurden angegeben
+recovered-secret = Wiederhergestelltes Geheimnis:
+
+## Fehler
+
+error = Fehler: { $message }
+error-aead = Entschlüsselung fehlgeschlagen: falsches Passwort, zu wenige Anteile oder ein Anteil aus einem anderen Satz
+error-wrong-password = Passwort falsch (AEAD-Authentifizierung fehlgeschlagen); die Anteile ergeben den erwarteten Satz
+
+## Wiederherstellungskarten, 40 Spalten breit
+
+card-title = WIEDERHERSTELLUNGSKARTE
+card-share = Anteil Nr. { $index }
+card-set = Satz
+card-format = Format
+card-quorum = Quorum
+card-quorum-value = beliebige { $threshold } von { $total } Anteilen
+card-recover = Zum Wiederherstellen ausführen:
+card-keep = Bewahren Sie diese Karte bei Ihrem Anteil auf. Sie enthält kein Geheimnis.
+
+## Druckbare Anteilsseiten
+
+sheet-title = Anteil Nr. { $index } von { $total }
+sheet-heading = Shamir-Anteil Nr. { $index } von { $total }
+sheet-held-by = Verwahrt von
+sheet-quorum = Quorum
+sheet-quorum-value = beliebige { $threshold } von { $total } Anteilen
+sheet-set = Anteilssatz
+sheet-created = Erstellt
+sheet-words = Wörter
+sheet-hex = Hex
+sheet-recover = Wiederherstellung
+sheet-step-gather = Sammeln Sie beliebige { $threshold } der { $total } Anteile des Satzes { $set }.
+sheet-step-run = Führen Sie { $command } aus.
+sheet-step-enter = Scannen Sie für jeden Anteil den QR-Code oder tippen Sie die Wörter oder das Hex ein, wenn Sie dazu aufgefordert werden.
+sheet-step-password = Geben Sie, falls gefragt, das Passwort ein, das beim Aufteilen des Geheimnisses gewählt wurde.
+sheet-warning = Diese Seite ist ein Anteil eines Geheimnisses. Bewahren Sie sie sicher und vertraulich auf; wer { $threshold } Anteile sammelt, und gegebenenfalls das Passwort kennt, kann das Geheimnis wiederherstellen.
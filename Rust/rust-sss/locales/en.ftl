@@ -0,0 +1,61 @@
+# English, the reference catalog: every other language translates these messages, and any
+# message a translation leaves out is shown in English. `sss locale template` prints this file.
+
+## Splitting
+
+prompt-secret = Enter secret value (prefix hex:/base64: for binary, json: for a JSON object)
+prompt-enter-encryption-password = Enter encryption password
+prompt-confirm-encryption-password = Confirm encryption password
+prompt-enter-new-encryption-password = Enter new encryption password
+prompt-confirm-new-encryption-password = Confirm new encryption password
+prompt-enter-decoy-password = Enter decoy password
+prompt-confirm-decoy-password = Confirm decoy password
+password-empty = The password cannot be empty
+password-mismatch = The passwords do not match; enter them again
+prompt-total-shares = Total number of shares
+prompt-threshold = Minimum shares required
+prompt-make-cards = Generate printable recovery cards for the custodians?
+prompt-cards-dir = Directory for the cards
+
+## Recovering
+
+prompt-share = Share #{ $number } (paste it, or @path to a share file; empty to finish)
+prompt-password = Enter encryption password
+share-rejected = Share rejected: { $reason }
+too-few-shares = only { $given } of the required { $threshold } shares were provided
+recovered-secret = Recovered secret:
+
+## Errors
+
+error = Error: { $message }
+error-aead = decryption failed: wrong password, too few shares, or a share from another set
+error-wrong-password = password incorrect (AEAD authentication failed); the shares rebuild the expected set
+
+## Recovery cards, 40 columns wide
+
+card-title = SHAMIR RECOVERY CARD
+card-share = share #{ $index }
+card-set = Set
+card-format = Format
+card-quorum = Quorum
+card-quorum-value = any { $threshold } of { $total } shares
+card-recover = To recover, run:
+card-keep = Keep this card with your share. It does not contain any secret.
+
+## Printable share pages
+
+sheet-title = Share #{ $index } of { $total }
+sheet-heading = Shamir share #{ $index } of { $total }
+sheet-held-by = Held by
+sheet-quorum = Quorum
+sheet-quorum-value = any { $threshold } of { $total } shares
+sheet-set = Share set
+sheet-created = Created
+sheet-words = Words
+sheet-hex = Hex
+sheet-recover = To recover
+sheet-step-gather = Gather any { $threshold } of the { $total } shares of set { $set }.
+sheet-step-run = Run { $command }.
+sheet-step-enter = For each share, scan the QR code, or type the words or the hex, when asked.
+sheet-step-password = If asked, enter the password that was chosen when the secret was split.
+sheet-warning = This page is a share of a secret. Keep it somewhere safe and private; anyone who collects { $threshold } shares, and the password if there is one, can recover the secret.
@@ -0,0 +1,60 @@
+# Español
+
+## Dividir
+
+prompt-secret = Introduzca el secreto (prefijo hex:/base64: para datos binarios, json: para un objeto JSON)
+prompt-enter-encryption-password = Introduzca la contraseña de cifrado
+prompt-confirm-encryption-password = Confirme la contraseña de cifrado
+prompt-enter-new-encryption-password = Introduzca la nueva contraseña de cifrado
+prompt-confirm-new-encryption-password = Confirme la nueva contraseña de cifrado
+prompt-enter-decoy-password = Introduzca la contraseña señuelo
+prompt-confirm-decoy-password = Confirme la contraseña señuelo
+password-empty = La contraseña no puede estar vacía
+password-mismatch = Las contraseñas no coinciden; vuelva a introducirlas
+prompt-total-shares = Número total de partes
+prompt-threshold = Mínimo de partes necesarias
+prompt-make-cards = ¿Generar tarjetas de recuperación imprimibles para los custodios?
+prompt-cards-dir = Directorio para las tarjetas
+
+## Recuperar
+
+prompt-share = Parte n.º { $number } (péguela, o @ruta a un archivo de parte; vacío para terminar)
+prompt-password = Introduzca la contraseña de cifrado
+share-rejected = Parte rechazada: { $reason }
+too-few-shares = solo se proporcionaron { $given } de las { $threshold } partes necesarias
+recovered-secret = Secreto recuperado:
+
+## Errores
+
+error = Error: { $message }
+error-aead = el descifrado falló: contraseña incorrecta, muy pocas partes o una parte de otro conjunto
+error-wrong-password = contraseña incorrecta (falló la autenticación AEAD); las partes reconstruyen el conjunto esperado
+
+## Tarjetas de recuperación, 40 columnas de ancho
+
+card-title = TARJETA DE RECUPERACIÓN
+card-share = parte n.º { $index }
+card-set = Conjunto
+card-format = Formato
+card-quorum = Quórum
+card-quorum-value = { $threshold } cualesquiera de { $total } partes
+card-recover = Para recuperar, ejecute:
+card-keep = Guarde esta tarjeta con su parte. No contiene ningún secreto.
+
+## Páginas de partes imprimibles
+
+sheet-title = Parte n.º { $index } de { $total }
+sheet-heading = Parte de Shamir n.º { $index } de { $total }
+sheet-held-by = En poder de
+sheet-quorum = Quórum
+sheet-quorum-value = { $threshold } cualesquiera de { $total } partes
+sheet-set = Conjunto de partes
+sheet-created = Creada
+sheet-words = Palabras
+sheet-hex = Hex
+sheet-recover = Para recuperar
+sheet-step-gather = Reúna { $threshold } cualesquiera de las { $total } partes del conjunto { $set }.
+sheet-step-run = Ejecute { $command }.
+sheet-step-enter = Para cada parte, escanee el código QR o escriba las palabras o el hex cuando se le pida.
+sheet-step-password = Si se le pide, introduzca la contraseña que se eligió al dividir el secreto.
+sheet-warning = Esta página es una parte de un secreto. Guárdela en un lugar seguro y privado; quien reúna { $threshold } partes, y la contraseña si la hay, puede recuperar el secreto.
@@ -0,0 +1,61 @@
+//! Interop with age. An identity file (`AGE-SECRET-KEY-1...` lines) can be split like any
+//! other secret, and shares can be encrypted to custodians' `age1...` recipients so that each
+//! one is opened with the ordinary `age -d -i key.txt` before it is pasted into `sss decrypt`.
+
+use ::age::x25519::{Identity, Recipient};
+use std::io::Read;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+/// Every identity in an age identity file, in order. Comments and blank lines are allowed;
+/// anything else must be an X25519 identity.
+pub fn identities(text: &str) -> Result<Vec<Identity>> {
+    let mut identities = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        identities.push(
+            Identity::from_str(line)
+                .map_err(|e| SssError::Parse(format!("age identity file, line {}: {}", n + 1, e)))?,
+        );
+    }
+    if identities.is_empty() {
+        return Err(SssError::Parse("age identity file contains no AGE-SECRET-KEY-1 lines".into()));
+    }
+    Ok(identities)
+}
+
+/// Checks every identity in an age identity file, returning the matching recipients in order.
+pub fn identity_recipients(text: &str) -> Result<Vec<String>> {
+    Ok(identities(text)?.iter().map(|identity| identity.to_public().to_string()).collect())
+}
+
+pub fn recipient(text: &str) -> Result<Recipient> {
+    Recipient::from_str(text.trim())
+        .map_err(|e| SssError::Parse(format!("age recipient '{}': {}", text.trim(), e)))
+}
+
+/// Encrypts `plaintext` to `recipient` as an ASCII-armored age file.
+pub fn encrypt_armored(recipient: &Recipient, plaintext: &[u8]) -> Result<String> {
+    ::age::encrypt_and_armor(recipient, plaintext)
+        .map_err(|e| SssError::Io(std::io::Error::other(e.to_string())))
+}
+
+/// Decrypts an age file, armored or not, with whichever of `identities` it was encrypted to.
+pub fn decrypt(identities: &[Identity], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let failed = |e: ::age::DecryptError| match e {
+        ::age::DecryptError::NoMatchingKeys => {
+            SssError::Parse("the age file was not encrypted to this identity".into())
+        }
+        e => SssError::Parse(format!("age file: {}", e)),
+    };
+    let decryptor = ::age::Decryptor::new_buffered(::age::armor::ArmoredReader::new(ciphertext)).map_err(failed)?;
+    let mut reader = decryptor.decrypt(identities.iter().map(|i| i as &dyn ::age::Identity)).map_err(failed)?;
+    let mut plaintext = Zeroizing::new(Vec::new());
+    reader.read_to_end(&mut plaintext)?;
+    Ok(plaintext)
+}
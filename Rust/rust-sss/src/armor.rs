@@ -0,0 +1,108 @@
+//! Shares in ASCII armor, laid out like OpenPGP's, which passes through mail clients, ticket
+//! systems and printers that mangle a long unbroken line:
+//!
+//! ```text
+//! -----BEGIN SSS SHARE-----
+//! Set: 4f1c2a9e0b7d3356
+//! Quorum: 2 of 3
+//! Index: 1
+//!
+//! AYHXf3BbB4wJWV2P6n3Hq0y2...
+//! =K2Fx
+//! -----END SSS SHARE-----
+//! ```
+//!
+//! The headers are the share's [`ShareHeader`](crate::format::ShareHeader) lines plus its
+//! index; the body is base64 in 64-column lines, followed by the OpenPGP CRC-24 of the share.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+use crate::error::{Result, SssError};
+
+pub const BEGIN: &str = "-----BEGIN SSS SHARE-----";
+pub const END: &str = "-----END SSS SHARE-----";
+const LINE_LEN: usize = 64;
+
+fn malformed(what: &str) -> SssError {
+    SssError::Parse(format!("armored share: {}", what))
+}
+
+/// The CRC-24 of RFC 4880, section 6.1.
+pub fn crc24(bytes: &[u8]) -> u32 {
+    let mut crc: u32 = 0xB7_04CE;
+    for &byte in bytes {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= 0x186_4CFB;
+            }
+        }
+    }
+    crc & 0xFF_FFFF
+}
+
+pub fn is_armored(text: &str) -> bool {
+    text.trim_start().starts_with(BEGIN)
+}
+
+/// The share as an armored block with an `Index:` header; [`ShareHeader::prepend`] adds the
+/// share's other headers above it.
+///
+/// [`ShareHeader::prepend`]: crate::format::ShareHeader::prepend
+pub fn encode(share: &[u8]) -> String {
+    let body = BASE64.encode(share);
+    let mut out = format!("{}\n", BEGIN);
+    if let Some(index) = share.first() {
+        out.push_str(&format!("Index: {}\n", index));
+    }
+    out.push('\n');
+    for line in body.as_bytes().chunks(LINE_LEN) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    let crc = crc24(share).to_be_bytes();
+    out.push_str(&format!("={}\n{}", BASE64.encode(&crc[1..]), END));
+    out
+}
+
+/// The header lines of the block, up to the blank line before the body.
+pub fn headers(text: &str) -> impl Iterator<Item = &str> {
+    let inner = text.trim_start().strip_prefix(BEGIN).unwrap_or_default();
+    inner.lines().skip_while(|line| line.trim().is_empty()).take_while(|line| !line.trim().is_empty())
+}
+
+/// Reads the share back out of an armored block, checking its CRC-24 and `Index:` header.
+pub fn decode(text: &str) -> Result<Vec<u8>> {
+    let inner = text
+        .trim()
+        .strip_prefix(BEGIN)
+        .and_then(|rest| rest.strip_suffix(END))
+        .ok_or_else(|| malformed(&format!("expected a block between {} and {}", BEGIN, END)))?;
+    let mut index = None;
+    let mut body = String::new();
+    let mut crc = None;
+    for line in inner.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some((key, value)) = line.split_once(": ") {
+            if key.eq_ignore_ascii_case("index") {
+                index = Some(value.trim().parse::<u8>().map_err(|_| malformed("the Index header is not a number"))?);
+            }
+        } else if let Some(checksum) = line.strip_prefix('=') {
+            crc = Some(checksum.to_string());
+        } else {
+            body.push_str(line);
+        }
+    }
+    let share = BASE64.decode(&body).map_err(|e| malformed(&format!("the body is not base64 ({})", e)))?;
+    if let Some(crc) = crc {
+        let expected = crc24(&share).to_be_bytes();
+        if BASE64.decode(&crc).ok().as_deref() != Some(&expected[1..]) {
+            return Err(SssError::Checksum("armored share: the CRC-24 does not match; the block was damaged".into()));
+        }
+    }
+    if let Some(index) = index.filter(|&index| share.first() != Some(&index)) {
+        let found = share.first().copied().unwrap_or_default();
+        return Err(malformed(&format!("the Index header says {} but the share is #{}", index, found)));
+    }
+    Ok(share)
+}
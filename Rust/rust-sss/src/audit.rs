@@ -0,0 +1,174 @@
+//! An append-only audit log of splits and recoveries for compliance records. Each line is one
+//! operation as JSON: when, what, with which parameters, the IDs of the shares involved and
+//! whether it worked, never a secret or a password. Every record carries the hash of the one
+//! before it, so editing, removing or reordering a line breaks the chain that `sss log verify`
+//! walks. Cutting records off the end leaves a shorter valid chain, so keep the head hash that
+//! `verify` prints somewhere else and check it with `--expect`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, SssError};
+use crate::secure::create_private;
+use crate::timelock::system_now;
+
+const HASH_CONTEXT: &[u8] = b"sss audit v1";
+/// The `prev` of the first record.
+pub const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub seq: u64,
+    pub at: u64,
+    pub operation: String,
+    pub parameters: Map<String, Value>,
+    /// [`share_id`]s of the shares made or combined.
+    pub shares: Vec<String>,
+    pub ok: bool,
+    pub detail: String,
+    pub prev: String,
+    pub hash: String,
+}
+
+impl Record {
+    fn compute_hash(&self) -> String {
+        let unhashed = Record { hash: String::new(), ..self.clone() };
+        let json = serde_json::to_vec(&unhashed).expect("records serialize");
+        hex::encode(Sha256::new().chain_update(HASH_CONTEXT).chain_update(json).finalize())
+    }
+}
+
+/// The short ID of a share: the first four bytes of its SHA-256, in hex.
+pub fn share_id(share: &[u8]) -> String {
+    hex::encode(&Sha256::digest(share)[..4])
+}
+
+/// The hashes of a log's records, in order, once the chain has been checked.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Chain {
+    pub hashes: Vec<String>,
+}
+
+impl Chain {
+    pub fn records(&self) -> u64 {
+        self.hashes.len() as u64
+    }
+
+    /// The hash of the last record, which the next one must follow.
+    pub fn head(&self) -> &str {
+        self.hashes.last().map_or(GENESIS, String::as_str)
+    }
+
+    /// How many records follow the one hashed `hash`, or `None` when no record has that hash,
+    /// as after the log was cut short.
+    pub fn records_after(&self, hash: &str) -> Option<u64> {
+        match hash.eq_ignore_ascii_case(GENESIS) {
+            true => Some(self.records()),
+            false => self.hashes.iter().position(|h| h.eq_ignore_ascii_case(hash)).map(|i| self.records() - i as u64 - 1),
+        }
+    }
+}
+
+fn read_records(file: impl Read) -> Result<Vec<Record>> {
+    let reader = BufReader::new(file);
+    let mut records = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line)
+            .map_err(|e| SssError::Parse(format!("audit log line {}: {}", i + 1, e)))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Walks the chain of the log at `path`, failing at the first record that was altered,
+/// removed or moved.
+pub fn verify(path: &Path) -> Result<Chain> {
+    verify_records(File::open(path)?)
+}
+
+fn verify_records(file: impl Read) -> Result<Chain> {
+    let mut chain = Chain::default();
+    let records = read_records(file)?;
+    for (i, record) in records.iter().enumerate() {
+        let broken = |why: &str| SssError::Policy(format!("audit log record {} {}; the log was tampered with", i + 1, why));
+        if record.seq != i as u64 + 1 {
+            return Err(broken(&format!("is numbered {}", record.seq)));
+        }
+        if record.prev != chain.head() {
+            return Err(broken("does not follow the record before it"));
+        }
+        if record.hash != record.compute_hash() {
+            return Err(broken("does not match its hash"));
+        }
+        chain.hashes.push(record.hash.clone());
+    }
+    Ok(chain)
+}
+
+/// A log opened for appending.
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        AuditLog { path: path.into() }
+    }
+
+    /// Appends a record chained to the last one. The chain is checked first, so a record is
+    /// never added to a log that was tampered with, under an exclusive lock held until the
+    /// record is written, so two processes never chain records to the same head. A new log is
+    /// created readable only by its owner.
+    pub fn append(
+        &self,
+        operation: &str,
+        parameters: Map<String, Value>,
+        shares: Vec<String>,
+        ok: bool,
+        detail: &str,
+    ) -> Result<Record> {
+        let mut file = self.open()?;
+        file.lock()?;
+        let chain = verify_records(&file)?;
+        let mut record = Record {
+            seq: chain.records() + 1,
+            at: system_now(),
+            operation: operation.to_string(),
+            parameters,
+            shares,
+            ok,
+            detail: detail.to_string(),
+            prev: chain.head().to_string(),
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash();
+        let line = serde_json::to_string(&record).map_err(|e| SssError::Parse(e.to_string()))?;
+        writeln!(file, "{}", line)?;
+        file.sync_all()?;
+        Ok(record)
+    }
+
+    /// Opens the log to read and append, creating it first if there is none yet.
+    fn open(&self) -> Result<File> {
+        loop {
+            match OpenOptions::new().read(true).append(true).open(&self.path) {
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                opened => return Ok(opened?),
+            }
+            if let Err(e) = create_private(&self.path) {
+                // another process may have created it first
+                if !self.path.exists() {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
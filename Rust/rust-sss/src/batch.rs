@@ -0,0 +1,252 @@
+//! Splitting many labelled secrets in one run, for rotations where the interactive flow would
+//! be run dozens of times. Rows come from a JSON array or a CSV file with a header line:
+//!
+//! ```text
+//! label,secret,threshold,total,encoding
+//! stripe-live,sk_live_...,3,5,
+//! github-deploy,ghp_...,2,3,bech32
+//! ```
+//!
+//! Every row is checked before anything is written. Each secret is then encrypted under the
+//! one batch password and its shares written to a directory of its own, as `encrypt --out-dir`
+//! does, with `batch.json` beside them listing every set.
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::{Result, SssError};
+use crate::format::{set_fingerprint, ShareEncoding, ShareHeader, FORMAT_VERSION};
+use crate::manifest::{self, ShareFile};
+use crate::pipeline::seal_bound;
+use crate::secret::{pad, serialize_secret, Padding, SecretType};
+use crate::shamir::Field;
+use crate::timelock::system_now;
+
+pub const SUMMARY_FILE: &str = "batch.json";
+
+/// One secret to split.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Row {
+    pub label: String,
+    pub secret: String,
+    pub threshold: u8,
+    pub total: u8,
+    #[serde(default, deserialize_with = "encoding")]
+    pub encoding: Option<ShareEncoding>,
+}
+
+impl Drop for Row {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.secret);
+    }
+}
+
+fn encoding<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<ShareEncoding>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(text) if !text.trim().is_empty() => text.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+impl Row {
+    /// The row's directory name: its label in lowercase, with anything but letters and digits
+    /// turned into dashes, and a dash added to the names Windows keeps for devices (`con`,
+    /// `nul`, `com1` ..), so a batch written anywhere can be copied to a Windows machine.
+    pub fn dir_name(&self) -> String {
+        let dash = |c: char| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' };
+        let name: String = self.label.trim().chars().map(dash).collect();
+        let device = matches!(name.as_str(), "con" | "prn" | "aux" | "nul")
+            || (name.len() == 4
+                && (name.starts_with("com") || name.starts_with("lpt"))
+                && name.as_bytes()[3].is_ascii_digit());
+        match device {
+            true => name + "-",
+            false => name,
+        }
+    }
+}
+
+/// A set written by the batch, as listed in `batch.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub label: String,
+    /// The set's directory, relative to the batch directory.
+    pub dir: String,
+    pub set_fingerprint: String,
+    pub threshold: u8,
+    pub total: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub format_version: u8,
+    pub created_at: u64,
+    pub sets: Vec<Entry>,
+}
+
+/// Splits CSV text into records of fields, honouring double-quoted fields with `""` escapes
+/// and line breaks inside quotes.
+fn csv_records(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(SssError::Parse("the CSV ends inside a quoted field".into()));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    Ok(records)
+}
+
+/// Reads rows from CSV with a header line naming the columns.
+pub fn parse_csv(text: &str) -> Result<Vec<Row>> {
+    let mut records = csv_records(text)?.into_iter();
+    let header: Vec<String> = records.next().unwrap_or_default().iter().map(|h| h.trim().to_lowercase()).collect();
+    records
+        .enumerate()
+        .map(|(i, record)| {
+            if record.len() != header.len() {
+                return Err(SssError::Parse(format!(
+                    "CSV row {} has {} fields but the header names {}",
+                    i + 1,
+                    record.len(),
+                    header.len()
+                )));
+            }
+            let object: serde_json::Map<String, serde_json::Value> = header
+                .iter()
+                .zip(record)
+                .map(|(name, value)| {
+                    let value = match name.as_str() {
+                        "threshold" | "total" => value.trim().parse::<u64>().map_or(value.into(), Into::into),
+                        _ => value.into(),
+                    };
+                    (name.clone(), value)
+                })
+                .collect();
+            serde_json::from_value(object.into()).map_err(|e| SssError::Parse(format!("CSV row {}: {}", i + 1, e)))
+        })
+        .collect()
+}
+
+pub fn parse_json(text: &str) -> Result<Vec<Row>> {
+    serde_json::from_str(text).map_err(|e| SssError::Parse(format!("batch JSON: {}", e)))
+}
+
+/// Reads the rows in `path`: JSON when it ends in `.json` or starts with `[`, CSV otherwise.
+pub fn read_rows(path: &Path) -> Result<Vec<Row>> {
+    let text = zeroize::Zeroizing::new(std::fs::read_to_string(path)?);
+    let json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    match json || text.trim_start().starts_with('[') {
+        true => parse_json(&text),
+        false => parse_csv(&text),
+    }
+}
+
+/// Checks every row before anything is split: labels present and distinct as directories,
+/// a possible quorum, and no empty secret.
+pub fn check(rows: &[Row]) -> Result<()> {
+    if rows.is_empty() {
+        return Err(SssError::Parse("the batch has no rows".into()));
+    }
+    let mut dirs = HashSet::new();
+    for (i, row) in rows.iter().enumerate() {
+        let problem = if row.label.trim().is_empty() {
+            Some("has no label".to_string())
+        } else if !dirs.insert(row.dir_name()) {
+            Some(format!("'{}' would share a directory with an earlier row", row.label))
+        } else if row.secret.is_empty() {
+            Some("has an empty secret".to_string())
+        } else if row.total < 2 || row.threshold < 2 || row.threshold > row.total {
+            Some(format!(
+                "asks for {} of {} shares; use 2 to 255 shares and a threshold of 2 up to the total",
+                row.threshold, row.total
+            ))
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(SssError::Policy(format!("batch row {} {}", i + 1, problem)));
+        }
+    }
+    Ok(())
+}
+
+/// Splits every row into `dir`, one directory per row, and writes the summary there.
+pub fn split_all<R: RngCore + ?Sized>(
+    rows: &[Row],
+    dir: &Path,
+    password: &str,
+    rounds: u32,
+    rng: &mut R,
+) -> Result<(Summary, Vec<Vec<u8>>)> {
+    check(rows)?;
+    let summary_path = dir.join(SUMMARY_FILE);
+    if let Some(taken) = std::iter::once(summary_path.clone())
+        .chain(rows.iter().map(|row| dir.join(row.dir_name())))
+        .find(|path| path.exists())
+    {
+        return Err(SssError::Policy(format!("{} already exists; not writing over it", taken.display())));
+    }
+    let mut sets = Vec::with_capacity(rows.len());
+    let mut all_shares = Vec::new();
+    for row in rows {
+        let plaintext = pad(&serialize_secret(&SecretType::String(row.secret.clone())), Padding::default())?;
+        let payload = seal_bound(&plaintext, password, rounds, row.threshold, rng)?;
+        let shares = Field::Gf256.split_with_rng(&payload, row.threshold, row.total, rng)?;
+        let fingerprint = set_fingerprint(&payload);
+        let header = ShareHeader {
+            label: Some(row.label.clone()),
+            note: None,
+            set: Some(fingerprint.clone()),
+            quorum: Some((row.threshold, row.total)),
+            parents: Vec::new(),
+        };
+        let encoding = row.encoding.unwrap_or_default();
+        let files = shares
+            .iter()
+            .map(|share| {
+                let text = header.prepend(&encoding.encode(share)?);
+                Ok(ShareFile { share, text, label: header.label.clone() })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        manifest::write_share_dir(&dir.join(row.dir_name()), &files, &fingerprint, "gf256", row.threshold, None, None)?;
+        sets.push(Entry {
+            label: row.label.clone(),
+            dir: row.dir_name(),
+            set_fingerprint: fingerprint,
+            threshold: row.threshold,
+            total: row.total,
+        });
+        all_shares.extend(shares);
+    }
+    let summary = Summary { format_version: FORMAT_VERSION, created_at: system_now(), sets };
+    let json = serde_json::to_string_pretty(&summary).map_err(|e| SssError::Parse(e.to_string()))?;
+    std::fs::write(&summary_path, json + "\n")?;
+    Ok((summary, all_shares))
+}
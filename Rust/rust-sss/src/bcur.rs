@@ -0,0 +1,177 @@
+//! Blockchain Commons Uniform Resources, so shares and seeds travel over QR to and from
+//! hardware wallets and apps that speak UR. A share is `ur:crypto-sss` and a seed
+//! `ur:crypto-seed` (a CBOR map with the entropy under key 1), while `ur:bytes` carries plain
+//! bytes either way. Long resources are cut into fountain-coded parts (`ur:<type>/<n>-<m>/...`)
+//! for animated QR codes.
+
+use minicbor::data::Type;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+pub const SHARE_TYPE: &str = "crypto-sss";
+pub const SEED_TYPE: &str = "crypto-seed";
+pub const BYTES_TYPE: &str = "bytes";
+
+pub fn is_ur(text: &str) -> bool {
+    text.trim().get(..3).is_some_and(|scheme| scheme.eq_ignore_ascii_case("ur:"))
+}
+
+fn ur_error(detail: impl std::fmt::Display) -> SssError {
+    SssError::Parse(detail.to_string())
+}
+
+fn malformed(e: ur::ur::Error) -> SssError {
+    SssError::Parse(format!("malformed UR ({:?})", e))
+}
+
+fn cbor_bytes(bytes: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut encoder = minicbor::Encoder::new(Vec::new());
+    encoder.bytes(bytes).expect("writing to a Vec cannot fail");
+    Zeroizing::new(encoder.into_writer())
+}
+
+fn read_cbor_bytes(cbor: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let mut decoder = minicbor::Decoder::new(cbor);
+    let bytes = decoder.bytes().map_err(|e| ur_error(format!("expected a CBOR byte string: {}", e)))?;
+    if decoder.position() != cbor.len() {
+        return Err(ur_error("trailing data after the CBOR byte string"));
+    }
+    Ok(Zeroizing::new(bytes.to_vec()))
+}
+
+/// What a single-part resource holds.
+pub enum Resource {
+    Share(Vec<u8>),
+    Seed(Zeroizing<Vec<u8>>),
+    Bytes(Zeroizing<Vec<u8>>),
+}
+
+pub fn decode(text: &str) -> Result<Resource> {
+    let text = Zeroizing::new(text.trim().to_ascii_lowercase());
+    let ur_type = text.get(3..).and_then(|rest| rest.split('/').next()).unwrap_or_default().to_string();
+    let cbor = match ur::ur::decode(&text).map_err(malformed)? {
+        (ur::ur::Kind::SinglePart, cbor) => Zeroizing::new(cbor),
+        (ur::ur::Kind::MultiPart, _) => {
+            return Err(ur_error("this is one part of a multi-part resource; scan all of it with decrypt --scan"))
+        }
+    };
+    match ur_type.as_str() {
+        SHARE_TYPE => Ok(Resource::Share(read_cbor_bytes(&cbor)?.to_vec())),
+        SEED_TYPE => Ok(Resource::Seed(read_seed(&cbor)?)),
+        BYTES_TYPE => Ok(Resource::Bytes(read_cbor_bytes(&cbor)?)),
+        other => Err(ur_error(format!("ur:{} is not a type sss reads", other))),
+    }
+}
+
+/// A share as `ur:crypto-sss`.
+pub fn encode_share(share: &[u8]) -> String {
+    ur::ur::encode(&cbor_bytes(share), &ur::Type::Custom(SHARE_TYPE))
+}
+
+/// A share from `ur:crypto-sss` or `ur:bytes`.
+pub fn decode_share(text: &str) -> Result<Vec<u8>> {
+    match decode(text)? {
+        Resource::Share(share) => Ok(share),
+        Resource::Bytes(bytes) => Ok(bytes.to_vec()),
+        Resource::Seed(_) => Err(ur_error("a ur:crypto-seed is a secret, not a share")),
+    }
+}
+
+pub fn encode_bytes(bytes: &[u8]) -> String {
+    ur::ur::encode(&cbor_bytes(bytes), &ur::Type::Bytes)
+}
+
+/// Seed entropy as `ur:crypto-seed`.
+pub fn encode_seed(entropy: &[u8]) -> String {
+    let mut encoder = minicbor::Encoder::new(Vec::new());
+    encoder.map(1).and_then(|e| e.u32(1)).and_then(|e| e.bytes(entropy)).expect("writing to a Vec cannot fail");
+    let cbor = Zeroizing::new(encoder.into_writer());
+    ur::ur::encode(&cbor, &ur::Type::Custom(SEED_TYPE))
+}
+
+/// The entropy of a `crypto-seed`, ignoring its optional creation date, name and note.
+fn read_seed(cbor: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let malformed = |e: minicbor::decode::Error| ur_error(format!("malformed crypto-seed: {}", e));
+    let mut decoder = minicbor::Decoder::new(cbor);
+    let entries = decoder.map().map_err(malformed)?.ok_or_else(|| ur_error("indefinite crypto-seed map"))?;
+    let mut entropy = None;
+    for _ in 0..entries {
+        match decoder.datatype().map_err(malformed)? {
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 if decoder.u32().map_err(malformed)? == 1 => {
+                entropy = Some(Zeroizing::new(decoder.bytes().map_err(malformed)?.to_vec()));
+            }
+            // a key that was just read as a number leaves only its value to skip
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 => decoder.skip().map_err(malformed)?,
+            _ => decoder.skip().and_then(|_| decoder.skip()).map_err(malformed)?,
+        }
+    }
+    entropy.ok_or_else(|| ur_error("the crypto-seed has no payload"))
+}
+
+/// The parts of a multi-part `ur:crypto-sss`, for an animated QR code: frames `1..=count`
+/// and then as many mixed ones as wanted.
+pub struct ShareParts {
+    encoder: ur::Encoder<'static>,
+}
+
+impl ShareParts {
+    pub fn new(share: &[u8], fragment_len: usize) -> Result<Self> {
+        let encoder = ur::Encoder::new(&cbor_bytes(share), fragment_len, SHARE_TYPE).map_err(malformed)?;
+        Ok(ShareParts { encoder })
+    }
+
+    pub fn fragment_count(&self) -> usize {
+        self.encoder.fragment_count()
+    }
+
+    /// The next part, uppercased as QR codes store most compactly.
+    pub fn next_part(&mut self) -> Result<String> {
+        Ok(self.encoder.next_part().map_err(malformed)?.to_ascii_uppercase())
+    }
+}
+
+/// Whether `text` is one part of a multi-part resource, and how many fragments it has.
+pub fn part_count(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if !is_ur(text) {
+        return None;
+    }
+    let mut fields = text.split('/');
+    let (_, Some(position), Some(_), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+        return None;
+    };
+    position.split_once('-')?.1.parse().ok()
+}
+
+/// Collects the parts of one multi-part share.
+#[derive(Default)]
+pub struct PartsDecoder {
+    decoder: ur::Decoder,
+    received: Vec<String>,
+}
+
+impl PartsDecoder {
+    pub fn receive(&mut self, text: &str) -> Result<()> {
+        let text = text.trim().to_ascii_lowercase();
+        if self.received.contains(&text) {
+            return Ok(());
+        }
+        self.decoder.receive(&text).map_err(malformed)?;
+        self.received.push(text);
+        Ok(())
+    }
+
+    /// How many distinct parts have come in.
+    pub fn received(&self) -> usize {
+        self.received.len()
+    }
+
+    /// The share, once enough parts are in.
+    pub fn share(&self) -> Result<Option<Vec<u8>>> {
+        match self.decoder.message().map_err(malformed)? {
+            Some(cbor) => Ok(Some(read_cbor_bytes(&Zeroizing::new(cbor))?.to_vec())),
+            None => Ok(None),
+        }
+    }
+}
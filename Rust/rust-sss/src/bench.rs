@@ -0,0 +1,122 @@
+//! Timings of the slow parts of a split and a recovery, for `sss bench`: one PBKDF2 key
+//! derivation, AES-256-GCM over the padded secret, and share generation and reconstruction over
+//! GF(2^8). The criterion benches in `benches/` cover the same operations in more detail; this
+//! is the quick number to compare between machines or releases.
+
+use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use rand::RngCore;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::crypto::{self, NONCE_LEN, SALT_LEN};
+use crate::error::Result;
+use crate::shamir;
+
+/// Payload sizes `sss bench` measures by default: a short password, a key file, and two large
+/// files, in bytes.
+pub const DEFAULT_SIZES: [usize; 4] = [32, 4 * 1024, 64 * 1024, 1024 * 1024];
+
+/// One operation, repeated until `min_time` has passed.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// `kdf`, `aead-seal`, `aead-open`, `split` or `reconstruct`.
+    pub operation: String,
+    /// Payload bytes per run; 0 for the KDF, whose cost does not depend on the secret.
+    pub bytes: usize,
+    pub runs: u32,
+    /// Mean time of one run, in microseconds.
+    pub mean_us: f64,
+    /// Payload bytes per second, or `None` for the KDF.
+    pub mib_per_s: Option<f64>,
+}
+
+impl Measurement {
+    fn new(operation: &str, bytes: usize, runs: u32, elapsed: Duration) -> Self {
+        let mean = elapsed.as_secs_f64() / f64::from(runs.max(1));
+        Measurement {
+            operation: operation.to_string(),
+            bytes,
+            runs,
+            mean_us: mean * 1e6,
+            mib_per_s: (bytes > 0).then(|| bytes as f64 / mean / (1024.0 * 1024.0)),
+        }
+    }
+}
+
+/// What to measure.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub sizes: Vec<usize>,
+    /// PBKDF2 rounds for the KDF timing.
+    pub rounds: u32,
+    pub threshold: u8,
+    pub total: u8,
+    /// Each operation is repeated at least this long, and at least once.
+    pub min_time: Duration,
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Plan {
+            sizes: DEFAULT_SIZES.to_vec(),
+            rounds: crypto::PBKDF2_ROUNDS,
+            threshold: 3,
+            total: 5,
+            min_time: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Runs `run` until `min_time` has passed and returns how often it ran and how long that took.
+fn repeat(min_time: Duration, mut run: impl FnMut() -> Result<()>) -> Result<(u32, Duration)> {
+    let started = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || started.elapsed() < min_time {
+        run()?;
+        runs += 1;
+    }
+    Ok((runs, started.elapsed()))
+}
+
+/// Measures the KDF once and every other operation at every size in `plan`.
+pub fn run(plan: &Plan) -> Result<Vec<Measurement>> {
+    let mut rng = rand::rngs::OsRng;
+    let mut results = Vec::new();
+    // an empty split checks the share counts before anything slow is timed
+    shamir::split_with_rng(&[], plan.threshold, plan.total, &mut rng)?;
+
+    let salt = [0u8; SALT_LEN];
+    let (runs, elapsed) = repeat(plan.min_time, || {
+        crypto::derive_key("benchmark", &salt, plan.rounds).map(drop)
+    })?;
+    results.push(Measurement::new("kdf", 0, runs, elapsed));
+
+    let mut key = [0u8; 32];
+    rng.fill_bytes(&mut key);
+    let cipher = Aes256Gcm::new(&key.into());
+    let nonce = [0u8; NONCE_LEN];
+    for &size in &plan.sizes {
+        let mut payload = vec![0u8; size];
+        rng.fill_bytes(&mut payload);
+
+        let (runs, elapsed) = repeat(plan.min_time, || {
+            cipher.encrypt(&nonce.into(), payload.as_slice()).map(drop).map_err(Into::into)
+        })?;
+        results.push(Measurement::new("aead-seal", size, runs, elapsed));
+        let sealed = cipher.encrypt(&nonce.into(), payload.as_slice())?;
+        let (runs, elapsed) = repeat(plan.min_time, || {
+            cipher.decrypt(&nonce.into(), sealed.as_slice()).map(drop).map_err(Into::into)
+        })?;
+        results.push(Measurement::new("aead-open", size, runs, elapsed));
+
+        let (runs, elapsed) = repeat(plan.min_time, || {
+            shamir::split_with_rng(&payload, plan.threshold, plan.total, &mut rng).map(drop)
+        })?;
+        results.push(Measurement::new("split", size, runs, elapsed));
+        let shares = shamir::split_with_rng(&payload, plan.threshold, plan.total, &mut rng)?;
+        let quorum = &shares[..plan.threshold as usize];
+        let (runs, elapsed) = repeat(plan.min_time, || shamir::reconstruct(quorum).map(drop))?;
+        results.push(Measurement::new("reconstruct", size, runs, elapsed));
+    }
+    Ok(results)
+}
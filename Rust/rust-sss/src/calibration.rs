@@ -0,0 +1,56 @@
+//! The PBKDF2 cost `sss bench-kdf` measured on this machine, which `encrypt` uses unless
+//! `--kdf-rounds` says otherwise. It lives in `kdf.json` under `$SSS_CONFIG_DIR`, or else
+//! `$XDG_CONFIG_HOME/sss` or `~/.config/sss`.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::error::{Result, SssError};
+
+pub const CALIBRATION_FILE: &str = "kdf.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KdfCalibration {
+    pub algorithm: String,
+    pub rounds: u32,
+    pub target_ms: u64,
+    /// How long `rounds` took when measured.
+    pub measured_ms: u64,
+    /// Unix seconds.
+    pub measured_at: u64,
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    var("SSS_CONFIG_DIR")
+        .or_else(|| var("XDG_CONFIG_HOME").map(|d| d.join("sss")))
+        .or_else(|| var("HOME").map(|d| d.join(".config").join("sss")))
+}
+
+pub fn path() -> Result<PathBuf> {
+    config_dir()
+        .map(|dir| dir.join(CALIBRATION_FILE))
+        .ok_or_else(|| SssError::Parse("no configuration directory: set SSS_CONFIG_DIR or HOME".into()))
+}
+
+/// The saved calibration, or `None` if there is none yet.
+pub fn load() -> Result<Option<KdfCalibration>> {
+    let path = path()?;
+    match std::fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| SssError::Parse(format!("{} is not a KDF calibration: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save(calibration: &KdfCalibration) -> Result<PathBuf> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let text = serde_json::to_string_pretty(calibration).map_err(|e| SssError::Parse(e.to_string()))?;
+    std::fs::write(&path, text + "\n")?;
+    Ok(path)
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::secure::write_private;
+use crate::tr;
+
+pub const CARD_WIDTH_MM: f32 = 85.6;
+pub const CARD_HEIGHT_MM: f32 = 53.98;
+const TEXT_COLUMNS: usize = 40;
+const SVG_MARGIN: f32 = 3.5;
+
+/// Wallet-sized reference card handed to a custodian along with their share.
+#[derive(Debug, Clone)]
+pub struct RecoveryCard {
+    pub command: String,
+    pub format_version: u8,
+    pub set_fingerprint: String,
+    pub share_index: u8,
+    pub threshold: u8,
+    pub total: u8,
+}
+
+impl RecoveryCard {
+    /// The card's text, in the language messages are shown in.
+    pub fn lines(&self) -> Vec<String> {
+        let share = tr!("card-share", index = self.share_index);
+        let title = tr!("card-title");
+        let labels = [tr!("card-set"), tr!("card-format"), tr!("card-quorum")];
+        let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or_default() + 2;
+        let field = |label: &str, value: String| format!("{:<width$}{}", format!("{}:", label), value, width = width);
+        let mut lines = vec![
+            format!("{:<w$}{:>14}", title, share, w = TEXT_COLUMNS.saturating_sub(share.chars().count().max(14))),
+            String::new(),
+            field(&labels[0], self.set_fingerprint.clone()),
+            field(&labels[1], format!("v{}", self.format_version)),
+            field(&labels[2], tr!("card-quorum-value", threshold = self.threshold, total = self.total)),
+            String::new(),
+        ];
+        lines.extend(wrap(&tr!("card-recover"), TEXT_COLUMNS));
+        lines.extend(wrap(&self.command, TEXT_COLUMNS - 2).into_iter().map(|l| format!("  {}", l)));
+        lines.push(String::new());
+        lines.extend(wrap(&tr!("card-keep"), TEXT_COLUMNS));
+        lines
+    }
+
+    pub fn to_text(&self) -> String {
+        let border = format!("+{}+", "-".repeat(TEXT_COLUMNS + 2));
+        let mut out = border.clone();
+        out.push('\n');
+        for line in self.lines() {
+            out.push_str(&format!("| {:<width$} |\n", line, width = TEXT_COLUMNS));
+        }
+        out.push_str(&border);
+        out.push('\n');
+        out
+    }
+
+    /// Renders the card at its physical size so it prints 1:1 from any browser or viewer.
+    pub fn to_svg(&self) -> String {
+        let lines = self.lines();
+        let usable_height = CARD_HEIGHT_MM - 2.0 * SVG_MARGIN;
+        let usable_width = CARD_WIDTH_MM - 2.0 * SVG_MARGIN;
+        // monospace glyphs are roughly 0.6em wide; pick the largest size that fits both ways
+        let line_height = usable_height / lines.len() as f32;
+        let font_size = (line_height / 1.2).min(usable_width / (TEXT_COLUMNS as f32 * 0.6));
+
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\">\n\
+             <rect x=\"0.25\" y=\"0.25\" width=\"{rw}\" height=\"{rh}\" rx=\"3.18\" fill=\"white\" stroke=\"black\" stroke-width=\"0.25\"/>\n\
+             <g font-family=\"monospace\" font-size=\"{fs:.2}\" fill=\"black\" xml:space=\"preserve\">\n",
+            w = CARD_WIDTH_MM,
+            h = CARD_HEIGHT_MM,
+            rw = CARD_WIDTH_MM - 0.5,
+            rh = CARD_HEIGHT_MM - 0.5,
+            fs = font_size,
+        );
+        for (i, line) in lines.iter().enumerate() {
+            let y = SVG_MARGIN + line_height * (i as f32 + 1.0) - (line_height - font_size) / 2.0;
+            out.push_str(&format!(
+                "<text x=\"{:.2}\" y=\"{:.2}\">{}</text>\n",
+                SVG_MARGIN,
+                y,
+                escape_xml(line)
+            ));
+        }
+        out.push_str("</g>\n</svg>\n");
+        out
+    }
+}
+
+/// Writes a `.txt` and `.svg` rendering of every card into `dir`.
+pub fn write_cards<P: AsRef<Path>>(dir: P, cards: &[RecoveryCard]) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for card in cards {
+        let stem = format!("card-{:03}", card.share_index);
+        let text_path = dir.join(format!("{}.txt", stem));
+        write_private(&text_path, card.to_text().as_bytes())?;
+        let svg_path = dir.join(format!("{}.svg", stem));
+        write_private(&svg_path, card.to_svg().as_bytes())?;
+        written.push(text_path);
+        written.push(svg_path);
+    }
+    Ok(written)
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,133 @@
+//! Ceremony records: a plain-text account of one split or recovery to file with the legal
+//! paperwork. It is built from what the audit log records (the parameters, the IDs of the shares
+//! involved and the outcome) plus who took part, every check made along the way and when it
+//! started and finished. Secrets, passwords and share contents never appear in it. Signed with
+//! an Ed25519 key, the record ends in a signature block over everything above it, which
+//! `sss log verify-ceremony` checks.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+use crate::error::{Result, SssError};
+use crate::timelock::format_date;
+
+const BEGIN_SIGNATURE: &str = "-----BEGIN SSS CEREMONY SIGNATURE-----";
+const END_SIGNATURE: &str = "-----END SSS CEREMONY SIGNATURE-----";
+
+/// One validation made during the ceremony, as the session transcript records it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub at: u64,
+    pub subject: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ceremony {
+    pub operation: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub parameters: Map<String, Value>,
+    /// Custodians and share labels named during the ceremony.
+    pub participants: Vec<String>,
+    /// [`crate::audit::share_id`]s of the shares made or combined.
+    pub shares: Vec<String>,
+    pub checks: Vec<Check>,
+    pub ok: bool,
+    pub detail: String,
+    /// Sequence number and hash of the audit log record written for the same operation.
+    pub audit_record: Option<(u64, String)>,
+}
+
+impl Ceremony {
+    /// The record as text, unsigned.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let outcome = match self.ok {
+            true => self.detail.clone(),
+            false => format!("FAILED: {}", self.detail),
+        };
+        let _ = writeln!(text, "SSS CEREMONY RECORD");
+        let _ = writeln!(text);
+        let _ = writeln!(text, "Operation:  {}", self.operation);
+        let _ = writeln!(text, "Outcome:    {}", outcome);
+        let _ = writeln!(text, "Started:    {}", format_date(self.started_at));
+        let _ = writeln!(text, "Finished:   {}", format_date(self.finished_at));
+        let _ = writeln!(text, "Tool:       sss {}", env!("CARGO_PKG_VERSION"));
+        if let Some((seq, hash)) = &self.audit_record {
+            let _ = writeln!(text, "Audit log:  record {}, hash {}", seq, hash);
+        }
+
+        let _ = writeln!(text, "\nParameters");
+        for (key, value) in &self.parameters {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Array(items) => {
+                    let items: Vec<String> = items.iter().map(|v| v.as_str().map_or(v.to_string(), str::to_string)).collect();
+                    items.join(", ")
+                }
+                other => other.to_string(),
+            };
+            let _ = writeln!(text, "  {:<12} {}", key, value);
+        }
+        let _ = writeln!(text, "\nParticipants");
+        if self.participants.is_empty() {
+            let _ = writeln!(text, "  (none named)");
+        }
+        for participant in &self.participants {
+            let _ = writeln!(text, "  {}", participant);
+        }
+        let _ = writeln!(text, "\nShares (first 4 bytes of SHA-256)");
+        for (i, id) in self.shares.iter().enumerate() {
+            let _ = writeln!(text, "  {:>3}. {}", i + 1, id);
+        }
+        let _ = writeln!(text, "\nChecks");
+        for check in &self.checks {
+            let verdict = if check.ok { "ok" } else { "FAILED" };
+            let time = format_date(check.at);
+            let time = time.split(' ').nth(1).unwrap_or_default();
+            let _ = writeln!(text, "  {}  {:<6}  {}: {}", time, verdict, check.subject, check.detail);
+        }
+        text
+    }
+
+    /// The record followed by an Ed25519 signature over it.
+    pub fn sign(&self, key: &SigningKey) -> String {
+        let text = self.render();
+        let signature = key.sign(text.as_bytes());
+        format!(
+            "{}\n{}\nkey: {}\nsignature: {}\n{}\n",
+            text,
+            BEGIN_SIGNATURE,
+            hex::encode(key.verifying_key().to_bytes()),
+            hex::encode(signature.to_bytes()),
+            END_SIGNATURE
+        )
+    }
+}
+
+/// Checks the signature at the end of a record and returns the key that made it. With `signer`
+/// set, the record must have been signed by that key.
+pub fn verify(record: &str, signer: Option<&VerifyingKey>) -> Result<VerifyingKey> {
+    let (body, block) = record
+        .split_once(&format!("\n{}\n", BEGIN_SIGNATURE))
+        .ok_or_else(|| SssError::Signing("the ceremony record is not signed".into()))?;
+    let field = |name: &str| block.lines().find_map(|line| line.strip_prefix(name)).map(str::trim);
+    if !block.trim_end().ends_with(END_SIGNATURE) {
+        return Err(SssError::Signing("the ceremony record's signature is cut short".into()));
+    }
+    let public_key = field("key: ").ok_or_else(|| SssError::Signing("the ceremony record names no key".into()))?;
+    let key = crate::token::parse_verifying_key(public_key)?;
+    if signer.is_some_and(|expected| *expected != key) {
+        return Err(SssError::Signing(format!("the ceremony record was signed by another key ({})", public_key)));
+    }
+    let signature = field("signature: ")
+        .and_then(|text| hex::decode(text).ok())
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| SssError::Signing("malformed ceremony record signature".into()))?;
+    key.verify(body.as_bytes(), &signature)
+        .map_err(|_| SssError::Signing("the ceremony record was changed after it was signed".into()))?;
+    Ok(key)
+}
@@ -0,0 +1,155 @@
+//! Shell completion worked out from the clap definition of the command line.
+//!
+//! `sss completions <shell>` prints a script that registers `sss` with the shell; from then on
+//! the shell runs `sss` itself with [`VAR`] set to ask for candidates, which `main` answers
+//! before parsing anything. Besides subcommands, flags and fixed values, profile names come
+//! from `config.toml` and share arguments are offered only the files that read as shares, each
+//! with its number, quorum and label beside it, so the wrong file is less often typed at a
+//! ceremony.
+
+use clap::ValueEnum;
+use clap_complete::engine::{CompletionCandidate, PathCompleter, ValueCompleter};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use shamir_cli::format::share_index;
+use shamir_cli::input;
+use shamir_cli::profile::{Config, Profile};
+
+/// Environment variable the registered scripts set when they ask `sss` for candidates.
+pub const VAR: &str = "SSS_COMPLETE";
+
+/// Shells `sss completions` writes a script for.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    /// The registration script for this shell, which calls `completer` back for candidates.
+    pub fn registration(self, completer: &str) -> std::io::Result<Vec<u8>> {
+        let name = self.to_possible_value().expect("no variant is skipped");
+        let shells = clap_complete::env::Shells::builtins();
+        let shell = shells
+            .completer(name.get_name())
+            .expect("clap_complete supports every listed shell");
+        let mut script = Vec::new();
+        shell.write_registration(VAR, "sss", "sss", completer, &mut script)?;
+        Ok(script)
+    }
+}
+
+/// Profile names from `config.toml`, each with the settings it chooses.
+pub fn profile_names() -> Vec<CompletionCandidate> {
+    let Ok(config) = Config::load() else {
+        return Vec::new();
+    };
+    config
+        .profiles
+        .keys()
+        .map(|name| {
+            let summary = config.resolve(Some(name)).map(|profile| summarize(&profile));
+            CompletionCandidate::new(name).help(summary.ok().filter(|s| !s.is_empty()).map(Into::into))
+        })
+        .collect()
+}
+
+fn summarize(profile: &Profile) -> String {
+    let mut parts = Vec::new();
+    let total = profile.total.or(match profile.custodians.len() {
+        0 => None,
+        n => Some(n as u8),
+    });
+    match (profile.threshold, total) {
+        (Some(threshold), Some(total)) => parts.push(format!("{} of {}", threshold, total)),
+        (Some(threshold), None) => parts.push(format!("threshold {}", threshold)),
+        (None, Some(total)) => parts.push(format!("{} shares", total)),
+        (None, None) => {}
+    }
+    if let Some(encoding) = profile.encoding {
+        parts.push(format!("{:?}", encoding).to_lowercase());
+    }
+    if let Some(dir) = &profile.out_dir {
+        parts.push(format!("into {}", dir.display()));
+    }
+    parts.join(", ")
+}
+
+/// Share files for a share argument: directories to move through, and files that read as
+/// shares. With nothing typed yet, the shares in the `out_dir` of the defaults and of every
+/// profile are offered as well.
+pub fn share_files(current: &OsStr) -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<_> = PathCompleter::any()
+        .filter(|path| path.is_dir() || describe(path).is_some() || is_fragment_manifest(path))
+        .complete(current)
+        .into_iter()
+        .filter(|candidate| candidate.get_value() != ".")
+        .map(|candidate| {
+            let help = describe(Path::new(candidate.get_value()));
+            candidate.help(help.map(Into::into))
+        })
+        .collect();
+    if current.is_empty() {
+        for dir in out_dirs() {
+            // the shares of the current directory are already offered by their short names
+            if std::env::current_dir().is_ok_and(|cwd| same_dir(&cwd, &dir)) {
+                continue;
+            }
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            let mut shares: Vec<_> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter_map(|path| describe(&path).map(|help| CompletionCandidate::new(&path).help(Some(help.into()))))
+                .collect();
+            shares.sort();
+            candidates.extend(shares);
+        }
+    }
+    candidates
+}
+
+/// The `out_dir`s that `config.toml` names, without repeats.
+fn out_dirs() -> Vec<PathBuf> {
+    let Ok(config) = Config::load() else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let names = std::iter::once(None).chain(config.profiles.keys().map(|name| Some(name.as_str())));
+    for dir in names.filter_map(|name| config.resolve(name).ok()?.out_dir) {
+        if !dirs.iter().any(|known| same_dir(known, &dir)) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// `sss fragment split` writes these beside the fragments; `inspect` reads them like shares.
+fn is_fragment_manifest(path: &Path) -> bool {
+    path.is_file() && path.to_string_lossy().ends_with(".fragments.json")
+}
+
+/// `share 2, 3 of 5, 'label'` for a file that reads as a share, `None` for anything else.
+fn describe(path: &Path) -> Option<String> {
+    if !path.is_file() {
+        return None;
+    }
+    let text = input::read_share_file(path).ok()?;
+    let parsed = input::parse_share_details(&text).ok()?;
+    let mut parts = vec![format!("share {}", share_index(&parsed.share)?)];
+    if let Some((threshold, total)) = parsed.header.quorum {
+        parts.push(format!("{} of {}", threshold, total));
+    }
+    if let Some(label) = &parsed.header.label {
+        parts.push(format!("'{}'", label));
+    }
+    Some(parts.join(", "))
+}
@@ -0,0 +1,97 @@
+//! Hidden input for passwords, PINs and shares typed at a prompt.
+//!
+//! On Unix this is dialoguer's password prompt. In a Windows console (cmd, PowerShell, Windows
+//! Terminal) the line is read with `ReadConsoleW` while echo is switched off, instead of key by
+//! key: the console then does the line editing itself, so dead keys, AltGr, IME composition,
+//! characters outside the BMP and pasted text arrive as typed, and backspace removes whole
+//! characters. The console's CR LF is dropped. Without a console (mintty, redirected input)
+//! dialoguer's prompt is used as everywhere else.
+
+use dialoguer::Password;
+
+/// Asks for a line without echoing it. An empty answer is asked again unless `allow_empty`.
+pub fn password(prompt: &str, allow_empty: bool) -> dialoguer::Result<String> {
+    #[cfg(windows)]
+    if let Some(answer) = windows::read_hidden(prompt, allow_empty)? {
+        return Ok(answer);
+    }
+    Password::new().with_prompt(prompt).allow_empty_password(allow_empty).interact()
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::io::{Error, ErrorKind, Write};
+    use windows_sys::Win32::Foundation::{HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, ReadConsoleW, SetConsoleMode, CONSOLE_MODE, ENABLE_ECHO_INPUT,
+        ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT, STD_INPUT_HANDLE,
+    };
+    use zeroize::Zeroizing;
+
+    /// Puts the console mode back however the read ends.
+    struct Restore {
+        handle: HANDLE,
+        mode: CONSOLE_MODE,
+    }
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            // SAFETY: the handle is the process's console input handle, valid for its lifetime.
+            unsafe { SetConsoleMode(self.handle, self.mode) };
+        }
+    }
+
+    /// Reads one hidden line from the console, or `None` when standard input is not one.
+    pub fn read_hidden(prompt: &str, allow_empty: bool) -> std::io::Result<Option<String>> {
+        // SAFETY: GetStdHandle and GetConsoleMode only read the process's handle table and
+        // write the mode into a local.
+        let (handle, mode) = unsafe {
+            let handle = GetStdHandle(STD_INPUT_HANDLE);
+            let mut mode: CONSOLE_MODE = 0;
+            if handle.is_null() || handle == INVALID_HANDLE_VALUE || GetConsoleMode(handle, &mut mode) == 0 {
+                return Ok(None);
+            }
+            (handle, mode)
+        };
+        let _restore = Restore { handle, mode };
+        let hidden = (mode & !ENABLE_ECHO_INPUT) | ENABLE_LINE_INPUT | ENABLE_PROCESSED_INPUT;
+        // SAFETY: as above; the mode is restored when `_restore` drops.
+        if unsafe { SetConsoleMode(handle, hidden) } == 0 {
+            return Err(Error::last_os_error());
+        }
+        loop {
+            eprint!("{}: ", prompt);
+            std::io::stderr().flush()?;
+            let line = read_line(handle)?;
+            // Enter is not echoed either
+            eprintln!();
+            if allow_empty || !line.is_empty() {
+                return Ok(Some(line));
+            }
+        }
+    }
+
+    fn read_line(handle: HANDLE) -> std::io::Result<String> {
+        let mut units: Zeroizing<Vec<u16>> = Zeroizing::new(Vec::new());
+        let mut buffer: Zeroizing<[u16; 256]> = Zeroizing::new([0; 256]);
+        while units.last() != Some(&u16::from(b'\n')) {
+            let mut read = 0u32;
+            // SAFETY: the buffer is a live local of the length given, and `read` says how much
+            // of it the console filled.
+            let ok = unsafe {
+                ReadConsoleW(handle, buffer.as_mut_ptr().cast(), buffer.len() as u32, &mut read, core::ptr::null())
+            };
+            if ok == 0 {
+                return Err(Error::last_os_error());
+            }
+            if read == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "the console closed before a line was entered"));
+            }
+            units.extend_from_slice(&buffer[..read as usize]);
+        }
+        let mut line = String::from_utf16(&units)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "the console sent invalid UTF-16"))?;
+        line.truncate(line.trim_end_matches(['\r', '\n']).len());
+        Ok(line)
+    }
+}
@@ -0,0 +1,229 @@
+use sha2::{Sha256, Digest};
+use ed25519_dalek::VerifyingKey;
+use std::io::{BufRead, Read};
+use std::net::{SocketAddr, UdpSocket};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use tiny_http::{Header, Method, Response, Server, SslConfig};
+
+use shamir_cli::coordinator::{Contribution, GuardianSession};
+use shamir_cli::input;
+use shamir_cli::token::{self, Capability, Ledger};
+use shamir_cli::{Result, SssError};
+
+use crate::cli::session::Session;
+
+const MAX_BODY: u64 = 1024 * 1024;
+
+/// The files `wasm-bindgen --target web` writes for the library, which the guardian page loads.
+const WASM_FILES: [(&str, &str); 2] =
+    [("shamir_cli.js", "text/javascript"), ("shamir_cli_bg.wasm", "application/wasm")];
+
+enum Message {
+    Share { share: Vec<u8>, label: Option<String> },
+    Rejected(String),
+    Done,
+}
+
+pub fn coordinate_flow(
+    session: &mut Session,
+    listen: SocketAddr,
+    wasm: &Path,
+    authority: Option<&(VerifyingKey, Ledger)>,
+) -> Result<()> {
+    let module = WASM_FILES
+        .iter()
+        .map(|(name, _)| {
+            std::fs::read(wasm.join(name)).map_err(|e| {
+                SssError::InvalidArgument(format!("--wasm {}: {}: {}", wasm.display(), name, e))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let ceremony = tracing::info_span!("ceremony", %listen, shares_collected = 0usize, rejected = 0usize);
+    let _entered = ceremony.enter();
+    let mut args = crate::CombineArgs::default();
+    if let Some((issuer, ledger)) = authority {
+        args.authorized_set = Some((authorize(session, issuer)?, ledger.clone()));
+    }
+    let guardian = GuardianSession::generate();
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .map_err(|e| SssError::Io(std::io::Error::other(e)))?;
+    let server = Server::https(
+        listen,
+        SslConfig {
+            certificate: cert.cert.pem().into_bytes(),
+            private_key: cert.key_pair.serialize_pem().into_bytes(),
+        },
+    )
+    .map_err(|e| SssError::Io(std::io::Error::other(e)))?;
+
+    let host = if listen.ip().is_unspecified() { lan_address() } else { listen.ip().to_string() };
+    println!("Guardians can open https://{}:{}/ on their phones.", host, listen.port());
+    println!("Session code: {}  (guardians must see the same code on the page)", guardian.code());
+    println!("The certificate is self-signed, so browsers will ask for confirmation. Guardians should");
+    println!("accept it only if its SHA-256 fingerprint is {}", fingerprint(cert.cert.der()));
+    println!("Press Enter once enough shares have arrived.\n");
+
+    let (tx, rx) = mpsc::channel();
+    let stdin_tx = tx.clone();
+    thread::spawn(move || {
+        let _ = std::io::stdin().lock().lines().next();
+        let _ = stdin_tx.send(Message::Done);
+    });
+    let page = guardian.page();
+    let parent = ceremony.clone();
+    thread::spawn(move || serve(server, guardian, page, module, tx, parent));
+
+    let mut shares: Vec<Vec<u8>> = Vec::new();
+    let mut rejected = 0usize;
+    for message in rx {
+        match message {
+            Message::Share { share, label } => {
+                let who = label.unwrap_or_else(|| "unnamed guardian".to_string());
+                let subject = format!("contribution from {}", who);
+                if shares.iter().any(|s| s[0] == share[0]) {
+                    println!("Ignoring a second copy of share #{} (from {})", share[0], who);
+                    session.validation(&subject, false, format!("duplicate index {}", share[0]))?;
+                    continue;
+                }
+                let digest = Sha256::digest(&share);
+                println!("Received share #{} from {} ({} collected)", share[0], who, shares.len() + 1);
+                session.validation(
+                    &subject,
+                    true,
+                    format!("index {}, id {}", share[0], hex::encode(&digest[..4])),
+                )?;
+                shares.push(share);
+                ceremony.record("shares_collected", shares.len());
+            }
+            Message::Rejected(reason) => {
+                rejected += 1;
+                ceremony.record("rejected", rejected);
+                println!("Rejected a contribution: {}", reason);
+                session.validation("contribution", false, reason)?;
+            }
+            Message::Done => break,
+        }
+    }
+
+    crate::recover_flow(session, shares, &crate::Expected::default(), &args)
+}
+
+/// Asks for a capability token and checks it. It is spent only once the collected shares
+/// are found to rebuild the set it covers.
+fn authorize(session: &mut Session, issuer: &VerifyingKey) -> Result<Capability> {
+    let text: String = session.ask_secret("Capability token", |p| {
+        super::console::password(p, false)
+    })?;
+    let capability = token::verify(&text, issuer);
+    session.validation(
+        "capability token",
+        capability.is_ok(),
+        match &capability {
+            Ok(c) => format!("holder {}, set {}", c.holder, c.fingerprint),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    let capability = capability?;
+    println!(
+        "Authorized: {} may recover set {} (token {} is used up once the shares rebuild it)\n",
+        capability.holder, capability.fingerprint, capability.id
+    );
+    Ok(capability)
+}
+
+fn serve(
+    server: Server,
+    guardian: GuardianSession,
+    page: String,
+    module: Vec<Vec<u8>>,
+    tx: mpsc::Sender<Message>,
+    ceremony: tracing::Span,
+) {
+    for mut request in server.incoming_requests() {
+        let span = tracing::info_span!(
+            parent: &ceremony,
+            "request",
+            method = %request.method(),
+            path = request.url(),
+            status = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let file = WASM_FILES.iter().position(|(name, _)| request.url().strip_prefix('/') == Some(name));
+        let response = match (request.method(), request.url(), file) {
+            (Method::Get, "/", _) => Response::from_string(page.clone()).with_header(
+                Header::from_bytes("Content-Type", "text/html; charset=utf-8").expect("static header"),
+            ),
+            (Method::Get, _, Some(i)) => Response::from_data(module[i].clone())
+                .with_header(Header::from_bytes("Content-Type", WASM_FILES[i].1).expect("static header")),
+            (Method::Post, "/contribute", _) => {
+                let mut body = String::new();
+                let read = request.as_reader().take(MAX_BODY).read_to_string(&mut body);
+                let outcome = read
+                    .map_err(SssError::from)
+                    .and_then(|_| accept(&guardian, &body));
+                match outcome {
+                    Ok((share, label)) => {
+                        let reply = format!("{{\"index\":{}}}", share[0]);
+                        if tx.send(Message::Share { share, label }).is_err() {
+                            return;
+                        }
+                        json(200, reply)
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Message::Rejected(e.to_string()));
+                        json(400, serde_json::json!({ "error": e.to_string() }).to_string())
+                    }
+                }
+            }
+            _ => Response::from_string("not found").with_status_code(404),
+        };
+        span.record("status", response.status_code().0);
+        let _ = request.respond(response);
+    }
+}
+
+#[tracing::instrument(
+    name = "contribution.validate",
+    skip_all,
+    fields(body_len = body.len(), share_index = tracing::field::Empty, error = tracing::field::Empty)
+)]
+fn accept(guardian: &GuardianSession, body: &str) -> Result<(Vec<u8>, Option<String>)> {
+    let outcome = (|| {
+        let contribution: Contribution = serde_json::from_str(body)
+            .map_err(|e| SssError::Parse(format!("malformed contribution: {}", e)))?;
+        let text = guardian.open(&contribution)?;
+        let share = input::parse_share(&text)?;
+        if share.len() < 2 || share[0] == 0 {
+            return Err(SssError::Parse("that is not a valid share".into()));
+        }
+        Ok((share, contribution.label))
+    })();
+    let span = tracing::Span::current();
+    match &outcome {
+        Ok((share, _)) => span.record("share_index", share[0]),
+        Err(e) => span.record("error", e.kind()),
+    };
+    outcome
+}
+
+fn json(status: u16, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("static header"))
+}
+
+/// SHA-256 of the certificate as browsers show it, `AB:CD:...`.
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der).iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":")
+}
+
+// Connecting a UDP socket sends nothing; it only asks the OS which interface it would use.
+fn lan_address() -> String {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|s| s.connect("192.0.2.1:9").map(|_| s))
+        .and_then(|s| s.local_addr())
+        .map(|a| a.ip().to_string())
+        .unwrap_or_else(|_| "localhost".to_string())
+}
@@ -0,0 +1,7 @@
+#[macro_use]
+pub mod output;
+pub mod complete;
+pub mod console;
+#[cfg(feature = "coordinator")]
+pub mod coordinate;
+pub mod session;
@@ -0,0 +1,60 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use shamir_cli::SssError;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// What `encrypt` and `decrypt` print on stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// A single JSON document, with the human-readable messages moved to stderr.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = SssError;
+
+    fn from_str(s: &str) -> Result<Self, SssError> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(SssError::InvalidArgument(format!("unknown output format '{}': use text or json", s))),
+        }
+    }
+}
+
+pub fn set_json(on: bool) {
+    JSON.store(on, Ordering::Relaxed);
+}
+
+pub fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// With `--quiet`, only results are printed: shares, secrets and errors, never the messages
+/// around them.
+pub fn set_quiet(on: bool) {
+    QUIET.store(on, Ordering::Relaxed);
+}
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for human-readable output, which moves to stderr while stdout is reserved for
+/// the JSON document, and is left out altogether with `--quiet`.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::cli::output::quiet() {
+            if $crate::cli::output::json() {
+                eprintln!($($arg)*)
+            } else {
+                println!($($arg)*)
+            }
+        }
+    };
+}
@@ -0,0 +1,214 @@
+use dialoguer::Select;
+use ed25519_dalek::SigningKey;
+use serde_json::{Map, Value};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use shamir_cli::audit::{self, AuditLog};
+use shamir_cli::ceremony::{Ceremony, Check};
+use shamir_cli::secure::write_private;
+use shamir_cli::timelock::system_now;
+use shamir_cli::transcript::{Event, Transcript};
+use shamir_cli::{Result, SssError};
+
+pub fn prompt_error(e: dialoguer::Error) -> SssError {
+    match e {
+        dialoguer::Error::IO(e) => SssError::Io(e),
+    }
+}
+
+/// Wraps every interactive prompt so it can be mirrored into a transcript.
+pub struct Session {
+    operation: String,
+    transcript: Option<Transcript>,
+    report: Option<Map<String, Value>>,
+    audit: Option<Audit>,
+}
+
+/// The audit record being gathered for the operation, and the ceremony record written from the
+/// same data.
+struct Audit {
+    log: Option<AuditLog>,
+    ceremony: Option<(PathBuf, Option<SigningKey>)>,
+    started_at: u64,
+    parameters: Map<String, Value>,
+    shares: Vec<String>,
+    participants: Vec<String>,
+    checks: Vec<Check>,
+}
+
+impl Session {
+    pub fn new(transcript_path: Option<&Path>, operation: &str) -> Result<Self> {
+        let transcript = match transcript_path {
+            Some(path) => Some(Transcript::create(path, operation)?),
+            None => None,
+        };
+        Ok(Session { operation: operation.to_string(), transcript, report: None, audit: None })
+    }
+
+    /// Appends a record of the operation to the audit log at `path` when it finishes.
+    pub fn audit_to(&mut self, path: &Path) {
+        self.gather().log = Some(AuditLog::new(path));
+    }
+
+    /// Writes a ceremony record to `path` when the operation finishes, signed with `key` if given.
+    pub fn ceremony_to(&mut self, path: &Path, key: Option<SigningKey>) {
+        self.gather().ceremony = Some((path.to_path_buf(), key));
+    }
+
+    fn gather(&mut self) -> &mut Audit {
+        self.audit.get_or_insert_with(|| Audit {
+            log: None,
+            ceremony: None,
+            started_at: system_now(),
+            parameters: Map::new(),
+            shares: Vec::new(),
+            participants: Vec::new(),
+            checks: Vec::new(),
+        })
+    }
+
+    /// Notes a parameter for the audit record; it must not be secret.
+    pub fn audit_param(&mut self, key: &str, value: impl Into<Value>) {
+        if let Some(audit) = self.audit.as_mut() {
+            audit.parameters.insert(key.to_string(), value.into());
+        }
+    }
+
+    /// Notes the IDs of the shares the operation made or used.
+    pub fn audit_shares<S: AsRef<[u8]>>(&mut self, shares: &[S]) {
+        if let Some(audit) = self.audit.as_mut() {
+            audit.shares = shares.iter().map(|share| audit::share_id(share.as_ref())).collect();
+        }
+    }
+
+    /// Notes a custodian or share label taking part, for the ceremony record.
+    pub fn audit_participant(&mut self, name: &str) {
+        if let Some(audit) = self.audit.as_mut().filter(|audit| !audit.participants.iter().any(|p| p == name)) {
+            audit.participants.push(name.to_string());
+        }
+    }
+
+    /// Collects results for a JSON document that `finish` prints on stdout.
+    pub fn report_json(&mut self) {
+        super::output::set_json(true);
+        self.report = Some(Map::new());
+    }
+
+    /// Adds a field to the JSON document; does nothing for text output.
+    pub fn report(&mut self, key: &str, value: impl Into<Value>) {
+        if let Some(report) = self.report.as_mut() {
+            report.insert(key.to_string(), value.into());
+        }
+    }
+
+    fn record(&mut self, event: Event) -> Result<()> {
+        match self.transcript.as_mut() {
+            Some(t) => t.record(event),
+            None => Ok(()),
+        }
+    }
+
+    fn prompt<T, F>(&mut self, prompt: &str, redact: bool, ask: F) -> Result<T>
+    where
+        T: Display,
+        F: FnOnce(&str) -> dialoguer::Result<T>,
+    {
+        self.record(Event::Prompt { prompt: prompt.to_string() })?;
+        let started = Instant::now();
+        let answer = ask(prompt).map_err(prompt_error)?;
+        self.record(Event::Answer {
+            prompt: prompt.to_string(),
+            value: if redact { None } else { Some(answer.to_string()) },
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })?;
+        Ok(answer)
+    }
+
+    pub fn ask<T, F>(&mut self, prompt: &str, ask: F) -> Result<T>
+    where
+        T: Display,
+        F: FnOnce(&str) -> dialoguer::Result<T>,
+    {
+        self.prompt(prompt, false, ask)
+    }
+
+    /// Same as `ask`, but the answer is left out of the transcript.
+    pub fn ask_secret<T, F>(&mut self, prompt: &str, ask: F) -> Result<T>
+    where
+        T: Display,
+        F: FnOnce(&str) -> dialoguer::Result<T>,
+    {
+        self.prompt(prompt, true, ask)
+    }
+
+    pub fn select(&mut self, prompt: &str, items: &[&str]) -> Result<usize> {
+        self.record(Event::Prompt { prompt: prompt.to_string() })?;
+        let started = Instant::now();
+        let index = Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(0)
+            .interact()
+            .map_err(prompt_error)?;
+        self.record(Event::Answer {
+            prompt: prompt.to_string(),
+            value: Some(items[index].to_string()),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })?;
+        Ok(index)
+    }
+
+    pub fn validation(&mut self, subject: &str, ok: bool, detail: impl Into<String>) -> Result<()> {
+        let detail = detail.into();
+        if let Some(audit) = self.audit.as_mut() {
+            audit.checks.push(Check { at: system_now(), subject: subject.to_string(), ok, detail: detail.clone() });
+        }
+        self.record(Event::Validation { subject: subject.to_string(), ok, detail })
+    }
+
+    pub fn finish<T>(&mut self, result: &Result<T>) -> Result<()> {
+        let (ok, detail) = match result {
+            Ok(_) => (true, "completed".to_string()),
+            Err(e) => (false, e.to_string()),
+        };
+        if let Some(mut report) = self.report.take() {
+            report.insert("ok".into(), ok.into());
+            if let Err(e) = result {
+                report.insert("error".into(), detail.clone().into());
+                report.insert("exit_code".into(), e.exit_code().into());
+            }
+            let document = zeroize::Zeroizing::new(
+                serde_json::to_string_pretty(&Value::Object(report)).map_err(|e| SssError::Parse(e.to_string()))?,
+            );
+            println!("{}", document.as_str());
+        }
+        if let Some(audit) = self.audit.take() {
+            let record = match &audit.log {
+                Some(log) => Some(log.append(&self.operation, audit.parameters.clone(), audit.shares.clone(), ok, &detail)?),
+                None => None,
+            };
+            if let Some((path, key)) = &audit.ceremony {
+                let ceremony = Ceremony {
+                    operation: self.operation.clone(),
+                    started_at: audit.started_at,
+                    finished_at: system_now(),
+                    parameters: audit.parameters,
+                    participants: audit.participants,
+                    shares: audit.shares,
+                    checks: audit.checks,
+                    ok,
+                    detail: detail.clone(),
+                    audit_record: record.map(|record| (record.seq, record.hash)),
+                };
+                let text = match key {
+                    Some(key) => ceremony.sign(key),
+                    None => ceremony.render(),
+                };
+                write_private(path, text.as_bytes())?;
+            }
+        }
+        self.record(Event::Finished { ok, detail })
+    }
+}
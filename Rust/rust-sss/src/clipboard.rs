@@ -0,0 +1,79 @@
+//! Copying shares and recovered secrets to the system clipboard instead of the terminal. Each
+//! copy is cleared again after a timeout, unless something else has been copied since.
+
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+pub const DEFAULT_CLEAR_SECONDS: u64 = 45;
+
+fn io(e: arboard::Error) -> SssError {
+    SssError::Io(std::io::Error::other(format!("clipboard: {}", e)))
+}
+
+/// Text on the clipboard. It is cleared when the timeout passes, on `clear`, or on drop,
+/// whichever comes first.
+pub struct Copied {
+    cancel: Option<mpsc::Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// Puts `text` on the clipboard and clears it after `clear_after`. The clipboard is served
+/// from a thread of this process, which on X11 must stay alive for the text to be pasted.
+pub fn copy(text: &str, clear_after: Duration) -> Result<Copied> {
+    let text = Zeroizing::new(text.to_string());
+    let (ready_tx, ready) = mpsc::channel();
+    let (cancel, cancelled) = mpsc::channel::<()>();
+    let worker = std::thread::spawn(move || {
+        let copied = arboard::Clipboard::new().and_then(|mut c| c.set_text(text.as_str()).map(|_| c));
+        let mut clipboard = match copied {
+            Ok(clipboard) => clipboard,
+            Err(e) => return drop(ready_tx.send(Err(io(e)))),
+        };
+        let _ = ready_tx.send(Ok(()));
+        // a cancel, or the `Copied` going away, ends the wait early
+        let _ = cancelled.recv_timeout(clear_after);
+        if clipboard.get_text().is_ok_and(|now| *now == *text) {
+            let _ = clipboard.clear();
+        }
+    });
+    match ready.recv() {
+        Ok(Ok(())) => Ok(Copied { cancel: Some(cancel), worker: Some(worker) }),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(SssError::Io(std::io::Error::other("clipboard: the clipboard thread stopped"))),
+    }
+}
+
+impl Copied {
+    /// Clears the clipboard now.
+    pub fn clear(mut self) {
+        self.stop(true);
+    }
+
+    /// Blocks until the timeout has passed and the clipboard is cleared.
+    pub fn wait(mut self) {
+        self.stop(false);
+    }
+
+    fn stop(&mut self, now: bool) {
+        let cancel = self.cancel.take();
+        if now {
+            if let Some(cancel) = &cancel {
+                let _ = cancel.send(());
+            }
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        drop(cancel);
+    }
+}
+
+impl Drop for Copied {
+    fn drop(&mut self) {
+        self.stop(true);
+    }
+}
@@ -0,0 +1,121 @@
+//! Collecting shares from guardians' phones. The coordinator serves a page that loads the wasm
+//! build ([`crate::wasm`]), which seals the share to the coordinator's session key with
+//! [`seal`] before it leaves the phone; [`GuardianSession::open`] reads it back.
+
+use aes_gcm::{Aes256Gcm, aead::Aead, KeyInit};
+use aes::cipher::generic_array::GenericArray;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hkdf::Hkdf;
+use p256::{ecdh::{diffie_hellman, EphemeralSecret}, PublicKey, SecretKey};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
+use zeroize::Zeroizing;
+
+use crate::crypto::NONCE_LEN;
+use crate::error::{Result, SssError};
+
+const HKDF_INFO: &[u8] = b"sss guardian share v1";
+const PAGE: &str = include_str!("guardian.html");
+
+/// Ephemeral P-256 key a coordinator hands to guardians' browsers so that shares are
+/// encrypted on the phone and only readable by this process.
+pub struct GuardianSession {
+    secret: SecretKey,
+    public: Vec<u8>,
+}
+
+/// What the guardian page posts back: an ECDH-sealed share plus an optional name.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Contribution {
+    pub epk: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl GuardianSession {
+    pub fn generate() -> Self {
+        let secret = SecretKey::random(&mut rand::thread_rng());
+        let public = secret.public_key().to_sec1_bytes().to_vec();
+        GuardianSession { secret, public }
+    }
+
+    /// [`session_code`] of this session's key.
+    pub fn code(&self) -> String {
+        session_code(&self.public)
+    }
+
+    pub fn page(&self) -> String {
+        PAGE.replace("{{PUBLIC_KEY}}", &BASE64.encode(&self.public))
+    }
+
+    #[tracing::instrument(name = "contribution.open", skip_all, fields(ciphertext_len = contribution.ciphertext.len()))]
+    pub fn open(&self, contribution: &Contribution) -> Result<Zeroizing<String>> {
+        let decode = |field: &str, value: &str| {
+            BASE64
+                .decode(value)
+                .map_err(|e| SssError::Parse(format!("contribution {} is not base64: {}", field, e)))
+        };
+        let epk = decode("epk", &contribution.epk)?;
+        let nonce = decode("nonce", &contribution.nonce)?;
+        let ciphertext = decode("ciphertext", &contribution.ciphertext)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(SssError::Parse(format!("contribution nonce must be {} bytes", NONCE_LEN)));
+        }
+
+        let peer = PublicKey::from_sec1_bytes(&epk)
+            .map_err(|_| SssError::Parse("contribution key is not a P-256 point".into()))?;
+        let shared = diffie_hellman(self.secret.to_nonzero_scalar(), peer.as_affine());
+        let key = content_key(shared.raw_secret_bytes())?;
+
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+                .decrypt(GenericArray::from_slice(&nonce), ciphertext.as_slice())?,
+        );
+        let text = std::str::from_utf8(&plaintext)
+            .map_err(|_| SssError::Parse("contributed share is not UTF-8 text".into()))?;
+        Ok(Zeroizing::new(text.to_string()))
+    }
+}
+
+/// Short code of a coordinator key. The terminal prints it and the guardian page works it out
+/// from the key it seals to, so a page that would seal to someone else's key shows another code.
+pub fn session_code(public: &[u8]) -> String {
+    let hash = Sha256::digest(public);
+    let hex = hex::encode(&hash[..4]).to_uppercase();
+    format!("{}-{}", &hex[..4], &hex[4..])
+}
+
+/// Seals `share` to the coordinator key `public` (SEC1) under a fresh ephemeral key, as the
+/// guardian page does before posting it.
+pub fn seal<R: RngCore + CryptoRng>(
+    public: &[u8],
+    share: &str,
+    label: Option<String>,
+    rng: &mut R,
+) -> Result<Contribution> {
+    let peer = PublicKey::from_sec1_bytes(public)
+        .map_err(|_| SssError::InvalidArgument("the coordinator key is not a P-256 point".into()))?;
+    let ephemeral = EphemeralSecret::random(rng);
+    let key = content_key(ephemeral.diffie_hellman(&peer).raw_secret_bytes())?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+        .encrypt(GenericArray::from_slice(&nonce), share.as_bytes())?;
+    Ok(Contribution {
+        epk: BASE64.encode(ephemeral.public_key().to_sec1_bytes()),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+        label,
+    })
+}
+
+fn content_key(shared: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, shared)
+        .expand(HKDF_INFO, key.as_mut())
+        .map_err(|e| SssError::Kdf(e.to_string()))?;
+    Ok(key)
+}
@@ -0,0 +1,283 @@
+use aes_gcm::{Aes256Gcm, aead::{Aead, AeadInPlace, Payload}, KeyInit};
+use aes::cipher::generic_array::GenericArray;
+use alloc::{format, string::ToString, vec, vec::Vec};
+use pbkdf2::{hmac::Hmac, pbkdf2};
+use rand::RngCore;
+use sha2::Sha256;
+
+use crate::error::{Result, SssError};
+use crate::secure::SecretBuf;
+#[cfg(feature = "std")]
+use {
+    crate::progress::{self, Event},
+    crate::timelock::Conditions,
+    pbkdf2::hmac::Mac,
+    rand::SeedableRng,
+    rand_chacha::ChaCha20Rng,
+    sha2::Digest,
+    std::time::{Duration, Instant},
+    zeroize::Zeroizing,
+};
+
+pub const SALT_LEN: usize = 16;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+/// PBKDF2 rounds of payloads without a KDF header, and the least a calibration settles on.
+pub const PBKDF2_ROUNDS: u32 = 100_000;
+/// Headers claiming more rounds than this are not trusted, so a tampered share cannot stall
+/// recovery for hours.
+pub const MAX_PBKDF2_ROUNDS: u32 = 100_000_000;
+const KDF_MAGIC: [u8; 4] = *b"kdf1";
+/// Bytes in front of the salt recording how many PBKDF2 rounds the key took.
+pub const KDF_HEADER_LEN: usize = KDF_MAGIC.len() + 4;
+
+/// `KDF_MAGIC` followed by `rounds` big-endian, to be put in front of salt ‖ nonce ‖ ciphertext.
+pub fn kdf_header(rounds: u32) -> [u8; KDF_HEADER_LEN] {
+    let mut header = [0u8; KDF_HEADER_LEN];
+    header[..KDF_MAGIC.len()].copy_from_slice(&KDF_MAGIC);
+    header[KDF_MAGIC.len()..].copy_from_slice(&rounds.to_be_bytes());
+    header
+}
+
+/// The PBKDF2 rounds a payload names in its KDF header, and the payload after it, or `None`
+/// for payloads written before the header existed.
+pub fn kdf_rounds(data: &[u8]) -> Option<(u32, &[u8])> {
+    let rest = data.strip_prefix(&KDF_MAGIC)?;
+    let rounds = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?);
+    (1..=MAX_PBKDF2_ROUNDS).contains(&rounds).then(|| (rounds, &rest[4..]))
+}
+
+/// Decrypts a reconstructed payload, with or without a KDF header. A legacy payload whose salt
+/// happens to start like a header is tried both ways, and a payload with two slots (see
+/// [`encrypt_with_decoy_with_rng`]) is tried slot by slot. Conditions in front of the payload
+/// are authenticated but not enforced; see [`crate::timelock`]. Without `std` only the set
+/// binding is parsed, so sets with a time lock or switch do not open.
+pub fn open_payload(data: &[u8], password: &str) -> Result<SecretBuf> {
+    #[cfg(feature = "std")]
+    let (_, header, rest) = Conditions::split(data);
+    #[cfg(not(feature = "std"))]
+    let (header, rest) = match crate::format::SetBinding::split(data) {
+        Some((_, rest)) => data.split_at(data.len() - rest.len()),
+        None => (&[][..], data),
+    };
+    if !header.is_empty() {
+        return open_with_aad(rest, password, header);
+    }
+    let legacy = || {
+        let (salt, nonce, ciphertext) = split_payload(data)?;
+        decrypt_data(ciphertext, password, salt, nonce, PBKDF2_ROUNDS)
+    };
+    match open_with_aad(data, password, b"") {
+        Err(SssError::Aead) if kdf_rounds(data).is_some() => legacy().map_err(|_| SssError::Aead),
+        Err(SssError::Parse(_)) if kdf_rounds(data).is_none() => legacy(),
+        result => result,
+    }
+}
+
+/// Opens a payload with a KDF header whose ciphertext authenticates `aad`.
+fn open_with_aad(data: &[u8], password: &str, aad: &[u8]) -> Result<SecretBuf> {
+    let (rounds, rest) = kdf_rounds(data).ok_or_else(|| SssError::Parse("the payload has no KDF header".into()))?;
+    let (salt, nonce, ciphertext) = split_payload(rest)?;
+    let key = derive_key(password, salt, rounds)?;
+    match open_with_key(&key, nonce, ciphertext, aad) {
+        Err(SssError::Aead) => open_decoy_slots(&key, &rest[SALT_LEN..], aad),
+        result => result,
+    }
+}
+
+/// Encrypts `data` under `password` and `decoy` under its own password with one salt, as
+/// salt ‖ nonce ‖ ciphertext ‖ nonce ‖ ciphertext with the two slots in random order. Both
+/// plaintexts must be the same length. Without a decoy the other slot is random bytes of the
+/// same length, so a set with a decoy and one without look alike. Either password opens only
+/// its own slot, and nothing short of the other password shows what the other slot holds.
+/// Both ciphertexts authenticate `aad`.
+pub fn encrypt_with_decoy_with_rng<R: RngCore + ?Sized>(
+    data: &[u8],
+    password: &str,
+    decoy: Option<(&[u8], &str)>,
+    rounds: u32,
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>> {
+    if let Some((decoy, decoy_password)) = decoy {
+        if data.len() != decoy.len() {
+            return Err(SssError::Parse("the secret and the decoy must be padded to the same length".into()));
+        }
+        if password == decoy_password {
+            return Err(SssError::InvalidArgument("the decoy password must differ from the real one".into()));
+        }
+    }
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut slots = Vec::with_capacity(2);
+    for (plaintext, password) in core::iter::once((data, password)).chain(decoy) {
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+        let key = derive_key(password, &salt, rounds)?;
+        let mut slot = nonce.to_vec();
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+        slot.extend(cipher.encrypt(GenericArray::from_slice(&nonce), Payload { msg: plaintext, aad })?);
+        slots.push(slot);
+    }
+    if slots.len() == 1 {
+        let mut filler = vec![0u8; slots[0].len()];
+        rng.fill_bytes(&mut filler);
+        slots.push(filler);
+    }
+    if rng.next_u32() & 1 == 1 {
+        slots.swap(0, 1);
+    }
+    let mut out = salt.to_vec();
+    out.extend(slots.concat());
+    Ok(out)
+}
+
+/// Tries `key` on each half of nonce ‖ ciphertext ‖ nonce ‖ ciphertext. An odd byte at the
+/// end, which pads a share to an exact size, belongs to neither slot.
+fn open_decoy_slots(key: &[u8], slots: &[u8], aad: &[u8]) -> Result<SecretBuf> {
+    let slots = &slots[..slots.len() - slots.len() % 2];
+    let half = slots.len() / 2;
+    if half < NONCE_LEN + TAG_LEN {
+        return Err(SssError::Aead);
+    }
+    for slot in [&slots[..half], &slots[half..]] {
+        match open_with_key(key, &slot[..NONCE_LEN], &slot[NONCE_LEN..], aad) {
+            Err(SssError::Aead) => continue,
+            result => return result,
+        }
+    }
+    Err(SssError::Aead)
+}
+
+/// PBKDF2 rounds that take about `target` on this machine, never fewer than `PBKDF2_ROUNDS`,
+/// and how long those rounds actually took.
+#[cfg(feature = "std")]
+pub fn calibrate(target: Duration) -> Result<(u32, Duration)> {
+    const PROBE: u32 = 20_000;
+    let started = Instant::now();
+    derive_key("calibration", &[0u8; SALT_LEN], PROBE)?;
+    let per_round = started.elapsed().as_secs_f64() / PROBE as f64;
+    let rounds = (target.as_secs_f64() / per_round.max(f64::MIN_POSITIVE))
+        .clamp(PBKDF2_ROUNDS as f64, MAX_PBKDF2_ROUNDS as f64) as u32;
+    let rounds = rounds / 1000 * 1000;
+    let started = Instant::now();
+    derive_key("calibration", &[0u8; SALT_LEN], rounds)?;
+    Ok((rounds, started.elapsed()))
+}
+
+#[cfg(feature = "std")]
+pub fn encrypt_data(data: &[u8], password: &str, rounds: u32) -> Result<(Vec<u8>, [u8; SALT_LEN], [u8; NONCE_LEN])> {
+    encrypt_data_with_rng(data, password, rounds, &mut rand::rngs::OsRng)
+}
+
+/// ChaCha20 seeded with the SHA-256 of `seed`: the same stream on every platform, for
+/// reproducible splits in tests and test vectors.
+#[cfg(feature = "std")]
+pub fn seeded_rng(seed: &str) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(Sha256::digest(seed.as_bytes()).into())
+}
+
+/// `encrypt_data` with the salt and nonce drawn from `rng`, so a seeded generator gives the
+/// same ciphertext every time. Only tests should pass anything but the OS generator.
+pub fn encrypt_data_with_rng<R: RngCore + ?Sized>(
+    data: &[u8],
+    password: &str,
+    rounds: u32,
+    rng: &mut R,
+) -> Result<(Vec<u8>, [u8; SALT_LEN], [u8; NONCE_LEN])> {
+    encrypt_data_with_aad(data, password, rounds, b"", rng)
+}
+
+/// `encrypt_data_with_rng` with a ciphertext that also authenticates `aad`.
+pub fn encrypt_data_with_aad<R: RngCore + ?Sized>(
+    data: &[u8],
+    password: &str,
+    rounds: u32,
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<(Vec<u8>, [u8; SALT_LEN], [u8; NONCE_LEN])> {
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let key = derive_key(password, &salt, rounds)?;
+    let key_array = GenericArray::from_slice(&key);
+
+    let encrypted_data = Aes256Gcm::new(key_array)
+        .encrypt(GenericArray::from_slice(&nonce), Payload { msg: data, aad })?;
+
+    Ok((encrypted_data, salt, nonce))
+}
+
+pub fn decrypt_data(data: &[u8], password: &str, salt: &[u8], nonce: &[u8], rounds: u32) -> Result<SecretBuf> {
+    if nonce.len() != NONCE_LEN {
+        return Err(SssError::Parse(format!("nonce must be {} bytes, got {}", NONCE_LEN, nonce.len())));
+    }
+    let key = derive_key(password, salt, rounds)?;
+    open_with_key(&key, nonce, data, b"")
+}
+
+fn open_with_key(key: &[u8], nonce: &[u8], data: &[u8], aad: &[u8]) -> Result<SecretBuf> {
+    let key_array = GenericArray::from_slice(key);
+    // decrypt in place so the plaintext only ever lives in the (possibly locked) buffer
+    let mut plaintext = SecretBuf::from_slice(data)?;
+    Aes256Gcm::new(key_array)
+        .decrypt_in_place(GenericArray::from_slice(nonce), aad, plaintext.as_mut_vec())?;
+    Ok(plaintext)
+}
+
+/// Splits reconstructed share data into its salt, nonce and ciphertext (which includes the
+/// GCM tag), checking that each part is actually present.
+pub fn split_payload(data: &[u8]) -> Result<(&[u8], &[u8], &[u8])> {
+    let min = SALT_LEN + NONCE_LEN + TAG_LEN;
+    if data.len() < min {
+        return Err(SssError::Parse(format!(
+            "reconstructed data is {} bytes, need at least {} for salt, nonce and tag",
+            data.len(),
+            min
+        )));
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Ok((salt, nonce, ciphertext))
+}
+
+/// The AES-256 key PBKDF2-HMAC-SHA256 derives from `password`, as `encrypt` and `decrypt` do.
+pub fn derive_key(password: &str, salt: &[u8], rounds: u32) -> Result<SecretBuf> {
+    let mut key = SecretBuf::zeroed(KEY_LEN)?;
+    #[cfg(feature = "std")]
+    if progress::enabled() {
+        derive_key_reporting(password, salt, rounds, &mut key)?;
+        return Ok(key);
+    }
+    pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, rounds, &mut key).map_err(|e| SssError::Kdf(e.to_string()))?;
+    Ok(key)
+}
+
+/// PBKDF2-HMAC-SHA256 worked out round by round, the key being a single SHA-256 block, so
+/// progress can be reported along the way.
+#[cfg(feature = "std")]
+fn derive_key_reporting(password: &str, salt: &[u8], rounds: u32, key: &mut [u8]) -> Result<()> {
+    progress::emit(Event::KdfStarted { rounds });
+    let prf = <Hmac<Sha256> as Mac>::new_from_slice(password.as_bytes()).map_err(|e| SssError::Kdf(e.to_string()))?;
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut block = Zeroizing::new([0u8; KEY_LEN]);
+    block.copy_from_slice(&mac.finalize().into_bytes());
+    key.copy_from_slice(block.as_slice());
+    let step = (rounds / 100).max(1);
+    for done in 2..=rounds {
+        let mut mac = prf.clone();
+        mac.update(block.as_slice());
+        block.copy_from_slice(&mac.finalize().into_bytes());
+        key.iter_mut().zip(block.iter()).for_each(|(k, b)| *k ^= b);
+        if done.is_multiple_of(step) && done < rounds {
+            progress::emit(Event::KdfProgress { done, rounds });
+        }
+    }
+    progress::emit(Event::KdfProgress { done: rounds, rounds });
+    Ok(())
+}
@@ -0,0 +1,354 @@
+//! `sss daemon`: split and combine for local apps over a Unix socket, without a process per
+//! call. Each frame is a big-endian `u32` length followed by that many bytes of JSON, and
+//! every request frame gets exactly one response frame on the same connection.
+//!
+//! Requests name an `op`: `split`, `combine`, `unlock` (remember a password for `ttl_secs`
+//! so later requests may leave it out), `lock` (forget it) or `ping`. Responses carry
+//! `"ok": true` and the result, or `"ok": false` with `error` and `kind`. Only processes of
+//! the daemon's own user, or of `--allow-uid` users, may connect.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::format::{set_fingerprint, ShareEncoding};
+use crate::input;
+use crate::pipeline;
+use crate::secret::{pad, serialize_secret, Padding, SecretType};
+use crate::secure::SecretBuf;
+use crate::shamir::Field;
+
+pub const SOCKET_FILE: &str = "sss.sock";
+pub const MAX_FRAME: u32 = 1024 * 1024;
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+/// Connections served at once; more are turned away instead of each getting a thread.
+pub const MAX_CONNECTIONS: usize = 32;
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+enum Request {
+    Split {
+        secret: String,
+        #[serde(default)]
+        password: Option<String>,
+        threshold: u8,
+        total: u8,
+        #[serde(default)]
+        encoding: Option<String>,
+        #[serde(default)]
+        padding: Option<String>,
+    },
+    Combine {
+        shares: Vec<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Unlock {
+        password: String,
+        #[serde(default)]
+        ttl_secs: Option<u64>,
+    },
+    Lock,
+    Ping,
+}
+
+/// `$XDG_RUNTIME_DIR/sss.sock`, which only the user can reach.
+pub fn default_socket() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()).map(|d| PathBuf::from(d).join(SOCKET_FILE))
+}
+
+/// Reads one frame, or `None` if the peer closed the connection between frames.
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME {
+        return Err(SssError::Parse(format!("a {} byte frame is over the {} byte limit", len, MAX_FRAME)));
+    }
+    let mut frame = vec![0u8; len as usize];
+    reader.read_exact(&mut frame)?;
+    Ok(Some(frame))
+}
+
+pub fn write_frame<W: Write>(writer: &mut W, frame: &[u8]) -> Result<()> {
+    let len = u32::try_from(frame.len()).ok().filter(|&len| len <= MAX_FRAME).ok_or_else(|| {
+        SssError::Parse(format!("a {} byte frame is over the {} byte limit", frame.len(), MAX_FRAME))
+    })?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(frame)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Sends one request to the daemon at `socket` and returns its response.
+pub fn call(socket: &Path, request: &Value) -> Result<Value> {
+    let mut stream = UnixStream::connect(socket)?;
+    write_frame(&mut stream, request.to_string().as_bytes())?;
+    let frame = read_frame(&mut stream)?
+        .ok_or_else(|| SssError::Io(std::io::Error::new(ErrorKind::UnexpectedEof, "the daemon hung up")))?;
+    serde_json::from_slice(&frame).map_err(|e| SssError::Parse(format!("malformed response: {}", e)))
+}
+
+/// User ID of the process at the other end of `stream`.
+pub fn peer_uid(stream: &UnixStream) -> Result<u32> {
+    let fd = stream.as_raw_fd();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: `cred` and `len` are valid for writes and `len` is the size of `cred`.
+        let out = (&mut cred as *mut libc::ucred).cast();
+        let rc = unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_PEERCRED, out, &mut len) };
+        if rc != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(cred.uid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let (mut uid, mut gid) = (0, 0);
+        // SAFETY: both out-pointers are valid for writes.
+        if unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(uid)
+    }
+}
+
+struct Unlocked {
+    password: SecretBuf,
+    until: Instant,
+}
+
+pub struct Daemon {
+    rounds: u32,
+    allowed: Vec<u32>,
+    unlocked: Mutex<Option<Unlocked>>,
+}
+
+impl Daemon {
+    /// Splits use `rounds` of PBKDF2. Connections are accepted from the daemon's own user and
+    /// from `allow_uids`.
+    pub fn new(rounds: u32, allow_uids: &[u32]) -> Self {
+        // SAFETY: geteuid has no preconditions and cannot fail.
+        let own = unsafe { libc::geteuid() };
+        let mut allowed = vec![own];
+        allowed.extend_from_slice(allow_uids);
+        Daemon { rounds, allowed, unlocked: Mutex::new(None) }
+    }
+
+    /// Answers one request frame with one response frame.
+    pub fn handle(&self, frame: &[u8]) -> Vec<u8> {
+        let response = serde_json::from_slice(frame)
+            .map_err(|e| SssError::Parse(format!("malformed request: {}", e)))
+            .and_then(|request| self.dispatch(request));
+        let response = match response {
+            Ok(Value::Object(mut fields)) => {
+                fields.insert("ok".into(), true.into());
+                Value::Object(fields)
+            }
+            Ok(other) => json!({ "ok": true, "result": other }),
+            Err(e) => json!({ "ok": false, "error": e.to_string(), "kind": e.kind() }),
+        };
+        response.to_string().into_bytes()
+    }
+
+    fn dispatch(&self, request: Request) -> Result<Value> {
+        match request {
+            Request::Ping => Ok(json!({ "unlocked": self.password(None).is_ok() })),
+            Request::Unlock { password, ttl_secs } => {
+                let password = Zeroizing::new(password);
+                let ttl = ttl_secs.map_or(DEFAULT_TTL, Duration::from_secs);
+                let until = Instant::now()
+                    .checked_add(ttl)
+                    .ok_or_else(|| {
+                        SssError::InvalidArgument(format!("a ttl of {} seconds is too long", ttl.as_secs()))
+                    })?;
+                *self.unlock_state() =
+                    Some(Unlocked { password: SecretBuf::from_slice(password.as_bytes())?, until });
+                Ok(json!({ "ttl_secs": ttl.as_secs() }))
+            }
+            Request::Lock => {
+                *self.unlock_state() = None;
+                Ok(json!({}))
+            }
+            Request::Split { secret, password, threshold, total, encoding, padding } => {
+                let password = self.password(password)?;
+                let secret = SecretType::from_input(&Zeroizing::new(secret))?;
+                let encoding: ShareEncoding = encoding.as_deref().unwrap_or("hex").parse()?;
+                let padding: Padding = match padding {
+                    Some(padding) => padding.parse().map_err(SssError::InvalidArgument)?,
+                    None => Padding::default(),
+                };
+                let rng = &mut rand::rngs::OsRng;
+                let plaintext = pad(&serialize_secret(&secret), padding)?;
+                let payload = pipeline::seal_bound(&plaintext, &password, self.rounds, threshold, rng)?;
+                let shares = Field::Gf256.split_with_rng(&payload, threshold, total, rng)?;
+                Ok(json!({
+                    "shares": shares.iter().map(|s| encoding.encode(s)).collect::<Result<Vec<_>>>()?,
+                    "set_fingerprint": set_fingerprint(&payload),
+                }))
+            }
+            Request::Combine { shares, password } => {
+                let password = self.password(password)?;
+                let shares = shares.iter().map(|s| input::parse_share(s)).collect::<std::result::Result<Vec<_>, _>>()?;
+                let secret = pipeline::combine_secret(&shares, &password)?;
+                Ok(json!({ "secret": pipeline::secret_text(&secret)?.as_str() }))
+            }
+        }
+    }
+
+    /// The remembered password. A connection thread that panicked while holding the lock
+    /// leaves nothing half-written behind, as the state is replaced whole, so a poisoned lock
+    /// is taken over rather than failing every later request.
+    fn unlock_state(&self) -> MutexGuard<'_, Option<Unlocked>> {
+        self.unlocked.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The password the request gave, or else the one remembered by `unlock` if it has not
+    /// expired.
+    fn password(&self, given: Option<String>) -> Result<Zeroizing<String>> {
+        if let Some(password) = given {
+            return Ok(Zeroizing::new(password));
+        }
+        let mut unlocked = self.unlock_state();
+        if unlocked.as_ref().is_some_and(|u| u.until <= Instant::now()) {
+            *unlocked = None;
+        }
+        let unlocked = unlocked
+            .as_ref()
+            .ok_or_else(|| SssError::Unauthorized("no password given and the daemon is locked".into()))?;
+        let password = std::str::from_utf8(&unlocked.password).expect("stored from a str");
+        Ok(Zeroizing::new(password.to_string()))
+    }
+
+    /// Serves one connection until the peer hangs up.
+    pub fn serve_connection(&self, mut stream: UnixStream) -> Result<()> {
+        if let Err(e) = self.admit(&stream) {
+            refuse(&mut stream, &e)?;
+            return Err(e);
+        }
+        self.answer(stream)
+    }
+
+    /// Lets in only the daemon's own user and the `--allow-uid` users.
+    fn admit(&self, stream: &UnixStream) -> Result<()> {
+        let uid = peer_uid(stream)?;
+        if !self.allowed.contains(&uid) {
+            return Err(SssError::Unauthorized(format!("user {} may not use this daemon", uid)));
+        }
+        Ok(())
+    }
+
+    /// Answers the request frames of an admitted connection until the peer hangs up.
+    fn answer(&self, mut stream: UnixStream) -> Result<()> {
+        while let Some(frame) = read_frame(&mut stream)? {
+            let frame = Zeroizing::new(frame);
+            write_frame(&mut stream, &Zeroizing::new(self.handle(&frame)))?;
+        }
+        Ok(())
+    }
+
+    /// Accepts connections on `listener` forever, one thread each for up to
+    /// [`MAX_CONNECTIONS`] at once. Peers that may not connect, and connections over the limit,
+    /// are answered with an error frame on the accepting thread, so they hold no thread of
+    /// their own. A failed accept, such as a client hanging up first or the process running
+    /// out of file descriptors, is logged and the daemon carries on.
+    pub fn serve(&self, listener: &UnixListener) -> Result<()> {
+        let active = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "daemon could not accept a connection");
+                        // give descriptors time to free up instead of spinning on EMFILE
+                        std::thread::sleep(Duration::from_millis(100));
+                        continue;
+                    }
+                };
+                let admitted = self.admit(&stream).and_then(|()| match active.load(Ordering::SeqCst) {
+                    n if n >= MAX_CONNECTIONS => Err(SssError::Io(std::io::Error::new(
+                        ErrorKind::ResourceBusy,
+                        format!("the daemon is already serving {} connections; try again", n),
+                    ))),
+                    _ => Ok(()),
+                });
+                if let Err(e) = admitted {
+                    tracing::warn!(error = e.kind(), "daemon turned a connection away: {}", e);
+                    let _ = refuse(&mut stream, &e);
+                    continue;
+                }
+                let slot = Slot::take(&active);
+                scope.spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = self.answer(stream) {
+                        tracing::warn!(error = e.kind(), "daemon connection ended: {}", e);
+                    }
+                });
+            }
+            Ok(())
+        })
+    }
+}
+
+/// One of the [`MAX_CONNECTIONS`] in use, given back when the connection's thread ends, even
+/// by a panic.
+struct Slot<'a>(&'a AtomicUsize);
+
+impl<'a> Slot<'a> {
+    fn take(active: &'a AtomicUsize) -> Self {
+        active.fetch_add(1, Ordering::SeqCst);
+        Slot(active)
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Answers a connection that is not served with one error frame.
+fn refuse(stream: &mut UnixStream, e: &SssError) -> Result<()> {
+    let reply = json!({ "ok": false, "error": e.to_string(), "kind": e.kind() });
+    write_frame(stream, reply.to_string().as_bytes())
+}
+
+/// Binds the socket at `path`, replacing a stale one. Unless `shared`, only its owner can
+/// reach it; otherwise anyone can connect and the peer check alone decides.
+pub fn bind(path: &Path, shared: bool) -> Result<UnixListener> {
+    if UnixStream::connect(path).is_ok() {
+        return Err(SssError::Io(std::io::Error::new(
+            ErrorKind::AddrInUse,
+            format!("a daemon is already listening on {}", path.display()),
+        )));
+    }
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(SssError::Io(std::io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            )))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(if shared { 0o666 } else { 0o600 }))?;
+    Ok(listener)
+}
@@ -0,0 +1,344 @@
+//! `sss doctor`: works out why a recovery fails from whatever the user still has. It reads each
+//! share in every encoding this tool knows, sets aside duplicates and shares of other splits,
+//! looks for a quorum whose payload is well formed (headers and all, so no password is needed
+//! to tell the right shares from the wrong ones), and only then tries the password candidates,
+//! along with the slips people make typing them. The checks run in that order and the first
+//! one that fails is reported as the most likely cause.
+
+use serde::Serialize;
+use zeroize::Zeroizing;
+
+use crate::crypto::{kdf_rounds, open_payload, seeded_rng};
+use crate::error::SssError;
+use crate::format::set_fingerprint;
+use crate::inspect;
+use crate::legacy::{self, Saved};
+use crate::pkcs11::is_hsm_payload;
+use crate::shamir::{self, Field};
+use crate::timelock::{self, format_date, Conditions};
+
+/// Reconstructions tried while looking for a quorum, so a big box of shares stays quick.
+const MAX_TRIALS: usize = 4096;
+
+/// What the user has to go on.
+#[derive(Default)]
+pub struct Evidence {
+    /// Each share's name (its file, or where it was pasted) and text.
+    pub shares: Vec<(String, String)>,
+    pub field: Field,
+    /// The salt and nonce an old split printed, if the user kept them.
+    pub saved: Option<Saved>,
+    pub passwords: Vec<Zeroizing<String>>,
+}
+
+/// One check and what it found.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub check: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Cause {
+    /// Nothing is wrong: a quorum and a password open the set.
+    Recoverable,
+    NoShares,
+    UnreadableShares,
+    TooFewShares,
+    MixedSets,
+    NoPassword,
+    WrongPassword,
+    /// The set opens, but is time-locked to a date still ahead.
+    TimeLocked,
+    /// The set opens, but only with the dealer's approval or once their heartbeats lapse.
+    Switched,
+    /// The set was encrypted with an HSM key rather than a password.
+    HsmKey,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub findings: Vec<Finding>,
+    pub cause: Cause,
+    /// The most likely cause in a sentence, with what to do about it.
+    pub summary: String,
+    /// Names of the shares that rebuild the set, if any quorum does.
+    pub quorum: Vec<String>,
+    /// Which password candidate (from 1) opens the set.
+    pub password: Option<usize>,
+}
+
+impl Diagnosis {
+    /// The diagnosis as the error `decrypt` would have failed with, or `Ok` when nothing was
+    /// found wrong.
+    pub fn result(&self) -> crate::error::Result<()> {
+        let summary = self.summary.clone();
+        Err(match self.cause {
+            Cause::Recoverable | Cause::NoPassword => return Ok(()),
+            Cause::NoShares | Cause::UnreadableShares => SssError::Parse(summary),
+            Cause::TooFewShares => SssError::InsufficientShares(summary),
+            Cause::MixedSets => SssError::Share(summary),
+            Cause::WrongPassword => SssError::WrongPassword,
+            Cause::TimeLocked | Cause::Switched | Cause::HsmKey => SssError::Policy(summary),
+        })
+    }
+}
+
+struct Readable {
+    name: String,
+    share: Vec<u8>,
+    set: Option<String>,
+    quorum: Option<(u8, u8)>,
+}
+
+struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn add(&mut self, check: impl Into<String>, ok: bool, detail: impl Into<String>) {
+        self.findings.push(Finding { check: check.into(), ok, detail: detail.into() });
+    }
+
+    fn conclude(self, cause: Cause, summary: impl Into<String>, quorum: Vec<String>, password: Option<usize>) -> Diagnosis {
+        Diagnosis { findings: self.findings, cause, summary: summary.into(), quorum, password }
+    }
+}
+
+/// Runs every check on `evidence`, stopping at the first that rules recovery out.
+pub fn diagnose(evidence: &Evidence) -> Diagnosis {
+    let mut report = Report { findings: Vec::new() };
+    if evidence.shares.is_empty() {
+        return report.conclude(Cause::NoShares, "no shares were given; name share files or paste them", vec![], None);
+    }
+
+    let mut readable: Vec<Readable> = Vec::new();
+    for (name, text) in &evidence.shares {
+        let parsed = match inspect::parse(text, evidence.field) {
+            Ok(parsed) if parsed.share.len() > 1 && parsed.share[0] != 0 => parsed,
+            Ok(_) => {
+                report.add(format!("encoding of {}", name), false, "not a share: it has no index or no data");
+                continue;
+            }
+            Err(e) => {
+                report.add(format!("encoding of {}", name), false, e.to_string());
+                continue;
+            }
+        };
+        let corrected = match parsed.corrected {
+            0 => String::new(),
+            n => format!(", its parity corrected {} byte(s)", n),
+        };
+        let detail = format!("{}, index {}{}", parsed.encoding, parsed.share[0], corrected);
+        report.add(format!("encoding of {}", name), true, detail);
+        if let Some(other) = readable.iter().find(|r| r.share == parsed.share) {
+            report.add(format!("membership of {}", name), false, format!("the same share as {}; counted once", other.name));
+            continue;
+        }
+        let header = parsed.header;
+        readable.push(Readable { name: name.clone(), share: parsed.share, set: header.set, quorum: header.quorum });
+    }
+    if readable.is_empty() {
+        let summary = "none of the shares could be read: check that each was copied whole, or try --field";
+        return report.conclude(Cause::UnreadableShares, summary, vec![], None);
+    }
+
+    // shares of one split are all the same length, so the most common length is the set's
+    let count = |len: usize| readable.iter().filter(|r| r.share.len() == len).count();
+    let len = readable.iter().map(|r| r.share.len()).max_by_key(|&len| count(len)).expect("a share was read");
+    let (members, strays): (Vec<Readable>, Vec<Readable>) = readable.into_iter().partition(|r| r.share.len() == len);
+    for stray in &strays {
+        let detail = format!("{} bytes where the others are {}, so it comes from a different split", stray.share.len(), len);
+        report.add(format!("membership of {}", stray.name), false, detail);
+    }
+    let mut sets: Vec<&str> = members.iter().filter_map(|r| r.set.as_deref()).collect();
+    sets.sort_unstable();
+    sets.dedup();
+    if sets.len() > 1 {
+        report.add("declared sets", false, format!("the shares name {} different sets: {}", sets.len(), sets.join(", ")));
+    }
+    let threshold = members.iter().find_map(|r| r.quorum).map(|(threshold, _)| threshold);
+    let names = |positions: &[usize]| positions.iter().map(|&i| members[i].name.clone()).collect::<Vec<_>>();
+
+    let Some((quorum, payload)) = find_quorum(evidence.field, &members, threshold) else {
+        if let Some(threshold) = threshold.filter(|&t| members.len() < t as usize) {
+            let summary = format!(
+                "the set needs {} shares but only {} of it were given; find {} more",
+                threshold,
+                members.len(),
+                threshold as usize - members.len()
+            );
+            report.add("quorum", false, summary.clone());
+            return report.conclude(Cause::TooFewShares, summary, vec![], None);
+        }
+        // sets split before payloads had headers only show they are whole when they decrypt
+        let all: Vec<usize> = (0..members.len()).collect();
+        let payload = evidence.field.reconstruct(&members.iter().map(|r| &r.share).collect::<Vec<_>>());
+        if let Some(payload) = payload.ok().filter(|p| legacy::is_legacy(p)) {
+            if let Some(found) = try_passwords(&mut report, &payload, evidence) {
+                report.add("quorum", true, format!("all {} shares rebuild a set of the first format", members.len()));
+                return report.conclude(Cause::Recoverable, "the set opens; recover it with sss migrate", names(&all), Some(found));
+            }
+        }
+        let summary = "no group of these shares rebuilds a well-formed payload: some come from another set or \
+                       are damaged, or the set needs more shares than were given";
+        report.add("quorum", false, summary);
+        return report.conclude(Cause::MixedSets, summary, vec![], None);
+    };
+
+    let (conditions, _, _) = Conditions::split(&payload);
+    let mut detail = format!("{} rebuild set {}", names(&quorum).join(", "), set_fingerprint(&payload));
+    if let Some(binding) = conditions.binding {
+        detail.push_str(&format!(" (set ID {}, threshold {})", binding.set_id(), binding.threshold));
+    }
+    report.add("quorum", true, detail);
+    let mut quorum_names = names(&quorum);
+    for member in members.iter().enumerate().filter(|(i, _)| !quorum.contains(i)).map(|(_, member)| member) {
+        if let Some(&twin) = quorum.iter().find(|&&q| members[q].share[0] == member.share[0]) {
+            let detail = format!(
+                "has index {} like {} but different contents: it is from another set, or damaged",
+                member.share[0],
+                members[twin].name
+            );
+            report.add(format!("membership of {}", member.name), false, detail);
+            continue;
+        }
+        let mut with: Vec<&Vec<u8>> = quorum.iter().map(|&q| &members[q].share).collect();
+        with.push(&member.share);
+        match evidence.field.reconstruct(&with).is_ok_and(|p| p == payload) {
+            true => quorum_names.push(member.name.clone()),
+            false => report.add(
+                format!("membership of {}", member.name),
+                false,
+                "does not lie on the set's polynomial: it is from another set, or damaged",
+            ),
+        }
+    }
+
+    if is_hsm_payload(&payload) {
+        let summary = "the set is encrypted with an HSM key, not a password; decrypt it with --pkcs11";
+        return report.conclude(Cause::HsmKey, summary, quorum_names, None);
+    }
+    if evidence.passwords.is_empty() {
+        let summary = "the shares are sound; give the password, or candidates for it, to test";
+        return report.conclude(Cause::NoPassword, summary, quorum_names, None);
+    }
+    let Some(found) = try_passwords(&mut report, &payload, evidence) else {
+        let mut summary = "the shares are sound, but none of the password candidates opens the set".to_string();
+        if legacy::is_legacy(&payload) && evidence.saved.is_none() {
+            summary.push_str("; if the shares hold only the ciphertext, give the saved salt and nonce");
+        }
+        return report.conclude(Cause::WrongPassword, summary, quorum_names, None);
+    };
+
+    if let Some(lock) = conditions.time_lock.filter(|lock| lock.check(timelock::system_now()).is_err()) {
+        let summary = format!(
+            "the shares and password are right, but the set is time-locked until {}",
+            format_date(lock.not_before)
+        );
+        return report.conclude(Cause::TimeLocked, summary, quorum_names, Some(found));
+    }
+    if conditions.switch.is_some() {
+        let summary = "the shares and password are right, but the set is behind a dead man's switch: give \
+                       decrypt the dealer's approval token or their lapsed heartbeat";
+        return report.conclude(Cause::Switched, summary, quorum_names, Some(found));
+    }
+    report.conclude(Cause::Recoverable, "the set opens; recover it with sss decrypt", quorum_names, Some(found))
+}
+
+/// The first quorum of `members`, `threshold` strong if that is known and otherwise as small as
+/// possible, that rebuilds a well-formed payload.
+fn find_quorum(field: Field, members: &[Readable], threshold: Option<u8>) -> Option<(Vec<usize>, Vec<u8>)> {
+    let total = u8::try_from(members.len()).unwrap_or(u8::MAX);
+    let sizes = match threshold {
+        Some(threshold) => threshold..=threshold,
+        None => 2.min(total)..=total,
+    };
+    let mut rng = seeded_rng("sss doctor");
+    let mut trials = 0;
+    for size in sizes {
+        for quorum in shamir::quorums(size, total, MAX_TRIALS, &mut rng) {
+            let mut indices: Vec<u8> = quorum.iter().map(|&i| members[i].share[0]).collect();
+            indices.sort_unstable();
+            indices.dedup();
+            if indices.len() < quorum.len() {
+                continue;
+            }
+            trials += 1;
+            if trials > MAX_TRIALS {
+                return None;
+            }
+            let shares: Vec<&Vec<u8>> = quorum.iter().map(|&i| &members[i].share).collect();
+            match field.reconstruct(&shares) {
+                Ok(payload) if well_formed(&payload) => return Some((quorum, payload)),
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// Whether a payload has the shape this tool writes: conditions, then a KDF header, or an
+/// HSM header. A quorum of the wrong shares rebuilds noise, which almost never does.
+fn well_formed(payload: &[u8]) -> bool {
+    let (conditions, _, rest) = Conditions::split(payload);
+    let bound = conditions.binding.is_none_or(|binding| binding.check().is_ok());
+    is_hsm_payload(payload) || (bound && kdf_rounds(rest).is_some())
+}
+
+/// A password as typed, and as it would be with the usual slips undone.
+fn variants(password: &str) -> Vec<(String, &'static str)> {
+    let swapped: String = password
+        .chars()
+        .map(|c| match c.is_uppercase() {
+            true => c.to_lowercase().next().unwrap_or(c),
+            false => c.to_uppercase().next().unwrap_or(c),
+        })
+        .collect();
+    let mut variants = vec![
+        (password.to_string(), ""),
+        (password.trim().to_string(), " with the spaces around it removed"),
+        (swapped, " with caps lock on"),
+    ];
+    let mut seen = Vec::new();
+    variants.retain(|(text, _)| !text.is_empty() && !seen.contains(text) && {
+        seen.push(text.clone());
+        true
+    });
+    variants
+}
+
+/// Tries each candidate and its variants on `payload`, returning the number of the one that
+/// opens it.
+fn try_passwords(report: &mut Report, payload: &[u8], evidence: &Evidence) -> Option<usize> {
+    for (n, candidate) in evidence.passwords.iter().enumerate() {
+        let mut tried = Vec::new();
+        for (password, how) in variants(candidate) {
+            let password = Zeroizing::new(password);
+            let opened = match (&evidence.saved, legacy::is_legacy(payload)) {
+                (Some(saved), true) => legacy::open(payload, Some(saved), &password),
+                _ => open_payload(payload, &password),
+            };
+            match opened {
+                Ok(_) => {
+                    report.add(format!("password candidate {}", n + 1), true, format!("opens the set{}", how));
+                    return Some(n + 1);
+                }
+                Err(SssError::Aead) => tried.push(how.trim_start_matches(" with ").to_string()),
+                Err(e) => {
+                    report.add(format!("password candidate {}", n + 1), false, e.to_string());
+                    return None;
+                }
+            }
+        }
+        let tried = tried.iter().filter(|t| !t.is_empty()).map(String::as_str).collect::<Vec<_>>().join(", ");
+        let detail = match tried.is_empty() {
+            true => "does not open the set".to_string(),
+            false => format!("does not open the set, nor with {}", tried),
+        };
+        report.add(format!("password candidate {}", n + 1), false, detail);
+    }
+    None
+}
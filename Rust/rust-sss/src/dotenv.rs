@@ -0,0 +1,123 @@
+//! `.env` files as a secret. The file is split whole and comes back byte for byte; parsing
+//! only checks it at split time and picks variables out for `decrypt --export`. Lines are
+//! `NAME=value`, optionally after `export `, with `#` comments. Values may be single-quoted
+//! (taken literally) or double-quoted (with `\n`, `\"` and `\\` escapes, and line breaks
+//! inside the quotes); unquoted values end at ` #`.
+
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+/// A variable and its value, as the file defines it.
+pub struct Variable {
+    pub name: String,
+    pub value: Zeroizing<String>,
+}
+
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The variables in `text`, in file order. A name defined twice keeps its last value, as
+/// shells and dotenv loaders do.
+pub fn parse(text: &str) -> Result<Vec<Variable>> {
+    let mut variables: Vec<Variable> = Vec::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        let (name, rest) = line
+            .split_once('=')
+            .ok_or_else(|| SssError::Parse(format!(".env line {} is not NAME=value", i + 1)))?;
+        let name = name.trim();
+        if !valid_name(name) {
+            return Err(SssError::Parse(format!(".env line {}: '{}' is not a variable name", i + 1, name)));
+        }
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut raw = Zeroizing::new(rest[1..].to_string());
+                while !closes(&raw, quote) {
+                    let (_, next) = lines
+                        .next()
+                        .ok_or_else(|| SssError::Parse(format!(".env line {}: the quote is never closed", i + 1)))?;
+                    raw.push('\n');
+                    raw.push_str(next);
+                }
+                let end = closing_quote(&raw, quote).unwrap_or(raw.len());
+                match quote {
+                    '"' => unescape(&raw[..end]),
+                    _ => Zeroizing::new(raw[..end].to_string()),
+                }
+            }
+            _ => {
+                let end = rest.find(" #").unwrap_or(rest.len());
+                Zeroizing::new(rest[..end].trim_end().to_string())
+            }
+        };
+        variables.retain(|v| v.name != name);
+        variables.push(Variable { name: name.to_string(), value });
+    }
+    Ok(variables)
+}
+
+fn closing_quote(raw: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in raw.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn closes(raw: &str, quote: char) -> bool {
+    closing_quote(raw, quote).is_some()
+}
+
+fn unescape(raw: &str) -> Zeroizing<String> {
+    let mut value = Zeroizing::new(String::with_capacity(raw.len()));
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => value.push('\n'),
+            ('\\', Some('t')) => value.push('\t'),
+            ('\\', Some(escaped @ ('"' | '\\' | '$'))) => value.push(escaped),
+            (c, _) => {
+                value.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    value
+}
+
+/// `export NAME='value'`, quoted so any shell reads the value back unchanged.
+pub fn export_line(variable: &Variable) -> Zeroizing<String> {
+    Zeroizing::new(format!("export {}='{}'", variable.name, variable.value.replace('\'', r"'\''")))
+}
+
+/// Picks the variables named in `names` out of `variables`, in the order asked for; all of
+/// them when `names` is empty.
+pub fn select<'a>(variables: &'a [Variable], names: &[String]) -> Result<Vec<&'a Variable>> {
+    if names.is_empty() {
+        return Ok(variables.iter().collect());
+    }
+    names
+        .iter()
+        .map(|name| {
+            variables.iter().find(|v| &v.name == name).ok_or_else(|| {
+                let available: Vec<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+                SssError::Parse(format!("no variable {} in the .env file (available: {})", name, available.join(", ")))
+            })
+        })
+        .collect()
+}
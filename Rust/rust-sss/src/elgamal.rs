@@ -0,0 +1,151 @@
+//! Threshold ElGamal decryption over secp256k1. The dealer shares a private key x with
+//! [`shamir::secp256k1`](crate::shamir::secp256k1) and publishes X = xG. Anyone can encrypt to
+//! X; to decrypt, each holder of a key share x_i sends x_i·R for the ciphertext's ephemeral
+//! point R, and any quorum of those partials interpolates to x·R without x ever being rebuilt.
+//!
+//! Ciphertexts are MAGIC ‖ R (33 bytes, compressed) ‖ nonce ‖ AES-256-GCM ciphertext and tag,
+//! with the AES key hashed from R and the shared point.
+
+use aes::cipher::generic_array::GenericArray;
+use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::elliptic_curve::Field;
+use k256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::crypto::NONCE_LEN;
+use crate::error::{Result, SssError};
+use crate::shamir::secp256k1::{self, lagrange_at_zero, SHARE_LEN};
+
+const MAGIC: &[u8; 8] = b"SSSTDEC1";
+const POINT_LEN: usize = 33;
+const KDF_LABEL: &[u8] = b"sss threshold decryption v1";
+
+/// A holder's partial decryption: `[x, x_i·R (33 bytes)]`.
+pub const PARTIAL_LEN: usize = 1 + POINT_LEN;
+
+fn point(bytes: &[u8]) -> Result<ProjectivePoint> {
+    let encoded = EncodedPoint::from_bytes(bytes)
+        .map_err(|_| SssError::Parse("not a SEC1-encoded secp256k1 point".into()))?;
+    Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded))
+        .map(ProjectivePoint::from)
+        .ok_or_else(|| SssError::Parse("not a point on secp256k1".into()))
+}
+
+fn compress(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+}
+
+fn message_key(ephemeral: &[u8], shared: &ProjectivePoint) -> Zeroizing<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(KDF_LABEL);
+    hasher.update(ephemeral);
+    hasher.update(shared.to_bytes());
+    Zeroizing::new(hasher.finalize().into())
+}
+
+/// A dealt decryption key: the public key to encrypt to and one key share per holder.
+pub struct Dealt {
+    pub public_key: Vec<u8>,
+    pub shares: Vec<Zeroizing<Vec<u8>>>,
+}
+
+/// Generates a key and splits it; the full private key only exists inside this call.
+pub fn keygen(threshold: u8, total: u8) -> Result<Dealt> {
+    let key = Zeroizing::new(Scalar::random(&mut rand::rngs::OsRng).to_bytes().to_vec());
+    let shares = secp256k1::split(&key, threshold, total)?;
+    Ok(Dealt {
+        public_key: secp256k1::public_key(&key)?,
+        shares: shares.into_iter().map(Zeroizing::new).collect(),
+    })
+}
+
+pub fn encrypt(public_key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let recipient = point(public_key)?;
+    let r = Zeroizing::new(Scalar::random(&mut rand::rngs::OsRng));
+    let ephemeral = compress(&(ProjectivePoint::GENERATOR * *r));
+    let key = message_key(&ephemeral, &(recipient * *r));
+
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let sealed = Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+        .encrypt(GenericArray::from_slice(&nonce), plaintext)?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + POINT_LEN + NONCE_LEN + sealed.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&ephemeral);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+fn parse_ciphertext(ciphertext: &[u8]) -> Result<(&[u8], &[u8], &[u8])> {
+    let body = ciphertext
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| SssError::Parse("not a threshold-encrypted file".into()))?;
+    if body.len() < POINT_LEN + NONCE_LEN {
+        return Err(SssError::Parse("threshold ciphertext is truncated".into()));
+    }
+    let (ephemeral, rest) = body.split_at(POINT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    Ok((ephemeral, nonce, sealed))
+}
+
+/// One holder's contribution towards decrypting `ciphertext`. It reveals nothing about the
+/// key share or, on its own, the plaintext.
+pub fn partial_decrypt(share: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if share.len() != SHARE_LEN || share[0] == 0 {
+        return Err(SssError::Share(format!("a decryption key share is {} bytes", SHARE_LEN)));
+    }
+    let secret = Zeroizing::new(secp256k1::scalar(&share[1..])?);
+    let (ephemeral, _, _) = parse_ciphertext(ciphertext)?;
+    let mut partial = vec![share[0]];
+    partial.extend_from_slice(&compress(&(point(ephemeral)? * *secret)));
+    Ok(partial)
+}
+
+/// Opens `ciphertext` with the whole private key, for a key that was never split.
+pub fn decrypt(private_key: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let (ephemeral, nonce, sealed) = parse_ciphertext(ciphertext)?;
+    let secret = Zeroizing::new(secp256k1::scalar(private_key)?);
+    let key = message_key(ephemeral, &(point(ephemeral)? * *secret));
+    Ok(Zeroizing::new(
+        Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+            .decrypt(GenericArray::from_slice(nonce), sealed)?,
+    ))
+}
+
+/// Combines a quorum of partial decryptions and opens the ciphertext. Too few or mismatched
+/// partials fail authentication rather than giving wrong plaintext.
+pub fn combine<S: AsRef<[u8]>>(partials: &[S], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let (ephemeral, nonce, sealed) = parse_ciphertext(ciphertext)?;
+    let mut xs = Vec::with_capacity(partials.len());
+    let mut points = Vec::with_capacity(partials.len());
+    for partial in partials {
+        let partial = partial.as_ref();
+        if partial.len() != PARTIAL_LEN {
+            return Err(SssError::Share(format!("a partial decryption is {} bytes", PARTIAL_LEN)));
+        }
+        if partial[0] == 0 || xs.contains(&partial[0]) {
+            return Err(SssError::Share(format!("share index {} is invalid or repeated", partial[0])));
+        }
+        xs.push(partial[0]);
+        points.push(point(&partial[1..])?);
+    }
+    if xs.is_empty() {
+        return Err(SssError::Share("no partial decryptions to combine".into()));
+    }
+
+    let shared = lagrange_at_zero(&xs)
+        .into_iter()
+        .zip(&points)
+        .fold(ProjectivePoint::IDENTITY, |acc, (basis, p)| acc + *p * basis);
+    let key = message_key(ephemeral, &shared);
+    Ok(Zeroizing::new(
+        Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+            .decrypt(GenericArray::from_slice(nonce), sealed)?,
+    ))
+}
@@ -0,0 +1,279 @@
+//! Envelope encryption, the way a KMS does it: a random 256-bit data-encryption key (DEK)
+//! encrypts the data, and only the DEK is split. The ciphertext lives in its own file, as
+//! large as the data, while each key share stays 33 bytes, so the shares can be reissued to a
+//! new quorum ([`reshare`], `sss envelope rewrap`) without touching the ciphertext, and the key
+//! replaced outright ([`rotate`], `sss envelope rotate`) when a quorum may have leaked it.
+//!
+//! The ciphertext file is `sse1` ‖ key ID ‖ nonce prefix ‖ chunk length (big-endian u32),
+//! followed by the data in AES-256-GCM chunks, each sealed under prefix ‖ chunk counter ‖
+//! final flag with the file header as associated data. Reordered, dropped or truncated chunks
+//! fail to authenticate, and the data streams through in constant memory. The key ID is a
+//! hash of the DEK, so shares are checked against the file before anything is decrypted.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::Aes256Gcm;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::io::{ErrorKind, Read, Write};
+use zeroize::Zeroizing;
+
+use crate::crypto::{NONCE_LEN, TAG_LEN};
+use crate::error::{Result, SssError};
+use crate::shamir::Field;
+
+const MAGIC: &[u8; 4] = b"sse1";
+const KEY_ID_DOMAIN: &[u8] = b"sss envelope key id v1";
+pub const KEY_LEN: usize = 32;
+pub const KEY_ID_LEN: usize = 16;
+const PREFIX_LEN: usize = NONCE_LEN - 5;
+pub const HEADER_LEN: usize = MAGIC.len() + KEY_ID_LEN + PREFIX_LEN + 4;
+/// Plaintext bytes per chunk unless the caller picks another size.
+pub const CHUNK_LEN: u32 = 64 * 1024;
+/// Chunks are held in memory whole, so a header cannot ask for more than this.
+pub const MAX_CHUNK_LEN: u32 = 16 * 1024 * 1024;
+
+pub type Dek = Zeroizing<[u8; KEY_LEN]>;
+
+/// What the front of a ciphertext file says.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub key_id: [u8; KEY_ID_LEN],
+    pub nonce_prefix: [u8; PREFIX_LEN],
+    pub chunk_len: u32,
+}
+
+impl Header {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.nonce_prefix);
+        bytes.extend_from_slice(&self.chunk_len.to_be_bytes());
+        bytes
+    }
+
+    pub fn read(input: &mut dyn Read) -> Result<Header> {
+        let mut bytes = [0u8; HEADER_LEN];
+        if fill(input, &mut bytes)? < HEADER_LEN || !bytes.starts_with(MAGIC) {
+            return Err(SssError::Parse("not an envelope ciphertext file".into()));
+        }
+        let (key_id, rest) = bytes[MAGIC.len()..].split_at(KEY_ID_LEN);
+        let (nonce_prefix, chunk_len) = rest.split_at(PREFIX_LEN);
+        let chunk_len = u32::from_be_bytes(chunk_len.try_into().expect("four bytes"));
+        if !(1..=MAX_CHUNK_LEN).contains(&chunk_len) {
+            return Err(SssError::Parse(format!("the envelope's chunk length {} is out of range", chunk_len)));
+        }
+        Ok(Header {
+            key_id: key_id.try_into().expect("sized above"),
+            nonce_prefix: nonce_prefix.try_into().expect("sized above"),
+            chunk_len,
+        })
+    }
+
+    pub fn key_id_hex(&self) -> String {
+        hex::encode(self.key_id)
+    }
+}
+
+pub fn generate_key<R: RngCore + ?Sized>(rng: &mut R) -> Dek {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    rng.fill_bytes(&mut key[..]);
+    key
+}
+
+pub fn key_id(key: &[u8; KEY_LEN]) -> [u8; KEY_ID_LEN] {
+    let hash = Sha256::new().chain_update(KEY_ID_DOMAIN).chain_update(key).finalize();
+    hash[..KEY_ID_LEN].try_into().expect("a SHA-256 hash is longer")
+}
+
+/// Reads until `buf` is full or the input ends, returning how much was read.
+fn fill(input: &mut dyn Read, buf: &mut [u8]) -> Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match input.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(read)
+}
+
+/// Reads the next `len` bytes, reporting whether they are the last: the byte after them, if
+/// any, is carried over into the next read.
+struct Chunks<'a> {
+    input: &'a mut dyn Read,
+    carry: Option<u8>,
+}
+
+impl Chunks<'_> {
+    fn next(&mut self, buf: &mut Vec<u8>, len: usize) -> Result<bool> {
+        buf.clear();
+        buf.extend(self.carry.take());
+        let start = buf.len();
+        buf.resize(len, 0);
+        let read = fill(self.input, &mut buf[start..])?;
+        buf.truncate(start + read);
+        let mut next = [0u8; 1];
+        if buf.len() == len && fill(self.input, &mut next)? == 1 {
+            self.carry = Some(next[0]);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+fn nonce(header: &Header, counter: u32, last: bool) -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..PREFIX_LEN].copy_from_slice(&header.nonce_prefix);
+    nonce[PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_LEN - 1] = last as u8;
+    nonce
+}
+
+/// Encrypts `input` under `key` into `output`, returning the number of plaintext bytes.
+pub fn seal<R: RngCore + ?Sized>(
+    key: &[u8; KEY_LEN],
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    chunk_len: u32,
+    rng: &mut R,
+) -> Result<u64> {
+    if !(1..=MAX_CHUNK_LEN).contains(&chunk_len) {
+        return Err(SssError::Parse(format!("the chunk length must be 1 to {} bytes", MAX_CHUNK_LEN)));
+    }
+    let mut header = Header { key_id: key_id(key), nonce_prefix: [0; PREFIX_LEN], chunk_len };
+    rng.fill_bytes(&mut header.nonce_prefix);
+    let aad = header.to_bytes();
+    output.write_all(&aad)?;
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| SssError::Aead)?;
+    let mut chunks = Chunks { input, carry: None };
+    let mut plaintext = Zeroizing::new(Vec::with_capacity(chunk_len as usize));
+    let mut total = 0u64;
+    for counter in 0u32.. {
+        let last = chunks.next(&mut plaintext, chunk_len as usize)?;
+        let nonce = nonce(&header, counter, last);
+        let sealed = cipher
+            .encrypt(&nonce.into(), Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| SssError::Aead)?;
+        output.write_all(&sealed)?;
+        total += plaintext.len() as u64;
+        if last {
+            output.flush()?;
+            return Ok(total);
+        }
+    }
+    Err(SssError::Parse("the data is too large for one envelope".into()))
+}
+
+fn check_key(header: &Header, key: &[u8; KEY_LEN]) -> Result<()> {
+    if header.key_id != key_id(key) {
+        return Err(SssError::Share(format!(
+            "the key shares rebuild key {}, but the file was encrypted with key {}",
+            hex::encode(key_id(key)),
+            header.key_id_hex()
+        )));
+    }
+    Ok(())
+}
+
+/// Decrypts an envelope from `input` into `output`, returning the number of plaintext bytes.
+/// The key must match the file's key ID; a chunk that fails to authenticate stops the output
+/// there with an error, so callers should write to a file they discard on failure.
+pub fn open(key: &[u8; KEY_LEN], input: &mut dyn Read, output: &mut dyn Write) -> Result<u64> {
+    let header = Header::read(input)?;
+    check_key(&header, key)?;
+    let aad = header.to_bytes();
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| SssError::Aead)?;
+    let mut chunks = Chunks { input, carry: None };
+    let mut sealed = Vec::with_capacity(header.chunk_len as usize + TAG_LEN);
+    let mut total = 0u64;
+    for counter in 0u32.. {
+        let last = chunks.next(&mut sealed, header.chunk_len as usize + TAG_LEN)?;
+        let nonce = nonce(&header, counter, last);
+        let plaintext = Zeroizing::new(
+            cipher.decrypt(&nonce.into(), Payload { msg: &sealed, aad: &aad }).map_err(|_| SssError::Aead)?,
+        );
+        output.write_all(&plaintext)?;
+        total += plaintext.len() as u64;
+        if last {
+            output.flush()?;
+            return Ok(total);
+        }
+    }
+    Err(SssError::Parse("the envelope has more chunks than one can hold".into()))
+}
+
+/// Splits the key into `total` GF(256) shares, any `threshold` of which rebuild it.
+pub fn split_key<R: RngCore + ?Sized>(key: &[u8; KEY_LEN], threshold: u8, total: u8, rng: &mut R) -> Result<Vec<Vec<u8>>> {
+    Field::Gf256.split_with_rng(key, threshold, total, rng)
+}
+
+/// Rebuilds the key from a quorum of its shares, checking it against `key_id`.
+pub fn combine_key<S: AsRef<[u8]>>(shares: &[S], key_id: &[u8; KEY_ID_LEN]) -> Result<Dek> {
+    let bytes = Zeroizing::new(Field::Gf256.reconstruct(shares)?);
+    let key: Dek = Zeroizing::new(
+        bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| SssError::Share(format!("key shares hold {} bytes, not a {}-byte key", bytes.len(), KEY_LEN)))?,
+    );
+    if self::key_id(&key) != *key_id {
+        return Err(SssError::Share(format!(
+            "these shares do not rebuild key {}: too few were given, or some belong to another key",
+            hex::encode(key_id)
+        )));
+    }
+    Ok(key)
+}
+
+/// New shares of the same key for a new quorum; the ciphertext stays as it is, and shares of
+/// the old quorum keep working until they are destroyed.
+pub fn reshare<S: AsRef<[u8]>, R: RngCore + ?Sized>(
+    shares: &[S],
+    key_id: &[u8; KEY_ID_LEN],
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    let key = combine_key(shares, key_id)?;
+    split_key(&key, threshold, total, rng)
+}
+
+/// Re-encrypts an envelope under a fresh key, chunk by chunk, returning that key. Nothing
+/// the old key or its shares open is left in `output`.
+pub fn rotate<R: RngCore + ?Sized>(
+    old_key: &[u8; KEY_LEN],
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    rng: &mut R,
+) -> Result<Dek> {
+    let old = Header::read(input)?;
+    check_key(&old, old_key)?;
+    let key = generate_key(rng);
+    let mut new = Header { key_id: key_id(&key), nonce_prefix: [0; PREFIX_LEN], chunk_len: old.chunk_len };
+    rng.fill_bytes(&mut new.nonce_prefix);
+    let (old_aad, new_aad) = (old.to_bytes(), new.to_bytes());
+    output.write_all(&new_aad)?;
+    let opener = Aes256Gcm::new_from_slice(old_key).map_err(|_| SssError::Aead)?;
+    let sealer = Aes256Gcm::new_from_slice(&key[..]).map_err(|_| SssError::Aead)?;
+    let mut chunks = Chunks { input, carry: None };
+    let mut sealed = Vec::with_capacity(old.chunk_len as usize + TAG_LEN);
+    for counter in 0u32.. {
+        let last = chunks.next(&mut sealed, old.chunk_len as usize + TAG_LEN)?;
+        let plaintext = Zeroizing::new(
+            opener
+                .decrypt(&nonce(&old, counter, last).into(), Payload { msg: &sealed, aad: &old_aad })
+                .map_err(|_| SssError::Aead)?,
+        );
+        let resealed = sealer
+            .encrypt(&nonce(&new, counter, last).into(), Payload { msg: &plaintext, aad: &new_aad })
+            .map_err(|_| SssError::Aead)?;
+        output.write_all(&resealed)?;
+        if last {
+            output.flush()?;
+            return Ok(key);
+        }
+    }
+    Err(SssError::Parse("the envelope has more chunks than one can hold".into()))
+}
@@ -0,0 +1,149 @@
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::input::InputError;
+
+#[derive(Debug)]
+pub enum SssError {
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    #[cfg(feature = "std")]
+    Input(InputError),
+    /// A command-line argument or parameter is out of range, unknown, or conflicts with another.
+    InvalidArgument(String),
+    /// Data read from a share, file or message is malformed.
+    Parse(String),
+    /// A share, word list or block failed its checksum or parity and could not be repaired.
+    Checksum(String),
+    Share(String),
+    /// Fewer shares were given than the set needs.
+    InsufficientShares(String),
+    Kdf(String),
+    Aead,
+    /// Authentication failed although the shares were confirmed to rebuild the expected set.
+    WrongPassword,
+    SecureMemory(String),
+    Store(String),
+    Unauthorized(String),
+    Policy(String),
+    Signing(String),
+}
+
+impl SssError {
+    /// The process exit code for this error. Scripts may rely on these staying the same:
+    ///
+    /// | code | meaning |
+    /// |------|---------|
+    /// | 0 | success |
+    /// | 1 | I/O error |
+    /// | 2 | bad input or an invalid argument |
+    /// | 3 | malformed data, or a failed checksum |
+    /// | 4 | authentication failed: wrong password, or recovery not authorized |
+    /// | 5 | not enough shares |
+    /// | 6 | other secret sharing errors, such as shares from different sets |
+    /// | 7 | secure memory unavailable |
+    /// | 8 | share store error |
+    /// | 9 | key derivation failed |
+    /// | 10 | policy violation |
+    /// | 11 | signing or signature error |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            #[cfg(feature = "std")]
+            SssError::Io(_) => 1,
+            #[cfg(feature = "std")]
+            SssError::Input(_) => 2,
+            SssError::InvalidArgument(_) => 2,
+            SssError::Parse(_) | SssError::Checksum(_) => 3,
+            SssError::Aead | SssError::WrongPassword | SssError::Unauthorized(_) => 4,
+            SssError::InsufficientShares(_) => 5,
+            SssError::Share(_) => 6,
+            SssError::SecureMemory(_) => 7,
+            SssError::Store(_) => 8,
+            SssError::Kdf(_) => 9,
+            SssError::Policy(_) => 10,
+            SssError::Signing(_) => 11,
+        }
+    }
+
+    /// Short, message-free name of the error, safe to attach to telemetry.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "std")]
+            SssError::Io(_) => "io",
+            #[cfg(feature = "std")]
+            SssError::Input(_) => "input",
+            SssError::InvalidArgument(_) => "invalid-argument",
+            SssError::Parse(_) => "parse",
+            SssError::Checksum(_) => "checksum",
+            SssError::Share(_) => "share",
+            SssError::InsufficientShares(_) => "insufficient-shares",
+            SssError::Kdf(_) => "kdf",
+            SssError::Aead => "aead",
+            SssError::WrongPassword => "wrong-password",
+            SssError::SecureMemory(_) => "secure-memory",
+            SssError::Store(_) => "store",
+            SssError::Unauthorized(_) => "unauthorized",
+            SssError::Policy(_) => "policy",
+            SssError::Signing(_) => "signing",
+        }
+    }
+}
+
+impl fmt::Display for SssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            SssError::Io(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "std")]
+            SssError::Input(e) => write!(f, "invalid input: {}", e),
+            SssError::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+            SssError::Parse(msg) => write!(f, "malformed data: {}", msg),
+            SssError::Checksum(msg) => write!(f, "checksum failed: {}", msg),
+            SssError::Share(e) => write!(f, "secret sharing failed: {}", e),
+            SssError::InsufficientShares(msg) => write!(f, "not enough shares: {}", msg),
+            SssError::Kdf(msg) => write!(f, "key derivation failed: {}", msg),
+            #[cfg(feature = "std")]
+            SssError::Aead => f.write_str(&crate::tr!("error-aead")),
+            #[cfg(not(feature = "std"))]
+            SssError::Aead => {
+                f.write_str("decryption failed: wrong password, too few shares, or a share from another set")
+            }
+            #[cfg(feature = "std")]
+            SssError::WrongPassword => f.write_str(&crate::tr!("error-wrong-password")),
+            #[cfg(not(feature = "std"))]
+            SssError::WrongPassword => {
+                f.write_str("password incorrect (AEAD authentication failed); the shares rebuild the expected set")
+            }
+            SssError::SecureMemory(msg) => write!(f, "secure memory unavailable: {}", msg),
+            SssError::Store(msg) => write!(f, "share store error: {}", msg),
+            SssError::Unauthorized(msg) => write!(f, "recovery not authorized: {}", msg),
+            SssError::Policy(msg) => write!(f, "share distribution violates policy: {}", msg),
+            SssError::Signing(msg) => write!(f, "threshold signing failed: {}", msg),
+        }
+    }
+}
+
+impl core::error::Error for SssError {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for SssError {
+    fn from(e: std::io::Error) -> Self {
+        SssError::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<InputError> for SssError {
+    fn from(e: InputError) -> Self {
+        SssError::Input(e)
+    }
+}
+
+impl From<aes_gcm::Error> for SssError {
+    fn from(_: aes_gcm::Error) -> Self {
+        SssError::Aead
+    }
+}
+
+pub type Result<T> = core::result::Result<T, SssError>;
@@ -0,0 +1,235 @@
+//! C bindings, declared in `include/sss.h` (regenerate it with
+//! `cbindgen --config cbindgen.toml --output include/sss.h`). Build the library to link against
+//! with `cargo rustc --release --lib --features ffi --crate-type cdylib` (or `staticlib`).
+//!
+//! Ownership: pointers passed in are only borrowed for the call. Every `SssBuffer` and
+//! `SssShares` the library fills belongs to the caller, who releases it with
+//! `sss_buffer_free` or `sss_shares_free`; both zero the bytes first and accept a value that
+//! was never filled. Functions return `SSS_OK` on success, otherwise one of the `SSS_ERR_*`
+//! codes with the message in `sss_last_error`. The codes are fixed for the C API and do not
+//! follow the exit codes of `sss`, which may change between releases.
+
+#![cfg(feature = "ffi")]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::crypto::PBKDF2_ROUNDS;
+use crate::error::{Result, SssError};
+use crate::pipeline;
+use crate::secret::{Padding, SecretType};
+
+/// Bytes owned by whoever the library handed them to.
+#[repr(C)]
+pub struct SssBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+/// `count` shares, each an `SssBuffer` starting with its index byte.
+#[repr(C)]
+pub struct SssShares {
+    pub shares: *mut SssBuffer,
+    pub count: usize,
+}
+
+/// The call succeeded.
+pub const SSS_OK: i32 = 0;
+/// Reading or writing failed.
+pub const SSS_ERR_IO: i32 = 1;
+/// An argument was invalid, such as a null pointer or a threshold above the share count.
+pub const SSS_ERR_INPUT: i32 = 2;
+/// A share or payload is malformed or failed its checksum.
+pub const SSS_ERR_PARSE: i32 = 3;
+/// The shares could not be combined: too few, or from different sets.
+pub const SSS_ERR_SHARE: i32 = 4;
+/// Deriving the key from the password failed.
+pub const SSS_ERR_KDF: i32 = 5;
+/// Decryption failed: a wrong password, or shares that do not rebuild the set.
+pub const SSS_ERR_DECRYPT: i32 = 6;
+/// Memory for the secret could not be locked.
+pub const SSS_ERR_SECURE_MEMORY: i32 = 7;
+/// A share store failed.
+pub const SSS_ERR_STORE: i32 = 8;
+/// Recovery was not authorized.
+pub const SSS_ERR_UNAUTHORIZED: i32 = 9;
+/// The request violates a policy, such as a time lock that has not opened.
+pub const SSS_ERR_POLICY: i32 = 10;
+/// Signing or checking a signature failed.
+pub const SSS_ERR_SIGNING: i32 = 11;
+/// The library panicked.
+pub const SSS_ERR_PANIC: i32 = -1;
+
+fn code(e: &SssError) -> i32 {
+    match e {
+        SssError::Io(_) => SSS_ERR_IO,
+        SssError::Input(_) | SssError::InvalidArgument(_) => SSS_ERR_INPUT,
+        SssError::Parse(_) | SssError::Checksum(_) => SSS_ERR_PARSE,
+        SssError::Share(_) | SssError::InsufficientShares(_) => SSS_ERR_SHARE,
+        SssError::Kdf(_) => SSS_ERR_KDF,
+        SssError::Aead | SssError::WrongPassword => SSS_ERR_DECRYPT,
+        SssError::SecureMemory(_) => SSS_ERR_SECURE_MEMORY,
+        SssError::Store(_) => SSS_ERR_STORE,
+        SssError::Unauthorized(_) => SSS_ERR_UNAUTHORIZED,
+        SssError::Policy(_) => SSS_ERR_POLICY,
+        SssError::Signing(_) => SSS_ERR_SIGNING,
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn run(f: impl FnOnce() -> Result<()>) -> i32 {
+    let (code, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (SSS_OK, None),
+        Ok(Err(e)) => (code(&e), Some(e.to_string())),
+        Err(_) => (SSS_ERR_PANIC, Some("internal error: the library panicked".to_string())),
+    };
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = message.map(|m| CString::new(m.replace('\0', " ")).unwrap_or_default());
+    });
+    code
+}
+
+fn buffer(bytes: Vec<u8>) -> SssBuffer {
+    let len = bytes.len();
+    SssBuffer { data: Box::into_raw(bytes.into_boxed_slice()).cast(), len }
+}
+
+unsafe fn borrowed<'a>(data: *const u8, len: usize, what: &str) -> Result<&'a [u8]> {
+    if data.is_null() {
+        return if len == 0 { Ok(&[]) } else { Err(SssError::InvalidArgument(format!("{} is a null pointer", what))) };
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+unsafe fn password<'a>(password: *const c_char) -> Result<&'a str> {
+    if password.is_null() {
+        return Err(SssError::InvalidArgument("the password is a null pointer".into()));
+    }
+    CStr::from_ptr(password).to_str().map_err(|_| SssError::InvalidArgument("the password is not UTF-8".into()))
+}
+
+/// Encrypts `secret` under `password` and splits it into `total` shares, any `threshold` of
+/// which recover it, written to `out`.
+///
+/// # Safety
+///
+/// `secret` must point to `secret_len` readable bytes, `password` to a NUL-terminated string,
+/// and `out` to writable memory for one `SssShares`.
+#[no_mangle]
+pub unsafe extern "C" fn sss_split(
+    secret: *const u8,
+    secret_len: usize,
+    password: *const c_char,
+    threshold: u8,
+    total: u8,
+    out: *mut SssShares,
+) -> i32 {
+    run(|| {
+        if out.is_null() {
+            return Err(SssError::InvalidArgument("out is a null pointer".into()));
+        }
+        let secret = SecretType::Bytes(borrowed(secret, secret_len, "secret")?.to_vec());
+        let password = self::password(password)?;
+        let shares = pipeline::split_secret(
+            &secret,
+            password,
+            threshold,
+            total,
+            Padding::default(),
+            PBKDF2_ROUNDS,
+            &mut rand::rngs::OsRng,
+        )?;
+        let shares: Box<[SssBuffer]> = shares.into_iter().map(buffer).collect();
+        let count = shares.len();
+        out.write(SssShares { shares: Box::into_raw(shares).cast(), count });
+        Ok(())
+    })
+}
+
+/// Recovers the secret from `count` shares and writes it to `out`: the bytes given to
+/// `sss_split`, or for secrets split by `sss encrypt` the text `sss decrypt` prints.
+///
+/// # Safety
+///
+/// `shares` must point to `count` buffers, each pointing to `len` readable bytes, `password`
+/// to a NUL-terminated string, and `out` to writable memory for one `SssBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn sss_combine(
+    shares: *const SssBuffer,
+    count: usize,
+    password: *const c_char,
+    out: *mut SssBuffer,
+) -> i32 {
+    run(|| {
+        if out.is_null() {
+            return Err(SssError::InvalidArgument("out is a null pointer".into()));
+        }
+        let shares = match count {
+            0 => &[],
+            _ if shares.is_null() => return Err(SssError::InvalidArgument("shares is a null pointer".into())),
+            _ => std::slice::from_raw_parts(shares, count),
+        };
+        let shares = shares
+            .iter()
+            .enumerate()
+            .map(|(i, s)| borrowed(s.data, s.len, &format!("share {}", i + 1)))
+            .collect::<Result<Vec<_>>>()?;
+        let secret = pipeline::combine_secret(&shares, self::password(password)?)?;
+        let bytes = match &secret {
+            SecretType::Bytes(bytes) => Zeroizing::new(bytes.clone()),
+            other => Zeroizing::new(pipeline::secret_text(other)?.as_bytes().to_vec()),
+        };
+        out.write(buffer(bytes.to_vec()));
+        Ok(())
+    })
+}
+
+/// Zeroes and frees a buffer filled by the library, leaving it empty.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to an `SssBuffer` that is empty or was filled by this
+/// library and not freed since.
+#[no_mangle]
+pub unsafe extern "C" fn sss_buffer_free(buffer: *mut SssBuffer) {
+    if let Some(buffer) = buffer.as_mut() {
+        if !buffer.data.is_null() {
+            let mut bytes = Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len));
+            bytes.zeroize();
+        }
+        buffer.data = std::ptr::null_mut();
+        buffer.len = 0;
+    }
+}
+
+/// Zeroes and frees every share and the list itself, leaving it empty.
+///
+/// # Safety
+///
+/// `shares` must be null or point to an `SssShares` that is empty or was filled by
+/// `sss_split` and not freed since.
+#[no_mangle]
+pub unsafe extern "C" fn sss_shares_free(shares: *mut SssShares) {
+    if let Some(shares) = shares.as_mut() {
+        if !shares.shares.is_null() {
+            let mut list = Box::from_raw(std::ptr::slice_from_raw_parts_mut(shares.shares, shares.count));
+            for share in list.iter_mut() {
+                sss_buffer_free(share);
+            }
+        }
+        shares.shares = std::ptr::null_mut();
+        shares.count = 0;
+    }
+}
+
+/// The message of the last failed call on this thread, or null. It stays valid until the next
+/// call into the library on the same thread.
+#[no_mangle]
+pub extern "C" fn sss_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |m| m.as_ptr()))
+}
@@ -0,0 +1,374 @@
+use alloc::{format, string::String, vec::Vec};
+use rand::RngCore;
+use sha2::{Sha256, Digest};
+
+use crate::crypto::{KDF_HEADER_LEN, NONCE_LEN, SALT_LEN, TAG_LEN};
+use crate::error::{Result, SssError};
+#[cfg(feature = "std")]
+use {
+    crate::reed_solomon,
+    base64::{engine::general_purpose::STANDARD as BASE64, Engine},
+    bech32::{Bech32m, Hrp},
+    std::fmt,
+    std::str::FromStr,
+};
+
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Short identifier shared by every share of one split, derived from the salt, nonce and
+/// ciphertext that the shares encode. It reveals nothing about the plaintext.
+pub fn set_fingerprint(combined_data: &[u8]) -> String {
+    let hash = Sha256::digest(combined_data);
+    hex::encode(&hash[..8])
+}
+
+/// Bytes of share overhead on top of the padded plaintext: the share index, KDF header, salt,
+/// nonce and GCM tag.
+pub const SHARE_OVERHEAD: usize = 1 + KDF_HEADER_LEN + SALT_LEN + NONCE_LEN + TAG_LEN;
+
+/// Plaintext length that makes every share `share_size` bytes when `conditions_len` bytes of
+/// conditions (time lock, switch and binding) ride in front of the payload and the ciphertext
+/// fills `slots` slots of one size (two for a GF(256) password set, see
+/// [`crate::crypto::encrypt_with_decoy_with_rng`]), so sets split with the same bucket are
+/// indistinguishable by size. Two slots can fall a byte short, which the payload makes up
+/// with a random byte at its end.
+pub fn plaintext_len_for_share_size(share_size: usize, conditions_len: usize, slots: usize) -> Result<usize> {
+    let overhead = 1 + KDF_HEADER_LEN + SALT_LEN + conditions_len + slots * (NONCE_LEN + TAG_LEN);
+    share_size.checked_sub(overhead).map(|n| n / slots).filter(|&n| n > 0).ok_or_else(|| {
+        SssError::InvalidArgument(format!("share size must be larger than {} bytes", overhead + slots - 1))
+    })
+}
+
+const BINDING_MAGIC: &[u8; 4] = b"sid1";
+pub const SET_ID_LEN: usize = 16;
+
+/// What a split ties its payload to: a random ID drawn for the set, and the threshold and
+/// format version it was made with. The binding rides in front of the payload as `sid1` ‖ ID ‖
+/// threshold ‖ version, so every share carries it, and the ciphertext authenticates it like
+/// a time lock: a payload rebuilt from shares of different sets, or one whose binding was
+/// edited, fails to decrypt instead of opening as something else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBinding {
+    pub id: [u8; SET_ID_LEN],
+    pub threshold: u8,
+    pub version: u8,
+}
+
+impl SetBinding {
+    /// Bytes the binding takes in front of the payload.
+    pub const LEN: usize = BINDING_MAGIC.len() + SET_ID_LEN + 2;
+
+    /// A binding with a fresh set ID, for a set of `threshold` shares.
+    pub fn new<R: RngCore + ?Sized>(threshold: u8, rng: &mut R) -> Self {
+        let mut id = [0u8; SET_ID_LEN];
+        rng.fill_bytes(&mut id);
+        SetBinding { id, threshold, version: FORMAT_VERSION }
+    }
+
+    pub fn header(&self) -> Vec<u8> {
+        let mut header = BINDING_MAGIC.to_vec();
+        header.extend_from_slice(&self.id);
+        header.extend_from_slice(&[self.threshold, self.version]);
+        header
+    }
+
+    /// The binding in front of `payload` and the payload after it.
+    pub fn split(payload: &[u8]) -> Option<(SetBinding, &[u8])> {
+        let rest = payload.strip_prefix(BINDING_MAGIC)?;
+        let id = rest.get(..SET_ID_LEN)?.try_into().ok()?;
+        let [threshold, version] = rest.get(SET_ID_LEN..SET_ID_LEN + 2)?.try_into().ok()?;
+        Some((SetBinding { id, threshold, version }, &rest[SET_ID_LEN + 2..]))
+    }
+
+    /// The set ID as hex.
+    pub fn set_id(&self) -> String {
+        hex::encode(self.id)
+    }
+
+    /// Refuses a binding made by a newer format than this build reads.
+    pub fn check(&self) -> Result<()> {
+        match self.version {
+            1..=FORMAT_VERSION => Ok(()),
+            version => Err(SssError::Parse(format!(
+                "set {} was split with share format version {}, which this version of sss does not read",
+                self.set_id(),
+                version
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// How shares are written and read on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShareFormat {
+    /// This tool's encrypted shares, printed as byte lists.
+    #[default]
+    Sss,
+    /// `index-hex` lines interchangeable with ssss-split and ssss-combine.
+    Ssss,
+}
+
+#[cfg(feature = "std")]
+impl FromStr for ShareFormat {
+    type Err = SssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "sss" => Ok(ShareFormat::Sss),
+            "ssss" => Ok(ShareFormat::Ssss),
+            _ => Err(SssError::InvalidArgument(format!("unknown share format '{}': use sss or ssss", s))),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Human-readable part of bech32 shares, which therefore start with `sss1`.
+pub const BECH32_HRP: &str = "sss";
+
+#[cfg(feature = "std")]
+/// How shares are printed. `decrypt` recognises every encoding on its own: byte lists by
+/// their brackets, bech32 by its `sss1` prefix and checksum, base64 and base58 by their
+/// `base64:` and `base58:` prefixes, armor by its BEGIN line, and anything else is read as hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShareEncoding {
+    /// `[1, 2, 3]`, as the tool has always printed shares.
+    #[default]
+    List,
+    Hex,
+    Base64,
+    Base58,
+    /// Bech32m with the `sss` prefix; its checksum catches typos before recovery starts.
+    Bech32,
+    /// A `ur:crypto-sss` Uniform Resource, for wallets and apps that speak BC-UR.
+    Ur,
+    /// A `-----BEGIN SSS SHARE-----` block with the share's headers inside; see [`crate::armor`].
+    Armor,
+}
+
+#[cfg(feature = "std")]
+impl FromStr for ShareEncoding {
+    type Err = SssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "list" => Ok(ShareEncoding::List),
+            "hex" => Ok(ShareEncoding::Hex),
+            "base64" => Ok(ShareEncoding::Base64),
+            "base58" => Ok(ShareEncoding::Base58),
+            "bech32" => Ok(ShareEncoding::Bech32),
+            "ur" => Ok(ShareEncoding::Ur),
+            "armor" => Ok(ShareEncoding::Armor),
+            _ => Err(SssError::InvalidArgument(format!(
+                "unknown share encoding '{}': use list, hex, base64, base58, bech32, ur or armor",
+                s
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ShareEncoding {
+    pub fn encode(self, share: &[u8]) -> Result<String> {
+        Ok(match self {
+            ShareEncoding::List => format!("{:?}", share),
+            ShareEncoding::Hex => hex::encode(share),
+            ShareEncoding::Base64 => format!("base64:{}", BASE64.encode(share)),
+            ShareEncoding::Base58 => format!("base58:{}", bs58::encode(share).into_string()),
+            ShareEncoding::Bech32 => bech32::encode::<Bech32m>(Hrp::parse_unchecked(BECH32_HRP), share)
+                .map_err(|_| SssError::Parse("share is too long for bech32; use another encoding".into()))?,
+            ShareEncoding::Ur => crate::bcur::encode_share(share),
+            ShareEncoding::Armor => crate::armor::encode(share),
+        })
+    }
+
+    /// Encodes the share with `parity` Reed-Solomon bytes per block as `rs<N>:<encoded>`.
+    /// Bech32, UR and armor are left out: their checksums would reject the typos the parity is
+    /// there to fix.
+    pub fn encode_with_parity(self, share: &[u8], parity: Option<usize>) -> Result<String> {
+        match parity {
+            None => self.encode(share),
+            Some(_) if matches!(self, ShareEncoding::Bech32 | ShareEncoding::Ur | ShareEncoding::Armor) => {
+                Err(SssError::InvalidArgument(
+                    "parity cannot be added to bech32, ur or armor shares; pick another encoding".into(),
+                ))
+            }
+            Some(parity) => Ok(format!("rs{}:{}", parity, self.encode(&reed_solomon::encode(share, parity)?)?)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// The share a sub-share was split from, written as `Parent: <share id> <k> of <n>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parent {
+    /// [`crate::audit::share_id`] of the parent share, which the rebuilt share must match.
+    pub share: String,
+    /// Sub-shares needed to rebuild the parent, and how many were made.
+    pub quorum: (u8, u8),
+}
+
+#[cfg(feature = "std")]
+/// Who a share is meant for, written as `Label:` and `Note:` lines above the share itself so
+/// a share found in a drawer can be traced back to its holder, and which split it came from as
+/// `Set:` and `Quorum:` lines. Every reader skips the lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShareHeader {
+    pub label: Option<String>,
+    pub note: Option<String>,
+    /// Fingerprint of the share set, so shares of different splits are told apart on entry.
+    pub set: Option<String>,
+    /// Threshold and total of the set, so too few shares are caught before decrypting.
+    pub quorum: Option<(u8, u8)>,
+    /// For a sub-share, the shares it was split from, nearest first.
+    pub parents: Vec<Parent>,
+}
+
+#[cfg(feature = "std")]
+impl ShareHeader {
+    pub fn is_empty(&self) -> bool {
+        self.label.is_none()
+            && self.note.is_none()
+            && self.set.is_none()
+            && self.quorum.is_none()
+            && self.parents.is_empty()
+    }
+
+    /// `share_text` with the header lines above it. Line breaks in a value become spaces.
+    pub fn prepend(&self, share_text: &str) -> String {
+        let mut out = String::new();
+        let quorum = self.quorum.map(|(threshold, total)| format!("{} of {}", threshold, total));
+        let fields = [("Label", &self.label), ("Note", &self.note), ("Set", &self.set), ("Quorum", &quorum)];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                out.push_str(&format!("{}: {}\n", key, value.split_whitespace().collect::<Vec<_>>().join(" ")));
+            }
+        }
+        for parent in &self.parents {
+            out.push_str(&format!("Parent: {} {} of {}\n", parent.share, parent.quorum.0, parent.quorum.1));
+        }
+        // an armored share carries the lines as armor headers, inside the block
+        match share_text.strip_prefix(crate::armor::BEGIN) {
+            Some(block) => format!("{}\n{}{}", crate::armor::BEGIN, out, block.trim_start_matches('\n')),
+            None => out + share_text,
+        }
+    }
+
+    /// Takes in one header line, returning whether it was one.
+    fn read_line(&mut self, line: &str) -> bool {
+        let value = |key: &str| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case(key).then(|| value.trim().to_string())
+        };
+        if let Some(label) = value("label") {
+            self.label = Some(label);
+        } else if let Some(note) = value("note") {
+            self.note = Some(note);
+        } else if let Some(set) = value("set") {
+            self.set = Some(set);
+        } else if let Some(quorum) = value("quorum") {
+            let (threshold, total) = quorum.split_once(" of ").unwrap_or_default();
+            self.quorum = threshold.trim().parse().ok().zip(total.trim().parse().ok());
+        } else if let Some(parent) = value("parent") {
+            let (share, quorum) = parent.split_once(' ').unwrap_or_default();
+            let (threshold, total) = quorum.split_once(" of ").unwrap_or_default();
+            if let Some(quorum) = threshold.trim().parse().ok().zip(total.trim().parse().ok()) {
+                self.parents.push(Parent { share: share.to_string(), quorum });
+            }
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Splits any header lines, and blank lines among them, off the front of a share's text.
+    /// An armored share keeps its block whole, with the headers read from inside it.
+    pub fn split(text: &str) -> (ShareHeader, &str) {
+        let mut header = ShareHeader::default();
+        let mut rest = text;
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if trimmed == crate::armor::BEGIN {
+                for line in crate::armor::headers(rest) {
+                    header.read_line(line.trim());
+                }
+                break;
+            }
+            if !header.read_line(trimmed) && !trimmed.is_empty() {
+                break;
+            }
+            rest = &rest[line.len()..];
+        }
+        (header, rest)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ShareHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.label, &self.note) {
+            (Some(label), Some(note)) => write!(f, "'{}' ({})", label, note),
+            (Some(label), None) => write!(f, "'{}'", label),
+            (None, Some(note)) => write!(f, "({})", note),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+pub fn share_index(share: &[u8]) -> Option<u8> {
+    share.first().copied()
+}
+
+/// Structural checks on decoded shares before they are handed to `reconstruct`.
+pub fn validate_shares(shares: &[Vec<u8>]) -> Result<()> {
+    let first = shares
+        .first()
+        .ok_or_else(|| SssError::Parse("no shares were entered".into()))?;
+    for (i, share) in shares.iter().enumerate() {
+        if share.len() < 2 {
+            return Err(SssError::Parse(format!("share #{} is only {} byte(s) long", i + 1, share.len())));
+        }
+        if share[0] == 0 {
+            return Err(SssError::Parse(format!("share #{} has index 0, which is never issued", i + 1)));
+        }
+        if share.len() != first.len() {
+            return Err(SssError::Parse(format!(
+                "share #{} is {} bytes but share #1 is {} bytes",
+                i + 1,
+                share.len(),
+                first.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Why `share` does not belong with the shares already `entered` (each with the set its text
+/// named, if any), or `None` if it may. Shares are numbered from 1 in the order entered.
+pub fn share_conflict(entered: &[(Vec<u8>, Option<String>)], share: &[u8], set: Option<&str>) -> Option<String> {
+    for (i, (other, other_set)) in entered.iter().enumerate() {
+        if other.as_slice() == share {
+            return Some(format!("it is the same share as share #{}", i + 1));
+        }
+        if other.first() == share.first() {
+            return Some(format!(
+                "it has index {} like share #{} but different contents, so the two come from different splits",
+                share[0],
+                i + 1
+            ));
+        }
+        if let (Some(set), Some(other_set)) = (set, other_set) {
+            if !set.eq_ignore_ascii_case(other_set) {
+                return Some(format!("it belongs to set {} but share #{} belongs to set {}", set, i + 1, other_set));
+            }
+        }
+    }
+    match entered.first() {
+        Some((first, _)) if first.len() != share.len() => Some(format!(
+            "it is {} bytes but share #1 is {} bytes, so it comes from a different split",
+            share.len(),
+            first.len()
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,258 @@
+//! Fountain-coded QR frames, for shares and files too big for one QR code. The message is cut
+//! into equal fragments; frames `1..=count` carry one fragment each and every later frame the
+//! XOR of a pseudo-random mix of them, so a camera that misses frames of a looping animation
+//! still finishes after about `count` good reads.
+//!
+//! A frame is `SSSF:<seq>/<count>:<length>:<checksum>:<data>`, all uppercase so QR codes store
+//! it in alphanumeric mode. The checksum is the first 4 bytes of SHA-256 of the message; it
+//! names the message and is checked once the message is rebuilt.
+
+use rand::{seq::index, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+pub const FRAME_PREFIX: &str = "SSSF:";
+/// Message bytes per frame: about a version 13 QR code at medium error correction.
+pub const DEFAULT_FRAGMENT_LEN: usize = 200;
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+pub fn is_frame(text: &str) -> bool {
+    text.trim().get(..FRAME_PREFIX.len()).is_some_and(|p| p.eq_ignore_ascii_case(FRAME_PREFIX))
+}
+
+fn checksum(message: &[u8]) -> [u8; 4] {
+    Sha256::digest(message)[..4].try_into().expect("4 bytes")
+}
+
+/// The fragments frame `seq` mixes.
+fn fragments_of(seq: u32, count: usize, checksum: [u8; 4]) -> Vec<usize> {
+    if seq as usize <= count {
+        return vec![seq as usize - 1];
+    }
+    let mut seed = Sha256::new();
+    seed.update(checksum);
+    seed.update(seq.to_be_bytes());
+    let mut rng = ChaCha20Rng::from_seed(seed.finalize().into());
+    // ideal soliton: degree 1 with weight 1/count, degree d with weight 1/(d(d-1))
+    let mut pick = rng.gen::<f64>();
+    let mut degree = count;
+    for d in 1..=count {
+        let weight = if d == 1 { 1.0 / count as f64 } else { 1.0 / (d * (d - 1)) as f64 };
+        if pick < weight {
+            degree = d;
+            break;
+        }
+        pick -= weight;
+    }
+    index::sample(&mut rng, count, degree).into_vec()
+}
+
+fn xor_into(target: &mut [u8], other: &[u8]) {
+    target.iter_mut().zip(other).for_each(|(t, o)| *t ^= o);
+}
+
+/// Produces the frames of one message.
+pub struct Encoder {
+    fragments: Vec<Zeroizing<Vec<u8>>>,
+    len: usize,
+    checksum: [u8; 4],
+}
+
+impl Encoder {
+    pub fn new(message: &[u8], fragment_len: usize) -> Result<Self> {
+        if message.is_empty() || message.len() > MAX_MESSAGE_LEN {
+            return Err(SssError::Parse(format!("a fountain-coded message is 1 to {} bytes", MAX_MESSAGE_LEN)));
+        }
+        if fragment_len == 0 {
+            return Err(SssError::Parse("frames must carry at least one byte".into()));
+        }
+        let count = message.len().div_ceil(fragment_len);
+        // equal fragments, as short as the count allows, so the last one is not mostly padding
+        let fragment_len = message.len().div_ceil(count);
+        let fragments = message
+            .chunks(fragment_len)
+            .map(|chunk| {
+                let mut fragment = Zeroizing::new(chunk.to_vec());
+                fragment.resize(fragment_len, 0);
+                fragment
+            })
+            .collect();
+        Ok(Encoder { fragments, len: message.len(), checksum: checksum(message) })
+    }
+
+    /// How many frames the shortest complete sequence has.
+    pub fn fragment_count(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Frame `seq`, counting from 1; frames past `fragment_count` can go on forever.
+    pub fn frame(&self, seq: u32) -> Zeroizing<String> {
+        let seq = seq.max(1);
+        let mut data = Zeroizing::new(vec![0u8; self.fragments[0].len()]);
+        for i in fragments_of(seq, self.fragments.len(), self.checksum) {
+            xor_into(&mut data, &self.fragments[i]);
+        }
+        Zeroizing::new(format!(
+            "{}{}/{}:{}:{}:{}",
+            FRAME_PREFIX,
+            seq,
+            self.fragments.len(),
+            self.len,
+            hex::encode_upper(self.checksum),
+            hex::encode_upper(data.as_slice())
+        ))
+    }
+}
+
+/// One parsed frame.
+pub struct Frame {
+    pub seq: u32,
+    pub count: usize,
+    pub len: usize,
+    pub checksum: [u8; 4],
+    pub data: Zeroizing<Vec<u8>>,
+}
+
+impl Frame {
+    pub fn parse(text: &str) -> Result<Self> {
+        let malformed = |what: &str| SssError::Parse(format!("malformed fountain frame: {}", what));
+        let text = text.trim();
+        if !is_frame(text) {
+            return Err(malformed("no SSSF: prefix"));
+        }
+        let mut fields = text[FRAME_PREFIX.len()..].split(':');
+        let (Some(position), Some(len), Some(checksum), Some(data), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(malformed("it does not have five fields"));
+        };
+        let (seq, count) = position.split_once('/').ok_or_else(|| malformed("no sequence number"))?;
+        let seq: u32 = seq.parse().ok().filter(|&s| s > 0).ok_or_else(|| malformed("bad sequence number"))?;
+        let count: usize = count.parse().ok().filter(|&c| c > 0).ok_or_else(|| malformed("bad fragment count"))?;
+        let len: usize = len.parse().ok().filter(|&l| l <= MAX_MESSAGE_LEN).ok_or_else(|| malformed("bad length"))?;
+        let checksum = hex::decode(checksum)
+            .ok()
+            .and_then(|c| <[u8; 4]>::try_from(c).ok())
+            .ok_or_else(|| malformed("bad checksum"))?;
+        let data = Zeroizing::new(hex::decode(data).map_err(|_| malformed("the data is not hex"))?);
+        if data.is_empty() || count != len.div_ceil(data.len()) {
+            return Err(malformed("the length, count and data size disagree"));
+        }
+        Ok(Frame { seq, count, len, checksum, data })
+    }
+}
+
+/// Rebuilds a message from frames received in any order, with gaps and repeats.
+pub struct Decoder {
+    count: usize,
+    len: usize,
+    fragment_len: usize,
+    checksum: [u8; 4],
+    known: Vec<Option<Zeroizing<Vec<u8>>>>,
+    mixed: Vec<(Vec<usize>, Zeroizing<Vec<u8>>)>,
+}
+
+impl Decoder {
+    /// A decoder for the message `first` belongs to, already holding it.
+    pub fn new(first: Frame) -> Self {
+        let mut decoder = Decoder {
+            count: first.count,
+            len: first.len,
+            fragment_len: first.data.len(),
+            checksum: first.checksum,
+            known: vec![None; first.count],
+            mixed: Vec::new(),
+        };
+        decoder.receive(first).expect("the frame this decoder was made for fits it");
+        decoder
+    }
+
+    /// The checksum naming the message.
+    pub fn checksum(&self) -> [u8; 4] {
+        self.checksum
+    }
+
+    pub fn receive(&mut self, frame: Frame) -> Result<()> {
+        if (frame.count, frame.len, frame.data.len(), frame.checksum)
+            != (self.count, self.len, self.fragment_len, self.checksum)
+        {
+            return Err(SssError::Parse("the frame belongs to a different message".into()));
+        }
+        let mut data = frame.data;
+        let mut fragments = fragments_of(frame.seq, self.count, self.checksum);
+        fragments.retain(|&i| match &self.known[i] {
+            Some(known) => {
+                xor_into(&mut data, known);
+                false
+            }
+            None => true,
+        });
+        match fragments.as_slice() {
+            [] => {}
+            [i] => self.learn(*i, data),
+            _ => {
+                fragments.sort_unstable();
+                if !self.mixed.iter().any(|(mix, _)| *mix == fragments) {
+                    self.mixed.push((fragments, data));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records fragment `i` and peels it out of every mix, learning whatever that frees.
+    fn learn(&mut self, i: usize, data: Zeroizing<Vec<u8>>) {
+        let mut queue = vec![(i, data)];
+        while let Some((i, data)) = queue.pop() {
+            if self.known[i].is_some() {
+                continue;
+            }
+            for (mix, mixed) in self.mixed.iter_mut() {
+                if let Some(at) = mix.iter().position(|&j| j == i) {
+                    mix.remove(at);
+                    xor_into(mixed, &data);
+                }
+            }
+            self.known[i] = Some(data);
+            let (freed, rest): (Vec<_>, Vec<_>) =
+                std::mem::take(&mut self.mixed).into_iter().partition(|(mix, _)| mix.len() <= 1);
+            self.mixed = rest;
+            queue.extend(freed.into_iter().filter_map(|(mix, data)| Some((*mix.first()?, data))));
+        }
+    }
+
+    /// Fragments recovered so far, and how many there are.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.known.iter().filter(|k| k.is_some()).count(), self.count)
+    }
+
+    /// The message, once every fragment is in.
+    pub fn message(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        if self.known.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+        let mut message = Zeroizing::new(Vec::with_capacity(self.count * self.fragment_len));
+        for fragment in self.known.iter().flatten() {
+            message.extend_from_slice(fragment);
+        }
+        message.truncate(self.len);
+        if checksum(&message) != self.checksum {
+            return Err(SssError::Checksum("the rebuilt message does not match its checksum".into()));
+        }
+        Ok(Some(message))
+    }
+}
+
+/// Renders text as a QR code for the terminal.
+pub fn terminal_qr(text: &str) -> Result<String> {
+    let code = qrcode::QrCode::new(text.as_bytes())
+        .map_err(|e| SssError::Parse(format!("frame does not fit in a QR code: {}", e)))?;
+    Ok(code
+        .render::<qrcode::render::unicode::Dense1x2>()
+        .dark_color(qrcode::render::unicode::Dense1x2::Light)
+        .light_color(qrcode::render::unicode::Dense1x2::Dark)
+        .build())
+}
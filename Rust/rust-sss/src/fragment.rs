@@ -0,0 +1,220 @@
+//! Erasure-coded copies of one share, for a custodian who keeps it on several devices. The
+//! share is cut into `needed` data fragments plus spare fragments, any `needed` of which
+//! rebuild it, so losing a USB stick costs a fragment rather than the whole share.
+//!
+//! Byte b of fragment x is the value at x of the polynomial of degree below `needed` through
+//! byte b of the data fragments at x = 1..=needed, over the same GF(2^8) as the sharing.
+//! A manifest listing every fragment is written next to each one; `decrypt` takes a manifest
+//! (`@share.fragments.json`) wherever it takes a share file and gathers the fragments itself.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::manifest::share_fingerprint;
+use crate::shamir::{inv, mul, REDUCTION};
+
+pub const MANIFEST_SUFFIX: &str = ".fragments.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub format_version: u8,
+    /// Hex SHA-256 of the whole share, which the rebuilt share must match.
+    pub share_fingerprint: String,
+    pub share_len: usize,
+    pub index: u8,
+    pub needed: u8,
+    pub total: u8,
+    #[serde(with = "hex_bytes")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FragmentEntry {
+    pub index: u8,
+    /// Where the fragment was written; the manifest's own directory is searched as well.
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FragmentManifest {
+    pub format_version: u8,
+    pub share_fingerprint: String,
+    pub needed: u8,
+    pub total: u8,
+    pub fragments: Vec<FragmentEntry>,
+}
+
+pub(crate) mod hex_bytes {
+    pub fn serialize<S: serde::Serializer>(bytes: &[u8], s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Vec<u8>, D::Error> {
+        let text = <String as serde::Deserialize>::deserialize(d)?;
+        hex::decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Value at `x` of the polynomial through `points`.
+fn interpolate(points: &[(u8, u8)], x: u8) -> u8 {
+    let mul = |a, b| mul(REDUCTION, a, b);
+    points.iter().fold(0, |sum, &(xi, yi)| {
+        let (num, den) = points
+            .iter()
+            .filter(|&&(xj, _)| xj != xi)
+            .fold((1u8, 1u8), |(num, den), &(xj, _)| (mul(num, x ^ xj), mul(den, xi ^ xj)));
+        sum ^ mul(yi, mul(num, inv(REDUCTION, den)))
+    })
+}
+
+pub fn split(share: &[u8], needed: u8, total: u8) -> Result<Vec<Fragment>> {
+    if needed == 0 || needed > total {
+        return Err(SssError::Share(format!("cannot rebuild from {} of {} fragments", needed, total)));
+    }
+    if share.is_empty() {
+        return Err(SssError::Share("the share is empty".into()));
+    }
+    let len = share.len().div_ceil(needed as usize);
+    let mut stripes: Vec<Vec<u8>> = share.chunks(len).map(|c| c.to_vec()).collect();
+    stripes.resize(needed as usize, Vec::new());
+    stripes.iter_mut().for_each(|s| s.resize(len, 0));
+
+    let fingerprint = share_fingerprint(share);
+    Ok((1..=total)
+        .map(|x| Fragment {
+            format_version: FORMAT_VERSION,
+            share_fingerprint: fingerprint.clone(),
+            share_len: share.len(),
+            index: x,
+            needed,
+            total,
+            data: if x <= needed {
+                stripes[x as usize - 1].clone()
+            } else {
+                (0..len)
+                    .map(|b| {
+                        let points: Vec<(u8, u8)> = (1..=needed).map(|j| (j, stripes[j as usize - 1][b])).collect();
+                        interpolate(&points, x)
+                    })
+                    .collect()
+            },
+        })
+        .collect())
+}
+
+/// Rebuilds the share from any `needed` fragments of it, checking it against its fingerprint.
+pub fn join(fragments: &[Fragment]) -> Result<Vec<u8>> {
+    let first = fragments.first().ok_or_else(|| SssError::Share("no fragments were found".into()))?;
+    let mut chosen: Vec<&Fragment> = Vec::new();
+    for fragment in fragments {
+        if (&fragment.share_fingerprint, fragment.needed, fragment.total, fragment.data.len())
+            != (&first.share_fingerprint, first.needed, first.total, first.data.len())
+        {
+            return Err(SssError::Share(format!("fragment #{} belongs to another share", fragment.index)));
+        }
+        if fragment.index == 0 || fragment.index > fragment.total {
+            return Err(SssError::Parse(format!("fragment index {} is out of range", fragment.index)));
+        }
+        if !chosen.iter().any(|c| c.index == fragment.index) && chosen.len() < first.needed as usize {
+            chosen.push(fragment);
+        }
+    }
+    if chosen.len() < first.needed as usize {
+        return Err(SssError::InsufficientShares(format!(
+            "{} different fragments found but {} are needed",
+            chosen.len(),
+            first.needed
+        )));
+    }
+
+    let mut share = Vec::with_capacity(first.data.len() * first.needed as usize);
+    for x in 1..=first.needed {
+        match chosen.iter().find(|f| f.index == x) {
+            Some(fragment) => share.extend_from_slice(&fragment.data),
+            None => share.extend((0..first.data.len()).map(|b| {
+                let points: Vec<(u8, u8)> = chosen.iter().map(|f| (f.index, f.data[b])).collect();
+                interpolate(&points, x)
+            })),
+        }
+    }
+    share.truncate(first.share_len);
+    if share_fingerprint(&share) != first.share_fingerprint {
+        return Err(SssError::Checksum("the rebuilt share does not match its fingerprint; a fragment is damaged".into()));
+    }
+    Ok(share)
+}
+
+/// Writes fragment i to the i-th location (cycling when there are fewer locations than
+/// fragments) and a copy of the manifest to every location.
+pub fn write(share: &[u8], needed: u8, total: u8, locations: &[PathBuf], stem: &str) -> Result<Vec<PathBuf>> {
+    if locations.is_empty() {
+        return Err(SssError::InvalidArgument("give at least one location for the fragments".into()));
+    }
+    let fragments = split(share, needed, total)?;
+    let mut written = Vec::new();
+    let mut entries = Vec::new();
+    for (i, fragment) in fragments.iter().enumerate() {
+        let dir = &locations[i % locations.len()];
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.frag-{}-of-{}.json", stem, fragment.index, total));
+        let json = serde_json::to_string_pretty(fragment).map_err(|e| SssError::Parse(e.to_string()))?;
+        crate::secure::write_private(&path, json.as_bytes())?;
+        entries.push(FragmentEntry { index: fragment.index, path: path.display().to_string() });
+        written.push(path);
+    }
+    let manifest = FragmentManifest {
+        format_version: FORMAT_VERSION,
+        share_fingerprint: share_fingerprint(share),
+        needed,
+        total,
+        fragments: entries,
+    };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| SssError::Parse(e.to_string()))?;
+    for dir in locations {
+        let path = dir.join(format!("{}{}", stem, MANIFEST_SUFFIX));
+        if !written.contains(&path) {
+            fs::write(&path, json.as_bytes())?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+pub fn is_manifest(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(MANIFEST_SUFFIX)
+}
+
+/// Reads a fragment manifest and rebuilds the share from whichever fragments can be found,
+/// at their recorded paths or beside the manifest. Returns the share and how many fragments
+/// were found.
+pub fn read(manifest_path: &Path) -> Result<(Vec<u8>, usize)> {
+    let text = fs::read_to_string(manifest_path)?;
+    let manifest: FragmentManifest = serde_json::from_str(&text)
+        .map_err(|e| SssError::Parse(format!("{}: {}", manifest_path.display(), e)))?;
+    let beside = manifest_path.parent().unwrap_or(Path::new("."));
+    let mut fragments = Vec::new();
+    for entry in &manifest.fragments {
+        let recorded = PathBuf::from(&entry.path);
+        let candidates = [Some(recorded.clone()), recorded.file_name().map(|name| beside.join(name))];
+        let found = candidates
+            .iter()
+            .flatten()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|text| serde_json::from_str::<Fragment>(&text).ok())
+            .find(|f| f.index == entry.index && f.share_fingerprint == manifest.share_fingerprint);
+        if let Some(fragment) = found {
+            fragments.push(fragment);
+        }
+    }
+    if fragments.len() < manifest.needed as usize {
+        return Err(SssError::InsufficientShares(format!(
+            "only {} of the {} fragments needed for this share were found",
+            fragments.len(),
+            manifest.needed
+        )));
+    }
+    Ok((join(&fragments)?, fragments.len()))
+}
@@ -0,0 +1,312 @@
+//! FROST threshold signing (RFC 9591) over Ed25519 or secp256k1. A dealer splits a key into
+//! key packages, and any quorum of holders then signs in two rounds without the private key
+//! ever being put back together.
+//!
+//! Every message is one line of text, `sss-frost-v1.<kind>.<suite>.<base64url>`, so it can be
+//! written to a file, pasted, or shown as a QR code.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL, Engine};
+use frost_core::keys::{KeyPackage, PublicKeyPackage, SecretShare};
+use frost_core::round1::{SigningCommitments, SigningNonces};
+use frost_core::round2::SignatureShare;
+use frost_core::{Ciphersuite, Identifier, SigningKey, SigningPackage};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+const PREFIX: &str = "sss-frost-v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suite {
+    Ed25519,
+    Secp256k1,
+}
+
+impl Suite {
+    fn name(self) -> &'static str {
+        match self {
+            Suite::Ed25519 => "ed25519",
+            Suite::Secp256k1 => "secp256k1",
+        }
+    }
+}
+
+impl FromStr for Suite {
+    type Err = SssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ed25519" => Ok(Suite::Ed25519),
+            "secp256k1" => Ok(Suite::Secp256k1),
+            _ => Err(SssError::InvalidArgument(format!("unknown curve '{}': use ed25519 or secp256k1", s))),
+        }
+    }
+}
+
+/// What a message line carries. Key packages and nonces are secret; the rest may be shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    KeyPackage,
+    PublicKeyPackage,
+    Nonces,
+    Commitments,
+    SignatureShare,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::KeyPackage => "key",
+            Kind::PublicKeyPackage => "public",
+            Kind::Nonces => "nonces",
+            Kind::Commitments => "commitments",
+            Kind::SignatureShare => "share",
+        }
+    }
+}
+
+fn encode(kind: Kind, suite: Suite, bytes: &[u8]) -> String {
+    format!("{}.{}.{}.{}", PREFIX, kind.name(), suite.name(), BASE64URL.encode(bytes))
+}
+
+/// Decodes a message line, checking it is of the expected kind.
+fn decode(text: &str, kind: Kind) -> Result<(Suite, Zeroizing<Vec<u8>>)> {
+    let malformed = || SssError::Parse(format!("not a FROST {} message", kind.name()));
+    let mut parts = text.trim().split('.');
+    if parts.next() != Some(PREFIX) {
+        return Err(malformed());
+    }
+    let found = parts.next().ok_or_else(malformed)?;
+    if found != kind.name() {
+        return Err(SssError::Parse(format!("expected a FROST {} message, got '{}'", kind.name(), found)));
+    }
+    let suite = parts.next().ok_or_else(malformed)?.parse()?;
+    let bytes = BASE64URL.decode(parts.next().ok_or_else(malformed)?).map_err(|_| malformed())?;
+    if parts.next().is_some() {
+        return Err(malformed());
+    }
+    Ok((suite, Zeroizing::new(bytes)))
+}
+
+fn same_suite(suite: Suite, messages: &[(Suite, Zeroizing<Vec<u8>>)]) -> Result<()> {
+    match messages.iter().find(|(s, _)| *s != suite) {
+        Some((other, _)) => Err(SssError::Signing(format!(
+            "mixed curves: expected {} messages, got {}",
+            suite.name(),
+            other.name()
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn signing_error<C: Ciphersuite>(e: frost_core::Error<C>) -> SssError {
+    SssError::Signing(e.to_string())
+}
+
+/// Prefixes a message body with the identifier of the participant who produced it.
+fn tag<C: Ciphersuite>(id: &Identifier<C>, body: &[u8]) -> Vec<u8> {
+    let id = id.serialize();
+    let mut bytes = Vec::with_capacity(1 + id.len() + body.len());
+    bytes.push(id.len() as u8);
+    bytes.extend_from_slice(&id);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+fn untag<C: Ciphersuite>(bytes: &[u8]) -> Result<(Identifier<C>, &[u8])> {
+    let len = *bytes.first().ok_or_else(|| SssError::Parse("empty FROST message".into()))? as usize;
+    if bytes.len() < 1 + len {
+        return Err(SssError::Parse("truncated FROST message".into()));
+    }
+    let id = Identifier::deserialize(&bytes[1..1 + len]).map_err(signing_error)?;
+    Ok((id, &bytes[1 + len..]))
+}
+
+macro_rules! dispatch {
+    ($suite:expr, $f:ident($($arg:expr),*)) => {
+        match $suite {
+            Suite::Ed25519 => $f::<frost_ed25519::Ed25519Sha512>($suite, $($arg),*),
+            Suite::Secp256k1 => $f::<frost_secp256k1::Secp256K1Sha256>($suite, $($arg),*),
+        }
+    };
+}
+
+/// Output of the dealer: one secret key package per holder, in identifier order, and the
+/// public package every signer and the aggregator use.
+pub struct Dealt {
+    pub key_packages: Vec<Zeroizing<String>>,
+    pub public_package: String,
+    pub group_key: Vec<u8>,
+}
+
+/// Deals a fresh key, or `existing` (a 32-byte Ed25519 seed or secp256k1 scalar), into
+/// `total` key packages with a signing threshold of `threshold`.
+pub fn keygen(suite: Suite, existing: Option<&[u8]>, threshold: u16, total: u16) -> Result<Dealt> {
+    let existing = match (suite, existing) {
+        (Suite::Ed25519, Some(seed)) => {
+            let seed: &[u8; 32] = seed
+                .try_into()
+                .map_err(|_| SssError::Parse("an Ed25519 seed is 32 bytes".into()))?;
+            // The signing scalar an Ed25519 key uses, so the group key is the key's public key
+            let scalar = ed25519_dalek::SigningKey::from_bytes(seed).to_scalar();
+            Some(Zeroizing::new(scalar.to_bytes().to_vec()))
+        }
+        (_, existing) => existing.map(|key| Zeroizing::new(key.to_vec())),
+    };
+    dispatch!(suite, keygen_with(existing.as_deref().map(Vec::as_slice), threshold, total))
+}
+
+fn keygen_with<C: Ciphersuite>(
+    suite: Suite,
+    existing: Option<&[u8]>,
+    threshold: u16,
+    total: u16,
+) -> Result<Dealt> {
+    let mut rng = rand::rngs::OsRng;
+    let key = match existing {
+        Some(bytes) => SigningKey::<C>::deserialize(bytes).map_err(signing_error)?,
+        None => SigningKey::<C>::new(&mut rng),
+    };
+    let (shares, public) = frost_core::keys::split(
+        &key,
+        total,
+        threshold,
+        frost_core::keys::IdentifierList::Default,
+        &mut rng,
+    )
+    .map_err(signing_error)?;
+    let key_packages = shares
+        .into_values()
+        .map(|share: SecretShare<C>| {
+            let package = KeyPackage::try_from(share).map_err(signing_error)?;
+            let bytes = Zeroizing::new(package.serialize().map_err(signing_error)?);
+            Ok(Zeroizing::new(encode(Kind::KeyPackage, suite, &bytes)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Dealt {
+        key_packages,
+        public_package: encode(Kind::PublicKeyPackage, suite, &public.serialize().map_err(signing_error)?),
+        group_key: public.verifying_key().serialize().map_err(signing_error)?,
+    })
+}
+
+/// Group public key held in a public key package.
+pub fn group_key(public_package: &str) -> Result<Vec<u8>> {
+    let (suite, bytes) = decode(public_package, Kind::PublicKeyPackage)?;
+    dispatch!(suite, group_key_with(&bytes))
+}
+
+fn group_key_with<C: Ciphersuite>(_: Suite, bytes: &[u8]) -> Result<Vec<u8>> {
+    let public = PublicKeyPackage::<C>::deserialize(bytes).map_err(signing_error)?;
+    public.verifying_key().serialize().map_err(signing_error)
+}
+
+/// Round one: fresh nonces, which the signer keeps secret and must use only once, and the
+/// commitments to them, which go to every other signer.
+pub fn round1(key_package: &str) -> Result<(Zeroizing<String>, String)> {
+    let (suite, bytes) = decode(key_package, Kind::KeyPackage)?;
+    dispatch!(suite, round1_with(&bytes))
+}
+
+fn round1_with<C: Ciphersuite>(suite: Suite, key_package: &[u8]) -> Result<(Zeroizing<String>, String)> {
+    let package = KeyPackage::<C>::deserialize(key_package).map_err(signing_error)?;
+    let (nonces, commitments) = frost_core::round1::commit(package.signing_share(), &mut rand::rngs::OsRng);
+    let nonces = Zeroizing::new(nonces.serialize().map_err(signing_error)?);
+    let commitments = commitments.serialize().map_err(signing_error)?;
+    Ok((
+        Zeroizing::new(encode(Kind::Nonces, suite, &nonces)),
+        encode(Kind::Commitments, suite, &tag(package.identifier(), &commitments)),
+    ))
+}
+
+fn signing_package<C: Ciphersuite>(
+    suite: Suite,
+    commitments: &[String],
+    message: &[u8],
+) -> Result<SigningPackage<C>> {
+    let decoded = commitments
+        .iter()
+        .map(|c| decode(c, Kind::Commitments))
+        .collect::<Result<Vec<_>>>()?;
+    same_suite(suite, &decoded)?;
+    let mut map = BTreeMap::new();
+    for (_, bytes) in &decoded {
+        let (id, body) = untag::<C>(bytes)?;
+        let commitment = SigningCommitments::<C>::deserialize(body).map_err(signing_error)?;
+        if map.insert(id, commitment).is_some() {
+            return Err(SssError::Signing("the same signer's commitments were given twice".into()));
+        }
+    }
+    Ok(SigningPackage::new(map, message))
+}
+
+/// Round two: this holder's share of the signature over `message`, given the commitments of
+/// every signer taking part (its own included).
+pub fn round2(key_package: &str, nonces: &str, commitments: &[String], message: &[u8]) -> Result<String> {
+    let (suite, key_bytes) = decode(key_package, Kind::KeyPackage)?;
+    let (nonce_suite, nonce_bytes) = decode(nonces, Kind::Nonces)?;
+    same_suite(suite, &[(nonce_suite, nonce_bytes.clone())])?;
+    dispatch!(suite, round2_with(&key_bytes, &nonce_bytes, commitments, message))
+}
+
+fn round2_with<C: Ciphersuite>(
+    suite: Suite,
+    key_package: &[u8],
+    nonces: &[u8],
+    commitments: &[String],
+    message: &[u8],
+) -> Result<String> {
+    let package = KeyPackage::<C>::deserialize(key_package).map_err(signing_error)?;
+    let nonces = SigningNonces::<C>::deserialize(nonces).map_err(signing_error)?;
+    let signing_package = signing_package::<C>(suite, commitments, message)?;
+    let share = frost_core::round2::sign(&signing_package, &nonces, &package).map_err(signing_error)?;
+    Ok(encode(Kind::SignatureShare, suite, &tag(package.identifier(), &share.serialize())))
+}
+
+/// Combines the signature shares into one signature and checks it against the group key.
+/// A share that does not verify is reported by its signer's identifier.
+pub fn aggregate(
+    public_package: &str,
+    commitments: &[String],
+    shares: &[String],
+    message: &[u8],
+) -> Result<Vec<u8>> {
+    let (suite, bytes) = decode(public_package, Kind::PublicKeyPackage)?;
+    dispatch!(suite, aggregate_with(&bytes, commitments, shares, message))
+}
+
+fn aggregate_with<C: Ciphersuite>(
+    suite: Suite,
+    public_package: &[u8],
+    commitments: &[String],
+    shares: &[String],
+    message: &[u8],
+) -> Result<Vec<u8>> {
+    let public = PublicKeyPackage::<C>::deserialize(public_package).map_err(signing_error)?;
+    let signing_package = signing_package::<C>(suite, commitments, message)?;
+    let decoded = shares
+        .iter()
+        .map(|s| decode(s, Kind::SignatureShare))
+        .collect::<Result<Vec<_>>>()?;
+    same_suite(suite, &decoded)?;
+    let mut map = BTreeMap::new();
+    for (_, bytes) in &decoded {
+        let (id, body) = untag::<C>(bytes)?;
+        map.insert(id, SignatureShare::<C>::deserialize(body).map_err(signing_error)?);
+    }
+    let signature = frost_core::aggregate(&signing_package, &map, &public).map_err(signing_error)?;
+    signature.serialize().map_err(signing_error)
+}
+
+/// Renders a message as a terminal QR code, for carrying it to an air-gapped signer.
+pub fn qr(message: &str) -> Result<String> {
+    let code = qrcode::QrCode::new(message.as_bytes())
+        .map_err(|e| SssError::Signing(format!("message does not fit in a QR code: {}", e)))?;
+    Ok(code
+        .render::<qrcode::render::unicode::Dense1x2>()
+        .dark_color(qrcode::render::unicode::Dense1x2::Light)
+        .light_color(qrcode::render::unicode::Dense1x2::Dark)
+        .build())
+}
@@ -0,0 +1,211 @@
+//! Files in the format of jesseduffield/horcrux. Each `.horcrux` file is a commented preamble,
+//! a JSON header carrying one Vault-style fragment of a random AES-256 key, and the file
+//! encrypted with that key in OFB mode with a zero IV. When every horcrux is needed, the
+//! ciphertext is dealt out byte by byte across the files instead of copied into each one.
+
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use aes::Aes256;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::shamir::vault;
+
+const HEADER_MARKER: &[u8] = b"-- HEADER --\n";
+const BODY_MARKER: &[u8] = b"-- BODY --\n";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Header {
+    pub original_filename: String,
+    pub timestamp: i64,
+    pub index: u8,
+    pub total: u8,
+    pub threshold: u8,
+    /// A Vault share of the key: the y bytes followed by x, base64 in the JSON.
+    #[serde(with = "base64_bytes")]
+    pub key_fragment: Vec<u8>,
+}
+
+mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(bytes: &[u8], s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(d)?;
+        BASE64.decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Horcrux {
+    pub header: Header,
+    pub body: Vec<u8>,
+}
+
+impl Horcrux {
+    /// `name_1_of_5.horcrux`, the name horcrux itself gives the file.
+    pub fn file_name(&self) -> String {
+        let name = &self.header.original_filename;
+        let stem = match name.rfind('.') {
+            Some(dot) if dot > 0 => &name[..dot],
+            _ => name,
+        };
+        format!("{}_{}_of_{}.horcrux", stem, self.header.index, self.header.total)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let h = &self.header;
+        let mut out = format!(
+            "# THIS FILE IS A HORCRUX.\n\
+             # THIS IS HORCRUX NUMBER {}.\n\
+             # IT IS ONE OF {} HORCRUXES THAT EACH CONTAIN PART OF AN ORIGINAL FILE.\n\
+             # THIS IS THE ORIGINAL FILE NAME: {}\n\
+             # IN ORDER TO RESURRECT THIS ORIGINAL FILE YOU MUST FIND THE OTHER {} HORCRUX(ES) AND THEN BIND THEM USING THE PROGRAM FOUND AT THE FOLLOWING URL\n\
+             # https://github.com/jesseduffield/horcrux\n\n",
+            h.index,
+            h.total,
+            h.original_filename,
+            h.threshold.saturating_sub(1)
+        )
+        .into_bytes();
+        out.extend_from_slice(HEADER_MARKER);
+        out.extend(serde_json::to_vec(h).map_err(|e| SssError::Parse(e.to_string()))?);
+        out.push(b'\n');
+        out.extend_from_slice(BODY_MARKER);
+        out.extend_from_slice(&self.body);
+        Ok(out)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Horcrux> {
+        let malformed = |what: &str| SssError::Parse(format!("not a horcrux file: {}", what));
+        let start = find(bytes, HEADER_MARKER)
+            .filter(|&at| at == 0 || bytes[at - 1] == b'\n')
+            .ok_or_else(|| malformed("no '-- HEADER --' line"))?
+            + HEADER_MARKER.len();
+        let rest = &bytes[start..];
+        let end = rest.iter().position(|&b| b == b'\n').ok_or_else(|| malformed("truncated header"))?;
+        let header: Header = serde_json::from_slice(&rest[..end])
+            .map_err(|e| malformed(&format!("header JSON: {}", e)))?;
+        let body = rest[end + 1..]
+            .strip_prefix(BODY_MARKER)
+            .ok_or_else(|| malformed("no '-- BODY --' line after the header"))?;
+        if header.index == 0 || header.index > header.total || header.threshold > header.total {
+            return Err(malformed("header has an impossible index or threshold"));
+        }
+        Ok(Horcrux { header, body: body.to_vec() })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// AES-256 in OFB mode with a zero IV; encryption and decryption are the same operation.
+fn ofb(key: &[u8], data: &mut [u8]) {
+    let cipher = Aes256::new(GenericArray::from_slice(key));
+    let mut block = GenericArray::from([0u8; 16]);
+    for chunk in data.chunks_mut(16) {
+        cipher.encrypt_block(&mut block);
+        chunk.iter_mut().zip(block.iter()).for_each(|(b, k)| *b ^= k);
+    }
+}
+
+/// Encrypts `data` under a fresh key and makes `total` horcruxes, any `threshold` of which
+/// bring it back.
+pub fn split(data: &[u8], original_filename: &str, timestamp: i64, threshold: u8, total: u8) -> Result<Vec<Horcrux>> {
+    if threshold < 2 {
+        return Err(SssError::Share("horcrux needs a threshold of at least 2".into()));
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    rand::rngs::OsRng.fill_bytes(key.as_mut_slice());
+    let fragments = vault::split(key.as_slice(), threshold, total)?;
+
+    let mut ciphertext = data.to_vec();
+    ofb(key.as_slice(), &mut ciphertext);
+    let distributed = threshold == total;
+
+    Ok(fragments
+        .iter()
+        .enumerate()
+        .map(|(i, fragment)| {
+            let body = if distributed {
+                ciphertext.iter().skip(i).step_by(total as usize).copied().collect()
+            } else {
+                ciphertext.clone()
+            };
+            let mut key_fragment = fragment[1..].to_vec();
+            key_fragment.push(fragment[0]);
+            Horcrux {
+                header: Header {
+                    original_filename: original_filename.to_string(),
+                    timestamp,
+                    index: i as u8 + 1,
+                    total,
+                    threshold,
+                    key_fragment,
+                },
+                body,
+            }
+        })
+        .collect())
+}
+
+/// Puts the original file back together from at least `threshold` horcruxes of one split,
+/// returning its name and contents.
+pub fn bind(horcruxes: &mut [Horcrux]) -> Result<(String, Zeroizing<Vec<u8>>)> {
+    let first = horcruxes
+        .first()
+        .map(|h| h.header.clone())
+        .ok_or_else(|| SssError::Share("no horcruxes to bind".into()))?;
+    for h in horcruxes.iter() {
+        let other = &h.header;
+        if (other.original_filename.as_str(), other.timestamp, other.total, other.threshold)
+            != (first.original_filename.as_str(), first.timestamp, first.total, first.threshold)
+        {
+            return Err(SssError::Share(format!(
+                "horcrux #{} belongs to a different split than horcrux #{}",
+                other.index, first.index
+            )));
+        }
+    }
+    horcruxes.sort_by_key(|h| h.header.index);
+    if horcruxes.windows(2).any(|w| w[0].header.index == w[1].header.index) {
+        return Err(SssError::Share("the same horcrux was given twice".into()));
+    }
+    if horcruxes.len() < first.threshold as usize {
+        return Err(SssError::InsufficientShares(format!(
+            "{} horcruxes given but {} are needed",
+            horcruxes.len(),
+            first.threshold
+        )));
+    }
+
+    let fragments = horcruxes
+        .iter()
+        .map(|h| match h.header.key_fragment.split_last() {
+            Some((&x, ys)) => Ok([&[x], ys].concat()),
+            None => Err(SssError::Share(format!("horcrux #{} has an empty key fragment", h.header.index))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let key = Zeroizing::new(vault::reconstruct(&fragments)?);
+    if key.len() != 32 {
+        return Err(SssError::Share("horcrux key fragments are not 33 bytes".into()));
+    }
+
+    let mut data = Zeroizing::new(if first.threshold == first.total {
+        let len: usize = horcruxes.iter().map(|h| h.body.len()).sum();
+        (0..len)
+            .map_while(|k| horcruxes[k % horcruxes.len()].body.get(k / horcruxes.len()).copied())
+            .collect()
+    } else {
+        horcruxes[0].body.clone()
+    });
+    ofb(&key, &mut data);
+    Ok((first.original_filename, data))
+}
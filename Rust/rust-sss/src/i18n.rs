@@ -0,0 +1,254 @@
+//! Translations of the prompts, the main messages and the printable recovery cards and share
+//! pages, so custodians can be walked through a recovery in their own language.
+//!
+//! Catalogs are Fluent (`.ftl`) files limited to plain messages: `id = text`, with `{ $name }`
+//! placeables, `#` comments and indented continuation lines. English, German and Spanish are
+//! built in; a translation saved as `locales/<language>.ftl` in the configuration directory is
+//! used over the built-in one, so a community translation works without a new release.
+//! Messages a catalog leaves out are shown in English. `sss locale check` lists what a
+//! translation is missing.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use crate::calibration;
+use crate::error::{Result, SssError};
+
+pub const REFERENCE: &str = "en";
+const BUILTIN: [(&str, &str); 3] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+];
+
+static ACTIVE: RwLock<Option<Catalog>> = RwLock::new(None);
+
+/// One language's messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Catalog {
+    pub language: String,
+    messages: BTreeMap<String, String>,
+}
+
+/// Something wrong with a translation, next to the English catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Missing(String),
+    /// A message English does not have, often a typo in its id.
+    Unknown(String),
+    /// The message does not use the same placeables as its English original.
+    Placeables { id: String, expected: Vec<String>, found: Vec<String> },
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Missing(id) => write!(f, "{}: not translated", id),
+            Problem::Unknown(id) => write!(f, "{}: not a message", id),
+            Problem::Placeables { id, expected, found } => {
+                write!(f, "{}: uses {{{}}} where English uses {{{}}}", id, found.join(", "), expected.join(", "))
+            }
+        }
+    }
+}
+
+impl Catalog {
+    pub fn parse(language: &str, text: &str) -> Result<Catalog> {
+        let invalid = |line: usize, why: &str| SssError::Parse(format!("{}.ftl line {}: {}", language, line, why));
+        let mut messages = BTreeMap::new();
+        let mut current: Option<String> = None;
+        for (n, line) in text.lines().enumerate().map(|(n, line)| (n + 1, line)) {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                current = current.filter(|_| !trimmed.is_empty());
+                continue;
+            }
+            if line.starts_with(char::is_whitespace) {
+                let id = current.as_ref().ok_or_else(|| invalid(n, "an indented line must continue a message"))?;
+                if trimmed.starts_with('.') || trimmed.starts_with('[') || trimmed.starts_with('*') {
+                    return Err(invalid(n, "attributes and variants are not supported"));
+                }
+                let value: &mut String = messages.get_mut(id).expect("current message");
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(trimmed);
+                continue;
+            }
+            let (id, value) = line.split_once('=').ok_or_else(|| invalid(n, "expected `id = text`"))?;
+            let id = id.trim();
+            if id.starts_with('-') {
+                return Err(invalid(n, "terms are not supported"));
+            }
+            if !id.starts_with(|c: char| c.is_ascii_alphabetic())
+                || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(invalid(n, &format!("'{}' is not a message id", id)));
+            }
+            if value.contains("->") {
+                return Err(invalid(n, "selectors are not supported"));
+            }
+            if messages.insert(id.to_string(), value.trim().to_string()).is_some() {
+                return Err(invalid(n, &format!("{} is defined twice", id)));
+            }
+            current = Some(id.to_string());
+        }
+        Ok(Catalog { language: language.to_string(), messages })
+    }
+
+    /// Reads a catalog file, named for its language.
+    pub fn read(path: &Path) -> Result<Catalog> {
+        let language = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| SssError::Parse(format!("{} is not named for a language", path.display())))?;
+        Self::parse(language, &std::fs::read_to_string(path)?)
+    }
+
+    pub fn builtin(language: &str) -> Option<Catalog> {
+        let (_, text) = BUILTIN.iter().find(|(name, _)| *name == language)?;
+        Some(Self::parse(language, text).expect("built-in catalogs parse"))
+    }
+
+    /// The languages built in.
+    pub fn builtin_languages() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(language, _)| *language)
+    }
+
+    pub fn reference() -> Catalog {
+        Self::builtin(REFERENCE).expect("the English catalog is built in")
+    }
+
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.messages.get(id).map(String::as_str)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+
+    /// Messages missing, unknown or with other placeables than in English.
+    pub fn check(&self) -> Vec<Problem> {
+        let reference = Self::reference();
+        let mut problems: Vec<Problem> =
+            reference.ids().filter(|id| self.get(id).is_none()).map(|id| Problem::Missing(id.into())).collect();
+        for (id, text) in &self.messages {
+            match reference.get(id) {
+                None => problems.push(Problem::Unknown(id.clone())),
+                Some(english) if placeables(english) != placeables(text) => problems.push(Problem::Placeables {
+                    id: id.clone(),
+                    expected: placeables(english).into_iter().collect(),
+                    found: placeables(text).into_iter().collect(),
+                }),
+                Some(_) => {}
+            }
+        }
+        problems
+    }
+}
+
+fn placeables(text: &str) -> BTreeSet<String> {
+    text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name.trim().to_string()).collect()
+}
+
+/// Fills in a message's `{ $name }` placeables.
+fn format(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else { break };
+        let name = rest[start + 1..start + end].trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => out.push_str(&value.to_string()),
+            None => out.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The English catalog's text, to start a translation from.
+pub fn template() -> &'static str {
+    BUILTIN[0].1
+}
+
+/// Where translations are installed: `locales/` in the configuration directory.
+pub fn locale_dir() -> Option<PathBuf> {
+    calibration::config_dir().map(|dir| dir.join("locales"))
+}
+
+/// The languages to try for a locale such as `de_AT.UTF-8`, most specific first.
+pub fn candidates(locale: &str) -> Vec<String> {
+    let tag = locale.split(['.', '@']).next().unwrap_or_default().replace('_', "-");
+    match tag.as_str() {
+        "" | "C" | "POSIX" => vec![REFERENCE.to_string()],
+        _ => {
+            let language = tag.split('-').next().unwrap_or_default().to_ascii_lowercase();
+            let mut tried = vec![tag.clone()];
+            if language != tag {
+                tried.push(language);
+            }
+            tried
+        }
+    }
+}
+
+/// The catalog for `language`, an installed translation before a built-in one.
+pub fn load(language: &str) -> Result<Option<Catalog>> {
+    for candidate in candidates(language) {
+        if let Some(path) = locale_dir().map(|dir| dir.join(format!("{}.ftl", candidate))).filter(|p| p.exists()) {
+            return Catalog::read(&path).map(Some);
+        }
+        if let Some(catalog) = Catalog::builtin(&candidate) {
+            return Ok(Some(catalog));
+        }
+    }
+    Ok(None)
+}
+
+/// The language asked for in the environment: `SSS_LANG`, then the usual locale variables.
+pub fn from_environment() -> Option<String> {
+    ["SSS_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+/// Shows messages in `language` from now on.
+pub fn set_language(language: &str) -> Result<()> {
+    let catalog = load(language)?
+        .ok_or_else(|| SssError::InvalidArgument(format!("no translation for '{}'; see sss locale list", language)))?;
+    use_catalog(catalog);
+    Ok(())
+}
+
+pub fn use_catalog(catalog: Catalog) {
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = Some(catalog);
+}
+
+/// The language messages are shown in.
+pub fn language() -> String {
+    let active = ACTIVE.read().unwrap_or_else(|e| e.into_inner());
+    active.as_ref().map_or_else(|| REFERENCE.to_string(), |catalog| catalog.language.clone())
+}
+
+/// The message `id` in the current language, or in English when it is not translated.
+pub fn tr(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let active = ACTIVE.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(text) = active.as_ref().and_then(|catalog| catalog.get(id)) {
+        return format(text, args);
+    }
+    static ENGLISH: OnceLock<Catalog> = OnceLock::new();
+    format(ENGLISH.get_or_init(Catalog::reference).get(id).unwrap_or(id), args)
+}
+
+/// `tr!("card-share", index = 3)` is the message `card-share` with `{ $index }` filled in.
+#[macro_export]
+macro_rules! tr {
+    ($id:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::tr($id, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
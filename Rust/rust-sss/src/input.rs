@@ -0,0 +1,333 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bech32::primitives::decode::CheckedHrpstring;
+use bech32::Bech32m;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::format::{ShareHeader, BECH32_HRP};
+use crate::reed_solomon;
+
+const UTF8_BOM: char = '\u{feff}';
+pub const MAX_SHARE_TEXT_LEN: usize = 128 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(String, std::io::Error),
+    InvalidUtf8 { offset: usize },
+    Utf16,
+    Empty,
+    TooLarge { len: u64 },
+    Unterminated,
+    UnexpectedChar { ch: char, line: usize, column: usize },
+    OddHexLength { len: usize },
+    ByteOutOfRange { value: String, line: usize, column: usize },
+    Encoding { encoding: &'static str, detail: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(path, e) => write!(f, "could not read '{}': {}", path, e),
+            InputError::InvalidUtf8 { offset } => {
+                write!(f, "input is not valid UTF-8 (bad byte at offset {})", offset)
+            }
+            InputError::Utf16 => write!(f, "input looks UTF-16 encoded, save it as UTF-8"),
+            InputError::Empty => write!(f, "input is empty"),
+            InputError::TooLarge { len } => write!(
+                f,
+                "input is {} bytes, more than the {} byte limit for a share",
+                len, MAX_SHARE_TEXT_LEN
+            ),
+            InputError::Unterminated => write!(f, "share is missing its closing ']'"),
+            InputError::UnexpectedChar { ch, line, column } => write!(
+                f,
+                "unexpected character {:?} (U+{:04X}) at line {}, column {}",
+                ch, *ch as u32, line, column
+            ),
+            InputError::OddHexLength { len } => {
+                write!(f, "hex share has an odd number of digits ({})", len)
+            }
+            InputError::ByteOutOfRange { value, line, column } => write!(
+                f,
+                "value '{}' at line {}, column {} is not a byte (0-255)",
+                value, line, column
+            ),
+            InputError::Encoding { encoding, detail } => write!(f, "invalid {} share: {}", encoding, detail),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Decodes raw input as UTF-8 and undoes the usual copy/paste damage: BOMs, CRLF line
+/// endings, smart quotes, non-breaking spaces and zero-width characters.
+pub fn normalize(raw: &[u8]) -> Result<String, InputError> {
+    if raw.starts_with(&[0xff, 0xfe]) || raw.starts_with(&[0xfe, 0xff]) {
+        return Err(InputError::Utf16);
+    }
+    let text = std::str::from_utf8(raw)
+        .map_err(|e| InputError::InvalidUtf8 { offset: e.valid_up_to() })?;
+    Ok(normalize_str(text))
+}
+
+pub fn normalize_str(text: &str) -> String {
+    let text = text.strip_prefix(UTF8_BOM).unwrap_or(text);
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                out.push('\n');
+            }
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => out.push('\''),
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' => out.push('"'),
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => out.push('-'),
+            '\u{a0}' | '\u{2007}' | '\u{202f}' => out.push(' '),
+            '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | UTF8_BOM => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn read_share_file<P: AsRef<Path>>(path: P) -> Result<String, InputError> {
+    let path = path.as_ref();
+    let len = fs::metadata(path)
+        .map_err(|e| InputError::Io(path.display().to_string(), e))?
+        .len();
+    if len > MAX_SHARE_TEXT_LEN as u64 {
+        return Err(InputError::TooLarge { len });
+    }
+    let raw = fs::read(path).map_err(|e| InputError::Io(path.display().to_string(), e))?;
+    normalize(&raw)
+}
+
+/// Parses a single share in any [`ShareEncoding`](crate::format::ShareEncoding): a byte list
+/// (`[1, 2, 3]`), bech32, prefixed base64 or base58, or hex; or as the words of a printed share page.
+/// Whitespace and line breaks inside the share are ignored, as are `Label:` and `Note:` lines
+/// above it (see [`ShareHeader`]). Shares with Reed-Solomon parity (`rs<N>:...`) are
+/// corrected silently; see [`parse_share_corrected`].
+pub fn parse_share(text: &str) -> Result<Vec<u8>, InputError> {
+    parse_share_corrected(text).map(|(share, _)| share)
+}
+
+/// Like [`parse_share`], also returning how many bytes the share's parity corrected.
+pub fn parse_share_corrected(text: &str) -> Result<(Vec<u8>, usize), InputError> {
+    parse_share_details(text).map(|parsed| (parsed.share, parsed.corrected))
+}
+
+/// A share together with what its text said about it.
+#[derive(Debug, Clone)]
+pub struct ParsedShare {
+    pub share: Vec<u8>,
+    /// `list`, `words`, `base64`, `base58`, `bech32`, `ur`, `armor` or `hex`.
+    pub encoding: &'static str,
+    /// Reed-Solomon parity bytes per block, if the share carries any.
+    pub parity: Option<usize>,
+    pub corrected: usize,
+    pub header: ShareHeader,
+}
+
+/// Like [`parse_share`], also returning the encoding, parity and header of the share.
+pub fn parse_share_details(text: &str) -> Result<ParsedShare, InputError> {
+    if text.len() > MAX_SHARE_TEXT_LEN {
+        return Err(InputError::TooLarge { len: text.len() as u64 });
+    }
+    let normalized = normalize_str(text);
+    let (header, body) = ShareHeader::split(&normalized);
+    let trimmed = body.trim().trim_matches(|c| c == '"' || c == '\'');
+    let Some((parity, rest)) = parity_prefix(trimmed) else {
+        let (share, encoding) = parse_encoded(body)?;
+        return Ok(ParsedShare { share, encoding, parity: None, corrected: 0, header });
+    };
+    let (codeword, encoding) = parse_encoded(rest)?;
+    let (share, corrected) = reed_solomon::decode(&codeword, parity)
+        .map_err(|e| InputError::Encoding { encoding: "Reed-Solomon", detail: e.to_string() })?;
+    Ok(ParsedShare { share, encoding, parity: Some(parity), corrected, header })
+}
+
+/// Splits text holding several shares, such as a script pipes to `decrypt`, into one entry per
+/// share: a line with the share, together with any header lines above it, or a whole armored
+/// block. Blank lines between shares are skipped.
+pub fn share_entries(text: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut entry = String::new();
+    for line in text.lines() {
+        if entry.is_empty() && line.trim().is_empty() {
+            continue;
+        }
+        entry.push_str(line);
+        entry.push('\n');
+        let body = ShareHeader::split(&entry).1;
+        let complete = match crate::armor::is_armored(body) {
+            true => line.trim() == crate::armor::END,
+            false => !body.trim().is_empty(),
+        };
+        if complete {
+            entries.push(std::mem::take(&mut entry));
+        }
+    }
+    if !entry.trim().is_empty() {
+        entries.push(entry);
+    }
+    entries
+}
+
+/// Splits `rs<N>:` off the front of a share.
+fn parity_prefix(text: &str) -> Option<(usize, &str)> {
+    let rest = text.strip_prefix("rs").or_else(|| text.strip_prefix("RS"))?;
+    let (digits, rest) = rest.split_once(':')?;
+    Some((digits.parse().ok()?, rest))
+}
+
+fn parse_encoded(text: &str) -> Result<(Vec<u8>, &'static str), InputError> {
+    if text.len() > MAX_SHARE_TEXT_LEN {
+        return Err(InputError::TooLarge { len: text.len() as u64 });
+    }
+    let text = normalize_str(text);
+    let trimmed = text.trim().trim_matches(|c| c == '"' || c == '\'');
+    if trimmed.is_empty() {
+        return Err(InputError::Empty);
+    }
+    if trimmed.starts_with('[') {
+        return parse_byte_list(&text).map(|share| (share, "list"));
+    }
+    if trimmed.split_whitespace().nth(1).is_some() {
+        if let Some(words) = crate::paper::parse_words(trimmed) {
+            return words
+                .map(|share| (share, "words"))
+                .map_err(|e| InputError::Encoding { encoding: "word", detail: e.to_string() });
+        }
+    }
+    if crate::armor::is_armored(trimmed) {
+        return crate::armor::decode(trimmed).map(|share| (share, "armor")).map_err(|e| InputError::Encoding {
+            encoding: "armor",
+            detail: match e {
+                crate::error::SssError::Parse(detail) => detail,
+                e => e.to_string(),
+            },
+        });
+    }
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(digits) = compact.strip_prefix("base64:") {
+        return BASE64
+            .decode(digits)
+            .map(|share| (share, "base64"))
+            .map_err(|e| InputError::Encoding { encoding: "base64", detail: e.to_string() });
+    }
+    if let Some(digits) = compact.strip_prefix("base58:") {
+        return bs58::decode(digits)
+            .into_vec()
+            .map(|share| (share, "base58"))
+            .map_err(|e| InputError::Encoding { encoding: "base58", detail: e.to_string() });
+    }
+    if crate::bcur::is_ur(&compact) {
+        return crate::bcur::decode_share(&compact)
+            .map(|share| (share, "ur"))
+            .map_err(|e| InputError::Encoding {
+                encoding: "UR",
+                detail: match e {
+                    crate::error::SssError::Parse(detail) => detail,
+                    e => e.to_string(),
+                },
+            });
+    }
+    if compact.len() > BECH32_HRP.len() + 1
+        && compact
+            .get(..BECH32_HRP.len() + 1)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}1", BECH32_HRP)))
+    {
+        return parse_bech32(&compact).map(|share| (share, "bech32"));
+    }
+    parse_hex(&text).map(|share| (share, "hex"))
+}
+
+fn parse_bech32(text: &str) -> Result<Vec<u8>, InputError> {
+    let error = |detail: String| InputError::Encoding { encoding: "bech32", detail };
+    let checked = CheckedHrpstring::new::<Bech32m>(text).map_err(|e| error(e.to_string()))?;
+    if !checked.hrp().as_str().eq_ignore_ascii_case(BECH32_HRP) {
+        return Err(error(format!("prefix is '{}', not '{}'", checked.hrp(), BECH32_HRP)));
+    }
+    Ok(checked.byte_iter().collect())
+}
+
+fn positions(text: &str) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+    let mut line = 1;
+    let mut column = 0;
+    text.chars().map(move |c| {
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+        (line, column, c)
+    })
+}
+
+fn parse_byte_list(text: &str) -> Result<Vec<u8>, InputError> {
+    let mut bytes = Vec::new();
+    let mut token: Option<(String, usize, usize)> = None;
+    let mut token_ended = false;
+    let mut opened = false;
+    let mut closed = false;
+
+    let mut finish = |token: &mut Option<(String, usize, usize)>| -> Result<bool, InputError> {
+        match token.take() {
+            Some((digits, line, column)) => {
+                let value = digits
+                    .parse::<u8>()
+                    .map_err(|_| InputError::ByteOutOfRange { value: digits, line, column })?;
+                bytes.push(value);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    };
+
+    for (line, column, c) in positions(text) {
+        match c {
+            _ if c.is_whitespace() => token_ended = token.is_some(),
+            '"' | '\'' if !opened || closed => {}
+            '[' if !opened => opened = true,
+            '0'..='9' if opened && !closed && !token_ended => match token.as_mut() {
+                Some((digits, _, _)) => digits.push(c),
+                None => token = Some((c.to_string(), line, column)),
+            },
+            ',' if opened && !closed => {
+                if !finish(&mut token)? {
+                    return Err(InputError::UnexpectedChar { ch: c, line, column });
+                }
+                token_ended = false;
+            }
+            ']' if opened && !closed => {
+                finish(&mut token)?;
+                closed = true;
+            }
+            _ => return Err(InputError::UnexpectedChar { ch: c, line, column }),
+        }
+    }
+    if !closed {
+        return Err(InputError::Unterminated);
+    }
+    Ok(bytes)
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, InputError> {
+    let mut digits = String::with_capacity(text.len());
+    for (line, column, c) in positions(text) {
+        if c.is_ascii_hexdigit() {
+            digits.push(c);
+        } else if !(c.is_whitespace() || c == '"' || c == '\'') {
+            return Err(InputError::UnexpectedChar { ch: c, line, column });
+        }
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(InputError::OddHexLength { len: digits.len() });
+    }
+    hex::decode(&digits).map_err(|_| InputError::OddHexLength { len: digits.len() })
+}
@@ -0,0 +1,204 @@
+//! What can be learned from a share without recovering anything: its encoding, index, label
+//! and fingerprint, whether its checksum or parity holds, and what the format implies about
+//! the cipher. Shares do not record their quorum, so the threshold, total and signature come
+//! from the `manifest.json` beside the share when it was written with `encrypt --out-dir`.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+
+use crate::crypto::{PBKDF2_ROUNDS, SALT_LEN};
+use crate::error::{Result, SssError};
+use crate::format::{ShareHeader, FORMAT_VERSION, SHARE_OVERHEAD};
+use crate::input::{self, ParsedShare};
+use crate::manifest::{self, share_fingerprint, MANIFEST_FILE};
+use crate::shamir::{vault, Field};
+use crate::{fragment, paper};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShareInfo {
+    pub file: String,
+    pub field: String,
+    pub encoding: String,
+    pub index: u8,
+    pub len: usize,
+    /// The short ID printed next to the share when it was split.
+    pub id: String,
+    /// Hex SHA-256 of the share, as listed in manifests.
+    pub fingerprint: String,
+    pub label: Option<String>,
+    pub note: Option<String>,
+    /// The set fingerprint written above the share, if any.
+    pub declared_set: Option<String>,
+    /// What vouched for the share's text: its checksum or parity, if it has either.
+    pub checksum: Option<String>,
+    pub format_version: u8,
+    pub cipher: Option<String>,
+    /// Length of the padded secret inside the ciphertext.
+    pub padded_secret_len: Option<usize>,
+    pub set: Option<SetInfo>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SetInfo {
+    pub set_fingerprint: String,
+    pub threshold: u8,
+    pub total: u8,
+    pub created: String,
+    /// Whether the manifest lists this share with the same fingerprint.
+    pub listed: bool,
+    /// `unsigned`, `valid (<public key>)` or `invalid: <reason>`.
+    pub signature: String,
+}
+
+/// Reads the share at `path` (or rebuilds it from a fragment manifest) and describes it.
+/// `field` is used unless a manifest beside the share says otherwise.
+pub fn inspect(path: &Path, field: Field) -> Result<ShareInfo> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let set_manifest = manifest::read_manifest(dir).ok();
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let listed_label = set_manifest
+        .as_ref()
+        .and_then(|m| m.shares.iter().find(|e| e.file == file_name))
+        .and_then(|e| e.label.clone());
+    let field = match &set_manifest {
+        Some(m) => m.field.parse()?,
+        None => field,
+    };
+
+    let parsed = if fragment::is_manifest(path) {
+        let (share, found) = fragment::read(path)?;
+        ParsedShare {
+            share,
+            encoding: "fragments",
+            parity: None,
+            corrected: found,
+            header: ShareHeader::default(),
+        }
+    } else {
+        parse(&input::read_share_file(path)?, field)?
+    };
+    describe(path.display().to_string(), parsed, field, set_manifest, listed_label)
+}
+
+/// Describes a share given as text, as `inspect` does for a file without a manifest beside it.
+pub fn inspect_text(name: &str, text: &str, field: Field) -> Result<ShareInfo> {
+    describe(name.to_string(), parse(text, field)?, field, None, None)
+}
+
+pub(crate) fn parse(text: &str, field: Field) -> Result<ParsedShare> {
+    Ok(match field {
+        Field::Vault => ParsedShare {
+            share: vault::parse_unseal_key(text)?,
+            encoding: "unseal key",
+            parity: None,
+            corrected: 0,
+            header: ShareHeader::split(text).0,
+        },
+        _ => input::parse_share_details(text)?,
+    })
+}
+
+fn describe(
+    file: String,
+    parsed: ParsedShare,
+    field: Field,
+    set_manifest: Option<manifest::Manifest>,
+    listed_label: Option<String>,
+) -> Result<ShareInfo> {
+    let share = &parsed.share;
+    let index = share.first().copied().filter(|&x| x != 0 && share.len() > 1).ok_or_else(|| {
+        SssError::Share(format!("{} is not a share: it has no index or no data", file))
+    })?;
+
+    let checksum = match (parsed.encoding, parsed.parity) {
+        (_, Some(parity)) => Some(format!("Reed-Solomon, {} parity bytes, {} byte(s) corrected", parity, parsed.corrected)),
+        ("fragments", _) => Some(format!("rebuilt from {} fragment(s) and their fingerprint", parsed.corrected)),
+        ("bech32", _) => Some("bech32m checksum valid".to_string()),
+        ("words", _) => Some("word checksum valid".to_string()),
+        _ => None,
+    };
+    let encrypted = field != Field::Secp256k1;
+    let fingerprint = share_fingerprint(share);
+
+    let set = set_manifest.map(|m| SetInfo {
+        listed: m.shares.iter().any(|e| e.index == index && e.fingerprint == fingerprint),
+        signature: match &m.signature {
+            None => "unsigned".to_string(),
+            Some(signed) => match m.verify_signature(None) {
+                Ok(_) => format!("valid ({})", signed.public_key),
+                Err(e) => format!("invalid: {}", e),
+            },
+        },
+        set_fingerprint: m.set_fingerprint,
+        threshold: m.threshold,
+        total: m.total,
+        created: paper::civil_date(m.created_at),
+    });
+
+    Ok(ShareInfo {
+        file,
+        field: field.to_string(),
+        encoding: parsed.encoding.to_string(),
+        index,
+        len: share.len(),
+        id: hex::encode(&Sha256::digest(share)[..4]),
+        fingerprint,
+        label: parsed.header.label.or(listed_label),
+        note: parsed.header.note,
+        declared_set: parsed.header.set,
+        checksum,
+        format_version: FORMAT_VERSION,
+        cipher: encrypted.then(|| {
+            format!(
+                "AES-256-GCM, key from PBKDF2-HMAC-SHA256 ({}-byte salt; rounds are recorded inside the \
+                 ciphertext, {} for sets split before they were)",
+                SALT_LEN, PBKDF2_ROUNDS
+            )
+        }),
+        padded_secret_len: if encrypted { share.len().checked_sub(SHARE_OVERHEAD) } else { None },
+        set,
+    })
+}
+
+impl fmt::Display for ShareInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.file)?;
+        writeln!(f, "  share        #{} ({} bytes, {} field, {})", self.index, self.len, self.field, self.encoding)?;
+        writeln!(f, "  id           {}", self.id)?;
+        writeln!(f, "  fingerprint  {}", self.fingerprint)?;
+        let header = ShareHeader { label: self.label.clone(), note: self.note.clone(), ..Default::default() };
+        if !header.is_empty() {
+            writeln!(f, "  label        {}", header)?;
+        }
+        writeln!(f, "  checksum     {}", self.checksum.as_deref().unwrap_or("none in this encoding"))?;
+        writeln!(f, "  format       version {}", self.format_version)?;
+        if let Some(cipher) = &self.cipher {
+            writeln!(f, "  cipher       {}", cipher)?;
+        }
+        if let Some(len) = self.padded_secret_len {
+            writeln!(f, "  secret       {} bytes after padding", len)?;
+        }
+        match &self.set {
+            Some(set) => {
+                writeln!(
+                    f,
+                    "  set          {}: any {} of {} shares, created {}",
+                    set.set_fingerprint, set.threshold, set.total, set.created
+                )?;
+                writeln!(
+                    f,
+                    "  manifest     {}; signature {}",
+                    if set.listed { "lists this share" } else { "DOES NOT list this share" },
+                    set.signature
+                )?;
+            }
+            None => match &self.declared_set {
+                Some(set) => writeln!(f, "  set          {}: quorum unknown, as no {} is beside it", set, MANIFEST_FILE)?,
+                None => writeln!(f, "  set          unknown: shares do not record their quorum and no {} is beside it", MANIFEST_FILE)?,
+            },
+        }
+        Ok(())
+    }
+}
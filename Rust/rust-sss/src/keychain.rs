@@ -0,0 +1,47 @@
+//! One share kept in the platform keyring (Secret Service, macOS Keychain or Windows
+//! Credential Manager) under a name, so a machine can be a custodian without a share file on
+//! disk. `decrypt` reads it when a share is entered as `keyring:<name>`.
+
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+/// Service the entries are filed under.
+pub const SERVICE: &str = "sss";
+/// Prefix that names a keyring entry at the share prompt.
+pub const PREFIX: &str = "keyring:";
+
+fn entry(name: &str) -> Result<keyring::Entry> {
+    if name.trim().is_empty() {
+        return Err(SssError::Store("a keyring entry needs a name".into()));
+    }
+    keyring::Entry::new(SERVICE, name.trim()).map_err(|e| store_error(name, e))
+}
+
+fn store_error(name: &str, e: keyring::Error) -> SssError {
+    SssError::Store(format!("keyring entry '{}': {}", name.trim(), e))
+}
+
+/// Saves the share text under `name`, replacing what was there.
+pub fn store(name: &str, share_text: &str) -> Result<()> {
+    entry(name)?.set_password(share_text).map_err(|e| store_error(name, e))
+}
+
+pub fn load(name: &str) -> Result<Zeroizing<String>> {
+    match entry(name)?.get_password() {
+        Ok(text) => Ok(Zeroizing::new(text)),
+        Err(keyring::Error::NoEntry) => {
+            Err(SssError::Store(format!("the keyring holds no share named '{}'", name.trim())))
+        }
+        Err(e) => Err(store_error(name, e)),
+    }
+}
+
+/// Removes the entry, returning whether there was one.
+pub fn delete(name: &str) -> Result<bool> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(store_error(name, e)),
+    }
+}
@@ -0,0 +1,71 @@
+//! Sets split by the first versions of this tool, before shares carried headers and payloads a
+//! KDF header. Their payload is salt ‖ nonce ‖ AES-256-GCM ciphertext under `PBKDF2_ROUNDS`,
+//! the secret inside is padded with zeros only, and the salt and nonce were also printed on
+//! their own ("SAVE THESE VALUES FOR DECRYPTION"). `sss migrate` opens such a set with
+//! [`open`] and splits it again in the current format.
+
+use zeroize::Zeroizing;
+
+use crate::crypto::{self, NONCE_LEN, PBKDF2_ROUNDS, SALT_LEN};
+use crate::error::{Result, SssError};
+use crate::secret::{deserialize_secret, pad, serialize_secret, Padding};
+use crate::secure::SecretBuf;
+use crate::timelock::Conditions;
+
+/// The salt and nonce an old split printed next to its shares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saved {
+    pub salt: [u8; SALT_LEN],
+    pub nonce: [u8; NONCE_LEN],
+}
+
+impl Saved {
+    pub fn from_hex(salt: &str, nonce: &str) -> Result<Saved> {
+        Ok(Saved { salt: fixed(salt, "salt")?, nonce: fixed(nonce, "nonce")? })
+    }
+
+    /// Reads the `Salt: <hex>` and `Nonce: <hex>` lines of a saved printout; anything else in
+    /// it, such as the shares, is skipped.
+    pub fn parse(text: &str) -> Result<Saved> {
+        let value = |name: &str| {
+            text.lines()
+                .find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+                })
+                .ok_or_else(|| SssError::Parse(format!("no '{}:' line in the saved values", name)))
+        };
+        Self::from_hex(value("Salt")?, value("Nonce")?)
+    }
+}
+
+fn fixed<const N: usize>(text: &str, what: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(text.trim()).map_err(|_| SssError::Parse(format!("the {} is not hex", what)))?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| SssError::Parse(format!("the {} must be {} bytes, got {}", what, N, b.len())))
+}
+
+/// Whether a reconstructed payload predates KDF headers and recovery conditions.
+pub fn is_legacy(payload: &[u8]) -> bool {
+    Conditions::split(payload).1.is_empty() && crypto::kdf_rounds(payload).is_none()
+}
+
+/// Decrypts a legacy payload. With the saved values it also opens a payload holding the
+/// ciphertext alone, as shares of the ciphertext without its salt and nonce would rebuild.
+pub fn open(payload: &[u8], saved: Option<&Saved>, password: &str) -> Result<SecretBuf> {
+    match saved {
+        Some(saved) if !payload.starts_with(&[&saved.salt[..], &saved.nonce[..]].concat()) => {
+            crypto::decrypt_data(payload, password, &saved.salt, &saved.nonce, PBKDF2_ROUNDS)
+        }
+        _ => {
+            let (salt, nonce, ciphertext) = crypto::split_payload(payload)?;
+            crypto::decrypt_data(ciphertext, password, salt, nonce, PBKDF2_ROUNDS)
+        }
+    }
+}
+
+/// A legacy plaintext serialized and padded the way new splits are.
+pub fn upgrade(plaintext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    pad(&serialize_secret(&deserialize_secret(plaintext)?), Padding::default())
+}
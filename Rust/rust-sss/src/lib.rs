@@ -0,0 +1,142 @@
+// without `std`, only the GF(256) math, the payload layout and its AES-GCM wrapping are built
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "age")]
+pub mod age;
+#[cfg(feature = "std")]
+pub mod armor;
+#[cfg(feature = "std")]
+pub mod audit;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bcur;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "std")]
+pub mod calibration;
+#[cfg(feature = "std")]
+pub mod card;
+#[cfg(feature = "std")]
+pub mod ceremony;
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+#[cfg(any(feature = "coordinator", feature = "wasm"))]
+pub mod coordinator;
+pub mod crypto;
+#[cfg(all(unix, feature = "daemon"))]
+pub mod daemon;
+#[cfg(feature = "std")]
+pub mod doctor;
+#[cfg(feature = "std")]
+pub mod dotenv;
+#[cfg(feature = "std")]
+pub mod elgamal;
+#[cfg(feature = "std")]
+pub mod envelope;
+pub mod error;
+// gated inside, so cbindgen does not wrap the header in a feature check
+pub mod ffi;
+pub mod format;
+#[cfg(feature = "std")]
+pub mod fountain;
+#[cfg(feature = "std")]
+pub mod fragment;
+#[cfg(feature = "frost")]
+pub mod frost;
+#[cfg(feature = "std")]
+pub mod gpg;
+#[cfg(feature = "std")]
+pub mod horcrux;
+#[cfg(feature = "std")]
+pub mod i18n;
+#[cfg(feature = "std")]
+pub mod input;
+#[cfg(feature = "std")]
+pub mod inspect;
+#[cfg(feature = "std")]
+pub mod legacy;
+#[cfg(feature = "keyring")]
+pub mod keychain;
+#[cfg(feature = "smtp")]
+pub mod mail;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+#[cfg(feature = "std")]
+pub mod otp;
+#[cfg(feature = "std")]
+pub mod paper;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod pkcs11;
+#[cfg(feature = "std")]
+pub mod placement;
+#[cfg(feature = "std")]
+pub mod plan;
+#[cfg(feature = "std")]
+pub mod policy;
+#[cfg(feature = "pq")]
+pub mod pq;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod receipt;
+#[cfg(feature = "std")]
+pub mod reed_solomon;
+#[cfg(feature = "std")]
+pub mod roughtime;
+#[cfg(feature = "std")]
+pub mod scan;
+#[cfg(feature = "std")]
+pub mod secret;
+pub mod secure;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shamir;
+#[cfg(feature = "std")]
+pub mod source;
+#[cfg(feature = "std")]
+pub mod social;
+#[cfg(feature = "std")]
+pub mod ssh;
+#[cfg(feature = "std")]
+pub mod stego;
+#[cfg(feature = "std")]
+pub mod store;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod strength;
+#[cfg(feature = "std")]
+pub mod subshare;
+#[cfg(feature = "std")]
+pub mod switch;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod timelock;
+#[cfg(feature = "std")]
+pub mod token;
+#[cfg(feature = "std")]
+pub mod tpm;
+#[cfg(feature = "std")]
+pub mod transcript;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "std")]
+pub mod vectors;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod yubikey;
+
+pub use error::{Result, SssError};
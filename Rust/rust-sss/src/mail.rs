@@ -0,0 +1,160 @@
+//! Emailing each custodian their share. Only shares encrypted to the custodian (the
+//! `share-N.age` files `encrypt --age-recipient` writes) are sent, since mail passes through
+//! servers the dealer does not control; the share's recovery card, which holds no secret, is
+//! attached beside it. Every attempt is appended to a log as a JSON line, so a distribution
+//! that failed halfway can be checked and finished.
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, SssError};
+use crate::timelock::system_now;
+
+/// One custodian's mail: share `index` to `to`, with its cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub index: u8,
+    pub to: String,
+    pub share: PathBuf,
+    pub cards: Vec<PathBuf>,
+}
+
+/// An attempt to send, as appended to the log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub at: u64,
+    pub share: u8,
+    pub to: String,
+    pub sent: bool,
+    pub detail: String,
+}
+
+fn share_index(path: &Path) -> Option<u8> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_prefix("share-")?.strip_suffix(".age")?.parse().ok()
+}
+
+/// Pairs share #n in `shares_dir` with the n-th address, and with its cards in `cards_dir`.
+pub fn plan(shares_dir: &Path, cards_dir: Option<&Path>, recipients: &[String]) -> Result<Vec<Delivery>> {
+    let mut shares: Vec<(u8, PathBuf)> = std::fs::read_dir(shares_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| share_index(&path).map(|index| (index, path)))
+        .collect();
+    shares.sort();
+    if shares.is_empty() {
+        return Err(SssError::Parse(format!(
+            "{} holds no share-N.age files; only shares encrypted with --age-recipient are mailed",
+            shares_dir.display()
+        )));
+    }
+    if shares.len() != recipients.len() {
+        return Err(SssError::InvalidArgument(format!(
+            "{} holds {} shares but {} addresses were given",
+            shares_dir.display(),
+            shares.len(),
+            recipients.len()
+        )));
+    }
+    Ok(shares
+        .into_iter()
+        .zip(recipients)
+        .map(|((index, share), to)| {
+            let cards = cards_dir
+                .map(|dir| ["txt", "svg"].map(|ext| dir.join(format!("card-{:03}.{}", index, ext))))
+                .into_iter()
+                .flatten()
+                .filter(|path| path.exists())
+                .collect();
+            Delivery { index, to: to.clone(), share, cards }
+        })
+        .collect())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox> {
+    address.parse().map_err(|e| SssError::InvalidArgument(format!("'{}' is not an email address: {}", address, e)))
+}
+
+fn attachment(path: &Path, content_type: &str) -> Result<SinglePart> {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let content_type = ContentType::parse(content_type).map_err(|e| SssError::Parse(e.to_string()))?;
+    Ok(Attachment::new(name).body(std::fs::read(path)?, content_type))
+}
+
+/// The mail carrying `delivery`, from `from`.
+pub fn message(from: &str, delivery: &Delivery) -> Result<Message> {
+    let share_name = delivery.share.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let body = format!(
+        "You are a custodian of share #{} of a secret split with sss.\n\n\
+         The attached {} is encrypted to your age key. Check that it opens with\n\n    \
+         age -d -i <your identity file> {}\n\n\
+         then keep the file, and the recovery card if one is attached, somewhere safe.\n\
+         Do not forward this mail; nobody will ask you for the share by email.\n",
+        delivery.index, share_name, share_name
+    );
+    let mut parts = MultiPart::mixed()
+        .singlepart(SinglePart::plain(body))
+        .singlepart(attachment(&delivery.share, "application/octet-stream")?);
+    for card in &delivery.cards {
+        let content_type = match card.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => "image/svg+xml",
+            _ => "text/plain; charset=utf-8",
+        };
+        parts = parts.singlepart(attachment(card, content_type)?);
+    }
+    Message::builder()
+        .from(mailbox(from)?)
+        .to(mailbox(&delivery.to)?)
+        .subject(format!("Your share #{} of a secret", delivery.index))
+        .multipart(parts)
+        .map_err(|e| SssError::Parse(e.to_string()))
+}
+
+/// An SMTP transport for `url`: `smtps://host`, `smtp://host?tls=required` for STARTTLS, or
+/// plain `smtp://host:port` for a relay on this machine. `login` overrides the URL's.
+pub fn connect(url: &str, login: Option<(String, String)>) -> Result<SmtpTransport> {
+    let builder = SmtpTransport::from_url(url).map_err(|e| SssError::Parse(format!("SMTP URL {}: {}", url, e)))?;
+    Ok(match login {
+        Some((user, password)) => builder.credentials(Credentials::new(user, password)),
+        None => builder,
+    }
+    .build())
+}
+
+/// Sends every delivery, going on past failures, and appends each attempt to `log`.
+pub fn send_all<T>(transport: &T, from: &str, deliveries: &[Delivery], log: &Path) -> Result<Vec<LogEntry>>
+where
+    T: Transport,
+    T::Error: std::fmt::Display,
+{
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    // the log names every custodian and which share went to them
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(log)?;
+    let mut entries = Vec::with_capacity(deliveries.len());
+    for delivery in deliveries {
+        let sent = message(from, delivery).and_then(|message| {
+            transport.send(&message).map_err(|e| SssError::Store(format!("SMTP: {}", e)))
+        });
+        let entry = LogEntry {
+            at: system_now(),
+            share: delivery.index,
+            to: delivery.to.clone(),
+            sent: sent.is_ok(),
+            detail: match sent {
+                Ok(_) => format!("sent {}", delivery.share.display()),
+                Err(e) => e.to_string(),
+            },
+        };
+        let line = serde_json::to_string(&entry).map_err(|e| SssError::Parse(e.to_string()))?;
+        writeln!(file, "{}", line)?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
@@ -1,215 +1,5468 @@
-use dialoguer::{Select, Input, Password};
-use sss_rs::prelude::*;
-// use std::io::Cursor;
-use aes_gcm::{Aes256Gcm, aead::Aead, KeyInit};
-use aes::cipher::generic_array::GenericArray;
-use pbkdf2::pbkdf2_hmac;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::engine::{ArgValueCandidates, ArgValueCompleter};
+use dialoguer::{Confirm, Input};
+use rand::RngCore;
+use serde_json::json;
 use sha2::{Sha256, Digest};
-use serde::{Serialize, Deserialize};
-use std::iter;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::BufRead;
+use std::path::PathBuf;
+use zeroize::{Zeroize, Zeroizing};
 
-#[derive(Serialize, Deserialize, Debug)]
-enum SecretType {
-    String(String),
-    Int(i64),
-    Float(f64),
+use shamir_cli::audit;
+use shamir_cli::batch;
+use shamir_cli::bench;
+use shamir_cli::calibration;
+use shamir_cli::card::{write_cards, RecoveryCard};
+use shamir_cli::crypto::{self, kdf_rounds, open_payload, split_payload};
+use shamir_cli::dotenv;
+use shamir_cli::format::{
+    self, plaintext_len_for_share_size, validate_shares, set_fingerprint, share_index, ShareEncoding,
+    SetBinding, ShareFormat, ShareHeader, FORMAT_VERSION, SET_ID_LEN,
+};
+use shamir_cli::gpg;
+use shamir_cli::i18n::{self, Catalog};
+use shamir_cli::legacy;
+use shamir_cli::input;
+use shamir_cli::manifest::{self, ShareFile};
+use shamir_cli::paper::{self, ShareSheet};
+use shamir_cli::pipeline;
+use shamir_cli::pkcs11;
+use shamir_cli::placement::PlacementConfig;
+use shamir_cli::plan::{self, Assignment, PlanFile};
+use shamir_cli::policy::{Custodian, JurisdictionPolicy};
+use shamir_cli::profile::Config;
+use shamir_cli::receipt;
+use shamir_cli::secure::{self, write_private, SecretBuf, WritePolicy};
+use shamir_cli::secret::{
+    compress, deserialize_secret, mnemonic_words, pad, pad_alike, select_json_keys, select_labels,
+    serialize_secret, uuid_text, Padding, SecretKind, SecretType,
+};
+use shamir_cli::shamir::{self, pedersen, secp256k1, ssss, vault, Field};
+use shamir_cli::social;
+use shamir_cli::source::Source;
+use shamir_cli::ssh;
+use shamir_cli::stream;
+use shamir_cli::strength;
+use shamir_cli::subshare::{self, Entered};
+use shamir_cli::switch::{Heartbeat, Release, Switch};
+use shamir_cli::timelock::{self, Conditions, TimeLock};
+use shamir_cli::token;
+use shamir_cli::tr;
+use shamir_cli::transcript;
+use shamir_cli::{Result, SssError};
+
+#[macro_use]
+mod cli;
+
+use cli::output::OutputFormat;
+use cli::session::Session;
+
+const RECOVERY_COMMAND: &str = "sss decrypt";
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0   success
+  1   I/O error
+  2   bad input or an invalid argument
+  3   malformed data, or a failed checksum
+  4   authentication failed: wrong password, or recovery not authorized
+  5   not enough shares
+  6   other secret sharing errors, such as shares from different sets
+  7   secure memory unavailable
+  8   share store error
+  9   key derivation failed
+  10  policy violation
+  11  signing or signature error";
+
+#[derive(Parser)]
+#[command(
+    name = "sss",
+    version,
+    about = "Encrypt a secret and split it into Shamir shares",
+    after_long_help = EXIT_CODES
+)]
+struct Cli {
+    /// Record a secret-free transcript of the session (JSON lines) to this file
+    #[arg(long, global = true, value_name = "FILE")]
+    transcript: Option<PathBuf>,
+
+    /// Append a hash-chained record of each split and recovery to this audit log
+    /// (default: $SSS_AUDIT_LOG)
+    #[arg(long, global = true, value_name = "FILE")]
+    audit_log: Option<PathBuf>,
+
+    /// Write a plain-text record of the ceremony to this file for archiving: parameters,
+    /// participants, share IDs, every check and its time, never a secret
+    #[arg(long, global = true, value_name = "FILE")]
+    ceremony_record: Option<PathBuf>,
+
+    /// Sign the ceremony record with this key, created by `sss token keygen`
+    #[arg(long, global = true, value_name = "FILE", requires = "ceremony_record")]
+    ceremony_key: Option<PathBuf>,
+
+    /// Lock derived keys and recovered plaintext in RAM so they cannot be swapped to disk
+    #[arg(long, global = true)]
+    secure_memory: bool,
+
+    /// Export tracing spans to this OTLP/HTTP collector (e.g. http://localhost:4318)
+    #[cfg(feature = "telemetry")]
+    #[arg(long, global = true, value_name = "URL")]
+    otlp_endpoint: Option<String>,
+
+    /// What encrypt and decrypt print on stdout: text, or json for one JSON document
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    output_format: OutputFormat,
+
+    /// Language of the prompts, messages and printed cards, such as de or es
+    /// (default: $SSS_LANG, or else the locale)
+    #[arg(long, global = true, value_name = "LANGUAGE")]
+    lang: Option<String>,
+
+    /// Write progress events (KDF rounds, shares generated, verification) as JSON lines to this
+    /// open file descriptor, for a GUI to draw progress bars from
+    #[cfg(unix)]
+    #[arg(long, global = true, value_name = "FD")]
+    progress_fd: Option<u32>,
+
+    /// Print only results (shares, the recovered secret) and errors, without the messages
+    /// around them
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Replace share, secret and card files that already exist instead of refusing to
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Write each file to a temporary file and rename it into place once it is complete
+    #[arg(long, global = true)]
+    atomic: bool,
+
+    /// Overwrite temporary files with zeros before removing them
+    #[arg(long, global = true)]
+    shred: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Encrypt a secret and split it into shares
+    #[command(visible_alias = "split")]
+    Encrypt(Box<SplitArgs>),
+    /// Recover a secret from a quorum of shares
+    #[command(visible_alias = "combine")]
+    Decrypt(Box<CombineArgs>),
+    /// Split every secret listed in a CSV or JSON file, each with its own threshold and total,
+    /// under one password, into a directory per secret
+    Batch {
+        /// Rows of label, secret, threshold, total and optionally encoding
+        input: PathBuf,
+        #[arg(long, value_name = "DIR", default_value = "batch")]
+        out_dir: PathBuf,
+        /// PBKDF2 rounds for the encryption keys [default: the calibration, or 100000]
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1_000..=crypto::MAX_PBKDF2_ROUNDS as i64)
+        )]
+        kdf_rounds: Option<u32>,
+        /// Refuse passwords whose estimated strength scores below this, from 0 to 4
+        #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_password_score: Option<u8>,
+    },
+    /// Re-encrypt a set under a new password from a quorum and the old password, writing out a
+    /// new set of shares without ever showing the secret
+    RotatePassword {
+        /// Fingerprint of the set being rotated, printed when it was split
+        #[arg(long = "set", value_name = "FINGERPRINT")]
+        expected_set: Option<String>,
+        /// Shares in the new set, instead of asking
+        #[arg(long, value_name = "N")]
+        total: Option<u8>,
+        /// Shares the new set needs, instead of asking
+        #[arg(long, value_name = "N")]
+        threshold: Option<u8>,
+        /// How to print the new shares [default: list]
+        #[arg(long, value_name = "ENCODING")]
+        encoding: Option<ShareEncoding>,
+        /// Write each new share to its own file in this directory, with a manifest.json of the set
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+        /// PBKDF2 rounds for the new key [default: the calibration, or 100000]
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1_000..=crypto::MAX_PBKDF2_ROUNDS as i64)
+        )]
+        kdf_rounds: Option<u32>,
+        /// Refuse new passwords whose estimated strength scores below this, from 0 to 4
+        #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_password_score: Option<u8>,
+    },
+    /// Split a set made by the first versions of sss (shares as byte lists, with the salt and
+    /// nonce printed apart) again in the current format, under a new password
+    Migrate {
+        /// The old printout or a file with its "Salt:" and "Nonce:" lines
+        #[arg(long, value_name = "FILE", conflicts_with = "salt")]
+        saved: Option<PathBuf>,
+        /// The salt the old split printed, in hex
+        #[arg(long, value_name = "HEX", requires = "nonce")]
+        salt: Option<String>,
+        /// The nonce the old split printed, in hex
+        #[arg(long, value_name = "HEX", requires = "salt")]
+        nonce: Option<String>,
+        /// Shares in the new set, instead of asking
+        #[arg(long, value_name = "N")]
+        total: Option<u8>,
+        /// Shares the new set needs, instead of asking
+        #[arg(long, value_name = "N")]
+        threshold: Option<u8>,
+        /// How to print the new shares [default: list]
+        #[arg(long, value_name = "ENCODING")]
+        encoding: Option<ShareEncoding>,
+        /// Write each new share to its own file in this directory, with a manifest.json of the set
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+        /// PBKDF2 rounds for the new key [default: the calibration, or 100000]
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1_000..=crypto::MAX_PBKDF2_ROUNDS as i64)
+        )]
+        kdf_rounds: Option<u32>,
+    },
+    /// Assign custodians to storage locations under the rules in a plan file, show which losses
+    /// of locations, cities or countries the set survives, and split the secret to match
+    Plan {
+        /// TOML listing the threshold, custodians, locations and rules
+        file: PathBuf,
+        /// Show the plan and its redundancy without splitting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// List at most this many fatal groups of each kind of place
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        limit: usize,
+        /// How to print the shares [default: list]
+        #[arg(long, value_name = "ENCODING")]
+        encoding: Option<ShareEncoding>,
+        /// Write each share to its own file in this directory, with a manifest.json of the set
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+        /// PBKDF2 rounds for the encryption key [default: the calibration, or 100000]
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1_000..=crypto::MAX_PBKDF2_ROUNDS as i64)
+        )]
+        kdf_rounds: Option<u32>,
+    },
+    /// Collect shares from guardians' phones through a page served on the local network
+    #[cfg(feature = "coordinator")]
+    Coordinate {
+        /// Address to listen on
+        #[arg(long, default_value = "0.0.0.0:8443")]
+        listen: std::net::SocketAddr,
+        /// Require a capability token signed by this dealer key (hex) before collecting shares
+        #[arg(long, value_name = "HEX")]
+        issuer_key: Option<String>,
+        /// File recording which capability tokens have already been used
+        #[arg(long, value_name = "FILE", default_value = "sss-spent-tokens")]
+        token_ledger: PathBuf,
+        /// Directory holding shamir_cli.js and shamir_cli_bg.wasm, the wasm build (see
+        /// src/wasm.rs) passed through `wasm-bindgen --target web`, which seals shares on the phones
+        #[arg(long, value_name = "DIR")]
+        wasm: PathBuf,
+    },
+    /// Serve split, combine, inspect and verify as a JSON API over HTTP(S)
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on; anything but loopback needs --tls-cert and --tls-key
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: std::net::SocketAddr,
+        /// PEM certificate chain to serve HTTPS with
+        #[arg(long, value_name = "FILE", requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+        /// PEM private key of the certificate
+        #[arg(long, value_name = "FILE", requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+        /// File holding the bearer token clients must send; defaults to $SSS_SERVE_TOKEN, or
+        /// a fresh token printed at startup
+        #[arg(long, value_name = "FILE")]
+        token_file: Option<PathBuf>,
+        /// Requests handled at once
+        #[arg(long, default_value_t = 4)]
+        workers: usize,
+        /// Also let /combine through on a capability token signed by this dealer key (hex), for
+        /// one recovery of the set it names
+        #[arg(long, value_name = "HEX")]
+        issuer_key: Option<String>,
+        /// File recording which capability tokens have already been used
+        #[arg(long, value_name = "FILE", default_value = "sss-spent-tokens")]
+        token_ledger: PathBuf,
+    },
+    /// Email each custodian their age-encrypted share and recovery card
+    #[cfg(feature = "smtp")]
+    Distribute {
+        /// SMTP server: smtps://host, smtp://host?tls=required for STARTTLS, or smtp://localhost:25
+        #[arg(long, value_name = "URL")]
+        smtp: String,
+        /// User to log in as; the password is read from $SSS_SMTP_PASSWORD or asked for
+        #[arg(long, value_name = "NAME")]
+        smtp_user: Option<String>,
+        #[arg(long, value_name = "ADDRESS")]
+        from: String,
+        /// Address of the custodian of share #n, given in share order (repeatable)
+        #[arg(long = "to", value_name = "ADDRESS", required = true)]
+        to: Vec<String>,
+        /// The share-N.age files written by `encrypt --age-recipient`
+        #[arg(long, value_name = "DIR", default_value = "age-shares")]
+        shares: PathBuf,
+        /// Recovery cards to attach, if the directory exists
+        #[arg(long, value_name = "DIR", default_value = "recovery-cards")]
+        cards: PathBuf,
+        /// Where each delivery attempt is appended
+        #[arg(long, value_name = "FILE", default_value = "sss-distribution.log")]
+        log: PathBuf,
+    },
+    /// Re-encrypt a custodian's share-N.age or share-N.pq file to their new key; the share
+    /// inside stays as it is, so no ceremony is needed
+    #[cfg(any(feature = "age", feature = "pq"))]
+    Rewrap {
+        /// The file encrypt --age-recipient or --pq-recipient wrote
+        file: PathBuf,
+        /// The custodian's current identity file, which opens the file
+        #[arg(long, value_name = "FILE")]
+        identity: PathBuf,
+        /// The custodian's new key: an age1... recipient or an sss-pq1-... public key
+        #[arg(long, value_name = "KEY")]
+        new_key: String,
+    },
+    /// Split and combine for local apps over a Unix socket, speaking length-prefixed JSON
+    #[cfg(all(unix, feature = "daemon"))]
+    Daemon {
+        /// Socket to listen on [default: $XDG_RUNTIME_DIR/sss.sock]
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+        /// Also accept connections from processes of this user ID
+        #[arg(long, value_name = "UID")]
+        allow_uid: Vec<u32>,
+    },
+    /// Keep a share in the OS keyring, for `decrypt` to read as `keyring:<name>`
+    #[cfg(feature = "keyring")]
+    #[command(subcommand)]
+    Keyring(KeyringCommand),
+    /// Seal a share to this machine's TPM, for `decrypt` to read as `@<file>.tpm.json`
+    #[command(subcommand)]
+    Tpm(TpmCommand),
+    /// Wrap a share with a YubiKey, for `decrypt` to read as `@<file>.yubikey.json`
+    #[command(subcommand)]
+    Yubikey(YubikeyCommand),
+    /// Hide a share in a PNG, for `decrypt` to read as `@<file>.png`
+    #[command(subcommand)]
+    Stego(StegoCommand),
+    /// Generate the HSM-held AES key that `encrypt --pkcs11-key` encrypts with
+    #[cfg(feature = "pkcs11")]
+    #[command(subcommand)]
+    Pkcs11(Pkcs11Command),
+    /// Make keys for encrypting shares to custodians with ML-KEM-768 + X25519
+    #[cfg(feature = "pq")]
+    #[command(subcommand)]
+    Pq(PqCommand),
+    /// Issue capability tokens that delegate a single recovery ceremony
+    #[command(subcommand)]
+    Token(TokenCommand),
+    /// List, start and check translations of the prompts, messages and cards
+    #[command(subcommand)]
+    Locale(LocaleCommand),
+    /// Sign a heartbeat that holds a set behind a dead man's switch shut for a while longer
+    Heartbeat {
+        /// Dealer signing key created by `sss token keygen`
+        #[arg(long, value_name = "FILE", default_value = "sss-dealer.key")]
+        key: PathBuf,
+        /// Fingerprint of the switched set
+        #[arg(long = "set", value_name = "FINGERPRINT")]
+        fingerprint: String,
+        /// How long the heartbeat holds the set shut
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        valid_days: u64,
+        /// Where to write the heartbeat, replacing the last one
+        #[arg(long, value_name = "FILE", default_value = "sss-heartbeat.json")]
+        out: PathBuf,
+    },
+    /// Register the guardians a social recovery collects shares from
+    #[command(subcommand)]
+    Guardian(GuardianCommand),
+    /// Collect shares from guardians through challenge and answer files
+    #[command(subcommand)]
+    Recover(RecoverCommand),
+    /// Check a heartbeat's signature and whether it still holds its set shut
+    CheckIn {
+        /// The heartbeat written by `sss heartbeat`
+        #[arg(long, value_name = "FILE", default_value = "sss-heartbeat.json")]
+        heartbeat: PathBuf,
+        /// The dealer's public key (hex)
+        #[arg(long, value_name = "KEY", value_parser = token::parse_verifying_key)]
+        dealer_key: ed25519_dalek::VerifyingKey,
+    },
+    /// Encrypt to a shared key that only a quorum of holders can jointly decrypt
+    #[command(subcommand)]
+    Tdec(TdecCommand),
+    /// Work with HashiCorp Vault unseal keys
+    #[command(subcommand)]
+    Vault(VaultCommand),
+    /// Split files into, and bind them back from, jesseduffield/horcrux `.horcrux` files
+    #[command(subcommand)]
+    Horcrux(HorcruxCommand),
+    /// Spread a share over several devices so that losing one does not lose the share
+    #[command(subcommand)]
+    Fragment(FragmentCommand),
+    /// Split a share file again between its holder's own devices; decrypt takes any --threshold
+    /// of the sub-shares in place of the share
+    SubSplit {
+        #[arg(add = ArgValueCompleter::new(cli::complete::share_files))]
+        share: PathBuf,
+        /// Sub-shares needed to rebuild the share
+        #[arg(long)]
+        threshold: u8,
+        /// Sub-shares to write
+        #[arg(long)]
+        total: u8,
+        /// How to write the sub-shares [default: list]
+        #[arg(long, value_name = "ENCODING")]
+        encoding: Option<ShareEncoding>,
+        /// Directory for the sub-shares
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Envelope encryption for large files: a random key encrypts the file and only the key is
+    /// split, so its shares can be reissued or the key rotated without handling the secret
+    #[command(subcommand)]
+    Envelope(EnvelopeCommand),
+    /// Check share directories written with `encrypt --out-dir`
+    #[command(subcommand)]
+    Manifest(ManifestCommand),
+    /// Acknowledge a share as its custodian, or check every share of a set was acknowledged
+    #[command(subcommand)]
+    Receipts(ReceiptsCommand),
+    /// Check an audit log written with --audit-log
+    #[command(subcommand)]
+    Log(LogCommand),
+    /// Show a share file too big for one QR code as a looping animation of fountain-coded QR
+    /// frames, which `decrypt --scan` reads back
+    QrExport {
+        file: PathBuf,
+        /// Bytes of the file each frame carries
+        #[arg(long, value_name = "BYTES", default_value_t = shamir_cli::fountain::DEFAULT_FRAGMENT_LEN)]
+        fragment_len: usize,
+        /// Frames per second
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u64).range(1..=30))]
+        fps: u64,
+        /// Print the text of this many frames, one per line, instead of animating
+        #[arg(long, value_name = "N")]
+        frames: Option<u32>,
+        /// Read the file as a share and show it as a multi-part ur:crypto-sss, for BC-UR apps
+        #[arg(long)]
+        ur: bool,
+    },
+    /// Describe share files without recovering anything: index, label, checksum and set
+    Inspect {
+        /// Share files, or fragment manifests (`*.fragments.json`)
+        #[arg(required = true, add = ArgValueCompleter::new(cli::complete::share_files))]
+        files: Vec<PathBuf>,
+        /// Field the shares were split over, when no manifest beside them says
+        #[arg(long, value_name = "FIELD", default_value = "gf256")]
+        field: Field,
+    },
+    /// Work out why a recovery fails: read the shares in every encoding, find which belong
+    /// together, try the password candidates and name the most likely cause
+    Doctor {
+        /// Share files; without any, paste the shares one at a time
+        #[arg(add = ArgValueCompleter::new(cli::complete::share_files))]
+        files: Vec<PathBuf>,
+        /// Field the shares were split over
+        #[arg(long, value_name = "FIELD", default_value = "gf256")]
+        field: Field,
+        /// The old printout or a file with its "Salt:" and "Nonce:" lines
+        #[arg(long, value_name = "FILE", conflicts_with = "salt")]
+        saved: Option<PathBuf>,
+        /// The salt an old split printed, in hex
+        #[arg(long, value_name = "HEX", requires = "nonce")]
+        salt: Option<String>,
+        /// The nonce an old split printed, in hex
+        #[arg(long, value_name = "HEX", requires = "salt")]
+        nonce: Option<String>,
+        /// Password candidates, one per line, instead of asking
+        #[arg(long, value_name = "FILE")]
+        passwords: Option<PathBuf>,
+    },
+    /// Print the script that teaches a shell to complete sss: subcommands, flags, profile names
+    /// and share files. Load it with `source <(sss completions bash)` (zsh alike), `sss
+    /// completions fish | source`, or `sss completions powershell | Out-String | Invoke-Expression`
+    Completions {
+        #[arg(value_enum)]
+        shell: cli::complete::Shell,
+    },
+    /// Threshold-sign with FROST, so a quorum signs without rebuilding the private key
+    #[cfg(feature = "frost")]
+    #[command(subcommand)]
+    Frost(FrostCommand),
+    /// Measure how many PBKDF2 rounds this machine derives in the target time and save that as
+    /// the cost `encrypt` uses
+    BenchKdf {
+        /// How long one key derivation should take
+        #[arg(long, value_name = "MS", default_value_t = 500)]
+        target_ms: u64,
+        /// Only print the recommendation
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Time the KDF, AES-256-GCM, share generation and reconstruction across payload sizes, to
+    /// compare machines and catch slowdowns between releases
+    Bench {
+        /// Payload sizes in bytes, comma separated
+        #[arg(long, value_name = "BYTES", value_delimiter = ',', default_values_t = bench::DEFAULT_SIZES)]
+        sizes: Vec<usize>,
+        /// PBKDF2 rounds for the KDF timing
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = crypto::PBKDF2_ROUNDS,
+            value_parser = clap::value_parser!(u32).range(1_000..=crypto::MAX_PBKDF2_ROUNDS as i64)
+        )]
+        kdf_rounds: u32,
+        /// Shares needed to reconstruct
+        #[arg(long, default_value_t = 3)]
+        threshold: u8,
+        /// Shares to generate
+        #[arg(long, default_value_t = 5)]
+        total: u8,
+        /// Repeat each operation for at least this long
+        #[arg(long, value_name = "MS", default_value_t = 200)]
+        min_ms: u64,
+    },
+    /// Print the canonical test vectors of the share format, or check vectors from another
+    /// implementation
+    #[command(subcommand)]
+    TestVectors(TestVectorsCommand),
+    /// Step through a recorded session transcript
+    Replay {
+        file: PathBuf,
+        /// Print every step at once instead of waiting for Enter
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[cfg(feature = "keyring")]
+#[derive(Subcommand)]
+enum KeyringCommand {
+    /// Store a share, typed or pasted without echo, under this name
+    Store { name: String },
+    /// Remove the share stored under this name
+    Delete { name: String },
+}
+
+#[derive(Subcommand)]
+enum TpmCommand {
+    /// Seal a share, typed or pasted without echo, into a file only this machine can unseal
+    Seal {
+        /// Where to write the sealed share; the name should end in .tpm.json
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+        /// PCRs the share is bound to
+        #[arg(long, value_name = "BANK:PCRS", default_value = shamir_cli::tpm::DEFAULT_PCRS)]
+        pcrs: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum YubikeyCommand {
+    /// Wrap a share, typed or pasted without echo, with the YubiKey's challenge-response slot
+    Wrap {
+        /// Where to write the wrapped share; the name should end in .yubikey.json
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+        /// OTP slot programmed for HMAC-SHA1 challenge-response
+        #[arg(
+            long,
+            default_value_t = shamir_cli::yubikey::DEFAULT_SLOT,
+            value_parser = clap::value_parser!(u8).range(1..=2)
+        )]
+        slot: u8,
+    },
+}
+
+#[derive(Subcommand)]
+enum StegoCommand {
+    /// Hide a share, typed or pasted without echo, in the low bits of a PNG's pixels
+    Hide {
+        /// The picture to hide the share in
+        #[arg(long, value_name = "PNG")]
+        cover: PathBuf,
+        /// Where to write the picture with the share in it; keep it a PNG, since resizing or
+        /// converting it loses the share
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+    },
+}
+
+#[cfg(feature = "pkcs11")]
+#[derive(Subcommand)]
+enum Pkcs11Command {
+    /// Generate a sensitive, non-extractable AES-256 key on the token
+    Keygen {
+        /// Label of the new key, which encrypt --pkcs11-key names
+        #[arg(long, value_name = "LABEL")]
+        label: String,
+        #[command(flatten)]
+        hsm: Pkcs11Args,
+    },
+}
+
+#[cfg(feature = "pq")]
+#[derive(Subcommand)]
+enum PqCommand {
+    /// Create a custodian's identity file and print the public key shares are encrypted to
+    Keygen {
+        /// Where to write the identity
+        #[arg(long, value_name = "FILE", default_value = "sss-pq.key")]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum LocaleCommand {
+    /// List the built-in and installed languages and how much of each is translated
+    List,
+    /// Print the English catalog, to translate into locales/<language>.ftl in the config directory
+    Template,
+    /// Check a translation against the English catalog
+    Check { file: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum TokenCommand {
+    /// Create a dealer signing key and print its public half
+    Keygen {
+        /// Where to write the private key
+        #[arg(long, value_name = "FILE", default_value = "sss-dealer.key")]
+        out: PathBuf,
+    },
+    /// Authorize a named person to run one recovery of a share set
+    Issue {
+        /// Dealer signing key created by `sss token keygen`
+        #[arg(long, value_name = "FILE", default_value = "sss-dealer.key")]
+        key: PathBuf,
+        /// Who may use the token
+        #[arg(long = "to", value_name = "NAME")]
+        holder: String,
+        /// Fingerprint of the share set that may be recovered
+        #[arg(long, value_name = "HEX")]
+        fingerprint: String,
+        /// How long the token stays valid
+        #[arg(long, value_name = "HOURS", default_value_t = 24)]
+        valid_hours: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum GuardianCommand {
+    /// Create a guardian's signing key and print its public half for the dealer
+    Keygen {
+        /// Where to write the private key
+        #[arg(long, value_name = "FILE", default_value = "sss-guardian.key")]
+        out: PathBuf,
+    },
+    /// Register a guardian
+    Add {
+        name: String,
+        /// The guardian's public key (hex), from `sss guardian keygen`
+        #[arg(long, value_name = "KEY")]
+        key: String,
+        /// How to reach the guardian, printed beside their challenge file
+        #[arg(long, value_name = "TEXT")]
+        contact: Option<String>,
+        #[arg(long, value_name = "FILE", default_value = "sss-guardians.json")]
+        registry: PathBuf,
+    },
+    /// List the registered guardians
+    List {
+        #[arg(long, value_name = "FILE", default_value = "sss-guardians.json")]
+        registry: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum RecoverCommand {
+    /// Start a session: write a challenge file for every registered guardian
+    Start {
+        #[arg(long, value_name = "FILE", default_value = "sss-guardians.json")]
+        registry: PathBuf,
+        /// Fingerprint of the set being recovered, shown to guardians and checked at the end
+        #[arg(long = "set", value_name = "FINGERPRINT")]
+        fingerprint: Option<String>,
+        /// Directory for the session and its challenges
+        #[arg(long, value_name = "DIR", default_value = "sss-recovery")]
+        dir: PathBuf,
+    },
+    /// As a guardian, answer a challenge with your share
+    Answer {
+        challenge: PathBuf,
+        /// Your signing key from `sss guardian keygen`
+        #[arg(long, value_name = "FILE", default_value = "sss-guardian.key")]
+        key: PathBuf,
+        /// Read the share from this file instead of asking for it
+        #[arg(long, value_name = "FILE", add = ArgValueCompleter::new(cli::complete::share_files))]
+        share: Option<PathBuf>,
+        /// Where to write the answer [default: answer-<guardian>.json]
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// Check the guardians' answers and recover the secret from them
+    Finish {
+        /// Answer files [default: every answer-*.json in the session directory]
+        answers: Vec<PathBuf>,
+        #[arg(long, value_name = "DIR", default_value = "sss-recovery")]
+        dir: PathBuf,
+        #[arg(long, value_name = "FILE", default_value = "sss-guardians.json")]
+        registry: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum VaultCommand {
+    /// Rebuild the key behind `vault operator init -format=json` output and split it again
+    Resplit {
+        /// The init output (only the unseal or recovery keys and threshold are read)
+        #[arg(long, value_name = "FILE")]
+        init: PathBuf,
+        /// Keys needed to unseal from now on
+        #[arg(long)]
+        threshold: u8,
+        /// Unseal keys to create
+        #[arg(long)]
+        total: u8,
+        /// Write the new keys here (mode 0600) instead of printing them
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FragmentCommand {
+    /// Erasure-code a share file into fragments, any --needed of which rebuild it
+    Split {
+        #[arg(add = ArgValueCompleter::new(cli::complete::share_files))]
+        share: PathBuf,
+        /// Fragments needed to rebuild the share
+        #[arg(long)]
+        needed: u8,
+        /// Fragments to write
+        #[arg(long)]
+        total: u8,
+        /// Directory for the fragments, one per device (repeatable; fragments are dealt out in turn)
+        #[arg(long = "to", value_name = "DIR", required = true)]
+        locations: Vec<PathBuf>,
+    },
+    /// Rebuild a share from its fragment manifest and print it as hex
+    Join {
+        manifest: PathBuf,
+        /// Write the share to this file (mode 0600) instead of printing it
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TestVectorsCommand {
+    /// Print the vectors as JSON: secret, password, parameters, payload and shares
+    Generate {
+        /// Write them to this file instead
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// Check that every vector's shares rebuild its payload and that it decrypts to its secret
+    Verify { file: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum LogCommand {
+    /// Walk the log's hash chain and print its head
+    Verify {
+        /// The log (default: --audit-log or $SSS_AUDIT_LOG)
+        file: Option<PathBuf>,
+        /// Require the log to still hold the record with this hash, as an earlier verify printed
+        /// it, so a log cut short is caught
+        #[arg(long, value_name = "HASH")]
+        expect: Option<String>,
+    },
+    /// Check the signature on a record written with --ceremony-record and --ceremony-key
+    VerifyCeremony {
+        file: PathBuf,
+        /// Require the record to be signed by this key (hex, as `sss token keygen` prints it)
+        #[arg(long, value_name = "HEX")]
+        signer: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ManifestCommand {
+    /// Check the manifest's signature and that every share file matches its fingerprint
+    Verify {
+        dir: PathBuf,
+        /// Require the manifest to be signed by this key (hex, as `sss token keygen` prints it)
+        #[arg(long, value_name = "HEX")]
+        signer: Option<String>,
+    },
+    /// Check one custodian's share against the manifest: its fingerprint, and the Pedersen
+    /// commitments of a `pedersen` set
+    CheckShare {
+        dir: PathBuf,
+        #[arg(add = ArgValueCompleter::new(cli::complete::share_files))]
+        share: PathBuf,
+    },
+    /// List which custodians can recover the set together and flag risky distributions
+    Quorum {
+        dir: PathBuf,
+        /// List at most this many quorums
+        #[arg(long, value_name = "N", default_value_t = 50)]
+        limit: usize,
+        /// Exit with an error when the distribution has warnings
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReceiptsCommand {
+    /// Check a share you were given and write a receipt for the dealer
+    Sign {
+        #[arg(add = ArgValueCompleter::new(cli::complete::share_files))]
+        share: PathBuf,
+        /// Your name, as the dealer knows you
+        #[arg(long, value_name = "NAME")]
+        custodian: String,
+        /// Sign with this key, from `sss guardian keygen`
+        #[arg(long, value_name = "FILE", required_unless_present = "hmac_key", conflicts_with = "hmac_key")]
+        key: Option<PathBuf>,
+        /// Seal with an HMAC under a key shared with the dealer instead
+        #[arg(long, value_name = "FILE")]
+        hmac_key: Option<PathBuf>,
+        /// Also check the share is listed in this share directory's manifest
+        #[arg(long, value_name = "DIR")]
+        manifest: Option<PathBuf>,
+        /// Where to write the receipt [default: receipt-NN.json]
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// Check receipts against a share directory's manifest and list shares not accounted for
+    Verify {
+        /// The share directory written with `encrypt --out-dir`
+        dir: PathBuf,
+        /// Receipt files, or directories holding them
+        #[arg(required = true)]
+        receipts: Vec<PathBuf>,
+        /// Key for HMAC receipts
+        #[arg(long, value_name = "FILE")]
+        hmac_key: Option<PathBuf>,
+        /// Require signed receipts to use the custodian's registered guardian key
+        #[arg(long, value_name = "FILE")]
+        registry: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HorcruxCommand {
+    /// Encrypt a file and write `name_N_of_M.horcrux` files that horcrux can bind
+    Split {
+        file: PathBuf,
+        /// Horcruxes needed to bind the file again
+        #[arg(long)]
+        threshold: u8,
+        /// Horcruxes to create
+        #[arg(long)]
+        total: u8,
+        /// Directory for the horcruxes
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Rebuild the original file from horcrux files, or directories holding them
+    Bind {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Where to write the file (defaults to its original name in the current directory)
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct KeyShareArgs {
+    /// Key shares needed to rebuild the key
+    #[arg(long)]
+    threshold: u8,
+    /// Key shares to write
+    #[arg(long)]
+    total: u8,
+    /// How to write the key shares [default: list]
+    #[arg(long, value_name = "ENCODING")]
+    encoding: Option<ShareEncoding>,
+    /// Directory for the key shares
+    #[arg(long, value_name = "DIR", default_value = "envelope-keys")]
+    out_dir: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum EnvelopeCommand {
+    /// Encrypt a file under a fresh data key, write the ciphertext beside it and split the key
+    Seal {
+        file: PathBuf,
+        /// Where to write the ciphertext [default: the file name with .sse added]
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        /// Plaintext bytes per authenticated chunk
+        #[arg(long, value_name = "BYTES", default_value_t = shamir_cli::envelope::CHUNK_LEN)]
+        chunk_size: u32,
+        #[command(flatten)]
+        shares: KeyShareArgs,
+    },
+    /// Decrypt a ciphertext file with a quorum of its key shares
+    Open {
+        file: PathBuf,
+        /// Key share files
+        #[arg(
+            long = "share",
+            value_name = "FILE",
+            required = true,
+            add = ArgValueCompleter::new(cli::complete::share_files)
+        )]
+        shares: Vec<PathBuf>,
+        /// Where to write the plaintext [default: the file name without .sse]
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// Issue new key shares to a new quorum; the ciphertext and the key stay as they are
+    Rewrap {
+        file: PathBuf,
+        /// Key share files of the current quorum
+        #[arg(
+            long = "share",
+            value_name = "FILE",
+            required = true,
+            add = ArgValueCompleter::new(cli::complete::share_files)
+        )]
+        old_shares: Vec<PathBuf>,
+        #[command(flatten)]
+        shares: KeyShareArgs,
+    },
+    /// Re-encrypt the file under a new key and split that, so the old shares open nothing
+    Rotate {
+        file: PathBuf,
+        /// Key share files of the current quorum
+        #[arg(
+            long = "share",
+            value_name = "FILE",
+            required = true,
+            add = ArgValueCompleter::new(cli::complete::share_files)
+        )]
+        old_shares: Vec<PathBuf>,
+        /// Where to write the re-encrypted file
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+        #[command(flatten)]
+        shares: KeyShareArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum TdecCommand {
+    /// Generate a decryption key, write one key share per holder and print the public key
+    Keygen {
+        /// Holders needed to decrypt
+        #[arg(long)]
+        threshold: u8,
+        /// Key shares to create
+        #[arg(long)]
+        total: u8,
+        /// Directory for the key shares
+        #[arg(long, value_name = "DIR", default_value = "tdec-keys")]
+        out_dir: PathBuf,
+    },
+    /// Encrypt a file to the shared public key
+    Encrypt {
+        /// Public key printed by keygen (hex)
+        #[arg(long, value_name = "HEX")]
+        public_key: String,
+        #[arg(long = "in", value_name = "FILE")]
+        input: PathBuf,
+        #[arg(long = "out", value_name = "FILE")]
+        output: PathBuf,
+    },
+    /// Compute this holder's partial decryption of a file; it is safe to send to the combiner
+    Partial {
+        /// This holder's key share
+        #[arg(long, value_name = "FILE")]
+        share: PathBuf,
+        #[arg(long, value_name = "FILE")]
+        ciphertext: PathBuf,
+        /// Also write the partial decryption to this file
+        #[arg(long = "out", value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Decrypt a file from a quorum of partial decryptions
+    Combine {
+        #[arg(long, value_name = "FILE")]
+        ciphertext: PathBuf,
+        /// Partial decryptions (repeatable)
+        #[arg(long = "partial", value_name = "FILE", required = true)]
+        partials: Vec<PathBuf>,
+        #[arg(long = "out", value_name = "FILE")]
+        output: PathBuf,
+    },
+}
+
+#[cfg(feature = "frost")]
+#[derive(Subcommand)]
+enum FrostCommand {
+    /// Deal a signing key into key packages, one per holder
+    Keygen {
+        /// Curve to sign over: ed25519 or secp256k1
+        #[arg(long, value_name = "CURVE", default_value = "ed25519")]
+        curve: shamir_cli::frost::Suite,
+        /// Signers needed for a signature
+        #[arg(long)]
+        threshold: u16,
+        /// Key packages to create
+        #[arg(long)]
+        total: u16,
+        /// Split an existing private key (prompted for) instead of generating one
+        #[arg(long)]
+        import: bool,
+        /// Directory for the key packages and the public key package
+        #[arg(long, value_name = "DIR", default_value = "frost-keys")]
+        out_dir: PathBuf,
+    },
+    /// Commit to fresh signing nonces; send the commitments to the other signers
+    SignRound1 {
+        /// This holder's key package
+        #[arg(long, value_name = "FILE")]
+        key: PathBuf,
+        /// Where to keep the secret nonces until round two
+        #[arg(long, value_name = "FILE", default_value = "frost-nonces.txt")]
+        nonces_out: PathBuf,
+        /// Also write the commitments to this file
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        /// Also show the commitments as a QR code
+        #[arg(long)]
+        qr: bool,
+    },
+    /// Produce this holder's signature share; the nonces file is deleted so it is never reused
+    SignRound2 {
+        #[arg(long, value_name = "FILE")]
+        key: PathBuf,
+        /// Nonces written by sign-round1
+        #[arg(long, value_name = "FILE")]
+        nonces: PathBuf,
+        /// Commitments of every signer taking part, this holder's included (repeatable)
+        #[arg(long = "commitments", value_name = "FILE", required = true)]
+        commitments: Vec<PathBuf>,
+        /// File holding the message to sign
+        #[arg(long, value_name = "FILE")]
+        message: PathBuf,
+        /// Also write the signature share to this file
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        /// Also show the signature share as a QR code
+        #[arg(long)]
+        qr: bool,
+    },
+    /// Combine signature shares into a signature and check it against the group key
+    Aggregate {
+        /// Public key package written by keygen
+        #[arg(long, value_name = "FILE")]
+        public: PathBuf,
+        #[arg(long = "commitments", value_name = "FILE", required = true)]
+        commitments: Vec<PathBuf>,
+        /// Signature shares from sign-round2 (repeatable)
+        #[arg(long = "share", value_name = "FILE", required = true)]
+        shares: Vec<PathBuf>,
+        #[arg(long, value_name = "FILE")]
+        message: PathBuf,
+        /// Also write the signature (hex) to this file
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Default)]
+struct SplitArgs {
+    /// Take defaults from this profile in the config directory's config.toml; flags given here
+    /// still win
+    #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(cli::complete::profile_names))]
+    profile: Option<String>,
+
+    /// Shares to make, instead of asking
+    #[arg(long, value_name = "N", conflicts_with_all = ["custodians", "placement"])]
+    total: Option<u8>,
+
+    /// Shares needed to recover the secret, instead of asking
+    #[arg(long, value_name = "N")]
+    threshold: Option<u8>,
+
+    /// How to pad the secret to hide its length: minimum, pow2 or bucket:<bytes>
+    #[arg(long, value_name = "POLICY", default_value = "pow2")]
+    padding: Padding,
+
+    /// Pad so every share is exactly this many bytes, hiding which sets hold larger secrets
+    #[arg(long, value_name = "BYTES", conflicts_with = "padding")]
+    share_size: Option<usize>,
+
+    /// Compress the secret with zstd before encrypting it, keeping large secrets' shares small
+    #[arg(long)]
+    compress: bool,
+
+    /// Also encrypt a harmless decoy secret under a second password, which recovers only the
+    /// decoy at decrypt
+    #[arg(long, conflicts_with = "file")]
+    decoy: bool,
+
+    /// Refuse to recover the set before this UTC date, YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ
+    #[arg(long, value_name = "DATE", value_parser = timelock::parse_date)]
+    not_before: Option<u64>,
+
+    /// Only take the date from a Roughtime server signing with this Ed25519 key (base64 or
+    /// hex), not from the clock of the machine recovering the set
+    #[arg(long, value_name = "KEY", value_parser = shamir_cli::roughtime::parse_key, requires = "not_before")]
+    roughtime_key: Option<[u8; 32]>,
+
+    /// Put the set behind a dead man's switch: it then only opens with an approval token signed
+    /// by this dealer key (hex, as `sss token keygen` prints it) or once the dealer's heartbeats
+    /// (`sss heartbeat`) stop
+    #[arg(long, value_name = "KEY", value_parser = token::parse_verifying_key)]
+    dead_mans_switch: Option<ed25519_dalek::VerifyingKey>,
+
+    /// Give share #n to the n-th custodian listed, tagged with their jurisdiction (repeatable)
+    #[arg(long = "custodian", value_name = "NAME:JURISDICTION")]
+    custodians: Vec<Custodian>,
+
+    /// Label share #n with the n-th label listed, e.g. "Safe deposit box" (repeatable; defaults
+    /// to the custodian's name). Shown whenever the share is read back
+    #[arg(long = "share-label", value_name = "LABEL", conflicts_with = "format")]
+    share_labels: Vec<String>,
+
+    /// Free-form note for share #n, kept beside its label (repeatable)
+    #[arg(long = "share-note", value_name = "NOTE", conflicts_with = "format")]
+    share_notes: Vec<String>,
+
+    /// Refuse splits where some quorum spans fewer than this many jurisdictions
+    #[arg(long, value_name = "N", requires = "custodians")]
+    min_jurisdictions: Option<usize>,
+
+    /// Encrypt this file with a random key and split only the key, streaming the file to disk
+    #[arg(long, value_name = "PATH", conflicts_with = "multi")]
+    file: Option<PathBuf>,
+
+    /// Where to write the encrypted file (defaults to the input path plus .sss)
+    #[arg(long, value_name = "PATH", requires = "file")]
+    ciphertext_out: Option<PathBuf>,
+
+    /// Split several labelled secrets into one share set, one share per custodian
+    #[arg(long)]
+    multi: bool,
+
+    /// Enter a BIP-39 seed phrase; it is checked word by word and only its entropy is split
+    #[arg(long, conflicts_with_all = ["multi", "file"])]
+    bip39: bool,
+
+    /// Read the typed secret as auto, string, int, float, hex, base64, json, uuid, date or totp
+    /// instead of going by its prefix; string keeps "0042" or "+1 555 0100" exactly as typed
+    #[arg(
+        long = "type",
+        value_name = "TYPE",
+        default_value = "auto",
+        conflicts_with_all = ["multi", "file", "bip39", "ssh_key", "gpg_key", "gpg_export", "env_file"]
+    )]
+    secret_type: SecretKind,
+
+    /// Split this SSH private key file (OpenSSH or PEM), after checking it parses
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["multi", "file", "bip39"])]
+    ssh_key: Option<PathBuf>,
+
+    /// Split this armored GPG secret key export, recording its fingerprint in the secret
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["multi", "file", "bip39", "ssh_key"])]
+    gpg_key: Option<PathBuf>,
+
+    /// Export this secret key with `gpg --export-secret-keys --armor` and split it
+    #[arg(long, value_name = "KEYID", conflicts_with_all = ["multi", "file", "bip39", "ssh_key", "gpg_key"])]
+    gpg_export: Option<String>,
+
+    /// Split this .env file whole; decrypt gives it back byte for byte or as `export` lines
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["multi", "file", "bip39", "ssh_key", "gpg_key", "gpg_export"]
+    )]
+    env_file: Option<PathBuf>,
+
+    /// Split this age identity file, after checking every identity in it parses
+    #[cfg(feature = "age")]
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "multi", "file", "bip39", "ssh_key", "gpg_key", "gpg_export", "env_file", "field", "format",
+            "secret_type", "secret_env", "secret_fd", "secret_file"
+        ]
+    )]
+    age_identity: Option<PathBuf>,
+
+    /// Encrypt share #n to the n-th age recipient listed instead of printing it (repeatable)
+    #[cfg(feature = "age")]
+    #[arg(
+        long = "age-recipient",
+        value_name = "AGE1...",
+        conflicts_with_all = ["custodians", "format", "out_dir", "placement", "total"]
+    )]
+    age_recipients: Vec<String>,
+
+    /// Directory for the age-encrypted shares
+    #[cfg(feature = "age")]
+    #[arg(long, value_name = "DIR", default_value = "age-shares", requires = "age_recipients")]
+    age_out: PathBuf,
+
+    /// Encrypt share #n to the n-th post-quantum public key (sss-pq1-...) with ML-KEM-768 and
+    /// X25519 instead of printing it (repeatable)
+    #[cfg(feature = "pq")]
+    #[arg(
+        long = "pq-recipient",
+        value_name = "KEY",
+        conflicts_with_all = ["custodians", "format", "out_dir", "placement", "total"]
+    )]
+    pq_recipients: Vec<String>,
+
+    /// Directory for the post-quantum encrypted shares
+    #[cfg(feature = "pq")]
+    #[arg(long, value_name = "DIR", default_value = "pq-shares", requires = "pq_recipients")]
+    pq_out: PathBuf,
+
+    /// Field to share over: gf256, secp256k1 to split a raw 32-byte private key as a scalar,
+    /// vault for shares in the base64 format of Vault unseal keys, or pedersen for shares custodians
+    /// can check against published commitments that reveal nothing about the secret
+    #[arg(
+        long,
+        value_name = "FIELD",
+        default_value = "gf256",
+        conflicts_with_all = [
+            "padding", "share_size", "compress", "file", "multi", "bip39", "ssh_key", "gpg_key", "gpg_export",
+            "env_file"
+        ]
+    )]
+    field: Field,
+
+    /// Share format: sss, or ssss to split the secret itself, without a password, into
+    /// `index-hex` lines that ssss-combine accepts
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "sss",
+        conflicts_with_all = [
+            "padding", "share_size", "compress", "file", "multi", "bip39", "ssh_key", "gpg_key", "gpg_export",
+            "env_file", "custodians", "field"
+        ]
+    )]
+    format: ShareFormat,
+
+    /// With --format ssss, skip ssss's diffusion layer, like ssss-split -D
+    #[arg(long)]
+    no_diffusion: bool,
+
+    /// How to print shares: list, hex, base64, base58, bech32, ur or armor (decrypt recognises
+    /// each one)
+    /// [default: list]
+    #[arg(long, value_name = "ENCODING", conflicts_with = "format")]
+    encoding: Option<ShareEncoding>,
+
+    /// Add this many Reed-Solomon parity bytes per share block, so up to half as many damaged
+    /// bytes are corrected when the share is read back
+    #[arg(long, value_name = "BYTES", conflicts_with = "format")]
+    parity: Option<usize>,
+
+    /// Write a printable HTML page per share (QR code, words and hex) into this directory
+    #[arg(long, value_name = "DIR", conflicts_with = "format")]
+    paper: Option<PathBuf>,
+
+    /// Write a recovery card per share into this directory without asking
+    #[arg(long, value_name = "DIR", conflicts_with = "no_cards")]
+    cards: Option<PathBuf>,
+
+    /// Write no recovery cards and do not ask; the default when standard input is not a
+    /// terminal, with --quiet or with --output-format json
+    #[arg(long)]
+    no_cards: bool,
+
+    /// Write each share to its own file in this directory, with a manifest.json of the set
+    #[arg(long, value_name = "DIR", conflicts_with = "format")]
+    out_dir: Option<PathBuf>,
+
+    /// Push each share to the backend a placement file names for it (local paths, S3, Google
+    /// Drive, WebDAV); the file sets the number of shares
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["out_dir", "format", "custodians", "tpm_seal", "yubikey", "stego"]
+    )]
+    placement: Option<PathBuf>,
+
+    /// Before printing anything, rebuild the secret from this many quorums of the new shares
+    /// (every quorum, if there are no more) and check each one; 0 skips the rehearsal [default: 64]
+    #[arg(long, value_name = "N")]
+    rehearsals: Option<usize>,
+
+    /// Refuse passwords whose estimated strength scores below this, from 0 (guessable in a
+    /// thousand tries) to 4 (over ten billion)
+    #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=4), conflicts_with = "format")]
+    min_password_score: Option<u8>,
+
+    /// PBKDF2 rounds for the encryption key, instead of the calibration saved by `sss bench-kdf`
+    /// [default: the calibration, or 100000]
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1_000..=crypto::MAX_PBKDF2_ROUNDS as i64),
+        conflicts_with = "format"
+    )]
+    kdf_rounds: Option<u32>,
+
+    /// For tests only: draw every random value of the split from this seed, so the same inputs
+    /// give the same shares. Anyone who learns the seed can recompute the randomness
+    #[arg(long, value_name = "SEED")]
+    deterministic_seed: Option<String>,
+
+    /// Sign the manifest with this key, created by `sss token keygen`
+    #[arg(long, value_name = "FILE", requires = "out_dir")]
+    manifest_key: Option<PathBuf>,
+
+    /// Once the shares have been copied down, clear the screen and scrollback on Enter
+    #[arg(long)]
+    clear_screen: bool,
+
+    /// Keep share #1 in the OS keyring under this name instead of printing it
+    #[cfg(feature = "keyring")]
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["out_dir", "format", "tpm_seal", "yubikey", "stego", "placement"]
+    )]
+    keyring: Option<String>,
+
+    /// Seal share #1 to this machine's TPM and write it to this file instead of printing it;
+    /// it then only unseals while the machine boots into the same measured state
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["out_dir", "format"])]
+    tpm_seal: Option<PathBuf>,
+
+    /// PCRs the sealed share is bound to
+    #[arg(long, value_name = "BANK:PCRS", default_value = shamir_cli::tpm::DEFAULT_PCRS, requires = "tpm_seal")]
+    tpm_pcrs: String,
+
+    /// Wrap share #1 with the connected YubiKey and write it to this file instead of printing
+    /// it; it then only unwraps with that YubiKey plugged in
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["out_dir", "format", "tpm_seal"])]
+    yubikey: Option<PathBuf>,
+
+    /// OTP slot programmed for HMAC-SHA1 challenge-response
+    #[arg(
+        long,
+        default_value_t = shamir_cli::yubikey::DEFAULT_SLOT,
+        value_parser = clap::value_parser!(u8).range(1..=2),
+        requires = "yubikey"
+    )]
+    yubikey_slot: u8,
+
+    /// Hide share #1 in the PNG given by --stego-cover and write the picture to this file
+    /// instead of printing the share
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "stego_cover",
+        conflicts_with_all = ["out_dir", "tpm_seal", "yubikey"]
+    )]
+    stego: Option<PathBuf>,
+
+    /// The picture share #1 is hidden in
+    #[arg(long, value_name = "PNG", requires = "stego")]
+    stego_cover: Option<PathBuf>,
+
+    /// Encrypt with this AES key held in an HSM instead of a password-derived key
+    #[cfg(feature = "pkcs11")]
+    #[arg(
+        long,
+        value_name = "LABEL",
+        requires = "pkcs11_module",
+        conflicts_with_all = [
+            "format", "kdf_rounds", "min_password_score", "not_before", "dead_mans_switch", "share_size"
+        ]
+    )]
+    pkcs11_key: Option<String>,
+
+    #[command(flatten)]
+    pkcs11: Pkcs11Args,
+
+    #[command(flatten)]
+    clipboard: ClipboardArgs,
+
+    #[command(flatten)]
+    secret_source: SecretSourceArgs,
+
+    #[command(flatten)]
+    password_source: PasswordSourceArgs,
+
+    /// A plaintext already opened by `rotate-password`, sealed as it is instead of asking for
+    /// a secret
+    #[arg(skip)]
+    resealed: Option<Resealed>,
+}
+
+struct Resealed {
+    plaintext: SecretBuf,
+    conditions: Conditions,
+}
+
+#[derive(Args, Default)]
+struct ClipboardArgs {
+    /// Copy to the clipboard instead of printing: each share in turn, or the recovered secret
+    #[cfg(feature = "clipboard")]
+    #[arg(long)]
+    clipboard: bool,
+
+    /// Clear the clipboard this long after copying, unless something else was copied since
+    #[cfg(feature = "clipboard")]
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = shamir_cli::clipboard::DEFAULT_CLEAR_SECONDS,
+        requires = "clipboard"
+    )]
+    clipboard_seconds: u64,
+}
+
+#[derive(Args, Default)]
+struct SecretSourceArgs {
+    /// Take the secret from this environment variable instead of asking
+    #[arg(
+        long,
+        value_name = "VAR",
+        conflicts_with_all = [
+            "secret_fd", "secret_file", "multi", "file", "bip39", "ssh_key", "gpg_key", "gpg_export", "env_file"
+        ]
+    )]
+    secret_env: Option<String>,
+
+    /// Read the secret from this open file descriptor, such as 3 for `3<secret.txt`
+    #[arg(
+        long,
+        value_name = "FD",
+        conflicts_with_all = [
+            "secret_file", "multi", "file", "bip39", "ssh_key", "gpg_key", "gpg_export", "env_file"
+        ]
+    )]
+    secret_fd: Option<i32>,
+
+    /// Read the secret from this file; one trailing line break is dropped
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["multi", "file", "bip39", "ssh_key", "gpg_key", "gpg_export", "env_file"]
+    )]
+    secret_file: Option<PathBuf>,
+}
+
+impl SecretSourceArgs {
+    fn source(&self) -> Option<Source> {
+        Source::pick(self.secret_env.as_deref(), self.secret_fd, self.secret_file.as_deref())
+    }
+}
+
+#[derive(Args, Default)]
+struct PasswordSourceArgs {
+    /// Take the password from this environment variable instead of asking
+    #[arg(long, value_name = "VAR", conflicts_with_all = ["password_fd", "password_file"])]
+    password_env: Option<String>,
+
+    /// Read the password from this open file descriptor, such as 3 for `3<password.txt`
+    #[arg(long, value_name = "FD", conflicts_with = "password_file")]
+    password_fd: Option<i32>,
+
+    /// Read the password from this file; one trailing line break is dropped
+    #[arg(long, value_name = "PATH")]
+    password_file: Option<PathBuf>,
+}
+
+impl PasswordSourceArgs {
+    fn source(&self) -> Option<Source> {
+        Source::pick(self.password_env.as_deref(), self.password_fd, self.password_file.as_deref())
+    }
+}
+
+#[derive(Args, Default)]
+struct Pkcs11Args {
+    /// PKCS#11 module of the HSM holding the encryption key, such as libsofthsm2.so
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, value_name = "PATH")]
+    pkcs11_module: Option<PathBuf>,
+
+    /// Label of the token to use, when the module sees more than one
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, value_name = "LABEL", requires = "pkcs11_module")]
+    pkcs11_token: Option<String>,
+}
+
+impl ClipboardArgs {
+    #[cfg(feature = "clipboard")]
+    fn enabled(&self) -> bool {
+        self.clipboard
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn enabled(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Args, Default)]
+struct CombineArgs {
+    /// For JSON secrets, print only this key (repeatable)
+    #[arg(long = "key", value_name = "KEY")]
+    keys: Vec<String>,
+
+    /// For multi-secret sets, recover only this labelled secret (repeatable)
+    #[arg(long = "label", value_name = "LABEL")]
+    labels: Vec<String>,
+
+    /// Decrypt this file, written by `encrypt --file`, with the key the shares protect
+    #[arg(long, value_name = "PATH", requires = "output")]
+    file: Option<PathBuf>,
+
+    /// Where to write the decrypted file
+    #[arg(long, value_name = "PATH", requires = "file")]
+    output: Option<PathBuf>,
+
+    /// For SSH key sets, write the recovered key to this path (mode 0600)
+    #[arg(long, value_name = "PATH")]
+    ssh_out: Option<PathBuf>,
+
+    /// For SSH key sets, check the recovered key against this OpenSSH public key file
+    #[arg(long, value_name = "PATH")]
+    ssh_public: Option<PathBuf>,
+
+    /// For GPG key sets, write the recovered armored key to this path (mode 0600)
+    #[arg(long, value_name = "PATH")]
+    gpg_out: Option<PathBuf>,
+
+    /// For GPG key sets, import the recovered key with `gpg --import` and check it is listed
+    #[arg(long, conflicts_with = "gpg_out")]
+    gpg_import: bool,
+
+    /// For age identity sets, write the recovered identity file to this path (mode 0600)
+    #[cfg(feature = "age")]
+    #[arg(long, value_name = "PATH")]
+    age_identity_out: Option<PathBuf>,
+
+    /// For .env sets, write the recovered file, byte for byte, to this path (mode 0600)
+    #[arg(long, value_name = "PATH")]
+    env_out: Option<PathBuf>,
+
+    /// For .env sets, print `export NAME='value'` lines alone on stdout instead, for
+    /// `eval "$(sss decrypt --export)"`
+    #[arg(long, conflicts_with = "env_out")]
+    export: bool,
+
+    /// With --export, export only this variable (repeatable)
+    #[arg(long = "env-var", value_name = "NAME", requires = "export")]
+    env_vars: Vec<String>,
+
+    /// Field the shares were made over: gf256, secp256k1, vault (base64 or hex unseal keys) or
+    /// pedersen
+    #[arg(long, value_name = "FIELD", default_value = "gf256")]
+    field: Field,
+
+    /// Share format: sss, or ssss for `index-hex` lines from ssss-split. ssss shares cannot be
+    /// checked, so give exactly the threshold number of them
+    #[arg(long, value_name = "FORMAT", default_value = "sss", conflicts_with = "field")]
+    format: ShareFormat,
+
+    /// With --format ssss, the shares were made without ssss's diffusion layer (ssss-split -D)
+    #[arg(long)]
+    no_diffusion: bool,
+
+    /// Fingerprint printed when the set was split; with it a wrong password is told apart from
+    /// missing or foreign shares
+    #[arg(long = "set", value_name = "FINGERPRINT")]
+    expected_set: Option<String>,
+
+    /// Shares the set needs, so too few are reported before asking for the password
+    #[arg(long = "threshold", value_name = "N")]
+    expected_threshold: Option<u8>,
+
+    /// A verified capability token restricting this recovery to one set, spent in its ledger
+    /// once the shares are found to rebuild that set
+    #[arg(skip)]
+    authorized_set: Option<(token::Capability, token::Ledger)>,
+
+    /// Type shares without echoing them, like the password
+    #[arg(long)]
+    hide_input: bool,
+
+    /// Identity file from `sss pq keygen` that opens post-quantum encrypted share files
+    #[cfg(feature = "pq")]
+    #[arg(long, value_name = "FILE")]
+    pq_identity: Option<PathBuf>,
+
+    /// Recover a time-locked set before its date
+    #[arg(long)]
+    ignore_time_lock: bool,
+
+    /// Roughtime server (HOST:PORT) to take the date from, for sets whose time lock names one
+    #[arg(long, value_name = "HOST:PORT", conflicts_with = "ignore_time_lock")]
+    roughtime: Option<String>,
+
+    /// For sets behind a dead man's switch, the dealer's approval: a token from `sss token issue`
+    #[arg(long, value_name = "TOKEN")]
+    approval: Option<String>,
+
+    /// For sets behind a dead man's switch, the dealer's latest heartbeat, which must have lapsed
+    #[arg(long, value_name = "FILE", conflicts_with = "approval")]
+    heartbeat: Option<PathBuf>,
+
+    /// Print a recovered seed phrase as ur:crypto-seed and byte secrets as ur:bytes
+    #[arg(long)]
+    ur: bool,
+
+    /// Read the shares as QR codes from the webcam, going on by itself once the threshold is in
+    #[arg(long, conflicts_with_all = ["format", "hide_input"])]
+    scan: bool,
+
+    /// Pull the shares from the backends of the placement file they were pushed with
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["format", "scan"])]
+    placement: Option<PathBuf>,
+
+    /// Read a share from this file instead of asking for it (repeatable)
+    #[arg(
+        long = "share",
+        value_name = "FILE",
+        conflicts_with_all = ["scan", "placement"],
+        add = ArgValueCompleter::new(cli::complete::share_files)
+    )]
+    shares: Vec<PathBuf>,
+
+    /// Read the shares from this file (`-` for standard input): each on its own line as typed at
+    /// the prompt, with any header lines above it. Without --share or this, shares are read from
+    /// standard input when it is not a terminal
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["scan", "placement"])]
+    shares_file: Option<PathBuf>,
+
+    /// Enter the shares and password on a full-screen ceremony screen instead of prompts
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with_all = ["hide_input", "scan", "placement", "shares", "shares_file"])]
+    tui: bool,
+
+    /// The password the ceremony screen took, asked for no more
+    #[arg(skip)]
+    entered_password: Option<Zeroizing<String>>,
+
+    /// Camera to scan with, such as /dev/video1
+    #[arg(long, value_name = "DEVICE", requires = "scan")]
+    camera: Option<PathBuf>,
+
+    /// Write the recovered secret to this file (mode 0600) instead of printing it
+    #[arg(long, value_name = "PATH")]
+    secret_out: Option<PathBuf>,
+
+    /// Once the secret has been read, clear the screen and scrollback on Enter
+    #[arg(long)]
+    clear_screen: bool,
+
+    #[command(flatten)]
+    pkcs11: Pkcs11Args,
+
+    #[command(flatten)]
+    clipboard: ClipboardArgs,
+
+    #[command(flatten)]
+    password_source: PasswordSourceArgs,
+}
+
+impl CombineArgs {
+    /// Whether the recovered secret goes to a file or the clipboard rather than the terminal.
+    fn secret_elsewhere(&self) -> bool {
+        self.secret_out.is_some() || self.clipboard.enabled()
+    }
+}
+
+fn main() {
+    // a shell asking for completions is answered before anything else runs
+    clap_complete::CompleteEnv::with_factory(Cli::command).var(cli::complete::VAR).complete();
+    let cli = Cli::parse();
+    if let Err(e) = run(cli) {
+        eprintln!("{}", tr!("error", message = e));
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.lang.as_deref() {
+        Some(language) => i18n::set_language(language)?,
+        None => match i18n::from_environment().map(|locale| i18n::load(&locale)) {
+            Some(Ok(Some(catalog))) => i18n::use_catalog(catalog),
+            Some(Err(e)) => eprintln!("Ignoring the translation for this locale: {}", e),
+            _ => {}
+        },
+    }
+    if cli.secure_memory {
+        secure::enable_memory_locking();
+    }
+    cli::output::set_quiet(cli.quiet);
+    // stdout holds nothing but the JSON document, from the first message on
+    cli::output::set_json(cli.output_format == OutputFormat::Json);
+    secure::set_write_policy(WritePolicy { force: cli.force, atomic: cli.atomic, shred: cli.shred });
+    #[cfg(unix)]
+    if let Some(fd) = cli.progress_fd {
+        let events = std::fs::OpenOptions::new()
+            .write(true)
+            .open(format!("/dev/fd/{}", fd))
+            .map_err(|e| SssError::Io(std::io::Error::new(e.kind(), format!("progress fd {}: {}", fd, e))))?;
+        shamir_cli::progress::report_to(events);
+    }
+    #[cfg(feature = "telemetry")]
+    let _telemetry = match cli.otlp_endpoint.as_deref() {
+        Some(endpoint) => Some(shamir_cli::telemetry::init(Some(endpoint))?),
+        None if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_some() => {
+            Some(shamir_cli::telemetry::init(None)?)
+        }
+        None => None,
+    };
+    let audit_log = cli.audit_log.or_else(|| std::env::var_os("SSS_AUDIT_LOG").map(PathBuf::from));
+    let ceremony_key = cli.ceremony_key.as_deref().map(token::read_signing_key).transpose()?;
+    let records = Records {
+        transcript: cli.transcript.as_deref(),
+        audit_log: audit_log.as_deref(),
+        ceremony: cli.ceremony_record.as_deref().map(|path| (path, ceremony_key.as_ref())),
+    };
+    let audit_log = audit_log.as_deref();
+    let json = cli.output_format == OutputFormat::Json;
+    match cli.command {
+        Some(Command::Encrypt(args)) => {
+            let args = with_profile(*args)?;
+            let mut session = new_session(records, "encrypt")?;
+            if json {
+                session.report_json();
+            }
+            let result = encrypt_flow(&mut session, &args)
+                .and_then(|_| clear_screen_after(&mut session, args.clear_screen && !json));
+            session.finish(&result)?;
+            result
+        }
+        Some(Command::Batch { input, out_dir, kdf_rounds, min_password_score }) => {
+            let mut session = new_session(records, "batch")?;
+            if json {
+                session.report_json();
+            }
+            let args = SplitArgs { kdf_rounds, min_password_score, ..SplitArgs::default() };
+            let result = batch_flow(&mut session, &input, &out_dir, &args);
+            session.finish(&result)?;
+            result
+        }
+        Some(Command::Plan { file, dry_run, limit, encoding, out_dir, kdf_rounds }) => {
+            let mut session = new_session(records, "plan")?;
+            if json {
+                session.report_json();
+            }
+            let args = SplitArgs { encoding, out_dir, kdf_rounds, ..SplitArgs::default() };
+            let result = plan_flow(&mut session, &file, dry_run, limit, args);
+            session.finish(&result)?;
+            result
+        }
+        Some(Command::Migrate { saved, salt, nonce, total, threshold, encoding, out_dir, kdf_rounds }) => {
+            let saved = match (saved, salt, nonce) {
+                (Some(path), _, _) => Some(legacy::Saved::parse(&std::fs::read_to_string(path)?)?),
+                (None, Some(salt), Some(nonce)) => Some(legacy::Saved::from_hex(&salt, &nonce)?),
+                _ => None,
+            };
+            let mut session = new_session(records, "migrate")?;
+            if json {
+                session.report_json();
+            }
+            let args = SplitArgs { total, threshold, encoding, out_dir, kdf_rounds, ..SplitArgs::default() };
+            let result = migrate_flow(&mut session, saved.as_ref(), args);
+            session.finish(&result)?;
+            result
+        }
+        Some(Command::RotatePassword {
+            expected_set,
+            total,
+            threshold,
+            encoding,
+            out_dir,
+            kdf_rounds,
+            min_password_score,
+        }) => {
+            let mut session = new_session(records, "rotate-password")?;
+            if json {
+                session.report_json();
+            }
+            let args = SplitArgs {
+                total,
+                threshold,
+                encoding,
+                out_dir,
+                kdf_rounds,
+                min_password_score,
+                ..SplitArgs::default()
+            };
+            let result = rotate_password_flow(&mut session, expected_set, args);
+            session.finish(&result)?;
+            result
+        }
+        Some(Command::Decrypt(mut args)) => {
+            let mut session = new_session(records, "decrypt")?;
+            if json {
+                session.report_json();
+            } else if args.export {
+                // stdout is left to the export lines, so the shell can eval them
+                cli::output::set_json(true);
+            }
+            let result = decrypt_flow(&mut session, &mut args);
+            session.finish(&result)?;
+            result
+        }
+        #[cfg(feature = "coordinator")]
+        Some(Command::Coordinate { listen, issuer_key, token_ledger, wasm }) => {
+            let mut session = new_session(records, "coordinate")?;
+            let result = issuer_key
+                .as_deref()
+                .map(token::parse_verifying_key)
+                .transpose()
+                .and_then(|issuer| {
+                    let authority = issuer.map(|key| (key, token::Ledger::new(token_ledger)));
+                    cli::coordinate::coordinate_flow(&mut session, listen, &wasm, authority.as_ref())
+                });
+            session.finish(&result)?;
+            result
+        }
+        #[cfg(feature = "server")]
+        Some(Command::Serve { listen, tls_cert, tls_key, token_file, workers, issuer_key, token_ledger }) => {
+            let authority = issuer_key.as_deref().map(token::parse_verifying_key).transpose()?;
+            let authority = authority.map(|key| (key, token::Ledger::new(token_ledger)));
+            serve_command(listen, tls_cert.zip(tls_key), token_file, workers, authority)
+        }
+        #[cfg(feature = "smtp")]
+        Some(Command::Distribute { smtp, smtp_user, from, to, shares, cards, log }) => {
+            distribute_command(&smtp, smtp_user, &from, &to, &shares, &cards, &log)
+        }
+        #[cfg(any(feature = "age", feature = "pq"))]
+        Some(Command::Rewrap { file, identity, new_key }) => rewrap_command(&file, &identity, &new_key),
+        #[cfg(all(unix, feature = "daemon"))]
+        Some(Command::Daemon { socket, allow_uid }) => daemon_command(socket, &allow_uid),
+        Some(Command::BenchKdf { target_ms, no_save }) => bench_kdf_command(target_ms, no_save, json),
+        Some(Command::Bench { sizes, kdf_rounds, threshold, total, min_ms }) => {
+            let plan = bench::Plan {
+                sizes,
+                rounds: kdf_rounds,
+                threshold,
+                total,
+                min_time: std::time::Duration::from_millis(min_ms),
+            };
+            bench_command(&plan, json)
+        }
+        Some(Command::TestVectors(command)) => test_vectors_command(command, json),
+        Some(Command::Replay { file, all }) => replay(&file, all),
+        #[cfg(feature = "keyring")]
+        Some(Command::Keyring(command)) => keyring_command(command),
+        Some(Command::Tpm(command)) => tpm_command(command),
+        Some(Command::Yubikey(command)) => yubikey_command(command),
+        Some(Command::Stego(command)) => stego_command(command),
+        #[cfg(feature = "pkcs11")]
+        Some(Command::Pkcs11(command)) => pkcs11_command(command),
+        #[cfg(feature = "pq")]
+        Some(Command::Pq(command)) => pq_command(command),
+        Some(Command::Token(command)) => token_command(command),
+        Some(Command::Locale(command)) => locale_command(command),
+        Some(Command::Heartbeat { key, fingerprint, valid_days, out }) => {
+            let key = token::read_signing_key(&key)?;
+            let valid_for = std::time::Duration::from_secs(valid_days.saturating_mul(86_400));
+            let heartbeat = Heartbeat::sign(&key, &fingerprint, valid_for);
+            heartbeat.write(&out)?;
+            println!(
+                "Wrote a heartbeat for set {} to {}; it holds the switch shut until {}",
+                heartbeat.fingerprint,
+                out.display(),
+                timelock::format_date(heartbeat.expires_at)
+            );
+            Ok(())
+        }
+        Some(Command::Guardian(command)) => guardian_command(command),
+        Some(Command::Recover(command)) => recover_command(command, records, json),
+        Some(Command::CheckIn { heartbeat, dealer_key }) => check_in(&heartbeat, &dealer_key),
+        Some(Command::Tdec(command)) => tdec_command(command),
+        Some(Command::Vault(command)) => {
+            let mut session = new_session(records, "vault resplit")?;
+            let result = vault_command(&mut session, command);
+            session.finish(&result)?;
+            result
+        }
+        Some(Command::Horcrux(command)) => horcrux_command(command),
+        Some(Command::Fragment(command)) => fragment_command(command),
+        Some(Command::SubSplit { share, threshold, total, encoding, out_dir }) => {
+            sub_split(&share, threshold, total, encoding.unwrap_or_default(), &out_dir)
+        }
+        Some(Command::Envelope(command)) => envelope_command(command),
+        Some(Command::Manifest(command)) => manifest_command(command, json),
+        Some(Command::Receipts(command)) => receipts_command(command),
+        Some(Command::Log(command)) => log_command(command, audit_log),
+        Some(Command::QrExport { file, fragment_len, fps, frames, ur }) => {
+            qr_export(&file, fragment_len, fps, frames, ur)
+        }
+        Some(Command::Inspect { files, field }) => inspect_command(&files, field, json),
+        Some(Command::Doctor { files, field, saved, salt, nonce, passwords }) => {
+            let saved = match (saved, salt, nonce) {
+                (Some(path), _, _) => Some(legacy::Saved::parse(&std::fs::read_to_string(path)?)?),
+                (None, Some(salt), Some(nonce)) => Some(legacy::Saved::from_hex(&salt, &nonce)?),
+                _ => None,
+            };
+            doctor_command(&files, field, saved, passwords.as_deref(), json)
+        }
+        Some(Command::Completions { shell }) => {
+            use std::io::Write;
+
+            let exe = std::env::current_exe()?;
+            std::io::stdout().write_all(&shell.registration(&exe.to_string_lossy())?)?;
+            Ok(())
+        }
+        #[cfg(feature = "frost")]
+        Some(Command::Frost(command)) => frost_command(command),
+        None => {
+            let mut session = new_session(records, "interactive")?;
+            let mode = session.select("Choose operation", &["Encrypt", "Decrypt"])?;
+            let result = match mode {
+                0 => with_profile(SplitArgs::default()).and_then(|args| encrypt_flow(&mut session, &args)),
+                1 => decrypt_flow(&mut session, &mut CombineArgs::default()),
+                _ => unreachable!(),
+            };
+            session.finish(&result)?;
+            result
+        }
+    }
+}
+
+/// Where a session's transcript, audit record and ceremony record go, from the global options.
+#[derive(Clone, Copy)]
+struct Records<'a> {
+    transcript: Option<&'a std::path::Path>,
+    audit_log: Option<&'a std::path::Path>,
+    ceremony: Option<(&'a std::path::Path, Option<&'a ed25519_dalek::SigningKey>)>,
+}
+
+/// A session for `operation`, audited when an audit log is set.
+fn new_session(records: Records, operation: &str) -> Result<Session> {
+    let mut session = Session::new(records.transcript, operation)?;
+    if let Some(path) = records.audit_log {
+        session.audit_to(path);
+    }
+    if let Some((path, key)) = records.ceremony {
+        session.ceremony_to(path, key.cloned());
+    }
+    Ok(session)
+}
+
+fn read_secret(session: &mut Session, prompt: &str, kind: SecretKind) -> Result<SecretType> {
+    let mut secret: String = session.ask_secret(prompt, |p| {
+        Input::<String>::new().with_prompt(p).interact()
+    })?;
+
+    let secret_data = SecretType::parse_as(&secret, kind);
+    secret.zeroize();
+    secret_data
+}
+
+fn read_ssh_key(path: &std::path::Path) -> Result<SecretType> {
+    let text = Zeroizing::new(std::fs::read_to_string(path)?);
+    let info = ssh::inspect(&text)?;
+    say!(
+        "Read {} {} key{}{}",
+        info.format,
+        info.algorithm.as_deref().unwrap_or("private"),
+        if info.encrypted { " (passphrase-protected)" } else { "" },
+        info.fingerprint.map(|f| format!(", fingerprint {}", f)).unwrap_or_default()
+    );
+    Ok(SecretType::SshKey(text.trim_end().to_string()))
+}
+
+fn read_env_file(path: &std::path::Path) -> Result<SecretType> {
+    let text = Zeroizing::new(std::fs::read_to_string(path)?);
+    let variables = dotenv::parse(&text)?;
+    if variables.is_empty() {
+        return Err(SssError::Parse(format!("{} defines no variables", path.display())));
+    }
+    let names: Vec<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+    say!("Read {} variables from {}: {}", names.len(), path.display(), names.join(", "));
+    Ok(SecretType::EnvFile(text.to_string()))
+}
+
+#[cfg(feature = "age")]
+fn read_age_identity(path: &std::path::Path) -> Result<SecretType> {
+    let text = Zeroizing::new(std::fs::read_to_string(path)?);
+    for recipient in shamir_cli::age::identity_recipients(&text)? {
+        say!("Read age identity for {}", recipient);
+    }
+    Ok(SecretType::AgeIdentity(text.trim_end().to_string()))
+}
+
+/// Recovers an age identity set, printing the recipients it decrypts for so they can be
+/// compared with the published ones.
+#[cfg(feature = "age")]
+fn recover_age_identity(session: &mut Session, identity: &str, args: &CombineArgs) -> Result<()> {
+    let recipients = shamir_cli::age::identity_recipients(identity);
+    session.validation(
+        "age identity",
+        recipients.is_ok(),
+        match &recipients {
+            Ok(r) => r.join(", "),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    say!();
+    for recipient in recipients? {
+        say!("Identity for {}", recipient);
+    }
+    match &args.age_identity_out {
+        Some(out) => {
+            let mut contents = Zeroizing::new(identity.to_string());
+            contents.push('\n');
+            write_private(out, contents.as_bytes())?;
+            say!("Wrote the recovered identity to {} (mode 0600)", out.display());
+            session.report("written", out.display().to_string());
+        }
+        None => reveal(session, args, identity)?,
+    }
+    Ok(())
+}
+
+/// Mails each custodian their share, reporting every delivery and failing if any did not go.
+#[cfg(feature = "smtp")]
+fn distribute_command(
+    url: &str,
+    user: Option<String>,
+    from: &str,
+    to: &[String],
+    shares: &std::path::Path,
+    cards: &std::path::Path,
+    log: &std::path::Path,
+) -> Result<()> {
+    use shamir_cli::mail;
+
+    let deliveries = mail::plan(shares, cards.is_dir().then_some(cards), to)?;
+    let login = match user {
+        Some(user) => {
+            let password = match std::env::var("SSS_SMTP_PASSWORD") {
+                Ok(password) => password,
+                Err(_) => cli::console::password(&format!("SMTP password for {}", user), false)
+                    .map_err(cli::session::prompt_error)?,
+            };
+            Some((user, password))
+        }
+        None => None,
+    };
+    let transport = mail::connect(url, login)?;
+    let entries = mail::send_all(&transport, from, &deliveries, log)?;
+    for entry in &entries {
+        let status = if entry.sent { "sent" } else { "FAILED" };
+        println!("  share #{} -> {}: {} ({})", entry.share, entry.to, status, entry.detail);
+    }
+    let failed = entries.iter().filter(|entry| !entry.sent).count();
+    if failed > 0 {
+        return Err(SssError::Store(format!(
+            "{} of {} mails were not sent; see {}",
+            failed,
+            entries.len(),
+            log.display()
+        )));
+    }
+    say!("Mailed {} shares; the log is in {}", entries.len(), log.display());
+    Ok(())
+}
+
+/// Writes each share, as printed, to `dir/share-<index>.age` encrypted to its recipient.
+#[cfg(feature = "age")]
+fn write_age_shares(
+    shares: &[Vec<u8>],
+    recipients: &[::age::x25519::Recipient],
+    args: &SplitArgs,
+    dir: &std::path::Path,
+) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    say!("\nShares encrypted to age recipients:");
+    for (share, recipient) in shares.iter().zip(recipients) {
+        let encoded = Zeroizing::new(args.encoding.unwrap_or_default().encode_with_parity(share, args.parity)?);
+        let text = Zeroizing::new(format!("{}\n", encoded.as_str()));
+        let path = dir.join(format!("share-{}.age", share[0]));
+        write_private(&path, shamir_cli::age::encrypt_armored(recipient, text.as_bytes())?.as_bytes())?;
+        say!("  share #{} -> {} ({})", share[0], recipient, path.display());
+        written.push(path);
+    }
+    say!("Custodians open theirs with `age -d -i <identity file> share-N.age`.");
+    Ok(written)
+}
+
+#[cfg(feature = "pq")]
+fn write_pq_shares(
+    shares: &[Vec<u8>],
+    recipients: &[shamir_cli::pq::Recipient],
+    args: &SplitArgs,
+    dir: &std::path::Path,
+) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    say!("\nShares encrypted to post-quantum keys:");
+    for (share, recipient) in shares.iter().zip(recipients) {
+        let encoded = Zeroizing::new(args.encoding.unwrap_or_default().encode_with_parity(share, args.parity)?);
+        let text = Zeroizing::new(format!("{}\n", encoded.as_str()));
+        let path = dir.join(format!("share-{}{}", share[0], shamir_cli::pq::SEALED_SUFFIX));
+        write_private(&path, shamir_cli::pq::seal(recipient, text.as_bytes(), &mut rand::rngs::OsRng)?.as_bytes())?;
+        say!("  share #{} -> key {} ({})", share[0], recipient.id(), path.display());
+        written.push(path);
+    }
+    say!("Custodians give `@share-N.pq` to `sss decrypt --pq-identity <identity file>`.");
+    Ok(written)
+}
+
+/// Opens a custodian's encrypted share with their old identity and encrypts it to `new_key`
+/// in its place. Moving between age and post-quantum keys changes the file's extension.
+#[cfg(any(feature = "age", feature = "pq"))]
+fn rewrap_command(file: &std::path::Path, identity: &std::path::Path, new_key: &str) -> Result<()> {
+    let text = open_wrapped(file, identity)?;
+    let share = input::parse_share(&text)
+        .map_err(|e| SssError::Parse(format!("{} does not hold a share: {}", file.display(), e)))?;
+    let (sealed, extension) = wrap_to(new_key, text.as_bytes())?;
+    let target = file.with_extension(extension);
+    // the file is replaced where it lies, and never left half written
+    secure::set_write_policy(WritePolicy { force: true, atomic: true, ..secure::write_policy() });
+    write_private(&target, sealed.as_bytes())?;
+    if target != file {
+        secure::discard(file);
+    }
+    say!("Share #{} in {} is now encrypted to {}", share[0], target.display(), new_key.trim());
+    Ok(())
+}
+
+#[cfg(any(feature = "age", feature = "pq"))]
+fn open_wrapped(file: &std::path::Path, identity: &std::path::Path) -> Result<Zeroizing<String>> {
+    #[cfg(feature = "pq")]
+    if shamir_cli::pq::is_sealed(file) {
+        return shamir_cli::pq::read(file, &shamir_cli::pq::Identity::read(identity)?);
+    }
+    #[cfg(feature = "age")]
+    if file.extension().is_some_and(|extension| extension == "age") {
+        let identities = shamir_cli::age::identities(&Zeroizing::new(std::fs::read_to_string(identity)?))?;
+        let plaintext = shamir_cli::age::decrypt(&identities, &std::fs::read(file)?)?;
+        return String::from_utf8(plaintext.to_vec())
+            .map(Zeroizing::new)
+            .map_err(|_| SssError::Parse(format!("{} does not hold text", file.display())));
+    }
+    Err(SssError::InvalidArgument(format!("{} is not a .age or .pq share file", file.display())))
+}
+
+/// `text` encrypted to `key`, and the extension of a file holding it.
+#[cfg(any(feature = "age", feature = "pq"))]
+fn wrap_to(key: &str, text: &[u8]) -> Result<(String, &'static str)> {
+    #[cfg(feature = "pq")]
+    if key.trim().starts_with(shamir_cli::pq::PUBLIC_PREFIX) {
+        let recipient = shamir_cli::pq::Recipient::parse(key)?;
+        return Ok((shamir_cli::pq::seal(&recipient, text, &mut rand::rngs::OsRng)?, "pq"));
+    }
+    #[cfg(feature = "age")]
+    if key.trim().starts_with("age1") {
+        return Ok((shamir_cli::age::encrypt_armored(&shamir_cli::age::recipient(key)?, text)?, "age"));
+    }
+    Err(SssError::InvalidArgument(format!("'{}' is not an age1... or sss-pq1-... key", key.trim())))
+}
+
+fn read_gpg_key(armored: Zeroizing<String>) -> Result<SecretType> {
+    let fingerprint = gpg::fingerprint(&armored)?;
+    say!("Read GPG secret key {}", fingerprint);
+    Ok(SecretType::GpgKey { fingerprint, armored: armored.trim_end().to_string() })
+}
+
+/// Recovers a GPG key set: the fingerprint of the recovered key block must match the one
+/// recorded when it was split before the key is written, imported or printed.
+fn recover_gpg_key(session: &mut Session, fingerprint: &str, armored: &str, args: &CombineArgs) -> Result<()> {
+    let found = gpg::fingerprint(armored)?;
+    let ok = found == fingerprint;
+    session.validation("gpg fingerprint", ok, format!("{} against {}", found, fingerprint))?;
+    if !ok {
+        return Err(SssError::Parse(format!(
+            "recovered key is {} but the shares record {}",
+            found, fingerprint
+        )));
+    }
+    say!("\nFingerprint {} matches the shares", found);
+    if let Some(out) = &args.gpg_out {
+        let mut contents = Zeroizing::new(armored.to_string());
+        contents.push('\n');
+        write_private(out, contents.as_bytes())?;
+        say!("Wrote the recovered key to {} (mode 0600)", out.display());
+        session.report("written", out.display().to_string());
+    } else if args.gpg_import {
+        gpg::import(armored, fingerprint)?;
+        say!("Imported secret key {} into the gpg keyring", fingerprint);
+        session.report("imported", fingerprint);
+    } else {
+        reveal(session, args, armored)?;
+    }
+    Ok(())
+}
+
+/// Recovers an SSH key set: checks the key against the original public key when one is given,
+/// then writes it out with owner-only permissions.
+fn recover_ssh_key(session: &mut Session, key: &str, args: &CombineArgs) -> Result<()> {
+    let info = ssh::inspect(key)?;
+    if let Some(public) = &args.ssh_public {
+        let expected = ssh::public_fingerprint(&std::fs::read_to_string(public)?)?;
+        let found = info.fingerprint.ok_or_else(|| {
+            SssError::Parse("only OpenSSH-format keys can be checked against a public key".into())
+        })?;
+        let ok = found == expected;
+        session.validation("ssh fingerprint", ok, format!("{} against {}", found, public.display()))?;
+        if !ok {
+            return Err(SssError::Parse(format!(
+                "recovered key is {} but {} is {}",
+                found,
+                public.display(),
+                expected
+            )));
+        }
+        say!("\nFingerprint {} matches {}", found, public.display());
+    }
+    match &args.ssh_out {
+        Some(out) => {
+            ssh::write_key(out, key)?;
+            say!("Wrote the recovered key to {} (mode 0600)", out.display());
+            session.report("written", out.display().to_string());
+        }
+        None => reveal(session, args, key)?,
+    }
+    Ok(())
+}
+
+/// Recovers a .env set: the file itself, `export` lines for the shell, or the text on screen.
+fn recover_env_file(session: &mut Session, text: &str, args: &CombineArgs) -> Result<()> {
+    if let Some(out) = &args.env_out {
+        write_private(out, text.as_bytes())?;
+        say!("Wrote the recovered .env file to {} (mode 0600)", out.display());
+        session.report("written", out.display().to_string());
+        return Ok(());
+    }
+    if !args.export {
+        return reveal(session, args, text);
+    }
+    let variables = dotenv::parse(text)?;
+    let selected = dotenv::select(&variables, &args.env_vars)?;
+    for variable in &selected {
+        println!("{}", dotenv::export_line(variable).as_str());
+    }
+    session.validation("export", true, format!("{} variables", selected.len()))
+}
+
+/// Asks for a seed phrase until it passes the wordlist and checksum checks.
+fn read_mnemonic(session: &mut Session) -> Result<SecretType> {
+    loop {
+        let phrase = Zeroizing::new(session.ask_secret("Enter the BIP-39 seed phrase", |p| {
+            cli::console::password(p, false)
+        })?);
+        match SecretType::mnemonic(&phrase) {
+            Ok(secret) => return Ok(secret),
+            Err(e) => {
+                eprintln!("{}", e);
+                // the message can quote a word of the phrase, so keep it out of the transcript
+                session.validation("seed phrase", false, "rejected".to_string())?;
+            }
+        }
+    }
+}
+
+fn read_bundle(session: &mut Session) -> Result<SecretType> {
+    let mut entries: Vec<(String, SecretType)> = Vec::new();
+    loop {
+        let prompt = format!("Label for secret #{} (empty to finish)", entries.len() + 1);
+        let label: String = session.ask(&prompt, |p| {
+            Input::<String>::new()
+                .with_prompt(p)
+                .allow_empty(true)
+                .interact_text()
+        })?;
+        let label = label.trim().to_string();
+        if label.is_empty() {
+            break;
+        }
+        if entries.iter().any(|(l, _)| *l == label) {
+            eprintln!("Label '{}' is already used", label);
+            continue;
+        }
+        let prompt = format!("Value for '{}' (prefix hex:/base64: for binary, json: for a JSON object)", label);
+        let secret = read_secret(session, &prompt, SecretKind::Auto)?;
+        entries.push((label, secret));
+    }
+    SecretType::bundle(entries)
+}
+
+fn encrypt_file(path: &std::path::Path, out: Option<&std::path::Path>) -> Result<SecretType> {
+    let out = match out {
+        Some(out) => out.to_path_buf(),
+        None => {
+            let mut name = path.as_os_str().to_owned();
+            name.push(".sss");
+            PathBuf::from(name)
+        }
+    };
+    let key = stream::generate_content_key();
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let len = secure::write_private_with(&out, |writer| {
+        stream::encrypt_stream(reader, std::io::BufWriter::new(writer), &key)
+    })?;
+    say!("Encrypted {} bytes of {} to {}", len, path.display(), out.display());
+    say!("The shares below protect its key; keep the encrypted file alongside them.");
+    Ok(SecretType::Bytes(key.to_vec()))
+}
+
+fn decrypt_file(key: &[u8], path: &std::path::Path, out: &std::path::Path) -> Result<()> {
+    let key: &[u8; stream::CONTENT_KEY_LEN] = key
+        .try_into()
+        .map_err(|_| SssError::Parse("these shares do not protect a file key".into()))?;
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let len = secure::write_private_with(out, |writer| {
+        stream::decrypt_stream(reader, std::io::BufWriter::new(writer), key)
+    })?;
+    say!("\nDecrypted {} bytes to {}", len, out.display());
+    Ok(())
+}
+
+fn read_split_secret(session: &mut Session, args: &SplitArgs) -> Result<SecretType> {
+    if let Some(path) = &args.file {
+        return encrypt_file(path, args.ciphertext_out.as_deref());
+    }
+    if args.multi {
+        return read_bundle(session);
+    }
+    if args.bip39 {
+        return read_mnemonic(session);
+    }
+    if let Some(path) = &args.ssh_key {
+        return read_ssh_key(path);
+    }
+    if let Some(path) = &args.gpg_key {
+        return read_gpg_key(Zeroizing::new(std::fs::read_to_string(path)?));
+    }
+    if let Some(key_id) = &args.gpg_export {
+        return read_gpg_key(gpg::export(key_id)?);
+    }
+    if let Some(path) = &args.env_file {
+        return read_env_file(path);
+    }
+    #[cfg(feature = "age")]
+    if let Some(path) = &args.age_identity {
+        return read_age_identity(path);
+    }
+    if let Some(source) = args.secret_source.source() {
+        let secret = source.read()?;
+        session.validation("secret", true, format!("read from {}", source))?;
+        return SecretType::parse_as(&secret, args.secret_type);
+    }
+    read_secret(session, &tr!("prompt-secret"), args.secret_type)
+}
+
+/// The generator behind a split: the OS one, or for `--deterministic-seed` one seeded from it,
+/// once the user has been warned.
+fn split_rng(session: &mut Session, args: &SplitArgs) -> Result<Box<dyn RngCore>> {
+    match &args.deterministic_seed {
+        None => Ok(Box::new(rand::rngs::OsRng)),
+        Some(seed) => {
+            eprintln!(
+                "WARNING: --deterministic-seed is for testing. The shares, salt and nonce follow from \
+                 the seed, so never use it for a real secret."
+            );
+            session.validation("randomness", true, "deterministic seed for testing")?;
+            Ok(Box::new(crypto::seeded_rng(seed)))
+        }
+    }
+}
+
+/// A secret read and padded for a split, with the password it goes under, waiting for the
+/// threshold that its set binding names.
+enum Sealing {
+    /// Already the payload to split: a secp256k1 key, shared as it is.
+    Done(Zeroizing<Vec<u8>>),
+    /// Encrypted with the HSM key named `label`.
+    #[cfg(feature = "pkcs11")]
+    Hsm { plaintext: Zeroizing<Vec<u8>>, label: String },
+    Password { plaintext: Zeroizing<Vec<u8>>, password: Zeroizing<String>, rounds: u32 },
+    /// The plaintext `rotate-password` opened, which stays in [`SplitArgs::resealed`].
+    Resealed { password: Zeroizing<String>, rounds: u32 },
+    Decoy {
+        plaintext: Zeroizing<Vec<u8>>,
+        password: Zeroizing<String>,
+        decoy: Zeroizing<Vec<u8>>,
+        decoy_password: Zeroizing<String>,
+        rounds: u32,
+    },
+}
+
+impl Sealing {
+    /// Encrypts the secret under a fresh set ID bound to `threshold`, returning the payload.
+    fn seal(
+        self,
+        session: &mut Session,
+        args: &SplitArgs,
+        threshold: u8,
+        rng: &mut dyn RngCore,
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        match self {
+            Sealing::Done(payload) => Ok(payload),
+            #[cfg(feature = "pkcs11")]
+            Sealing::Hsm { plaintext, label } => {
+                let conditions = conditions(args, bind(session, threshold, rng));
+                hsm_login(session, &args.pkcs11)?.seal(&label, &plaintext, &conditions, rng)
+            }
+            Sealing::Password { plaintext, password, rounds } if args.field == Field::Gf256 => {
+                let conditions = conditions(args, bind(session, threshold, rng));
+                seal_two_slots(args, &plaintext, &password, None, rounds, &conditions, rng)
+            }
+            Sealing::Password { plaintext, password, rounds } => {
+                let conditions = conditions(args, bind(session, threshold, rng));
+                pipeline::seal_locked(&plaintext, &password, rounds, &conditions, rng)
+            }
+            Sealing::Resealed { password, rounds } => {
+                let resealed = args.resealed.as_ref().expect("only rotate-password reseals");
+                let conditions = Conditions { binding: Some(bind(session, threshold, rng)), ..resealed.conditions };
+                seal_two_slots(args, &resealed.plaintext, &password, None, rounds, &conditions, rng)
+            }
+            Sealing::Decoy { plaintext, password, decoy, decoy_password, rounds } => {
+                let conditions = conditions(args, bind(session, threshold, rng));
+                let decoy = Some((&decoy[..], &decoy_password[..]));
+                seal_two_slots(args, &plaintext, &password, decoy, rounds, &conditions, rng)
+            }
+        }
+    }
+}
+
+/// Seals a GF(256) password set into two slots, the second holding `decoy` or random bytes,
+/// and adds the byte that two slots can fall short of `--share-size`.
+fn seal_two_slots(
+    args: &SplitArgs,
+    plaintext: &[u8],
+    password: &str,
+    decoy: Option<(&[u8], &str)>,
+    rounds: u32,
+    conditions: &Conditions,
+    rng: &mut dyn RngCore,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let mut payload = pipeline::seal_with_decoy(plaintext, password, decoy, rounds, conditions, rng)?;
+    // a share is the index byte and the payload
+    if args.share_size == Some(payload.len() + 2) {
+        let mut filler = [0u8];
+        rng.fill_bytes(&mut filler);
+        payload.push(filler[0]);
+    }
+    Ok(payload)
+}
+
+/// A fresh set ID for a set of `threshold` shares, recorded in the audit log.
+fn bind(session: &mut Session, threshold: u8, rng: &mut dyn RngCore) -> SetBinding {
+    let binding = SetBinding::new(threshold, rng);
+    session.audit_param("set_id", binding.set_id());
+    binding
+}
+
+/// Reads and pads the secret and reads its password, to be encrypted with [`Sealing::seal`]
+/// once the threshold is known.
+fn seal_secret(session: &mut Session, args: &SplitArgs) -> Result<Sealing> {
+    if args.resealed.is_some() {
+        let rounds = split_rounds(args)?;
+        let password = read_new_password(session, args, rounds, "new-encryption-password")?;
+        return Ok(Sealing::Resealed { password, rounds });
+    }
+    let secret_data = read_split_secret(session, args)?;
+
+    let padding = match args.share_size {
+        Some(size) => {
+            // the set ID is drawn at sealing; only the length of the header matters here
+            let placeholder = SetBinding { id: [0; SET_ID_LEN], threshold: 0, version: FORMAT_VERSION };
+            let slots = if args.field == Field::Gf256 { 2 } else { 1 };
+            Padding::Exact(plaintext_len_for_share_size(size, conditions(args, placeholder).header().len(), slots)?)
+        }
+        None => args.padding,
+    };
+    let mut serialized = serialize_secret(&secret_data);
+    drop(secret_data);
+    if args.compress {
+        serialized = compress(serialized)?;
+    }
+    if args.decoy {
+        return seal_with_decoy(session, args, &serialized, padding);
+    }
+    let bytes = pad(&serialized, padding)?;
+    drop(serialized);
+
+    #[cfg(feature = "pkcs11")]
+    if let Some(label) = &args.pkcs11_key {
+        return Ok(Sealing::Hsm { plaintext: bytes, label: label.clone() });
+    }
+    let rounds = split_rounds(args)?;
+    let password = read_new_password(session, args, rounds, "encryption-password")?;
+
+    Ok(Sealing::Password { plaintext: bytes, password, rounds })
+}
+
+fn conditions(args: &SplitArgs, binding: SetBinding) -> Conditions {
+    Conditions {
+        time_lock: args.not_before.map(|not_before| TimeLock { not_before, roughtime_key: args.roughtime_key }),
+        switch: args.dead_mans_switch.map(|key| Switch { dealer_key: key.to_bytes() }),
+        binding: Some(binding),
+    }
+}
+
+fn split_rounds(args: &SplitArgs) -> Result<u32> {
+    Ok(match args.kdf_rounds {
+        Some(rounds) => rounds,
+        None => calibration::load()?.map_or(crypto::PBKDF2_ROUNDS, |c| c.rounds),
+    })
+}
+
+/// Fills in what the command line leaves out from the config file's defaults and the chosen
+/// profile, leaving out settings that would clash with the flags given.
+fn with_profile(mut args: SplitArgs) -> Result<SplitArgs> {
+    let profile = Config::load()?.resolve(args.profile.as_deref())?;
+    if let Some(name) = &args.profile {
+        say!("Using profile {}", name);
+    }
+    #[cfg(feature = "age")]
+    let recipients = !args.age_recipients.is_empty();
+    #[cfg(not(feature = "age"))]
+    let recipients = false;
+    #[cfg(feature = "pq")]
+    let recipients = recipients || !args.pq_recipients.is_empty();
+    #[cfg(feature = "keyring")]
+    let keyring = args.keyring.is_some();
+    #[cfg(not(feature = "keyring"))]
+    let keyring = false;
+    #[cfg(feature = "pkcs11")]
+    let hsm = args.pkcs11_key.is_some();
+    #[cfg(not(feature = "pkcs11"))]
+    let hsm = false;
+
+    let plain = args.format == ShareFormat::Sss;
+    let counted_elsewhere = args.placement.is_some() || recipients;
+    if plain && !counted_elsewhere && args.total.is_none() && args.custodians.is_empty() {
+        args.custodians = profile.custodians;
+    }
+    if !counted_elsewhere && args.custodians.is_empty() {
+        args.total = args.total.or(profile.total);
+    }
+    args.threshold = args.threshold.or(profile.threshold);
+    if plain {
+        args.encoding = args.encoding.or(profile.encoding);
+    }
+    if plain && !hsm {
+        args.kdf_rounds = args.kdf_rounds.or(profile.kdf_rounds);
+    }
+    let written_elsewhere = counted_elsewhere
+        || keyring
+        || args.tpm_seal.is_some()
+        || args.yubikey.is_some()
+        || args.stego.is_some();
+    if plain && !written_elsewhere {
+        args.out_dir = args.out_dir.or(profile.out_dir);
+    }
+    Ok(args)
+}
+
+/// Asks for the decoy secret and its password after the real password, and pads both secrets
+/// alike to be encrypted into one payload.
+fn seal_with_decoy(session: &mut Session, args: &SplitArgs, serialized: &[u8], padding: Padding) -> Result<Sealing> {
+    if args.field != Field::Gf256 {
+        return Err(SssError::InvalidArgument("a decoy needs --field gf256".into()));
+    }
+    #[cfg(feature = "pkcs11")]
+    if args.pkcs11_key.is_some() {
+        return Err(SssError::InvalidArgument("a decoy needs a password, not an HSM key".into()));
+    }
+    let rounds = split_rounds(args)?;
+    let password = read_new_password(session, args, rounds, "encryption-password")?;
+    let prompt = "Enter the decoy secret, which the decoy password recovers instead";
+    let decoy = read_secret(session, prompt, SecretKind::Auto)?;
+    let mut decoy = serialize_secret(&decoy);
+    if args.compress {
+        decoy = compress(decoy)?;
+    }
+    let decoy_password = loop {
+        let decoy_password = read_new_password(session, args, rounds, "decoy-password")?;
+        if *decoy_password != *password {
+            break decoy_password;
+        }
+        eprintln!("The decoy password must differ from the encryption password");
+        session.validation("decoy password", false, "same as the encryption password")?;
+    };
+    let [plaintext, decoy] = pad_alike(serialized, &decoy, padding)?;
+    Ok(Sealing::Decoy { plaintext, password, decoy, decoy_password, rounds })
+}
+
+/// Asks for the password named `what` and its confirmation until they match, are not empty
+/// and meet `--min-password-score`. Custodian names and share labels count as easy to guess.
+fn read_new_password(session: &mut Session, args: &SplitArgs, rounds: u32, what: &str) -> Result<Zeroizing<String>> {
+    let context: Vec<&str> = args
+        .custodians
+        .iter()
+        .map(|c| c.name.as_str())
+        .chain(args.share_labels.iter().map(String::as_str))
+        .collect();
+    if let Some(source) = args.password_source.source().filter(|_| what != "decoy-password") {
+        let password = source.read()?;
+        let strength = strength::estimate(&password, &context, rounds);
+        if let Some(min) = args.min_password_score.filter(|&min| strength.score < min) {
+            session.validation("password strength", false, format!("score {} below {}", strength.score, min))?;
+            return Err(SssError::Policy(format!(
+                "the password from {} scores {}, below the required {}",
+                source, strength.score, min
+            )));
+        }
+        session.validation("password strength", true, format!("score {}, read from {}", strength.score, source))?;
+        return Ok(password);
+    }
+    loop {
+        let password = Zeroizing::new(session.ask_secret(&tr!(&format!("prompt-enter-{}", what)), |p| {
+            cli::console::password(p, true)
+        })?);
+        if password.is_empty() {
+            eprintln!("{}", tr!("password-empty"));
+            session.validation("password", false, "empty")?;
+            continue;
+        }
+        let strength = strength::estimate(&password, &context, rounds);
+        match args.min_password_score {
+            Some(min) if strength.score < min => {
+                eprintln!("Password strength {}\nA score of at least {} is required; choose another", strength, min);
+                session.validation("password strength", false, format!("score {} below {}", strength.score, min))?;
+                continue;
+            }
+            _ => say!("Password strength {}", strength),
+        }
+        session.validation("password strength", true, format!("score {}", strength.score))?;
+        let confirmation = Zeroizing::new(session.ask_secret(&tr!(&format!("prompt-confirm-{}", what)), |p| {
+            cli::console::password(p, true)
+        })?);
+        if *password == *confirmation {
+            return Ok(password);
+        }
+        eprintln!("{}", tr!("password-mismatch"));
+        session.validation("password", false, "confirmation did not match")?;
+    }
+}
+
+/// Asks for a whole number until `check` accepts it, printing why it did not.
+fn read_count(
+    session: &mut Session,
+    prompt: &str,
+    given: Option<u8>,
+    check: impl Fn(u64) -> std::result::Result<u8, String>,
+) -> Result<u8> {
+    if let Some(n) = given {
+        let verdict = check(n as u64);
+        session.validation(
+            prompt,
+            verdict.is_ok(),
+            match &verdict {
+                Ok(n) => format!("{} given up front", n),
+                Err(e) => e.clone(),
+            },
+        )?;
+        let n = verdict.map_err(SssError::InvalidArgument)?;
+        say!("{}: {}", prompt, n);
+        return Ok(n);
+    }
+    loop {
+        let answer: String = session.ask(prompt, |p| Input::new().with_prompt(p).interact_text())?;
+        let verdict = match answer.trim().parse::<u64>() {
+            Ok(n) => check(n),
+            Err(_) => Err(format!("'{}' is not a whole number", answer.trim())),
+        };
+        match verdict {
+            Ok(n) => return Ok(n),
+            Err(e) => {
+                eprintln!("{}", e);
+                session.validation(prompt, false, e)?;
+            }
+        }
+    }
+}
+
+fn read_total_shares(session: &mut Session, given: Option<u8>) -> Result<u8> {
+    read_count(session, &tr!("prompt-total-shares"), given, |n| match n {
+        0 | 1 => Err("At least 2 shares are needed; a single share would just be the secret".to_string()),
+        2..=255 => Ok(n as u8),
+        _ => Err("At most 255 shares can be made, one per nonzero share index".to_string()),
+    })
+}
+
+fn read_threshold(session: &mut Session, total_shares: u8, given: Option<u8>) -> Result<u8> {
+    read_count(session, &tr!("prompt-threshold"), given, |n| match n {
+        0 | 1 => Err("A threshold of 1 would let any single share recover the secret; use at least 2".to_string()),
+        n if n > total_shares as u64 => Err(format!(
+            "Only {} shares will be made, so at most {} can be required",
+            total_shares, total_shares
+        )),
+        _ => Ok(n as u8),
+    })
+}
+
+/// Reads a raw secp256k1 private key. It is shared as is, without a password, so the shares
+/// can be used directly as key shares by threshold protocols.
+fn read_scalar(session: &mut Session) -> Result<Zeroizing<Vec<u8>>> {
+    let prompt = "Enter the 32-byte secp256k1 key (hex: or base64:)";
+    let secret = read_secret(session, prompt, SecretKind::Auto)?;
+    match &secret {
+        SecretType::Bytes(key) if key.len() == secp256k1::SCALAR_LEN => Ok(Zeroizing::new(key.clone())),
+        SecretType::Bytes(_) => {
+            Err(SssError::InvalidArgument(format!("a secp256k1 key is exactly {} bytes", secp256k1::SCALAR_LEN)))
+        }
+        _ => Err(SssError::InvalidArgument("give the secp256k1 key as hex: or base64: bytes".into())),
+    }
+}
+
+fn recovery_command(field: Field) -> String {
+    match field {
+        Field::Gf256 => RECOVERY_COMMAND.to_string(),
+        _ => format!("{} --field {}", RECOVERY_COMMAND, field),
+    }
+}
+
+/// A share as one line of text that `decrypt` reads back: an unseal key for Vault sets,
+/// otherwise the chosen encoding, with hex standing in for byte lists.
+fn share_text(args: &SplitArgs, share: &[u8]) -> Result<String> {
+    match (args.field, args.encoding.unwrap_or_default()) {
+        (Field::Vault, _) => Ok(vault::unseal_key(share)),
+        (_, ShareEncoding::List) => ShareEncoding::Hex.encode_with_parity(share, args.parity),
+        (_, encoding) => encoding.encode_with_parity(share, args.parity),
+    }
+}
+
+/// The label and note of the `i`-th share, and the set and quorum it belongs to.
+fn share_header(args: &SplitArgs, i: usize, set_fingerprint: &str, quorum: (u8, u8)) -> ShareHeader {
+    ShareHeader {
+        label: args.share_labels.get(i).or(args.custodians.get(i).map(|c| &c.name)).cloned(),
+        note: args.share_notes.get(i).cloned(),
+        set: Some(set_fingerprint.to_string()),
+        quorum: Some(quorum),
+        parents: Vec::new(),
+    }
+}
+
+/// Fingerprint of a share set. Prime-field sets are identified by their public key, since the
+/// shared value is a private key rather than ciphertext.
+fn share_set_fingerprint(field: Field, payload: &[u8]) -> Result<String> {
+    match field {
+        Field::Gf256 | Field::Vault | Field::Pedersen => Ok(set_fingerprint(payload)),
+        Field::Secp256k1 => Ok(set_fingerprint(&secp256k1::public_key(payload)?)),
+    }
+}
+
+/// The Pedersen commitments of a new set, checked against every share before they are handed out.
+fn pedersen_commitments(session: &mut Session, shares: &[Vec<u8>], threshold: u8) -> Result<pedersen::Commitments> {
+    let commitments = pedersen::commit(shares, threshold)?;
+    let checked = shares.iter().try_for_each(|share| pedersen::verify_share(share, &commitments));
+    session.validation(
+        "commitments",
+        checked.is_ok(),
+        match &checked {
+            Ok(()) => format!("{} shares match {} Pedersen commitments per chunk", shares.len(), threshold),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    checked.map(|()| commitments)
+}
+
+fn encrypt_flow(session: &mut Session, args: &SplitArgs) -> Result<()> {
+    if args.format == ShareFormat::Ssss {
+        return ssss_split_flow(session, args);
+    }
+    #[cfg(feature = "age")]
+    let age_recipients = args
+        .age_recipients
+        .iter()
+        .map(|r| shamir_cli::age::recipient(r))
+        .collect::<Result<Vec<_>>>()?;
+    #[cfg(not(feature = "age"))]
+    let age_recipients: Vec<()> = Vec::new();
+    #[cfg(feature = "pq")]
+    let pq_recipients =
+        args.pq_recipients.iter().map(|r| shamir_cli::pq::Recipient::parse(r)).collect::<Result<Vec<_>>>()?;
+    #[cfg(not(feature = "pq"))]
+    let pq_recipients: Vec<()> = Vec::new();
+    if !age_recipients.is_empty() && !pq_recipients.is_empty() {
+        return Err(SssError::InvalidArgument("encrypt shares to age recipients or post-quantum keys, not both".into()));
+    }
+
+    if args.field == Field::Vault && (args.paper.is_some() || args.parity.is_some()) {
+        return Err(SssError::InvalidArgument("Vault unseal keys cannot carry parity or go on share pages".into()));
+    }
+    if (args.not_before.is_some() || args.dead_mans_switch.is_some()) && args.field == Field::Secp256k1 {
+        return Err(SssError::InvalidArgument(
+            "secp256k1 keys are shared as they are, so they cannot be time-locked or switched".into(),
+        ));
+    }
+    #[cfg(feature = "pkcs11")]
+    if args.pkcs11_key.is_some() && args.field == Field::Secp256k1 {
+        return Err(SssError::InvalidArgument(
+            "secp256k1 keys are shared as they are, not encrypted with an HSM key".into(),
+        ));
+    }
+    if args.tpm_seal.is_some() {
+        shamir_cli::tpm::parse_pcrs(&args.tpm_pcrs)?;
+    }
+    if let Some(parity) = args.parity {
+        // a bad parity size or a bech32, ur or armor encoding fails here, before any prompt
+        args.encoding.unwrap_or_default().encode_with_parity(&[1, 0], Some(parity))?;
+    }
+    let manifest_key = args.manifest_key.as_deref().map(token::read_signing_key).transpose()?;
+    let placement = args.placement.as_deref().map(PlacementConfig::read).transpose()?;
+    let mut rng = split_rng(session, args)?;
+
+    let sealing = match args.field {
+        Field::Gf256 | Field::Vault | Field::Pedersen => seal_secret(session, args)?,
+        Field::Secp256k1 => Sealing::Done(read_scalar(session)?),
+    };
+
+    let total_shares: u8 = if let Some(placement) = &placement {
+        placement.placements.len() as u8
+    } else if !age_recipients.is_empty() || !pq_recipients.is_empty() {
+        u8::try_from(age_recipients.len().max(pq_recipients.len()))
+            .map_err(|_| SssError::InvalidArgument("at most 255 recipients can hold shares".into()))?
+    } else if args.custodians.is_empty() {
+        read_total_shares(session, args.total)?
+    } else {
+        u8::try_from(args.custodians.len())
+            .map_err(|_| SssError::InvalidArgument("at most 255 custodians can hold shares".into()))?
+    };
+    if total_shares < 2 {
+        return Err(SssError::InvalidArgument("at least 2 holders are needed to split a secret".into()));
+    }
+
+    if args.share_labels.len().max(args.share_notes.len()) > total_shares as usize {
+        return Err(SssError::InvalidArgument(format!("more share labels or notes than the {} shares", total_shares)));
+    }
+
+    let threshold = read_threshold(session, total_shares, args.threshold)?;
+    if let Some(profile) = &args.profile {
+        session.audit_param("profile", profile.clone());
+    }
+    session.audit_param("field", args.field.to_string());
+    session.audit_param("threshold", threshold);
+    session.audit_param("total", total_shares);
+    let combined_data = sealing.seal(session, args, threshold, &mut *rng)?;
+
+    if let Some(min_jurisdictions) = args.min_jurisdictions {
+        let policy = JurisdictionPolicy { min_jurisdictions };
+        let verdict = policy.check(&args.custodians, threshold as usize);
+        session.validation(
+            "jurisdiction policy",
+            verdict.is_ok(),
+            match &verdict {
+                Ok(()) => format!("every quorum spans at least {} jurisdictions", min_jurisdictions),
+                Err(e) => e.to_string(),
+            },
+        )?;
+        verdict?;
+    }
+
+    let shares = args.field.split_with_rng(&combined_data, threshold, total_shares, &mut *rng);
+    session.validation(
+        "share generation",
+        shares.is_ok(),
+        match &shares {
+            Ok(s) => format!("{} shares, threshold {}", s.len(), threshold),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    let shares = shares?;
+    session.audit_shares(&shares);
+    for i in 0..shares.len() {
+        if let Some(name) = args.share_labels.get(i).or(args.custodians.get(i).map(|c| &c.name)) {
+            session.audit_participant(name);
+        }
+    }
+    let rehearsals = args.rehearsals.unwrap_or(64);
+    if rehearsals > 0 {
+        let rehearsal = args.field.rehearse(&shares, threshold, &combined_data, rehearsals);
+        let combinations = shamir::quorum_count(threshold, total_shares);
+        session.validation(
+            "rehearsal",
+            rehearsal.is_ok(),
+            match &rehearsal {
+                Ok(n) => format!("verified {}/{} quorum combinations of {}", n, n, combinations),
+                Err(e) => e.to_string(),
+            },
+        )?;
+        let verified = rehearsal?;
+        let sampled = match combinations {
+            c if c > verified as u64 => format!(" (a random sample of {})", c),
+            _ => String::new(),
+        };
+        say!("Verified {}/{} quorum combinations{}", verified, verified, sampled);
+        session.report("rehearsal", json!({ "verified": verified, "combinations": combinations }));
+    }
+    let fingerprint = share_set_fingerprint(args.field, &combined_data)?;
+    session.validation("share set", true, format!("fingerprint {}", fingerprint))?;
+    session.audit_param("set", fingerprint.clone());
+    let commitments = match args.field {
+        Field::Pedersen => Some(pedersen_commitments(session, &shares, threshold)?),
+        _ => None,
+    };
+
+    say!("\nShare set fingerprint: {}", fingerprint);
+    session.report("set_fingerprint", fingerprint.clone());
+    session.report("field", args.field.to_string());
+    session.report("threshold", threshold);
+    session.report("total", total_shares);
+    match args.field {
+        Field::Gf256 | Field::Vault | Field::Pedersen if pkcs11::is_hsm_payload(&combined_data) => {
+            let payload = pkcs11::HsmPayload::parse(&combined_data)?;
+            say!("\nSAVE THESE VALUES FOR DECRYPTION:");
+            say!("Nonce: {}", hex::encode(payload.nonce));
+            say!("Key: AES-256 key '{}' in the HSM", payload.label);
+            session.report("nonce", hex::encode(payload.nonce));
+            session.report("hsm_key", payload.label);
+            if let Some(binding) = Conditions::split(&combined_data).0.binding {
+                say!("Set ID: {} (bound to a threshold of {})", binding.set_id(), binding.threshold);
+                session.report("set_id", binding.set_id());
+            }
+        }
+        Field::Gf256 | Field::Vault | Field::Pedersen => {
+            let (conditions, _, unlocked) = Conditions::split(&combined_data);
+            let (rounds, payload) = kdf_rounds(unlocked)
+                .ok_or_else(|| SssError::Kdf("the new payload has no KDF header".into()))?;
+            let (salt, nonce, _) = split_payload(payload)?;
+            say!("\nSAVE THESE VALUES FOR DECRYPTION:");
+            say!("Salt: {}", hex::encode(salt));
+            say!("Nonce: {}", hex::encode(nonce));
+            say!("KDF: PBKDF2-HMAC-SHA256, {} rounds", rounds);
+            session.report("salt", hex::encode(salt));
+            session.report("nonce", hex::encode(nonce));
+            session.report("kdf_rounds", rounds);
+            if let Some(binding) = conditions.binding {
+                say!("Set ID: {} (bound to a threshold of {})", binding.set_id(), binding.threshold);
+                session.report("set_id", binding.set_id());
+            }
+            if let Some(lock) = conditions.time_lock {
+                let source = if lock.roughtime_key.is_some() { "a Roughtime server's" } else { "the local" };
+                say!("Time lock: not before {}, by {} clock", timelock::format_date(lock.not_before), source);
+                session.report("not_before", lock.not_before);
+            }
+            if let Some(switch) = conditions.switch {
+                say!(
+                    "Dead man's switch: opens with an approval token or once the heartbeats signed by {} lapse",
+                    hex::encode(switch.dealer_key)
+                );
+                say!("Sign the first heartbeat now: sss heartbeat --set {}", set_fingerprint(&combined_data));
+                session.report("dealer_key", hex::encode(switch.dealer_key));
+            }
+        }
+        Field::Secp256k1 => {
+            let public_key = hex::encode(secp256k1::public_key(&combined_data)?);
+            say!("Public key: {}", public_key);
+            session.report("public_key", public_key);
+        }
+    }
+    if let Some(commitments) = &commitments {
+        let text = serde_json::to_string(commitments).map_err(|e| SssError::Parse(e.to_string()))?;
+        say!("Pedersen commitments (publish these to every custodian): {}", text);
+        session.report("commitments", serde_json::to_value(commitments).map_err(|e| SssError::Parse(e.to_string()))?);
+    }
+    #[cfg(feature = "age")]
+    if !age_recipients.is_empty() {
+        let written = write_age_shares(&shares, &age_recipients, args, &args.age_out)?;
+        session.report("files", written.iter().map(|p| p.display().to_string()).collect::<Vec<_>>());
+    }
+    #[cfg(feature = "pq")]
+    if !pq_recipients.is_empty() {
+        let written = write_pq_shares(&shares, &pq_recipients, args, &args.pq_out)?;
+        session.report("files", written.iter().map(|p| p.display().to_string()).collect::<Vec<_>>());
+    }
+    if let Some(placement) = &placement {
+        let texts = shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| {
+                let header = share_header(args, i, &fingerprint, (threshold, total_shares));
+                Ok(Zeroizing::new(header.prepend(&share_text(args, share)?)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        place_shares(session, placement, &texts)?;
+    } else if let Some(dir) = &args.out_dir {
+        let files = shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| {
+                let header = share_header(args, i, &fingerprint, (threshold, total_shares));
+                Ok(ShareFile { share, text: header.prepend(&share_text(args, share)?), label: header.label })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (_, written) = manifest::write_share_dir(
+            dir,
+            &files,
+            &fingerprint,
+            &args.field.to_string(),
+            threshold,
+            commitments.as_ref(),
+            manifest_key.as_ref(),
+        )?;
+        say!("\nWrote {} share files and {} to {}", shares.len(), manifest::MANIFEST_FILE, dir.display());
+        for path in &written {
+            say!("  {}", path.display());
+        }
+        session.report("files", written.iter().map(|p| p.display().to_string()).collect::<Vec<_>>());
+    } else if age_recipients.is_empty() && pq_recipients.is_empty() {
+        // with JSON output the shares are only in the document, not echoed to stderr
+        let listed = !cli::output::json();
+        let quiet = cli::output::quiet();
+        if listed && !quiet {
+            println!("\nGenerated shares:");
+        }
+        let mut report = Vec::new();
+        for (i, share) in shares.iter().enumerate() {
+            let header = share_header(args, i, &fingerprint, (threshold, total_shares));
+            // the set fingerprint and quorum are printed once above, not over every share
+            let shown = ShareHeader { set: None, quorum: None, ..header.clone() };
+            let mut hasher = Sha256::new();
+            hasher.update(share.as_slice());
+            let hash_bytes = hasher.finalize();
+            let short_hash_hex = hex::encode(&hash_bytes[..4]);
+
+            // an armored share shows its headers inside the block
+            let armored = args.encoding == Some(ShareEncoding::Armor);
+            if listed && !quiet && !shown.is_empty() && !armored {
+                print!("{}", shown.prepend(""));
+            }
+            let file_text = header.prepend(&share_text(args, share)?);
+            let kept = if let Some(name) = keep_in_keyring(args, i, &file_text)? {
+                Some(format!("stored in the OS keyring as '{}'", name))
+            } else if let Some(path) = args.tpm_seal.as_ref().filter(|_| i == 0) {
+                shamir_cli::tpm::write(path, &shamir_cli::tpm::seal(share, &args.tpm_pcrs)?)?;
+                Some(format!("sealed to this machine's TPM in {}", path.display()))
+            } else if let Some(path) = args.yubikey.as_ref().filter(|_| i == 0) {
+                shamir_cli::yubikey::write(path, &shamir_cli::yubikey::wrap(share, args.yubikey_slot)?)?;
+                Some(format!("wrapped by the YubiKey in {}", path.display()))
+            } else if let (Some(out), Some(cover)) = (args.stego.as_ref().filter(|_| i == 0), &args.stego_cover) {
+                shamir_cli::stego::hide_file(cover, out, &file_text)?;
+                Some(format!("hidden in the picture {}", out.display()))
+            } else {
+                None
+            };
+            if listed {
+                let text = match args.field {
+                    Field::Vault => vault::unseal_key(share),
+                    _ if armored => shown.prepend(&share_text(args, share)?),
+                    _ => args.encoding.unwrap_or_default().encode_with_parity(share, args.parity)?,
+                };
+                if let Some(kept) = &kept {
+                    if !quiet {
+                        println!("Share ID '{:?}': {}", short_hash_hex, kept);
+                    }
+                } else if !copy_share(session, &args.clipboard, share[0], &short_hash_hex, &text)? {
+                    if quiet {
+                        // one share per line, for scripts
+                        println!("{}", text);
+                    } else {
+                        println!("Share ID '{:?}':{}{}", short_hash_hex, if armored { "\n" } else { " " }, text);
+                    }
+                }
+            }
+            report.push(json!({
+                "index": share[0],
+                "id": short_hash_hex,
+                "share": if kept.is_none() { Some(file_text) } else { None },
+                "kept": kept,
+                "label": header.label,
+                "note": header.note,
+            }));
+        }
+        session.report("shares", report);
+    }
+    if !args.custodians.is_empty() {
+        say!("\nDistribution:");
+        for (share, custodian) in shares.iter().zip(&args.custodians) {
+            say!("  share #{} -> {} ({})", share[0], custodian.name, custodian.jurisdiction);
+        }
+        session.report(
+            "custodians",
+            shares
+                .iter()
+                .zip(&args.custodians)
+                .map(|(share, c)| json!({ "index": share[0], "name": c.name, "jurisdiction": c.jurisdiction }))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    if let Some(dir) = &args.paper {
+        let created = paper::civil_date(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        );
+        let sheets: Vec<ShareSheet> = shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| ShareSheet {
+                share,
+                set_fingerprint: fingerprint.clone(),
+                threshold,
+                total: total_shares,
+                created: created.clone(),
+                custodian: share_header(args, i, &fingerprint, (threshold, total_shares)).label,
+                command: recovery_command(args.field),
+            })
+            .collect();
+        let written = paper::write_sheets(dir, &sheets)?;
+        say!("\nWrote {} printable share pages to {}", written.len(), dir.display());
+        session.report("paper", written.iter().map(|p| p.display().to_string()).collect::<Vec<_>>());
+    }
+
+    if let Some(dir) = cards_dir(session, args)? {
+        let command = recovery_command(args.field);
+        let cards: Vec<RecoveryCard> = shares
+            .iter()
+            .filter_map(|share| share_index(share))
+            .map(|index| RecoveryCard {
+                command: command.clone(),
+                format_version: FORMAT_VERSION,
+                set_fingerprint: fingerprint.clone(),
+                share_index: index,
+                threshold,
+                total: total_shares,
+            })
+            .collect();
+        let written = write_cards(&dir, &cards)?;
+        say!("\nWrote {} recovery card files to {}", written.len(), dir.display());
+        session.report("cards", written.iter().map(|p| p.display().to_string()).collect::<Vec<_>>());
+    }
+    Ok(())
+}
+
+/// Where recovery cards go: --cards, nowhere with --no-cards, and otherwise the operator's
+/// answer. A run that nobody answers (input not a terminal, --quiet, JSON output) gets none.
+fn cards_dir(session: &mut Session, args: &SplitArgs) -> Result<Option<PathBuf>> {
+    use std::io::IsTerminal;
+
+    if args.cards.is_some() || args.no_cards {
+        return Ok(args.cards.clone());
+    }
+    if !std::io::stdin().is_terminal() || cli::output::quiet() || cli::output::json() {
+        return Ok(None);
+    }
+    let make_cards = session.ask(&tr!("prompt-make-cards"), |p| {
+        Confirm::new().with_prompt(p).default(false).interact()
+    })?;
+    if !make_cards {
+        return Ok(None);
+    }
+    let dir: String = session.ask(&tr!("prompt-cards-dir"), |p| {
+        Input::new()
+            .with_prompt(p)
+            .default("recovery-cards".to_string())
+            .interact_text()
+    })?;
+    Ok(Some(PathBuf::from(dir)))
+}
+
+/// Pushes share #n to the n-th placement. A share that cannot be placed is printed instead, so
+/// it can be put somewhere by hand, and the split fails once every share has been tried.
+fn place_shares(session: &mut Session, placement: &PlacementConfig, texts: &[Zeroizing<String>]) -> Result<()> {
+    say!("\nPlacing shares:");
+    let mut failed = 0;
+    for (placed, text) in placement.placements.iter().zip(texts) {
+        let subject = format!("share #{} -> {}", placed.share, placed.backend);
+        match placed.backend.push(text) {
+            Ok(()) => {
+                say!("  {}", subject);
+                session.validation(&subject, true, "placed")?;
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("  {}: {}; place it by hand:\n{}", subject, e, text.as_str());
+                session.validation(&subject, false, e.to_string())?;
+            }
+        }
+    }
+    session.report("placements", placement.placements.iter().map(|p| p.backend.to_string()).collect::<Vec<_>>());
+    if failed > 0 {
+        return Err(SssError::Store(format!("{} of {} shares could not be placed", failed, texts.len())));
+    }
+    Ok(())
+}
+
+/// Checks every row of the batch, asks for the password once and splits each secret into
+/// its own directory under `out_dir`.
+fn batch_flow(
+    session: &mut Session,
+    input: &std::path::Path,
+    out_dir: &std::path::Path,
+    args: &SplitArgs,
+) -> Result<()> {
+    let rows = batch::read_rows(input)?;
+    let checked = batch::check(&rows);
+    session.validation(
+        "batch",
+        checked.is_ok(),
+        match &checked {
+            Ok(()) => format!("{} secrets", rows.len()),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    checked?;
+    say!("{} secrets to split into {}", rows.len(), out_dir.display());
+    let rounds = split_rounds(args)?;
+    let password = read_new_password(session, args, rounds, "encryption-password")?;
+    let (summary, shares) = batch::split_all(&rows, out_dir, &password, rounds, &mut rand::rngs::OsRng)?;
+    session.audit_param("sets", summary.sets.iter().map(|set| set.set_fingerprint.clone()).collect::<Vec<_>>());
+    session.audit_shares(&shares);
+    for set in &summary.sets {
+        say!(
+            "  {}: {} of {} shares in {} (set {})",
+            set.label,
+            set.threshold,
+            set.total,
+            out_dir.join(&set.dir).display(),
+            set.set_fingerprint
+        );
+    }
+    say!("Listed every set in {}", out_dir.join(batch::SUMMARY_FILE).display());
+    session.report("sets", serde_json::to_value(&summary.sets).map_err(|e| SssError::Parse(e.to_string()))?);
+    Ok(())
+}
+
+fn plan_flow(
+    session: &mut Session,
+    file: &std::path::Path,
+    dry_run: bool,
+    limit: usize,
+    mut args: SplitArgs,
+) -> Result<()> {
+    let plan = PlanFile::read(file)?.solve()?;
+    say!("Plan: any {} of {} shares", plan.threshold, plan.total());
+    for assignment in &plan.assignments {
+        say!("  #{} {} at {}", assignment.index, assignment.custodian, assignment.note());
+    }
+    let redundancy = plan::analyse(&plan, limit);
+    say!("\nRedundancy:");
+    for places in &redundancy {
+        match places.tolerates {
+            0 => say!("  None of the {} {} can be lost", places.places, places.attribute.plural()),
+            n => say!("  Any {} of the {} {} can be lost", n, places.places, places.attribute.plural()),
+        }
+        for fatal in &places.fatal {
+            say!("    losing {} together loses the secret", fatal.join(" + "));
+        }
+        if places.truncated {
+            say!("    ... and more; raise --limit to list them");
+        }
+    }
+    session.report("plan", serde_json::to_value(&plan).map_err(|e| SssError::Parse(e.to_string()))?);
+    session.report("redundancy", serde_json::to_value(&redundancy).map_err(|e| SssError::Parse(e.to_string()))?);
+    if dry_run {
+        return Ok(());
+    }
+    args.total = Some(plan.total());
+    args.threshold = Some(plan.threshold);
+    args.share_labels = plan.assignments.iter().map(|a| a.custodian.clone()).collect();
+    args.share_notes = plan.assignments.iter().map(Assignment::note).collect();
+    encrypt_flow(session, &args)
+}
+
+/// Opens the set with its old password and hands the plaintext, still padded and with the set's
+/// time lock or switch, to `encrypt_flow` to seal under a new one. A decoy set rotates only the
+/// slot the old password opens.
+fn rotate_password_flow(session: &mut Session, expected_set: Option<String>, mut args: SplitArgs) -> Result<()> {
+    let combine = CombineArgs { expected_set, ..CombineArgs::default() };
+    let (shares, declared) = read_shares(session, &combine)?;
+    let expected = Expected { set: combine.expected_set.clone().or(declared.set), threshold: declared.threshold };
+    let valid = validate_shares(&shares);
+    session.validation(
+        "share structure",
+        valid.is_ok(),
+        match &valid {
+            Ok(()) => format!("{} shares", shares.len()),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    valid?;
+    session.audit_param("old_shares", shares.iter().map(|share| audit::share_id(share)).collect::<Vec<_>>());
+    if let Some(threshold) = expected.threshold.filter(|&t| shares.len() < t as usize) {
+        let e = SssError::InsufficientShares(tr!("too-few-shares", given = shares.len(), threshold = threshold));
+        session.validation("quorum", false, e.to_string())?;
+        return Err(e);
+    }
+
+    let encrypted_data = Field::Gf256.reconstruct(&shares)?;
+    if pkcs11::is_hsm_payload(&encrypted_data) {
+        return Err(SssError::InvalidArgument(
+            "this set is encrypted with an HSM key, so it has no password to rotate".into(),
+        ));
+    }
+    check_conditions(session, &encrypted_data, &combine)?;
+    let password = Zeroizing::new(session.ask_secret("Enter current encryption password", |p| {
+        cli::console::password(p, false)
+    })?);
+    let fingerprint = set_fingerprint(&encrypted_data);
+    session.audit_param("old_set", fingerprint.clone());
+    let set_confirmed = check_expected_set(session, &fingerprint, &shares, &expected, Field::Gf256)?;
+    let plaintext = open_payload(&encrypted_data, &password).map_err(|e| match e {
+        SssError::Aead if set_confirmed => SssError::WrongPassword,
+        e => e,
+    });
+    session.validation(
+        "decryption",
+        plaintext.is_ok(),
+        match &plaintext {
+            Ok(_) => "authenticated".to_string(),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    drop(password);
+    say!("Opened set {}; choose the new password", fingerprint);
+    let (conditions, _, _) = Conditions::split(&encrypted_data);
+    args.resealed = Some(Resealed { plaintext: plaintext?, conditions });
+    encrypt_flow(session, &args)
+}
+
+/// Opens a set in the first, header-less format and hands its secret, serialized and padded
+/// the current way, to `encrypt_flow` to seal under a KDF header and split with share headers.
+fn migrate_flow(session: &mut Session, saved: Option<&legacy::Saved>, mut args: SplitArgs) -> Result<()> {
+    let (shares, _) = read_shares(session, &CombineArgs::default())?;
+    let valid = validate_shares(&shares);
+    session.validation(
+        "share structure",
+        valid.is_ok(),
+        match &valid {
+            Ok(()) => format!("{} shares", shares.len()),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    valid?;
+    session.audit_param("old_shares", shares.iter().map(|share| audit::share_id(share)).collect::<Vec<_>>());
+
+    let payload = Field::Gf256.reconstruct(&shares)?;
+    if !legacy::is_legacy(&payload) {
+        return Err(SssError::Parse(
+            "these shares are already in the current format; use rotate-password to re-encrypt them".into(),
+        ));
+    }
+    let password = Zeroizing::new(session.ask_secret("Enter the old set's encryption password", |p| {
+        cli::console::password(p, false)
+    })?);
+    let plaintext = legacy::open(&payload, saved, &password).and_then(|plaintext| legacy::upgrade(&plaintext));
+    session.validation(
+        "decryption",
+        plaintext.is_ok(),
+        match &plaintext {
+            Ok(_) => "authenticated".to_string(),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    drop(password);
+    say!("Opened the old set {}; choose the password for the new one", set_fingerprint(&payload));
+    args.resealed = Some(Resealed { plaintext: SecretBuf::from_slice(&plaintext?)?, conditions: Conditions::default() });
+    encrypt_flow(session, &args)
+}
+
+/// Splits the secret itself the way ssss-split does, so ssss-combine can put it back together.
+fn ssss_split_flow(session: &mut Session, args: &SplitArgs) -> Result<()> {
+    let text = Zeroizing::new(session.ask_secret("Enter secret value (prefix hex: for binary)", |p| {
+        Input::<String>::new().with_prompt(p).interact()
+    })?);
+    let secret = Zeroizing::new(match text.strip_prefix("hex:") {
+        Some(digits) => hex::decode(digits.trim())
+            .map_err(|_| SssError::InvalidArgument("the secret after hex: is not hex".into()))?,
+        None => text.as_bytes().to_vec(),
+    });
+    let total_shares = read_total_shares(session, args.total)?;
+    let threshold = read_threshold(session, total_shares, args.threshold)?;
+
+    let mut rng = split_rng(session, args)?;
+    let shares = ssss::split_with_rng(&secret, threshold, total_shares, !args.no_diffusion, &mut *rng);
+    session.validation(
+        "share generation",
+        shares.is_ok(),
+        match &shares {
+            Ok(s) => format!("{} ssss shares, threshold {}", s.len(), threshold),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    say!("\nUsing a {} bit security level.", secret.len() * 8);
+    let lines: Vec<String> = shares?.iter().map(|share| ssss::format_share(share, total_shares)).collect();
+    if !cli::output::json() {
+        for line in &lines {
+            println!("{}", line);
+        }
+    }
+    session.audit_param("format", "ssss");
+    session.audit_param("threshold", threshold);
+    session.audit_param("total", total_shares);
+    session.audit_shares(&lines);
+    session.report("format", "ssss");
+    session.report("threshold", threshold);
+    session.report("total", total_shares);
+    session.report("shares", lines);
+    say!(
+        "\nCombine exactly {} of them with `ssss-combine -t {}{}` or `sss decrypt --format ssss`.",
+        threshold,
+        threshold,
+        if args.no_diffusion { " -D" } else { "" }
+    );
+    Ok(())
+}
+
+/// Checks the conditions in front of a payload: its set binding, time lock and dead man's switch.
+fn check_conditions(session: &mut Session, payload: &[u8], args: &CombineArgs) -> Result<()> {
+    let (conditions, _, _) = Conditions::split(payload);
+    if let Some(binding) = conditions.binding {
+        let verdict = binding.check();
+        session.validation(
+            "set binding",
+            verdict.is_ok(),
+            match &verdict {
+                Ok(()) => format!("set ID {}, threshold {}", binding.set_id(), binding.threshold),
+                Err(e) => e.to_string(),
+            },
+        )?;
+        verdict?;
+        session.audit_param("set_id", binding.set_id());
+    }
+    if let Some(lock) = conditions.time_lock {
+        check_time_lock(session, lock, args)?;
+    }
+    if let Some(switch) = conditions.switch {
+        check_switch(session, switch, &set_fingerprint(payload), args)?;
+    }
+    Ok(())
+}
+
+/// Refuses a time-locked set before its date, by the local clock or the Roughtime server the
+/// lock names, unless `--ignore-time-lock` was given.
+fn check_time_lock(session: &mut Session, lock: TimeLock, args: &CombineArgs) -> Result<()> {
+    let until = timelock::format_date(lock.not_before);
+    if args.ignore_time_lock {
+        eprintln!("WARNING: recovering a set time-locked until {}", until);
+        return session.validation("time lock", true, format!("until {}, ignored", until));
+    }
+    let now = match (&lock.roughtime_key, &args.roughtime) {
+        (None, _) => timelock::system_now(),
+        (Some(key), Some(server)) => shamir_cli::roughtime::query(server, key)?.earliest_secs(),
+        (Some(key), None) => {
+            let e = SssError::Policy(format!(
+                "this set is time-locked until {} by the Roughtime server with key {}; give --roughtime HOST:PORT",
+                until,
+                hex::encode(key)
+            ));
+            session.validation("time lock", false, e.to_string())?;
+            return Err(e);
+        }
+    };
+    let verdict = lock.check(now);
+    session.validation(
+        "time lock",
+        verdict.is_ok(),
+        match &verdict {
+            Ok(()) => format!("opened {}", until),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    verdict
+}
+
+/// Lets a switched set be recovered with the dealer's approval token or a lapsed heartbeat.
+fn check_switch(session: &mut Session, switch: Switch, fingerprint: &str, args: &CombineArgs) -> Result<()> {
+    let heartbeat = args.heartbeat.as_deref().map(Heartbeat::read).transpose()?;
+    let verdict = switch.check(fingerprint, args.approval.as_deref(), heartbeat.as_ref(), timelock::system_now());
+    let detail = match &verdict {
+        Ok(Release::Approved(holder)) => format!("approved by the dealer for {}", holder),
+        Ok(Release::Lapsed(at)) => format!("the dealer's heartbeat lapsed at {}", timelock::format_date(*at)),
+        Err(e) => e.to_string(),
+    };
+    if verdict.is_ok() {
+        say!("Dead man's switch: {}", detail);
+    }
+    session.validation("dead man's switch", verdict.is_ok(), detail)?;
+    verdict.map(|_| ())
+}
+
+fn recover_ssss(session: &mut Session, shares: &[Vec<u8>], args: &CombineArgs) -> Result<()> {
+    if args.authorized_set.is_some() {
+        return Err(SssError::Unauthorized("ssss shares carry no set fingerprint for a token to cover".into()));
+    }
+    let secret = Zeroizing::new(ssss::reconstruct(shares, !args.no_diffusion)?);
+    session.validation("ssss combine", true, format!("{} shares, {} bit", shares.len(), secret.len() * 8))?;
+    let text = Zeroizing::new(match std::str::from_utf8(&secret) {
+        Ok(text) if !text.chars().any(char::is_control) => text.to_string(),
+        _ => format!("hex:{}", hex::encode(secret.as_slice())),
+    });
+    reveal(session, args, &text)?;
+    Ok(())
+}
+
+/// What the command line, the shares' headers or their manifests say about the set being
+/// recovered, used to explain a failed recovery.
+#[derive(Default)]
+struct Expected {
+    set: Option<String>,
+    threshold: Option<u8>,
+}
+
+fn decrypt_flow(session: &mut Session, args: &mut CombineArgs) -> Result<()> {
+    let (shares, declared) = match &args.placement {
+        Some(path) => placed_shares(session, &PlacementConfig::read(path)?)?,
+        #[cfg(feature = "tui")]
+        None if args.tui => {
+            let (shares, declared, password) = ceremony_shares(session, args)?;
+            args.entered_password = Some(password);
+            (shares, declared)
+        }
+        None => read_shares(session, args)?,
+    };
+    let expected = Expected {
+        set: args.expected_set.clone().or(declared.set),
+        threshold: args.expected_threshold.or(declared.threshold),
+    };
+    recover_flow(session, shares, &expected, args)
+}
+
+#[tracing::instrument(
+    name = "reconstruction",
+    skip_all,
+    fields(shares = shares.len(), fingerprint = tracing::field::Empty, error = tracing::field::Empty)
+)]
+fn recover_flow(
+    session: &mut Session,
+    shares: Vec<Vec<u8>>,
+    expected: &Expected,
+    args: &CombineArgs,
+) -> Result<()> {
+    let result = recover(session, shares, expected, args);
+    if let Err(e) = &result {
+        tracing::Span::current().record("error", e.kind());
+    }
+    result
+}
+
+fn recover(
+    session: &mut Session,
+    shares: Vec<Vec<u8>>,
+    expected: &Expected,
+    args: &CombineArgs,
+) -> Result<()> {
+    let valid = validate_shares(&shares);
+    session.validation(
+        "share structure",
+        valid.is_ok(),
+        match &valid {
+            Ok(()) => format!("{} shares", shares.len()),
+            Err(e) => e.to_string(),
+        },
+    )?;
+    valid?;
+    session.audit_param("field", args.field.to_string());
+    session.audit_shares(&shares);
+    if let Some(threshold) = expected.threshold.filter(|&t| shares.len() < t as usize) {
+        let e = SssError::InsufficientShares(tr!("too-few-shares", given = shares.len(), threshold = threshold));
+        session.validation("quorum", false, e.to_string())?;
+        return Err(e);
+    }
+
+    if args.format == ShareFormat::Ssss {
+        return recover_ssss(session, &shares, args);
+    }
+    if args.field == Field::Secp256k1 {
+        let key = Zeroizing::new(args.field.reconstruct(&shares)?);
+        let fingerprint = share_set_fingerprint(args.field, &key)?;
+        session.audit_param("set", fingerprint.clone());
+        check_expected_set(session, &fingerprint, &shares, expected, args.field)?;
+        check_share_set(session, &fingerprint, args)?;
+        let public_key = hex::encode(secp256k1::public_key(&key)?);
+        say!("Public key: {}", public_key);
+        session.report("public_key", public_key);
+        reveal(session, args, &Zeroizing::new(hex::encode(key.as_slice())))?;
+        return Ok(());
+    }
+
+    let encrypted_data = args.field.reconstruct(&shares)?;
+    check_conditions(session, &encrypted_data, args)?;
+    let hsm = pkcs11::is_hsm_payload(&encrypted_data);
+    let password = match hsm {
+        true => None,
+        false if args.entered_password.is_some() => args.entered_password.clone(),
+        false => match args.password_source.source() {
+            Some(source) => Some(source.read()?),
+            None => Some(Zeroizing::new(session.ask_secret(&tr!("prompt-password"), |p| {
+                cli::console::password(p, false)
+            })?)),
+        },
+    };
+
+    let fingerprint = set_fingerprint(&encrypted_data);
+    session.audit_param("set", fingerprint.clone());
+    let set_confirmed = check_expected_set(session, &fingerprint, &shares, expected, args.field)?;
+    check_share_set(session, &fingerprint, args)?;
+
+    let decrypted_data = match &password {
+        Some(password) => open_payload(&encrypted_data, password),
+        None => open_hsm_payload(session, &args.pkcs11, &encrypted_data),
+    };
+    let decrypted_data = decrypted_data.map_err(|e| match e {
+        // the shares are known to be right, so only the password can be
+        SssError::Aead if set_confirmed && !hsm => SssError::WrongPassword,
+        e => e,
+    });
+    if decrypted_data.is_err() && expected.set.is_none() {
+        eprintln!(
+            "Pass --set with the fingerprint printed at split time to tell a wrong password from \
+             missing or foreign shares."
+        );
+    }
+    session.validation(
+        "decryption",
+        decrypted_data.is_ok(),
+        match &decrypted_data {
+            Ok(_) => "authenticated".to_string(),
+            Err(e) => e.to_string(),
+        },
+    )?;
+
+    let secret = deserialize_secret(&decrypted_data?)?;
+    
+    if let SecretType::SshKey(key) = &secret {
+        return recover_ssh_key(session, key, args);
+    }
+    if args.ssh_out.is_some() || args.ssh_public.is_some() {
+        return Err(SssError::InvalidArgument("--ssh-out and --ssh-public only apply to SSH key sets".into()));
+    }
+    if let SecretType::GpgKey { fingerprint, armored } = &secret {
+        return recover_gpg_key(session, fingerprint, armored, args);
+    }
+    if args.gpg_out.is_some() || args.gpg_import {
+        return Err(SssError::InvalidArgument("--gpg-out and --gpg-import only apply to GPG key sets".into()));
+    }
+    #[cfg(feature = "age")]
+    if let SecretType::AgeIdentity(identity) = &secret {
+        return recover_age_identity(session, identity, args);
+    }
+    #[cfg(feature = "age")]
+    if args.age_identity_out.is_some() {
+        return Err(SssError::InvalidArgument("--age-identity-out only applies to age identity sets".into()));
+    }
+    if let SecretType::EnvFile(text) = &secret {
+        return recover_env_file(session, text, args);
+    }
+    if args.env_out.is_some() || args.export {
+        return Err(SssError::InvalidArgument("--env-out and --export only apply to .env sets".into()));
+    }
+    if let (Some(file), Some(output)) = (&args.file, &args.output) {
+        return match &secret {
+            SecretType::Bytes(key) => {
+                decrypt_file(key, file, output)?;
+                session.report("written", output.display().to_string());
+                Ok(())
+            }
+            _ => Err(SssError::InvalidArgument("these shares do not protect a file key".into())),
+        };
+    }
+    match &secret {
+        SecretType::Bundle(entries) => {
+            let selected = select_labels(entries, &args.labels)?;
+            say!("\nRecovered {} of {} secrets:", selected.len(), entries.len());
+            let mut report = serde_json::Map::new();
+            for (label, secret) in selected {
+                let text = secret_text(secret, args)?;
+                if !cli::output::json() && !args.secret_elsewhere() {
+                    println!("{}:", label);
+                    println!("{}", text.as_str());
+                }
+                report.insert(label.clone(), text.as_str().into());
+            }
+            if args.secret_elsewhere() {
+                // a label to secret object, as the API and bindings return bundles
+                let object = Zeroizing::new(serde_json::Value::Object(report).to_string());
+                reveal(session, args, &object)?;
+            } else {
+                session.report("secrets", report);
+            }
+        }
+        _ if !args.labels.is_empty() => {
+            return Err(SssError::InvalidArgument("--label only applies to multi-secret share sets".into()));
+        }
+        _ => reveal(session, args, &secret_text(&secret, args)?)?,
+    }
+    Ok(())
 }
 
-const SALT_LEN: usize = 16;
-const NONCE_LEN: usize = 12;
-const MIN_SECRET_SIZE: usize = 32;
+/// Compares the rebuilt set with the one expected, if any, and names what went wrong when they
+/// differ: a share that does not belong (found by leaving each out in turn), or too few shares.
+/// Returns whether the set was confirmed.
+fn check_expected_set(
+    session: &mut Session,
+    fingerprint: &str,
+    shares: &[Vec<u8>],
+    expected: &Expected,
+    field: Field,
+) -> Result<bool> {
+    let Some(want) = expected.set.as_deref().map(str::trim) else {
+        return Ok(false);
+    };
+    if fingerprint.eq_ignore_ascii_case(want) {
+        session.validation("expected set", true, format!("fingerprint {}", want))?;
+        return Ok(true);
+    }
+    let enough_without_one = shares.len() > expected.threshold.map_or(2, usize::from);
+    let odd_one_out = (0..shares.len()).filter(|_| enough_without_one).find(|&i| {
+        let rest: Vec<&Vec<u8>> = shares.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, s)| s).collect();
+        field
+            .reconstruct(&rest)
+            .and_then(|data| share_set_fingerprint(field, &data))
+            .is_ok_and(|other| other.eq_ignore_ascii_case(want))
+    });
+    let reason = match (odd_one_out, expected.threshold) {
+        (Some(i), _) => format!(
+            "share #{} (index {}) is from a different set or damaged; the others rebuild set {}",
+            i + 1,
+            shares[i][0],
+            want
+        ),
+        // too few shares were already ruled out against the threshold
+        (None, Some(_)) => format!(
+            "these {} shares rebuild set {}, not {}: at least one of them is from a different set or damaged",
+            shares.len(),
+            fingerprint,
+            want
+        ),
+        (None, None) => format!(
+            "these {} shares rebuild set {}, not {}: the set needs more shares, or one is from a different set",
+            shares.len(),
+            fingerprint,
+            want
+        ),
+    };
+    session.validation("expected set", false, reason.clone())?;
+    Err(SssError::Share(reason))
+}
 
-fn main() {
-    let mode = Select::new()
-        .with_prompt("Choose operation")
-        .items(&["Encrypt", "Decrypt"])
-        .interact()
-        .unwrap();
+fn check_share_set(session: &mut Session, fingerprint: &str, args: &CombineArgs) -> Result<()> {
+    say!("Share set fingerprint: {}", fingerprint);
+    session.report("set_fingerprint", fingerprint);
+    tracing::Span::current().record("fingerprint", fingerprint);
+    if let Some((capability, ledger)) = &args.authorized_set {
+        let spent = ledger.spend(capability, fingerprint);
+        session.validation(
+            "authorized set",
+            spent.is_ok(),
+            match &spent {
+                Ok(()) => format!("token {} used up", capability.id),
+                Err(e) => e.to_string(),
+            },
+        )?;
+        spent?;
+        say!("Token {} for {} is now used up", capability.id, capability.holder);
+    }
+    session.validation("share set", true, format!("fingerprint {}", fingerprint))
+}
+
+fn secret_text(secret: &SecretType, args: &CombineArgs) -> Result<Zeroizing<String>> {
+    Ok(match secret {
+        SecretType::Mnemonic(entropy) if args.ur => Zeroizing::new(shamir_cli::bcur::encode_seed(entropy)),
+        SecretType::Bytes(b) if args.ur => Zeroizing::new(shamir_cli::bcur::encode_bytes(b)),
+        _ if args.ur => {
+            return Err(SssError::InvalidArgument("--ur only applies to seed phrases and byte secrets".into()))
+        }
+        SecretType::String(s) => Zeroizing::new(s.clone()),
+        SecretType::Int(i) => Zeroizing::new(i.to_string()),
+        SecretType::Float(f) => Zeroizing::new(f.to_string()),
+        SecretType::Uuid(uuid) => Zeroizing::new(uuid_text(uuid)),
+        SecretType::Date(date) => Zeroizing::new(date.clone()),
+        SecretType::Totp(totp) => totp.uri(),
+        SecretType::Bytes(b) => Zeroizing::new(hex::encode(b)),
+        SecretType::Json(j) => select_json_keys(j, &args.keys)?,
+        SecretType::Mnemonic(entropy) => mnemonic_words(entropy)?,
+        SecretType::SshKey(key) => Zeroizing::new(key.clone()),
+        SecretType::GpgKey { armored, .. } => Zeroizing::new(armored.clone()),
+        SecretType::AgeIdentity(identity) => Zeroizing::new(identity.clone()),
+        SecretType::EnvFile(text) => Zeroizing::new(text.clone()),
+        SecretType::Bundle(_) => unreachable!("bundles cannot be nested"),
+    })
+}
+
+/// Prints a recovered secret. With JSON output it only goes into the document, never to
+/// stderr with the other messages.
+fn reveal(session: &mut Session, args: &CombineArgs, text: &str) -> Result<()> {
+    if let Some(out) = &args.secret_out {
+        let mut contents = Zeroizing::new(text.to_string());
+        contents.push('\n');
+        write_private(out, contents.as_bytes())?;
+        say!("\nWrote the recovered secret to {} (mode 0600)", out.display());
+        session.report("written", out.display().to_string());
+        return Ok(());
+    }
+    if copy_secret(session, &args.clipboard, text)? {
+        return Ok(());
+    }
+    if cli::output::json() {
+        session.report("secret", text);
+    } else if cli::output::quiet() {
+        println!("{}", text);
+    } else {
+        println!("\n{}", tr!("recovered-secret"));
+        println!("{}", text);
+        clear_screen_after(session, args.clear_screen)?;
+    }
+    Ok(())
+}
+
+/// With --clear-screen on a terminal, waits for Enter and then wipes the screen and its
+/// scrollback, so what was shown does not stay behind.
+fn clear_screen_after(session: &mut Session, clear: bool) -> Result<()> {
+    use std::io::{IsTerminal, Write};
+
+    if !clear || !std::io::stdout().is_terminal() {
+        return Ok(());
+    }
+    session.ask("Press Enter to clear the screen", |p| {
+        Input::<String>::new().with_prompt(p).allow_empty(true).interact_text()
+    })?;
+    let mut stdout = std::io::stdout();
+    // home, erase the screen, erase the scrollback
+    write!(stdout, "\x1b[H\x1b[2J\x1b[3J")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// With --keyring, stores the first share in the OS keyring and returns the entry's name.
+#[cfg(feature = "keyring")]
+fn keep_in_keyring<'a>(args: &'a SplitArgs, i: usize, text: &str) -> Result<Option<&'a str>> {
+    match &args.keyring {
+        Some(name) if i == 0 => {
+            shamir_cli::keychain::store(name, text)?;
+            Ok(Some(name))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keep_in_keyring<'a>(_: &'a SplitArgs, _: usize, _: &str) -> Result<Option<&'a str>> {
+    Ok(None)
+}
+
+/// A share entered at the prompt: the entry itself, or the share a `keyring:<name>` entry
+/// names.
+#[cfg(feature = "keyring")]
+fn entered_share(entry: &str) -> std::result::Result<String, String> {
+    match entry.strip_prefix(shamir_cli::keychain::PREFIX) {
+        Some(name) => shamir_cli::keychain::load(name).map(|text| text.to_string()).map_err(|e| e.to_string()),
+        None => Ok(entry.to_string()),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn entered_share(entry: &str) -> std::result::Result<String, String> {
+    Ok(entry.to_string())
+}
+
+/// With --clipboard, copies a new share and waits for Enter before copying the next one.
+/// Returns whether the share was copied rather than left to print.
+#[cfg(feature = "clipboard")]
+fn copy_share(session: &mut Session, clip: &ClipboardArgs, index: u8, id: &str, text: &str) -> Result<bool> {
+    if !clip.clipboard {
+        return Ok(false);
+    }
+    let copied = shamir_cli::clipboard::copy(text, std::time::Duration::from_secs(clip.clipboard_seconds))?;
+    say!("Share ID '{:?}': copied to the clipboard, cleared in {}s", id, clip.clipboard_seconds);
+    session.ask(&format!("Press Enter once share #{} is stored", index), |p| {
+        Input::<String>::new().with_prompt(p).allow_empty(true).interact_text()
+    })?;
+    copied.clear();
+    Ok(true)
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_share(_: &mut Session, _: &ClipboardArgs, _: u8, _: &str, _: &str) -> Result<bool> {
+    Ok(false)
+}
+
+/// With --clipboard, copies the recovered secret and waits until it is cleared again.
+#[cfg(feature = "clipboard")]
+fn copy_secret(session: &mut Session, clip: &ClipboardArgs, text: &str) -> Result<bool> {
+    if !clip.clipboard {
+        return Ok(false);
+    }
+    let copied = shamir_cli::clipboard::copy(text, std::time::Duration::from_secs(clip.clipboard_seconds))?;
+    say!("\nRecovered secret copied to the clipboard; it is cleared in {} seconds.", clip.clipboard_seconds);
+    session.report("clipboard_seconds", clip.clipboard_seconds);
+    copied.wait();
+    Ok(true)
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_secret(_: &mut Session, _: &ClipboardArgs, _: &str) -> Result<bool> {
+    Ok(false)
+}
+
+/// Reads QR-coded shares from the webcam until the threshold is in or the camera window is
+/// closed, showing which indexes have been captured.
+fn scan_shares(session: &mut Session, args: &CombineArgs) -> Result<(Vec<Vec<u8>>, Expected)> {
+    use shamir_cli::scan::{Capture, Scanned, Scanner};
+
+    let mut capture = Capture::new(args.expected_threshold);
+    let mut scanner = Scanner::open(args.camera.as_deref())?;
+    eprintln!("Hold each share's QR code up to the camera; close the camera window to stop early.");
+    let (mut last_rejected, mut last_progress) = (None, None);
+    while !capture.complete() {
+        let Some(code) = scanner.next_code()? else { break };
+        match capture.offer(&code) {
+            Scanned::Added(index) => {
+                let indexes = capture.indexes();
+                let listed: Vec<String> = indexes.iter().map(u8::to_string).collect();
+                let need = capture.threshold().map_or("?".to_string(), |t| t.to_string());
+                eprintln!("Captured index {} ({} of {}; indexes {})", index, indexes.len(), need, listed.join(", "));
+                session.validation(&format!("share #{}", indexes.len()), true, format!("index {}, scanned", index))?;
+            }
+            Scanned::Again(_) => {}
+            Scanned::Partial { have, of } if last_progress != Some((have, of)) => {
+                eprintln!("Receiving a multi-frame share: {}/{} fragments", have, of);
+                last_progress = Some((have, of));
+            }
+            Scanned::Partial { .. } => {}
+            // the camera reads a code many times over, so each rejection is reported once
+            Scanned::Rejected(e) if last_rejected.as_ref() != Some(&e) => {
+                eprintln!("Code rejected: {}", e);
+                session.validation("scanned code", false, e.clone())?;
+                last_rejected = Some(e);
+            }
+            Scanned::Rejected(_) => {}
+        }
+    }
+    drop(scanner);
+    let declared = Expected { set: capture.set().map(str::to_string), threshold: capture.threshold() };
+    Ok((capture.into_shares(), declared))
+}
+
+/// Prompts for shares until an empty entry. Returns them with the set and threshold their
+/// headers or manifests declare.
+fn read_shares(session: &mut Session, args: &CombineArgs) -> Result<(Vec<Vec<u8>>, Expected)> {
+    if args.scan {
+        return scan_shares(session, args);
+    }
+    let mut intake = Intake::default();
+    if let Some(entries) = listed_shares(args)? {
+        for entry in entries.iter() {
+            for note in intake.take(session, args, entry)?.notes {
+                eprintln!("{}", note);
+            }
+        }
+        return Ok(intake.finish(session));
+    }
+    loop {
+        let prompt = tr!("prompt-share", number = intake.shares.len() + 1);
+        let entry: String = session.ask_secret(&prompt, |p| {
+            if args.hide_input {
+                cli::console::password(p, true)
+            } else {
+                Input::<String>::new()
+                    .with_prompt(p)
+                    .allow_empty(true)
+                    .interact_text()
+            }
+        })?;
+        let entry = input::normalize_str(&entry);
+        let entry = entry.trim();
+        if entry.is_empty() {
+            break;
+        }
+        for note in intake.take(session, args, entry)?.notes {
+            eprintln!("{}", note);
+        }
+    }
+    Ok(intake.finish(session))
+}
+
+/// The share entries given up front: the --share files, then the lines of --shares-file, or
+/// of standard input when it is not a terminal and nothing else names the shares, split as
+/// [`input::share_entries`] does. `None` when they are to be typed at the prompt.
+fn listed_shares(args: &CombineArgs) -> Result<Option<Zeroizing<Vec<String>>>> {
+    use std::io::{IsTerminal, Read};
+
+    let mut entries = Zeroizing::new(args.shares.iter().map(|path| format!("@{}", path.display())).collect::<Vec<_>>());
+    let from_stdin = match &args.shares_file {
+        Some(path) => path.as_os_str() == "-",
+        None => entries.is_empty() && !std::io::stdin().is_terminal(),
+    };
+    let text = match &args.shares_file {
+        _ if from_stdin => {
+            let mut text = Zeroizing::new(String::new());
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        Some(path) => Zeroizing::new(std::fs::read_to_string(path)?),
+        None if entries.is_empty() => return Ok(None),
+        None => Zeroizing::new(String::new()),
+    };
+    entries.extend(input::share_entries(&Zeroizing::new(input::normalize_str(&text))));
+    Ok(Some(entries))
+}
+
+/// The shares read so far, each with the set its header or manifest names, checked against
+/// the earlier ones.
+#[derive(Default)]
+struct Intake {
+    shares: Vec<(Vec<u8>, Option<String>)>,
+    declared: Expected,
+    labels: Vec<serde_json::Value>,
+    nest: subshare::Nest,
+}
+
+/// What became of one entry, and what to tell the custodians about it.
+#[derive(Default)]
+struct Taken {
+    /// The share's index, id and header, once it is accepted.
+    share: Option<(u8, String, ShareHeader)>,
+    /// Set for a sub-share still waiting for the others of its parent.
+    pending: bool,
+    notes: Vec<String>,
+}
+
+impl Intake {
+    fn take(&mut self, session: &mut Session, args: &CombineArgs, entry: &str) -> Result<Taken> {
+        let mut taken = Taken::default();
+        let subject = format!("share #{}", self.shares.len() + 1);
+        let path = entry.strip_prefix('@').map(|p| std::path::Path::new(p.trim()));
+        let read = match path {
+            Some(path) if shamir_cli::fragment::is_manifest(path) => shamir_cli::fragment::read(path)
+                .map(|(share, found)| (share, ShareHeader::default(), format!("rebuilt from {} fragments", found)))
+                .map_err(|e| e.to_string()),
+            Some(path) if shamir_cli::tpm::is_sealed(path) => shamir_cli::tpm::read(path)
+                .map(|share| (share.to_vec(), ShareHeader::default(), "unsealed by the TPM".to_string()))
+                .map_err(|e| e.to_string()),
+            Some(path) if shamir_cli::yubikey::is_wrapped(path) => shamir_cli::yubikey::read(path)
+                .map(|share| (share.to_vec(), ShareHeader::default(), "unwrapped by the YubiKey".to_string()))
+                .map_err(|e| e.to_string()),
+            _ => {
+                let text = match path {
+                    Some(path) if shamir_cli::stego::is_image(path) => shamir_cli::stego::reveal_file(path)
+                        .map(|text| text.to_string())
+                        .map_err(|e| e.to_string()),
+                    #[cfg(feature = "pq")]
+                    Some(path) if shamir_cli::pq::is_sealed(path) => open_pq_share(path, args),
+                    Some(path) => input::read_share_file(path).map_err(|e| e.to_string()),
+                    None => entered_share(entry),
+                };
+                let text = match text {
+                    Ok(text) => text,
+                    Err(e) => {
+                        taken.notes.push(e.clone());
+                        session.validation(&subject, false, e)?;
+                        return Ok(taken);
+                    }
+                };
+                let parsed = match (args.format, args.field) {
+                    (ShareFormat::Ssss, _) => ssss::parse_share(&text).map(|s| (s, 0)).map_err(|e| e.to_string()),
+                    (_, Field::Vault) => vault::parse_unseal_key(&text).map(|s| (s, 0)).map_err(|e| e.to_string()),
+                    _ => input::parse_share_corrected(&text).map_err(|e| e.to_string()),
+                };
+                parsed.map(|(share, corrected)| {
+                    if corrected > 0 {
+                        taken.notes.push(format!("Corrected {} damaged byte(s) in {}", corrected, subject));
+                    }
+                    let digest = Sha256::digest(&share);
+                    let detail = format!("id {}, {} byte(s) corrected", hex::encode(&digest[..4]), corrected);
+                    (share, ShareHeader::split(&text).0, detail)
+                })
+            }
+        };
+        let (share, header, detail) = match read {
+            Ok(read) => read,
+            Err(e) => return reject(session, &subject, e, taken),
+        };
+        let (share, header, detail) = match self.nest.add(share, header) {
+            Ok(Entered::Share(share, header, 0)) => (share, header, detail),
+            Ok(Entered::Share(share, header, used)) => {
+                taken.notes.push(format!("Rebuilt {} from {} sub-shares", subject, used));
+                (share, header, format!("rebuilt from {} sub-shares", used))
+            }
+            Ok(Entered::Pending { parent, have, need }) => {
+                taken.notes.push(format!("Sub-share {} of {} needed for share {}", have, need, parent));
+                session.validation(&subject, true, format!("sub-share {} of {} for share {}", have, need, parent))?;
+                taken.pending = true;
+                return Ok(taken);
+            }
+            Err(e) => return reject(session, &subject, e.to_string(), taken),
+        };
+
+        let listed = path.and_then(listed_set);
+        let set = header.set.clone().or(listed.as_ref().map(|(set, _)| set.clone()));
+        if let Some(conflict) = format::share_conflict(&self.shares, &share, set.as_deref()) {
+            let e = format!("{} does not belong with the others: {}", subject, conflict);
+            return reject(session, &subject, e, taken);
+        }
+        if let Some(label) = &header.label {
+            session.audit_participant(label);
+        }
+        if header.label.is_some() || header.note.is_some() {
+            taken.notes.push(format!("{} is labelled {}", subject, header));
+            self.labels.push(json!({ "index": share[0], "label": header.label, "note": header.note }));
+        }
+        session.validation(&subject, true, format!("index {}, {}", share[0], detail))?;
+        self.declared.set = self.declared.set.take().or(set.clone());
+        self.declared.threshold = self
+            .declared
+            .threshold
+            .or(header.quorum.map(|(threshold, _)| threshold))
+            .or(listed.map(|(_, threshold)| threshold));
+        taken.share = Some((share[0], hex::encode(&Sha256::digest(&share)[..4]), header));
+        self.shares.push((share, set));
+        Ok(taken)
+    }
 
-    match mode {
-        0 => encrypt_flow(),
-        1 => decrypt_flow(),
-        _ => unreachable!(),
+    fn finish(self, session: &mut Session) -> (Vec<Vec<u8>>, Expected) {
+        for (parent, have) in self.nest.pending() {
+            eprintln!("Left out share {}: only {} of its sub-shares were entered", parent, have);
+        }
+        if !self.labels.is_empty() {
+            session.report("labels", self.labels);
+        }
+        (self.shares.into_iter().map(|(share, _)| share).collect(), self.declared)
     }
 }
 
-fn encrypt_flow() {
-    let secret = Input::<String>::new()
-        .with_prompt("Enter secret value")
-        .interact()
-        .unwrap();
+/// Reads the shares, and the password for them, on the ceremony screen.
+#[cfg(feature = "tui")]
+fn ceremony_shares(session: &mut Session, args: &CombineArgs) -> Result<(Vec<Vec<u8>>, Expected, Zeroizing<String>)> {
+    use shamir_cli::tui::{self, Entry, Status};
+    let mut intake = Intake::default();
+    let password = tui::run(args.expected_threshold, |entry| {
+        let entry = input::normalize_str(entry);
+        let subject = format!("Share #{}", intake.shares.len() + 1);
+        let taken = intake.take(session, args, entry.trim())?;
+        let status = match (&taken.share, taken.pending) {
+            (Some(_), _) => Status::Accepted,
+            (None, true) => Status::Pending,
+            (None, false) => Status::Rejected,
+        };
+        let (index, id, header) = taken.share.map_or((None, None, ShareHeader::default()), |(index, id, header)| {
+            (Some(index), Some(id), header)
+        });
+        let entry = Entry { subject, status, index, id, header, notes: taken.notes };
+        Ok((entry, intake.declared.threshold))
+    })?;
+    let (shares, declared) = intake.finish(session);
+    Ok((shares, declared, password))
+}
+
+fn reject(session: &mut Session, subject: &str, e: String, mut taken: Taken) -> Result<Taken> {
+    taken.notes.push(tr!("share-rejected", reason = e));
+    session.validation(subject, false, e)?;
+    Ok(taken)
+}
+
+#[cfg(feature = "pq")]
+fn open_pq_share(path: &std::path::Path, args: &CombineArgs) -> std::result::Result<String, String> {
+    let identity = args.pq_identity.as_deref().ok_or("pass --pq-identity to open post-quantum encrypted shares")?;
+    let identity = shamir_cli::pq::Identity::read(identity).map_err(|e| e.to_string())?;
+    shamir_cli::pq::read(path, &identity).map(|text| text.to_string()).map_err(|e| e.to_string())
+}
+
+/// The set fingerprint and threshold of the `manifest.json` beside a share file, if it lists
+/// the file.
+fn listed_set(path: &std::path::Path) -> Option<(String, u8)> {
+    let set = manifest::read_manifest(path.parent()?).ok()?;
+    let name = path.file_name()?.to_string_lossy();
+    set.shares.iter().any(|e| e.file == name).then_some((set.set_fingerprint, set.threshold))
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_command(command: KeyringCommand) -> Result<()> {
+    use shamir_cli::keychain;
 
-    let secret_data = match secret.parse::<i64>() {
-        Ok(i) => SecretType::Int(i),
-        Err(_) => match secret.parse::<f64>() {
-            Ok(f) => SecretType::Float(f),
-            Err(_) => SecretType::String(secret),
+    match command {
+        KeyringCommand::Store { name } => {
+            let text = Zeroizing::new(
+                cli::console::password(&format!("Share to keep as '{}'", name), false)
+                    .map_err(cli::session::prompt_error)?,
+            );
+            let text = input::normalize_str(&text);
+            let parsed = input::parse_share_details(&text)?;
+            keychain::store(&name, text.trim())?;
+            println!(
+                "Stored share #{} in the OS keyring as '{}'; enter {}{} at the share prompt to use it",
+                parsed.share[0],
+                name,
+                keychain::PREFIX,
+                name
+            );
+        }
+        KeyringCommand::Delete { name } => match keychain::delete(&name)? {
+            true => println!("Removed '{}' from the OS keyring", name),
+            false => println!("The OS keyring holds no share named '{}'", name),
         },
+    }
+    Ok(())
+}
+
+fn tpm_command(command: TpmCommand) -> Result<()> {
+    use shamir_cli::tpm;
+
+    match command {
+        TpmCommand::Seal { out, pcrs } => {
+            let pcrs = tpm::parse_pcrs(&pcrs)?;
+            let text = Zeroizing::new(
+                cli::console::password("Share to seal", false).map_err(cli::session::prompt_error)?,
+            );
+            let share = Zeroizing::new(input::parse_share(&input::normalize_str(&text))?);
+            let sealed = tpm::seal(&share, &pcrs)?;
+            tpm::write(&out, &sealed)?;
+            println!("Sealed share #{} (ID {}) to this TPM under {} in {}", sealed.index, sealed.id, pcrs, out.display());
+        }
+    }
+    Ok(())
+}
+
+/// Loads the HSM's PKCS#11 module and logs in with the user PIN.
+#[cfg(feature = "pkcs11")]
+fn hsm_login(session: &mut Session, hsm: &Pkcs11Args) -> Result<pkcs11::Hsm> {
+    let module = hsm
+        .pkcs11_module
+        .as_deref()
+        .ok_or_else(|| {
+            SssError::InvalidArgument("these shares were encrypted by an HSM; give --pkcs11-module".into())
+        })?;
+    let pin = Zeroizing::new(session.ask_secret("Enter the HSM user PIN", |p| {
+        cli::console::password(p, false)
+    })?);
+    pkcs11::Hsm::login(module, hsm.pkcs11_token.as_deref(), &pin)
+}
+
+#[cfg(feature = "pkcs11")]
+fn open_hsm_payload(session: &mut Session, hsm: &Pkcs11Args, payload: &[u8]) -> Result<SecretBuf> {
+    hsm_login(session, hsm)?.open(payload)
+}
+
+#[cfg(not(feature = "pkcs11"))]
+fn open_hsm_payload(_: &mut Session, _: &Pkcs11Args, _: &[u8]) -> Result<SecretBuf> {
+    Err(SssError::Parse(
+        "these shares were encrypted by an HSM; decrypt them with sss built with the pkcs11 feature".into(),
+    ))
+}
+
+#[cfg(feature = "pkcs11")]
+fn pkcs11_command(command: Pkcs11Command) -> Result<()> {
+    match command {
+        Pkcs11Command::Keygen { label, hsm } => {
+            let mut session = Session::new(None, "pkcs11 keygen")?;
+            hsm_login(&mut session, &hsm)?.generate_key(&label)?;
+            println!("Generated AES-256 key '{}' on the token; encrypt with --pkcs11-key {}", label, label);
+        }
+    }
+    Ok(())
+}
+
+fn yubikey_command(command: YubikeyCommand) -> Result<()> {
+    use shamir_cli::yubikey;
+
+    match command {
+        YubikeyCommand::Wrap { out, slot } => {
+            let text = Zeroizing::new(
+                cli::console::password("Share to wrap", false).map_err(cli::session::prompt_error)?,
+            );
+            let share = Zeroizing::new(input::parse_share(&input::normalize_str(&text))?);
+            let wrapped = yubikey::wrap(&share, slot)?;
+            yubikey::write(&out, &wrapped)?;
+            let (index, id) = (wrapped.index, &wrapped.id);
+            println!("Wrapped share #{} (ID {}) with YubiKey slot {} in {}", index, id, slot, out.display());
+        }
+    }
+    Ok(())
+}
+
+fn stego_command(command: StegoCommand) -> Result<()> {
+    match command {
+        StegoCommand::Hide { cover, out } => {
+            let text = Zeroizing::new(
+                cli::console::password("Share to hide", false).map_err(cli::session::prompt_error)?,
+            );
+            let text = Zeroizing::new(input::normalize_str(&text));
+            let share = Zeroizing::new(input::parse_share(&text)?);
+            shamir_cli::stego::hide_file(&cover, &out, &text)?;
+            println!("Hid share #{} in {}", share[0], out.display());
+        }
+    }
+    Ok(())
+}
+
+/// Verifies a heartbeat and reports whether it still holds its set shut; a lapsed heartbeat
+/// is a policy error, so schedulers can alert on the exit code.
+fn check_in(path: &std::path::Path, dealer_key: &ed25519_dalek::VerifyingKey) -> Result<()> {
+    let heartbeat = Heartbeat::read(path)?;
+    heartbeat.verify(dealer_key.as_bytes(), &heartbeat.fingerprint)?;
+    let until = timelock::format_date(heartbeat.expires_at);
+    if timelock::system_now() >= heartbeat.expires_at {
+        return Err(SssError::Policy(format!(
+            "the heartbeat for set {} lapsed at {}; the switch is open",
+            heartbeat.fingerprint, until
+        )));
+    }
+    println!(
+        "Heartbeat for set {} signed at {} holds until {}",
+        heartbeat.fingerprint,
+        timelock::format_date(heartbeat.issued_at),
+        until
+    );
+    Ok(())
+}
+
+fn guardian_command(command: GuardianCommand) -> Result<()> {
+    match command {
+        GuardianCommand::Keygen { out } => {
+            let key = token::generate_key();
+            token::write_signing_key(&out, &key)?;
+            println!("Wrote the guardian signing key to {}", out.display());
+            println!("Guardian key (give this to the dealer): {}", hex::encode(key.verifying_key().to_bytes()));
+        }
+        GuardianCommand::Add { name, key, contact, registry: path } => {
+            let mut registry = social::Registry::read(&path)?;
+            registry.add(social::Guardian { name: name.clone(), contact, public_key: key.trim().to_lowercase() })?;
+            registry.write(&path)?;
+            println!("Registered {} as guardian #{} in {}", name, registry.guardians.len(), path.display());
+        }
+        GuardianCommand::List { registry } => {
+            for guardian in social::Registry::read(&registry)?.guardians {
+                let contact = guardian.contact.map(|c| format!(" <{}>", c)).unwrap_or_default();
+                println!("{}{}  {}", guardian.name, contact, guardian.public_key);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn recover_command(command: RecoverCommand, records: Records, json: bool) -> Result<()> {
+    match command {
+        RecoverCommand::Start { registry, fingerprint, dir } => {
+            let registry = social::Registry::read(&registry)?;
+            let recovery = social::RecoverySession::generate(fingerprint, &mut rand::rngs::OsRng);
+            let challenges = recovery.write(&dir, &registry)?;
+            println!("Started recovery session {} in {}", recovery.id, dir.display());
+            for (path, challenge) in challenges {
+                let contact = registry.find(&challenge.guardian).and_then(|g| g.contact.clone());
+                let to = contact.map(|c| format!(" ({})", c)).unwrap_or_default();
+                println!("  send {} to {}{}", path.display(), challenge.guardian, to);
+            }
+            println!("Put the answers in {} and run: sss recover finish --dir {}", dir.display(), dir.display());
+        }
+        RecoverCommand::Answer { challenge: path, key, share, out } => {
+            let challenge = social::Challenge::read(&path)?;
+            let key = token::read_signing_key(&key)?;
+            let set = challenge.set.as_deref().map(|set| format!(" of set {}", set)).unwrap_or_default();
+            println!("Recovery session {} asks {} for their share{}", challenge.session, challenge.guardian, set);
+            let text = match share {
+                Some(share) => Zeroizing::new(input::read_share_file(&share)?),
+                None => Zeroizing::new(
+                    cli::console::password("Your share", false).map_err(cli::session::prompt_error)?,
+                ),
+            };
+            input::parse_share_corrected(&text)?;
+            let answer = social::answer(&challenge, &key, &text)?;
+            let out = out.unwrap_or_else(|| {
+                let name = path.file_name().map(|n| n.to_string_lossy().replacen("challenge-", "answer-", 1));
+                path.with_file_name(name.unwrap_or_else(|| "answer.json".into()))
+            });
+            answer.write(&out)?;
+            println!("Wrote your answer to {}; send it back to the coordinator", out.display());
+        }
+        RecoverCommand::Finish { answers, dir, registry } => {
+            let mut session = new_session(records, "recover")?;
+            if json {
+                session.report_json();
+            }
+            let result = finish_recovery(&mut session, &dir, &registry, answers);
+            session.finish(&result)?;
+            return result;
+        }
+    }
+    Ok(())
+}
+
+/// Opens each guardian's answer, keeping the shares that check out, and recovers from them.
+fn finish_recovery(
+    session: &mut Session,
+    dir: &std::path::Path,
+    registry: &std::path::Path,
+    mut answers: Vec<PathBuf>,
+) -> Result<()> {
+    let recovery = social::RecoverySession::read(dir)?;
+    let registry = social::Registry::read(registry)?;
+    if answers.is_empty() {
+        answers = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.file_name().is_some_and(|n| n.to_string_lossy().starts_with("answer-")))
+            .collect();
+        answers.sort();
+    }
+    let mut shares: Vec<(Vec<u8>, Option<String>)> = Vec::new();
+    let mut declared = Expected { set: recovery.set.clone(), threshold: None };
+    for path in answers {
+        let subject = path.display().to_string();
+        let opened = social::Answer::read(&path)
+            .and_then(|answer| Ok((recovery.open(&registry, &answer)?, answer.guardian)));
+        let (text, guardian) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                eprintln!("Answer rejected: {}: {}", subject, e);
+                session.validation(&subject, false, e.to_string())?;
+                continue;
+            }
+        };
+        if accept_share(session, &mut shares, &mut declared, &format!("{}'s share", guardian), &text)? {
+            say!("Accepted {}'s share", guardian);
+        }
+    }
+    let shares = shares.into_iter().map(|(share, _)| share).collect();
+    recover_flow(session, shares, &declared, &CombineArgs::default())
+}
+
+/// Takes the share in `text` unless it is malformed or belongs with another set than the
+/// shares before it, noting the set and threshold its header declares. Returns whether it
+/// was taken.
+fn accept_share(
+    session: &mut Session,
+    shares: &mut Vec<(Vec<u8>, Option<String>)>,
+    declared: &mut Expected,
+    subject: &str,
+    text: &str,
+) -> Result<bool> {
+    let (share, _) = match input::parse_share_corrected(text) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", tr!("share-rejected", reason = format!("{}: {}", subject, e)));
+            session.validation(subject, false, e.to_string())?;
+            return Ok(false);
+        }
     };
+    let header = ShareHeader::split(text).0;
+    let set = header.set.clone().or(declared.set.clone());
+    if let Some(conflict) = format::share_conflict(shares, &share, set.as_deref()) {
+        let e = format!("{} does not belong with the others: {}", subject, conflict);
+        eprintln!("{}", tr!("share-rejected", reason = e));
+        session.validation(subject, false, e)?;
+        return Ok(false);
+    }
+    session.validation(subject, true, format!("index {}", share[0]))?;
+    declared.set = declared.set.take().or(set.clone());
+    declared.threshold = declared.threshold.or(header.quorum.map(|(threshold, _)| threshold));
+    shares.push((share, set));
+    Ok(true)
+}
 
-    let mut bytes = serialize_secret(secret_data);
-    pad_to_minimum(&mut bytes);
-    
-    let password = Password::new()
-        .with_prompt("Enter encryption password")
-        .interact()
-        .unwrap();
-
-    let (encrypted_data, salt, nonce) = encrypt_data(&bytes, &password);
-
-    // Prepend salt and nonce to encrypted data
-    let mut combined_data = Vec::new();
-    combined_data.extend_from_slice(&salt);
-    combined_data.extend_from_slice(&nonce);
-    combined_data.extend_from_slice(&encrypted_data);
-
-    let total_shares: u8 = Input::new()
-        .with_prompt("Total number of shares")
-        .interact()
-        .unwrap();
-
-    let threshold: u8 = Input::new()
-        .with_prompt("Minimum shares required")
-        .interact()
-        .unwrap();
-
-    let shares = share(&combined_data, threshold, total_shares, false)
-        .expect("Failed to create shares");
-
-    println!("\nSAVE THESE VALUES FOR DECRYPTION:");
-    println!("Salt: {}", hex::encode(salt));
-    println!("Nonce: {}", hex::encode(nonce));
-    println!("\nGenerated shares:");
-    for share in &shares {
-        let mut hasher = Sha256::new();
-        hasher.update(share.as_slice());
-        let hash_bytes = hasher.finalize();
-        let short_hash_hex = hex::encode(&hash_bytes[..4]);
-
-        println!("Share ID '{:?}': {:?}", short_hash_hex, share);
-    }
-}
-
-fn decrypt_flow() {
-    let shares: Vec<String> = Input::<String>::new()
-        .with_prompt("Enter shares (comma separated)")
-        .interact_text()
-        .unwrap()
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .collect();
+/// Pulls shares back from their placements until the threshold their headers declare is in,
+/// going past backends that cannot be reached.
+fn placed_shares(session: &mut Session, placement: &PlacementConfig) -> Result<(Vec<Vec<u8>>, Expected)> {
+    let mut shares = Vec::new();
+    let mut declared = Expected::default();
+    for placed in &placement.placements {
+        if declared.threshold.is_some_and(|threshold| shares.len() >= threshold as usize) {
+            break;
+        }
+        let subject = format!("share #{} from {}", placed.share, placed.backend);
+        match placed.backend.pull() {
+            Ok(text) => {
+                if accept_share(session, &mut shares, &mut declared, &subject, &text)? {
+                    eprintln!("Pulled {}", subject);
+                }
+            }
+            Err(e) => {
+                eprintln!("Could not pull {}: {}", subject, e);
+                session.validation(&subject, false, e.to_string())?;
+            }
+        }
+    }
+    Ok((shares.into_iter().map(|(share, _)| share).collect(), declared))
+}
+
+#[cfg(feature = "pq")]
+fn pq_command(command: PqCommand) -> Result<()> {
+    match command {
+        PqCommand::Keygen { out } => {
+            secure::ensure_writable(&out)?;
+            let identity = shamir_cli::pq::Identity::generate(&mut rand::rngs::OsRng);
+            let recipient = identity.to_public();
+            let text = Zeroizing::new(format!("# public key: {}\n{}\n", recipient, identity.to_text().as_str()));
+            write_private(&out, text.as_bytes())?;
+            println!("Wrote the identity to {}", out.display());
+            println!("Public key {} (give this to the dealer):\n{}", recipient.id(), recipient);
+        }
+    }
+    Ok(())
+}
+
+fn locale_command(command: LocaleCommand) -> Result<()> {
+    match command {
+        LocaleCommand::List => {
+            let english = Catalog::reference().ids().count();
+            let installed = i18n::locale_dir().and_then(|dir| std::fs::read_dir(dir).ok());
+            let installed = installed.into_iter().flatten().filter_map(|entry| entry.ok()).map(|entry| entry.path());
+            let mut languages: Vec<(Catalog, &str)> = Catalog::builtin_languages()
+                .filter_map(|language| Catalog::builtin(language).map(|catalog| (catalog, "built in")))
+                .collect();
+            for path in installed.filter(|path| path.extension().is_some_and(|e| e == "ftl")) {
+                let catalog = Catalog::read(&path)?;
+                languages.retain(|(other, _)| other.language != catalog.language);
+                languages.push((catalog, "installed"));
+            }
+            for (catalog, source) in languages {
+                let translated = catalog.ids().filter(|id| Catalog::reference().get(id).is_some()).count();
+                println!("{:<8} {:>3}/{} messages  {}", catalog.language, translated, english, source);
+            }
+        }
+        LocaleCommand::Template => print!("{}", i18n::template()),
+        LocaleCommand::Check { file } => {
+            let catalog = Catalog::read(&file)?;
+            let problems = catalog.check();
+            for problem in &problems {
+                println!("{}", problem);
+            }
+            if !problems.is_empty() {
+                return Err(SssError::Parse(format!("{} problem(s) in {}", problems.len(), file.display())));
+            }
+            println!("{} translates every message", catalog.language);
+        }
+    }
+    Ok(())
+}
 
-    let password = Password::new()
-        .with_prompt("Enter encryption password")
-        .interact()
-        .unwrap();
+fn token_command(command: TokenCommand) -> Result<()> {
+    match command {
+        TokenCommand::Keygen { out } => {
+            let key = token::generate_key();
+            token::write_signing_key(&out, &key)?;
+            println!("Wrote the dealer signing key to {}", out.display());
+            println!("Issuer key (give this to coordinators): {}", hex::encode(key.verifying_key().to_bytes()));
+        }
+        TokenCommand::Issue { key, holder, fingerprint, valid_hours } => {
+            let key = token::read_signing_key(&key)?;
+            let valid_for = std::time::Duration::from_secs(valid_hours.saturating_mul(3600));
+            println!("{}", token::issue(&key, &holder, &fingerprint, valid_for)?);
+        }
+    }
+    Ok(())
+}
 
-    let encrypted_data = reconstruct(&shares, false)
-        .expect("Failed to recover secret");
+fn tdec_command(command: TdecCommand) -> Result<()> {
+    use shamir_cli::elgamal;
 
-    // Extract salt and nonce from the beginning of the encrypted data
-    let salt = &encrypted_data[..SALT_LEN];
-    let nonce = &encrypted_data[SALT_LEN..SALT_LEN + NONCE_LEN];
-    let encrypted_data = &encrypted_data[SALT_LEN + NONCE_LEN..];
+    let read_hex = |path: &PathBuf, what: &str| -> Result<Zeroizing<Vec<u8>>> {
+        let text = Zeroizing::new(std::fs::read_to_string(path)?);
+        hex::decode(text.trim())
+            .map(Zeroizing::new)
+            .map_err(|_| SssError::Parse(format!("{} in {} is not hex", what, path.display())))
+    };
 
-    let decrypted_data = decrypt_data(
-        encrypted_data,
-        &password,
-        salt,
-        nonce
-    ).expect("Decryption failed");
+    match command {
+        TdecCommand::Keygen { threshold, total, out_dir } => {
+            let dealt = elgamal::keygen(threshold, total)?;
+            std::fs::create_dir_all(&out_dir)?;
+            for share in &dealt.shares {
+                let path = out_dir.join(format!("key-share-{}.txt", share[0]));
+                write_private(path, Zeroizing::new(format!("{}\n", hex::encode(share.as_slice()))).as_bytes())?;
+            }
+            println!("Wrote {} key shares to {}; hand one to each holder", dealt.shares.len(), out_dir.display());
+            println!("Public key: {}", hex::encode(&dealt.public_key));
+        }
+        TdecCommand::Encrypt { public_key, input, output } => {
+            let public_key = hex::decode(public_key.trim())
+                .map_err(|_| SssError::Parse("public key is not hex".into()))?;
+            let sealed = elgamal::encrypt(&public_key, &std::fs::read(&input)?)?;
+            std::fs::write(&output, sealed)?;
+            println!("Encrypted {} to {}", input.display(), output.display());
+        }
+        TdecCommand::Partial { share, ciphertext, output } => {
+            let share = read_hex(&share, "key share")?;
+            let partial = hex::encode(elgamal::partial_decrypt(&share, &std::fs::read(&ciphertext)?)?);
+            if let Some(output) = output {
+                std::fs::write(&output, format!("{}\n", partial))?;
+                eprintln!("Wrote {}", output.display());
+            }
+            println!("{}", partial);
+        }
+        TdecCommand::Combine { ciphertext, partials, output } => {
+            let partials = partials
+                .iter()
+                .map(|p| read_hex(p, "partial decryption"))
+                .collect::<Result<Vec<_>>>()?;
+            let plaintext = elgamal::combine(&partials, &std::fs::read(&ciphertext)?)?;
+            write_private(&output, plaintext.as_slice())?;
+            println!("Decrypted {} bytes to {}", plaintext.len(), output.display());
+        }
+    }
+    Ok(())
+}
 
-    let secret = deserialize_secret(&decrypted_data);
-    
-    println!("\nRecovered secret:");
-    match secret {
-        SecretType::String(s) => println!("{}", s),
-        SecretType::Int(i) => println!("{}", i),
-        SecretType::Float(f) => println!("{}", f),
+fn vault_command(session: &mut Session, command: VaultCommand) -> Result<()> {
+    match command {
+        VaultCommand::Resplit { init, threshold, total, out } => {
+            let text = Zeroizing::new(std::fs::read_to_string(&init)?);
+            let init_output: vault::InitOutput = serde_json::from_str(&text)
+                .map_err(|e| SssError::Parse(format!("{} is not Vault init output: {}", init.display(), e)))?;
+            session.audit_param("threshold", threshold);
+            session.audit_param("total", total);
+            let shares = vault::resplit(&init_output, threshold, total)?;
+            session.audit_shares(&shares);
+            drop(init_output);
+            let document = Zeroizing::new(
+                serde_json::to_string_pretty(&vault::InitOutput::from_shares(&shares, threshold))
+                    .map_err(|e| SssError::Parse(e.to_string()))?,
+            );
+            match out {
+                Some(out) => {
+                    write_private(&out, format!("{}\n", document.as_str()).as_bytes())?;
+                    println!("Wrote {} unseal keys, threshold {}, to {}", shares.len(), threshold, out.display());
+                }
+                None => println!("{}", document.as_str()),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn qr_export(path: &std::path::Path, fragment_len: usize, fps: u64, frames: Option<u32>, ur: bool) -> Result<()> {
+    use shamir_cli::bcur::ShareParts;
+    use shamir_cli::fountain::{terminal_qr, Encoder};
+    use std::io::{IsTerminal, Write};
+    type NextFrame = Box<dyn FnMut(u32) -> Result<Zeroizing<String>>>;
+
+    let (mut next_frame, count): (NextFrame, usize) = if ur {
+        let share = Zeroizing::new(input::parse_share(&input::read_share_file(path)?)?);
+        let mut parts = ShareParts::new(&share, fragment_len)?;
+        let count = parts.fragment_count();
+        (Box::new(move |_| parts.next_part().map(Zeroizing::new)), count)
+    } else {
+        let encoder = Encoder::new(&Zeroizing::new(std::fs::read(path)?), fragment_len)?;
+        let count = encoder.fragment_count();
+        (Box::new(move |seq| Ok(encoder.frame(seq))), count)
+    };
+    if let Some(frames) = frames {
+        for seq in 1..=frames {
+            println!("{}", next_frame(seq)?.as_str());
+        }
+        return Ok(());
+    }
+    if !std::io::stdout().is_terminal() {
+        return Err(SssError::InvalidArgument(
+            "the animation needs a terminal; give --frames to print frame texts".into(),
+        ));
+    }
+    let mut stdout = std::io::stdout();
+    for seq in 1.. {
+        let code = terminal_qr(&next_frame(seq)?)?;
+        // home and erase, then the next frame, so the code stays put on screen
+        write!(stdout, "\x1b[H\x1b[2J{}", code)?;
+        writeln!(stdout, "Frame {} of a {}-fragment loop; Ctrl-C to stop", seq, count)?;
+        stdout.flush()?;
+        std::thread::sleep(std::time::Duration::from_millis(1000 / fps));
+    }
+    Ok(())
+}
+
+fn fragment_command(command: FragmentCommand) -> Result<()> {
+    use shamir_cli::fragment;
+
+    match command {
+        FragmentCommand::Split { share, needed, total, locations } => {
+            let text = input::read_share_file(&share)?;
+            let bytes = Zeroizing::new(input::parse_share(&text)?);
+            let stem = share
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "share".to_string());
+            for path in fragment::write(&bytes, needed, total, &locations, &stem)? {
+                println!("Wrote {}", path.display());
+            }
+            println!(
+                "Any {} of the {} fragments rebuild share #{}; give `@{}{}` wherever the share is asked for.",
+                needed,
+                total,
+                bytes[0],
+                stem,
+                fragment::MANIFEST_SUFFIX
+            );
+        }
+        FragmentCommand::Join { manifest, out } => {
+            let (share, found) = fragment::read(&manifest)?;
+            let share = Zeroizing::new(share);
+            eprintln!("Rebuilt share #{} from {} fragment(s)", share[0], found);
+            match out {
+                Some(out) => write_private(&out, format!("{}\n", hex::encode(share.as_slice())).as_bytes())?,
+                None => println!("{}", hex::encode(share.as_slice())),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sub_split(
+    path: &std::path::Path,
+    threshold: u8,
+    total: u8,
+    encoding: ShareEncoding,
+    out_dir: &std::path::Path,
+) -> Result<()> {
+    let text = Zeroizing::new(input::read_share_file(path)?);
+    let share = Zeroizing::new(input::parse_share(&text)?);
+    let (header, _) = ShareHeader::split(&text);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "share".to_string());
+    std::fs::create_dir_all(out_dir)?;
+    for (subshare, header) in subshare::split(&share, &header, threshold, total, &mut rand::rngs::OsRng)? {
+        let subshare = Zeroizing::new(subshare);
+        let out = out_dir.join(format!("{}-sub-{:02}-of-{:02}.sss", stem, subshare[0], total));
+        write_private(&out, format!("{}\n", header.prepend(&encoding.encode(&subshare)?)).as_bytes())?;
+        println!("Wrote {}", out.display());
+    }
+    println!(
+        "Any {} of the {} sub-shares stand in for share #{} ({}) wherever it is asked for.",
+        threshold,
+        total,
+        share[0],
+        audit::share_id(&share)
+    );
+    Ok(())
+}
+
+fn envelope_command(command: EnvelopeCommand) -> Result<()> {
+    use shamir_cli::envelope::{self, Header};
+    use std::io::{BufReader, BufWriter};
+
+    let rng = &mut rand::rngs::OsRng;
+    let open_file = |path: &std::path::Path| -> Result<BufReader<std::fs::File>> {
+        Ok(BufReader::new(std::fs::File::open(path)?))
+    };
+    match command {
+        EnvelopeCommand::Seal { file, out, chunk_size, shares } => {
+            let out = out.unwrap_or_else(|| {
+                let mut name = file.clone().into_os_string();
+                name.push(".sse");
+                PathBuf::from(name)
+            });
+            shamir::check_parameters(shares.threshold, shares.total)?;
+            let key = envelope::generate_key(rng);
+            let len = secure::write_private_with(&out, |output| {
+                envelope::seal(&key, &mut open_file(&file)?, &mut BufWriter::new(output), chunk_size, rng)
+            })?;
+            println!("Encrypted {} ({} bytes) into {}", file.display(), len, out.display());
+            let note = format!("envelope key for {}", file_label(&out));
+            write_key_shares(&envelope::split_key(&key, shares.threshold, shares.total, rng)?, &key, &note, &shares)?;
+        }
+        EnvelopeCommand::Open { file, shares, out } => {
+            let header = Header::read(&mut open_file(&file)?)?;
+            let key = envelope::combine_key(&read_key_shares(&shares)?, &header.key_id)?;
+            let out = out.unwrap_or_else(|| match file.extension() {
+                Some(ext) if ext == "sse" => file.with_extension(""),
+                _ => file.with_extension("opened"),
+            });
+            let len = secure::write_private_with(&out, |output| {
+                envelope::open(&key, &mut open_file(&file)?, &mut BufWriter::new(output))
+            })?;
+            println!("Decrypted {} bytes into {}", len, out.display());
+        }
+        EnvelopeCommand::Rewrap { file, old_shares, shares } => {
+            let header = Header::read(&mut open_file(&file)?)?;
+            let old = read_key_shares(&old_shares)?;
+            let key = envelope::combine_key(&old, &header.key_id)?;
+            let note = format!("envelope key for {}", file_label(&file));
+            write_key_shares(&envelope::split_key(&key, shares.threshold, shares.total, rng)?, &key, &note, &shares)?;
+            println!("The old key shares still open {}; destroy them once the new ones are stored", file.display());
+        }
+        EnvelopeCommand::Rotate { file, old_shares, out, shares } => {
+            let header = Header::read(&mut open_file(&file)?)?;
+            let old_key = envelope::combine_key(&read_key_shares(&old_shares)?, &header.key_id)?;
+            shamir::check_parameters(shares.threshold, shares.total)?;
+            let key = secure::write_private_with(&out, |output| {
+                envelope::rotate(&old_key, &mut open_file(&file)?, &mut BufWriter::new(output), rng)
+            })?;
+            println!("Re-encrypted {} under a new key into {}", file.display(), out.display());
+            let note = format!("envelope key for {}", file_label(&out));
+            write_key_shares(&envelope::split_key(&key, shares.threshold, shares.total, rng)?, &key, &note, &shares)?;
+            println!("Delete {} once the new key shares are stored; the old shares open only it", file.display());
+        }
+    }
+    Ok(())
+}
+
+fn file_label(path: &std::path::Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+/// Writes one file per key share, each naming the key it rebuilds and the quorum.
+fn write_key_shares(
+    key_shares: &[Vec<u8>],
+    key: &shamir_cli::envelope::Dek,
+    note: &str,
+    args: &KeyShareArgs,
+) -> Result<()> {
+    let key_id = hex::encode(shamir_cli::envelope::key_id(key));
+    let header = ShareHeader {
+        note: Some(note.to_string()),
+        set: Some(key_id.clone()),
+        quorum: Some((args.threshold, args.total)),
+        ..ShareHeader::default()
+    };
+    std::fs::create_dir_all(&args.out_dir)?;
+    for share in key_shares {
+        let share = Zeroizing::new(share.clone());
+        let text = header.prepend(&args.encoding.unwrap_or_default().encode(&share)?);
+        let out = args.out_dir.join(format!("key-{}-share-{:02}-of-{:02}.txt", &key_id[..8], share[0], args.total));
+        write_private(&out, format!("{}\n", text).as_bytes())?;
+        println!("Wrote {}", out.display());
     }
+    println!("Any {} of the {} key shares rebuild key {}.", args.threshold, args.total, key_id);
+    Ok(())
 }
 
-fn encrypt_data(data: &[u8], password: &str) -> (Vec<u8>, [u8; SALT_LEN], [u8; NONCE_LEN]) {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_millis();
+fn read_key_shares(paths: &[PathBuf]) -> Result<Vec<Zeroizing<Vec<u8>>>> {
+    paths
+        .iter()
+        .map(|path| Ok(Zeroizing::new(input::parse_share(&Zeroizing::new(input::read_share_file(path)?))?)))
+        .collect()
+}
+
+fn test_vectors_command(command: TestVectorsCommand, json: bool) -> Result<()> {
+    use shamir_cli::vectors::{self, VectorFile};
+
+    match command {
+        TestVectorsCommand::Generate { out } => {
+            let text =
+                serde_json::to_string_pretty(&vectors::generate()?).map_err(|e| SssError::Parse(e.to_string()))?;
+            match out {
+                Some(out) => {
+                    std::fs::write(&out, text + "\n")?;
+                    eprintln!("Wrote the test vectors to {}", out.display());
+                }
+                None => println!("{}", text),
+            }
+            Ok(())
+        }
+        TestVectorsCommand::Verify { file } => {
+            let file: VectorFile = serde_json::from_str(&std::fs::read_to_string(&file)?)
+                .map_err(|e| SssError::Parse(format!("{} is not a test vector file: {}", file.display(), e)))?;
+            let mut failed = 0;
+            let mut report = Vec::new();
+            for vector in &file.vectors {
+                let verdict = vectors::verify(vector);
+                if !json {
+                    match &verdict {
+                        Ok(()) => println!("ok      {}", vector.name),
+                        Err(e) => println!("FAILED  {}: {}", vector.name, e),
+                    }
+                }
+                failed += verdict.is_err() as usize;
+                let error = verdict.as_ref().err().map(|e| e.to_string());
+                report.push(json!({ "name": vector.name, "ok": verdict.is_ok(), "error": error }));
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report).map_err(|e| SssError::Parse(e.to_string()))?);
+            }
+            if failed > 0 {
+                return Err(SssError::Share(format!("{} of {} test vector(s) failed", failed, file.vectors.len())));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+fn serve_command(
+    listen: std::net::SocketAddr,
+    tls: Option<(PathBuf, PathBuf)>,
+    token_file: Option<PathBuf>,
+    workers: usize,
+    authority: Option<(ed25519_dalek::VerifyingKey, token::Ledger)>,
+) -> Result<()> {
+    use shamir_cli::server::{self, Api, Tls};
+
+    let tls = tls
+        .map(|(cert, key)| -> Result<Tls> {
+            Ok(Tls { certificate: std::fs::read(cert)?, private_key: std::fs::read(key)? })
+        })
+        .transpose()?;
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    let token = match token_file {
+        Some(path) => std::fs::read_to_string(path)?.trim().to_string(),
+        None => std::env::var("SSS_SERVE_TOKEN").unwrap_or_default(),
+    };
+    let token = if token.is_empty() {
+        let mut bytes = [0u8; 24];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        println!("Bearer token for this run: {}", token);
+        token
+    } else {
+        token
+    };
+    let rounds = calibration::load()?.map_or(crypto::PBKDF2_ROUNDS, |c| c.rounds);
+
+    let server = server::bind(listen, tls)?;
+    println!("Serving {}://{}/ (POST /split, /combine, /inspect, /verify; GET /health)", scheme, server.server_addr());
+    let api = match authority {
+        Some((issuer, ledger)) => Api::new(token, rounds).with_capabilities(issuer, ledger),
+        None => Api::new(token, rounds),
+    };
+    server::serve(&server, &api, workers);
+    Ok(())
+}
+
+#[cfg(all(unix, feature = "daemon"))]
+fn daemon_command(socket: Option<PathBuf>, allow_uid: &[u32]) -> Result<()> {
+    use shamir_cli::daemon::{self, Daemon};
+
+    let socket = socket
+        .or_else(daemon::default_socket)
+        .ok_or_else(|| SssError::InvalidArgument("XDG_RUNTIME_DIR is not set; give --socket".into()))?;
+    let listener = daemon::bind(&socket, !allow_uid.is_empty())?;
+    let rounds = calibration::load()?.map_or(crypto::PBKDF2_ROUNDS, |c| c.rounds);
+    println!("Listening on {}", socket.display());
+    Daemon::new(rounds, allow_uid).serve(&listener)
+}
 
-    let mut hasher = Sha256::new();
-    hasher.update(timestamp.to_be_bytes());
-    let hash_bytes = hasher.finalize();
+fn bench_kdf_command(target_ms: u64, no_save: bool, json: bool) -> Result<()> {
+    let (rounds, took) = crypto::calibrate(std::time::Duration::from_millis(target_ms))?;
+    let calibration = calibration::KdfCalibration {
+        algorithm: "pbkdf2-sha256".to_string(),
+        rounds,
+        target_ms,
+        measured_ms: took.as_millis() as u64,
+        measured_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    };
+    let saved = if no_save { None } else { Some(calibration::save(&calibration)?) };
+    if json {
+        let mut report = serde_json::to_value(&calibration).map_err(|e| SssError::Parse(e.to_string()))?;
+        report["saved"] = saved.as_ref().map(|p| p.display().to_string()).into();
+        println!("{}", serde_json::to_string_pretty(&report).map_err(|e| SssError::Parse(e.to_string()))?);
+        return Ok(());
+    }
+    println!(
+        "PBKDF2-HMAC-SHA256: {} rounds took {} ms here (target {} ms)",
+        rounds, calibration.measured_ms, target_ms
+    );
+    if rounds == crypto::PBKDF2_ROUNDS && calibration.measured_ms > target_ms {
+        println!("This machine is slower than the target; {} rounds is the least encrypt will use.", rounds);
+    }
+    match saved {
+        Some(path) => println!("Saved to {}; encrypt now uses {} rounds unless --kdf-rounds is given", path.display(), rounds),
+        None => println!("Pass --kdf-rounds {} to encrypt to use it", rounds),
+    }
+    Ok(())
+}
 
-    let mut salt = [0u8; SALT_LEN];
-    salt.copy_from_slice(&hash_bytes[..SALT_LEN]);
+fn bench_command(plan: &bench::Plan, json: bool) -> Result<()> {
+    let results = bench::run(plan)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).map_err(|e| SssError::Parse(e.to_string()))?);
+        return Ok(());
+    }
+    println!("{:<12} {:>10} {:>8} {:>14} {:>12}", "operation", "bytes", "runs", "mean", "throughput");
+    for m in &results {
+        let throughput = match m.mib_per_s {
+            Some(rate) => format!("{:.1} MiB/s", rate),
+            None => format!("{} rounds", plan.rounds),
+        };
+        println!("{:<12} {:>10} {:>8} {:>11.1} us {:>12}", m.operation, m.bytes, m.runs, m.mean_us, throughput);
+    }
+    Ok(())
+}
 
-    let mut nonce = [0u8; NONCE_LEN];
-    nonce.copy_from_slice(&hash_bytes[SALT_LEN..SALT_LEN + NONCE_LEN]);
+fn inspect_command(files: &[PathBuf], field: Field, json: bool) -> Result<()> {
+    let mut problems = 0;
+    let mut report = Vec::new();
+    for path in files {
+        match shamir_cli::inspect::inspect(path, field) {
+            Ok(info) => {
+                if !json {
+                    println!("{}", info);
+                }
+                report.push(json!({ "ok": true, "share": info }));
+            }
+            Err(e) => {
+                problems += 1;
+                if !json {
+                    println!("{}\n  FAILED       {}\n", path.display(), e);
+                }
+                report.push(json!({ "ok": false, "file": path.display().to_string(), "error": e.to_string() }));
+            }
+        }
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).map_err(|e| SssError::Parse(e.to_string()))?);
+    }
+    if problems > 0 {
+        return Err(SssError::Share(format!("{} of {} file(s) could not be read as shares", problems, files.len())));
+    }
+    Ok(())
+}
 
-    let mut key = [0u8; 32];
-    pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, 100_000, &mut key);
+fn doctor_command(
+    files: &[PathBuf],
+    field: Field,
+    saved: Option<legacy::Saved>,
+    passwords: Option<&std::path::Path>,
+    json: bool,
+) -> Result<()> {
+    use shamir_cli::doctor::{self, Evidence};
 
-    let key_array = GenericArray::from_slice(&key);
+    if field == Field::Secp256k1 {
+        return Err(SssError::InvalidArgument(
+            "secp256k1 keys are shared as they are, so there is no payload to check".into(),
+        ));
+    }
+    let mut evidence = Evidence { field, saved, ..Evidence::default() };
+    for path in files {
+        let text = input::read_share_file(path).map_err(SssError::from);
+        match text {
+            Ok(text) => evidence.shares.push((path.display().to_string(), text)),
+            Err(e) => eprintln!("FAILED  reading {}: {}", path.display(), e),
+        }
+    }
+    if files.is_empty() {
+        loop {
+            let number = evidence.shares.len() + 1;
+            let text: String = Input::new()
+                .with_prompt(format!("Paste share #{} (empty when done)", number))
+                .allow_empty(true)
+                .interact_text()
+                .map_err(cli::session::prompt_error)?;
+            if text.trim().is_empty() {
+                break;
+            }
+            evidence.shares.push((format!("share #{}", number), text));
+        }
+    }
+    match passwords {
+        Some(path) => {
+            let text = Zeroizing::new(std::fs::read_to_string(path)?);
+            let lines = text.lines().filter(|line| !line.is_empty());
+            evidence.passwords = lines.map(|line| Zeroizing::new(line.to_string())).collect();
+        }
+        None => loop {
+            let password = Zeroizing::new(
+                cli::console::password(
+                    &format!("Password candidate #{} (empty when done)", evidence.passwords.len() + 1),
+                    true,
+                )
+                .map_err(cli::session::prompt_error)?,
+            );
+            if password.is_empty() {
+                break;
+            }
+            evidence.passwords.push(password);
+        },
+    }
 
-    let encrypted_data = Aes256Gcm::new(key_array)
-        .encrypt(GenericArray::from_slice(&nonce), data)
-        .expect("Encryption failed");
+    let diagnosis = doctor::diagnose(&evidence);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diagnosis).map_err(|e| SssError::Parse(e.to_string()))?);
+    } else {
+        for finding in &diagnosis.findings {
+            let verdict = if finding.ok { "ok" } else { "FAILED" };
+            println!("{:<6}  {}: {}", verdict, finding.check, finding.detail);
+        }
+        println!("\nMost likely cause: {}", diagnosis.summary);
+    }
+    diagnosis.result()
+}
 
-    (encrypted_data, salt, nonce)
+fn log_command(command: LogCommand, audit_log: Option<&std::path::Path>) -> Result<()> {
+    match command {
+        LogCommand::Verify { file, expect } => {
+            let file = file
+                .as_deref()
+                .or(audit_log)
+                .ok_or_else(|| SssError::InvalidArgument("name the log, or set --audit-log or $SSS_AUDIT_LOG".into()))?;
+            let chain = audit::verify(file)?;
+            println!("{}: {} records, chain intact, head {}", file.display(), chain.records(), chain.head());
+            if let Some(expect) = expect {
+                match chain.records_after(&expect) {
+                    Some(0) => println!("The head is the expected one"),
+                    Some(n) => println!("{} records were added after the expected one", n),
+                    None => {
+                        return Err(SssError::Policy(format!(
+                            "the log holds no record hashed {}; it was cut short or replaced",
+                            expect
+                        )));
+                    }
+                }
+            }
+        }
+        LogCommand::VerifyCeremony { file, signer } => {
+            let signer = signer.as_deref().map(token::parse_verifying_key).transpose()?;
+            let key = shamir_cli::ceremony::verify(&std::fs::read_to_string(&file)?, signer.as_ref())?;
+            println!("{}: signed by {}, unchanged since", file.display(), hex::encode(key.to_bytes()));
+        }
+    }
+    Ok(())
 }
 
-fn decrypt_data(data: &[u8], password: &str, salt: &[u8], nonce: &[u8]) -> Result<Vec<u8>, aes_gcm::Error> {
-    let mut key = [0u8; 32];
-    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, 100_000, &mut key);
+fn read_hmac_key(path: &std::path::Path) -> Result<Zeroizing<Vec<u8>>> {
+    let bytes = Zeroizing::new(std::fs::read(path)?);
+    let key = Zeroizing::new(bytes.trim_ascii().to_vec());
+    if key.is_empty() {
+        return Err(input::InputError::Empty.into());
+    }
+    Ok(key)
+}
 
-    let key_array = GenericArray::from_slice(&key);
-    Aes256Gcm::new(key_array)
-        .decrypt(GenericArray::from_slice(nonce), data)
+fn receipts_command(command: ReceiptsCommand) -> Result<()> {
+    match command {
+        ReceiptsCommand::Sign { share, custodian, key, hmac_key, manifest, out } => {
+            let text = input::read_share_file(&share)?;
+            let manifest = manifest.as_deref().map(manifest::read_manifest).transpose()?;
+            let bytes = match manifest.as_ref().map(|m| m.field.parse::<Field>()).transpose()? {
+                Some(Field::Vault) => vault::parse_unseal_key(&text)?,
+                _ => input::parse_share(&text)?,
+            };
+            let mut receipt = receipt::Receipt::new(&bytes, &custodian, None);
+            if let Some(manifest) = &manifest {
+                let listed = manifest.shares.iter().any(|e| e.index == receipt.share_index && e.fingerprint == receipt.share_fingerprint);
+                if !listed {
+                    return Err(SssError::Checksum(format!(
+                        "share #{} is not the one the manifest lists",
+                        receipt.share_index
+                    )));
+                }
+                receipt.set_fingerprint = Some(manifest.set_fingerprint.clone());
+            }
+            match (key, hmac_key) {
+                (Some(key), _) => receipt.sign(&token::read_signing_key(key)?)?,
+                (None, Some(path)) => receipt.seal(&read_hmac_key(&path)?)?,
+                (None, None) => unreachable!("clap requires --key or --hmac-key"),
+            }
+            let out = out.unwrap_or_else(|| PathBuf::from(receipt::receipt_file_name(receipt.share_index)));
+            receipt.write(&out)?;
+            say!("Share #{} checked; wrote a receipt for the dealer to {}", receipt.share_index, out.display());
+        }
+        ReceiptsCommand::Verify { dir, receipts, hmac_key, registry } => {
+            let manifest = manifest::read_manifest(&dir)?;
+            let hmac_key = hmac_key.as_deref().map(read_hmac_key).transpose()?;
+            let registry = registry.as_deref().map(social::Registry::read).transpose()?;
+            let verifier = receipt::ReceiptVerifier { hmac_key: hmac_key.as_deref().map(|k| k.as_slice()), registry: registry.as_ref() };
+            let receipts = receipt::collect(&receipts)?
+                .iter()
+                .map(|path| receipt::Receipt::read(path))
+                .collect::<Result<Vec<_>>>()?;
+            let mut unaccounted = 0;
+            for (index, status) in receipt::account(&manifest, &receipts, &verifier) {
+                match status {
+                    receipt::Status::Received { custodian, received_at } => {
+                        println!("  ok       share #{} received by {} at {}", index, custodian, received_at)
+                    }
+                    receipt::Status::Invalid(e) => {
+                        unaccounted += 1;
+                        println!("  FAILED   share #{}: {}", index, e);
+                    }
+                    receipt::Status::Missing => {
+                        unaccounted += 1;
+                        println!("  MISSING  share #{}: no receipt", index);
+                    }
+                }
+            }
+            if unaccounted > 0 {
+                return Err(SssError::Signing(format!(
+                    "{} of {} shares have no valid receipt",
+                    unaccounted, manifest.total
+                )));
+            }
+            println!("All {} shares of set {} are accounted for", manifest.total, manifest.set_fingerprint);
+        }
+    }
+    Ok(())
 }
 
-fn serialize_secret(secret: SecretType) -> Vec<u8> {
-    let mut bytes = vec![];
-    match secret {
-        SecretType::String(s) => {
-            bytes.push(0u8);
-            bytes.extend_from_slice(&(s.len() as u32).to_be_bytes());
-            bytes.extend_from_slice(s.as_bytes());
+fn manifest_command(command: ManifestCommand, json: bool) -> Result<()> {
+    match command {
+        ManifestCommand::Verify { dir, signer } => {
+            let manifest = manifest::read_manifest(&dir)?;
+            let signer = signer.as_deref().map(token::parse_verifying_key).transpose()?;
+            if manifest.signature.is_some() || signer.is_some() {
+                let key = manifest.verify_signature(signer.as_ref())?;
+                println!("Manifest signed by {}", hex::encode(key.to_bytes()));
+            } else {
+                println!("Manifest is not signed; pass --signer to require a signature");
+            }
+            let field: Field = manifest.field.parse()?;
+            let mut problems = 0;
+            for entry in &manifest.shares {
+                let share = input::read_share_file(dir.join(&entry.file))
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| match field {
+                        Field::Vault => vault::parse_unseal_key(&text).map_err(|e| e.to_string()),
+                        _ => input::parse_share(&text).map_err(|e| e.to_string()),
+                    });
+                let verdict = match share {
+                    Ok(share) if manifest::share_fingerprint(&share) == entry.fingerprint => {
+                        match &manifest.commitments {
+                            Some(commitments) => pedersen::verify_share(&share, commitments).map_err(|e| e.to_string()),
+                            None => Ok(()),
+                        }
+                    }
+                    Ok(_) => Err("does not match its fingerprint".to_string()),
+                    Err(e) => Err(e),
+                };
+                match verdict {
+                    Ok(()) => println!("  ok       share #{} {}", entry.index, entry.file),
+                    Err(e) => {
+                        problems += 1;
+                        println!("  FAILED   share #{} {}: {}", entry.index, entry.file, e);
+                    }
+                }
+            }
+            println!(
+                "Set {}: any {} of {} shares, created {}",
+                manifest.set_fingerprint, manifest.threshold, manifest.total, manifest.created_at
+            );
+            if manifest.commitments.is_some() {
+                println!("Every share was checked against the set's Pedersen commitments");
+            }
+            if problems > 0 {
+                return Err(SssError::Checksum(format!("{} share file(s) do not match the manifest", problems)));
+            }
+        }
+        ManifestCommand::CheckShare { dir, share } => {
+            let manifest = manifest::read_manifest(&dir)?;
+            let share = input::parse_share(&input::read_share_file(&share)?)?;
+            let fingerprint = manifest::share_fingerprint(&share);
+            if !manifest.shares.iter().any(|entry| entry.index == share[0] && entry.fingerprint == fingerprint) {
+                return Err(SssError::Checksum(format!("share #{} is not listed in the manifest", share[0])));
+            }
+            println!("Share #{} is listed in the manifest of set {}", share[0], manifest.set_fingerprint);
+            match &manifest.commitments {
+                Some(commitments) => {
+                    pedersen::verify_share(&share, commitments)?;
+                    println!("Share #{} matches the set's Pedersen commitments", share[0]);
+                }
+                None => println!("The set has no commitments; only the fingerprint was checked"),
+            }
         }
-        SecretType::Int(i) => {
-            bytes.push(1u8);
-            bytes.extend_from_slice(&i.to_be_bytes());
+        ManifestCommand::Quorum { dir, limit, strict } => {
+            let manifest = manifest::read_manifest(&dir)?;
+            let names: Vec<String> = manifest
+                .shares
+                .iter()
+                .map(|e| e.label.clone().unwrap_or_else(|| format!("share #{}", e.index)))
+                .collect();
+            let holders = shamir_cli::policy::holders(names.iter().map(String::as_str));
+            let report = shamir_cli::policy::simulate(holders, manifest.threshold as usize, limit);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report).map_err(|e| SssError::Parse(e.to_string()))?);
+            } else {
+                println!("Set {}: any {} of {} shares", manifest.set_fingerprint, report.threshold, report.total);
+                println!("\nHolders:");
+                for holder in &report.holders {
+                    println!("  {} ({} share{})", holder.name, holder.shares, if holder.shares == 1 { "" } else { "s" });
+                }
+                println!("\nThese groups can recover the secret (any group containing one of them can too):");
+                for quorum in &report.quorums {
+                    println!("  {}", quorum.join(" + "));
+                }
+                if report.truncated {
+                    println!("  ... and more; raise --limit to list them");
+                }
+                let spare = report.total.saturating_sub(report.threshold);
+                println!("\nUp to {} share(s) can be lost before the secret is.", spare);
+                match report.warnings.as_slice() {
+                    [] => println!("No warnings."),
+                    warnings => {
+                        println!("\nWarnings:");
+                        for warning in warnings {
+                            println!("  ! {}", warning);
+                        }
+                    }
+                }
+            }
+            if strict && !report.warnings.is_empty() {
+                return Err(SssError::Policy(format!("{} warning(s) about this distribution", report.warnings.len())));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn horcrux_command(command: HorcruxCommand) -> Result<()> {
+    use shamir_cli::horcrux::{self, Horcrux};
+
+    match command {
+        HorcruxCommand::Split { file, threshold, total, out_dir } => {
+            let data = Zeroizing::new(std::fs::read(&file)?);
+            let name = file
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .ok_or_else(|| SssError::InvalidArgument(format!("{} is not a file", file.display())))?;
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            let horcruxes = horcrux::split(&data, &name, timestamp, threshold, total)?;
+            std::fs::create_dir_all(&out_dir)?;
+            for h in &horcruxes {
+                let path = out_dir.join(h.file_name());
+                write_private(&path, &h.to_bytes()?)?;
+                println!("Wrote {}", path.display());
+            }
+            println!("Any {} of the {} horcruxes bind {} again.", threshold, total, name);
         }
-        SecretType::Float(f) => {
-            bytes.push(2u8);
-            bytes.extend_from_slice(&f.to_be_bytes());
+        HorcruxCommand::Bind { paths, out } => {
+            let mut files = Vec::new();
+            for path in paths {
+                if path.is_dir() {
+                    let mut found: Vec<PathBuf> = std::fs::read_dir(&path)?
+                        .filter_map(|entry| entry.ok().map(|e| e.path()))
+                        .filter(|p| p.extension().is_some_and(|e| e == "horcrux"))
+                        .collect();
+                    found.sort();
+                    files.extend(found);
+                } else {
+                    files.push(path);
+                }
+            }
+            let mut horcruxes = files
+                .iter()
+                .map(|f| Horcrux::parse(&std::fs::read(f)?))
+                .collect::<Result<Vec<_>>>()?;
+            let (name, data) = horcrux::bind(&mut horcruxes)?;
+            // only the final path component of the recorded name is trusted
+            let out = out.unwrap_or_else(|| {
+                PathBuf::from(std::path::Path::new(&name).file_name().unwrap_or("recovered".as_ref()))
+            });
+            write_private(&out, &data)?;
+            println!("Bound {} horcruxes into {} ({} bytes)", horcruxes.len(), out.display(), data.len());
         }
     }
-    bytes
+    Ok(())
 }
 
-fn deserialize_secret(bytes: &[u8]) -> SecretType {
-    let type_byte = bytes[0];
-    match type_byte {
-        0 => {
-            let len = u32::from_be_bytes(bytes[1..5].try_into().unwrap()) as usize;
-            let s = String::from_utf8(bytes[5..5+len].to_vec()).unwrap();
-            SecretType::String(s)
+#[cfg(feature = "frost")]
+fn frost_command(command: FrostCommand) -> Result<()> {
+    use shamir_cli::frost;
+
+    let read = |path: &PathBuf| -> Result<Zeroizing<String>> {
+        Ok(Zeroizing::new(std::fs::read_to_string(path)?.trim().to_string()))
+    };
+    let read_all = |paths: &[PathBuf]| -> Result<Vec<String>> {
+        paths.iter().map(|p| read(p).map(|m| m.to_string())).collect()
+    };
+    let emit = |message: &str, out: Option<&PathBuf>, qr: bool| -> Result<()> {
+        if let Some(out) = out {
+            std::fs::write(out, format!("{}\n", message))?;
+            eprintln!("Wrote {}", out.display());
+        }
+        println!("{}", message);
+        if qr {
+            println!("{}", frost::qr(message)?);
+        }
+        Ok(())
+    };
+
+    match command {
+        FrostCommand::Keygen { curve, threshold, total, import, out_dir } => {
+            let existing = if import {
+                let text = Zeroizing::new(
+                    cli::console::password("Private key to split (hex: or base64:)", false)
+                        .map_err(cli::session::prompt_error)?,
+                );
+                match &SecretType::from_input(&text)? {
+                    SecretType::Bytes(key) => Some(Zeroizing::new(key.clone())),
+                    _ => return Err(SssError::InvalidArgument("give the private key as hex: or base64: bytes".into())),
+                }
+            } else {
+                None
+            };
+            let dealt = frost::keygen(curve, existing.as_deref().map(Vec::as_slice), threshold, total)?;
+            std::fs::create_dir_all(&out_dir)?;
+            for (i, package) in dealt.key_packages.iter().enumerate() {
+                let path = out_dir.join(format!("key-package-{}.txt", i + 1));
+                write_private(path, Zeroizing::new(format!("{}\n", package.as_str())).as_bytes())?;
+            }
+            std::fs::write(out_dir.join("public-key-package.txt"), format!("{}\n", dealt.public_package))?;
+            println!("Wrote {} key packages and the public key package to {}", total, out_dir.display());
+            println!("Group public key: {}", hex::encode(dealt.group_key));
         }
-        1 => {
-            let i = i64::from_be_bytes(bytes[1..9].try_into().unwrap());
-            SecretType::Int(i)
+        FrostCommand::SignRound1 { key, nonces_out, out, qr } => {
+            let (nonces, commitments) = frost::round1(&read(&key)?)?;
+            write_private(&nonces_out, Zeroizing::new(format!("{}\n", nonces.as_str())).as_bytes())?;
+            eprintln!("Kept the nonces in {}; do not share them", nonces_out.display());
+            emit(&commitments, out.as_ref(), qr)?;
         }
-        2 => {
-            let f = f64::from_be_bytes(bytes[1..9].try_into().unwrap());
-            SecretType::Float(f)
+        FrostCommand::SignRound2 { key, nonces, commitments, message, out, qr } => {
+            let nonce_message = read(&nonces).map_err(|e| match e {
+                SssError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => SssError::Signing(format!(
+                    "no nonces at {}; they are deleted after one use, so run sign-round1 again",
+                    nonces.display()
+                )),
+                e => e,
+            })?;
+            std::fs::remove_file(&nonces)?;
+            let share = frost::round2(
+                &read(&key)?,
+                &nonce_message,
+                &read_all(&commitments)?,
+                &std::fs::read(&message)?,
+            )?;
+            emit(&share, out.as_ref(), qr)?;
+        }
+        FrostCommand::Aggregate { public, commitments, shares, message, out } => {
+            let public = read(&public)?;
+            let signature = frost::aggregate(
+                &public,
+                &read_all(&commitments)?,
+                &read_all(&shares)?,
+                &std::fs::read(&message)?,
+            )?;
+            println!("Group public key: {}", hex::encode(frost::group_key(&public)?));
+            println!("Signature: {}", hex::encode(&signature));
+            if let Some(out) = out {
+                std::fs::write(&out, format!("{}\n", hex::encode(&signature)))?;
+            }
         }
-        _ => panic!("Invalid type byte"),
     }
+    Ok(())
 }
 
-fn pad_to_minimum(bytes: &mut Vec<u8>) {
-    if bytes.len() < MIN_SECRET_SIZE {
-        let padding = MIN_SECRET_SIZE - bytes.len();
-        bytes.extend(iter::repeat(0u8).take(padding));
+fn replay(path: &std::path::Path, all: bool) -> Result<()> {
+    let entries = transcript::load(path)?;
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    for (i, entry) in entries.iter().enumerate() {
+        println!("{}", entry.describe());
+        if !all && i + 1 < entries.len() {
+            eprint!("  -- Enter for next step, q to stop -- ");
+            match lines.next() {
+                Some(Ok(line)) if line.trim() == "q" => break,
+                Some(Ok(_)) => {}
+                _ => break,
+            }
+        }
     }
+    Ok(())
 }
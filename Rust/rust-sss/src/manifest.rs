@@ -0,0 +1,160 @@
+//! Share sets written to a directory: one file per share and a `manifest.json` describing the
+//! set. The manifest holds no secret material, only each share's fingerprint, so it can be
+//! kept with every custodian and checked against the share files before a recovery.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::secure::write_private;
+use crate::shamir::pedersen::Commitments;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShareEntry {
+    pub index: u8,
+    pub file: String,
+    /// Hex SHA-256 of the share bytes.
+    pub fingerprint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestSignature {
+    /// Hex Ed25519 public key, the same kind of key `sss token keygen` writes.
+    pub public_key: String,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub format_version: u8,
+    pub set_fingerprint: String,
+    pub field: String,
+    pub threshold: u8,
+    pub total: u8,
+    pub created_at: u64,
+    pub shares: Vec<ShareEntry>,
+    /// Pedersen commitments every share of a `pedersen` set can be checked against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitments: Option<Commitments>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ManifestSignature>,
+}
+
+/// `share-01-of-05.sss`; the index is padded to the width of the total so files sort in order.
+pub fn share_file_name(index: u8, total: u8) -> String {
+    let width = total.to_string().len().max(2);
+    format!("share-{:0w$}-of-{:0w$}.sss", index, total, w = width)
+}
+
+pub fn share_fingerprint(share: &[u8]) -> String {
+    hex::encode(Sha256::digest(share))
+}
+
+impl Manifest {
+    /// The bytes covered by the signature: the manifest as JSON without its signature.
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let unsigned = Manifest { signature: None, ..self.clone() };
+        serde_json::to_vec(&unsigned).map_err(|e| SssError::Parse(e.to_string()))
+    }
+
+    pub fn sign(&mut self, key: &SigningKey) -> Result<()> {
+        let signature = key.sign(&self.signed_bytes()?);
+        self.signature = Some(ManifestSignature {
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+        });
+        Ok(())
+    }
+
+    /// Checks the signature, and that it was made by `signer` when one is given. Returns the
+    /// signer's public key.
+    pub fn verify_signature(&self, signer: Option<&VerifyingKey>) -> Result<VerifyingKey> {
+        let signed = self
+            .signature
+            .as_ref()
+            .ok_or_else(|| SssError::Signing("the manifest is not signed".into()))?;
+        let key = crate::token::parse_verifying_key(&signed.public_key)?;
+        if signer.is_some_and(|expected| *expected != key) {
+            return Err(SssError::Signing(format!("the manifest was signed by another key ({})", signed.public_key)));
+        }
+        let signature = hex::decode(&signed.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(|| SssError::Signing("malformed manifest signature".into()))?;
+        key.verify(&self.signed_bytes()?, &signature)
+            .map_err(|_| SssError::Signing("the manifest signature does not match its contents".into()))?;
+        Ok(key)
+    }
+}
+
+/// A share to write, already encoded as the text a custodian would paste.
+pub struct ShareFile<'a> {
+    pub share: &'a [u8],
+    pub text: String,
+    pub label: Option<String>,
+}
+
+/// Writes every share to its own file in `dir` and a manifest listing them, with the set's
+/// commitments if it has any, signed when a key is given. Existing share files are never
+/// overwritten.
+pub fn write_share_dir(
+    dir: &Path,
+    shares: &[ShareFile],
+    set_fingerprint: &str,
+    field: &str,
+    threshold: u8,
+    commitments: Option<&Commitments>,
+    signing_key: Option<&SigningKey>,
+) -> Result<(Manifest, Vec<PathBuf>)> {
+    let total = u8::try_from(shares.len()).map_err(|_| SssError::Share("too many shares".into()))?;
+    fs::create_dir_all(dir)?;
+    let mut entries = Vec::new();
+    let mut written = Vec::new();
+    for share in shares {
+        let index = share.share.first().copied().unwrap_or(0);
+        let file = share_file_name(index, total);
+        let path = dir.join(&file);
+        write_private(&path, format!("{}\n", share.text).as_bytes())?;
+        entries.push(ShareEntry {
+            index,
+            file,
+            fingerprint: share_fingerprint(share.share),
+            label: share.label.clone(),
+        });
+        written.push(path);
+    }
+
+    let mut manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        set_fingerprint: set_fingerprint.to_string(),
+        field: field.to_string(),
+        threshold,
+        total,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        shares: entries,
+        commitments: commitments.cloned(),
+        signature: None,
+    };
+    if let Some(key) = signing_key {
+        manifest.sign(key)?;
+    }
+    let path = dir.join(MANIFEST_FILE);
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| SssError::Parse(e.to_string()))?;
+    write_private(&path, (json + "\n").as_bytes())?;
+    written.push(path);
+    Ok((manifest, written))
+}
+
+pub fn read_manifest(dir: &Path) -> Result<Manifest> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    serde_json::from_str(&text).map_err(|e| SssError::Parse(format!("{}: {}", MANIFEST_FILE, e)))
+}
@@ -0,0 +1,76 @@
+//! Async versions of the `pipeline` and share math entry points, for tokio services. Key
+//! derivation, and share math on payloads over `INLINE_LIMIT` bytes, run on tokio's blocking
+//! pool so they never hold up the executor; smaller share math runs in place. Arguments are
+//! owned because the work may move to another thread.
+
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::crypto::open_payload as open;
+use crate::error::{Result, SssError};
+use crate::pipeline;
+use crate::secret::{Padding, SecretType};
+use crate::secure::SecretBuf;
+use crate::shamir::Field;
+
+/// Payloads up to this size are split and rebuilt without leaving the calling task.
+pub const INLINE_LIMIT: usize = 64 * 1024;
+
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(SssError::Io(std::io::Error::other(e))),
+    }
+}
+
+/// See `pipeline::seal`.
+pub async fn seal(
+    plaintext: Zeroizing<Vec<u8>>,
+    password: Zeroizing<String>,
+    rounds: u32,
+) -> Result<Zeroizing<Vec<u8>>> {
+    blocking(move || pipeline::seal(&plaintext, &password, rounds, &mut OsRng)).await
+}
+
+/// See `crypto::open_payload`.
+pub async fn open_payload(payload: Vec<u8>, password: Zeroizing<String>) -> Result<SecretBuf> {
+    blocking(move || open(&payload, &password)).await
+}
+
+/// See `pipeline::split_secret`.
+pub async fn split_secret(
+    secret: SecretType,
+    password: Zeroizing<String>,
+    threshold: u8,
+    total: u8,
+    padding: Padding,
+    rounds: u32,
+) -> Result<Vec<Vec<u8>>> {
+    blocking(move || pipeline::split_secret(&secret, &password, threshold, total, padding, rounds, &mut OsRng)).await
+}
+
+/// See `pipeline::combine_secret`.
+pub async fn combine_secret(shares: Vec<Vec<u8>>, password: Zeroizing<String>) -> Result<SecretType> {
+    blocking(move || pipeline::combine_secret(&shares, &password)).await
+}
+
+/// See `Field::split`.
+pub async fn split(field: Field, secret: Zeroizing<Vec<u8>>, threshold: u8, total: u8) -> Result<Vec<Vec<u8>>> {
+    if secret.len() <= INLINE_LIMIT {
+        return field.split(&secret, threshold, total);
+    }
+    blocking(move || field.split(&secret, threshold, total)).await
+}
+
+/// See `Field::reconstruct`.
+pub async fn reconstruct(field: Field, shares: Vec<Vec<u8>>) -> Result<Zeroizing<Vec<u8>>> {
+    if shares.first().map_or(0, Vec::len) <= INLINE_LIMIT {
+        return field.reconstruct(&shares).map(Zeroizing::new);
+    }
+    blocking(move || field.reconstruct(&shares).map(Zeroizing::new)).await
+}
@@ -0,0 +1,204 @@
+//! TOTP seeds as authenticator apps exchange them: RFC 4648 base32 and the `otpauth://totp/`
+//! URI of Google Authenticator's key URI format.
+
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Result, SssError};
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub const DEFAULT_ALGORITHM: &str = "SHA1";
+pub const DEFAULT_DIGITS: u8 = 6;
+pub const DEFAULT_PERIOD: u32 = 30;
+
+/// A TOTP seed and the parameters an authenticator needs to rebuild its URI.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Totp {
+    pub seed: Vec<u8>,
+    pub issuer: Option<String>,
+    pub account: Option<String>,
+    pub algorithm: String,
+    pub digits: u8,
+    pub period: u32,
+}
+
+impl Zeroize for Totp {
+    fn zeroize(&mut self) {
+        self.seed.zeroize();
+        self.issuer.zeroize();
+        self.account.zeroize();
+    }
+}
+
+/// Unpadded uppercase base32, as authenticators show seeds.
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u64, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u64::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+/// Accepts lowercase, spaces, dashes and `=` padding, since seeds are often copied in groups.
+pub fn base32_decode(text: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let mut out = Zeroizing::new(Vec::with_capacity(text.len() * 5 / 8));
+    let (mut buffer, mut bits) = (0u64, 0u32);
+    for c in text.chars().filter(|c| !matches!(c, ' ' | '-')).map(|c| c.to_ascii_uppercase()) {
+        if c == '=' {
+            break;
+        }
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or_else(|| SssError::Parse(format!("'{}' is not a base32 character", c)))?;
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    buffer.zeroize();
+    if out.is_empty() {
+        return Err(SssError::Parse("the TOTP seed is empty".into()));
+    }
+    Ok(out)
+}
+
+fn percent_decode(text: &str) -> Result<String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let byte = text
+                    .get(i + 1..i + 3)
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .ok_or_else(|| SssError::Parse("bad percent escape in otpauth URI".into()))?;
+                out.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).map_err(|_| SssError::Parse("otpauth URI is not UTF-8".into()))
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl Totp {
+    /// A bare seed with the usual SHA1, 6 digit, 30 second parameters.
+    pub fn from_seed(base32: &str) -> Result<Totp> {
+        Ok(Totp {
+            seed: base32_decode(base32)?.to_vec(),
+            issuer: None,
+            account: None,
+            algorithm: DEFAULT_ALGORITHM.into(),
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+        })
+    }
+
+    pub fn parse_uri(uri: &str) -> Result<Totp> {
+        let rest = uri
+            .strip_prefix("otpauth://totp/")
+            .ok_or_else(|| SssError::Parse("only otpauth://totp/ URIs hold a TOTP seed".into()))?;
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let label = percent_decode(label)?;
+        let (mut issuer, account) = match label.split_once(':') {
+            Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+            None => (None, label),
+        };
+        let mut totp = Totp {
+            seed: Vec::new(),
+            issuer: None,
+            account: (!account.is_empty()).then_some(account),
+            algorithm: DEFAULT_ALGORITHM.into(),
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+        };
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = Zeroizing::new(percent_decode(value)?);
+            match key {
+                "secret" => totp.seed = base32_decode(&value)?.to_vec(),
+                "issuer" => issuer = Some(value.to_string()),
+                "algorithm" => match value.to_ascii_uppercase().as_str() {
+                    a @ ("SHA1" | "SHA256" | "SHA512") => totp.algorithm = a.to_string(),
+                    a => return Err(SssError::Parse(format!("unknown TOTP algorithm '{}'", a))),
+                },
+                "digits" => {
+                    totp.digits = value
+                        .parse()
+                        .ok()
+                        .filter(|d| (6..=10).contains(d))
+                        .ok_or_else(|| SssError::Parse(format!("TOTP digits must be 6-10, not '{}'", *value)))?
+                }
+                "period" => {
+                    totp.period = value
+                        .parse()
+                        .ok()
+                        .filter(|&p| p > 0)
+                        .ok_or_else(|| SssError::Parse(format!("bad TOTP period '{}'", *value)))?
+                }
+                _ => {}
+            }
+        }
+        if totp.seed.is_empty() {
+            return Err(SssError::Parse("the otpauth URI has no secret".into()));
+        }
+        totp.issuer = issuer.filter(|i| !i.is_empty());
+        if [&totp.issuer, &totp.account].iter().any(|t| t.as_ref().is_some_and(|t| t.len() > 255)) {
+            return Err(SssError::Parse("the TOTP issuer and account must fit in 255 bytes".into()));
+        }
+        Ok(totp)
+    }
+
+    /// The `otpauth://totp/` URI, leaving out parameters at their defaults.
+    pub fn uri(&self) -> Zeroizing<String> {
+        let label = match (&self.issuer, &self.account) {
+            (Some(issuer), Some(account)) => format!("{}:{}", percent_encode(issuer), percent_encode(account)),
+            (Some(issuer), None) => format!("{}:", percent_encode(issuer)),
+            (None, account) => percent_encode(account.as_deref().unwrap_or_default()),
+        };
+        let mut uri = Zeroizing::new(format!("otpauth://totp/{}?secret={}", label, base32_encode(&self.seed)));
+        if let Some(issuer) = &self.issuer {
+            uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+        }
+        if self.algorithm != DEFAULT_ALGORITHM {
+            uri.push_str(&format!("&algorithm={}", self.algorithm));
+        }
+        if self.digits != DEFAULT_DIGITS {
+            uri.push_str(&format!("&digits={}", self.digits));
+        }
+        if self.period != DEFAULT_PERIOD {
+            uri.push_str(&format!("&period={}", self.period));
+        }
+        uri
+    }
+}
@@ -0,0 +1,214 @@
+//! Printable pages that carry a share itself, for custodians who keep theirs on paper. Each
+//! page has the share as a QR code, as BIP-39 words and as hex, so it can be scanned, typed
+//! or read aloud, along with the quorum and how to recover.
+//!
+//! Words pack the share 11 bits at a time into the BIP-39 English list, followed by one
+//! checksum word, so a misread word is caught before recovery starts.
+
+use bip39::Language;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::card::escape_xml;
+use crate::error::{Result, SssError};
+use crate::format::ShareEncoding;
+use crate::i18n;
+use crate::secure::write_private;
+use crate::tr;
+
+const WORD_BITS: usize = 11;
+/// Stands in for the recovery command in the translated step that names it.
+const COMMAND: &str = "\u{1}command\u{1}";
+
+fn checksum(share: &[u8]) -> u16 {
+    let digest = Sha256::digest(share);
+    u16::from_be_bytes([digest[0], digest[1]]) >> (16 - WORD_BITS)
+}
+
+/// The share as BIP-39 words, the last of which is a checksum.
+pub fn share_words(share: &[u8]) -> Vec<&'static str> {
+    let list = Language::English.word_list();
+    let mut words = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0usize);
+    for &byte in share {
+        acc = (acc << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= WORD_BITS {
+            bits -= WORD_BITS;
+            words.push(list[((acc >> bits) & 0x7ff) as usize]);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        words.push(list[((acc << (WORD_BITS - bits)) & 0x7ff) as usize]);
+    }
+    words.push(list[checksum(share) as usize]);
+    words
+}
+
+/// Reads words written by [`share_words`], or `None` if some token is not a BIP-39 word.
+pub fn parse_words(text: &str) -> Option<Result<Vec<u8>>> {
+    let indices = text
+        .split_whitespace()
+        .map(|w| Language::English.find_word(&w.to_lowercase()))
+        .collect::<Option<Vec<u16>>>()?;
+    let (&check, data) = indices.split_last()?;
+    let mut bytes = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0usize);
+    for &index in data {
+        acc = (acc << WORD_BITS) | u32::from(index);
+        bits += WORD_BITS;
+        while bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    // the padding of the last word can hold a whole zero byte that was never part of the share
+    while checksum(&bytes) != check {
+        match bytes.last() {
+            Some(0) if bits + 8 < WORD_BITS => {
+                bytes.pop();
+                bits += 8;
+            }
+            _ => return Some(Err(SssError::Checksum("share words fail their checksum; check each word".into()))),
+        }
+    }
+    Some(Ok(bytes))
+}
+
+/// Days since 1970-01-01 to a `YYYY-MM-DD` date in the proleptic Gregorian calendar.
+pub fn civil_date(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// One custodian's page.
+pub struct ShareSheet<'a> {
+    pub share: &'a [u8],
+    pub set_fingerprint: String,
+    pub threshold: u8,
+    pub total: u8,
+    pub created: String,
+    pub custodian: Option<String>,
+    pub command: String,
+}
+
+impl ShareSheet<'_> {
+    /// What the QR code holds: uppercase bech32, which QR codes store compactly and
+    /// `sss decrypt` checks, or uppercase hex for shares too long for bech32.
+    fn qr_text(&self) -> String {
+        ShareEncoding::Bech32
+            .encode(self.share)
+            .unwrap_or_else(|_| hex::encode(self.share))
+            .to_uppercase()
+    }
+
+    pub fn to_html(&self) -> Result<String> {
+        let index = self.share.first().copied().unwrap_or(0);
+        let qr = QrCode::new(self.qr_text().as_bytes())
+            .map_err(|e| SssError::Parse(format!("share does not fit in a QR code: {}", e)))?
+            .render::<svg::Color>()
+            .min_dimensions(220, 220)
+            .build();
+        let words: String = share_words(self.share)
+            .iter()
+            .enumerate()
+            .map(|(i, w)| format!("<li value=\"{}\">{}</li>", i + 1, w))
+            .collect();
+        let hex = hex::encode(self.share);
+        let grouped: Vec<&str> = (0..hex.len()).step_by(4).map(|i| &hex[i..(i + 4).min(hex.len())]).collect();
+        let custodian = match &self.custodian {
+            Some(name) => format!(
+                "<p class=\"for\">{} <strong>{}</strong></p>",
+                escape_xml(&tr!("sheet-held-by")),
+                escape_xml(name)
+            ),
+            None => String::new(),
+        };
+
+        Ok(format!(
+            "<!DOCTYPE html>\n<html lang=\"{lang}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>\n\
+             @page {{ size: A4; margin: 16mm; }}\n\
+             body {{ font-family: sans-serif; font-size: 11pt; max-width: 180mm; margin: auto; }}\n\
+             h1 {{ font-size: 20pt; margin-bottom: 0; }}\n\
+             .meta td {{ padding: 1mm 4mm 1mm 0; }}\n\
+             .qr {{ float: right; margin-left: 6mm; }}\n\
+             ol {{ columns: 4; font-family: monospace; font-size: 12pt; padding-left: 8mm; }}\n\
+             .hex {{ font-family: monospace; font-size: 11pt; word-spacing: 1.5mm; line-height: 1.6; }}\n\
+             .warn {{ border: 0.4mm solid black; padding: 3mm; }}\n\
+             </style>\n</head>\n<body>\n\
+             <div class=\"qr\">{qr}</div>\n\
+             <h1>{heading}</h1>\n{custodian}\n\
+             <table class=\"meta\">\n\
+             <tr><td>{quorum_label}</td><td>{quorum}</td></tr>\n\
+             <tr><td>{set_label}</td><td>{fingerprint}</td></tr>\n\
+             <tr><td>{created_label}</td><td>{created}</td></tr>\n\
+             </table>\n\
+             <h2>{words_label}</h2>\n<ol>{words}</ol>\n\
+             <h2>{hex_label}</h2>\n<p class=\"hex\">{hex}</p>\n\
+             <h2>{recover_label}</h2>\n\
+             <ol style=\"columns: 1; font-family: sans-serif; font-size: 11pt;\">\n\
+             <li>{gather}</li>\n\
+             <li>{run}</li>\n\
+             <li>{enter}</li>\n\
+             <li>{password}</li>\n\
+             </ol>\n\
+             <p class=\"warn\">{warning}</p>\n\
+             </body>\n</html>\n",
+            lang = escape_xml(&i18n::language()),
+            title = escape_xml(&tr!("sheet-title", index = index, total = self.total)),
+            heading = escape_xml(&tr!("sheet-heading", index = index, total = self.total)),
+            quorum_label = escape_xml(&tr!("sheet-quorum")),
+            quorum = escape_xml(&tr!("sheet-quorum-value", threshold = self.threshold, total = self.total)),
+            set_label = escape_xml(&tr!("sheet-set")),
+            fingerprint = escape_xml(&self.set_fingerprint),
+            created_label = escape_xml(&tr!("sheet-created")),
+            created = escape_xml(&self.created),
+            words_label = escape_xml(&tr!("sheet-words")),
+            hex_label = escape_xml(&tr!("sheet-hex")),
+            recover_label = escape_xml(&tr!("sheet-recover")),
+            gather = escape_xml(&tr!(
+                "sheet-step-gather",
+                threshold = self.threshold,
+                total = self.total,
+                set = self.set_fingerprint
+            )),
+            // the command goes in as markup, after the rest of the step is escaped
+            run = escape_xml(&tr!("sheet-step-run", command = COMMAND))
+                .replace(COMMAND, &format!("<code>{}</code>", escape_xml(&self.command))),
+            enter = escape_xml(&tr!("sheet-step-enter")),
+            password = escape_xml(&tr!("sheet-step-password")),
+            warning = escape_xml(&tr!("sheet-warning", threshold = self.threshold)),
+            qr = qr,
+            custodian = custodian,
+            words = words,
+            hex = grouped.join(" "),
+        ))
+    }
+}
+
+/// Writes `share-NNN.html` for every sheet into `dir`, readable only by the owner on Unix.
+pub fn write_sheets<P: AsRef<Path>>(dir: P, sheets: &[ShareSheet]) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for sheet in sheets {
+        let path = dir.join(format!("share-{:03}.html", sheet.share.first().copied().unwrap_or(0)));
+        write_private(&path, sheet.to_html()?.as_bytes())?;
+        written.push(path);
+    }
+    Ok(written)
+}
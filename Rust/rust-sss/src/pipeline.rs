@@ -0,0 +1,158 @@
+//! The whole `sss encrypt` and `sss decrypt` pipeline as plain functions, for front ends
+//! without a terminal: serialize and pad the secret, encrypt it under a PBKDF2 key, and split
+//! the KDF header ‖ salt ‖ nonce ‖ ciphertext payload over GF(256).
+
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::crypto::{encrypt_data_with_aad, encrypt_with_decoy_with_rng, kdf_header, open_payload};
+use crate::error::{Result, SssError};
+use crate::format::{validate_shares, SetBinding};
+use crate::secret::{deserialize_secret, mnemonic_words, pad, pad_alike, serialize_secret, uuid_text, Padding, SecretType};
+use crate::shamir::Field;
+use crate::timelock::{self, Conditions};
+
+/// Encrypts `plaintext` under `password` and returns the payload that gets split.
+pub fn seal<R: RngCore + ?Sized>(
+    plaintext: &[u8],
+    password: &str,
+    rounds: u32,
+    rng: &mut R,
+) -> Result<Zeroizing<Vec<u8>>> {
+    seal_locked(plaintext, password, rounds, &Conditions::default(), rng)
+}
+
+/// Like [`seal`], bound to a fresh set ID and a set of `threshold` shares, with the ciphertext
+/// in one of two slots like a set with a decoy.
+pub fn seal_bound<R: RngCore + ?Sized>(
+    plaintext: &[u8],
+    password: &str,
+    rounds: u32,
+    threshold: u8,
+    rng: &mut R,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let conditions = Conditions { binding: Some(SetBinding::new(threshold, rng)), ..Conditions::default() };
+    seal_with_decoy(plaintext, password, None, rounds, &conditions, rng)
+}
+
+/// Like [`seal`], with `conditions` in front of the payload.
+pub fn seal_locked<R: RngCore + ?Sized>(
+    plaintext: &[u8],
+    password: &str,
+    rounds: u32,
+    conditions: &Conditions,
+    rng: &mut R,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let header = conditions.header();
+    let (ciphertext, salt, nonce) = encrypt_data_with_aad(plaintext, password, rounds, &header, rng)?;
+    let mut payload = Zeroizing::new(header);
+    payload.extend_from_slice(&kdf_header(rounds));
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(payload)
+}
+
+/// Like [`seal_locked`], with the ciphertext in one of two slots and the other holding `decoy`
+/// under its own password, or random bytes without one. Both plaintexts must be padded to the
+/// same length.
+pub fn seal_with_decoy<R: RngCore + ?Sized>(
+    plaintext: &[u8],
+    password: &str,
+    decoy: Option<(&[u8], &str)>,
+    rounds: u32,
+    conditions: &Conditions,
+    rng: &mut R,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let header = conditions.header();
+    let slots = encrypt_with_decoy_with_rng(plaintext, password, decoy, rounds, &header, rng)?;
+    let mut payload = Zeroizing::new(header);
+    payload.extend_from_slice(&kdf_header(rounds));
+    payload.extend(slots);
+    Ok(payload)
+}
+
+/// Serializes, pads, encrypts and splits `secret` into `total` GF(256) shares.
+pub fn split_secret<R: RngCore + ?Sized>(
+    secret: &SecretType,
+    password: &str,
+    threshold: u8,
+    total: u8,
+    padding: Padding,
+    rounds: u32,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    let plaintext = pad(&serialize_secret(secret), padding)?;
+    let payload = seal_bound(&plaintext, password, rounds, threshold, rng)?;
+    Field::Gf256.split_with_rng(&payload, threshold, total, rng)
+}
+
+/// Like [`split_secret`], with a decoy secret that `decoy_password` recovers instead of the
+/// real one.
+#[allow(clippy::too_many_arguments)]
+pub fn split_secret_with_decoy<R: RngCore + ?Sized>(
+    secret: &SecretType,
+    password: &str,
+    decoy: &SecretType,
+    decoy_password: &str,
+    threshold: u8,
+    total: u8,
+    padding: Padding,
+    rounds: u32,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    let [plaintext, decoy] = pad_alike(&serialize_secret(secret), &serialize_secret(decoy), padding)?;
+    let conditions = Conditions { binding: Some(SetBinding::new(threshold, rng)), ..Conditions::default() };
+    let payload = seal_with_decoy(&plaintext, password, Some((&decoy, decoy_password)), rounds, &conditions, rng)?;
+    Field::Gf256.split_with_rng(&payload, threshold, total, rng)
+}
+
+/// Rebuilds, decrypts and deserializes the secret behind a quorum of GF(256) shares. A set
+/// time-locked to a date still ahead on this machine's clock, or to a Roughtime server, is
+/// refused, as is one behind a dead man's switch; `sss decrypt` is the way to recover those.
+pub fn combine_secret<S: AsRef<[u8]>>(shares: &[S], password: &str) -> Result<SecretType> {
+    let owned: Vec<Vec<u8>> = shares.iter().map(|s| s.as_ref().to_vec()).collect();
+    validate_shares(&owned)?;
+    let payload = Zeroizing::new(Field::Gf256.reconstruct(&owned)?);
+    let (conditions, _, _) = Conditions::split(&payload);
+    if let Some(lock) = conditions.time_lock {
+        if lock.roughtime_key.is_some() {
+            return Err(SssError::Policy("this set's time lock needs a Roughtime server; use sss decrypt".into()));
+        }
+        lock.check(timelock::system_now())?;
+    }
+    if conditions.switch.is_some() {
+        return Err(SssError::Policy("this set is behind a dead man's switch; use sss decrypt".into()));
+    }
+    if let Some(binding) = conditions.binding {
+        binding.check()?;
+    }
+    deserialize_secret(&open_payload(&payload, password)?)
+}
+
+/// A recovered secret as `sss decrypt` prints it: bytes as hex, seed phrases as words and
+/// bundles as a JSON object of label to text.
+pub fn secret_text(secret: &SecretType) -> Result<Zeroizing<String>> {
+    Ok(match secret {
+        SecretType::String(s)
+        | SecretType::Json(s)
+        | SecretType::SshKey(s)
+        | SecretType::AgeIdentity(s)
+        | SecretType::EnvFile(s) => Zeroizing::new(s.clone()),
+        SecretType::Int(i) => Zeroizing::new(i.to_string()),
+        SecretType::Float(f) => Zeroizing::new(f.to_string()),
+        SecretType::Uuid(uuid) => Zeroizing::new(uuid_text(uuid)),
+        SecretType::Date(date) => Zeroizing::new(date.clone()),
+        SecretType::Totp(totp) => totp.uri(),
+        SecretType::Bytes(b) => Zeroizing::new(hex::encode(b)),
+        SecretType::Mnemonic(entropy) => mnemonic_words(entropy)?,
+        SecretType::GpgKey { armored, .. } => Zeroizing::new(armored.clone()),
+        SecretType::Bundle(entries) => {
+            let mut object = serde_json::Map::new();
+            for (label, secret) in entries {
+                object.insert(label.clone(), secret_text(secret)?.as_str().into());
+            }
+            Zeroizing::new(serde_json::Value::Object(object).to_string())
+        }
+    })
+}
@@ -0,0 +1,201 @@
+//! Payloads encrypted under an AES-256 key held in an HSM, instead of one derived from a
+//! password. The key is generated on the token, marked sensitive and non-extractable, and the
+//! AES-GCM operations run on the token through its PKCS#11 module.
+//!
+//! Such a payload is conditions ‖ `p11k` ‖ label length ‖ key label ‖ nonce ‖ ciphertext, with
+//! everything before the nonce authenticated as associated data, so `decrypt` knows which key
+//! to ask for and the set binding (see [`crate::timelock::Conditions`]) is held to the set as
+//! it is for password payloads. The format is always understood; talking to a token needs the
+//! `pkcs11` feature.
+
+use crate::crypto::NONCE_LEN;
+use crate::error::{Result, SssError};
+use crate::timelock::Conditions;
+
+pub const HSM_MAGIC: [u8; 4] = *b"p11k";
+
+/// Whether a reconstructed payload was encrypted by an HSM rather than under a password.
+pub fn is_hsm_payload(data: &[u8]) -> bool {
+    Conditions::split(data).2.starts_with(&HSM_MAGIC)
+}
+
+/// The parts of an HSM payload.
+pub struct HsmPayload<'a> {
+    pub label: &'a str,
+    /// Everything before the nonce, conditions included, authenticated as associated data.
+    pub header: &'a [u8],
+    pub nonce: &'a [u8],
+    pub ciphertext: &'a [u8],
+}
+
+impl<'a> HsmPayload<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let malformed = || SssError::Parse("the HSM payload is truncated or malformed".into());
+        let (_, conditions, rest) = Conditions::split(data);
+        let rest = rest.strip_prefix(&HSM_MAGIC).ok_or_else(malformed)?;
+        let (&len, rest) = rest.split_first().ok_or_else(malformed)?;
+        if rest.len() < len as usize + NONCE_LEN {
+            return Err(malformed());
+        }
+        let label = std::str::from_utf8(&rest[..len as usize]).map_err(|_| malformed())?;
+        let (header, rest) = data.split_at(conditions.len() + HSM_MAGIC.len() + 1 + len as usize);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        Ok(HsmPayload { label, header, nonce, ciphertext })
+    }
+}
+
+/// The header of a payload encrypted with the key labelled `label`.
+pub fn hsm_header(label: &str) -> Result<Vec<u8>> {
+    let len = u8::try_from(label.len())
+        .ok()
+        .filter(|&len| len > 0)
+        .ok_or_else(|| SssError::Parse("an HSM key label is 1 to 255 bytes".into()))?;
+    let mut header = HSM_MAGIC.to_vec();
+    header.push(len);
+    header.extend_from_slice(label.as_bytes());
+    Ok(header)
+}
+
+#[cfg(feature = "pkcs11")]
+pub use token::Hsm;
+
+#[cfg(feature = "pkcs11")]
+mod token {
+    use cryptoki::context::{CInitializeArgs, Pkcs11};
+    use cryptoki::error::{Error, RvError};
+    use cryptoki::mechanism::aead::GcmParams;
+    use cryptoki::mechanism::Mechanism;
+    use cryptoki::object::{Attribute, KeyType, ObjectClass, ObjectHandle};
+    use cryptoki::session::{Session, UserType};
+    use cryptoki::types::AuthPin;
+    use rand::RngCore;
+    use std::path::Path;
+    use zeroize::Zeroizing;
+
+    use super::{hsm_header, HsmPayload};
+    use crate::crypto::{NONCE_LEN, TAG_LEN};
+    use crate::error::{Result, SssError};
+    use crate::secure::SecretBuf;
+    use crate::timelock::Conditions;
+
+    const TAG_BITS: std::ffi::c_ulong = TAG_LEN as std::ffi::c_ulong * 8;
+
+    fn hsm_error(e: Error) -> SssError {
+        match e {
+            Error::Pkcs11(RvError::PinIncorrect | RvError::PinLocked, _) => {
+                SssError::Unauthorized(format!("HSM: {}", e))
+            }
+            Error::Pkcs11(RvError::EncryptedDataInvalid, _) => SssError::Aead,
+            e => SssError::Store(format!("HSM: {}", e)),
+        }
+    }
+
+    /// A logged-in session on one token.
+    pub struct Hsm {
+        session: Session,
+    }
+
+    impl Hsm {
+        /// Loads the PKCS#11 `module` and logs in to the token labelled `token`, or to the only
+        /// token present if none is named.
+        pub fn login(module: &Path, token: Option<&str>, pin: &str) -> Result<Self> {
+            let context = Pkcs11::new(module).map_err(hsm_error)?;
+            context.initialize(CInitializeArgs::OsThreads).map_err(hsm_error)?;
+            let mut slots = Vec::new();
+            for slot in context.get_slots_with_token().map_err(hsm_error)? {
+                let label = context.get_token_info(slot).map_err(hsm_error)?.label().trim().to_string();
+                if token.is_none_or(|wanted| wanted == label) {
+                    slots.push((slot, label));
+                }
+            }
+            let slot = match (slots.as_slice(), token) {
+                ([(slot, _)], _) => *slot,
+                ([], Some(token)) => return Err(SssError::Store(format!("HSM: no token is labelled '{}'", token))),
+                ([], None) => return Err(SssError::Store("HSM: the module sees no token".into())),
+                (several, _) => {
+                    let labels: Vec<&str> = several.iter().map(|(_, label)| label.as_str()).collect();
+                    return Err(SssError::Parse(format!(
+                        "the module sees several tokens ({}); name one with --pkcs11-token",
+                        labels.join(", ")
+                    )));
+                }
+            };
+            let session = context.open_rw_session(slot).map_err(hsm_error)?;
+            session.login(UserType::User, Some(&AuthPin::new(pin.into()))).map_err(hsm_error)?;
+            Ok(Hsm { session })
+        }
+
+        fn find_key(&self, label: &str) -> Result<Option<ObjectHandle>> {
+            let template = [Attribute::Class(ObjectClass::SECRET_KEY), Attribute::Label(label.as_bytes().to_vec())];
+            let keys = self.session.find_objects(&template).map_err(hsm_error)?;
+            match keys.as_slice() {
+                [] => Ok(None),
+                [key] => Ok(Some(*key)),
+                _ => Err(SssError::Store(format!("HSM: several keys are labelled '{}'", label))),
+            }
+        }
+
+        /// Generates a sensitive, non-extractable AES-256 key on the token under `label`.
+        pub fn generate_key(&self, label: &str) -> Result<()> {
+            hsm_header(label)?;
+            if self.find_key(label)?.is_some() {
+                return Err(SssError::Store(format!("HSM: a key labelled '{}' already exists", label)));
+            }
+            let template = [
+                Attribute::Class(ObjectClass::SECRET_KEY),
+                Attribute::KeyType(KeyType::AES),
+                Attribute::ValueLen(32.into()),
+                Attribute::Token(true),
+                Attribute::Private(true),
+                Attribute::Sensitive(true),
+                Attribute::Extractable(false),
+                Attribute::Encrypt(true),
+                Attribute::Decrypt(true),
+                Attribute::Label(label.as_bytes().to_vec()),
+            ];
+            self.session.generate_key(&Mechanism::AesKeyGen, &template).map_err(hsm_error)?;
+            Ok(())
+        }
+
+        fn key(&self, label: &str) -> Result<ObjectHandle> {
+            self.find_key(label)?
+                .ok_or_else(|| SssError::Store(format!("HSM: the token holds no key labelled '{}'", label)))
+        }
+
+        /// Encrypts `plaintext` with the key labelled `label` behind `conditions` and returns
+        /// the payload that gets split.
+        pub fn seal<R: RngCore + ?Sized>(
+            &self,
+            label: &str,
+            plaintext: &[u8],
+            conditions: &Conditions,
+            rng: &mut R,
+        ) -> Result<Zeroizing<Vec<u8>>> {
+            let mut header = conditions.header();
+            header.extend(hsm_header(label)?);
+            let key = self.key(label)?;
+            let mut nonce = [0u8; NONCE_LEN];
+            rng.fill_bytes(&mut nonce);
+            let mut iv = nonce;
+            let params = GcmParams::new(&mut iv, &header, TAG_BITS.into()).map_err(hsm_error)?;
+            let ciphertext = self.session.encrypt(&Mechanism::AesGcm(params), key, plaintext).map_err(hsm_error)?;
+            let mut payload = Zeroizing::new(header);
+            payload.extend_from_slice(&nonce);
+            payload.extend_from_slice(&ciphertext);
+            Ok(payload)
+        }
+
+        /// Decrypts a payload written by `seal` with the key it names.
+        pub fn open(&self, payload: &[u8]) -> Result<SecretBuf> {
+            let payload = HsmPayload::parse(payload)?;
+            let key = self.key(payload.label)?;
+            let mut iv = [0u8; NONCE_LEN];
+            iv.copy_from_slice(payload.nonce);
+            let params = GcmParams::new(&mut iv, payload.header, TAG_BITS.into()).map_err(hsm_error)?;
+            let plaintext = Zeroizing::new(
+                self.session.decrypt(&Mechanism::AesGcm(params), key, payload.ciphertext).map_err(hsm_error)?,
+            );
+            SecretBuf::from_slice(&plaintext)
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! The network backends: S3 with Signature Version 4, Google Drive's v3 REST API, and plain
+//! WebDAV PUT and GET, all over ureq.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+use super::Backend;
+use crate::error::{Result, SssError};
+use crate::input::{self, MAX_SHARE_TEXT_LEN};
+use crate::timelock::{format_date, system_now};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const DRIVE_FILES: &str = "https://www.googleapis.com/drive/v3/files";
+const DRIVE_UPLOAD: &str = "https://www.googleapis.com/upload/drive/v3/files?uploadType=multipart";
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+}
+
+fn env(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| SssError::Store(format!("${} is not set", name)))
+}
+
+fn call_error(backend: &Backend, e: ureq::Error) -> SssError {
+    match e {
+        ureq::Error::Status(412, _) => SssError::Store(format!("{} already holds a share; not replacing it", backend)),
+        ureq::Error::Status(404, _) => SssError::Store(format!("{} holds no share", backend)),
+        e => SssError::Store(format!("{}: {}", backend, e)),
+    }
+}
+
+fn read_body(backend: &Backend, response: ureq::Response) -> Result<Zeroizing<String>> {
+    let mut body = Zeroizing::new(Vec::new());
+    response.into_reader().take(MAX_SHARE_TEXT_LEN as u64 + 1).read_to_end(&mut body)?;
+    if body.len() > MAX_SHARE_TEXT_LEN {
+        return Err(SssError::Store(format!("{} holds something too large to be a share", backend)));
+    }
+    Ok(Zeroizing::new(input::normalize(&body)?))
+}
+
+pub fn push(backend: &Backend, text: &str) -> Result<()> {
+    match backend {
+        Backend::S3 { .. } => s3_request(backend, "PUT", text.as_bytes()).map(|_| ()),
+        Backend::Webdav { url, user } => webdav_auth(agent().put(url), user.as_deref())?
+            .set("If-None-Match", "*")
+            .send_bytes(text.as_bytes())
+            .map(|_| ())
+            .map_err(|e| call_error(backend, e)),
+        Backend::Gdrive { folder, name } => {
+            if drive_find(backend, folder, name)?.is_some() {
+                return Err(SssError::Store(format!("{} already holds a share; not replacing it", backend)));
+            }
+            let mut boundary = [0u8; 12];
+            rand::rngs::OsRng.fill_bytes(&mut boundary);
+            let boundary = format!("sss-{}", hex::encode(boundary));
+            let metadata = serde_json::json!({ "name": name, "parents": [folder] });
+            let body = Zeroizing::new(format!(
+                "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n\
+                 --{b}\r\nContent-Type: text/plain\r\n\r\n{}\r\n--{b}--\r\n",
+                metadata,
+                text,
+                b = boundary
+            ));
+            agent()
+                .post(DRIVE_UPLOAD)
+                .set("Authorization", &format!("Bearer {}", env("GOOGLE_DRIVE_TOKEN")?))
+                .set("Content-Type", &format!("multipart/related; boundary={}", boundary))
+                .send_bytes(body.as_bytes())
+                .map(|_| ())
+                .map_err(|e| call_error(backend, e))
+        }
+        Backend::Local { .. } => unreachable!("local placements are written directly"),
+    }
+}
+
+pub fn pull(backend: &Backend) -> Result<Zeroizing<String>> {
+    match backend {
+        Backend::S3 { .. } => s3_request(backend, "GET", b"").and_then(|response| read_body(backend, response)),
+        Backend::Webdav { url, user } => webdav_auth(agent().get(url), user.as_deref())?
+            .call()
+            .map_err(|e| call_error(backend, e))
+            .and_then(|response| read_body(backend, response)),
+        Backend::Gdrive { folder, name } => {
+            let id = drive_find(backend, folder, name)?
+                .ok_or_else(|| SssError::Store(format!("{} holds no share", backend)))?;
+            agent()
+                .get(&format!("{}/{}", DRIVE_FILES, id))
+                .query("alt", "media")
+                .set("Authorization", &format!("Bearer {}", env("GOOGLE_DRIVE_TOKEN")?))
+                .call()
+                .map_err(|e| call_error(backend, e))
+                .and_then(|response| read_body(backend, response))
+        }
+        Backend::Local { .. } => unreachable!("local placements are read directly"),
+    }
+}
+
+fn webdav_auth(request: ureq::Request, user: Option<&str>) -> Result<ureq::Request> {
+    Ok(match user {
+        Some(user) => {
+            let login = Zeroizing::new(format!("{}:{}", user, env("SSS_WEBDAV_PASSWORD")?));
+            request.set("Authorization", &format!("Basic {}", BASE64.encode(login.as_bytes())))
+        }
+        None => request,
+    })
+}
+
+/// The ID of the file called `name` in Drive folder `folder`, if there is one.
+fn drive_find(backend: &Backend, folder: &str, name: &str) -> Result<Option<String>> {
+    let quote = |s: &str| s.replace('\\', "\\\\").replace('\'', "\\'");
+    let query = format!("name = '{}' and '{}' in parents and trashed = false", quote(name), quote(folder));
+    let reply: serde_json::Value = agent()
+        .get(DRIVE_FILES)
+        .query("q", &query)
+        .query("fields", "files(id)")
+        .set("Authorization", &format!("Bearer {}", env("GOOGLE_DRIVE_TOKEN")?))
+        .call()
+        .map_err(|e| call_error(backend, e))?
+        .into_json()
+        .map_err(|e| SssError::Store(format!("{}: {}", backend, e)))?;
+    Ok(reply["files"].get(0).and_then(|file| file["id"].as_str()).map(str::to_string))
+}
+
+/// AWS credentials as the SDKs read them from the environment.
+pub struct Credentials {
+    pub access_key: String,
+    pub secret_key: Zeroizing<String>,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    pub fn from_env() -> Result<Credentials> {
+        Ok(Credentials {
+            access_key: env("AWS_ACCESS_KEY_ID")?,
+            secret_key: Zeroizing::new(env("AWS_SECRET_ACCESS_KEY")?),
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Percent-encodes an object key for a SigV4 canonical URI, keeping its slashes.
+pub fn uri_encode(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// `20130524T000000Z`
+pub fn amz_date(secs: u64) -> String {
+    format_date(secs).replace(['-', ':'], "").replace(" UTC", "Z").replace(' ', "T")
+}
+
+/// The `Authorization` header signing a request to S3 in `region` at `amz_date`. `headers` are
+/// the lowercase headers to sign, `host`, `x-amz-date` and `x-amz-content-sha256` among them.
+pub fn authorization(
+    credentials: &Credentials,
+    region: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    payload_hash: &str,
+    amz_date: &str,
+) -> String {
+    let mut headers = headers.to_vec();
+    headers.sort();
+    let canonical_headers: String =
+        headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method,
+        uri_encode(path),
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let secret = Zeroizing::new(format!("AWS4{}", credentials.secret_key.as_str()));
+    let key = [date.as_bytes(), region.as_bytes(), b"s3", b"aws4_request"]
+        .iter()
+        .fold(Zeroizing::new(secret.as_bytes().to_vec()), |key, part| Zeroizing::new(hmac(&key, part).to_vec()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key,
+        scope,
+        signed_headers,
+        hex::encode(hmac(&key, string_to_sign.as_bytes()))
+    )
+}
+
+fn s3_request(backend: &Backend, method: &str, body: &[u8]) -> Result<ureq::Response> {
+    let Backend::S3 { bucket, key, region, endpoint } = backend else {
+        unreachable!("only S3 placements are signed");
+    };
+    let (base, host, path) = match endpoint {
+        Some(endpoint) => {
+            let endpoint = endpoint.trim_end_matches('/');
+            let host = endpoint.split_once("://").map_or(endpoint, |(_, rest)| rest);
+            (endpoint.to_string(), host.to_string(), format!("/{}/{}", bucket, key))
+        }
+        None => {
+            let host = format!("{}.s3.{}.amazonaws.com", bucket, region);
+            (format!("https://{}", host), host, format!("/{}", key))
+        }
+    };
+    let credentials = Credentials::from_env()?;
+    let date = amz_date(system_now());
+    let payload_hash = hex::encode(Sha256::digest(body));
+    let mut headers = vec![("host", host.as_str()), ("x-amz-content-sha256", &payload_hash), ("x-amz-date", &date)];
+    if method == "PUT" {
+        headers.push(("if-none-match", "*"));
+    }
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token));
+    }
+    let signature = authorization(&credentials, region, method, &path, &headers, &payload_hash, &date);
+    let mut request = agent().request(method, &format!("{}{}", base, uri_encode(&path)));
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.set(name, value);
+    }
+    request = request.set("Authorization", &signature);
+    let response = match method {
+        "PUT" => request.send_bytes(body),
+        _ => request.call(),
+    };
+    response.map_err(|e| call_error(backend, e))
+}
@@ -0,0 +1,140 @@
+//! Where each share of a set lives, declared once in a JSON placement file, so one `encrypt`
+//! pushes every share to its own backend and one `decrypt` pulls a quorum back:
+//!
+//! ```json
+//! { "placements": [
+//!     { "share": 1, "backend": "s3", "bucket": "family", "region": "eu-west-1", "key": "sss/share-1.txt" },
+//!     { "share": 2, "backend": "gdrive", "folder": "1AbC...", "name": "share-2.txt" },
+//!     { "share": 3, "backend": "webdav", "url": "https://nas.local/dav/share-3.txt", "user": "me" },
+//!     { "share": 4, "backend": "local", "path": "/media/usb-a/share-4.txt" },
+//!     { "share": 5, "backend": "local", "path": "/media/usb-b/share-5.txt" } ] }
+//! ```
+//!
+//! Credentials stay out of the file: S3 signs with `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`
+//! and `AWS_SESSION_TOKEN`, Google Drive takes an OAuth access token from
+//! `GOOGLE_DRIVE_TOKEN`, and WebDAV a password from `SSS_WEBDAV_PASSWORD`. Pushing never
+//! replaces a share already at the destination. The network backends need the `cloud`
+//! feature.
+
+#[cfg(feature = "cloud")]
+pub mod cloud;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::input;
+use crate::secure::write_private;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum Backend {
+    Local {
+        path: PathBuf,
+    },
+    S3 {
+        bucket: String,
+        key: String,
+        region: String,
+        /// An S3-compatible endpoint such as MinIO's, addressed path-style; AWS when absent.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endpoint: Option<String>,
+    },
+    Gdrive {
+        /// ID of the Drive folder the share is kept in.
+        folder: String,
+        name: String,
+    },
+    Webdav {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        user: Option<String>,
+    },
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Local { path } => write!(f, "{}", path.display()),
+            Backend::S3 { bucket, key, .. } => write!(f, "s3://{}/{}", bucket, key),
+            Backend::Gdrive { folder, name } => write!(f, "gdrive:{}/{}", folder, name),
+            Backend::Webdav { url, .. } => write!(f, "{}", url),
+        }
+    }
+}
+
+#[cfg(not(feature = "cloud"))]
+fn no_cloud(backend: &Backend) -> SssError {
+    SssError::Store(format!("{} needs sss built with --features cloud", backend))
+}
+
+impl Backend {
+    /// Puts a share's text at the backend, refusing to replace one that is already there.
+    pub fn push(&self, text: &str) -> Result<()> {
+        match self {
+            Backend::Local { path } => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                write_private(path, text.as_bytes())
+            }
+            #[cfg(feature = "cloud")]
+            backend => cloud::push(backend, text),
+            #[cfg(not(feature = "cloud"))]
+            backend => Err(no_cloud(backend)),
+        }
+    }
+
+    pub fn pull(&self) -> Result<Zeroizing<String>> {
+        match self {
+            Backend::Local { path } => Ok(Zeroizing::new(input::read_share_file(path)?)),
+            #[cfg(feature = "cloud")]
+            backend => cloud::pull(backend),
+            #[cfg(not(feature = "cloud"))]
+            backend => Err(no_cloud(backend)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// The share's index, counting from 1.
+    pub share: u8,
+    #[serde(flatten)]
+    pub backend: Backend,
+}
+
+/// A placement file: one backend for each share of a set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlacementConfig {
+    pub placements: Vec<Placement>,
+}
+
+impl PlacementConfig {
+    /// Parses a placement file, which must place shares 1 to n once each.
+    pub fn parse(text: &str) -> Result<PlacementConfig> {
+        let mut config: PlacementConfig =
+            serde_json::from_str(text).map_err(|e| SssError::Parse(format!("placement file: {}", e)))?;
+        config.placements.sort_by_key(|p| p.share);
+        for (i, placement) in config.placements.iter().enumerate() {
+            if placement.share as usize != i + 1 {
+                return Err(SssError::Parse(format!(
+                    "the placement file must place shares 1 to {} once each, but share {} is {}",
+                    config.placements.len(),
+                    i + 1,
+                    if (placement.share as usize) < i + 1 { "placed twice" } else { "missing" }
+                )));
+            }
+        }
+        if config.placements.len() < 2 {
+            return Err(SssError::Parse("the placement file must place at least 2 shares".into()));
+        }
+        Ok(config)
+    }
+
+    pub fn read(path: &Path) -> Result<PlacementConfig> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
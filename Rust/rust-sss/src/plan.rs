@@ -0,0 +1,335 @@
+//! Planning where a set's shares go before splitting it. A plan file lists the custodians, the
+//! places they can keep a share, and the rules the spread has to keep:
+//!
+//! ```toml
+//! threshold = 3
+//!
+//! [rules]
+//! distinct = ["city"]   # no two shares in the same city (also "location" or "country")
+//! survive = 1           # losing any one location must still leave a quorum
+//!
+//! [[custodian]]
+//! name = "alice"
+//! locations = ["boston-safe", "nyc-bank"]   # left out: any location
+//!
+//! [[location]]
+//! name = "boston-safe"
+//! city = "Boston"
+//! country = "US"
+//! ```
+//!
+//! Each custodian gets one share at one of their locations. The first assignment, in file
+//! order, that keeps every rule is the plan; [`analyse`] then says which losses of locations,
+//! cities or countries the secret survives.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::error::{Result, SssError};
+use crate::policy::{self, Holder};
+
+/// Assignments tried before the search gives up.
+const SEARCH_LIMIT: usize = 1_000_000;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Attribute {
+    Location,
+    City,
+    Country,
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Attribute::Location => "location",
+            Attribute::City => "city",
+            Attribute::Country => "country",
+        })
+    }
+}
+
+impl Attribute {
+    pub fn plural(self) -> &'static str {
+        match self {
+            Attribute::Location => "locations",
+            Attribute::City => "cities",
+            Attribute::Country => "countries",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Location {
+    pub name: String,
+    pub city: Option<String>,
+    pub country: Option<String>,
+}
+
+impl Location {
+    /// The location's city or country, or its own name when the file leaves that out.
+    fn attribute(&self, attribute: Attribute) -> &str {
+        match attribute {
+            Attribute::Location => &self.name,
+            Attribute::City => self.city.as_deref().unwrap_or(&self.name),
+            Attribute::Country => self.country.as_deref().unwrap_or(&self.name),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Custodian {
+    pub name: String,
+    /// Locations this custodian can keep a share at; empty means any.
+    #[serde(default)]
+    pub locations: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    /// No two shares may share one of these.
+    #[serde(default)]
+    pub distinct: Vec<Attribute>,
+    /// Location failures that must still leave a quorum.
+    #[serde(default)]
+    pub survive: usize,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanFile {
+    pub threshold: u8,
+    #[serde(default)]
+    pub rules: Rules,
+    #[serde(rename = "custodian")]
+    pub custodians: Vec<Custodian>,
+    #[serde(rename = "location")]
+    pub locations: Vec<Location>,
+}
+
+/// One share's holder and home.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub index: u8,
+    pub custodian: String,
+    pub location: String,
+    pub city: Option<String>,
+    pub country: Option<String>,
+}
+
+impl Assignment {
+    /// Where the share is kept, as it is written in the share's `Note:` line.
+    pub fn note(&self) -> String {
+        let place: Vec<&str> = self.city.iter().chain(&self.country).map(String::as_str).collect();
+        match place.is_empty() {
+            true => self.location.clone(),
+            false => format!("{} ({})", self.location, place.join(", ")),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub threshold: u8,
+    pub assignments: Vec<Assignment>,
+}
+
+impl Plan {
+    pub fn total(&self) -> u8 {
+        self.assignments.len() as u8
+    }
+
+    fn holders(&self, attribute: Attribute) -> Vec<Holder> {
+        policy::holders(self.assignments.iter().map(|a| match attribute {
+            Attribute::Location => a.location.as_str(),
+            Attribute::City => a.city.as_deref().unwrap_or(&a.location),
+            Attribute::Country => a.country.as_deref().unwrap_or(&a.location),
+        }))
+    }
+}
+
+impl PlanFile {
+    pub fn parse(text: &str) -> Result<PlanFile> {
+        let file: PlanFile = toml::from_str(text).map_err(|e| SssError::Parse(format!("plan: {}", e.message())))?;
+        file.check()?;
+        Ok(file)
+    }
+
+    pub fn read(path: &Path) -> Result<PlanFile> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    fn check(&self) -> Result<()> {
+        let invalid = |why: String| Err(SssError::Parse(format!("plan: {}", why)));
+        if !(2..=255).contains(&self.custodians.len()) {
+            return invalid(format!("{} custodians are listed; a set needs 2 to 255", self.custodians.len()));
+        }
+        if self.threshold < 2 || self.threshold as usize > self.custodians.len() {
+            let custodians = self.custodians.len();
+            return invalid(format!("a threshold of {} does not fit {} custodians", self.threshold, custodians));
+        }
+        let mut names = HashSet::new();
+        if let Some(location) = self.locations.iter().find(|l| !names.insert(l.name.as_str())) {
+            return invalid(format!("location {} is listed twice", location.name));
+        }
+        let mut people = HashSet::new();
+        if let Some(custodian) = self.custodians.iter().find(|c| !people.insert(c.name.as_str())) {
+            return invalid(format!("custodian {} is listed twice", custodian.name));
+        }
+        for custodian in &self.custodians {
+            if let Some(unknown) = custodian.locations.iter().find(|l| !names.contains(l.as_str())) {
+                return invalid(format!("{} names location {}, which is not listed", custodian.name, unknown));
+            }
+        }
+        Ok(())
+    }
+
+    /// The first assignment that keeps every rule.
+    pub fn solve(&self) -> Result<Plan> {
+        let choices: Vec<Vec<&Location>> = self
+            .custodians
+            .iter()
+            .map(|c| match c.locations.is_empty() {
+                true => self.locations.iter().collect(),
+                false => {
+                    c.locations.iter().filter_map(|name| self.locations.iter().find(|l| &l.name == name)).collect()
+                }
+            })
+            .collect();
+        let mut search = Search { file: self, choices: &choices, chosen: Vec::new(), tried: 0 };
+        match search.step() {
+            Some(chosen) => Ok(self.plan(&chosen)),
+            None if search.tried >= SEARCH_LIMIT => Err(SssError::Policy(format!(
+                "no plan found after trying {} assignments; give custodians fewer locations to choose from",
+                SEARCH_LIMIT
+            ))),
+            None => Err(SssError::Policy(self.why_unsolvable())),
+        }
+    }
+
+    fn plan(&self, chosen: &[&Location]) -> Plan {
+        let assignments = self
+            .custodians
+            .iter()
+            .zip(chosen)
+            .enumerate()
+            .map(|(i, (custodian, location))| Assignment {
+                index: i as u8 + 1,
+                custodian: custodian.name.clone(),
+                location: location.name.clone(),
+                city: location.city.clone(),
+                country: location.country.clone(),
+            })
+            .collect();
+        Plan { threshold: self.threshold, assignments }
+    }
+
+    fn why_unsolvable(&self) -> String {
+        for attribute in &self.rules.distinct {
+            let values: HashSet<&str> = self.locations.iter().map(|l| l.attribute(*attribute)).collect();
+            if values.len() < self.custodians.len() {
+                return format!(
+                    "{} custodians need a {} each but the locations span only {}",
+                    self.custodians.len(),
+                    attribute,
+                    values.len()
+                );
+            }
+        }
+        let spare = self.custodians.len() - self.threshold as usize;
+        format!(
+            "no assignment of the custodians to their locations keeps every rule (with {} spare share(s), \
+             surviving {} location failure(s) needs shares spread across more locations)",
+            spare, self.rules.survive
+        )
+    }
+}
+
+struct Search<'a> {
+    file: &'a PlanFile,
+    choices: &'a [Vec<&'a Location>],
+    chosen: Vec<&'a Location>,
+    tried: usize,
+}
+
+impl<'a> Search<'a> {
+    fn step(&mut self) -> Option<Vec<&'a Location>> {
+        if self.tried >= SEARCH_LIMIT {
+            return None;
+        }
+        self.tried += 1;
+        let Some(options) = self.choices.get(self.chosen.len()) else {
+            let plan = self.file.plan(&self.chosen);
+            return (tolerance(&plan, Attribute::Location) >= self.file.rules.survive).then(|| self.chosen.clone());
+        };
+        for &location in options {
+            let clash = self.file.rules.distinct.iter().any(|&attribute| {
+                self.chosen.iter().any(|chosen| chosen.attribute(attribute) == location.attribute(attribute))
+            });
+            if clash {
+                continue;
+            }
+            self.chosen.push(location);
+            if let Some(found) = self.step() {
+                return Some(found);
+            }
+            self.chosen.pop();
+        }
+        None
+    }
+}
+
+/// How many of a plan's locations, cities or countries can fail at once, whichever they are,
+/// with a quorum left: losing the ones holding most shares is the worst case.
+pub fn tolerance(plan: &Plan, attribute: Attribute) -> usize {
+    let mut held: Vec<usize> = plan.holders(attribute).iter().map(|h| h.shares).collect();
+    held.sort_unstable_by(|a, b| b.cmp(a));
+    let spare = plan.assignments.len().saturating_sub(plan.threshold as usize);
+    let mut lost = 0;
+    held.iter()
+        .take_while(|&&shares| {
+            lost += shares;
+            lost <= spare
+        })
+        .count()
+}
+
+/// What losing places of one kind does to a plan.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Redundancy {
+    pub attribute: Attribute,
+    pub places: usize,
+    /// Any this many can fail with a quorum left.
+    pub tolerates: usize,
+    /// The smallest groups of places whose loss together loses the secret.
+    pub fatal: Vec<Vec<String>>,
+    /// Set when there were more fatal groups than were listed.
+    pub truncated: bool,
+}
+
+/// Which failures of locations, cities and countries the plan survives, listing at most
+/// `limit` fatal groups of each.
+pub fn analyse(plan: &Plan, limit: usize) -> Vec<Redundancy> {
+    [Attribute::Location, Attribute::City, Attribute::Country]
+        .into_iter()
+        .map(|attribute| {
+            let holders = plan.holders(attribute);
+            let places = holders.len();
+            // losing more shares than are spare is the same as those places forming a quorum
+            let fatal_at = plan.assignments.len() + 1 - plan.threshold as usize;
+            let report = policy::simulate(holders, fatal_at, limit);
+            Redundancy {
+                attribute,
+                places,
+                tolerates: tolerance(plan, attribute),
+                fatal: report.quorums,
+                truncated: report.truncated,
+            }
+        })
+        .collect()
+}
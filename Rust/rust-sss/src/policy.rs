@@ -0,0 +1,172 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::error::{Result, SssError};
+
+/// Someone who will hold one share, and the jurisdiction they (and their share) sit in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Custodian {
+    pub name: String,
+    pub jurisdiction: String,
+}
+
+impl FromStr for Custodian {
+    type Err = String;
+
+    /// Parses `name:jurisdiction`, e.g. `alice:US`.
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s.rsplit_once(':') {
+            Some((name, jurisdiction)) if !name.trim().is_empty() && !jurisdiction.trim().is_empty() => {
+                Ok(Custodian {
+                    name: name.trim().to_string(),
+                    jurisdiction: jurisdiction.trim().to_uppercase(),
+                })
+            }
+            _ => Err("expected NAME:JURISDICTION".to_string()),
+        }
+    }
+}
+
+/// Requires every quorum to span at least `min_jurisdictions` jurisdictions; 2 means no quorum
+/// can be assembled inside a single one.
+#[derive(Debug, Clone, Copy)]
+pub struct JurisdictionPolicy {
+    pub min_jurisdictions: usize,
+}
+
+/// The quorum of `threshold` custodians drawn from as few jurisdictions as possible: taking
+/// whole jurisdictions largest first is the worst case any policy has to survive.
+pub fn most_concentrated_quorum(custodians: &[Custodian], threshold: usize) -> Vec<&Custodian> {
+    let mut by_jurisdiction: BTreeMap<&str, Vec<&Custodian>> = BTreeMap::new();
+    for custodian in custodians {
+        by_jurisdiction.entry(&custodian.jurisdiction).or_default().push(custodian);
+    }
+    let mut groups: Vec<Vec<&Custodian>> = by_jurisdiction.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.len()));
+    groups.into_iter().flatten().take(threshold).collect()
+}
+
+fn jurisdictions(quorum: &[&Custodian]) -> usize {
+    let mut seen: Vec<&str> = quorum.iter().map(|c| c.jurisdiction.as_str()).collect();
+    seen.sort_unstable();
+    seen.dedup();
+    seen.len()
+}
+
+impl JurisdictionPolicy {
+    /// Verifies that splitting among `custodians` with `threshold` satisfies the policy.
+    pub fn check(&self, custodians: &[Custodian], threshold: usize) -> Result<()> {
+        let quorum = most_concentrated_quorum(custodians, threshold);
+        if jurisdictions(&quorum) >= self.min_jurisdictions {
+            return Ok(());
+        }
+        let members: Vec<String> =
+            quorum.iter().map(|c| format!("{} ({})", c.name, c.jurisdiction)).collect();
+        let hint = match self.smallest_threshold(custodians) {
+            Some(t) => format!("a threshold of at least {} would satisfy it", t),
+            None => "no threshold can satisfy it with these custodians".to_string(),
+        };
+        Err(SssError::Policy(format!(
+            "{} could recover the secret while spanning fewer than {} jurisdictions; {}",
+            members.join(", "),
+            self.min_jurisdictions,
+            hint
+        )))
+    }
+
+    pub fn smallest_threshold(&self, custodians: &[Custodian]) -> Option<usize> {
+        (1..=custodians.len()).find(|&t| {
+            jurisdictions(&most_concentrated_quorum(custodians, t)) >= self.min_jurisdictions
+        })
+    }
+}
+
+/// Someone holding one or more shares of a set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Holder {
+    pub name: String,
+    pub shares: usize,
+}
+
+/// Which holders can recover a set together, and what about the split deserves a second look.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuorumReport {
+    pub threshold: usize,
+    pub total: usize,
+    pub holders: Vec<Holder>,
+    /// Smallest groups of holders that can recover the secret: leaving anyone out of one falls
+    /// short. Names, in holder order.
+    pub quorums: Vec<Vec<String>>,
+    /// Set when there were more quorums than were listed.
+    pub truncated: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Groups holders by name, in order of first appearance.
+pub fn holders<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<Holder> {
+    let mut holders: Vec<Holder> = Vec::new();
+    for name in names {
+        match holders.iter_mut().find(|h| h.name == name) {
+            Some(holder) => holder.shares += 1,
+            None => holders.push(Holder { name: name.to_string(), shares: 1 }),
+        }
+    }
+    holders
+}
+
+/// Lists up to `limit` minimal quorums of `holders` for `threshold` and flags risky splits:
+/// a single share or a single holder being enough, and anyone whose absence leaves too few.
+pub fn simulate(holders: Vec<Holder>, threshold: usize, limit: usize) -> QuorumReport {
+    let total: usize = holders.iter().map(|h| h.shares).sum();
+    let mut quorums = Vec::new();
+    collect_quorums(&holders, threshold, 0, 0, &mut Vec::new(), &mut quorums, limit + 1);
+    let truncated = quorums.len() > limit;
+    quorums.truncate(limit);
+
+    let mut warnings = Vec::new();
+    if threshold > total {
+        warnings.push(format!("{} shares are required but only {} exist; the secret cannot be recovered", threshold, total));
+    } else if threshold == total && total > 1 {
+        warnings.push("every share is required: losing any one of them loses the secret".to_string());
+    }
+    if threshold <= 1 {
+        warnings.push("any single share recovers the secret".to_string());
+    }
+    for holder in &holders {
+        if holder.shares >= threshold && threshold > 1 && holders.len() > 1 {
+            warnings.push(format!("{} holds {} shares and can recover the secret alone", holder.name, holder.shares));
+        }
+        if threshold < total && total - holder.shares < threshold {
+            warnings.push(format!("without {}'s {} share(s) the others cannot recover the secret", holder.name, holder.shares));
+        }
+    }
+
+    QuorumReport { threshold, total, holders, quorums, truncated, warnings }
+}
+
+fn collect_quorums(
+    holders: &[Holder],
+    threshold: usize,
+    start: usize,
+    held: usize,
+    chosen: &mut Vec<usize>,
+    quorums: &mut Vec<Vec<String>>,
+    limit: usize,
+) {
+    if held >= threshold {
+        // minimal only if no member is surplus
+        if !chosen.is_empty() && chosen.iter().all(|&i| held - holders[i].shares < threshold) {
+            quorums.push(chosen.iter().map(|&i| holders[i].name.clone()).collect());
+        }
+        return;
+    }
+    for i in start..holders.len() {
+        if quorums.len() >= limit {
+            return;
+        }
+        chosen.push(i);
+        collect_quorums(holders, threshold, i + 1, held + holders[i].shares, chosen, quorums, limit);
+        chosen.pop();
+    }
+}
@@ -0,0 +1,205 @@
+//! Shares encrypted to custodians' keys with a hybrid of ML-KEM-768 and X25519, for shares
+//! kept for years with cloud providers who may one day have a quantum computer. Both KEMs
+//! run against the custodian's key and the share key is derived from both secrets with
+//! HKDF-SHA256, so the share stays confidential while either one holds.
+//!
+//! Keys are `sss-pq1-...` (public) and `SSS-PQ1-SECRET-...` (secret), base64 over the X25519
+//! key and the ML-KEM key. A sealed share is the ephemeral X25519 key, the ML-KEM ciphertext
+//! and the AES-256-GCM ciphertext of the share's text, armored; `decrypt --pq-identity` takes
+//! the file (`@share-1.pq`) wherever it takes a share file.
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{aead::generic_array::GenericArray, Aes256Gcm, KeyInit};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hkdf::Hkdf;
+use ml_kem::kem::{Decapsulate, DecapsulationKey, Encapsulate, EncapsulationKey};
+use ml_kem::{EncodedSizeUser, KemCore, MlKem768, MlKem768Params};
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519Public, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::crypto::NONCE_LEN;
+use crate::error::{Result, SssError};
+
+pub const PUBLIC_PREFIX: &str = "sss-pq1-";
+pub const SECRET_PREFIX: &str = "SSS-PQ1-SECRET-";
+pub const SEALED_SUFFIX: &str = ".pq";
+const BEGIN: &str = "-----BEGIN SSS PQ SHARE-----";
+const END: &str = "-----END SSS PQ SHARE-----";
+const KDF_INFO: &[u8] = b"sss pq1 share key";
+
+const X25519_LEN: usize = 32;
+const EK_LEN: usize = 1184;
+const DK_LEN: usize = 2400;
+const CT_LEN: usize = 1088;
+
+type Ek = EncapsulationKey<MlKem768Params>;
+type Dk = DecapsulationKey<MlKem768Params>;
+
+/// A custodian's public key: shares are sealed to it.
+#[derive(Clone)]
+pub struct Recipient {
+    x25519: X25519Public,
+    ml_kem: Ek,
+}
+
+/// A custodian's secret key, which opens the shares sealed to its [`Recipient`].
+pub struct Identity {
+    x25519: StaticSecret,
+    ml_kem: Dk,
+}
+
+fn invalid(what: &str) -> SssError {
+    SssError::Parse(format!("post-quantum {}", what))
+}
+
+impl Recipient {
+    /// First four bytes of the key's SHA-256, to tell keys apart at a glance.
+    pub fn id(&self) -> String {
+        hex::encode(&Sha256::digest(self.to_string())[..4])
+    }
+
+    pub fn parse(text: &str) -> Result<Recipient> {
+        let bytes = text
+            .trim()
+            .strip_prefix(PUBLIC_PREFIX)
+            .and_then(|b64| BASE64.decode(b64).ok())
+            .filter(|bytes| bytes.len() == X25519_LEN + EK_LEN)
+            .ok_or_else(|| invalid(&format!("public key must be {}<base64>", PUBLIC_PREFIX)))?;
+        let (x25519, ml_kem) = bytes.split_at(X25519_LEN);
+        Ok(Recipient {
+            x25519: X25519Public::from(<[u8; X25519_LEN]>::try_from(x25519).expect("length checked")),
+            ml_kem: Ek::from_bytes(ml_kem.try_into().expect("length checked")),
+        })
+    }
+}
+
+impl std::fmt::Display for Recipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = self.x25519.as_bytes().to_vec();
+        bytes.extend_from_slice(&self.ml_kem.as_bytes());
+        write!(f, "{}{}", PUBLIC_PREFIX, BASE64.encode(bytes))
+    }
+}
+
+impl Identity {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Identity {
+        let (ml_kem, _) = MlKem768::generate(rng);
+        Identity { x25519: StaticSecret::random_from_rng(&mut *rng), ml_kem }
+    }
+
+    pub fn to_public(&self) -> Recipient {
+        Recipient { x25519: X25519Public::from(&self.x25519), ml_kem: self.ml_kem.encapsulation_key().clone() }
+    }
+
+    /// The key as it is kept in an identity file.
+    pub fn to_text(&self) -> Zeroizing<String> {
+        let mut bytes = Zeroizing::new(self.x25519.to_bytes().to_vec());
+        bytes.extend_from_slice(&self.ml_kem.as_bytes());
+        Zeroizing::new(format!("{}{}", SECRET_PREFIX, BASE64.encode(&bytes)))
+    }
+
+    /// Reads the key from an identity file's text, skipping `#` comments.
+    pub fn parse(text: &str) -> Result<Identity> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .unwrap_or_default();
+        let bytes = line
+            .strip_prefix(SECRET_PREFIX)
+            .and_then(|b64| BASE64.decode(b64).ok())
+            .map(Zeroizing::new)
+            .filter(|bytes| bytes.len() == X25519_LEN + DK_LEN)
+            .ok_or_else(|| invalid(&format!("identity must be {}<base64>", SECRET_PREFIX)))?;
+        let (x25519, ml_kem) = bytes.split_at(X25519_LEN);
+        Ok(Identity {
+            x25519: StaticSecret::from(<[u8; X25519_LEN]>::try_from(x25519).expect("length checked")),
+            ml_kem: Dk::from_bytes(ml_kem.try_into().expect("length checked")),
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Identity> {
+        Self::parse(&Zeroizing::new(std::fs::read_to_string(path)?))
+    }
+}
+
+fn share_key(
+    ml_kem_secret: &[u8],
+    x25519_secret: &[u8],
+    ephemeral: &X25519Public,
+    recipient: &X25519Public,
+) -> Zeroizing<[u8; 32]> {
+    let mut ikm = Zeroizing::new(ml_kem_secret.to_vec());
+    ikm.extend_from_slice(x25519_secret);
+    let mut info = KDF_INFO.to_vec();
+    info.extend_from_slice(ephemeral.as_bytes());
+    info.extend_from_slice(recipient.as_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, &ikm).expand(&info, key.as_mut()).expect("32 bytes is a valid HKDF length");
+    key
+}
+
+/// Seals `plaintext` to `recipient` as an armored block.
+pub fn seal<R: RngCore + CryptoRng>(recipient: &Recipient, plaintext: &[u8], rng: &mut R) -> Result<String> {
+    let (ciphertext, ml_kem_secret) =
+        recipient.ml_kem.encapsulate(rng).map_err(|_| invalid("encapsulation failed"))?;
+    let ephemeral = EphemeralSecret::random_from_rng(&mut *rng);
+    let ephemeral_public = X25519Public::from(&ephemeral);
+    let x25519_secret = ephemeral.diffie_hellman(&recipient.x25519);
+    let key = share_key(&ml_kem_secret, x25519_secret.as_bytes(), &ephemeral_public, &recipient.x25519);
+    // the key is used for this one message only, so a fixed nonce is safe
+    let sealed = Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+        .encrypt(GenericArray::from_slice(&[0u8; NONCE_LEN]), Payload { msg: plaintext, aad: &ciphertext })?;
+
+    let mut bytes = ephemeral_public.as_bytes().to_vec();
+    bytes.extend_from_slice(&ciphertext);
+    bytes.extend_from_slice(&sealed);
+    let body = BASE64.encode(bytes);
+    let lines: Vec<&str> =
+        body.as_bytes().chunks(64).map(|c| std::str::from_utf8(c).expect("base64 is ASCII")).collect();
+    Ok(format!("{}\n{}\n{}\n", BEGIN, lines.join("\n"), END))
+}
+
+/// Opens an armored block sealed to `identity`.
+pub fn open(identity: &Identity, armored: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let body: String = armored
+        .trim()
+        .strip_prefix(BEGIN)
+        .and_then(|rest| rest.strip_suffix(END))
+        .ok_or_else(|| invalid("share must be an armored SSS PQ SHARE block"))?
+        .split_whitespace()
+        .collect();
+    let bytes = BASE64.decode(body).map_err(|_| invalid("share is not valid base64"))?;
+    if bytes.len() < X25519_LEN + CT_LEN {
+        return Err(invalid("share is truncated"));
+    }
+    let (ephemeral, rest) = bytes.split_at(X25519_LEN);
+    let (ciphertext, sealed) = rest.split_at(CT_LEN);
+    let ephemeral = X25519Public::from(<[u8; X25519_LEN]>::try_from(ephemeral).expect("length checked"));
+    let ml_kem_secret = identity
+        .ml_kem
+        .decapsulate(ciphertext.try_into().expect("length checked"))
+        .map_err(|_| invalid("decapsulation failed"))?;
+    let x25519_secret = identity.x25519.diffie_hellman(&ephemeral);
+    let recipient = X25519Public::from(&identity.x25519);
+    let key = share_key(&ml_kem_secret, x25519_secret.as_bytes(), &ephemeral, &recipient);
+    let plaintext = Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+        .decrypt(GenericArray::from_slice(&[0u8; NONCE_LEN]), Payload { msg: sealed, aad: ciphertext })
+        .map_err(|_| SssError::Parse("the post-quantum share was not sealed to this identity".into()))?;
+    Ok(Zeroizing::new(plaintext))
+}
+
+pub fn is_sealed(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(SEALED_SUFFIX)
+}
+
+/// Reads a sealed share file and opens it with `identity`, returning the share's text.
+pub fn read(path: &Path, identity: &Identity) -> Result<Zeroizing<String>> {
+    let plaintext = open(identity, &std::fs::read_to_string(path)?)?;
+    String::from_utf8(plaintext.to_vec())
+        .map(Zeroizing::new)
+        .map_err(|_| invalid("share does not hold text"))
+}
@@ -0,0 +1,167 @@
+//! Defaults for `encrypt` kept in `config.toml` beside the KDF calibration (see
+//! [`crate::calibration::config_dir`]), so a ceremony run again and again is not answered
+//! prompt by prompt each time:
+//!
+//! ```toml
+//! [defaults]
+//! kdf_rounds = 600000
+//!
+//! [profile.family]
+//! threshold = 3
+//! total = 5
+//! encoding = "bech32"
+//! out_dir = "~/ceremonies/family"
+//! custodians = ["alice:US", "bob:DE", "carol:CH", "dave:US", "erin:JP"]
+//! ```
+//!
+//! `[defaults]` always applies; `--profile family` lays that profile over it. Flags given on
+//! the command line win over both.
+
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::calibration::config_dir;
+use crate::crypto::MAX_PBKDF2_ROUNDS;
+use crate::error::{Result, SssError};
+use crate::format::ShareEncoding;
+use crate::policy::Custodian;
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+fn parsed<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+fn parsed_list<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|text| text.parse().map_err(|e| serde::de::Error::custom(format!("'{}': {}", text, e))))
+        .collect()
+}
+
+/// Answers to `encrypt`'s questions, any of which may be left out.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub threshold: Option<u8>,
+    pub total: Option<u8>,
+    pub kdf_rounds: Option<u32>,
+    #[serde(default, deserialize_with = "parsed")]
+    pub encoding: Option<ShareEncoding>,
+    pub out_dir: Option<PathBuf>,
+    /// Who gets each share, as `NAME:JURISDICTION`; also sets the number of shares.
+    #[serde(default, deserialize_with = "parsed_list")]
+    pub custodians: Vec<Custodian>,
+}
+
+impl Profile {
+    /// This profile with any setting it leaves out taken from `base`.
+    fn over(self, base: &Profile) -> Profile {
+        Profile {
+            threshold: self.threshold.or(base.threshold),
+            total: self.total.or(base.total),
+            kdf_rounds: self.kdf_rounds.or(base.kdf_rounds),
+            encoding: self.encoding.or(base.encoding),
+            out_dir: self.out_dir.or_else(|| base.out_dir.clone()),
+            custodians: match self.custodians.is_empty() {
+                true => base.custodians.clone(),
+                false => self.custodians,
+            },
+        }
+    }
+
+    fn check(&self) -> std::result::Result<(), String> {
+        let total = match (self.total, self.custodians.len()) {
+            (Some(total), 0) => Some(total as usize),
+            (None, 0) => None,
+            (Some(total), n) if total as usize != n => {
+                return Err(format!("total is {} but {} custodians are listed", total, n));
+            }
+            (_, n) => Some(n),
+        };
+        if total.is_some_and(|total| !(2..=255).contains(&total)) {
+            return Err("a set needs 2 to 255 shares".into());
+        }
+        if let Some(threshold) = self.threshold {
+            if threshold < 2 {
+                return Err("the threshold must be at least 2".into());
+            }
+            if let Some(total) = total.filter(|&total| threshold as usize > total) {
+                return Err(format!("a threshold of {} is more than the {} shares", threshold, total));
+            }
+        }
+        if self.kdf_rounds.is_some_and(|rounds| !(1_000..=MAX_PBKDF2_ROUNDS).contains(&rounds)) {
+            return Err(format!("kdf_rounds must be between 1000 and {}", MAX_PBKDF2_ROUNDS));
+        }
+        Ok(())
+    }
+}
+
+/// The parsed configuration file.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Profile,
+    #[serde(default, rename = "profile")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config> {
+        toml::from_str(text).map_err(|e| SssError::Parse(format!("{}: {}", CONFIG_FILE, e.message())))
+    }
+
+    pub fn read(path: &Path) -> Result<Config> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The configuration in the config directory, or an empty one if there is no file.
+    pub fn load() -> Result<Config> {
+        match config_dir().map(|dir| dir.join(CONFIG_FILE)) {
+            Some(path) if path.exists() => Self::read(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// The defaults with profile `name`, if one is given, laid over them.
+    pub fn resolve(&self, name: Option<&str>) -> Result<Profile> {
+        let profile = match name {
+            Some(name) => self.profiles.get(name).cloned().ok_or_else(|| {
+                let known = self.profiles.keys().map(String::as_str).collect::<Vec<_>>();
+                SssError::InvalidArgument(match known.is_empty() {
+                    true => format!("no profile '{}': {} defines none", name, CONFIG_FILE),
+                    false => format!("no profile '{}' in {}; it has {}", name, CONFIG_FILE, known.join(", ")),
+                })
+            })?,
+            None => Profile::default(),
+        };
+        let mut profile = profile.over(&self.defaults);
+        profile.check().map_err(|e| {
+            SssError::Parse(format!("{} profile {}: {}", CONFIG_FILE, name.unwrap_or("defaults"), e))
+        })?;
+        profile.out_dir = profile.out_dir.map(|dir| expand_home(&dir));
+        Ok(profile)
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
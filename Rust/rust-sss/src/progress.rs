@@ -0,0 +1,58 @@
+//! Progress events for front ends that show a progress bar during the slow parts of a split or
+//! recovery: the PBKDF2 rounds, share generation, the rehearsal of quorums and streaming a large
+//! file. Nothing is reported until a reporter is installed, with [`set_reporter`] for a callback
+//! or [`report_to`] for JSON lines on a writer, as `sss --progress-fd` does:
+//!
+//! ```text
+//! {"event":"kdf-started","rounds":600000}
+//! {"event":"kdf-progress","done":6000,"rounds":600000}
+//! {"event":"share-generated","index":1,"total":3}
+//! {"event":"verification-passed","quorums":3}
+//! ```
+
+use serde::Serialize;
+use std::io::Write;
+use std::sync::{Mutex, RwLock};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    KdfStarted { rounds: u32 },
+    /// Sent about a hundred times per key, the last time with `done` equal to `rounds`.
+    KdfProgress { done: u32, rounds: u32 },
+    ShareGenerated { index: u8, total: u8 },
+    /// Every rehearsed quorum rebuilt the secret.
+    VerificationPassed { quorums: usize },
+    /// Plaintext bytes of a large file encrypted or decrypted so far.
+    StreamProgress { bytes: u64 },
+}
+
+type Reporter = Box<dyn Fn(&Event) + Send + Sync>;
+
+static REPORTER: RwLock<Option<Reporter>> = RwLock::new(None);
+
+/// Sends every event from now on to `reporter`, or stops reporting with `None`.
+pub fn set_reporter(reporter: Option<Reporter>) {
+    *REPORTER.write().unwrap_or_else(|e| e.into_inner()) = reporter;
+}
+
+/// Writes every event from now on to `writer` as a line of JSON, flushing each one.
+pub fn report_to<W: Write + Send + 'static>(writer: W) {
+    let writer = Mutex::new(writer);
+    set_reporter(Some(Box::new(move |event| {
+        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+        let line = serde_json::to_string(event).expect("events serialize");
+        // a front end that went away should not stop the split
+        let _ = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+    })));
+}
+
+pub fn enabled() -> bool {
+    REPORTER.read().map(|reporter| reporter.is_some()).unwrap_or(false)
+}
+
+pub(crate) fn emit(event: Event) {
+    if let Some(reporter) = REPORTER.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        reporter(&event);
+    }
+}
@@ -0,0 +1,192 @@
+//! Custodian receipts: a small signed statement that a custodian received a share and checked
+//! it. The receipt names the share only by its fingerprint, so it can be sent back to the dealer
+//! over any channel, and `sss receipts verify` checks a manifest's shares are all accounted for.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use pbkdf2::hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::manifest::{share_fingerprint, Manifest};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Proof {
+    /// Signed with the custodian's own key, as `sss guardian keygen` writes it.
+    Ed25519 { public_key: String, signature: String },
+    /// HMAC-SHA256 under a key the dealer shared with the custodian.
+    Hmac { mac: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub format_version: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_fingerprint: Option<String>,
+    pub share_index: u8,
+    /// Hex SHA-256 of the share bytes, as the manifest lists it.
+    pub share_fingerprint: String,
+    pub custodian: String,
+    pub received_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<Proof>,
+}
+
+/// How the dealer checks a receipt's proof.
+#[derive(Default)]
+pub struct ReceiptVerifier<'a> {
+    pub hmac_key: Option<&'a [u8]>,
+    /// When set, an Ed25519 receipt must be signed by the key registered for its custodian.
+    pub registry: Option<&'a crate::social::Registry>,
+}
+
+fn hmac(key: &[u8], bytes: &[u8]) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(bytes);
+    mac
+}
+
+impl Receipt {
+    pub fn new(share: &[u8], custodian: &str, set_fingerprint: Option<String>) -> Receipt {
+        Receipt {
+            format_version: FORMAT_VERSION,
+            set_fingerprint,
+            share_index: share.first().copied().unwrap_or(0),
+            share_fingerprint: share_fingerprint(share),
+            custodian: custodian.to_string(),
+            received_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            proof: None,
+        }
+    }
+
+    /// The bytes covered by the proof: the receipt as JSON without its proof.
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let unsigned = Receipt { proof: None, ..self.clone() };
+        serde_json::to_vec(&unsigned).map_err(|e| SssError::Parse(e.to_string()))
+    }
+
+    pub fn sign(&mut self, key: &SigningKey) -> Result<()> {
+        let signature = key.sign(&self.signed_bytes()?);
+        self.proof = Some(Proof::Ed25519 {
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+        });
+        Ok(())
+    }
+
+    pub fn seal(&mut self, hmac_key: &[u8]) -> Result<()> {
+        let mac = hmac(hmac_key, &self.signed_bytes()?).finalize().into_bytes();
+        self.proof = Some(Proof::Hmac { mac: hex::encode(mac) });
+        Ok(())
+    }
+
+    /// Checks the proof, and for signed receipts that the key belongs to the named custodian
+    /// when a registry is given.
+    pub fn verify(&self, verifier: &ReceiptVerifier) -> Result<()> {
+        let bytes = self.signed_bytes()?;
+        match &self.proof {
+            None => Err(SssError::Signing("the receipt is not signed".into())),
+            Some(Proof::Ed25519 { public_key, signature }) => {
+                let key = crate::token::parse_verifying_key(public_key)?;
+                if let Some(registry) = verifier.registry {
+                    let guardian = registry.find(&self.custodian).ok_or_else(|| {
+                        SssError::Signing(format!("{} is not a registered guardian", self.custodian))
+                    })?;
+                    if guardian.verifying_key()? != key {
+                        return Err(SssError::Signing(format!("signed with a key that is not {}'s", self.custodian)));
+                    }
+                }
+                let signature = hex::decode(signature)
+                    .ok()
+                    .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                    .ok_or_else(|| SssError::Signing("malformed receipt signature".into()))?;
+                key.verify(&bytes, &signature)
+                    .map_err(|_| SssError::Signing("the signature does not match the receipt".into()))
+            }
+            Some(Proof::Hmac { mac }) => {
+                let key = verifier
+                    .hmac_key
+                    .ok_or_else(|| SssError::Signing("an HMAC receipt needs --hmac-key to check".into()))?;
+                let mac = hex::decode(mac).map_err(|_| SssError::Signing("malformed receipt MAC".into()))?;
+                hmac(key, &bytes)
+                    .verify_slice(&mac)
+                    .map_err(|_| SssError::Signing("the MAC does not match the receipt".into()))
+            }
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Receipt> {
+        let text = fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| SssError::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| SssError::Parse(e.to_string()))?;
+        crate::secure::write_private(path, (json + "\n").as_bytes())
+    }
+}
+
+/// `receipt-01.json`, matching the share file numbering.
+pub fn receipt_file_name(index: u8) -> String {
+    format!("receipt-{:02}.json", index)
+}
+
+/// Receipt files named directly, plus every `.json` file in any directory given.
+pub fn collect(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found: Vec<PathBuf> = fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json") && !p.ends_with(crate::manifest::MANIFEST_FILE))
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// What a manifest share's receipt came to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Received { custodian: String, received_at: u64 },
+    Invalid(String),
+    Missing,
+}
+
+/// Matches receipts to the manifest's shares by index and fingerprint. A share is accounted for
+/// when any receipt for it checks out.
+pub fn account(manifest: &Manifest, receipts: &[Receipt], verifier: &ReceiptVerifier) -> Vec<(u8, Status)> {
+    manifest
+        .shares
+        .iter()
+        .map(|entry| {
+            let mut status = Status::Missing;
+            for receipt in receipts.iter().filter(|r| r.share_index == entry.index) {
+                let verdict = if receipt.share_fingerprint != entry.fingerprint {
+                    Err("the receipt does not match the share's fingerprint".to_string())
+                } else if receipt.set_fingerprint.as_ref().is_some_and(|set| *set != manifest.set_fingerprint) {
+                    Err("the receipt is for another set".to_string())
+                } else {
+                    receipt.verify(verifier).map_err(|e| e.to_string())
+                };
+                match verdict {
+                    Ok(()) => {
+                        status = Status::Received { custodian: receipt.custodian.clone(), received_at: receipt.received_at };
+                        break;
+                    }
+                    Err(e) => status = Status::Invalid(format!("{}: {}", receipt.custodian, e)),
+                }
+            }
+            (entry.index, status)
+        })
+        .collect()
+}
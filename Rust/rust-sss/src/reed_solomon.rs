@@ -0,0 +1,168 @@
+//! Reed-Solomon parity for shares kept as text. A share with parity is printed as
+//! `rs<N>:<share>`, where the share is followed by N parity bytes per block of up to 255
+//! bytes; when it is read back, up to N/2 wrong bytes per block are corrected, so a few
+//! smudged or mistyped characters on a paper backup no longer make the share unusable.
+//!
+//! The code is over the same GF(2^8) as the sharing (x^8 + x^4 + x^3 + x^2 + 1) with
+//! generator 2 and first consecutive root 2^0.
+
+use crate::error::{Result, SssError};
+use crate::shamir::{inv, mul, REDUCTION};
+
+const BLOCK_LEN: usize = 255;
+pub const MAX_PARITY: usize = 64;
+
+fn gmul(a: u8, b: u8) -> u8 {
+    mul(REDUCTION, a, b)
+}
+
+fn pow2(mut exponent: usize) -> u8 {
+    exponent %= BLOCK_LEN;
+    let (mut result, mut base) = (1u8, 2u8);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gmul(result, base);
+        }
+        base = gmul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Evaluates a polynomial given highest degree first.
+fn eval(poly: &[u8], x: u8) -> u8 {
+    poly.iter().fold(0, |acc, &c| gmul(acc, x) ^ c)
+}
+
+/// The generator (x - 2^0)(x - 2^1)...(x - 2^(parity-1)), highest degree first.
+fn generator(parity: usize) -> Vec<u8> {
+    let mut g = vec![1u8];
+    for i in 0..parity {
+        let root = pow2(i);
+        let mut next = vec![0u8; g.len() + 1];
+        for (j, &c) in g.iter().enumerate() {
+            next[j] ^= c;
+            next[j + 1] ^= gmul(c, root);
+        }
+        g = next;
+    }
+    g
+}
+
+fn check_parity(parity: usize) -> Result<()> {
+    if parity == 0 || parity > MAX_PARITY || !parity.is_multiple_of(2) {
+        return Err(SssError::InvalidArgument(format!(
+            "parity must be an even number of bytes from 2 to {}",
+            MAX_PARITY
+        )));
+    }
+    Ok(())
+}
+
+/// Appends `parity` bytes to every block of `255 - parity` bytes of `data`.
+pub fn encode(data: &[u8], parity: usize) -> Result<Vec<u8>> {
+    check_parity(parity)?;
+    let g = generator(parity);
+    let mut out = Vec::with_capacity(data.len() + parity * data.len().div_ceil(BLOCK_LEN - parity));
+    for block in data.chunks(BLOCK_LEN - parity) {
+        let mut remainder = block.to_vec();
+        remainder.resize(block.len() + parity, 0);
+        for i in 0..block.len() {
+            let coefficient = remainder[i];
+            if coefficient != 0 {
+                for (j, &gj) in g.iter().enumerate().skip(1) {
+                    remainder[i + j] ^= gmul(gj, coefficient);
+                }
+            }
+        }
+        out.extend_from_slice(block);
+        out.extend_from_slice(&remainder[block.len()..]);
+    }
+    Ok(out)
+}
+
+/// Strips the parity from `codeword`, correcting errors on the way. Returns the data and how
+/// many bytes were corrected.
+pub fn decode(codeword: &[u8], parity: usize) -> Result<(Vec<u8>, usize)> {
+    check_parity(parity)?;
+    let mut data = Vec::with_capacity(codeword.len());
+    let mut corrected = 0;
+    for block in codeword.chunks(BLOCK_LEN) {
+        if block.len() <= parity {
+            return Err(SssError::Share("share is shorter than its parity; it was cut off".into()));
+        }
+        let mut block = block.to_vec();
+        corrected += correct_block(&mut block, parity)?;
+        data.extend_from_slice(&block[..block.len() - parity]);
+    }
+    Ok((data, corrected))
+}
+
+fn correct_block(block: &mut [u8], parity: usize) -> Result<usize> {
+    let n = block.len();
+    let syndromes: Vec<u8> = (0..parity).map(|i| eval(block, pow2(i))).collect();
+    if syndromes.iter().all(|&s| s == 0) {
+        return Ok(0);
+    }
+    let uncorrectable = || SssError::Checksum("share has more damage than its parity can correct".into());
+
+    // Berlekamp-Massey; the locator is lowest degree first
+    let (mut locator, mut previous) = (vec![1u8], vec![1u8]);
+    let (mut errors, mut shift, mut last_discrepancy) = (0usize, 1usize, 1u8);
+    for k in 0..parity {
+        let discrepancy = (1..=errors).fold(syndromes[k], |d, i| d ^ gmul(locator.get(i).copied().unwrap_or(0), syndromes[k - i]));
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+        let scale = gmul(discrepancy, inv(REDUCTION, last_discrepancy));
+        let mut next = locator.clone();
+        next.resize(next.len().max(previous.len() + shift), 0);
+        for (i, &b) in previous.iter().enumerate() {
+            next[i + shift] ^= gmul(scale, b);
+        }
+        if 2 * errors <= k {
+            previous = std::mem::replace(&mut locator, next);
+            errors = k + 1 - errors;
+            last_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            locator = next;
+            shift += 1;
+        }
+    }
+    locator.truncate(errors + 1);
+    if errors > parity / 2 {
+        return Err(uncorrectable());
+    }
+
+    // error values by Forney's formula, with evaluator = syndromes * locator mod x^parity
+    let mut evaluator = vec![0u8; parity];
+    for (i, &s) in syndromes.iter().enumerate() {
+        for (j, &l) in locator.iter().enumerate().take(parity - i) {
+            evaluator[i + j] ^= gmul(s, l);
+        }
+    }
+    let eval_low = |poly: &[u8], x: u8| poly.iter().rev().fold(0, |acc, &c| gmul(acc, x) ^ c);
+    let derivative: Vec<u8> = locator.iter().enumerate().skip(1).map(|(i, &c)| if i % 2 == 1 { c } else { 0 }).collect();
+
+    let mut found = 0;
+    for (position, byte) in block.iter_mut().enumerate() {
+        let power = n - 1 - position;
+        let x_inv = pow2(BLOCK_LEN - power);
+        if eval_low(&locator, x_inv) != 0 {
+            continue;
+        }
+        let denominator = eval_low(&derivative, x_inv);
+        if denominator == 0 {
+            return Err(uncorrectable());
+        }
+        let magnitude = gmul(pow2(power), gmul(eval_low(&evaluator, x_inv), inv(REDUCTION, denominator)));
+        *byte ^= magnitude;
+        found += 1;
+    }
+    if found != errors || (0..parity).any(|i| eval(block, pow2(i)) != 0) {
+        return Err(uncorrectable());
+    }
+    Ok(found)
+}
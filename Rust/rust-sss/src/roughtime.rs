@@ -0,0 +1,223 @@
+//! A Roughtime client, speaking the original Google protocol over UDP, for a time that a
+//! server signs instead of the local clock. The reply is checked against the server's
+//! long-term Ed25519 key: the key's signature on a delegated key, the delegated key's
+//! signature on the signed response, and the Merkle path from our nonce to the signed root.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+use crate::error::{Result, SssError};
+
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 64;
+/// Servers ignore requests shorter than this, so a request cannot be amplified.
+const REQUEST_LEN: usize = 1024;
+const HASH_LEN: usize = 64;
+const DELEGATION_CONTEXT: &[u8] = b"RoughTime v1 delegation signature--\0";
+const RESPONSE_CONTEXT: &[u8] = b"RoughTime v1 response signature\0";
+const TIMEOUT: Duration = Duration::from_secs(3);
+const ATTEMPTS: usize = 3;
+
+pub type Tag = [u8; 4];
+
+pub const NONC: Tag = *b"NONC";
+pub const PAD: Tag = *b"PAD\xff";
+pub const SIG: Tag = *b"SIG\0";
+pub const PATH: Tag = *b"PATH";
+pub const SREP: Tag = *b"SREP";
+pub const CERT: Tag = *b"CERT";
+pub const INDX: Tag = *b"INDX";
+pub const RADI: Tag = *b"RADI";
+pub const MIDP: Tag = *b"MIDP";
+pub const ROOT: Tag = *b"ROOT";
+pub const DELE: Tag = *b"DELE";
+pub const MINT: Tag = *b"MINT";
+pub const MAXT: Tag = *b"MAXT";
+pub const PUBK: Tag = *b"PUBK";
+
+fn roughtime_error(what: impl std::fmt::Display) -> SssError {
+    SssError::Store(format!("Roughtime: {}", what))
+}
+
+/// A time the server vouched for, in microseconds since the Unix epoch, give or take `radius`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attested {
+    pub midpoint: u64,
+    pub radius: u32,
+}
+
+impl Attested {
+    /// The earliest second the true time can be.
+    pub fn earliest_secs(&self) -> u64 {
+        self.midpoint.saturating_sub(u64::from(self.radius)) / 1_000_000
+    }
+}
+
+/// A key given as base64 or hex.
+pub fn parse_key(text: &str) -> Result<[u8; PUBLIC_KEY_LEN]> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    let text = text.trim();
+    let bytes = hex::decode(text).or_else(|_| BASE64.decode(text)).unwrap_or_default();
+    bytes
+        .try_into()
+        .map_err(|_| SssError::InvalidArgument(format!("a Roughtime key is {} bytes of base64 or hex", PUBLIC_KEY_LEN)))
+}
+
+/// A message: the tag count, the offsets of every value but the first, the tags in ascending
+/// order (as little-endian numbers), then the values. Fields are sorted here.
+pub fn encode_message(fields: &[(Tag, &[u8])]) -> Vec<u8> {
+    let mut fields = fields.to_vec();
+    fields.sort_by_key(|(tag, _)| u32::from_le_bytes(*tag));
+    let mut out = (fields.len() as u32).to_le_bytes().to_vec();
+    let mut offset = 0u32;
+    for (_, value) in fields.iter().take(fields.len().saturating_sub(1)) {
+        offset += value.len() as u32;
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+    for (tag, _) in &fields {
+        out.extend_from_slice(tag);
+    }
+    for (_, value) in &fields {
+        out.extend_from_slice(value);
+    }
+    out
+}
+
+struct Message<'a> {
+    fields: Vec<(Tag, &'a [u8])>,
+}
+
+impl<'a> Message<'a> {
+    fn decode(bytes: &'a [u8]) -> Result<Message<'a>> {
+        let malformed = || roughtime_error("malformed message");
+        let word = |i: usize| -> Result<u32> {
+            let bytes = bytes.get(i * 4..i * 4 + 4).ok_or_else(malformed)?;
+            Ok(u32::from_le_bytes(bytes.try_into().expect("four bytes")))
+        };
+        let count = word(0)? as usize;
+        if count == 0 || !bytes.len().is_multiple_of(4) || count > bytes.len() / 8 {
+            return Err(malformed());
+        }
+        let values = bytes.get(4 * 2 * count..).ok_or_else(malformed)?;
+        let mut offsets = vec![0usize];
+        for i in 1..count {
+            offsets.push(word(i)? as usize);
+        }
+        offsets.push(values.len());
+        let mut fields = Vec::with_capacity(count);
+        for i in 0..count {
+            let tag = word(count + i)?;
+            if fields.last().is_some_and(|(last, _)| u32::from_le_bytes(*last) >= tag) {
+                return Err(malformed());
+            }
+            let (start, end) = (offsets[i], offsets[i + 1]);
+            if !start.is_multiple_of(4) || start > end || end > values.len() {
+                return Err(malformed());
+            }
+            fields.push((tag.to_le_bytes(), &values[start..end]));
+        }
+        Ok(Message { fields })
+    }
+
+    fn get(&self, tag: Tag) -> Result<&'a [u8]> {
+        self.fields
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| roughtime_error(format!("the reply has no {}", String::from_utf8_lossy(&tag))))
+    }
+
+    fn get_array<const N: usize>(&self, tag: Tag) -> Result<[u8; N]> {
+        self.get(tag)?.try_into().map_err(|_| roughtime_error("a field of the reply has the wrong size"))
+    }
+
+    fn get_u64(&self, tag: Tag) -> Result<u64> {
+        self.get_array(tag).map(u64::from_le_bytes)
+    }
+}
+
+/// A request for `nonce`, padded to the length servers answer.
+pub fn request(nonce: &[u8; NONCE_LEN]) -> Vec<u8> {
+    let header_len = 4 + 4 + 2 * 4;
+    let padding = vec![0u8; REQUEST_LEN - header_len - NONCE_LEN];
+    encode_message(&[(NONC, nonce), (PAD, &padding)])
+}
+
+fn verify_signature(key: &[u8; PUBLIC_KEY_LEN], context: &[u8], message: &[u8], signature: [u8; 64]) -> Result<()> {
+    let key = VerifyingKey::from_bytes(key).map_err(|_| roughtime_error("the public key is not a valid Ed25519 key"))?;
+    let signed = [context, message].concat();
+    key.verify(&signed, &Signature::from_bytes(&signature))
+        .map_err(|_| roughtime_error("the reply is not signed by the expected server key"))
+}
+
+pub fn leaf_hash(nonce: &[u8]) -> [u8; HASH_LEN] {
+    Sha512::new().chain_update([0u8]).chain_update(nonce).finalize().into()
+}
+
+pub fn node_hash(left: &[u8], right: &[u8]) -> [u8; HASH_LEN] {
+    Sha512::new().chain_update([1u8]).chain_update(left).chain_update(right).finalize().into()
+}
+
+/// Checks a reply to the request for `nonce` against the server's long-term `key`.
+pub fn verify(reply: &[u8], nonce: &[u8; NONCE_LEN], key: &[u8; PUBLIC_KEY_LEN]) -> Result<Attested> {
+    let reply = Message::decode(reply)?;
+    let cert = Message::decode(reply.get(CERT)?)?;
+    let delegation_bytes = cert.get(DELE)?;
+    verify_signature(key, DELEGATION_CONTEXT, delegation_bytes, cert.get_array(SIG)?)?;
+    let delegation = Message::decode(delegation_bytes)?;
+
+    let response_bytes = reply.get(SREP)?;
+    verify_signature(&delegation.get_array(PUBK)?, RESPONSE_CONTEXT, response_bytes, reply.get_array(SIG)?)?;
+    let response = Message::decode(response_bytes)?;
+
+    let mut index = u32::from_le_bytes(reply.get_array(INDX)?);
+    let path = reply.get(PATH)?;
+    if !path.len().is_multiple_of(HASH_LEN) {
+        return Err(roughtime_error("the Merkle path is malformed"));
+    }
+    let mut hash = leaf_hash(nonce);
+    for sibling in path.chunks(HASH_LEN) {
+        hash = if index & 1 == 0 { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+        index >>= 1;
+    }
+    if hash != response.get_array::<HASH_LEN>(ROOT)? {
+        return Err(roughtime_error("the reply does not answer our request"));
+    }
+
+    let midpoint = response.get_u64(MIDP)?;
+    let (earliest, latest) = (delegation.get_u64(MINT)?, delegation.get_u64(MAXT)?);
+    if !(earliest..=latest).contains(&midpoint) {
+        return Err(roughtime_error("the server's delegated key has expired"));
+    }
+    Ok(Attested { midpoint, radius: u32::from_le_bytes(response.get_array(RADI)?) })
+}
+
+/// Asks `server` (`host:port`) for the time and checks the answer against `key`.
+pub fn query(server: &str, key: &[u8; PUBLIC_KEY_LEN]) -> Result<Attested> {
+    let address = server
+        .to_socket_addrs()
+        .map_err(|e| roughtime_error(format!("could not resolve {}: {}", server, e)))?
+        .next()
+        .ok_or_else(|| roughtime_error(format!("{} has no address", server)))?;
+    let local = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(local)?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    socket.connect(address)?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let request = request(&nonce);
+    let mut reply = vec![0u8; 4096];
+    for _ in 0..ATTEMPTS {
+        socket.send(&request)?;
+        match socket.recv(&mut reply) {
+            Ok(len) => return verify(&reply[..len], &nonce, key),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(roughtime_error(format!("{} did not answer", server)))
+}
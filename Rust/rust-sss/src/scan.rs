@@ -0,0 +1,212 @@
+//! Share entry by webcam, for air-gapped ceremonies where typing hundreds of hex digits is
+//! impractical. `zbarcam` (from zbar-tools) opens the camera and prints each QR code it reads;
+//! [`Capture`] keeps the shares that belong together and knows when there are enough, and
+//! rebuilds shares shown as animated fountain-coded frames (`sss qr-export`) or as multi-part
+//! `ur:crypto-sss` resources.
+
+use std::io::{BufRead, BufReader, Lines};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+use crate::bcur::{self, PartsDecoder};
+use crate::error::{Result, SssError};
+use crate::format::{share_conflict, ShareHeader};
+use crate::fountain::{self, Decoder, Frame};
+use crate::input;
+
+/// What became of one scanned code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scanned {
+    /// A new share with this index.
+    Added(u8),
+    /// The share with this index again, as the camera sees it in frame after frame.
+    Again(u8),
+    /// A frame of a fountain-coded share or a part of a multi-part UR, with `have` of the
+    /// `of` fragments needed so far.
+    Partial { have: usize, of: usize },
+    /// Not a share, or one that does not belong with those captured.
+    Rejected(String),
+}
+
+/// The shares captured so far, with the set and threshold their headers declare.
+#[derive(Default)]
+pub struct Capture {
+    shares: Vec<(Vec<u8>, Option<String>)>,
+    threshold: Option<u8>,
+    fountains: Vec<Decoder>,
+    /// Checksums of the fountain-coded messages already rebuilt, with their share index.
+    rebuilt: Vec<([u8; 4], u8)>,
+    ur_parts: Option<PartsDecoder>,
+}
+
+impl Capture {
+    /// `threshold` is how many shares to wait for, if already known; otherwise the first share
+    /// whose header gives a quorum decides.
+    pub fn new(threshold: Option<u8>) -> Self {
+        Capture { threshold, ..Default::default() }
+    }
+
+    pub fn offer(&mut self, text: &str) -> Scanned {
+        if fountain::is_frame(text) {
+            return self.offer_frame(text);
+        }
+        if let Some(of) = bcur::part_count(text) {
+            return self.offer_ur_part(text, of);
+        }
+        match input::parse_share_details(text) {
+            Ok(parsed) => self.add(parsed.share, parsed.header),
+            Err(e) => Scanned::Rejected(e.to_string()),
+        }
+    }
+
+    fn add(&mut self, share: Vec<u8>, header: ShareHeader) -> Scanned {
+        if self.shares.iter().any(|(other, _)| *other == share) {
+            return Scanned::Again(share[0]);
+        }
+        let set = header.set;
+        if let Some(conflict) = share_conflict(&self.shares, &share, set.as_deref()) {
+            return Scanned::Rejected(format!("share index {} does not belong with the others: {}", share[0], conflict));
+        }
+        self.threshold = self.threshold.or(header.quorum.map(|(threshold, _)| threshold));
+        let index = share[0];
+        self.shares.push((share, set));
+        Scanned::Added(index)
+    }
+
+    /// Takes one part of a multi-part `ur:crypto-sss`. A part of some other resource means the
+    /// animation changed, so collecting starts over with it.
+    fn offer_ur_part(&mut self, text: &str, of: usize) -> Scanned {
+        let parts = self.ur_parts.get_or_insert_with(PartsDecoder::default);
+        if parts.receive(text).is_err() {
+            *parts = PartsDecoder::default();
+            if let Err(e) = parts.receive(text) {
+                return Scanned::Rejected(e.to_string());
+            }
+        }
+        match parts.share() {
+            Ok(None) => Scanned::Partial { have: parts.received().min(of), of },
+            Ok(Some(share)) => {
+                self.ur_parts = None;
+                self.add(share, ShareHeader::default())
+            }
+            Err(e) => {
+                self.ur_parts = None;
+                Scanned::Rejected(e.to_string())
+            }
+        }
+    }
+
+    fn offer_frame(&mut self, text: &str) -> Scanned {
+        let frame = match Frame::parse(text) {
+            Ok(frame) => frame,
+            Err(e) => return Scanned::Rejected(e.to_string()),
+        };
+        if let Some(&(_, index)) = self.rebuilt.iter().find(|(checksum, _)| *checksum == frame.checksum) {
+            return Scanned::Again(index);
+        }
+        let at = match self.fountains.iter().position(|d| d.checksum() == frame.checksum) {
+            Some(at) => match self.fountains[at].receive(frame) {
+                Ok(()) => at,
+                Err(e) => return Scanned::Rejected(e.to_string()),
+            },
+            None => {
+                self.fountains.push(Decoder::new(frame));
+                self.fountains.len() - 1
+            }
+        };
+        let message = match self.fountains[at].message() {
+            Ok(None) => {
+                let (have, of) = self.fountains[at].progress();
+                return Scanned::Partial { have, of };
+            }
+            Ok(Some(message)) => message,
+            Err(e) => {
+                self.fountains.remove(at);
+                return Scanned::Rejected(e.to_string());
+            }
+        };
+        let checksum = self.fountains.remove(at).checksum();
+        let Ok(text) = std::str::from_utf8(&message) else {
+            return Scanned::Rejected("the fountain-coded frames do not hold share text".into());
+        };
+        let scanned = self.offer(text);
+        if let Scanned::Added(index) | Scanned::Again(index) = scanned {
+            self.rebuilt.push((checksum, index));
+        }
+        scanned
+    }
+
+    /// Indexes of the captured shares, in the order scanned.
+    pub fn indexes(&self) -> Vec<u8> {
+        self.shares.iter().map(|(share, _)| share[0]).collect()
+    }
+
+    pub fn threshold(&self) -> Option<u8> {
+        self.threshold
+    }
+
+    /// The set fingerprint the first share naming one declared.
+    pub fn set(&self) -> Option<&str> {
+        self.shares.iter().find_map(|(_, set)| set.as_deref())
+    }
+
+    /// Whether the threshold is known and that many shares are in.
+    pub fn complete(&self) -> bool {
+        self.threshold.is_some_and(|threshold| self.shares.len() >= threshold as usize)
+    }
+
+    pub fn into_shares(self) -> Vec<Vec<u8>> {
+        self.shares.into_iter().map(|(share, _)| share).collect()
+    }
+}
+
+/// A running `zbarcam`, stopped when dropped.
+pub struct Scanner {
+    child: Child,
+    lines: Lines<BufReader<ChildStdout>>,
+}
+
+impl Scanner {
+    /// Opens `device`, or the default camera, in a preview window.
+    pub fn open(device: Option<&Path>) -> Result<Self> {
+        let mut command = Command::new("zbarcam");
+        command.args(["--raw", "-Sdisable", "-Sqrcode.enable"]);
+        if let Some(device) = device {
+            command.arg(device);
+        }
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                let message = format!("could not run zbarcam (is zbar-tools installed?): {}", e);
+                SssError::Io(std::io::Error::new(e.kind(), message))
+            })?;
+        let stdout = child.stdout.take().expect("piped stdout");
+        Ok(Scanner { child, lines: BufReader::new(stdout).lines() })
+    }
+
+    /// The next code read, or `None` once the camera window is closed. Header lines come out
+    /// one per line, so they are kept and handed back with the share line that follows them.
+    pub fn next_code(&mut self) -> Result<Option<String>> {
+        let mut code = String::new();
+        for line in self.lines.by_ref() {
+            let line = line?;
+            code.push_str(&line);
+            let (header, rest) = ShareHeader::split(&line);
+            if header.is_empty() || !rest.trim().is_empty() {
+                return Ok(Some(code));
+            }
+            code.push('\n');
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for Scanner {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
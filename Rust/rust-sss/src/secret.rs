@@ -0,0 +1,753 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::bcur::Resource;
+use crate::error::{Result, SssError};
+use crate::otp::Totp;
+
+pub const MIN_SECRET_SIZE: usize = 32;
+pub const MAX_SECRET_SIZE: usize = 16 * 1024 * 1024;
+/// Type byte of a zstd-compressed serialized secret: u32 length, then the compressed bytes.
+pub const COMPRESSED: u8 = 0xc0;
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 19;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum SecretType {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+    /// A JSON object kept as compact text, e.g. a bundle of related credentials.
+    Json(String),
+    /// Several independent, labelled secrets sharing one share set.
+    Bundle(Vec<(String, SecretType)>),
+    /// The entropy behind a BIP-39 seed phrase; recovered as the same English words.
+    Mnemonic(Vec<u8>),
+    /// An SSH private key file, OpenSSH or PEM, kept as its text.
+    SshKey(String),
+    /// An armored GPG secret key export and the fingerprint of its primary key, checked
+    /// again after recovery.
+    GpgKey { fingerprint: String, armored: String },
+    /// An age identity file, kept as its text.
+    AgeIdentity(String),
+    /// A `.env` file, kept byte for byte.
+    EnvFile(String),
+    /// A UUID as its 16 bytes; recovered in the hyphenated lowercase form.
+    Uuid([u8; 16]),
+    /// A calendar date as `YYYY-MM-DD`.
+    Date(String),
+    /// A TOTP seed; recovered as its `otpauth://` URI.
+    Totp(Totp),
+}
+
+impl Zeroize for SecretType {
+    fn zeroize(&mut self) {
+        match self {
+            SecretType::String(s) => s.zeroize(),
+            SecretType::Int(i) => i.zeroize(),
+            SecretType::Float(f) => f.zeroize(),
+            SecretType::Bytes(b) => b.zeroize(),
+            SecretType::Json(j) => j.zeroize(),
+            SecretType::Bundle(entries) => {
+                for (label, secret) in entries.iter_mut() {
+                    label.zeroize();
+                    secret.zeroize();
+                }
+                entries.clear();
+            }
+            SecretType::Mnemonic(entropy) => entropy.zeroize(),
+            SecretType::SshKey(key) => key.zeroize(),
+            SecretType::AgeIdentity(identity) => identity.zeroize(),
+            SecretType::EnvFile(text) => text.zeroize(),
+            SecretType::Uuid(uuid) => uuid.zeroize(),
+            SecretType::Date(date) => date.zeroize(),
+            SecretType::Totp(totp) => totp.zeroize(),
+            SecretType::GpgKey { fingerprint, armored } => {
+                fingerprint.zeroize();
+                armored.zeroize();
+            }
+        }
+    }
+}
+
+impl SecretType {
+    /// Interprets what the user typed. `hex:` and `base64:` prefixes mark binary secrets,
+    /// `json:` a JSON object and `bip39:` a seed phrase, a `ur:crypto-seed` is read as a seed
+    /// and a `ur:bytes` as binary. `uuid:`, `date:` and `totp:` (a base32 seed) name those types,
+    /// which are also detected, as are `otpauth://totp/` URIs. Numbers are only kept as numbers
+    /// when they print back the same, so "042" or "1.50" stay strings.
+    pub fn from_input(input: &str) -> Result<Self> {
+        if let Some(text) = input.strip_prefix("uuid:") {
+            return Self::uuid(text);
+        }
+        if let Some(text) = input.strip_prefix("date:") {
+            return Self::date(text);
+        }
+        if let Some(seed) = input.strip_prefix("totp:") {
+            return Ok(SecretType::Totp(Totp::from_seed(seed.trim())?));
+        }
+        if input.starts_with("otpauth://") {
+            return Ok(SecretType::Totp(Totp::parse_uri(input.trim())?));
+        }
+        if let Some(document) = input.strip_prefix("json:") {
+            return Self::json(document);
+        }
+        if let Some(phrase) = input.strip_prefix("bip39:") {
+            return Self::mnemonic(phrase);
+        }
+        if crate::bcur::is_ur(input) {
+            return match crate::bcur::decode(input)? {
+                Resource::Seed(entropy) => bip39::Mnemonic::from_entropy(&entropy)
+                    .map(|mnemonic| SecretType::Mnemonic(mnemonic.to_entropy()))
+                    .map_err(|e| SssError::Parse(format!("crypto-seed: {}", e))),
+                Resource::Bytes(bytes) => Ok(SecretType::Bytes(bytes.to_vec())),
+                Resource::Share(_) => Err(SssError::Parse("a ur:crypto-sss is a share, not a secret".into())),
+            };
+        }
+        if let Some(encoded) = input.strip_prefix("hex:") {
+            let bytes = hex::decode(encoded.trim())
+                .map_err(|e| SssError::Parse(format!("hex secret: {}", e)))?;
+            return Ok(SecretType::Bytes(bytes));
+        }
+        if let Some(encoded) = input.strip_prefix("base64:") {
+            let bytes = BASE64
+                .decode(encoded.trim())
+                .map_err(|e| SssError::Parse(format!("base64 secret: {}", e)))?;
+            return Ok(SecretType::Bytes(bytes));
+        }
+        if input.len() == 36 && input.as_bytes()[8] == b'-' {
+            if let Ok(uuid) = Self::uuid(input) {
+                return Ok(uuid);
+            }
+        }
+        if input.len() == 10 && input.as_bytes()[4] == b'-' {
+            if let Ok(date) = Self::date(input) {
+                return Ok(date);
+            }
+        }
+        Ok(match input.parse::<i64>() {
+            Ok(i) if i.to_string() == input => SecretType::Int(i),
+            _ => match input.parse::<f64>() {
+                Ok(f) if f.is_finite() && f.to_string() == input => SecretType::Float(f),
+                _ => SecretType::String(input.to_string()),
+            },
+        })
+    }
+
+    /// Reads `input` as the type the user named, without looking for prefixes or guessing;
+    /// [`SecretKind::Auto`] is [`SecretType::from_input`].
+    pub fn parse_as(input: &str, kind: SecretKind) -> Result<Self> {
+        let invalid = |what: &str| SssError::Parse(format!("the secret is not {}", what));
+        match kind {
+            SecretKind::Auto => Self::from_input(input),
+            SecretKind::String => Ok(SecretType::String(input.to_string())),
+            SecretKind::Int => input.trim().parse().map(SecretType::Int).map_err(|_| invalid("an integer")),
+            SecretKind::Float => match input.trim().parse::<f64>() {
+                Ok(f) if f.is_finite() => Ok(SecretType::Float(f)),
+                _ => Err(invalid("a finite number")),
+            },
+            SecretKind::Hex => hex::decode(input.trim())
+                .map(SecretType::Bytes)
+                .map_err(|e| SssError::Parse(format!("hex secret: {}", e))),
+            SecretKind::Base64 => BASE64
+                .decode(input.trim())
+                .map(SecretType::Bytes)
+                .map_err(|e| SssError::Parse(format!("base64 secret: {}", e))),
+            SecretKind::Json => Self::json(input),
+            SecretKind::Uuid => Self::uuid(input),
+            SecretKind::Date => Self::date(input),
+            SecretKind::Totp if input.trim().starts_with("otpauth://") => {
+                Ok(SecretType::Totp(Totp::parse_uri(input.trim())?))
+            }
+            SecretKind::Totp => Ok(SecretType::Totp(Totp::from_seed(input.trim())?)),
+        }
+    }
+
+    /// A UUID, hyphenated or as 32 hex digits, optionally as a `urn:uuid:` or in braces.
+    pub fn uuid(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix("urn:uuid:").unwrap_or(text);
+        let text = text.strip_prefix('{').and_then(|t| t.strip_suffix('}')).unwrap_or(text);
+        let hyphens_ok = match text.len() {
+            32 => true,
+            36 => [8, 13, 18, 23].iter().all(|&i| text.as_bytes()[i] == b'-'),
+            _ => false,
+        };
+        let digits: String = text.chars().filter(|&c| c != '-').collect();
+        let mut uuid = [0u8; 16];
+        if !hyphens_ok || digits.len() != 32 || hex::decode_to_slice(&digits, &mut uuid).is_err() {
+            return Err(SssError::Parse(format!("'{}' is not a UUID", text)));
+        }
+        Ok(SecretType::Uuid(uuid))
+    }
+
+    /// A `YYYY-MM-DD` date that exists on the calendar.
+    pub fn date(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let invalid = || SssError::Parse(format!("'{}' is not a YYYY-MM-DD date", text));
+        let parts: Vec<&str> = text.split('-').collect();
+        if text.len() != 10 || parts.len() != 3 || !text.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            return Err(invalid());
+        }
+        let (year, month, day): (u32, u32, u32) = (
+            parts[0].parse().map_err(|_| invalid())?,
+            parts[1].parse().map_err(|_| invalid())?,
+            parts[2].parse().map_err(|_| invalid())?,
+        );
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return Err(invalid()),
+        };
+        if day == 0 || day > days {
+            return Err(invalid());
+        }
+        Ok(SecretType::Date(text.to_string()))
+    }
+
+    pub fn json(document: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(document)
+            .map_err(|e| SssError::Parse(format!("JSON secret: {}", e)))?;
+        if !value.is_object() {
+            return Err(SssError::Parse("JSON secret must be an object of named values".into()));
+        }
+        Ok(SecretType::Json(value.to_string()))
+    }
+
+    /// Checks a seed phrase against the English wordlist and its checksum and keeps only its
+    /// entropy, so a mistyped word is caught now rather than at recovery.
+    pub fn mnemonic(phrase: &str) -> Result<Self> {
+        let normalized = Zeroizing::new(phrase.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase());
+        let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, &normalized)
+            .map_err(|e| match e {
+                bip39::Error::UnknownWord(i) => {
+                    let word = normalized.split(' ').nth(i).unwrap_or_default();
+                    let prefix: String = word.chars().take(3).collect();
+                    let close = bip39::Language::English.words_by_prefix(&prefix);
+                    let hint = match close.len() {
+                        0 => String::new(),
+                        n => format!(" (did you mean {}?)", close[..n.min(5)].join(", ")),
+                    };
+                    SssError::Parse(format!("word #{} '{}' is not a BIP-39 word{}", i + 1, word, hint))
+                }
+                bip39::Error::InvalidChecksum => SssError::Checksum(
+                    "seed phrase checksum does not match: a word is mistyped or out of order".into(),
+                ),
+                e => SssError::Parse(format!("seed phrase: {}", e)),
+            })?;
+        Ok(SecretType::Mnemonic(mnemonic.to_entropy()))
+    }
+
+    /// Builds a bundle, rejecting empty, duplicate or nested entries.
+    pub fn bundle(entries: Vec<(String, SecretType)>) -> Result<Self> {
+        if entries.is_empty() {
+            return Err(SssError::Parse("a bundle needs at least one secret".into()));
+        }
+        for (i, (label, secret)) in entries.iter().enumerate() {
+            if label.is_empty() || label.len() > u16::MAX as usize {
+                return Err(SssError::Parse(format!("secret #{} needs a label of 1-65535 bytes", i + 1)));
+            }
+            if entries[..i].iter().any(|(other, _)| other == label) {
+                return Err(SssError::Parse(format!("label '{}' is used twice", label)));
+            }
+            if matches!(secret, SecretType::Bundle(_)) {
+                return Err(SssError::Parse("bundles cannot be nested".into()));
+            }
+        }
+        Ok(SecretType::Bundle(entries))
+    }
+}
+
+/// The type `--type` asks a typed secret to be read as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecretKind {
+    /// Prefixes and detection, as [`SecretType::from_input`] does.
+    #[default]
+    Auto,
+    /// Exactly what was typed, even if it looks like a number.
+    String,
+    Int,
+    Float,
+    Hex,
+    Base64,
+    Json,
+    Uuid,
+    Date,
+    /// A base32 seed or an `otpauth://totp/` URI.
+    Totp,
+}
+
+impl std::str::FromStr for SecretKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(SecretKind::Auto),
+            "string" => Ok(SecretKind::String),
+            "int" => Ok(SecretKind::Int),
+            "float" => Ok(SecretKind::Float),
+            "hex" => Ok(SecretKind::Hex),
+            "base64" => Ok(SecretKind::Base64),
+            "json" => Ok(SecretKind::Json),
+            "uuid" => Ok(SecretKind::Uuid),
+            "date" => Ok(SecretKind::Date),
+            "totp" => Ok(SecretKind::Totp),
+            _ => Err("expected auto, string, int, float, hex, base64, json, uuid, date or totp".to_string()),
+        }
+    }
+}
+
+/// The hyphenated lowercase form, `8-4-4-4-12`.
+pub fn uuid_text(uuid: &[u8; 16]) -> String {
+    let hex = hex::encode(uuid);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Picks the entries named in `labels` out of a bundle, in the order asked for; all of them
+/// when `labels` is empty.
+pub fn select_labels<'a>(
+    entries: &'a [(String, SecretType)],
+    labels: &[String],
+) -> Result<Vec<&'a (String, SecretType)>> {
+    if labels.is_empty() {
+        return Ok(entries.iter().collect());
+    }
+    labels
+        .iter()
+        .map(|label| {
+            entries.iter().find(|(l, _)| l == label).ok_or_else(|| {
+                let available: Vec<&str> = entries.iter().map(|(l, _)| l.as_str()).collect();
+                SssError::Parse(format!(
+                    "no secret labelled '{}' (available: {})",
+                    label,
+                    available.join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+/// The English seed phrase for BIP-39 entropy.
+pub fn mnemonic_words(entropy: &[u8]) -> Result<Zeroizing<String>> {
+    let mnemonic = bip39::Mnemonic::from_entropy(entropy)
+        .map_err(|e| SssError::Parse(format!("seed phrase entropy: {}", e)))?;
+    Ok(Zeroizing::new(mnemonic.words().collect::<Vec<_>>().join(" ")))
+}
+
+/// Pretty-prints a JSON secret, keeping only `keys` when any are given.
+pub fn select_json_keys(document: &str, keys: &[String]) -> Result<Zeroizing<String>> {
+    let value: Value = serde_json::from_str(document)
+        .map_err(|e| SssError::Parse(format!("JSON secret: {}", e)))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| SssError::Parse("JSON secret is not an object".into()))?;
+    let render = |v: &Value| {
+        serde_json::to_string_pretty(v)
+            .map(Zeroizing::new)
+            .map_err(|e| SssError::Parse(format!("JSON secret: {}", e)))
+    };
+    if keys.is_empty() {
+        return render(&value);
+    }
+    let mut selected = Map::new();
+    for key in keys {
+        let v = object.get(key).ok_or_else(|| {
+            let available: Vec<&str> = object.keys().map(String::as_str).collect();
+            SssError::Parse(format!("key '{}' not in secret (available: {})", key, available.join(", ")))
+        })?;
+        selected.insert(key.clone(), v.clone());
+    }
+    render(&Value::Object(selected))
+}
+
+impl Drop for SecretType {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+pub fn serialize_secret(secret: &SecretType) -> Zeroizing<Vec<u8>> {
+    let nested: Vec<Zeroizing<Vec<u8>>> = match secret {
+        SecretType::Bundle(entries) => entries.iter().map(|(_, s)| serialize_secret(s)).collect(),
+        _ => Vec::new(),
+    };
+    // reserve the exact size, so growing the buffer never leaves a stray copy behind
+    let len = match secret {
+        SecretType::String(s) => 5 + s.len(),
+        SecretType::Bytes(b) => 5 + b.len(),
+        SecretType::Json(j) => 5 + j.len(),
+        SecretType::Mnemonic(e) => 5 + e.len(),
+        SecretType::SshKey(k) => 5 + k.len(),
+        SecretType::AgeIdentity(i) => 5 + i.len(),
+        SecretType::EnvFile(e) => 5 + e.len(),
+        SecretType::Uuid(_) => 17,
+        SecretType::Date(d) => 5 + d.len(),
+        SecretType::Totp(t) => 13 + t.seed.len() + t.issuer.as_ref().map_or(0, |i| i.len()) + t.account.as_ref().map_or(0, |a| a.len()),
+        SecretType::GpgKey { fingerprint, armored } => 6 + fingerprint.len() + armored.len(),
+        SecretType::Bundle(entries) => {
+            5 + entries.iter().map(|(l, _)| 6 + l.len()).sum::<usize>()
+                + nested.iter().map(|n| n.len()).sum::<usize>()
+        }
+        _ => 9,
+    };
+    let mut bytes = Zeroizing::new(Vec::with_capacity(len));
+    match secret {
+        SecretType::String(s) => {
+            bytes.push(0u8);
+            bytes.extend_from_slice(&(s.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(s.as_bytes());
+        }
+        SecretType::Int(i) => {
+            bytes.push(1u8);
+            bytes.extend_from_slice(&i.to_be_bytes());
+        }
+        SecretType::Float(f) => {
+            bytes.push(2u8);
+            bytes.extend_from_slice(&f.to_be_bytes());
+        }
+        SecretType::Bytes(b) => {
+            bytes.push(3u8);
+            bytes.extend_from_slice(&(b.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(b);
+        }
+        SecretType::Json(j) => {
+            bytes.push(4u8);
+            bytes.extend_from_slice(&(j.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(j.as_bytes());
+        }
+        SecretType::Bundle(entries) => {
+            bytes.push(5u8);
+            bytes.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+            for ((label, _), item) in entries.iter().zip(&nested) {
+                bytes.extend_from_slice(&(label.len() as u16).to_be_bytes());
+                bytes.extend_from_slice(label.as_bytes());
+                bytes.extend_from_slice(&(item.len() as u32).to_be_bytes());
+                bytes.extend_from_slice(item);
+            }
+        }
+        SecretType::Mnemonic(entropy) => {
+            bytes.push(6u8);
+            bytes.extend_from_slice(&(entropy.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(entropy);
+        }
+        SecretType::SshKey(key) => {
+            bytes.push(7u8);
+            bytes.extend_from_slice(&(key.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(key.as_bytes());
+        }
+        SecretType::GpgKey { fingerprint, armored } => {
+            bytes.push(8u8);
+            bytes.extend_from_slice(&((1 + fingerprint.len() + armored.len()) as u32).to_be_bytes());
+            bytes.push(fingerprint.len() as u8);
+            bytes.extend_from_slice(fingerprint.as_bytes());
+            bytes.extend_from_slice(armored.as_bytes());
+        }
+        SecretType::AgeIdentity(identity) => {
+            bytes.push(9u8);
+            bytes.extend_from_slice(&(identity.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(identity.as_bytes());
+        }
+        SecretType::EnvFile(text) => {
+            bytes.push(10u8);
+            bytes.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(text.as_bytes());
+        }
+        SecretType::Uuid(uuid) => {
+            bytes.push(11u8);
+            bytes.extend_from_slice(uuid);
+        }
+        SecretType::Date(date) => {
+            bytes.push(12u8);
+            bytes.extend_from_slice(&(date.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(date.as_bytes());
+        }
+        SecretType::Totp(totp) => {
+            // algorithm, digits, period, then the length-prefixed issuer and account and the seed
+            let issuer = totp.issuer.as_deref().unwrap_or_default();
+            let account = totp.account.as_deref().unwrap_or_default();
+            bytes.push(13u8);
+            bytes.extend_from_slice(&((8 + issuer.len() + account.len() + totp.seed.len()) as u32).to_be_bytes());
+            bytes.push(TOTP_ALGORITHMS.iter().position(|&a| a == totp.algorithm).unwrap_or(0) as u8);
+            bytes.push(totp.digits);
+            bytes.extend_from_slice(&totp.period.to_be_bytes());
+            bytes.push(issuer.len() as u8);
+            bytes.extend_from_slice(issuer.as_bytes());
+            bytes.push(account.len() as u8);
+            bytes.extend_from_slice(account.as_bytes());
+            bytes.extend_from_slice(&totp.seed);
+        }
+    }
+    bytes
+}
+
+/// Decodes a serialized secret. Everything after the value must be padding; a length
+/// that runs past the end of the buffer or beyond `MAX_SECRET_SIZE` is rejected.
+pub fn deserialize_secret(bytes: &[u8]) -> Result<SecretType> {
+    if bytes.len() > MAX_SECRET_SIZE + 5 {
+        return Err(SssError::Parse(format!("secret is larger than {} bytes", MAX_SECRET_SIZE)));
+    }
+    let type_byte = *bytes.first().ok_or_else(|| SssError::Parse("secret is empty".into()))?;
+    let (secret, used) = match type_byte {
+        0 => {
+            let body = length_prefixed(bytes, "string")?;
+            (SecretType::String(utf8(body, "string")?), 5 + body.len())
+        }
+        1 => (SecretType::Int(i64::from_be_bytes(take::<8>(bytes, 1)?)), 9),
+        2 => (SecretType::Float(f64::from_be_bytes(take::<8>(bytes, 1)?)), 9),
+        3 => {
+            let body = length_prefixed(bytes, "byte")?;
+            (SecretType::Bytes(body.to_vec()), 5 + body.len())
+        }
+        4 => {
+            let body = length_prefixed(bytes, "JSON")?;
+            let text = Zeroizing::new(utf8(body, "JSON")?);
+            (SecretType::json(&text)?, 5 + body.len())
+        }
+        5 => deserialize_bundle(bytes)?,
+        6 => {
+            let body = length_prefixed(bytes, "seed phrase")?;
+            mnemonic_words(body)?;
+            (SecretType::Mnemonic(body.to_vec()), 5 + body.len())
+        }
+        7 => {
+            let body = length_prefixed(bytes, "SSH key")?;
+            (SecretType::SshKey(utf8(body, "SSH key")?), 5 + body.len())
+        }
+        8 => {
+            let body = length_prefixed(bytes, "GPG key")?;
+            let fp_len = *body.first().ok_or_else(|| SssError::Parse("GPG key secret is empty".into()))? as usize;
+            let fingerprint = body
+                .get(1..1 + fp_len)
+                .ok_or_else(|| SssError::Parse("GPG key fingerprint is truncated".into()))?;
+            let secret = SecretType::GpgKey {
+                fingerprint: utf8(fingerprint, "GPG key fingerprint")?,
+                armored: utf8(&body[1 + fp_len..], "GPG key")?,
+            };
+            (secret, 5 + body.len())
+        }
+        9 => {
+            let body = length_prefixed(bytes, "age identity")?;
+            (SecretType::AgeIdentity(utf8(body, "age identity")?), 5 + body.len())
+        }
+        10 => {
+            let body = length_prefixed(bytes, ".env file")?;
+            (SecretType::EnvFile(utf8(body, ".env file")?), 5 + body.len())
+        }
+        11 => (SecretType::Uuid(take::<16>(bytes, 1)?), 17),
+        12 => {
+            let body = length_prefixed(bytes, "date")?;
+            (SecretType::date(&utf8(body, "date")?)?, 5 + body.len())
+        }
+        13 => {
+            let body = length_prefixed(bytes, "TOTP seed")?;
+            (SecretType::Totp(deserialize_totp(body)?), 5 + body.len())
+        }
+        COMPRESSED => {
+            let body = length_prefixed(bytes, "compressed")?;
+            let inner = Zeroizing::new(decompress(body)?);
+            if inner.first() == Some(&COMPRESSED) {
+                return Err(SssError::Parse("compressed secrets cannot be nested".into()));
+            }
+            (deserialize_secret(&inner)?, 5 + body.len())
+        }
+        b => return Err(SssError::Parse(format!("unknown secret type byte {}", b))),
+    };
+    // older sets were padded with zeros only, newer ones start the padding with a marker
+    let tail = match bytes.get(used) {
+        Some(&PADDING_MARKER) => used + 1,
+        _ => used,
+    };
+    if let Some(pos) = bytes[tail..].iter().position(|&b| b != 0) {
+        return Err(SssError::Parse(format!("unexpected data after the secret at byte {}", tail + pos)));
+    }
+    Ok(secret)
+}
+
+fn deserialize_bundle(bytes: &[u8]) -> Result<(SecretType, usize)> {
+    let count = u32::from_be_bytes(take::<4>(bytes, 1)?) as usize;
+    let mut offset = 5;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let label_len = u16::from_be_bytes(take::<2>(bytes, offset)?) as usize;
+        let label = bytes
+            .get(offset + 2..offset + 2 + label_len)
+            .ok_or_else(|| SssError::Parse("bundle label is truncated".into()))?;
+        let label = utf8(label, "bundle label")?;
+        offset += 2 + label_len;
+
+        let item_len = u32::from_be_bytes(take::<4>(bytes, offset)?) as usize;
+        let item = bytes
+            .get(offset + 4..offset + 4 + item_len)
+            .ok_or_else(|| SssError::Parse(format!("bundle entry '{}' is truncated", label)))?;
+        offset += 4 + item_len;
+        if item.first() == Some(&5) {
+            return Err(SssError::Parse("bundles cannot be nested".into()));
+        }
+        entries.push((label, deserialize_secret(item)?));
+    }
+    Ok((SecretType::bundle(entries)?, offset))
+}
+
+const TOTP_ALGORITHMS: [&str; 3] = ["SHA1", "SHA256", "SHA512"];
+
+fn deserialize_totp(body: &[u8]) -> Result<Totp> {
+    let truncated = || SssError::Parse("TOTP seed is truncated".into());
+    let algorithm = TOTP_ALGORITHMS
+        .get(*body.first().ok_or_else(truncated)? as usize)
+        .ok_or_else(|| SssError::Parse("unknown TOTP algorithm".into()))?;
+    let digits = *body.get(1).ok_or_else(truncated)?;
+    let period = u32::from_be_bytes(body.get(2..6).ok_or_else(truncated)?.try_into().unwrap_or_default());
+    let mut offset = 6;
+    let mut text = |kind: &str| -> Result<Option<String>> {
+        let len = *body.get(offset).ok_or_else(truncated)? as usize;
+        let field = body.get(offset + 1..offset + 1 + len).ok_or_else(truncated)?;
+        offset += 1 + len;
+        (len > 0).then(|| utf8(field, kind)).transpose()
+    };
+    let issuer = text("TOTP issuer")?;
+    let account = text("TOTP account")?;
+    let seed = body[offset..].to_vec();
+    if seed.is_empty() {
+        return Err(SssError::Parse("the TOTP seed is empty".into()));
+    }
+    Ok(Totp { seed, issuer, account, algorithm: algorithm.to_string(), digits, period })
+}
+
+fn utf8(body: &[u8], kind: &str) -> Result<String> {
+    String::from_utf8(body.to_vec()).map_err(|e| {
+        let valid_up_to = e.utf8_error().valid_up_to();
+        e.into_bytes().zeroize();
+        SssError::Parse(format!("{} secret is not valid UTF-8 (byte {})", kind, valid_up_to))
+    })
+}
+
+fn length_prefixed<'a>(bytes: &'a [u8], kind: &str) -> Result<&'a [u8]> {
+    let len = u32::from_be_bytes(take::<4>(bytes, 1)?) as usize;
+    if len > MAX_SECRET_SIZE {
+        return Err(SssError::Parse(format!("{} length {} exceeds the maximum", kind, len)));
+    }
+    bytes.get(5..5 + len).ok_or_else(|| SssError::Parse(format!(
+        "{} secret claims {} bytes but only {} are present",
+        kind,
+        len,
+        bytes.len().saturating_sub(5)
+    )))
+}
+
+fn take<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N]> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| SssError::Parse(format!(
+            "secret is truncated: needed {} bytes, have {}",
+            offset + N,
+            bytes.len()
+        )))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress(body: &[u8]) -> Result<Vec<u8>> {
+    zstd::bulk::decompress(body, MAX_SECRET_SIZE + 5)
+        .map_err(|e| SssError::Parse(format!("compressed secret is corrupt: {}", e)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress(_: &[u8]) -> Result<Vec<u8>> {
+    Err(SssError::Parse("the secret is compressed, and this build has no zstd support".into()))
+}
+
+/// Wraps a serialized secret in a zstd envelope, keeping the original when compression does
+/// not make it smaller.
+#[cfg(feature = "zstd")]
+pub fn compress(serialized: Zeroizing<Vec<u8>>) -> Result<Zeroizing<Vec<u8>>> {
+    let compressed = Zeroizing::new(
+        zstd::bulk::compress(&serialized, ZSTD_LEVEL).map_err(SssError::Io)?,
+    );
+    if compressed.len() + 5 >= serialized.len() {
+        return Ok(serialized);
+    }
+    let mut bytes = Zeroizing::new(Vec::with_capacity(compressed.len() + 5));
+    bytes.push(COMPRESSED);
+    bytes.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&compressed);
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "zstd"))]
+pub fn compress(_: Zeroizing<Vec<u8>>) -> Result<Zeroizing<Vec<u8>>> {
+    Err(SssError::InvalidArgument("this build has no zstd support; leave out --compress".into()))
+}
+
+/// Byte placed right after the serialized secret, before the zero fill, so the padding is
+/// unambiguous whatever the value ends with.
+pub const PADDING_MARKER: u8 = 0x80;
+
+/// How far a serialized secret is padded before encryption, to hide its length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Padding {
+    /// Only up to `MIN_SECRET_SIZE`; larger secrets leak their exact size.
+    Minimum,
+    /// Up to the next power of two, so the size leaks at most its order of magnitude.
+    #[default]
+    PowerOfTwo,
+    /// Up to the next multiple of the given number of bytes.
+    Bucket(usize),
+    /// To exactly this many bytes, failing if the secret does not fit.
+    Exact(usize),
+}
+
+impl std::str::FromStr for Padding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "minimum" => Ok(Padding::Minimum),
+            "pow2" => Ok(Padding::PowerOfTwo),
+            _ => match s.strip_prefix("bucket:").map(str::parse::<usize>) {
+                Some(Ok(n)) if n > 0 => Ok(Padding::Bucket(n)),
+                _ => Err("expected minimum, pow2 or bucket:<bytes>".to_string()),
+            },
+        }
+    }
+}
+
+impl Padding {
+    /// Padded length for a serialized secret of `len` bytes, including the marker byte.
+    pub fn padded_len(&self, len: usize) -> Result<usize> {
+        let needed = (len + 1).max(MIN_SECRET_SIZE);
+        match *self {
+            Padding::Minimum => Ok(needed),
+            Padding::PowerOfTwo => Ok(needed.next_power_of_two()),
+            Padding::Bucket(size) => Ok(needed.div_ceil(size) * size),
+            Padding::Exact(size) if needed <= size => Ok(size),
+            Padding::Exact(size) => Err(SssError::InvalidArgument(format!(
+                "secret needs {} bytes but the size bucket only allows {}",
+                needed, size
+            ))),
+        }
+    }
+}
+
+/// Appends the padding marker and zero-fills to the length the policy asks for. The copy is
+/// made into a buffer of the final size so no reallocation leaves plaintext behind.
+pub fn pad(bytes: &[u8], padding: Padding) -> Result<Zeroizing<Vec<u8>>> {
+    let len = padding.padded_len(bytes.len())?;
+    let mut padded = Zeroizing::new(Vec::with_capacity(len));
+    padded.extend_from_slice(bytes);
+    padded.push(PADDING_MARKER);
+    padded.resize(len, 0);
+    Ok(padded)
+}
+
+/// Pads two serialized secrets under `padding` and then the shorter up to the longer, so a
+/// secret and its decoy encrypt to slots of one size.
+pub fn pad_alike(first: &[u8], second: &[u8], padding: Padding) -> Result<[Zeroizing<Vec<u8>>; 2]> {
+    let len = padding.padded_len(first.len())?.max(padding.padded_len(second.len())?);
+    Ok([pad(first, Padding::Exact(len))?, pad(second, Padding::Exact(len))?])
+}
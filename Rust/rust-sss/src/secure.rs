@@ -0,0 +1,414 @@
+use alloc::{format, vec::Vec};
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+use zeroize::Zeroize;
+
+use crate::error::{Result, SssError};
+
+static LOCK_MEMORY: AtomicBool = AtomicBool::new(false);
+
+/// Opts the whole process into locking key and plaintext buffers in RAM (mlock on Unix,
+/// VirtualLock on Windows) so they are never written to swap.
+pub fn enable_memory_locking() {
+    LOCK_MEMORY.store(true, Ordering::SeqCst);
+}
+
+pub fn memory_locking_enabled() -> bool {
+    LOCK_MEMORY.load(Ordering::SeqCst)
+}
+
+#[cfg(feature = "std")]
+/// How written files are treated, for every share, secret and card the tool writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WritePolicy {
+    /// Replace a file that already exists instead of refusing to.
+    pub force: bool,
+    /// Write a temporary file next to the target and rename it into place once it is complete.
+    pub atomic: bool,
+    /// Overwrite a temporary file with zeros before it is removed.
+    pub shred: bool,
+}
+
+#[cfg(feature = "std")]
+static WRITE_POLICY: RwLock<WritePolicy> = RwLock::new(WritePolicy { force: false, atomic: false, shred: false });
+
+#[cfg(feature = "std")]
+pub fn set_write_policy(policy: WritePolicy) {
+    *WRITE_POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+#[cfg(feature = "std")]
+pub fn write_policy() -> WritePolicy {
+    *WRITE_POLICY.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(feature = "std")]
+/// Fails if `path` exists and the write policy does not allow replacing it.
+pub fn ensure_writable(path: &Path) -> Result<()> {
+    if !write_policy().force && (path.exists() || path.is_symlink()) {
+        return Err(SssError::Policy(format!(
+            "{} already exists; not writing over it without --force",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Opens `path` for writing, readable only by the owner: mode 0600 on Unix, and on Windows an
+/// ACL granting the current user alone access, with nothing inherited from the directory.
+pub fn create_private(path: &Path) -> Result<File> {
+    ensure_writable(path)?;
+    #[cfg(windows)]
+    return create_owner_only(path, write_policy().force);
+    #[cfg(not(windows))]
+    {
+        let mut options = OpenOptions::new();
+        options.write(true);
+        if write_policy().force {
+            options.create(true).truncate(true);
+        } else {
+            options.create_new(true);
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(path)?;
+        // a replaced file keeps the mode it had
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        Ok(file)
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+/// A protected DACL holding a single entry, full control for the user the process runs as, so
+/// the ACEs a share directory would pass down are dropped.
+struct OwnerOnlyAcl(*mut windows_sys::Win32::Security::ACL);
+
+#[cfg(all(feature = "std", windows))]
+impl OwnerOnlyAcl {
+    fn new() -> std::io::Result<Self> {
+        use std::io::Error;
+        use windows_sys::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, HANDLE};
+        use windows_sys::Win32::Security::Authorization::{
+            SetEntriesInAclW, EXPLICIT_ACCESS_W, NO_MULTIPLE_TRUSTEE, SET_ACCESS, TRUSTEE_IS_SID, TRUSTEE_IS_USER,
+            TRUSTEE_W,
+        };
+        use windows_sys::Win32::Security::{GetTokenInformation, TokenUser, NO_INHERITANCE, TOKEN_QUERY, TOKEN_USER};
+        use windows_sys::Win32::Storage::FileSystem::FILE_ALL_ACCESS;
+        use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+        // SAFETY: every pointer passed points at a live local of the length given, and the
+        // token handle is closed on every path; the ACL is released when `Self` is dropped.
+        unsafe {
+            let mut token: HANDLE = core::ptr::null_mut();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                return Err(Error::last_os_error());
+            }
+            // a TOKEN_USER is followed by the SID it points to; u64s keep the buffer aligned for it
+            let mut buffer = [0u64; 64];
+            let mut len = 0u32;
+            let size = core::mem::size_of_val(&buffer) as u32;
+            let queried = GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), size, &mut len);
+            let error = Error::last_os_error();
+            CloseHandle(token);
+            if queried == 0 {
+                return Err(error);
+            }
+            let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+            let access = EXPLICIT_ACCESS_W {
+                grfAccessPermissions: FILE_ALL_ACCESS,
+                grfAccessMode: SET_ACCESS,
+                grfInheritance: NO_INHERITANCE,
+                Trustee: TRUSTEE_W {
+                    pMultipleTrustee: core::ptr::null_mut(),
+                    MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+                    TrusteeForm: TRUSTEE_IS_SID,
+                    TrusteeType: TRUSTEE_IS_USER,
+                    ptstrName: user.User.Sid.cast(),
+                },
+            };
+            // SetEntriesInAclW copies the SID, so the ACL outlives `buffer`
+            let mut acl = core::ptr::null_mut();
+            let code = SetEntriesInAclW(1, &access, core::ptr::null(), &mut acl);
+            if code != ERROR_SUCCESS {
+                return Err(Error::from_raw_os_error(code as i32));
+            }
+            Ok(OwnerOnlyAcl(acl))
+        }
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl Drop for OwnerOnlyAcl {
+    fn drop(&mut self) {
+        // SAFETY: the ACL was allocated by SetEntriesInAclW and is freed only here.
+        unsafe { windows_sys::Win32::Foundation::LocalFree(self.0.cast()) };
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+/// Creates `path` with an [`OwnerOnlyAcl`] in the security attributes handed to CreateFileW, so
+/// the file is never readable by anyone else, not even between its creation and a later
+/// change of its ACL. A file that `force` replaces keeps its own DACL through CREATE_ALWAYS,
+/// so that one is restricted once it is open.
+fn create_owner_only(path: &Path, force: bool) -> Result<File> {
+    use std::io::Error;
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{FromRawHandle, RawHandle};
+    use windows_sys::Win32::Foundation::{ERROR_ALREADY_EXISTS, GENERIC_WRITE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Security::{
+        InitializeSecurityDescriptor, SetSecurityDescriptorControl, SetSecurityDescriptorDacl, PSECURITY_DESCRIPTOR,
+        SECURITY_ATTRIBUTES, SECURITY_DESCRIPTOR, SE_DACL_PROTECTED,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, CREATE_ALWAYS, CREATE_NEW, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_DELETE, FILE_SHARE_READ,
+        FILE_SHARE_WRITE, WRITE_DAC,
+    };
+    use windows_sys::Win32::System::SystemServices::SECURITY_DESCRIPTOR_REVISION;
+
+    let private = |e: Error| {
+        SssError::Io(Error::new(e.kind(), format!("could not make {} private: {}", path.display(), e)))
+    };
+    let acl = OwnerOnlyAcl::new().map_err(private)?;
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(core::iter::once(0)).collect();
+    // SAFETY: the descriptor, the attributes pointing at it and the ACL it points at all live
+    // until CreateFileW returns, and a valid handle it returns is owned by the `File` alone.
+    unsafe {
+        let mut descriptor = SECURITY_DESCRIPTOR::default();
+        let pointer: PSECURITY_DESCRIPTOR = (&mut descriptor as *mut SECURITY_DESCRIPTOR).cast();
+        if InitializeSecurityDescriptor(pointer, SECURITY_DESCRIPTOR_REVISION) == 0
+            || SetSecurityDescriptorDacl(pointer, 1, acl.0, 0) == 0
+            || SetSecurityDescriptorControl(pointer, SE_DACL_PROTECTED, SE_DACL_PROTECTED) == 0
+        {
+            return Err(private(Error::last_os_error()));
+        }
+        let attributes = SECURITY_ATTRIBUTES {
+            nLength: core::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: pointer,
+            bInheritHandle: 0,
+        };
+        let handle = CreateFileW(
+            wide.as_ptr(),
+            GENERIC_WRITE | WRITE_DAC,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            &attributes,
+            if force { CREATE_ALWAYS } else { CREATE_NEW },
+            FILE_ATTRIBUTE_NORMAL,
+            core::ptr::null_mut(),
+        );
+        let error = Error::last_os_error();
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(error.into());
+        }
+        let file = File::from_raw_handle(handle as RawHandle);
+        if error.raw_os_error() == Some(ERROR_ALREADY_EXISTS as i32) {
+            restrict_to_owner(&file, &acl).map_err(private)?;
+        }
+        Ok(file)
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+/// Replaces the DACL of an open file with `acl`.
+fn restrict_to_owner(file: &File, acl: &OwnerOnlyAcl) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{ERROR_SUCCESS, HANDLE};
+    use windows_sys::Win32::Security::Authorization::{SetSecurityInfo, SE_FILE_OBJECT};
+    use windows_sys::Win32::Security::{DACL_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION};
+
+    // SAFETY: the handle is open for WRITE_DAC and the ACL lives until the call returns.
+    let code = unsafe {
+        SetSecurityInfo(
+            file.as_raw_handle() as HANDLE,
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+            acl.0,
+            core::ptr::null(),
+        )
+    };
+    match code {
+        ERROR_SUCCESS => Ok(()),
+        code => Err(std::io::Error::from_raw_os_error(code as i32)),
+    }
+}
+
+#[cfg(feature = "std")]
+/// Creates `path`, readable only by the owner, and writes `contents` to it. Refuses to
+/// overwrite an existing file unless the write policy allows it.
+pub fn write_private<P: AsRef<Path>>(path: P, contents: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    if write_policy().atomic {
+        return write_private_with(path, |writer| Ok(writer.write_all(contents)?));
+    }
+    create_private(path)?.write_all(contents)?;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Writes `path` through `fill` into `<path>.partial`, renamed into place once `fill`
+/// succeeds; on failure the partial file is discarded.
+pub fn write_private_with<T, F>(path: &Path, fill: F) -> Result<T>
+where
+    F: FnOnce(&mut dyn Write) -> Result<T>,
+{
+    ensure_writable(path)?;
+    let partial = partial_path(path);
+    let mut file = create_private(&partial)?;
+    let written = fill(&mut file).and_then(|value| {
+        file.sync_all()?;
+        fs::rename(&partial, path)?;
+        Ok(value)
+    });
+    match written {
+        Ok(value) => {
+            #[cfg(unix)]
+            if let Some(dir) = path.parent().map(|p| if p.as_os_str().is_empty() { Path::new(".") } else { p }) {
+                // the rename itself only lasts once the directory is synced
+                let _ = File::open(dir).and_then(|dir| dir.sync_all());
+            }
+            Ok(value)
+        }
+        Err(e) => {
+            drop(file);
+            discard(&partial);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// `<path>.partial`, shortened when the name would outgrow the 255 characters NTFS and most
+/// Unix file systems allow in one path component; the name then ends in a hash of the full one.
+fn partial_path(path: &Path) -> PathBuf {
+    const SUFFIX: &str = ".partial";
+    const MAX_NAME: usize = 255;
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    // UTF-16 code units on Windows, bytes on Unix; counting bytes is the stricter of the two
+    if name.len() + SUFFIX.len() <= MAX_NAME {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(SUFFIX);
+        return PathBuf::from(partial);
+    }
+    use sha2::{Digest, Sha256};
+    let hash = hex::encode(&Sha256::digest(name.as_bytes())[..8]);
+    let mut keep = MAX_NAME - SUFFIX.len() - hash.len() - 1;
+    while !name.is_char_boundary(keep) {
+        keep -= 1;
+    }
+    path.with_file_name(format!("{}~{}{}", &name[..keep], hash, SUFFIX))
+}
+
+#[cfg(feature = "std")]
+/// Removes a temporary file, overwriting it with zeros first when the write policy shreds.
+pub fn discard(path: &Path) {
+    if write_policy().shred {
+        if let Ok(mut file) = OpenOptions::new().write(true).open(path) {
+            let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+            let zeros = [0u8; 4096];
+            let mut left = len;
+            while left > 0 {
+                let n = left.min(zeros.len() as u64) as usize;
+                if file.write_all(&zeros[..n]).is_err() {
+                    break;
+                }
+                left -= n as u64;
+            }
+            let _ = file.sync_all();
+        }
+    }
+    let _ = fs::remove_file(path);
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use memsec::{mlock, munlock};
+
+// wasm has no swap to keep pages out of, nor any way to pin them, and without std there is no
+// OS to ask
+#[cfg(any(target_arch = "wasm32", not(feature = "std")))]
+unsafe fn mlock(_: *mut u8, _: usize) -> bool {
+    false
+}
+
+#[cfg(any(target_arch = "wasm32", not(feature = "std")))]
+unsafe fn munlock(_: *mut u8, _: usize) -> bool {
+    false
+}
+
+/// Fixed-capacity byte buffer for secret material. It never reallocates, is zeroized on
+/// drop and, when memory locking is enabled, stays pinned in physical memory.
+pub struct SecretBuf {
+    data: Vec<u8>,
+    locked: bool,
+}
+
+impl SecretBuf {
+    pub fn with_capacity(capacity: usize) -> Result<Self> {
+        let mut data = Vec::with_capacity(capacity.max(1));
+        let mut locked = false;
+        if memory_locking_enabled() {
+            // SAFETY: the pointer and capacity describe this Vec's own allocation, which is
+            // never reallocated while the buffer is alive.
+            locked = unsafe { mlock(data.as_mut_ptr(), data.capacity()) };
+            if !locked {
+                return Err(SssError::SecureMemory(format!(
+                    "could not lock {} bytes of memory (check the memlock limit)",
+                    data.capacity()
+                )));
+            }
+        }
+        Ok(SecretBuf { data, locked })
+    }
+
+    pub fn zeroed(len: usize) -> Result<Self> {
+        let mut buf = Self::with_capacity(len)?;
+        buf.data.resize(len, 0);
+        Ok(buf)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let mut buf = Self::with_capacity(bytes.len())?;
+        buf.data.extend_from_slice(bytes);
+        Ok(buf)
+    }
+
+    /// Access for in-place AEAD operations, which may only shrink the buffer.
+    pub(crate) fn as_mut_vec(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+}
+
+impl Deref for SecretBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl DerefMut for SecretBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+}
+
+impl Drop for SecretBuf {
+    fn drop(&mut self) {
+        self.data.zeroize();
+        if self.locked {
+            // SAFETY: same allocation that was locked in `with_capacity`.
+            unsafe {
+                munlock(self.data.as_mut_ptr(), self.data.capacity());
+            }
+        }
+    }
+}
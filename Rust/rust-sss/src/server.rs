@@ -0,0 +1,343 @@
+//! `sss serve`: split, combine, inspect and verify over HTTP with JSON bodies, for tools that
+//! drive a recovery ceremony. Every endpoint but `GET /health` wants an
+//! `Authorization: Bearer <token>` header, and shares and passwords only cross the network
+//! under TLS: plain HTTP is refused unless the server listens on loopback. Given an issuer key,
+//! `/combine` also takes a capability token (see [`crate::token`]) in place of the bearer
+//! token, for one recovery of the set it names.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ed25519_dalek::VerifyingKey;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::{Mutex, PoisonError};
+use tiny_http::{Header, Method, Request, Response, Server, SslConfig};
+use zeroize::Zeroizing;
+
+use crate::crypto::open_payload;
+use crate::error::{Result, SssError};
+use crate::format::{set_fingerprint, share_conflict, validate_shares, ShareEncoding};
+use crate::inspect::{inspect_text, ShareInfo};
+use crate::input;
+use crate::pipeline;
+use crate::secret::{pad, serialize_secret, Padding, SecretType};
+use crate::shamir::{self, Field};
+use crate::token::{self, Capability, Ledger};
+
+const MAX_BODY: u64 = 1024 * 1024;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SplitRequest {
+    /// As typed at the `encrypt` prompt: text, `hex:`, `json:` and so on.
+    secret: String,
+    password: String,
+    threshold: u8,
+    total: u8,
+    #[serde(default)]
+    encoding: Option<String>,
+    #[serde(default)]
+    padding: Option<String>,
+    #[serde(default)]
+    kdf_rounds: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct SplitResponse {
+    shares: Vec<String>,
+    set_fingerprint: String,
+    kdf_rounds: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CombineRequest {
+    shares: Vec<String>,
+    password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InspectRequest {
+    shares: Vec<String>,
+    #[serde(default)]
+    field: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyRequest {
+    shares: Vec<String>,
+    /// Also check that the shares decrypt, without returning the secret.
+    #[serde(default)]
+    password: Option<String>,
+    /// Also check that every quorum of this size (or a sample of 64) rebuilds the same set.
+    #[serde(default)]
+    threshold: Option<u8>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct Verdict {
+    pub shares: usize,
+    pub set_fingerprint: String,
+    pub consistent: Option<bool>,
+    pub decrypts: Option<bool>,
+}
+
+/// The endpoints, apart from the HTTP plumbing.
+pub struct Api {
+    token: String,
+    kdf_rounds: u32,
+    // the lock keeps two workers from spending one token at once
+    capabilities: Option<(VerifyingKey, Mutex<Ledger>)>,
+}
+
+impl Api {
+    /// `kdf_rounds` is used for splits whose request does not name a cost.
+    pub fn new(token: impl Into<String>, kdf_rounds: u32) -> Self {
+        Api { token: token.into(), kdf_rounds, capabilities: None }
+    }
+
+    /// Also lets `/combine` through on a capability token signed by `issuer`, spent in `ledger`.
+    pub fn with_capabilities(self, issuer: VerifyingKey, ledger: Ledger) -> Self {
+        Api { capabilities: Some((issuer, Mutex::new(ledger))), ..self }
+    }
+
+    /// Answers one request with a status and a JSON body; failures carry `error` and `kind`.
+    pub fn handle(&self, method: &str, path: &str, authorization: Option<&str>, body: &str) -> (u16, Value) {
+        if (method, path) == ("GET", "/health") {
+            return (200, json!({ "status": "ok" }));
+        }
+        let outcome = match (method, path) {
+            ("POST", "/combine") => self
+                .authorize_combine(authorization)
+                .and_then(|capability| parse(body).and_then(|r| self.combine(r, capability))),
+            ("POST", "/split" | "/inspect" | "/verify") => {
+                self.authorize(authorization).and_then(|_| match path {
+                    "/split" => parse(body).and_then(|r| self.split(r)),
+                    "/inspect" => parse(body).and_then(inspect),
+                    _ => parse(body).and_then(verify),
+                })
+            }
+            (_, "/split" | "/combine" | "/inspect" | "/verify") => {
+                return (405, json!({ "error": format!("{} wants POST", path), "kind": "method" }))
+            }
+            _ => return (404, json!({ "error": format!("no endpoint {}", path), "kind": "not-found" })),
+        };
+        match outcome {
+            Ok(value) => (200, value),
+            Err(e) => (status(&e), json!({ "error": e.to_string(), "kind": e.kind() })),
+        }
+    }
+
+    fn authorize(&self, authorization: Option<&str>) -> Result<()> {
+        let given = bearer(authorization);
+        // comparing digests keeps the time taken independent of how much of the token matched
+        if given.is_empty() || Sha256::digest(given) != Sha256::digest(&self.token) {
+            return Err(SssError::Unauthorized("missing or wrong bearer token".into()));
+        }
+        Ok(())
+    }
+
+    /// The bearer token, giving `None`, or with an issuer key a capability token, which is
+    /// spent only once the shares are found to rebuild its set.
+    fn authorize_combine(&self, authorization: Option<&str>) -> Result<Option<Capability>> {
+        match (self.authorize(authorization), &self.capabilities) {
+            (Ok(()), _) => Ok(None),
+            (Err(_), Some((issuer, _))) if bearer(authorization).starts_with(token::TOKEN_PREFIX) => {
+                token::verify(bearer(authorization), issuer).map(Some)
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    fn combine(&self, request: CombineRequest, capability: Option<Capability>) -> Result<Value> {
+        let password = Zeroizing::new(request.password);
+        let shares = decode(&request.shares)?;
+        if let (Some(capability), Some((_, ledger))) = (capability, &self.capabilities) {
+            validate_shares(&shares)?;
+            let payload = Zeroizing::new(Field::Gf256.reconstruct(&shares)?);
+            // the lock guards only a path, so a panic in another request leaves nothing to repair
+            let ledger = ledger.lock().unwrap_or_else(PoisonError::into_inner);
+            ledger.spend(&capability, &set_fingerprint(&payload))?;
+        }
+        let secret = pipeline::combine_secret(&shares, &password)?;
+        Ok(json!({ "secret": pipeline::secret_text(&secret)?.as_str() }))
+    }
+
+    fn split(&self, request: SplitRequest) -> Result<Value> {
+        let password = Zeroizing::new(request.password);
+        let secret = SecretType::from_input(&Zeroizing::new(request.secret))?;
+        let encoding: ShareEncoding = request.encoding.as_deref().unwrap_or("hex").parse()?;
+        let padding: Padding = match &request.padding {
+            Some(padding) => padding.parse().map_err(SssError::InvalidArgument)?,
+            None => Padding::default(),
+        };
+        let rounds = request.kdf_rounds.unwrap_or(self.kdf_rounds);
+        let rng = &mut rand::rngs::OsRng;
+        let plaintext = pad(&serialize_secret(&secret), padding)?;
+        let payload = pipeline::seal_bound(&plaintext, &password, rounds, request.threshold, rng)?;
+        let shares = Field::Gf256.split_with_rng(&payload, request.threshold, request.total, rng)?;
+        Ok(json!(SplitResponse {
+            shares: shares.iter().map(|s| encoding.encode(s)).collect::<Result<_>>()?,
+            set_fingerprint: set_fingerprint(&payload),
+            kdf_rounds: rounds,
+        }))
+    }
+}
+
+fn bearer(authorization: Option<&str>) -> &str {
+    authorization.and_then(|h| h.strip_prefix("Bearer ")).map(str::trim).unwrap_or_default()
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|e| SssError::Parse(format!("malformed request: {}", e)))
+}
+
+fn decode(shares: &[String]) -> Result<Vec<Vec<u8>>> {
+    shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| input::parse_share(s).map_err(|e| SssError::Share(format!("share #{}: {}", i + 1, e))))
+        .collect()
+}
+
+fn inspect(request: InspectRequest) -> Result<Value> {
+    let field: Field = request.field.as_deref().unwrap_or("gf256").parse()?;
+    let infos = request
+        .shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| inspect_text(&format!("share #{}", i + 1), s, field))
+        .collect::<Result<Vec<ShareInfo>>>()?;
+    Ok(json!({ "shares": infos }))
+}
+
+/// Checks that the shares belong together and, if asked, that every quorum agrees and the
+/// password opens them. Nothing about the secret is returned.
+pub fn verify_shares(shares: &[Vec<u8>], threshold: Option<u8>, password: Option<&str>) -> Result<Verdict> {
+    validate_shares(shares)?;
+    let mut entered = Vec::new();
+    for (i, share) in shares.iter().enumerate() {
+        if let Some(reason) = share_conflict(&entered, share, None) {
+            return Err(SssError::Share(format!("share #{}: {}", i + 1, reason)));
+        }
+        entered.push((share.clone(), None));
+    }
+    let payload = Zeroizing::new(Field::Gf256.reconstruct(shares)?);
+
+    let consistent = match threshold {
+        Some(0) => return Err(SssError::Share("the threshold must be at least 1".into())),
+        Some(threshold) if shares.len() < threshold as usize => {
+            return Err(SssError::Share(format!(
+                "only {} of the required {} shares were provided",
+                shares.len(),
+                threshold
+            )));
+        }
+        Some(threshold) => {
+            let quorums = shamir::quorums(threshold, shares.len() as u8, 64, &mut rand::rngs::OsRng);
+            let agree = quorums.iter().all(|quorum| {
+                let picked: Vec<&Vec<u8>> = quorum.iter().map(|&i| &shares[i]).collect();
+                Field::Gf256.reconstruct(&picked).is_ok_and(|rebuilt| *rebuilt == **payload)
+            });
+            Some(agree)
+        }
+        None => None,
+    };
+    let decrypts = match password.map(|password| open_payload(&payload, password)) {
+        Some(Ok(_)) => Some(true),
+        Some(Err(SssError::Aead)) => Some(false),
+        Some(Err(e)) => return Err(e),
+        None => None,
+    };
+    Ok(Verdict { shares: shares.len(), set_fingerprint: set_fingerprint(&payload), consistent, decrypts })
+}
+
+fn verify(request: VerifyRequest) -> Result<Value> {
+    let password = request.password.map(Zeroizing::new);
+    let verdict = verify_shares(&decode(&request.shares)?, request.threshold, password.as_deref().map(String::as_str))?;
+    Ok(json!(verdict))
+}
+
+fn status(e: &SssError) -> u16 {
+    match e {
+        SssError::Unauthorized(_) => 401,
+        SssError::Share(_) | SssError::InsufficientShares(_) | SssError::Aead | SssError::WrongPassword => 422,
+        SssError::Input(_) | SssError::InvalidArgument(_) | SssError::Parse(_) | SssError::Checksum(_) => 400,
+        SssError::Kdf(_) | SssError::Policy(_) => 400,
+        _ => 500,
+    }
+}
+
+/// PEM certificate chain and private key for HTTPS.
+pub struct Tls {
+    pub certificate: Vec<u8>,
+    pub private_key: Vec<u8>,
+}
+
+/// Binds `listen`, over HTTPS when `tls` is given. Plain HTTP is only allowed on loopback.
+pub fn bind(listen: SocketAddr, tls: Option<Tls>) -> Result<Server> {
+    let server = match tls {
+        Some(tls) => Server::https(
+            listen,
+            SslConfig { certificate: tls.certificate, private_key: tls.private_key },
+        ),
+        None if listen.ip().is_loopback() => Server::http(listen),
+        None => {
+            return Err(SssError::Parse(format!(
+                "refusing to take shares and passwords over plain HTTP on {}; give --tls-cert and --tls-key",
+                listen
+            )))
+        }
+    };
+    server.map_err(|e| SssError::Io(std::io::Error::other(e)))
+}
+
+/// Answers requests on `workers` threads; each `Server::unblock` stops one of them.
+pub fn serve(server: &Server, api: &Api, workers: usize) {
+    std::thread::scope(|scope| {
+        for _ in 0..workers.max(1) {
+            scope.spawn(|| {
+                for request in server.incoming_requests() {
+                    respond(api, request);
+                }
+            });
+        }
+    });
+}
+
+fn respond(api: &Api, mut request: Request) {
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = request.url(),
+        status = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+    let method = match request.method() {
+        Method::Get => "GET",
+        Method::Post => "POST",
+        _ => "OTHER",
+    };
+    let path = request.url().split('?').next().unwrap_or_default().to_string();
+    let authorization = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.as_str().to_string());
+
+    let mut body = Zeroizing::new(String::new());
+    let read = request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body);
+    let (status, value) = match read {
+        Ok(len) if len as u64 > MAX_BODY => (413, json!({ "error": "request body over 1 MiB", "kind": "too-large" })),
+        Ok(_) => api.handle(method, &path, authorization.as_deref(), &body),
+        Err(e) => (400, json!({ "error": format!("could not read the body: {}", e), "kind": "io" })),
+    };
+    span.record("status", status);
+    let response = Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("static header"));
+    let _ = request.respond(response);
+}
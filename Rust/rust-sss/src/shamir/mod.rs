@@ -0,0 +1,326 @@
+//! Shamir secret sharing over GF(2^8), byte by byte.
+//!
+//! The field uses the reduction polynomial x^8 + x^4 + x^3 + x^2 + 1 (0x11d) and shares are laid
+//! out as `[x, y_0, y_1, ...]` with x = 1..=n, so sets made by sss_rs 0.12 still combine.
+//! Arithmetic never branches on or indexes by secret data: multiplication is a fixed
+//! shift-and-add loop and inversion is a fixed exponentiation, so there are no log/exp tables to
+//! leak through the cache.
+//!
+//! [`secp256k1`] shares a 32-byte scalar over that curve's group order instead, [`pedersen`]
+//! shares any payload over the same group with commitments custodians can check their shares
+//! against, and [`vault`] uses the AES polynomial the way HashiCorp Vault splits its unseal keys;
+//! [`Field`] picks between them. [`ssss`] reproduces the classic `ssss` tool's scheme for interop.
+
+#[cfg(feature = "std")]
+pub mod pedersen;
+#[cfg(feature = "std")]
+pub mod secp256k1;
+#[cfg(feature = "std")]
+pub mod ssss;
+#[cfg(feature = "std")]
+pub mod vault;
+
+use alloc::{format, vec, vec::Vec};
+use rand::RngCore;
+#[cfg(feature = "std")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::{fmt, str::FromStr};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+#[cfg(feature = "std")]
+use crate::progress::{self, Event};
+
+pub(crate) const REDUCTION: u8 = 0x1d;
+
+/// Which field the sharing polynomial is evaluated in.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Field {
+    /// Byte-wise GF(2^8): any secret length, one share byte per secret byte.
+    #[default]
+    Gf256,
+    /// The secp256k1 scalar field: exactly one 32-byte scalar below the group order.
+    Secp256k1,
+    /// Byte-wise GF(2^8) with the AES polynomial, as Vault uses for unseal keys.
+    Vault,
+    /// The secp256k1 scalar field in 31-byte chunks, with Pedersen commitments to every share.
+    Pedersen,
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Field {
+    type Err = SssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "gf256" => Ok(Field::Gf256),
+            "secp256k1" => Ok(Field::Secp256k1),
+            "vault" => Ok(Field::Vault),
+            "pedersen" => Ok(Field::Pedersen),
+            _ => Err(SssError::InvalidArgument(format!(
+                "unknown field '{}': use gf256, secp256k1, vault or pedersen",
+                s
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Field::Gf256 => "gf256",
+            Field::Secp256k1 => "secp256k1",
+            Field::Vault => "vault",
+            Field::Pedersen => "pedersen",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Field {
+    pub fn split(self, secret: &[u8], threshold: u8, total: u8) -> Result<Vec<Vec<u8>>> {
+        self.split_with_rng(secret, threshold, total, &mut rand::rngs::OsRng)
+    }
+
+    pub fn split_with_rng<R: RngCore + ?Sized>(
+        self,
+        secret: &[u8],
+        threshold: u8,
+        total: u8,
+        rng: &mut R,
+    ) -> Result<Vec<Vec<u8>>> {
+        let shares = match self {
+            Field::Gf256 => split_with_rng(secret, threshold, total, rng),
+            Field::Secp256k1 => secp256k1::split_with_rng(secret, threshold, total, rng),
+            Field::Vault => vault::split_with_rng(secret, threshold, total, rng),
+            Field::Pedersen => pedersen::split_with_rng(secret, threshold, total, rng),
+        }?;
+        for share in &shares {
+            progress::emit(Event::ShareGenerated { index: share[0], total });
+        }
+        Ok(shares)
+    }
+
+    pub fn reconstruct<S: AsRef<[u8]>>(self, shares: &[S]) -> Result<Vec<u8>> {
+        match self {
+            Field::Gf256 => reconstruct(shares),
+            Field::Secp256k1 => secp256k1::reconstruct(shares),
+            Field::Vault => vault::reconstruct(shares),
+            Field::Pedersen => pedersen::reconstruct(shares),
+        }
+    }
+
+    /// Rebuilds the secret from up to `limit` quorums of `shares` (all of them when there are
+    /// few enough) and checks each against the SHA-256 of `secret`. Returns how many were
+    /// checked; a quorum that rebuilds anything else is an error naming its shares.
+    pub fn rehearse(self, shares: &[Vec<u8>], threshold: u8, secret: &[u8], limit: usize) -> Result<usize> {
+        let total = u8::try_from(shares.len()).map_err(|_| SssError::Share("too many shares".into()))?;
+        let expected = Sha256::digest(secret);
+        let quorums = quorums(threshold, total, limit, &mut rand::rngs::OsRng);
+        for quorum in &quorums {
+            let picked: Vec<&Vec<u8>> = quorum.iter().map(|&i| &shares[i]).collect();
+            let rebuilt = Zeroizing::new(self.reconstruct(&picked)?);
+            if Sha256::digest(rebuilt.as_slice()) != expected {
+                let names: Vec<String> = picked.iter().map(|s| format!("#{}", s[0])).collect();
+                return Err(SssError::Share(format!(
+                    "shares {} do not rebuild the secret; do not rely on this share set",
+                    names.join(", ")
+                )));
+            }
+        }
+        progress::emit(Event::VerificationPassed { quorums: quorums.len() });
+        Ok(quorums.len())
+    }
+}
+
+/// Number of `threshold`-share quorums among `total` shares, saturating at `u64::MAX`.
+pub fn quorum_count(threshold: u8, total: u8) -> u64 {
+    let k = threshold.min(total - threshold.min(total)) as u64;
+    (0..k).fold(1u64, |acc, i| {
+        let next = acc as u128 * (total as u128 - i as u128) / (i as u128 + 1);
+        u64::try_from(next).unwrap_or(u64::MAX)
+    })
+}
+
+/// Every quorum of `threshold` share positions out of `total` when there are at most `limit`,
+/// otherwise `limit` distinct ones picked at random.
+pub fn quorums<R: RngCore + ?Sized>(threshold: u8, total: u8, limit: usize, rng: &mut R) -> Vec<Vec<usize>> {
+    let (k, n) = (threshold as usize, total as usize);
+    if k == 0 || k > n {
+        return Vec::new();
+    }
+    if quorum_count(threshold, total) <= limit as u64 {
+        let mut all = Vec::new();
+        let mut current: Vec<usize> = (0..k).collect();
+        loop {
+            all.push(current.clone());
+            let Some(i) = (0..k).rev().find(|&i| current[i] < n - k + i) else {
+                return all;
+            };
+            current[i] += 1;
+            for j in i + 1..k {
+                current[j] = current[j - 1] + 1;
+            }
+        }
+    }
+    let mut picked: Vec<Vec<usize>> = Vec::with_capacity(limit);
+    while picked.len() < limit {
+        let mut positions: Vec<usize> = (0..n).collect();
+        for i in 0..k {
+            let j = i + (rng.next_u32() as usize) % (n - i);
+            positions.swap(i, j);
+        }
+        let mut quorum = positions[..k].to_vec();
+        quorum.sort_unstable();
+        if !picked.contains(&quorum) {
+            picked.push(quorum);
+        }
+    }
+    picked
+}
+
+/// Multiplication modulo x^8 + `reduction`.
+pub(crate) fn mul(reduction: u8, mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (reduction & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// a^254, which is a^-1 for every non-zero a (and 0 for 0).
+pub(crate) fn inv(reduction: u8, a: u8) -> u8 {
+    let mul = |a, b| mul(reduction, a, b);
+    let a2 = mul(a, a);
+    let a3 = mul(a2, a);
+    let a6 = mul(a3, a3);
+    let a12 = mul(a6, a6);
+    let a15 = mul(a12, a3);
+    let a30 = mul(a15, a15);
+    let a60 = mul(a30, a30);
+    let a63 = mul(a60, a3);
+    let a126 = mul(a63, a63);
+    let a127 = mul(a126, a);
+    mul(a127, a127)
+}
+
+/// Splits `secret` into `total` shares, any `threshold` of which reconstruct it.
+#[cfg(feature = "std")]
+pub fn split(secret: &[u8], threshold: u8, total: u8) -> Result<Vec<Vec<u8>>> {
+    split_with_rng(secret, threshold, total, &mut rand::rngs::OsRng)
+}
+
+/// `split` with a caller-chosen source of polynomial coefficients, for reproducible vectors.
+pub fn split_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    split_over(REDUCTION, secret, threshold, total, rng)
+}
+
+fn split_over<R: RngCore + ?Sized>(
+    reduction: u8,
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    check_parameters(threshold, total)?;
+
+    let mul = |a, b| mul(reduction, a, b);
+    let degree = threshold as usize - 1;
+    let mut coefficients = Zeroizing::new(vec![0u8; degree]);
+    let mut shares: Vec<Vec<u8>> = (1..=total)
+        .map(|x| {
+            let mut share = Vec::with_capacity(secret.len() + 1);
+            share.push(x);
+            share
+        })
+        .collect();
+    for &byte in secret {
+        rng.fill_bytes(&mut coefficients);
+        for share in shares.iter_mut() {
+            let x = share[0];
+            // Horner's rule, highest coefficient first, ending with the secret byte itself
+            let y = coefficients.iter().rev().fold(0u8, |acc, &c| mul(acc, x) ^ c);
+            share.push(mul(y, x) ^ byte);
+        }
+    }
+    Ok(shares)
+}
+
+/// Interpolates the shares at x = 0. Any `threshold` shares of one set give back the secret;
+/// fewer, or shares of different sets, give unrelated bytes rather than an error.
+pub fn reconstruct<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>> {
+    reconstruct_over(REDUCTION, shares)
+}
+
+fn reconstruct_over<S: AsRef<[u8]>>(reduction: u8, shares: &[S]) -> Result<Vec<u8>> {
+    let xs = check_shares(shares)?;
+    let mul = |a, b| mul(reduction, a, b);
+    let len = shares[0].as_ref().len();
+
+    // Lagrange basis polynomials evaluated at 0; they only depend on the share indices
+    let basis: Vec<u8> = xs
+        .iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            xs.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(1u8, |acc, (_, &xj)| mul(acc, mul(xj, inv(reduction, xi ^ xj))))
+        })
+        .collect();
+
+    Ok((1..len)
+        .map(|k| {
+            shares
+                .iter()
+                .zip(&basis)
+                .fold(0u8, |acc, (share, &l)| acc ^ mul(l, share.as_ref()[k]))
+        })
+        .collect())
+}
+
+/// Checks that `threshold` of `total` shares is a quorum that can be dealt.
+pub fn check_parameters(threshold: u8, total: u8) -> Result<()> {
+    if threshold == 0 {
+        return Err(SssError::InvalidArgument("the threshold must be at least 1".into()));
+    }
+    if threshold > total {
+        return Err(SssError::InvalidArgument(format!(
+            "cannot require {} shares when only {} are created",
+            threshold, total
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the shares have one length and distinct, non-zero indices, and returns the indices.
+fn check_shares<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>> {
+    let first = shares
+        .first()
+        .ok_or_else(|| SssError::Share("no shares to combine".into()))?
+        .as_ref();
+    let len = first.len();
+    let mut xs = Vec::with_capacity(shares.len());
+    for share in shares {
+        let share = share.as_ref();
+        if share.len() != len || len < 2 {
+            return Err(SssError::Share("shares must all be the same, non-zero length".into()));
+        }
+        if share[0] == 0 || xs.contains(&share[0]) {
+            return Err(SssError::Share(format!("share index {} is invalid or repeated", share[0])));
+        }
+        xs.push(share[0]);
+    }
+    Ok(xs)
+}
@@ -0,0 +1,239 @@
+//! Pedersen verifiable secret sharing over secp256k1. The payload is cut into 31-byte chunks,
+//! each read as a scalar and shared with its own polynomial f, alongside a random blinding
+//! polynomial g. A share is `[x, f_1(x) .. f_k(x), g_1(x) .. g_k(x)]`, 32 bytes per value.
+//!
+//! The published [`Commitments`] are C_j = a_j·G + b_j·H for the coefficients a_j of f and b_j
+//! of g, where H is a second generator nobody knows the discrete logarithm of. A custodian checks
+//! f(x)·G + g(x)·H = Σ C_j·x^j for their share. Because every C_j is blinded by a uniformly
+//! random b_j·H, the commitments say nothing about the secret even to an adversary with
+//! unlimited computing power, unlike Feldman commitments a_j·G.
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::sec1::FromEncodedPoint;
+use k256::elliptic_curve::{ff::PrimeField, Field};
+use k256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::secp256k1::{lagrange_at_zero, SCALAR_LEN};
+use super::{check_parameters, check_shares};
+use crate::error::{Result, SssError};
+
+/// Payload bytes carried by one scalar; the leading byte of each scalar stays zero, so every
+/// chunk is below the group order.
+pub const CHUNK_LEN: usize = SCALAR_LEN - 1;
+const POINT_LEN: usize = 33;
+const GENERATOR_DOMAIN: &[u8] = b"sss pedersen generator H v1";
+
+type Scalars = Zeroizing<Vec<Scalar>>;
+
+/// What the dealer publishes for custodians to check their shares against.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Commitments {
+    pub threshold: u8,
+    /// For each chunk of the payload, the `threshold` compressed points C_0 .. C_{t-1} in hex.
+    pub chunks: Vec<String>,
+}
+
+/// The second generator: the first SHA-256 of the domain and a counter that is the x coordinate
+/// of a curve point, so its discrete logarithm to G is unknown to everyone.
+fn generator_h() -> ProjectivePoint {
+    (0u32..)
+        .find_map(|counter| {
+            let x = Sha256::new().chain_update(GENERATOR_DOMAIN).chain_update(counter.to_be_bytes()).finalize();
+            let mut compressed = [0u8; POINT_LEN];
+            compressed[0] = 0x02;
+            compressed[1..].copy_from_slice(&x);
+            let encoded = EncodedPoint::from_bytes(compressed).ok()?;
+            Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded)).map(ProjectivePoint::from)
+        })
+        .expect("about half of all x coordinates are on the curve")
+}
+
+/// The payload, prefixed with its length, as chunk scalars.
+fn encode(secret: &[u8]) -> Result<Scalars> {
+    let len = u32::try_from(secret.len()).map_err(|_| SssError::Share("the secret is too long".into()))?;
+    let mut bytes = Zeroizing::new(len.to_be_bytes().to_vec());
+    bytes.extend_from_slice(secret);
+    Ok(Zeroizing::new(
+        bytes
+            .chunks(CHUNK_LEN)
+            .map(|chunk| {
+                let mut repr = FieldBytes::default();
+                repr[1..1 + chunk.len()].copy_from_slice(chunk);
+                Scalar::from_repr(repr).expect("a leading zero byte keeps the value below the order")
+            })
+            .collect(),
+    ))
+}
+
+fn decode(chunks: &[Scalar]) -> Result<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(chunks.len() * CHUNK_LEN));
+    for chunk in chunks {
+        let repr = chunk.to_repr();
+        if repr[0] != 0 {
+            return Err(SssError::Share("the shares do not rebuild a Pedersen-shared payload".into()));
+        }
+        bytes.extend_from_slice(&repr[1..]);
+    }
+    let len = bytes
+        .get(..4)
+        .map(|len| u32::from_be_bytes(len.try_into().expect("four bytes")) as usize)
+        .filter(|&len| len <= bytes.len() - 4)
+        .ok_or_else(|| SssError::Share("the shares do not rebuild a Pedersen-shared payload".into()))?;
+    Ok(bytes[4..4 + len].to_vec())
+}
+
+/// Splits a share into its index, value scalars and blinding scalars.
+fn parse(share: &[u8]) -> Result<(u8, Scalars, Scalars)> {
+    let body = share.get(1..).filter(|body| !body.is_empty() && body.len() % (2 * SCALAR_LEN) == 0).ok_or_else(|| {
+        SssError::Share("a Pedersen share is an index and pairs of 32-byte values".into())
+    })?;
+    let scalars = body
+        .chunks(SCALAR_LEN)
+        .map(super::secp256k1::scalar)
+        .collect::<Result<Vec<_>>>()?;
+    let mut values = Zeroizing::new(scalars);
+    let half = values.len() / 2;
+    let blinding = Zeroizing::new(values.split_off(half));
+    Ok((share[0], values, blinding))
+}
+
+fn evaluate(coefficients: &[Scalar], x: Scalar) -> Scalar {
+    coefficients.iter().rev().fold(Scalar::ZERO, |acc, c| acc * x + c)
+}
+
+/// Splits `secret`, of any length, into `total` shares, any `threshold` of which rebuild it.
+pub fn split_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    check_parameters(threshold, total)?;
+    let chunks = encode(secret)?;
+    let mut shares: Vec<Vec<u8>> = (1..=total)
+        .map(|x| {
+            let mut share = Vec::with_capacity(1 + 2 * SCALAR_LEN * chunks.len());
+            share.push(x);
+            share
+        })
+        .collect();
+    let mut blinding = Zeroizing::new(Vec::with_capacity(total as usize * chunks.len()));
+    for chunk in chunks.iter() {
+        let mut f = Zeroizing::new(vec![*chunk]);
+        f.extend((1..threshold).map(|_| Scalar::random(&mut *rng)));
+        let g = Zeroizing::new((0..threshold).map(|_| Scalar::random(&mut *rng)).collect::<Vec<_>>());
+        for share in shares.iter_mut() {
+            let x = Scalar::from(u64::from(share[0]));
+            share.extend_from_slice(&evaluate(&f, x).to_bytes());
+            blinding.push(evaluate(&g, x));
+        }
+    }
+    // values first, then blinding, per share
+    for (i, share) in shares.iter_mut().enumerate() {
+        for t in blinding.iter().skip(i).step_by(total as usize) {
+            share.extend_from_slice(&t.to_bytes());
+        }
+    }
+    Ok(shares)
+}
+
+/// Rebuilds the payload from `threshold` or more shares; the blinding values are not needed.
+pub fn reconstruct<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>> {
+    let xs = check_shares(shares)?;
+    let parsed = shares.iter().map(|share| parse(share.as_ref())).collect::<Result<Vec<_>>>()?;
+    let basis = lagrange_at_zero(&xs);
+    let chunks = Zeroizing::new(
+        (0..parsed[0].1.len())
+            .map(|c| basis.iter().zip(&parsed).map(|(l, (_, values, _))| *l * values[c]).sum())
+            .collect::<Vec<Scalar>>(),
+    );
+    decode(&chunks)
+}
+
+/// Coefficients of the Lagrange basis polynomials through `xs`, lowest degree first:
+/// `basis[i][j]` is the coefficient of X^j in L_i.
+fn lagrange_coefficients(xs: &[u8]) -> Vec<Vec<Scalar>> {
+    let points: Vec<Scalar> = xs.iter().map(|&x| Scalar::from(u64::from(x))).collect();
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let mut poly = vec![Scalar::ONE];
+            let mut denominator = Scalar::ONE;
+            for (m, xm) in points.iter().enumerate().filter(|&(m, _)| m != i) {
+                // multiply by (X - x_m)
+                let mut next = vec![Scalar::ZERO; poly.len() + 1];
+                for (j, c) in poly.iter().enumerate() {
+                    next[j + 1] += c;
+                    next[j] -= *c * xm;
+                }
+                poly = next;
+                denominator *= *xi - points[m];
+            }
+            let inverse = denominator.invert().expect("indices are distinct");
+            poly.iter().map(|c| *c * inverse).collect()
+        })
+        .collect()
+}
+
+/// Works out the commitments from the first `threshold` shares, which fix both polynomials.
+/// Only the dealer holds that many shares at once; the result is what custodians are given.
+pub fn commit<S: AsRef<[u8]>>(shares: &[S], threshold: u8) -> Result<Commitments> {
+    let quorum = shares
+        .get(..threshold as usize)
+        .filter(|_| threshold > 0)
+        .ok_or_else(|| SssError::InsufficientShares(format!("committing needs {} shares", threshold)))?;
+    let xs = check_shares(quorum)?;
+    let parsed = quorum.iter().map(|share| parse(share.as_ref())).collect::<Result<Vec<_>>>()?;
+    let basis = lagrange_coefficients(&xs);
+    let h = generator_h();
+    let chunks = (0..parsed[0].1.len())
+        .map(|c| {
+            let mut points = Vec::with_capacity(threshold as usize * POINT_LEN);
+            for j in 0..threshold as usize {
+                let (a, b) = basis.iter().zip(&parsed).fold((Scalar::ZERO, Scalar::ZERO), |(a, b), (l, (_, f, g))| {
+                    (a + l[j] * f[c], b + l[j] * g[c])
+                });
+                points.extend_from_slice(&(ProjectivePoint::GENERATOR * a + h * b).to_affine().to_bytes());
+            }
+            hex::encode(points)
+        })
+        .collect();
+    Ok(Commitments { threshold, chunks })
+}
+
+/// Checks one share against the published commitments.
+pub fn verify_share(share: &[u8], commitments: &Commitments) -> Result<()> {
+    let (x, values, blinding) = parse(share)?;
+    if values.len() != commitments.chunks.len() {
+        return Err(SssError::Share(format!(
+            "share #{} covers {} chunks but the commitments cover {}",
+            x,
+            values.len(),
+            commitments.chunks.len()
+        )));
+    }
+    let h = generator_h();
+    let x_scalar = Scalar::from(u64::from(x));
+    for (c, chunk) in commitments.chunks.iter().enumerate() {
+        let bytes = hex::decode(chunk)
+            .ok()
+            .filter(|b| b.len() == commitments.threshold as usize * POINT_LEN)
+            .ok_or_else(|| SssError::Parse(format!("commitment {} is not {} hex points", c + 1, commitments.threshold)))?;
+        let expected = bytes.chunks(POINT_LEN).rev().try_fold(ProjectivePoint::IDENTITY, |acc, point| {
+            let point = EncodedPoint::from_bytes(point)
+                .ok()
+                .and_then(|p| Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&p)))
+                .ok_or_else(|| SssError::Parse(format!("commitment {} holds a point not on the curve", c + 1)))?;
+            Ok::<_, SssError>(acc * x_scalar + ProjectivePoint::from(point))
+        })?;
+        if ProjectivePoint::GENERATOR * values[c] + h * blinding[c] != expected {
+            return Err(SssError::Share(format!("share #{} does not match the published commitments", x)));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! Shamir over the scalar field of secp256k1. The secret is a single 32-byte big-endian scalar
+//! below the group order, and each share is `[x, y (32 bytes)]` with y = f(x) mod n, which is
+//! the form threshold signing and decryption protocols expect key shares in.
+
+use k256::elliptic_curve::{ff::PrimeField, Field};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{FieldBytes, ProjectivePoint, Scalar};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use super::{check_parameters, check_shares};
+use crate::error::{Result, SssError};
+
+pub const SCALAR_LEN: usize = 32;
+pub const SHARE_LEN: usize = 1 + SCALAR_LEN;
+
+pub(crate) fn scalar(bytes: &[u8]) -> Result<Scalar> {
+    let bytes: [u8; SCALAR_LEN] = bytes
+        .try_into()
+        .map_err(|_| SssError::Share(format!("a secp256k1 secret must be exactly {} bytes", SCALAR_LEN)))?;
+    Option::from(Scalar::from_repr(FieldBytes::from(bytes)))
+        .ok_or_else(|| SssError::Share("value is not below the secp256k1 group order".into()))
+}
+
+/// Splits a 32-byte scalar into `total` shares, any `threshold` of which reconstruct it.
+pub fn split(secret: &[u8], threshold: u8, total: u8) -> Result<Vec<Vec<u8>>> {
+    split_with_rng(secret, threshold, total, &mut rand::rngs::OsRng)
+}
+
+pub fn split_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    check_parameters(threshold, total)?;
+    let secret = Zeroizing::new(scalar(secret)?);
+    let coefficients: Zeroizing<Vec<Scalar>> =
+        Zeroizing::new((1..threshold).map(|_| Scalar::random(&mut *rng)).collect());
+    Ok((1..=total)
+        .map(|x| {
+            let x_scalar = Scalar::from(x as u64);
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |acc, c| acc * x_scalar + c)
+                * x_scalar
+                + *secret;
+            let mut share = Vec::with_capacity(SHARE_LEN);
+            share.push(x);
+            share.extend_from_slice(&y.to_bytes());
+            share
+        })
+        .collect())
+}
+
+/// Lagrange interpolation at x = 0 modulo the group order.
+pub fn reconstruct<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>> {
+    let xs = check_shares(shares)?;
+    let ys = shares
+        .iter()
+        .map(|share| {
+            let share = share.as_ref();
+            if share.len() != SHARE_LEN {
+                return Err(SssError::Share(format!("secp256k1 shares are {} bytes", SHARE_LEN)));
+            }
+            Ok(Zeroizing::new(scalar(&share[1..])?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut secret = Zeroizing::new(Scalar::ZERO);
+    for (basis, y) in lagrange_at_zero(&xs).into_iter().zip(&ys) {
+        *secret += basis * **y;
+    }
+    Ok(secret.to_bytes().to_vec())
+}
+
+/// Lagrange coefficients for interpolating at x = 0 from the given distinct, non-zero indices.
+pub(crate) fn lagrange_at_zero(xs: &[u8]) -> Vec<Scalar> {
+    xs.iter()
+        .map(|&i| {
+            let xi = Scalar::from(i as u64);
+            xs.iter().filter(|&&j| j != i).fold(Scalar::ONE, |basis, &j| {
+                let xj = Scalar::from(j as u64);
+                // indices are distinct, so xj - xi is invertible
+                basis * xj * (xj - xi).invert().unwrap()
+            })
+        })
+        .collect()
+}
+
+/// Compressed SEC1 public key for a 32-byte private scalar.
+pub fn public_key(secret: &[u8]) -> Result<Vec<u8>> {
+    let secret = Zeroizing::new(scalar(secret)?);
+    let point = (ProjectivePoint::GENERATOR * *secret).to_affine();
+    Ok(point.to_encoded_point(true).as_bytes().to_vec())
+}
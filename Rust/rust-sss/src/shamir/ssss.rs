@@ -0,0 +1,277 @@
+//! Compatibility with B. Poettering's `ssss` (`ssss-split` / `ssss-combine`). The whole secret
+//! is one element of GF(2^n), where n is eight times its length (at most 1024 bits), reduced by
+//! a fixed low-weight irreducible polynomial for each size. Before splitting, secrets of 64 bits
+//! or more go through ssss's "diffusion layer", an XTEA-based permutation with a zero key, so
+//! it has to be undone after combining too.
+//!
+//! ssss evaluates x^t + c_(t-1)·x^(t-1) + ... + c_0 rather than a degree t-1 polynomial, so
+//! combining takes exactly `threshold` shares. Shares print as `index-hex`, with an optional
+//! `token-` prefix; in memory they use the crate's `[x, y...]` layout with y big-endian.
+
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+pub const MAX_SECRET_LEN: usize = 128;
+
+/// The middle exponents (a, b, c) of x^n + x^a + x^b + x^c + 1 for n = 8, 16, ..., 1024.
+const IRREDUCIBLE: [[u8; 3]; 128] = [
+    [4, 3, 1], [5, 3, 1], [4, 3, 1], [7, 3, 2], [5, 4, 3], [5, 3, 2], [7, 4, 2], [4, 3, 1],
+    [10, 9, 3], [9, 4, 2], [7, 6, 2], [10, 9, 6], [4, 3, 1], [5, 4, 3], [4, 3, 1], [7, 2, 1],
+    [5, 3, 2], [7, 4, 2], [6, 3, 2], [5, 3, 2], [15, 3, 2], [11, 3, 2], [9, 8, 7], [7, 2, 1],
+    [5, 3, 2], [9, 3, 1], [7, 3, 1], [9, 8, 3], [9, 4, 2], [8, 5, 3], [15, 14, 10], [10, 5, 2],
+    [9, 6, 2], [9, 3, 2], [9, 5, 2], [11, 10, 1], [7, 3, 2], [11, 2, 1], [9, 7, 4], [4, 3, 1],
+    [8, 3, 1], [7, 4, 1], [7, 2, 1], [13, 11, 6], [5, 3, 2], [7, 3, 2], [8, 7, 5], [12, 3, 2],
+    [13, 10, 6], [5, 3, 2], [5, 3, 2], [9, 5, 2], [9, 7, 2], [13, 4, 3], [4, 3, 1], [11, 6, 4],
+    [18, 9, 6], [19, 18, 13], [11, 3, 2], [15, 9, 6], [4, 3, 1], [16, 5, 2], [15, 14, 6], [8, 5, 2],
+    [15, 11, 2], [11, 6, 2], [7, 5, 3], [8, 3, 1], [19, 16, 9], [11, 9, 6], [15, 7, 6], [13, 4, 3],
+    [14, 13, 3], [13, 6, 3], [9, 5, 2], [19, 13, 6], [19, 10, 3], [11, 6, 5], [9, 2, 1], [14, 3, 2],
+    [13, 3, 1], [7, 5, 4], [11, 9, 8], [11, 6, 5], [23, 16, 9], [19, 14, 6], [23, 10, 2], [8, 3, 2],
+    [5, 4, 3], [9, 6, 4], [4, 3, 2], [13, 8, 6], [13, 11, 1], [13, 10, 3], [11, 6, 5], [19, 17, 4],
+    [15, 14, 7], [13, 9, 6], [9, 7, 3], [9, 7, 1], [14, 3, 2], [11, 8, 2], [11, 6, 4], [13, 5, 2],
+    [11, 5, 1], [11, 4, 1], [19, 10, 3], [21, 10, 6], [13, 3, 1], [15, 7, 5], [19, 18, 10], [7, 5, 3],
+    [12, 7, 2], [7, 5, 1], [14, 9, 6], [10, 3, 2], [15, 13, 12], [12, 11, 9], [16, 9, 7], [12, 9, 3],
+    [9, 5, 2], [17, 10, 6], [24, 9, 3], [17, 15, 13], [5, 4, 3], [19, 17, 8], [15, 6, 3], [19, 6, 1],
+];
+
+type Element = Zeroizing<Vec<u64>>;
+
+struct Gf {
+    degree: usize,
+    /// x^a + x^b + x^c + 1, all below x^64
+    reduction: u64,
+}
+
+impl Gf {
+    fn new(bytes: usize) -> Result<Gf> {
+        if bytes == 0 || bytes > MAX_SECRET_LEN {
+            return Err(SssError::Share(format!("ssss secrets are 1 to {} bytes", MAX_SECRET_LEN)));
+        }
+        let [a, b, c] = IRREDUCIBLE[bytes - 1];
+        Ok(Gf { degree: bytes * 8, reduction: 1 << a | 1 << b | 1 << c | 1 })
+    }
+
+    fn zero(&self) -> Element {
+        Zeroizing::new(vec![0; self.degree.div_ceil(64)])
+    }
+
+    fn small(&self, x: u8) -> Element {
+        let mut e = self.zero();
+        e[0] = x as u64;
+        e
+    }
+
+    /// Multiplies by x in place.
+    fn shift(&self, e: &mut [u64]) {
+        let top = self.degree - 1;
+        let carry = (e[top / 64] >> (top % 64)) & 1;
+        for i in (1..e.len()).rev() {
+            e[i] = e[i] << 1 | e[i - 1] >> 63;
+        }
+        e[0] <<= 1;
+        if !self.degree.is_multiple_of(64) {
+            e[top / 64] &= (1 << (self.degree % 64)) - 1;
+        }
+        e[0] ^= self.reduction & 0u64.wrapping_sub(carry);
+    }
+
+    /// Shift-and-add without branching on either operand.
+    fn mul(&self, a: &[u64], b: &[u64]) -> Element {
+        let mut product = self.zero();
+        let mut term = Zeroizing::new(a.to_vec());
+        for i in 0..self.degree {
+            let mask = 0u64.wrapping_sub((b[i / 64] >> (i % 64)) & 1);
+            product.iter_mut().zip(term.iter()).for_each(|(p, t)| *p ^= t & mask);
+            self.shift(&mut term);
+        }
+        product
+    }
+
+    /// a^(2^n - 2), the product of a^(2^k) for k = 1..n.
+    fn inv(&self, a: &[u64]) -> Element {
+        let mut result = self.small(1);
+        let mut power = Zeroizing::new(a.to_vec());
+        for _ in 1..self.degree {
+            power = self.mul(&power, &power);
+            result = self.mul(&result, &power);
+        }
+        result
+    }
+
+    fn element(&self, bytes: &[u8]) -> Element {
+        let mut e = self.zero();
+        for (j, &byte) in bytes.iter().rev().enumerate() {
+            e[j / 8] |= (byte as u64) << (8 * (j % 8));
+        }
+        e
+    }
+
+    fn bytes(&self, e: &[u64]) -> Vec<u8> {
+        let len = self.degree / 8;
+        (0..len).rev().map(|j| (e[j / 8] >> (8 * (j % 8))) as u8).collect()
+    }
+}
+
+fn xor(a: &mut [u64], b: &[u64]) {
+    a.iter_mut().zip(b).for_each(|(a, b)| *a ^= b);
+}
+
+const DELTA: u32 = 0x9e37_79b9;
+
+fn encipher(v: &mut [u32; 2]) {
+    let mut sum = 0u32;
+    for _ in 0..32 {
+        v[0] = v[0].wrapping_add(((v[1] << 4 ^ v[1] >> 5).wrapping_add(v[1])) ^ sum);
+        sum = sum.wrapping_add(DELTA);
+        v[1] = v[1].wrapping_add(((v[0] << 4 ^ v[0] >> 5).wrapping_add(v[0])) ^ sum);
+    }
+}
+
+fn decipher(v: &mut [u32; 2]) {
+    let mut sum = DELTA.wrapping_mul(32);
+    for _ in 0..32 {
+        v[1] = v[1].wrapping_sub(((v[0] << 4 ^ v[0] >> 5).wrapping_add(v[0])) ^ sum);
+        sum = sum.wrapping_sub(DELTA);
+        v[0] = v[0].wrapping_sub(((v[1] << 4 ^ v[1] >> 5).wrapping_add(v[1])) ^ sum);
+    }
+}
+
+fn process_slice(data: &mut [u8], at: usize, block: fn(&mut [u32; 2])) {
+    let len = data.len();
+    let index = |k: usize| (at + k) % len;
+    let mut v = [0u32; 2];
+    for (i, word) in v.iter_mut().enumerate() {
+        *word = (0..4).fold(0, |acc, k| acc << 8 | data[index(4 * i + k)] as u32);
+    }
+    block(&mut v);
+    for (i, word) in v.iter().enumerate() {
+        for k in 0..4 {
+            data[index(4 * i + k)] = (word >> (24 - 8 * k)) as u8;
+        }
+    }
+}
+
+/// ssss's diffusion layer over a big-endian secret, or its inverse.
+fn diffuse(bytes: &mut [u8], decode: bool) {
+    let n = bytes.len();
+    // gmp hands ssss the value as 16-bit big-endian words, least significant word first; with
+    // an odd byte count the top word's only byte is moved down so the n bytes are contiguous
+    let position = |k: usize| if n % 2 == 1 && k == n - 1 { 0 } else { n - 1 - (k ^ 1) };
+    let mut v: Zeroizing<Vec<u8>> = Zeroizing::new((0..n).map(|k| bytes[position(k)]).collect());
+    let rounds = (0..40 * n).step_by(2);
+    if decode {
+        rounds.rev().for_each(|i| process_slice(&mut v, i, decipher));
+    } else {
+        rounds.for_each(|i| process_slice(&mut v, i, encipher));
+    }
+    for (k, &byte) in v.iter().enumerate() {
+        bytes[position(k)] = byte;
+    }
+}
+
+pub fn split(secret: &[u8], threshold: u8, total: u8, diffusion: bool) -> Result<Vec<Vec<u8>>> {
+    split_with_rng(secret, threshold, total, diffusion, &mut rand::rngs::OsRng)
+}
+
+pub fn split_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    diffusion: bool,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    super::check_parameters(threshold, total)?;
+    if threshold < 2 {
+        return Err(SssError::Share("ssss needs a threshold of at least 2".into()));
+    }
+    let gf = Gf::new(secret.len())?;
+
+    let mut constant = Zeroizing::new(secret.to_vec());
+    if diffusion && gf.degree >= 64 {
+        diffuse(&mut constant, false);
+    }
+    let mut coefficients = vec![gf.element(&constant)];
+    let mut random = Zeroizing::new(vec![0u8; secret.len()]);
+    for _ in 1..threshold {
+        rng.fill_bytes(&mut random);
+        coefficients.push(gf.element(&random));
+    }
+
+    Ok((1..=total)
+        .map(|x| {
+            let x_element = gf.small(x);
+            let mut y = Zeroizing::new(x_element.to_vec());
+            for coefficient in coefficients[1..].iter().rev() {
+                xor(&mut y, coefficient);
+                y = gf.mul(&y, &x_element);
+            }
+            xor(&mut y, &coefficients[0]);
+            let mut share = vec![x];
+            share.extend(gf.bytes(&y));
+            share
+        })
+        .collect())
+}
+
+/// Combines exactly `threshold` shares; with any other number the result is unrelated bytes.
+pub fn reconstruct<S: AsRef<[u8]>>(shares: &[S], diffusion: bool) -> Result<Vec<u8>> {
+    let xs = super::check_shares(shares)?;
+    let gf = Gf::new(shares[0].as_ref().len() - 1)?;
+
+    let mut secret = gf.zero();
+    for (i, share) in shares.iter().enumerate() {
+        let x = gf.small(xs[i]);
+        // remove the monic x^t term, then interpolate as usual
+        let mut y = gf.element(&share.as_ref()[1..]);
+        let mut power = gf.small(1);
+        for _ in 0..shares.len() {
+            power = gf.mul(&power, &x);
+        }
+        xor(&mut y, &power);
+
+        let mut numerator = gf.small(1);
+        let mut denominator = gf.small(1);
+        for (_, &xj) in xs.iter().enumerate().filter(|&(j, _)| j != i) {
+            numerator = gf.mul(&numerator, &gf.small(xj));
+            denominator = gf.mul(&denominator, &gf.small(xs[i] ^ xj));
+        }
+        let basis = gf.mul(&numerator, &gf.inv(&denominator));
+        xor(&mut secret, &gf.mul(&y, &basis));
+    }
+
+    let mut bytes = gf.bytes(&secret);
+    if diffusion && gf.degree >= 64 {
+        diffuse(&mut bytes, true);
+    }
+    Ok(bytes)
+}
+
+/// `index-hex` as ssss-split prints it, the index zero-padded to the width of `total`.
+pub fn format_share(share: &[u8], total: u8) -> String {
+    let width = total.to_string().len();
+    format!("{:0width$}-{}", share[0], hex::encode(&share[1..]), width = width)
+}
+
+/// Reads an `[token-]index-hex` line.
+pub fn parse_share(text: &str) -> Result<Vec<u8>> {
+    let text = text.trim();
+    let mut parts = text.rsplitn(3, '-');
+    let (hex_part, index) = match (parts.next(), parts.next()) {
+        (Some(h), Some(i)) => (h, i),
+        _ => return Err(SssError::Parse("an ssss share looks like 3-1a2b...".into())),
+    };
+    let index: u8 = index
+        .parse()
+        .ok()
+        .filter(|&i| i > 0)
+        .ok_or_else(|| SssError::Parse(format!("ssss share index '{}' is not 1-255", index)))?;
+    let y = hex::decode(hex_part)
+        .map_err(|_| SssError::Parse("ssss share data is not whole bytes of hex".into()))?;
+    if y.is_empty() || y.len() > MAX_SECRET_LEN {
+        return Err(SssError::Parse(format!("ssss share data must be 1 to {} bytes", MAX_SECRET_LEN)));
+    }
+    let mut share = vec![index];
+    share.extend(y);
+    Ok(share)
+}
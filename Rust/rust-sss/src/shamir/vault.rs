@@ -0,0 +1,150 @@
+//! Sharing compatible with HashiCorp Vault's `shamir` package: GF(2^8) with the AES polynomial
+//! x^8 + x^4 + x^3 + x + 1 (0x11b), and the x coordinate appended to each share rather than
+//! prefixed. Vault's unseal keys are exactly those shares, as base64 (`unseal_keys_b64`) or hex
+//! (`unseal_keys_hex`).
+//!
+//! Shares keep the crate's `[x, y_0, y_1, ...]` layout in memory; [`unseal_key`] and
+//! [`parse_unseal_key`] convert at the edges.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::format::ShareHeader;
+
+const REDUCTION: u8 = 0x1b;
+
+pub fn split(secret: &[u8], threshold: u8, total: u8) -> Result<Vec<Vec<u8>>> {
+    split_with_rng(secret, threshold, total, &mut rand::rngs::OsRng)
+}
+
+pub fn split_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<Vec<u8>>> {
+    super::split_over(REDUCTION, secret, threshold, total, rng)
+}
+
+pub fn reconstruct<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>> {
+    super::reconstruct_over(REDUCTION, shares)
+}
+
+/// A share as Vault prints an unseal key: base64 of the y bytes followed by x.
+pub fn unseal_key(share: &[u8]) -> String {
+    BASE64.encode(vault_layout(share))
+}
+
+fn vault_layout(share: &[u8]) -> Vec<u8> {
+    let mut bytes = share[1..].to_vec();
+    bytes.push(share[0]);
+    bytes
+}
+
+/// Reads an unseal key given as base64 or hex, returning it in `[x, y...]` layout.
+pub fn parse_unseal_key(text: &str) -> Result<Vec<u8>> {
+    let text = ShareHeader::split(text).1.trim().trim_matches('"');
+    let bytes = if text.len().is_multiple_of(2) && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        hex::decode(text).map_err(|e| SssError::Parse(format!("unseal key: {}", e)))?
+    } else {
+        BASE64
+            .decode(text)
+            .map_err(|_| SssError::Parse("unseal key is neither base64 nor hex".into()))?
+    };
+    let (&x, ys) = bytes
+        .split_last()
+        .filter(|(_, ys)| !ys.is_empty())
+        .ok_or_else(|| SssError::Parse("unseal key is too short".into()))?;
+    let mut share = vec![x];
+    share.extend_from_slice(ys);
+    Ok(share)
+}
+
+/// The parts of `vault operator init -format=json` output that describe the key shares.
+/// Anything else in the document, such as the root token, is ignored and never written back.
+#[derive(Serialize, Deserialize, Default)]
+pub struct InitOutput {
+    #[serde(default)]
+    pub unseal_keys_b64: Vec<String>,
+    #[serde(default)]
+    pub unseal_keys_hex: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recovery_keys_b64: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recovery_keys_hex: Vec<String>,
+    #[serde(default)]
+    pub unseal_shares: u8,
+    #[serde(default)]
+    pub unseal_threshold: u8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub recovery_keys_shares: u8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub recovery_keys_threshold: u8,
+}
+
+fn is_zero(n: &u8) -> bool {
+    *n == 0
+}
+
+impl InitOutput {
+    /// Shares and threshold from init output: the unseal keys, or the recovery keys of a
+    /// cluster using auto-unseal.
+    pub fn shares(&self) -> Result<(Vec<Vec<u8>>, u8)> {
+        let (b64, hex, threshold) = if self.unseal_keys_b64.is_empty() && self.unseal_keys_hex.is_empty() {
+            (&self.recovery_keys_b64, &self.recovery_keys_hex, self.recovery_keys_threshold)
+        } else {
+            (&self.unseal_keys_b64, &self.unseal_keys_hex, self.unseal_threshold)
+        };
+        let keys = if b64.is_empty() { hex } else { b64 };
+        if keys.is_empty() {
+            return Err(SssError::Parse("Vault init output lists no unseal or recovery keys".into()));
+        }
+        let shares = keys.iter().map(|k| parse_unseal_key(k)).collect::<Result<Vec<_>>>()?;
+        Ok((shares, threshold))
+    }
+
+    /// Init output for a fresh set of shares, in the same shape Vault prints.
+    pub fn from_shares(shares: &[Vec<u8>], threshold: u8) -> InitOutput {
+        InitOutput {
+            unseal_keys_b64: shares.iter().map(|s| unseal_key(s)).collect(),
+            unseal_keys_hex: shares.iter().map(|s| hex::encode(vault_layout(s))).collect(),
+            unseal_shares: shares.len() as u8,
+            unseal_threshold: threshold,
+            ..InitOutput::default()
+        }
+    }
+}
+
+/// Recovers the key behind Vault's shares and splits it again with new parameters. When more
+/// keys than the threshold are given, every window of `threshold` consecutive keys must agree,
+/// so a mistyped key is caught instead of silently producing a different root key.
+pub fn resplit(init: &InitOutput, threshold: u8, total: u8) -> Result<Vec<Vec<u8>>> {
+    // with a threshold of one Vault uses the key itself as the only unseal key
+    if threshold < 2 {
+        return Err(SssError::Share("Vault needs a threshold of at least 2 for split keys".into()));
+    }
+    let (shares, old_threshold) = init.shares()?;
+    if old_threshold < 2 {
+        return Err(SssError::Share("the init output has no Shamir threshold to re-split from".into()));
+    }
+    let old_threshold = usize::from(old_threshold);
+    if shares.len() < old_threshold {
+        return Err(SssError::Share(format!(
+            "{} keys given but the threshold is {}",
+            shares.len(),
+            old_threshold
+        )));
+    }
+    let key = Zeroizing::new(reconstruct(&shares[..old_threshold])?);
+    for window in shares.windows(old_threshold).skip(1) {
+        if *Zeroizing::new(reconstruct(window)?) != *key {
+            return Err(SssError::Share(
+                "the unseal keys do not agree; one of them is mistyped or from another cluster".into(),
+            ));
+        }
+    }
+    split(&key, threshold, total)
+}
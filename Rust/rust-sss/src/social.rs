@@ -0,0 +1,307 @@
+//! Social recovery by files, for guardians who are not in the room. The dealer registers each
+//! guardian's Ed25519 key and contact in a registry. A coordinator then starts a recovery
+//! session, which writes one challenge file per guardian naming the session's secp256k1 key.
+//! Each guardian answers theirs with their share encrypted to that key (as in
+//! [`crate::elgamal::encrypt`]) and signs the answer over the challenge. The coordinator only
+//! takes answers signed by a registered guardian for a challenge of this session.
+//!
+//! Challenges are derived from the session secret, so the session file is all the coordinator
+//! keeps; it is written readable only by its owner.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use k256::Scalar;
+use k256::elliptic_curve::Field;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::elgamal;
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::secure::write_private;
+use crate::shamir::secp256k1;
+use crate::timelock::system_now;
+
+const CHALLENGE_CONTEXT: &[u8] = b"sss guardian challenge v1";
+const ANSWER_CONTEXT: &[u8] = b"sss guardian answer v1";
+const SESSION_FILE: &str = "session.json";
+
+fn json_error(path: &Path, e: serde_json::Error) -> SssError {
+    SssError::Parse(format!("{}: {}", path.display(), e))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| json_error(path, e))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value).map(|text| text + "\n").map_err(|e| SssError::Parse(e.to_string()))
+}
+
+fn check_version(version: u8, what: &str) -> Result<()> {
+    if version != FORMAT_VERSION {
+        return Err(SssError::Parse(format!("unsupported {} version {}", what, version)));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Guardian {
+    pub name: String,
+    /// How to reach the guardian: an address, a phone number, a note.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    /// The guardian's Ed25519 public key, hex, as `sss guardian keygen` prints it.
+    pub public_key: String,
+}
+
+impl Guardian {
+    pub fn verifying_key(&self) -> Result<VerifyingKey> {
+        crate::token::parse_verifying_key(&self.public_key)
+    }
+
+    /// A name safe to put in a file name.
+    fn file_stem(&self) -> String {
+        self.name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' }).collect()
+    }
+}
+
+/// The dealer's list of guardians.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Registry {
+    pub format_version: u8,
+    pub guardians: Vec<Guardian>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry { format_version: FORMAT_VERSION, guardians: Vec::new() }
+    }
+}
+
+impl Registry {
+    /// The registry at `path`, or an empty one if there is no file yet.
+    pub fn read(path: &Path) -> Result<Registry> {
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+        let registry: Registry = read_json(path)?;
+        check_version(registry.format_version, "guardian registry")?;
+        Ok(registry)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, to_json(self)?)?;
+        Ok(())
+    }
+
+    /// Adds `guardian`, refusing a second guardian of the same name or key.
+    pub fn add(&mut self, guardian: Guardian) -> Result<()> {
+        let key = guardian.verifying_key()?;
+        if guardian.name.trim().is_empty() {
+            return Err(SssError::InvalidArgument("a guardian needs a name".into()));
+        }
+        if let Some(other) = self
+            .guardians
+            .iter()
+            .find(|g| g.name.eq_ignore_ascii_case(&guardian.name) || g.verifying_key().ok() == Some(key))
+        {
+            return Err(SssError::InvalidArgument(format!("{} is already registered as a guardian", other.name)));
+        }
+        self.guardians.push(guardian);
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Guardian> {
+        self.guardians.iter().find(|g| g.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// What the coordinator sends one guardian.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub format_version: u8,
+    pub session: String,
+    pub guardian: String,
+    /// The guardian's key the answer must be signed with.
+    pub guardian_key: String,
+    /// Fingerprint of the set being recovered, if the coordinator named one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set: Option<String>,
+    /// The session's secp256k1 public key, hex, that the share is encrypted to.
+    pub coordinator_key: String,
+    pub nonce: String,
+    pub issued_at: u64,
+}
+
+impl Challenge {
+    pub fn read(path: &Path) -> Result<Challenge> {
+        let challenge: Challenge = read_json(path)?;
+        check_version(challenge.format_version, "challenge")?;
+        Ok(challenge)
+    }
+
+    /// What the guardian's signature binds the answer to.
+    fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(CHALLENGE_CONTEXT);
+        for field in [&self.session, &self.guardian, &self.guardian_key, &self.coordinator_key, &self.nonce] {
+            hasher.update((field.len() as u32).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hasher.update(self.set.as_deref().unwrap_or("").as_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// A guardian's reply: their share, encrypted to the session and signed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub format_version: u8,
+    pub session: String,
+    pub guardian: String,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub ciphertext: Vec<u8>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub signature: Vec<u8>,
+}
+
+fn signed_answer(challenge_digest: &[u8; 32], ciphertext: &[u8]) -> Vec<u8> {
+    [ANSWER_CONTEXT, challenge_digest, ciphertext].concat()
+}
+
+impl Answer {
+    pub fn read(path: &Path) -> Result<Answer> {
+        let answer: Answer = read_json(path)?;
+        check_version(answer.format_version, "answer")?;
+        Ok(answer)
+    }
+
+    /// Writes the answer, refusing to overwrite an existing file.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_private(path, to_json(self)?.as_bytes())
+    }
+}
+
+/// Answers `challenge` with `share_text`, signing with the guardian's `key`.
+pub fn answer(challenge: &Challenge, key: &SigningKey, share_text: &str) -> Result<Answer> {
+    if hex::encode(key.verifying_key().as_bytes()) != challenge.guardian_key.to_ascii_lowercase() {
+        return Err(SssError::Unauthorized(format!(
+            "this challenge is for {}'s key, not the one given",
+            challenge.guardian
+        )));
+    }
+    let coordinator_key = hex::decode(&challenge.coordinator_key)
+        .map_err(|_| SssError::Parse("the challenge's coordinator key is not hex".into()))?;
+    let ciphertext = elgamal::encrypt(&coordinator_key, share_text.as_bytes())?;
+    let signature = key.sign(&signed_answer(&challenge.digest(), &ciphertext));
+    Ok(Answer {
+        format_version: FORMAT_VERSION,
+        session: challenge.session.clone(),
+        guardian: challenge.guardian.clone(),
+        ciphertext,
+        signature: signature.to_bytes().to_vec(),
+    })
+}
+
+/// A coordinator's recovery session.
+#[derive(Serialize, Deserialize)]
+pub struct RecoverySession {
+    pub format_version: u8,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set: Option<String>,
+    pub issued_at: u64,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    secret: Vec<u8>,
+}
+
+impl Drop for RecoverySession {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.secret);
+    }
+}
+
+impl RecoverySession {
+    pub fn generate<R: RngCore + CryptoRng>(set: Option<String>, rng: &mut R) -> RecoverySession {
+        let mut id = [0u8; 8];
+        rng.fill_bytes(&mut id);
+        RecoverySession {
+            format_version: FORMAT_VERSION,
+            id: hex::encode(id),
+            set: set.map(|set| set.to_ascii_lowercase()),
+            issued_at: system_now(),
+            secret: Scalar::random(rng).to_bytes().to_vec(),
+        }
+    }
+
+    /// The challenge for `guardian`, the same each time it is asked for.
+    pub fn challenge(&self, guardian: &Guardian) -> Result<Challenge> {
+        let nonce = Sha256::new()
+            .chain_update(CHALLENGE_CONTEXT)
+            .chain_update(&self.secret)
+            .chain_update(guardian.public_key.to_ascii_lowercase())
+            .finalize();
+        Ok(Challenge {
+            format_version: FORMAT_VERSION,
+            session: self.id.clone(),
+            guardian: guardian.name.clone(),
+            guardian_key: guardian.public_key.to_ascii_lowercase(),
+            set: self.set.clone(),
+            coordinator_key: hex::encode(secp256k1::public_key(&self.secret)?),
+            nonce: hex::encode(&nonce[..16]),
+            issued_at: self.issued_at,
+        })
+    }
+
+    /// Checks that `answer` was signed by the registered guardian it names, for this
+    /// session's challenge, and returns the share text it carries.
+    pub fn open(&self, registry: &Registry, answer: &Answer) -> Result<Zeroizing<String>> {
+        if answer.session != self.id {
+            return Err(SssError::Unauthorized(format!(
+                "the answer is for session {}, not {}",
+                answer.session, self.id
+            )));
+        }
+        let guardian = registry
+            .find(&answer.guardian)
+            .ok_or_else(|| SssError::Unauthorized(format!("{} is not a registered guardian", answer.guardian)))?;
+        let signature = Signature::from_slice(&answer.signature)
+            .map_err(|_| SssError::Unauthorized("the answer's signature is malformed".into()))?;
+        guardian
+            .verifying_key()?
+            .verify(&signed_answer(&self.challenge(guardian)?.digest(), &answer.ciphertext), &signature)
+            .map_err(|_| SssError::Unauthorized(format!("the answer was not signed by {}'s key", guardian.name)))?;
+        let plaintext = elgamal::decrypt(&self.secret, &answer.ciphertext)?;
+        let text = std::str::from_utf8(&plaintext)
+            .map_err(|_| SssError::Parse("the answered share is not UTF-8 text".into()))?;
+        Ok(Zeroizing::new(text.to_string()))
+    }
+
+    /// Writes the session and one challenge per guardian into `dir`, returning the
+    /// challenge files. Refuses a directory that already holds a session.
+    pub fn write(&self, dir: &Path, registry: &Registry) -> Result<Vec<(PathBuf, Challenge)>> {
+        if registry.guardians.is_empty() {
+            return Err(SssError::Parse("the guardian registry is empty; add guardians first".into()));
+        }
+        std::fs::create_dir_all(dir)?;
+        write_private(dir.join(SESSION_FILE), to_json(self)?.as_bytes())?;
+        let mut written = Vec::with_capacity(registry.guardians.len());
+        for guardian in &registry.guardians {
+            let challenge = self.challenge(guardian)?;
+            let path = dir.join(format!("challenge-{}.json", guardian.file_stem()));
+            std::fs::write(&path, to_json(&challenge)?)?;
+            written.push((path, challenge));
+        }
+        Ok(written)
+    }
+
+    pub fn read(dir: &Path) -> Result<RecoverySession> {
+        let session: RecoverySession = read_json(&dir.join(SESSION_FILE))?;
+        check_version(session.format_version, "recovery session")?;
+        secp256k1::public_key(&session.secret)?;
+        Ok(session)
+    }
+}
@@ -0,0 +1,79 @@
+//! Secrets and passwords handed over without a terminal, for CI jobs and wrapping tools: an
+//! environment variable, an inherited file descriptor, or a file. One trailing line break is
+//! dropped, as `echo` and most editors add it; anything else is kept as given.
+
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+
+/// Where a value is read from instead of a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The named environment variable.
+    Env(String),
+    /// A descriptor the caller left open, such as 3 for `3<password.txt`.
+    Fd(i32),
+    /// A file holding only the value.
+    File(PathBuf),
+}
+
+impl Source {
+    /// The source given by whichever of the three options is set, if any.
+    pub fn pick(env: Option<&str>, fd: Option<i32>, file: Option<&std::path::Path>) -> Option<Source> {
+        env.map(|name| Source::Env(name.to_string()))
+            .or(fd.map(Source::Fd))
+            .or(file.map(|path| Source::File(path.to_path_buf())))
+    }
+
+    /// Reads the value, which must be UTF-8 and not empty.
+    pub fn read(&self) -> Result<Zeroizing<String>> {
+        let mut value = match self {
+            Source::Env(name) => Zeroizing::new(std::env::var(name).map_err(|e| match e {
+                std::env::VarError::NotPresent => SssError::InvalidArgument(format!("${} is not set", name)),
+                std::env::VarError::NotUnicode(_) => SssError::InvalidArgument(format!("${} is not UTF-8", name)),
+            })?),
+            Source::Fd(fd) => read_fd(*fd)?,
+            Source::File(path) => {
+                Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+                    SssError::Io(std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+                })?)
+            }
+        };
+        let len = value.strip_suffix('\n').map_or(value.len(), |v| v.strip_suffix('\r').unwrap_or(v).len());
+        value.truncate(len);
+        if value.is_empty() {
+            return Err(SssError::Parse(format!("{} is empty", self)));
+        }
+        Ok(value)
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Env(name) => write!(f, "${}", name),
+            Source::Fd(fd) => write!(f, "file descriptor {}", fd),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32) -> Result<Zeroizing<String>> {
+    use std::io::Read;
+
+    // reopening through /dev/fd leaves the caller's descriptor alone and needs no unsafe
+    let mut file = std::fs::File::open(format!("/dev/fd/{}", fd))
+        .map_err(|e| SssError::Io(std::io::Error::new(e.kind(), format!("file descriptor {}: {}", fd, e))))?;
+    let mut bytes = Zeroizing::new(Vec::new());
+    file.read_to_end(&mut bytes)?;
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| SssError::Parse(format!("file descriptor {} did not hold UTF-8", fd)))?;
+    Ok(Zeroizing::new(text.to_string()))
+}
+
+#[cfg(not(unix))]
+fn read_fd(fd: i32) -> Result<Zeroizing<String>> {
+    Err(SssError::InvalidArgument(format!("reading file descriptor {} needs a Unix system; use a file instead", fd)))
+}
@@ -0,0 +1,140 @@
+//! A share hidden in the least-significant bits of a PNG, so a photo can stand in for a
+//! paper share. The share's text, header lines included, goes into the low bit of each color
+//! sample in turn (alpha is left alone), behind a magic and a length. That survives a look at
+//! the picture, not steganalysis, and only while the file is copied byte for byte: resizing or
+//! re-encoding it as JPEG destroys the share. `decrypt` reads `@photo.png` like a share file.
+
+use std::io::Cursor;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::input::{self, MAX_SHARE_TEXT_LEN};
+use crate::secure::write_private;
+
+const MAGIC: &[u8; 4] = b"sssg";
+const PREFIX_LEN: usize = MAGIC.len() + 4;
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+}
+
+fn png_error(e: impl std::fmt::Display) -> SssError {
+    SssError::Parse(format!("could not read the PNG: {}", e))
+}
+
+/// The image as 8-bit samples, with its color type and size.
+struct Image {
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+    pixels: Zeroizing<Vec<u8>>,
+}
+
+impl Image {
+    fn decode(png_bytes: &[u8]) -> Result<Image> {
+        let mut decoder = png::Decoder::new(Cursor::new(png_bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let size = reader.output_buffer_size().ok_or_else(|| png_error("the image is too large"))?;
+        let mut pixels = Zeroizing::new(vec![0u8; size]);
+        let frame = reader.next_frame(&mut pixels).map_err(png_error)?;
+        pixels.truncate(frame.buffer_size());
+        Ok(Image { width: frame.width, height: frame.height, color: frame.color_type, pixels })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(self.color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| SssError::Parse(e.to_string()))?;
+        writer.write_image_data(&self.pixels).map_err(|e| SssError::Parse(e.to_string()))?;
+        writer.finish().map_err(|e| SssError::Parse(e.to_string()))?;
+        Ok(out)
+    }
+
+    /// The samples that carry bits: every one but alpha.
+    fn carriers(&mut self) -> impl Iterator<Item = &mut u8> {
+        let samples = self.color.samples();
+        let has_alpha = matches!(self.color, png::ColorType::GrayscaleAlpha | png::ColorType::Rgba);
+        self.pixels
+            .iter_mut()
+            .enumerate()
+            .filter(move |(i, _)| !has_alpha || i % samples != samples - 1)
+            .map(|(_, sample)| sample)
+    }
+
+    fn capacity(&mut self) -> usize {
+        (self.carriers().count() / 8).saturating_sub(PREFIX_LEN)
+    }
+}
+
+/// How many bytes of share text the PNG `cover` can hide.
+pub fn capacity(cover: &[u8]) -> Result<usize> {
+    Ok(Image::decode(cover)?.capacity())
+}
+
+/// `cover` with `text` hidden in it, as PNG bytes. Palette and 16-bit images come out as
+/// 8-bit RGB or grayscale.
+pub fn hide(cover: &[u8], text: &str) -> Result<Vec<u8>> {
+    let mut image = Image::decode(cover)?;
+    let capacity = image.capacity();
+    if text.len() > capacity {
+        return Err(SssError::Parse(format!(
+            "the cover image hides at most {} bytes but the share is {} bytes; pick a larger image",
+            capacity,
+            text.len()
+        )));
+    }
+    let mut payload = Zeroizing::new(Vec::with_capacity(PREFIX_LEN + text.len()));
+    payload.extend_from_slice(MAGIC);
+    payload.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    payload.extend_from_slice(text.as_bytes());
+    let bits = payload.iter().flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1));
+    for (sample, bit) in image.carriers().zip(bits) {
+        *sample = (*sample & !1) | bit;
+    }
+    image.encode()
+}
+
+/// The share text hidden in a PNG by [`hide`].
+pub fn reveal(png_bytes: &[u8]) -> Result<Zeroizing<String>> {
+    let mut image = Image::decode(png_bytes)?;
+    let capacity = image.capacity();
+    let mut bytes = Zeroizing::new(Vec::new());
+    let mut byte = 0u8;
+    let mut len = None;
+    for (i, sample) in image.carriers().enumerate() {
+        byte = (byte << 1) | (*sample & 1);
+        if i % 8 != 7 {
+            continue;
+        }
+        bytes.push(byte);
+        if bytes.len() == PREFIX_LEN {
+            if &bytes[..MAGIC.len()] != MAGIC {
+                return Err(SssError::Parse("no share is hidden in this image".into()));
+            }
+            let declared = u32::from_be_bytes(bytes[MAGIC.len()..].try_into().expect("four bytes")) as usize;
+            if declared > capacity.min(MAX_SHARE_TEXT_LEN) {
+                return Err(SssError::Parse("the share hidden in this image is damaged".into()));
+            }
+            len = Some(declared);
+        }
+        if len.is_some_and(|len| bytes.len() == PREFIX_LEN + len) {
+            return Ok(Zeroizing::new(input::normalize(&bytes[PREFIX_LEN..])?));
+        }
+    }
+    Err(SssError::Parse("no share is hidden in this image".into()))
+}
+
+/// Hides `text` in the PNG at `cover` and writes the result to `out` (mode 0600), refusing to
+/// overwrite an existing file.
+pub fn hide_file(cover: &Path, out: &Path, text: &str) -> Result<()> {
+    let stego = hide(&std::fs::read(cover)?, text)?;
+    write_private(out, &stego)
+}
+
+pub fn reveal_file(path: &Path) -> Result<Zeroizing<String>> {
+    reveal(&std::fs::read(path)?)
+}
@@ -0,0 +1,120 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::{ShareStore, SHARE_PREFIX, STAGED_PREFIX};
+use crate::error::{Result, SssError};
+
+/// Talks to etcd through its v3 JSON gateway (`/v3/kv/*`), so no gRPC toolchain is needed.
+pub struct EtcdStore {
+    endpoint: String,
+    namespace: String,
+    agent: ureq::Agent,
+}
+
+impl EtcdStore {
+    /// `endpoint` is the client URL, e.g. `http://127.0.0.1:2379`.
+    pub fn new(endpoint: &str, namespace: &str) -> Self {
+        EtcdStore {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            namespace: namespace.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(10)).build(),
+        }
+    }
+
+    fn key(&self, prefix: &str, id: &str) -> String {
+        format!("{}/{}{}", self.namespace, prefix, id)
+    }
+
+    fn call(&self, path: &str, body: Value) -> Result<Value> {
+        self.agent
+            .post(&format!("{}{}", self.endpoint, path))
+            .send_json(body)
+            .map_err(|e| SssError::Store(format!("etcd {}: {}", path, e)))?
+            .into_json()
+            .map_err(|e| SssError::Store(format!("etcd {}: {}", path, e)))
+    }
+
+    fn put_with_lease(&self, key: &str, value: &[u8], lease: Option<&str>) -> Result<()> {
+        let mut body = json!({ "key": BASE64.encode(key), "value": BASE64.encode(value) });
+        if let Some(lease) = lease {
+            body["lease"] = Value::String(lease.to_string());
+        }
+        self.call("/v3/kv/put", body).map(|_| ())
+    }
+
+    fn get_key(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let reply = self.call("/v3/kv/range", json!({ "key": BASE64.encode(key) }))?;
+        match reply["kvs"].get(0).and_then(|kv| kv["value"].as_str()) {
+            Some(value) => BASE64
+                .decode(value)
+                .map(Some)
+                .map_err(|e| SssError::Store(format!("etcd returned bad base64: {}", e))),
+            // etcd omits "value" for empty values, but still returns the key
+            None if reply["kvs"].get(0).is_some() => Ok(Some(Vec::new())),
+            None => Ok(None),
+        }
+    }
+}
+
+impl ShareStore for EtcdStore {
+    #[tracing::instrument(name = "etcd.put", skip_all, fields(%fingerprint, bytes = share.len()))]
+    fn put(&mut self, fingerprint: &str, share: &[u8]) -> Result<()> {
+        self.put_with_lease(&self.key(SHARE_PREFIX, fingerprint), share, None)
+    }
+
+    #[tracing::instrument(name = "etcd.get", skip_all, fields(%fingerprint))]
+    fn get(&mut self, fingerprint: &str) -> Result<Option<Vec<u8>>> {
+        self.get_key(&self.key(SHARE_PREFIX, fingerprint))
+    }
+
+    #[tracing::instrument(name = "etcd.list", skip_all)]
+    fn list(&mut self) -> Result<Vec<String>> {
+        let prefix = self.key(SHARE_PREFIX, "");
+        let mut range_end = prefix.clone().into_bytes();
+        if let Some(last) = range_end.last_mut() {
+            *last += 1;
+        }
+        let reply = self.call(
+            "/v3/kv/range",
+            json!({ "key": BASE64.encode(&prefix), "range_end": BASE64.encode(range_end), "keys_only": true }),
+        )?;
+        let mut fingerprints = Vec::new();
+        for kv in reply["kvs"].as_array().into_iter().flatten() {
+            let key = kv["key"]
+                .as_str()
+                .and_then(|k| BASE64.decode(k).ok())
+                .and_then(|k| String::from_utf8(k).ok())
+                .ok_or_else(|| SssError::Store("etcd returned an unreadable key".into()))?;
+            if let Some(fingerprint) = key.strip_prefix(&prefix) {
+                fingerprints.push(fingerprint.to_string());
+            }
+        }
+        fingerprints.sort();
+        Ok(fingerprints)
+    }
+
+    #[tracing::instrument(name = "etcd.delete", skip_all, fields(%fingerprint))]
+    fn delete(&mut self, fingerprint: &str) -> Result<bool> {
+        let reply = self.call(
+            "/v3/kv/deleterange",
+            json!({ "key": BASE64.encode(self.key(SHARE_PREFIX, fingerprint)) }),
+        )?;
+        Ok(reply["deleted"].as_str().is_some_and(|n| n != "0"))
+    }
+
+    #[tracing::instrument(name = "etcd.stage", skip_all, fields(%id, bytes = data.len(), ttl_secs = ttl.as_secs()))]
+    fn stage(&mut self, id: &str, data: &[u8], ttl: Duration) -> Result<()> {
+        let lease = self.call("/v3/lease/grant", json!({ "TTL": ttl.as_secs().max(1) }))?;
+        let lease_id = lease["ID"]
+            .as_str()
+            .ok_or_else(|| SssError::Store("etcd did not grant a lease".into()))?
+            .to_string();
+        self.put_with_lease(&self.key(STAGED_PREFIX, id), data, Some(&lease_id))
+    }
+
+    #[tracing::instrument(name = "etcd.staged", skip_all, fields(%id))]
+    fn staged(&mut self, id: &str) -> Result<Option<Vec<u8>>> {
+        self.get_key(&self.key(STAGED_PREFIX, id))
+    }
+}
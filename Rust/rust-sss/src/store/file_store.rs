@@ -0,0 +1,115 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{ShareStore, SHARE_PREFIX, STAGED_PREFIX};
+use crate::error::{Result, SssError};
+use crate::secure::write_private;
+
+/// One file per share under `<root>/shares/`, readable only by the owner on Unix. Staged
+/// entries go under `<root>/staged/`, each starting with its expiry in Unix seconds.
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    /// Uses `root`, creating it when it does not exist yet.
+    pub fn open(root: &Path) -> Result<Self> {
+        for prefix in [SHARE_PREFIX, STAGED_PREFIX] {
+            fs::create_dir_all(root.join(prefix))?;
+        }
+        Ok(FileStore { root: root.to_path_buf() })
+    }
+
+    fn path(&self, prefix: &str, id: &str) -> Result<PathBuf> {
+        // ids become file names, so nothing that could leave the directory or hide in it
+        if id.is_empty() || id.starts_with('.') || !id.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b)) {
+            return Err(SssError::Store(format!("{:?} cannot be used as a file name in the store", id)));
+        }
+        Ok(self.root.join(prefix).join(id))
+    }
+
+    /// Writes next to `path` and renames into place, so a reader sees the old entry or the new
+    /// one and never half of either.
+    fn replace(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        let mut pending = path.as_os_str().to_owned();
+        pending.push(".new");
+        let pending = PathBuf::from(pending);
+        remove(&pending)?;
+        write_private(&pending, contents)?;
+        fs::rename(&pending, path)?;
+        Ok(())
+    }
+}
+
+/// Removes `path`, reporting whether it was there.
+fn remove(path: &Path) -> Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+impl ShareStore for FileStore {
+    fn put(&mut self, fingerprint: &str, share: &[u8]) -> Result<()> {
+        let path = self.path(SHARE_PREFIX, fingerprint)?;
+        self.replace(&path, share)
+    }
+
+    fn get(&mut self, fingerprint: &str) -> Result<Option<Vec<u8>>> {
+        read(&self.path(SHARE_PREFIX, fingerprint)?)
+    }
+
+    fn list(&mut self) -> Result<Vec<String>> {
+        let mut fingerprints = Vec::new();
+        for entry in fs::read_dir(self.root.join(SHARE_PREFIX))? {
+            let name = entry?.file_name();
+            match name.to_str() {
+                Some(name) if !name.starts_with('.') && !name.ends_with(".new") => fingerprints.push(name.to_string()),
+                _ => {}
+            }
+        }
+        fingerprints.sort();
+        Ok(fingerprints)
+    }
+
+    fn delete(&mut self, fingerprint: &str) -> Result<bool> {
+        remove(&self.path(SHARE_PREFIX, fingerprint)?)
+    }
+
+    fn stage(&mut self, id: &str, data: &[u8], ttl: Duration) -> Result<()> {
+        let path = self.path(STAGED_PREFIX, id)?;
+        let mut contents = now().saturating_add(ttl.as_secs().max(1)).to_be_bytes().to_vec();
+        contents.extend_from_slice(data);
+        self.replace(&path, &contents)
+    }
+
+    fn staged(&mut self, id: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(STAGED_PREFIX, id)?;
+        let Some(contents) = read(&path)? else {
+            return Ok(None);
+        };
+        let (expires, data) = contents
+            .split_first_chunk::<8>()
+            .ok_or_else(|| SssError::Store(format!("{} is not a staged entry", path.display())))?;
+        if u64::from_be_bytes(*expires) <= now() {
+            remove(&path)?;
+            return Ok(None);
+        }
+        Ok(Some(data.to_vec()))
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use super::ShareStore;
+use crate::error::Result;
+
+/// Keeps everything in the process, for tests and for applications that persist shares
+/// themselves. Nothing survives the store being dropped.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    shares: BTreeMap<String, Vec<u8>>,
+    staged: HashMap<String, (Instant, Vec<u8>)>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl ShareStore for MemoryStore {
+    fn put(&mut self, fingerprint: &str, share: &[u8]) -> Result<()> {
+        self.shares.insert(fingerprint.to_string(), share.to_vec());
+        Ok(())
+    }
+
+    fn get(&mut self, fingerprint: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.shares.get(fingerprint).cloned())
+    }
+
+    fn list(&mut self) -> Result<Vec<String>> {
+        Ok(self.shares.keys().cloned().collect())
+    }
+
+    fn delete(&mut self, fingerprint: &str) -> Result<bool> {
+        Ok(self.shares.remove(fingerprint).is_some())
+    }
+
+    fn stage(&mut self, id: &str, data: &[u8], ttl: Duration) -> Result<()> {
+        let now = Instant::now();
+        self.staged.retain(|_, (expires, _)| *expires > now);
+        self.staged.insert(id.to_string(), (now + ttl, data.to_vec()));
+        Ok(())
+    }
+
+    fn staged(&mut self, id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .staged
+            .get(id)
+            .filter(|(expires, _)| *expires > Instant::now())
+            .map(|(_, data)| data.clone()))
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use crate::error::{Result, SssError};
+use crate::manifest::share_fingerprint;
+
+#[cfg(feature = "etcd-store")]
+pub mod etcd_store;
+pub mod file_store;
+pub mod memory_store;
+#[cfg(feature = "redis-store")]
+pub mod redis_store;
+
+pub use file_store::FileStore;
+pub use memory_store::MemoryStore;
+
+/// Somewhere shares can be kept, addressed by their fingerprint. Staged entries are
+/// short-lived records (such as pending release requests) that the backend expires itself.
+pub trait ShareStore {
+    fn put(&mut self, fingerprint: &str, share: &[u8]) -> Result<()>;
+    fn get(&mut self, fingerprint: &str) -> Result<Option<Vec<u8>>>;
+    fn list(&mut self) -> Result<Vec<String>>;
+    fn delete(&mut self, fingerprint: &str) -> Result<bool>;
+
+    fn stage(&mut self, id: &str, data: &[u8], ttl: Duration) -> Result<()>;
+    fn staged(&mut self, id: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Stores `share` under its fingerprint as manifests give it, and returns the fingerprint.
+    fn put_share(&mut self, share: &[u8]) -> Result<String> {
+        let fingerprint = share_fingerprint(share);
+        self.put(&fingerprint, share)?;
+        Ok(fingerprint)
+    }
+
+    /// The share stored under `fingerprint`, refused if it no longer matches it.
+    fn get_share(&mut self, fingerprint: &str) -> Result<Option<Vec<u8>>> {
+        let share = self.get(fingerprint)?;
+        if let Some(share) = &share {
+            if !share_fingerprint(share).eq_ignore_ascii_case(fingerprint) {
+                return Err(SssError::Checksum(format!("the share stored as {} has been altered", fingerprint)));
+            }
+        }
+        Ok(share)
+    }
+}
+
+pub const SHARE_PREFIX: &str = "shares/";
+pub const STAGED_PREFIX: &str = "staged/";
@@ -0,0 +1,77 @@
+use redis::{Commands, Connection};
+use std::time::Duration;
+
+use super::{ShareStore, SHARE_PREFIX, STAGED_PREFIX};
+use crate::error::{Result, SssError};
+
+pub struct RedisStore {
+    connection: Connection,
+    namespace: String,
+}
+
+impl RedisStore {
+    /// Connects to e.g. `redis://127.0.0.1/`; every key is placed under `namespace`.
+    pub fn connect(url: &str, namespace: &str) -> Result<Self> {
+        let connection = redis::Client::open(url)
+            .and_then(|client| client.get_connection())
+            .map_err(store_error)?;
+        Ok(RedisStore { connection, namespace: namespace.to_string() })
+    }
+
+    fn key(&self, prefix: &str, id: &str) -> String {
+        format!("{}:{}{}", self.namespace, prefix, id)
+    }
+}
+
+fn store_error(e: redis::RedisError) -> SssError {
+    SssError::Store(format!("redis: {}", e))
+}
+
+impl ShareStore for RedisStore {
+    #[tracing::instrument(name = "redis.put", skip_all, fields(%fingerprint, bytes = share.len()))]
+    fn put(&mut self, fingerprint: &str, share: &[u8]) -> Result<()> {
+        let key = self.key(SHARE_PREFIX, fingerprint);
+        self.connection.set(key, share).map_err(store_error)
+    }
+
+    #[tracing::instrument(name = "redis.get", skip_all, fields(%fingerprint))]
+    fn get(&mut self, fingerprint: &str) -> Result<Option<Vec<u8>>> {
+        let key = self.key(SHARE_PREFIX, fingerprint);
+        self.connection.get(key).map_err(store_error)
+    }
+
+    #[tracing::instrument(name = "redis.list", skip_all)]
+    fn list(&mut self) -> Result<Vec<String>> {
+        let prefix = self.key(SHARE_PREFIX, "");
+        let keys: Vec<String> = self
+            .connection
+            .scan_match(format!("{}*", prefix))
+            .map_err(store_error)?
+            .collect();
+        let mut fingerprints: Vec<String> = keys
+            .into_iter()
+            .filter_map(|k| k.strip_prefix(&prefix).map(str::to_string))
+            .collect();
+        fingerprints.sort();
+        Ok(fingerprints)
+    }
+
+    #[tracing::instrument(name = "redis.delete", skip_all, fields(%fingerprint))]
+    fn delete(&mut self, fingerprint: &str) -> Result<bool> {
+        let key = self.key(SHARE_PREFIX, fingerprint);
+        let removed: i64 = self.connection.del(key).map_err(store_error)?;
+        Ok(removed > 0)
+    }
+
+    #[tracing::instrument(name = "redis.stage", skip_all, fields(%id, bytes = data.len(), ttl_secs = ttl.as_secs()))]
+    fn stage(&mut self, id: &str, data: &[u8], ttl: Duration) -> Result<()> {
+        let key = self.key(STAGED_PREFIX, id);
+        self.connection.set_ex(key, data, ttl.as_secs().max(1)).map_err(store_error)
+    }
+
+    #[tracing::instrument(name = "redis.staged", skip_all, fields(%id))]
+    fn staged(&mut self, id: &str) -> Result<Option<Vec<u8>>> {
+        let key = self.key(STAGED_PREFIX, id);
+        self.connection.get(key).map_err(store_error)
+    }
+}
@@ -0,0 +1,137 @@
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::Payload;
+use aes_gcm::Aes256Gcm;
+use aes::cipher::generic_array::GenericArray;
+use rand::RngCore;
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+use crate::crypto::TAG_LEN;
+use crate::error::{Result, SssError};
+use crate::progress::{self, Event};
+
+const MAGIC: &[u8; 8] = b"SSSSTRM1";
+/// AES-GCM's 12-byte nonce minus the 5 bytes STREAM uses for the chunk counter and last flag.
+const NONCE_PREFIX_LEN: usize = 7;
+const HEADER_LEN: usize = MAGIC.len() + NONCE_PREFIX_LEN + 4;
+pub const CHUNK_SIZE: usize = 64 * 1024;
+const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+pub const CONTENT_KEY_LEN: usize = 32;
+/// Chunks between progress events.
+const REPORT_EVERY: u64 = 16;
+
+/// Random key for one encrypted file. Only this key goes through Shamir, so shares stay small
+/// however large the file is.
+pub fn generate_content_key() -> Zeroizing<[u8; CONTENT_KEY_LEN]> {
+    let mut key = Zeroizing::new([0u8; CONTENT_KEY_LEN]);
+    rand::rngs::OsRng.fill_bytes(key.as_mut());
+    key
+}
+
+/// Reports the bytes streamed so far every `REPORT_EVERY` chunks.
+fn report(chunks: u64, bytes: u64) {
+    if chunks.is_multiple_of(REPORT_EVERY) {
+        progress::emit(Event::StreamProgress { bytes });
+    }
+}
+
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypts `reader` to `writer` in fixed-size chunks (AES-256-GCM in the STREAM construction),
+/// holding at most two chunks in memory. Returns the number of plaintext bytes.
+pub fn encrypt_stream<R: Read, W: Write>(mut reader: R, mut writer: W, key: &[u8; CONTENT_KEY_LEN]) -> Result<u64> {
+    let mut prefix = [0u8; NONCE_PREFIX_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut prefix);
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&prefix);
+    header.extend_from_slice(&(CHUNK_SIZE as u32).to_be_bytes());
+    writer.write_all(&header)?;
+
+    let mut encryptor = EncryptorBE32::<Aes256Gcm>::new(
+        GenericArray::from_slice(key),
+        GenericArray::from_slice(&prefix),
+    );
+    let mut current = Zeroizing::new(vec![0u8; CHUNK_SIZE]);
+    let mut next = Zeroizing::new(vec![0u8; CHUNK_SIZE]);
+    let mut len = read_full(&mut reader, &mut current)?;
+    let mut total = len as u64;
+    let mut chunks = 0;
+    loop {
+        // the final chunk is sealed differently, so look one chunk ahead to spot it
+        let next_len = if len == CHUNK_SIZE { read_full(&mut reader, &mut next)? } else { 0 };
+        if next_len == 0 {
+            break;
+        }
+        let payload = Payload { msg: &current[..len], aad: &header };
+        writer.write_all(&encryptor.encrypt_next(payload)?)?;
+        chunks += 1;
+        report(chunks, total);
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        total += len as u64;
+    }
+    let payload = Payload { msg: &current[..len], aad: &header };
+    writer.write_all(&encryptor.encrypt_last(payload)?)?;
+    writer.flush()?;
+    progress::emit(Event::StreamProgress { bytes: total });
+    Ok(total)
+}
+
+/// Reverses `encrypt_stream`. Every chunk is authenticated before it is written, and a file
+/// cut short at a chunk boundary is rejected at the end.
+pub fn decrypt_stream<R: Read, W: Write>(mut reader: R, mut writer: W, key: &[u8; CONTENT_KEY_LEN]) -> Result<u64> {
+    let corrupt = || SssError::Parse("encrypted file is damaged or does not belong to these shares".into());
+    let mut header = [0u8; HEADER_LEN];
+    if read_full(&mut reader, &mut header)? != HEADER_LEN || &header[..MAGIC.len()] != MAGIC {
+        return Err(SssError::Parse("not a file encrypted by sss".into()));
+    }
+    let prefix = &header[MAGIC.len()..MAGIC.len() + NONCE_PREFIX_LEN];
+    let chunk_size = u32::from_be_bytes(header[HEADER_LEN - 4..].try_into().expect("4 bytes")) as usize;
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(SssError::Parse(format!("unsupported chunk size {}", chunk_size)));
+    }
+
+    let mut decryptor = DecryptorBE32::<Aes256Gcm>::new(
+        GenericArray::from_slice(key),
+        GenericArray::from_slice(prefix),
+    );
+    let sealed = chunk_size + TAG_LEN;
+    let mut current = vec![0u8; sealed];
+    let mut next = vec![0u8; sealed];
+    let mut len = read_full(&mut reader, &mut current)?;
+    let mut total = 0u64;
+    let mut chunks = 0;
+    loop {
+        let next_len = if len == sealed { read_full(&mut reader, &mut next)? } else { 0 };
+        if next_len == 0 {
+            break;
+        }
+        let payload = Payload { msg: &current[..len], aad: &header };
+        let plaintext = Zeroizing::new(decryptor.decrypt_next(payload).map_err(|_| corrupt())?);
+        writer.write_all(&plaintext)?;
+        total += plaintext.len() as u64;
+        chunks += 1;
+        report(chunks, total);
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+    }
+    let payload = Payload { msg: &current[..len], aad: &header };
+    let plaintext = Zeroizing::new(decryptor.decrypt_last(payload).map_err(|_| corrupt())?);
+    writer.write_all(&plaintext)?;
+    total += plaintext.len() as u64;
+    writer.flush()?;
+    progress::emit(Event::StreamProgress { bytes: total });
+    Ok(total)
+}
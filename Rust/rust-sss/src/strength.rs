@@ -0,0 +1,77 @@
+//! Password strength at split time. The password is what still stands between a colluding
+//! quorum and the secret, so its estimated crack time is worked out against this tool's KDF
+//! rather than against an unhashed login form.
+
+use std::fmt;
+
+/// PBKDF2-HMAC-SHA256 iterations per second assumed for an attacker: roughly one high-end
+/// GPU.
+pub const ATTACKER_ITERATIONS_PER_SECOND: f64 = 1e10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+    /// zxcvbn score, 0 (guessable in a thousand tries) to 4 (over ten billion).
+    pub score: u8,
+    pub guesses: u64,
+    /// PBKDF2 rounds each guess costs.
+    pub rounds: u32,
+    /// Seconds for the attacker to try that many guesses, each costing a full key derivation.
+    pub crack_seconds: f64,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+/// Estimates how long `password` holds out when every guess costs `rounds` PBKDF2 rounds.
+/// Words in `context`, such as custodian names, count as easy to guess.
+pub fn estimate(password: &str, context: &[&str], rounds: u32) -> Strength {
+    let entropy = zxcvbn::zxcvbn(password, context);
+    let feedback = entropy.feedback();
+    Strength {
+        score: entropy.score().into(),
+        guesses: entropy.guesses(),
+        rounds,
+        crack_seconds: entropy.guesses() as f64 * rounds as f64 / ATTACKER_ITERATIONS_PER_SECOND,
+        warning: feedback.and_then(|f| f.warning()).map(|w| w.to_string()),
+        suggestions: feedback.map(|f| f.suggestions().iter().map(|s| s.to_string()).collect()).unwrap_or_default(),
+    }
+}
+
+/// `seconds` in the largest unit that keeps it readable, e.g. `about 3 days` or `more than a century`.
+pub fn describe_duration(seconds: f64) -> String {
+    const UNITS: [(&str, f64); 6] = [
+        ("second", 1.0),
+        ("minute", 60.0),
+        ("hour", 3600.0),
+        ("day", 86_400.0),
+        ("month", 2_629_800.0),
+        ("year", 31_557_600.0),
+    ];
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    if seconds >= 100.0 * 31_557_600.0 {
+        return "more than a century".to_string();
+    }
+    let (unit, size) = UNITS.iter().rev().find(|(_, size)| seconds >= *size).copied().unwrap_or(UNITS[0]);
+    let n = (seconds / size).round() as u64;
+    format!("about {} {}{}", n, unit, if n == 1 { "" } else { "s" })
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/4, {} to crack at {} PBKDF2 rounds on one GPU",
+            self.score,
+            describe_duration(self.crack_seconds),
+            self.rounds
+        )?;
+        if let Some(warning) = &self.warning {
+            write!(f, "\n  {}", warning)?;
+        }
+        for suggestion in &self.suggestions {
+            write!(f, "\n  {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
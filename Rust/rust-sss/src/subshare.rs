@@ -0,0 +1,84 @@
+//! Sub-shares: a custodian splits their own share k'-of-n' again, say between a laptop, a phone
+//! and a safe, without anyone else taking part. The share's bytes are shared over the same
+//! GF(2^8) as the secret, so fewer than k' sub-shares say nothing about the share. Each
+//! sub-share carries its parent's header with a `Parent:` line added in front, so sub-shares
+//! can be split again and `decrypt` takes a quorum of them wherever it takes the share.
+
+use rand::RngCore;
+use std::collections::HashMap;
+
+use crate::audit::share_id;
+use crate::error::{Result, SssError};
+use crate::format::{validate_shares, Parent, ShareHeader};
+use crate::shamir::Field;
+
+/// Splits `share` into `total` sub-shares, any `threshold` of which rebuild it, each with the
+/// header it should be written with.
+pub fn split<R: RngCore + ?Sized>(
+    share: &[u8],
+    header: &ShareHeader,
+    threshold: u8,
+    total: u8,
+    rng: &mut R,
+) -> Result<Vec<(Vec<u8>, ShareHeader)>> {
+    let mut header = header.clone();
+    header.parents.insert(0, Parent { share: share_id(share), quorum: (threshold, total) });
+    let subshares = Field::Gf256.split_with_rng(share, threshold, total, rng)?;
+    Ok(subshares.into_iter().map(|subshare| (subshare, header.clone())).collect())
+}
+
+/// What became of a share or sub-share handed to [`Nest::add`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entered {
+    /// A share of the set itself, with its header, and how many sub-shares went into it
+    /// (none when it was entered whole).
+    Share(Vec<u8>, ShareHeader, usize),
+    /// A sub-share held back until enough others of the same parent arrive.
+    Pending { parent: String, have: usize, need: u8 },
+}
+
+/// Sub-shares gathered so far, by the parent they rebuild.
+#[derive(Debug, Default)]
+pub struct Nest {
+    groups: HashMap<String, Vec<Vec<u8>>>,
+}
+
+impl Nest {
+    /// Takes a share as entered. Shares pass straight through; a sub-share is held until its
+    /// parent's quorum is in, and the parent, rebuilt up to the top level, is returned then.
+    pub fn add(&mut self, mut share: Vec<u8>, mut header: ShareHeader) -> Result<Entered> {
+        let mut used = 0;
+        while let Some(parent) = header.parents.first().cloned() {
+            let group = self.groups.entry(parent.share.clone()).or_default();
+            if group.iter().any(|entered| entered.first() == share.first()) {
+                return Err(SssError::Share(format!(
+                    "sub-share #{} of share {} was already entered",
+                    share.first().copied().unwrap_or_default(),
+                    parent.share
+                )));
+            }
+            group.push(share);
+            if group.len() < parent.quorum.0 as usize {
+                return Ok(Entered::Pending { parent: parent.share, have: group.len(), need: parent.quorum.0 });
+            }
+            let group = self.groups.remove(&parent.share).unwrap_or_default();
+            validate_shares(&group)?;
+            let rebuilt = Field::Gf256.reconstruct(&group)?;
+            if !share_id(&rebuilt).eq_ignore_ascii_case(&parent.share) {
+                return Err(SssError::Share(format!(
+                    "the sub-shares do not rebuild share {}; one of them is damaged or from another share",
+                    parent.share
+                )));
+            }
+            used += group.len();
+            header.parents.remove(0);
+            share = rebuilt;
+        }
+        Ok(Entered::Share(share, header, used))
+    }
+
+    /// Parents still short of sub-shares, with how many they have and need.
+    pub fn pending(&self) -> Vec<(&str, usize)> {
+        self.groups.iter().map(|(parent, group)| (parent.as_str(), group.len())).collect()
+    }
+}
@@ -0,0 +1,161 @@
+//! A dead man's switch on a share set, for estate plans. The set names the dealer's Ed25519
+//! key in front of its payload (`dms1` ‖ key, authenticated like a time lock), and then only
+//! opens with the dealer's live approval, a capability token from `sss token issue`, or once
+//! the dealer's signed heartbeats have stopped: the dealer runs `sss heartbeat` now and then,
+//! each heartbeat vouching for the dealer until a date, and `decrypt` goes ahead when the
+//! heartbeat it is shown has lapsed. The switch can only judge the heartbeat it is given, so
+//! publish heartbeats where the holders read them and cannot hold a newer one back.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::timelock::{format_date, system_now};
+use crate::token;
+
+const MAGIC: &[u8; 4] = b"dms1";
+const KEY_LEN: usize = 32;
+const HEARTBEAT_CONTEXT: &[u8] = b"sss heartbeat v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Switch {
+    /// The dealer's public key, which signs heartbeats and approvals.
+    pub dealer_key: [u8; KEY_LEN],
+}
+
+/// A dealer's signed word that they were alive at `issued_at`, good until `expires_at`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub format_version: u8,
+    /// Fingerprint of the set the heartbeat holds shut.
+    pub fingerprint: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Why a switched set may be recovered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Release {
+    /// The dealer approved this recovery with a token issued to this holder.
+    Approved(String),
+    /// The last heartbeat lapsed at this time.
+    Lapsed(u64),
+}
+
+impl Switch {
+    pub fn header(&self) -> Vec<u8> {
+        [&MAGIC[..], &self.dealer_key].concat()
+    }
+
+    /// The switch in front of `payload` and the payload after it.
+    pub fn split(payload: &[u8]) -> Option<(Switch, &[u8])> {
+        let rest = payload.strip_prefix(MAGIC)?;
+        let dealer_key = rest.get(..KEY_LEN)?.try_into().ok()?;
+        Some((Switch { dealer_key }, &rest[KEY_LEN..]))
+    }
+
+    fn verifying_key(&self) -> Result<VerifyingKey> {
+        VerifyingKey::from_bytes(&self.dealer_key)
+            .map_err(|_| SssError::Parse("the switch's dealer key is not a valid Ed25519 key".into()))
+    }
+
+    /// Lets the recovery of set `fingerprint` go ahead at `now` on the dealer's `approval`
+    /// token, or on a `heartbeat` that has lapsed.
+    pub fn check(
+        &self,
+        fingerprint: &str,
+        approval: Option<&str>,
+        heartbeat: Option<&Heartbeat>,
+        now: u64,
+    ) -> Result<Release> {
+        let dealer = self.verifying_key()?;
+        if let Some(approval) = approval {
+            let capability = token::verify(approval, &dealer)?;
+            if !capability.fingerprint.eq_ignore_ascii_case(fingerprint) {
+                return Err(SssError::Unauthorized(format!(
+                    "the dealer's approval covers set {}, not {}",
+                    capability.fingerprint, fingerprint
+                )));
+            }
+            return Ok(Release::Approved(capability.holder));
+        }
+        let heartbeat = heartbeat.ok_or_else(|| {
+            SssError::Policy(
+                "this set is behind a dead man's switch; give the dealer's approval token or their latest heartbeat"
+                    .into(),
+            )
+        })?;
+        heartbeat.verify(&self.dealer_key, fingerprint)?;
+        if now < heartbeat.expires_at {
+            return Err(SssError::Policy(format!(
+                "the dealer checked in at {} and the heartbeat holds until {}; recovery needs their approval",
+                format_date(heartbeat.issued_at),
+                format_date(heartbeat.expires_at)
+            )));
+        }
+        Ok(Release::Lapsed(heartbeat.expires_at))
+    }
+}
+
+impl Heartbeat {
+    fn signed_bytes(fingerprint: &str, issued_at: u64, expires_at: u64) -> Vec<u8> {
+        let mut bytes = HEARTBEAT_CONTEXT.to_vec();
+        bytes.extend_from_slice(&issued_at.to_be_bytes());
+        bytes.extend_from_slice(&expires_at.to_be_bytes());
+        bytes.extend_from_slice(fingerprint.to_ascii_lowercase().as_bytes());
+        bytes
+    }
+
+    /// A heartbeat for set `fingerprint`, good for `valid_for` from now.
+    pub fn sign(key: &SigningKey, fingerprint: &str, valid_for: Duration) -> Heartbeat {
+        let issued_at = system_now();
+        let expires_at = issued_at.saturating_add(valid_for.as_secs());
+        let signature = key.sign(&Self::signed_bytes(fingerprint, issued_at, expires_at));
+        Heartbeat {
+            format_version: FORMAT_VERSION,
+            fingerprint: fingerprint.to_ascii_lowercase(),
+            issued_at,
+            expires_at,
+            signature: signature.to_bytes().to_vec(),
+        }
+    }
+
+    /// Checks that the dealer with `dealer_key` signed the heartbeat, for set `fingerprint`.
+    pub fn verify(&self, dealer_key: &[u8; KEY_LEN], fingerprint: &str) -> Result<()> {
+        let dealer = VerifyingKey::from_bytes(dealer_key)
+            .map_err(|_| SssError::Parse("the dealer key is not a valid Ed25519 key".into()))?;
+        let signature = Signature::from_slice(&self.signature)
+            .map_err(|_| SssError::Unauthorized("the heartbeat's signature is malformed".into()))?;
+        dealer
+            .verify(&Self::signed_bytes(&self.fingerprint, self.issued_at, self.expires_at), &signature)
+            .map_err(|_| SssError::Unauthorized("the heartbeat was not signed by the dealer".into()))?;
+        if !self.fingerprint.eq_ignore_ascii_case(fingerprint) {
+            return Err(SssError::Unauthorized(format!(
+                "the heartbeat is for set {}, not {}",
+                self.fingerprint, fingerprint
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes the heartbeat, replacing the previous one.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| SssError::Parse(e.to_string()))?;
+        std::fs::write(path, format!("{}\n", text))?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Heartbeat> {
+        let heartbeat: Heartbeat = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| SssError::Parse(format!("{} is not a heartbeat: {}", path.display(), e)))?;
+        if heartbeat.format_version != FORMAT_VERSION {
+            return Err(SssError::Parse(format!("unsupported heartbeat version {}", heartbeat.format_version)));
+        }
+        Ok(heartbeat)
+    }
+}
@@ -0,0 +1,43 @@
+//! OTLP export of the `tracing` spans emitted by the coordinator, the share stores and
+//! reconstruction. Spans only ever carry counts, lengths, share indices, fingerprints and
+//! error kinds: never share bytes, passwords, labels typed by guardians or error messages,
+//! which can quote the input they choke on.
+
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::error::{Result, SssError};
+
+const SERVICE_NAME: &str = "sss";
+
+/// Keeps the exporter alive; dropping it flushes any spans still buffered.
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+/// Starts exporting spans over OTLP/HTTP. `endpoint` is the collector's base URL (e.g.
+/// `http://localhost:4318`); without it the standard `OTEL_EXPORTER_OTLP_*` variables apply.
+pub fn init(endpoint: Option<&str>) -> Result<Telemetry> {
+    let mut exporter = SpanExporter::builder().with_http();
+    if let Some(endpoint) = endpoint {
+        exporter = exporter.with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')));
+    }
+    let exporter = exporter.build().map_err(|e| SssError::Io(std::io::Error::other(e)))?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME));
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))
+        .map_err(|e| SssError::Io(std::io::Error::other(e)))?;
+    Ok(Telemetry { provider })
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        let _ = self.provider.shutdown();
+    }
+}
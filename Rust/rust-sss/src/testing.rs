@@ -0,0 +1,99 @@
+//! Proptest strategies and assertions for checking the share format, here and in crates built
+//! on it. Enabled by the `testing` feature.
+
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+use crate::crypto::{kdf_rounds, split_payload};
+use crate::format::{share_conflict, validate_shares, ShareHeader};
+use crate::input;
+use crate::secret::{deserialize_secret, serialize_secret, SecretType};
+use crate::shamir::Field;
+
+/// A threshold and total that `split` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub threshold: u8,
+    pub total: u8,
+}
+
+/// Secrets of 1 to `max_len` arbitrary bytes.
+pub fn secrets(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    proptest::collection::vec(any::<u8>(), 1..=max_len.max(1))
+}
+
+/// Secrets as typed at the `encrypt` prompt: text, integers, `hex:` and `base64:` bytes.
+pub fn secret_inputs() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[^\\x00]{1,64}",
+        any::<i64>().prop_map(|n| n.to_string()),
+        secrets(64).prop_map(|b| format!("hex:{}", hex::encode(b))),
+        secrets(64).prop_map(|b| {
+            use base64::Engine;
+            format!("base64:{}", base64::engine::general_purpose::STANDARD.encode(b))
+        }),
+    ]
+}
+
+/// Totals from 1 to `max_total` with any threshold up to the total.
+pub fn params(max_total: u8) -> impl Strategy<Value = Params> {
+    (1..=max_total.max(1)).prop_flat_map(|total| (1..=total).prop_map(move |threshold| Params { threshold, total }))
+}
+
+/// The fields that split arbitrary bytes; secp256k1 only takes private keys.
+pub fn byte_fields() -> impl Strategy<Value = Field> {
+    prop_oneof![Just(Field::Gf256), Just(Field::Vault)]
+}
+
+/// Splits `secret` and checks that the quorum picked by `order` (a permutation key per share),
+/// and all the shares together, rebuild it.
+pub fn assert_round_trip(field: Field, secret: &[u8], params: Params, order: &[u8]) -> Result<(), TestCaseError> {
+    let shares = field.split(secret, params.threshold, params.total).map_err(fail)?;
+    prop_assert_eq!(shares.len(), params.total as usize);
+    let mut indices: Vec<usize> = (0..shares.len()).collect();
+    indices.sort_by_key(|&i| order.get(i).copied().unwrap_or_default());
+    for quorum in [params.threshold as usize, shares.len()] {
+        let picked: Vec<&Vec<u8>> = indices[..quorum].iter().map(|&i| &shares[i]).collect();
+        prop_assert_eq!(field.reconstruct(&picked).map_err(fail)?, secret.to_vec());
+    }
+    Ok(())
+}
+
+/// Checks that one share fewer than the threshold rebuilds something other than `secret`.
+/// The odds of a chance match are 256^-len, so give secrets of 16 bytes or more.
+pub fn assert_below_threshold(field: Field, secret: &[u8], params: Params) -> Result<(), TestCaseError> {
+    prop_assume!(params.threshold > 1 && secret.len() >= 16);
+    let shares = field.split(secret, params.threshold, params.total).map_err(fail)?;
+    let short = &shares[..params.threshold as usize - 1];
+    prop_assert_ne!(field.reconstruct(short).map_err(fail)?, secret.to_vec());
+    Ok(())
+}
+
+/// Serializing the secret behind `input` and reading it back gives the same bytes.
+pub fn assert_secret_round_trip(input: &str) -> Result<(), TestCaseError> {
+    let secret = SecretType::from_input(input).map_err(fail)?;
+    let serialized = serialize_secret(&secret);
+    let again = serialize_secret(&deserialize_secret(&serialized).map_err(fail)?);
+    prop_assert_eq!(&*again, &*serialized);
+    Ok(())
+}
+
+/// Feeds `text` to every parser of untrusted input. They may reject it, but must not panic.
+pub fn assert_parsers_total(text: &str) {
+    let _ = ShareHeader::split(text);
+    let parsed = input::parse_share_details(text);
+    let _ = deserialize_secret(text.as_bytes());
+    let _ = kdf_rounds(text.as_bytes());
+    let _ = split_payload(text.as_bytes());
+    let _ = SecretType::from_input(text);
+    if let Ok(parsed) = parsed {
+        let shares = [parsed.share.clone()];
+        let _ = validate_shares(&shares);
+        let _ = share_conflict(&[(parsed.share.clone(), None)], &parsed.share, None);
+        let _ = Field::Gf256.reconstruct(&shares);
+    }
+}
+
+fn fail(e: crate::SssError) -> TestCaseError {
+    TestCaseError::fail(e.to_string())
+}
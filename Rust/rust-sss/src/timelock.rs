@@ -0,0 +1,174 @@
+//! A not-before date on a share set, for inheritance plans where recovery should wait. The
+//! lock rides in front of the payload as `tlk1` ‖ seconds since the epoch (big-endian u64) ‖
+//! key length ‖ Roughtime key, and the ciphertext authenticates it, so a set whose date was
+//! edited no longer decrypts. `decrypt` and [`crate::pipeline::combine_secret`] refuse a set
+//! before its date; a lock with a Roughtime key only takes the date from a server signing with
+//! that key, not from the local clock. The lock keeps honest holders from acting early: a
+//! quorum that knows the password can always patch the check out of its copy of the tool.
+//! A dead man's switch ([`crate::switch`]) and the set's binding ([`SetBinding`]) ride in front
+//! of the payload the same way; all three are [`Conditions`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, SssError};
+use crate::format::SetBinding;
+use crate::roughtime::PUBLIC_KEY_LEN;
+use crate::switch::Switch;
+
+const MAGIC: &[u8; 4] = b"tlk1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLock {
+    /// Seconds since the Unix epoch, UTC.
+    pub not_before: u64,
+    /// Ed25519 key of the Roughtime server whose signed time the lock trusts.
+    pub roughtime_key: Option<[u8; PUBLIC_KEY_LEN]>,
+}
+
+impl TimeLock {
+    /// The bytes put in front of the payload, which its ciphertext authenticates.
+    pub fn header(&self) -> Vec<u8> {
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&self.not_before.to_be_bytes());
+        match &self.roughtime_key {
+            Some(key) => {
+                header.push(PUBLIC_KEY_LEN as u8);
+                header.extend_from_slice(key);
+            }
+            None => header.push(0),
+        }
+        header
+    }
+
+    /// The lock in front of `payload`, with its header bytes and the payload after them, or
+    /// `None` for a payload without one.
+    pub fn split(payload: &[u8]) -> Option<(TimeLock, &[u8], &[u8])> {
+        let rest = payload.strip_prefix(MAGIC)?;
+        let not_before = u64::from_be_bytes(rest.get(..8)?.try_into().ok()?);
+        let (roughtime_key, len) = match *rest.get(8)? {
+            0 => (None, 0),
+            len if len as usize == PUBLIC_KEY_LEN => (Some(rest.get(9..9 + PUBLIC_KEY_LEN)?.try_into().ok()?), len),
+            _ => return None,
+        };
+        let header_len = MAGIC.len() + 9 + len as usize;
+        Some((TimeLock { not_before, roughtime_key }, &payload[..header_len], &payload[header_len..]))
+    }
+
+    /// Refuses a recovery at `now` (seconds since the epoch) before the lock's date.
+    pub fn check(&self, now: u64) -> Result<()> {
+        if now >= self.not_before {
+            return Ok(());
+        }
+        Err(SssError::Policy(format!(
+            "this set is time-locked until {}; give --ignore-time-lock to recover it anyway",
+            format_date(self.not_before)
+        )))
+    }
+}
+
+/// What must hold before a set is recovered, carried in front of its payload and
+/// authenticated by its ciphertext.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Conditions {
+    pub time_lock: Option<TimeLock>,
+    pub switch: Option<Switch>,
+    pub binding: Option<SetBinding>,
+}
+
+impl Conditions {
+    pub fn is_empty(&self) -> bool {
+        self.time_lock.is_none() && self.switch.is_none() && self.binding.is_none()
+    }
+
+    /// The bytes put in front of the payload: the time lock, the switch, then the binding.
+    pub fn header(&self) -> Vec<u8> {
+        let mut header = self.time_lock.map(|lock| lock.header()).unwrap_or_default();
+        header.extend(self.switch.map(|switch| switch.header()).unwrap_or_default());
+        header.extend(self.binding.map(|binding| binding.header()).unwrap_or_default());
+        header
+    }
+
+    /// The conditions in front of `payload`, the bytes they take and the payload after them.
+    pub fn split(payload: &[u8]) -> (Conditions, &[u8], &[u8]) {
+        let mut conditions = Conditions::default();
+        let mut rest = payload;
+        if let Some((lock, _, after)) = TimeLock::split(rest) {
+            conditions.time_lock = Some(lock);
+            rest = after;
+        }
+        if let Some((switch, after)) = Switch::split(rest) {
+            conditions.switch = Some(switch);
+            rest = after;
+        }
+        if let Some((binding, after)) = SetBinding::split(rest) {
+            conditions.binding = Some(binding);
+            rest = after;
+        }
+        let (header, rest) = payload.split_at(payload.len() - rest.len());
+        (conditions, header, rest)
+    }
+}
+
+pub fn system_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Days from 1970-01-01 to the given civil date (proleptic Gregorian).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parses a UTC date, `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SSZ`, into seconds since the epoch.
+pub fn parse_date(text: &str) -> Result<u64> {
+    let invalid =
+        || SssError::InvalidArgument(format!("'{}' is not a date: use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ", text));
+    let text = text.trim();
+    let (date, time) = match text.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time.strip_suffix(['Z', 'z']).ok_or_else(invalid)?)),
+        None => (text, None),
+    };
+    let numbers = |part: &str, sep: char| -> Option<Vec<u32>> { part.split(sep).map(|n| n.parse().ok()).collect() };
+    let [year, month, day] = numbers(date, '-').and_then(|n| <[u32; 3]>::try_from(n).ok()).ok_or_else(invalid)?;
+    let [hour, minute, second] = match time {
+        Some(time) => numbers(time, ':').and_then(|n| <[u32; 3]>::try_from(n).ok()).ok_or_else(invalid)?,
+        None => [0, 0, 0],
+    };
+    let days_in_month = match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => 0,
+    };
+    if year < 1970 || day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+    let days = days_from_civil(year as i64, month, day) as u64;
+    Ok(days * 86_400 + u64::from(hour) * 3_600 + u64::from(minute) * 60 + u64::from(second))
+}
+
+/// `2030-01-01 00:00:00 UTC`
+pub fn format_date(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, time / 3_600, time / 60 % 60, time % 60)
+}
@@ -0,0 +1,149 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::secure::write_private;
+
+/// What every capability token starts with.
+pub const TOKEN_PREFIX: &str = "sss-cap-v1";
+
+/// What a capability token grants: one recovery of one share set, by one named person,
+/// before a deadline.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Capability {
+    pub id: String,
+    pub holder: String,
+    pub fingerprint: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn generate_key() -> SigningKey {
+    SigningKey::generate(&mut rand::rngs::OsRng)
+}
+
+/// Writes the dealer's signing key as hex, readable only by the owner on Unix.
+pub fn write_signing_key<P: AsRef<Path>>(path: P, key: &SigningKey) -> Result<()> {
+    write_private(path, Zeroizing::new(hex::encode(key.to_bytes())).as_bytes())
+}
+
+pub fn read_signing_key<P: AsRef<Path>>(path: P) -> Result<SigningKey> {
+    let text = Zeroizing::new(fs::read_to_string(path)?);
+    let bytes = Zeroizing::new(
+        hex::decode(text.trim()).map_err(|_| SssError::Parse("signing key file is not hex".into()))?,
+    );
+    let bytes: &[u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| SssError::Parse("signing key must be 32 bytes".into()))?;
+    Ok(SigningKey::from_bytes(bytes))
+}
+
+pub fn parse_verifying_key(text: &str) -> Result<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(text.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| SssError::Parse("issuer key must be 64 hex digits".into()))?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| SssError::Parse("issuer key is not a valid Ed25519 public key".into()))
+}
+
+/// Issues a token letting `holder` run one recovery ceremony for the set `fingerprint`.
+pub fn issue(key: &SigningKey, holder: &str, fingerprint: &str, valid_for: Duration) -> Result<String> {
+    let mut id = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut id);
+    let issued_at = unix_now();
+    let capability = Capability {
+        id: hex::encode(id),
+        holder: holder.to_string(),
+        fingerprint: fingerprint.to_string(),
+        issued_at,
+        expires_at: issued_at + valid_for.as_secs(),
+    };
+    let payload = BASE64URL.encode(
+        serde_json::to_vec(&capability).map_err(|e| SssError::Parse(e.to_string()))?,
+    );
+    let signed = format!("{}.{}", TOKEN_PREFIX, payload);
+    let signature = key.sign(signed.as_bytes());
+    Ok(format!("{}.{}", signed, BASE64URL.encode(signature.to_bytes())))
+}
+
+/// Checks the signature and expiry of a token; it does not check whether it was already used.
+pub fn verify(token: &str, issuer: &VerifyingKey) -> Result<Capability> {
+    let token = token.trim();
+    let (signed, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| SssError::Unauthorized("malformed capability token".into()))?;
+    let payload = signed
+        .strip_prefix(TOKEN_PREFIX)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .ok_or_else(|| SssError::Unauthorized("not an sss capability token".into()))?;
+    let signature = BASE64URL
+        .decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| SssError::Unauthorized("malformed token signature".into()))?;
+    issuer
+        .verify(signed.as_bytes(), &signature)
+        .map_err(|_| SssError::Unauthorized("token was not signed by the issuer key".into()))?;
+
+    let capability: Capability = BASE64URL
+        .decode(payload)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| SssError::Unauthorized("malformed token payload".into()))?;
+    if unix_now() >= capability.expires_at {
+        return Err(SssError::Unauthorized(format!("token for {} has expired", capability.holder)));
+    }
+    Ok(capability)
+}
+
+/// Append-only record of token ids that have been spent on a ceremony.
+#[derive(Clone)]
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Ledger { path: path.into() }
+    }
+
+    /// Marks the token as used on the set `fingerprint`, which the shares were found to
+    /// rebuild, failing without using it up if it covers another set, and failing if it
+    /// already was used.
+    pub fn spend(&self, capability: &Capability, fingerprint: &str) -> Result<()> {
+        if !fingerprint.eq_ignore_ascii_case(capability.fingerprint.trim()) {
+            return Err(SssError::Unauthorized(format!(
+                "these shares belong to set {}, the token only covers {}",
+                fingerprint, capability.fingerprint
+            )));
+        }
+        let spent = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if spent.lines().any(|line| line.split_whitespace().next() == Some(capability.id.as_str())) {
+            return Err(SssError::Unauthorized(format!(
+                "token {} for {} has already been used",
+                capability.id, capability.holder
+            )));
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{} {} {}", capability.id, capability.fingerprint, unix_now())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
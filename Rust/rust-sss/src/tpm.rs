@@ -0,0 +1,203 @@
+//! A share sealed to this machine's TPM 2.0 under a PCR policy, so it only comes back while the
+//! machine boots into the same measured state. Sealed objects hold at most 128 bytes, so the
+//! TPM seals a random key and the share is encrypted under it with AES-256-GCM. The TPM work
+//! is done by the tpm2-tools commands; `decrypt` takes a sealed file (`@share.tpm.json`)
+//! wherever it takes a share file.
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{aead::generic_array::GenericArray, Aes256Gcm, KeyInit};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+use crate::crypto::NONCE_LEN;
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::secure::write_private;
+
+pub const SEALED_SUFFIX: &str = ".tpm.json";
+/// Firmware, option ROMs, boot loader and Secure Boot state.
+pub const DEFAULT_PCRS: &str = "sha256:0,2,4,7";
+const KEY_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SealedShare {
+    pub format_version: u8,
+    pub index: u8,
+    /// The short ID printed next to the share when it was split.
+    pub id: String,
+    /// PCR selection of the unseal policy, as tpm2-tools writes it (`sha256:0,2,4,7`).
+    pub pcrs: String,
+    /// The sealed key object, as `tpm2_create` wrote it.
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub public: Vec<u8>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub private: Vec<u8>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub nonce: Vec<u8>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub ciphertext: Vec<u8>,
+}
+
+fn tpm_error(what: impl std::fmt::Display) -> SssError {
+    SssError::Store(format!("TPM: {}", what))
+}
+
+pub fn is_sealed(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(SEALED_SUFFIX)
+}
+
+/// Checks a PCR selection such as `sha256:0,2,4,7` before it reaches the TPM.
+pub fn parse_pcrs(spec: &str) -> Result<String> {
+    let spec = spec.trim().to_ascii_lowercase();
+    let valid = spec.split_once(':').is_some_and(|(bank, list)| {
+        matches!(bank, "sha1" | "sha256" | "sha384" | "sha512")
+            && !list.is_empty()
+            && list.split(',').all(|pcr| pcr.parse::<u8>().is_ok_and(|n| n < 24))
+    });
+    if !valid {
+        return Err(SssError::InvalidArgument(format!(
+            "'{}' is not a PCR selection: give a bank and PCRs 0-23, like {}",
+            spec, DEFAULT_PCRS
+        )));
+    }
+    Ok(spec)
+}
+
+/// Scratch directory for the tpm2-tools context files, removed on drop.
+struct WorkDir(PathBuf);
+
+impl WorkDir {
+    fn new() -> Result<Self> {
+        let mut name = [0u8; 8];
+        rand::rngs::OsRng.fill_bytes(&mut name);
+        let dir = std::env::temp_dir().join(format!("sss-tpm-{}", hex::encode(name)));
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut std::fs::DirBuilder::new(), 0o700).create(&dir)?;
+        #[cfg(not(unix))]
+        std::fs::DirBuilder::new().create(&dir)?;
+        Ok(WorkDir(dir))
+    }
+
+    fn file(&self, name: &str) -> String {
+        self.0.join(name).display().to_string()
+    }
+
+    /// Runs one tpm2-tools command, feeding it `input`, and returns its standard output.
+    fn run(&self, tool: &str, args: &[&str], input: Option<&[u8]>) -> Result<Zeroizing<Vec<u8>>> {
+        let mut child = Command::new(tool)
+            .args(args)
+            .current_dir(&self.0)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| tpm_error(format!("could not run {} (is tpm2-tools installed?): {}", tool, e)))?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input)?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(tpm_error(format!("{} failed: {}", tool, stderr.trim())));
+        }
+        Ok(Zeroizing::new(output.stdout))
+    }
+
+    /// Recreates the owner-hierarchy primary key. The template is fixed, so the TPM derives
+    /// the same key every time and nothing about it needs storing.
+    fn primary(&self) -> Result<String> {
+        let primary = self.file("primary.ctx");
+        self.run("tpm2_createprimary", &["-Q", "-C", "o", "-g", "sha256", "-G", "ecc", "-c", &primary], None)?;
+        Ok(primary)
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Seals `share` to this TPM under a policy on the current values of `pcrs`.
+pub fn seal(share: &[u8], pcrs: &str) -> Result<SealedShare> {
+    let pcrs = parse_pcrs(pcrs)?;
+    let index = *share.first().filter(|_| share.len() > 1).ok_or_else(|| tpm_error("that is not a share"))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    rand::rngs::OsRng.fill_bytes(key.as_mut_slice());
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+        .encrypt(GenericArray::from_slice(&nonce), Payload { msg: share, aad: pcrs.as_bytes() })?;
+
+    let dir = WorkDir::new()?;
+    let primary = dir.primary()?;
+    let policy = dir.file("pcr.policy");
+    dir.run("tpm2_createpolicy", &["-Q", "--policy-pcr", "-l", &pcrs, "-L", &policy], None)?;
+    let (public, private) = (dir.file("seal.pub"), dir.file("seal.priv"));
+    dir.run(
+        "tpm2_create",
+        &[
+            "-Q", "-C", &primary, "-L", &policy, "-a", "fixedtpm|fixedparent|adminwithpolicy|noda", "-i", "-", "-u",
+            &public, "-r", &private,
+        ],
+        Some(key.as_slice()),
+    )?;
+
+    Ok(SealedShare {
+        format_version: FORMAT_VERSION,
+        index,
+        id: hex::encode(&Sha256::digest(share)[..4]),
+        pcrs,
+        public: std::fs::read(&public)?,
+        private: std::fs::read(&private)?,
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Unseals the key, which the TPM only releases while the PCRs hold their sealed values, and
+/// decrypts the share.
+pub fn unseal(sealed: &SealedShare) -> Result<Zeroizing<Vec<u8>>> {
+    let pcrs = parse_pcrs(&sealed.pcrs)?;
+    if sealed.nonce.len() != NONCE_LEN {
+        return Err(SssError::Parse("TPM-sealed share has a malformed nonce".into()));
+    }
+    let dir = WorkDir::new()?;
+    let primary = dir.primary()?;
+    let (public, private, object) = (dir.file("seal.pub"), dir.file("seal.priv"), dir.file("seal.ctx"));
+    std::fs::write(&public, &sealed.public)?;
+    std::fs::write(&private, &sealed.private)?;
+    dir.run("tpm2_load", &["-Q", "-C", &primary, "-u", &public, "-r", &private, "-c", &object], None)?;
+    let key = dir
+        .run("tpm2_unseal", &["-c", &object, "-p", &format!("pcr:{}", pcrs)], None)
+        .map_err(|e| tpm_error(format!("the key would not unseal; has the boot state changed? ({})", e)))?;
+    if key.len() != KEY_LEN {
+        return Err(tpm_error("the unsealed key has the wrong length"));
+    }
+    let share = Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+        .decrypt(
+            GenericArray::from_slice(&sealed.nonce),
+            Payload { msg: &sealed.ciphertext, aad: pcrs.as_bytes() },
+        )
+        .map_err(|_| tpm_error("the sealed share does not decrypt under the unsealed key"))?;
+    Ok(Zeroizing::new(share))
+}
+
+/// Writes a sealed share (mode 0600), refusing to overwrite an existing file.
+pub fn write(path: &Path, sealed: &SealedShare) -> Result<()> {
+    let text = serde_json::to_string_pretty(sealed).map_err(|e| SssError::Parse(e.to_string()))?;
+    write_private(path, (text + "\n").as_bytes())
+}
+
+/// Reads a sealed share file and unseals it.
+pub fn read(path: &Path) -> Result<Zeroizing<Vec<u8>>> {
+    let text = std::fs::read_to_string(path)?;
+    let sealed: SealedShare = serde_json::from_str(&text)
+        .map_err(|e| SssError::Parse(format!("{} is not a TPM-sealed share: {}", path.display(), e)))?;
+    unseal(&sealed)
+}
@@ -0,0 +1,109 @@
+use serde::{Serialize, Deserialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, SssError};
+
+/// One step of an interactive session. Secret answers are never stored: the `value` of a
+/// redacted answer is `None`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    Started { operation: String, version: String, unix_time: u64 },
+    Prompt { prompt: String },
+    Answer { prompt: String, value: Option<String>, elapsed_ms: u64 },
+    Validation { subject: String, ok: bool, detail: String },
+    Finished { ok: bool, detail: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// Appends events to a JSON-lines file as they happen, so an interrupted session still
+/// leaves a usable record behind.
+pub struct Transcript {
+    file: File,
+    start: Instant,
+}
+
+impl Transcript {
+    pub fn create<P: AsRef<Path>>(path: P, operation: &str) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut transcript = Transcript { file, start: Instant::now() };
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        transcript.record(Event::Started {
+            operation: operation.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            unix_time,
+        })?;
+        Ok(transcript)
+    }
+
+    pub fn record(&mut self, event: Event) -> Result<()> {
+        let entry = Entry {
+            at_ms: self.start.elapsed().as_millis() as u64,
+            event,
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| SssError::Parse(format!("could not encode transcript entry: {}", e)))?;
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line)
+            .map_err(|e| SssError::Parse(format!("transcript line {}: {}", i + 1, e)))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+impl Entry {
+    pub fn describe(&self) -> String {
+        let at = format!("[+{:>7.1}s]", self.at_ms as f64 / 1000.0);
+        match &self.event {
+            Event::Started { operation, version, unix_time } => {
+                format!("{} started {} (version {}, unix time {})", at, operation, version, unix_time)
+            }
+            Event::Prompt { prompt } => format!("{} prompt: {}", at, prompt),
+            Event::Answer { prompt, value, elapsed_ms } => format!(
+                "{} answer to \"{}\": {} (after {:.1}s)",
+                at,
+                prompt,
+                value.as_deref().unwrap_or("<redacted>"),
+                *elapsed_ms as f64 / 1000.0
+            ),
+            Event::Validation { subject, ok, detail } => format!(
+                "{} {} {}: {}",
+                at,
+                if *ok { "passed" } else { "FAILED" },
+                subject,
+                detail
+            ),
+            Event::Finished { ok, detail } => format!(
+                "{} finished {}: {}",
+                at,
+                if *ok { "successfully" } else { "with an error" },
+                detail
+            ),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! A full-screen recovery ceremony for `decrypt --tui`: the shares entered so far with their
+//! status and headers side by side, how close the quorum is, masked share and password fields
+//! and a last confirmation before the secret is recovered. Checking an entered share is left
+//! to the caller, which runs it through the same checks as the prompts.
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use zeroize::Zeroizing;
+
+use crate::error::{Result, SssError};
+use crate::format::ShareHeader;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Accepted,
+    /// A sub-share, waiting for the others of its parent.
+    Pending,
+    Rejected,
+}
+
+/// One entry in the ceremony, as the caller's checks left it.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub subject: String,
+    pub status: Status,
+    pub index: Option<u8>,
+    /// First four bytes of the share's SHA-256, in hex.
+    pub id: Option<String>,
+    pub header: ShareHeader,
+    /// What the checks had to say about it, such as why it was rejected.
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Shares,
+    Password,
+    Confirm,
+}
+
+/// What a key press led to.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    /// The shares were confirmed; this is the password typed for them.
+    Recover(Zeroizing<String>),
+    Cancel,
+}
+
+/// The ceremony's state, apart from the terminal it is drawn on.
+pub struct Ceremony {
+    entries: Vec<Entry>,
+    threshold: Option<u8>,
+    stage: Stage,
+    input: Zeroizing<String>,
+    password: Zeroizing<String>,
+    selected: ListState,
+    warning: Option<String>,
+}
+
+impl Ceremony {
+    /// A ceremony for a set needing `threshold` shares, when that is known up front.
+    pub fn new(threshold: Option<u8>) -> Ceremony {
+        Ceremony {
+            entries: Vec::new(),
+            threshold,
+            stage: Stage::Shares,
+            input: Zeroizing::new(String::new()),
+            password: Zeroizing::new(String::new()),
+            selected: ListState::default(),
+            warning: None,
+        }
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn accepted(&self) -> usize {
+        self.entries.iter().filter(|e| e.status == Status::Accepted).count()
+    }
+
+    /// Takes in one key press. `check` is handed each entered share and reports what it made
+    /// of it, along with the threshold the shares so far declare.
+    pub fn key<F>(&mut self, key: KeyEvent, check: &mut F) -> Result<Step>
+    where
+        F: FnMut(&str) -> Result<(Entry, Option<u8>)>,
+    {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(Step::Cancel);
+        }
+        self.warning = None;
+        match (self.stage, key.code) {
+            (Stage::Shares, KeyCode::Esc) => return Ok(Step::Cancel),
+            (Stage::Shares, KeyCode::Up) => self.selected.select_previous(),
+            (Stage::Shares, KeyCode::Down) => self.selected.select_next(),
+            (Stage::Shares, KeyCode::Enter) if self.input.trim().is_empty() => {
+                let missing = self.threshold.map_or(0, |t| (t as usize).saturating_sub(self.accepted()));
+                match (self.accepted(), missing) {
+                    (0, _) => self.warning = Some("Enter a share first".into()),
+                    (_, 0) => self.stage = Stage::Password,
+                    (_, missing) => self.warning = Some(format!("{} more share(s) are needed", missing)),
+                }
+            }
+            (Stage::Shares, KeyCode::Enter) => {
+                let entered = Zeroizing::new(std::mem::take(&mut *self.input));
+                let (entry, threshold) = check(entered.trim())?;
+                self.threshold = self.threshold.or(threshold);
+                self.entries.push(entry);
+                self.selected.select(Some(self.entries.len() - 1));
+            }
+            (Stage::Password, KeyCode::Esc) => {
+                self.password = Zeroizing::new(String::new());
+                self.stage = Stage::Shares;
+            }
+            (Stage::Password, KeyCode::Enter) => self.stage = Stage::Confirm,
+            (Stage::Confirm, KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter) => {
+                return Ok(Step::Recover(std::mem::take(&mut self.password)));
+            }
+            (Stage::Confirm, KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc) => self.stage = Stage::Password,
+            (Stage::Shares | Stage::Password, KeyCode::Backspace) => {
+                self.field().pop();
+            }
+            (Stage::Shares | Stage::Password, KeyCode::Char(c)) => self.field().push(c),
+            _ => {}
+        }
+        Ok(Step::Continue)
+    }
+
+    fn field(&mut self) -> &mut Zeroizing<String> {
+        match self.stage {
+            Stage::Password => &mut self.password,
+            _ => &mut self.input,
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [title, body, gauge, field, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(5),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list, details] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(body);
+
+        frame.render_widget(Line::from("Recovery ceremony").style(Style::new().add_modifier(Modifier::BOLD)), title);
+        let items: Vec<ListItem> = self.entries.iter().map(entry_line).collect();
+        let list_widget = List::new(items)
+            .block(Block::bordered().title(" Shares "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list_widget, list, &mut self.selected);
+        let shown = self.selected.selected().and_then(|i| self.entries.get(i));
+        frame.render_widget(
+            Paragraph::new(shown.map(entry_details).unwrap_or_default())
+                .block(Block::bordered().title(" Details "))
+                .wrap(Wrap { trim: false }),
+            details,
+        );
+
+        let accepted = self.accepted();
+        let (ratio, label) = match self.threshold {
+            Some(threshold) => (
+                (accepted as f64 / threshold as f64).min(1.0),
+                format!("{} of {} shares", accepted, threshold),
+            ),
+            None => (0.0, format!("{} share(s), threshold not yet known", accepted)),
+        };
+        let color = if self.threshold.is_some_and(|t| accepted >= t as usize) { Color::Green } else { Color::Yellow };
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(" Quorum "))
+                .gauge_style(Style::new().fg(color))
+                .ratio(ratio)
+                .label(label),
+            gauge,
+        );
+
+        let (prompt, typed) = match self.stage {
+            Stage::Password => (" Encryption password ", &self.password),
+            _ => (" Share (paste it, or @path to a share file) ", &self.input),
+        };
+        let masked = "•".repeat(typed.chars().count());
+        frame.render_widget(Paragraph::new(masked).block(Block::bordered().title(prompt)), field);
+
+        let hint = match (&self.warning, self.stage) {
+            (Some(warning), _) => Line::from(warning.as_str()).style(Style::new().fg(Color::Red)),
+            (None, Stage::Shares) => Line::from("Enter: add share, empty to finish · ↑/↓: inspect · Esc: cancel"),
+            (None, _) => Line::from("Enter: continue · Esc: back"),
+        };
+        frame.render_widget(hint, help);
+
+        if self.stage == Stage::Confirm {
+            let area = centered(frame.area(), 60, 7 + accepted as u16);
+            let mut lines = vec![Line::from(format!("Recover the secret from these {} shares?", accepted)), Line::from("")];
+            lines.extend(self.entries.iter().filter(|e| e.status == Status::Accepted).map(|e| {
+                Line::from(format!("  #{} {}", e.index.unwrap_or_default(), e.header.label.as_deref().unwrap_or("")))
+            }));
+            lines.push(Line::from(""));
+            lines.push(Line::from("y: recover · n: back"));
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Confirm ")), area);
+        }
+    }
+}
+
+fn entry_line(entry: &Entry) -> ListItem<'_> {
+    let (mark, color) = match entry.status {
+        Status::Accepted => ("✓", Color::Green),
+        Status::Pending => ("…", Color::Yellow),
+        Status::Rejected => ("✗", Color::Red),
+    };
+    let mut spans = vec![Span::styled(format!("{} ", mark), Style::new().fg(color)), Span::raw(entry.subject.clone())];
+    if let Some(index) = entry.index {
+        spans.push(Span::raw(format!(" (index {})", index)));
+    }
+    if let Some(label) = &entry.header.label {
+        spans.push(Span::raw(format!(" {}", label)));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+fn entry_details(entry: &Entry) -> Vec<Line<'_>> {
+    let header = &entry.header;
+    let quorum = header.quorum.map(|(threshold, total)| format!("{} of {}", threshold, total));
+    let fields = [
+        ("Index", entry.index.map(|i| i.to_string())),
+        ("ID", entry.id.clone()),
+        ("Label", header.label.clone()),
+        ("Note", header.note.clone()),
+        ("Set", header.set.clone()),
+        ("Quorum", quorum),
+    ];
+    let mut lines: Vec<Line> = fields
+        .into_iter()
+        .filter_map(|(name, value)| Some(Line::from(format!("{}: {}", name, value?))))
+        .collect();
+    lines.extend(entry.notes.iter().map(|note| Line::from(note.as_str())));
+    lines
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height)
+}
+
+/// Runs the ceremony on the terminal until the shares are confirmed, returning the password
+/// typed for them.
+pub fn run<F>(threshold: Option<u8>, mut check: F) -> Result<Zeroizing<String>>
+where
+    F: FnMut(&str) -> Result<(Entry, Option<u8>)>,
+{
+    let mut terminal = ratatui::init();
+    let mut ceremony = Ceremony::new(threshold);
+    let step = loop {
+        if let Err(e) = terminal.draw(|frame| ceremony.draw(frame)) {
+            break Err(e.into());
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match ceremony.key(key, &mut check) {
+                Ok(Step::Continue) => {}
+                step => break step,
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    ratatui::restore();
+    match step? {
+        Step::Recover(password) => Ok(password),
+        _ => Err(SssError::Io(std::io::Error::new(
+            std::io::ErrorKind::Interrupted,
+            "the recovery ceremony was cancelled",
+        ))),
+    }
+}
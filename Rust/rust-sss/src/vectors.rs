@@ -0,0 +1,174 @@
+//! Canonical test vectors for ports of the share format to other languages. Each vector runs
+//! one secret through the whole pipeline (serialize, pad, PBKDF2, AES-256-GCM, split) with the
+//! randomness drawn from its name, so `sss encrypt --deterministic-seed <name>` with the same
+//! answers prints the same shares. `verify` accepts vectors from any implementation: it only
+//! checks that the shares rebuild the payload and the payload opens to the secret.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{kdf_rounds, open_payload, seeded_rng};
+use crate::error::{Result, SssError};
+use crate::format::{SetBinding, FORMAT_VERSION};
+use crate::pipeline;
+use crate::secret::{deserialize_secret, pad, serialize_secret, Padding, SecretType};
+use crate::shamir::{self, Field};
+use crate::timelock::Conditions;
+
+/// Cheap enough that verifying every vector takes well under a second.
+pub const VECTOR_KDF_ROUNDS: u32 = 1_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VectorFile {
+    pub format_version: u8,
+    pub generator: String,
+    pub vectors: Vec<TestVector>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// Also the seed of the vector's randomness.
+    pub name: String,
+    pub field: String,
+    /// The secret as typed at the `encrypt` prompt (`hex:`, `json:` and so on).
+    pub secret: String,
+    pub padding: String,
+    /// Hex of the serialized and padded secret, before encryption.
+    pub plaintext: String,
+    pub password: String,
+    pub kdf_rounds: u32,
+    pub threshold: u8,
+    pub total: u8,
+    /// Hex of set binding ‖ KDF header ‖ salt ‖ nonce ‖ ciphertext, the value that is split.
+    /// Vectors from before sets were bound leave the binding out.
+    pub payload: String,
+    /// Hex shares, index byte first.
+    pub shares: Vec<String>,
+}
+
+struct Case {
+    name: &'static str,
+    field: Field,
+    secret: &'static str,
+    padding: &'static str,
+    threshold: u8,
+    total: u8,
+}
+
+const CASES: [Case; 5] = [
+    Case { name: "text-2-of-3", field: Field::Gf256, secret: "hello, world", padding: "pow2", threshold: 2, total: 3 },
+    Case {
+        name: "bytes-3-of-5",
+        field: Field::Gf256,
+        secret: "hex:000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        padding: "minimum",
+        threshold: 3,
+        total: 5,
+    },
+    Case { name: "int-5-of-5", field: Field::Gf256, secret: "42", padding: "pow2", threshold: 5, total: 5 },
+    Case {
+        name: "json-3-of-10",
+        field: Field::Gf256,
+        secret: r#"json:{"user":"admin","pin":"0000"}"#,
+        padding: "bucket:48",
+        threshold: 3,
+        total: 10,
+    },
+    Case { name: "vault-2-of-4", field: Field::Vault, secret: "Grüße 🔑", padding: "pow2", threshold: 2, total: 4 },
+];
+
+/// The canonical vectors. They change only with the share format, and files published before
+/// a change keep verifying.
+pub fn generate() -> Result<VectorFile> {
+    let vectors = CASES.iter().map(generate_one).collect::<Result<_>>()?;
+    Ok(VectorFile {
+        format_version: FORMAT_VERSION,
+        generator: format!("shamir-cli {}", env!("CARGO_PKG_VERSION")),
+        vectors,
+    })
+}
+
+fn generate_one(case: &Case) -> Result<TestVector> {
+    let password = format!("password for {}", case.name);
+    let padding: Padding = case.padding.parse().map_err(SssError::Parse)?;
+    let plaintext = pad(&serialize_secret(&SecretType::from_input(case.secret)?), padding)?;
+
+    // sealed the way `sss encrypt` seals each field: GF(256) sets in two slots
+    let mut rng = seeded_rng(case.name);
+    let payload = match case.field {
+        Field::Gf256 => pipeline::seal_bound(&plaintext, &password, VECTOR_KDF_ROUNDS, case.threshold, &mut rng)?,
+        _ => {
+            let binding = SetBinding::new(case.threshold, &mut rng);
+            let conditions = Conditions { binding: Some(binding), ..Conditions::default() };
+            pipeline::seal_locked(&plaintext, &password, VECTOR_KDF_ROUNDS, &conditions, &mut rng)?
+        }
+    };
+    let shares = case.field.split_with_rng(&payload, case.threshold, case.total, &mut rng)?;
+
+    Ok(TestVector {
+        name: case.name.to_string(),
+        field: case.field.to_string(),
+        secret: case.secret.to_string(),
+        padding: case.padding.to_string(),
+        plaintext: hex::encode(plaintext.as_slice()),
+        password,
+        kdf_rounds: VECTOR_KDF_ROUNDS,
+        threshold: case.threshold,
+        total: case.total,
+        payload: hex::encode(payload.as_slice()),
+        shares: shares.iter().map(hex::encode).collect(),
+    })
+}
+
+fn decode(what: &str, text: &str) -> Result<Vec<u8>> {
+    hex::decode(text).map_err(|e| SssError::Parse(format!("{} is not hex: {}", what, e)))
+}
+
+/// Checks one vector: every quorum (or a sample of 64) rebuilds the payload, one share fewer
+/// does not, the payload names its KDF rounds and opens to the plaintext, and the plaintext
+/// holds the secret.
+pub fn verify(vector: &TestVector) -> Result<()> {
+    let field: Field = vector.field.parse()?;
+    let payload = decode("payload", &vector.payload)?;
+    let shares = vector
+        .shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| decode(&format!("share {}", i + 1), s))
+        .collect::<Result<Vec<_>>>()?;
+    if shares.len() != vector.total as usize {
+        return Err(SssError::Share(format!("{} shares listed but total is {}", shares.len(), vector.total)));
+    }
+
+    let quorums = shamir::quorums(vector.threshold, vector.total, 64, &mut seeded_rng(&vector.name));
+    for quorum in &quorums {
+        let picked: Vec<&Vec<u8>> = quorum.iter().map(|&i| &shares[i]).collect();
+        if *field.reconstruct(&picked)? != *payload {
+            let names: Vec<String> = quorum.iter().map(|i| (i + 1).to_string()).collect();
+            return Err(SssError::Share(format!("shares {} do not rebuild the payload", names.join(", "))));
+        }
+    }
+    if vector.threshold > 1 && *field.reconstruct(&shares[..vector.threshold as usize - 1])? == *payload {
+        return Err(SssError::Share("fewer shares than the threshold rebuild the payload".into()));
+    }
+
+    match kdf_rounds(Conditions::split(&payload).2) {
+        Some((rounds, _)) if rounds == vector.kdf_rounds => {}
+        found => {
+            return Err(SssError::Kdf(format!(
+                "payload names {} PBKDF2 rounds, vector says {}",
+                found.map_or("no".to_string(), |(rounds, _)| rounds.to_string()),
+                vector.kdf_rounds
+            )))
+        }
+    }
+    let plaintext = open_payload(&payload, &vector.password)?;
+    if hex::encode(&*plaintext) != vector.plaintext.to_ascii_lowercase() {
+        return Err(SssError::Parse("the payload does not decrypt to the listed plaintext".into()));
+    }
+    let recovered = serialize_secret(&deserialize_secret(&plaintext)?);
+    let expected = serialize_secret(&SecretType::from_input(&vector.secret)?);
+    if *recovered != *expected {
+        return Err(SssError::Parse("the plaintext does not hold the listed secret".into()));
+    }
+    Ok(())
+}
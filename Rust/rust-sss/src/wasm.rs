@@ -0,0 +1,87 @@
+//! JavaScript bindings for an offline recovery page. Build with
+//! `cargo rustc --lib --crate-type cdylib --target wasm32-unknown-unknown --no-default-features
+//! --features wasm` and run `wasm-bindgen` on the output. Shares go in and out as text in any encoding
+//! `sss decrypt` reads, so they can be pasted straight from what `sss encrypt` printed. The
+//! guardian page of `sss coordinate` loads the same build to seal shares on the phone.
+
+use wasm_bindgen::prelude::*;
+
+use crate::coordinator;
+use crate::crypto::{open_payload, PBKDF2_ROUNDS};
+use crate::format::ShareEncoding;
+use crate::input;
+use crate::pipeline;
+use crate::secret::{Padding, SecretType};
+
+fn js(e: impl std::fmt::Display) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+/// Encrypts and splits `secret` (typed as at the `encrypt` prompt: text, `hex:`, `json:`...)
+/// and returns the shares in `encoding` (`list`, `hex`, `base64`, `base58`, `bech32` or `ur`).
+#[wasm_bindgen]
+pub fn split(
+    secret: &str,
+    password: &str,
+    threshold: u8,
+    total: u8,
+    encoding: &str,
+) -> Result<Vec<String>, JsError> {
+    let encoding: ShareEncoding = encoding.parse().map_err(js)?;
+    let secret = SecretType::from_input(secret).map_err(js)?;
+    let shares = pipeline::split_secret(
+        &secret,
+        password,
+        threshold,
+        total,
+        Padding::default(),
+        PBKDF2_ROUNDS,
+        &mut rand::rngs::OsRng,
+    )
+    .map_err(js)?;
+    shares.iter().map(|share| encoding.encode(share).map_err(js)).collect()
+}
+
+/// Recovers the secret from a quorum of shares and returns it as `sss decrypt` prints it.
+#[wasm_bindgen]
+pub fn combine(shares: Vec<String>, password: &str) -> Result<String, JsError> {
+    let shares = shares.iter().map(|s| input::parse_share(s).map_err(js)).collect::<Result<Vec<_>, _>>()?;
+    let secret = pipeline::combine_secret(&shares, password).map_err(js)?;
+    Ok(pipeline::secret_text(&secret).map_err(js)?.as_str().to_owned())
+}
+
+/// Encrypts raw bytes into a KDF header ‖ salt ‖ nonce ‖ ciphertext payload.
+#[wasm_bindgen]
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Vec<u8>, JsError> {
+    Ok(pipeline::seal(plaintext, password, PBKDF2_ROUNDS, &mut rand::rngs::OsRng).map_err(js)?.to_vec())
+}
+
+#[wasm_bindgen]
+pub fn decrypt(payload: &[u8], password: &str) -> Result<Vec<u8>, JsError> {
+    Ok(open_payload(payload, password).map_err(js)?.to_vec())
+}
+
+#[wasm_bindgen(js_name = encodeShare)]
+pub fn encode_share(share: &[u8], encoding: &str) -> Result<String, JsError> {
+    encoding.parse::<ShareEncoding>().map_err(js)?.encode(share).map_err(js)
+}
+
+/// Reads a share in any encoding `sss decrypt` accepts, header lines included.
+#[wasm_bindgen(js_name = decodeShare)]
+pub fn decode_share(text: &str) -> Result<Vec<u8>, JsError> {
+    input::parse_share(text).map_err(js)
+}
+
+/// Seals `share` to a coordinator's session key (SEC1 bytes) and returns the JSON body the
+/// coordinator's `/contribute` takes.
+#[wasm_bindgen(js_name = sealContribution)]
+pub fn seal_contribution(coordinator_key: &[u8], share: &str, label: Option<String>) -> Result<String, JsError> {
+    let contribution = coordinator::seal(coordinator_key, share, label, &mut rand::rngs::OsRng).map_err(js)?;
+    serde_json::to_string(&contribution).map_err(js)
+}
+
+/// The session code the coordinator prints for its key, worked out on the guardian's side.
+#[wasm_bindgen(js_name = sessionCode)]
+pub fn session_code(coordinator_key: &[u8]) -> String {
+    coordinator::session_code(coordinator_key)
+}
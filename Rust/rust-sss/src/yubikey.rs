@@ -0,0 +1,150 @@
+//! A share wrapped by a YubiKey's HMAC-SHA1 challenge-response slot, so the hardware token is
+//! the custodian. A random challenge goes to the key, and its response, hashed with the
+//! challenge, is the AES-256-GCM key the share is encrypted under; only the challenge and the
+//! ciphertext are written out. The YubiKey is driven through `ykman`; `decrypt` takes a wrapped
+//! file (`@share.yubikey.json`) wherever it takes a share file.
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{aead::generic_array::GenericArray, Aes256Gcm, KeyInit};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+use crate::crypto::NONCE_LEN;
+use crate::error::{Result, SssError};
+use crate::format::FORMAT_VERSION;
+use crate::secure::write_private;
+
+pub const WRAPPED_SUFFIX: &str = ".yubikey.json";
+/// The long-press slot, which YubiKeys leave free for challenge-response.
+pub const DEFAULT_SLOT: u8 = 2;
+const CHALLENGE_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WrappedShare {
+    pub format_version: u8,
+    pub index: u8,
+    /// The short ID printed next to the share when it was split.
+    pub id: String,
+    pub slot: u8,
+    /// Serial number of the YubiKey that wrapped the share, when ykman could read it.
+    pub serial: Option<u32>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub challenge: Vec<u8>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub nonce: Vec<u8>,
+    #[serde(with = "crate::fragment::hex_bytes")]
+    pub ciphertext: Vec<u8>,
+}
+
+fn yubikey_error(what: impl std::fmt::Display) -> SssError {
+    SssError::Store(format!("YubiKey: {}", what))
+}
+
+pub fn is_wrapped(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(WRAPPED_SUFFIX)
+}
+
+fn ykman(serial: Option<u32>, args: &[&str]) -> Result<Zeroizing<String>> {
+    let mut command = Command::new("ykman");
+    if let Some(serial) = serial {
+        command.args(["--device", &serial.to_string()]);
+    }
+    // stderr stays on the terminal, where ykman asks for a touch when the slot wants one
+    let output = command
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| yubikey_error(format!("could not run ykman (is yubikey-manager installed?): {}", e)))?;
+    if !output.status.success() {
+        return Err(yubikey_error(format!("ykman {} failed", args.first().copied().unwrap_or_default())));
+    }
+    String::from_utf8(output.stdout)
+        .map(Zeroizing::new)
+        .map_err(|_| yubikey_error("ykman printed something other than text"))
+}
+
+/// The single connected YubiKey's serial number, if ykman can read it.
+fn serial() -> Option<u32> {
+    let listed = ykman(None, &["list", "--serials"]).ok()?;
+    let mut serials = listed.lines().filter_map(|l| l.trim().parse().ok());
+    match (serials.next(), serials.next()) {
+        (Some(serial), None) => Some(serial),
+        _ => None,
+    }
+}
+
+fn wrapping_key(serial: Option<u32>, slot: u8, challenge: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let response = ykman(serial, &["otp", "calculate", &slot.to_string(), &hex::encode(challenge)])?;
+    let response = Zeroizing::new(
+        hex::decode(response.trim()).map_err(|_| yubikey_error("the challenge response is not hex"))?,
+    );
+    let mut hasher = Sha256::new();
+    hasher.update(b"sss yubikey wrap");
+    hasher.update(response.as_slice());
+    hasher.update(challenge);
+    Ok(Zeroizing::new(hasher.finalize().to_vec()))
+}
+
+fn aad(slot: u8, index: u8) -> [u8; 2] {
+    [slot, index]
+}
+
+/// Wraps `share` with the connected YubiKey's challenge-response `slot`.
+pub fn wrap(share: &[u8], slot: u8) -> Result<WrappedShare> {
+    if !(1..=2).contains(&slot) {
+        return Err(SssError::Parse(format!("YubiKeys have OTP slots 1 and 2, not {}", slot)));
+    }
+    let index = *share.first().filter(|_| share.len() > 1).ok_or_else(|| yubikey_error("that is not a share"))?;
+    let serial = serial();
+    let mut challenge = vec![0u8; CHALLENGE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut challenge);
+    let key = wrapping_key(serial, slot, &challenge)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(GenericArray::from_slice(&key))
+        .encrypt(GenericArray::from_slice(&nonce), Payload { msg: share, aad: &aad(slot, index) })?;
+    Ok(WrappedShare {
+        format_version: FORMAT_VERSION,
+        index,
+        id: hex::encode(&Sha256::digest(share)[..4]),
+        slot,
+        serial,
+        challenge,
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Asks the YubiKey that wrapped the share for the same response and decrypts the share.
+pub fn unwrap(wrapped: &WrappedShare) -> Result<Zeroizing<Vec<u8>>> {
+    if wrapped.nonce.len() != NONCE_LEN {
+        return Err(SssError::Parse("YubiKey-wrapped share has a malformed nonce".into()));
+    }
+    let key = wrapping_key(wrapped.serial, wrapped.slot, &wrapped.challenge)?;
+    let share = Aes256Gcm::new(GenericArray::from_slice(&key))
+        .decrypt(
+            GenericArray::from_slice(&wrapped.nonce),
+            Payload { msg: &wrapped.ciphertext, aad: &aad(wrapped.slot, wrapped.index) },
+        )
+        .map_err(|_| yubikey_error("the share does not decrypt; is this the YubiKey that wrapped it?"))?;
+    Ok(Zeroizing::new(share))
+}
+
+/// Writes a wrapped share (mode 0600), refusing to overwrite an existing file.
+pub fn write(path: &Path, wrapped: &WrappedShare) -> Result<()> {
+    let text = serde_json::to_string_pretty(wrapped).map_err(|e| SssError::Parse(e.to_string()))?;
+    write_private(path, (text + "\n").as_bytes())
+}
+
+/// Reads a wrapped share file and unwraps it with the YubiKey.
+pub fn read(path: &Path) -> Result<Zeroizing<Vec<u8>>> {
+    let text = std::fs::read_to_string(path)?;
+    let wrapped: WrappedShare = serde_json::from_str(&text)
+        .map_err(|e| SssError::Parse(format!("{} is not a YubiKey-wrapped share: {}", path.display(), e)))?;
+    unwrap(&wrapped)
+}
@@ -0,0 +1,97 @@
+#![cfg(all(unix, feature = "age"))]
+
+mod support;
+
+use age::secrecy::ExposeSecret;
+use age::x25519::Identity;
+use support::{shares_in, Workspace};
+
+#[test]
+fn identity_file_round_trip() {
+    let ws = Workspace::new();
+    let identity = Identity::generate();
+    let recipient = identity.to_public().to_string();
+    let file = format!("# created: today\n# public key: {}\n{}\n", recipient, identity.to_string().expose_secret());
+    std::fs::write(ws.path("key.txt"), &file).unwrap();
+    std::fs::write(ws.path("bad.txt"), "AGE-SECRET-KEY-1NOTAKEY\n").unwrap();
+
+    let outcome = ws.sss(&["encrypt", "--age-identity", "bad.txt"]).finish();
+    assert_eq!(outcome.code, 3, "{}", outcome.output);
+
+    let mut sss = ws.sss(&["encrypt", "--age-identity", "key.txt"]);
+    sss.expect(&recipient);
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let shares = shares_in(&sss.finish().output);
+
+    let outcome = ws.combine(&["--age-identity-out", "recovered.txt"], &shares[1..], "pw");
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains(&format!("Identity for {}", recipient)), "{}", outcome.output);
+    assert_eq!(std::fs::read_to_string(ws.path("recovered.txt")).unwrap(), file);
+}
+
+#[test]
+fn shares_encrypted_to_recipients_open_with_their_identities() {
+    let ws = Workspace::new();
+    let identities: Vec<Identity> = (0..3).map(|_| Identity::generate()).collect();
+    let mut args = vec!["encrypt".to_string(), "--age-out".to_string(), "out".to_string()];
+    for identity in &identities {
+        args.push("--age-recipient".to_string());
+        args.push(identity.to_public().to_string());
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let mut sss = ws.sss(&args);
+    sss.answer("Enter secret value", "age interop");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(shares_in(&outcome.output).is_empty(), "shares were printed:\n{}", outcome.output);
+
+    let opened: Vec<String> = [1, 3]
+        .iter()
+        .map(|&n| {
+            let armored = std::fs::read(ws.path(&format!("out/share-{}.age", n))).unwrap();
+            String::from_utf8(age::decrypt(&identities[n - 1], &armored).unwrap()).unwrap().trim().to_string()
+        })
+        .collect();
+    assert!(
+        age::decrypt(&identities[0], &std::fs::read(ws.path("out/share-2.age")).unwrap()).is_err()
+    );
+
+    let outcome = ws.combine(&[], &opened, "pw");
+    assert_eq!(outcome.recovered(), Some("age interop"), "{}", outcome.output);
+}
+
+#[test]
+fn rewrap_moves_a_share_to_the_custodians_new_key() {
+    let ws = Workspace::new();
+    let old = Identity::generate();
+    let new = Identity::generate();
+    std::fs::write(ws.path("old.txt"), format!("{}\n", old.to_string().expose_secret())).unwrap();
+    let (old_key, new_key) = (old.to_public().to_string(), new.to_public().to_string());
+    let mut sss = ws.sss(&["encrypt", "--age-recipient", &old_key, "--age-recipient", &new_key]);
+    sss.answer("Enter secret value", "rotated");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    assert!(sss.finish().success());
+    let before = age::decrypt(&old, &std::fs::read(ws.path("age-shares/share-1.age")).unwrap()).unwrap();
+
+    let rewrap = |file: &str| ws.sss(&["rewrap", file, "--identity", "old.txt", "--new-key", &new_key]).finish();
+    let outcome = rewrap("age-shares/share-2.age");
+    assert_eq!(outcome.code, 3, "{}", outcome.output);
+
+    let outcome = rewrap("age-shares/share-1.age");
+    assert!(outcome.success(), "{}", outcome.output);
+    let rewrapped = std::fs::read(ws.path("age-shares/share-1.age")).unwrap();
+    assert!(age::decrypt(&old, &rewrapped).is_err());
+    assert_eq!(age::decrypt(&new, &rewrapped).unwrap(), before);
+}
@@ -0,0 +1,62 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::armor;
+use shamir_cli::format::{ShareEncoding, ShareHeader};
+use shamir_cli::input::parse_share;
+use shamir_cli::SssError;
+use support::Workspace;
+
+#[test]
+fn armored_shares_carry_their_headers_inside() {
+    let share: Vec<u8> = (0..=200u8).map(|b| b.wrapping_mul(7)).collect();
+    let header = ShareHeader { label: Some("Mom".into()), quorum: Some((2, 3)), ..Default::default() };
+    let text = header.prepend(&ShareEncoding::Armor.encode(&share).unwrap());
+    assert!(text.starts_with("-----BEGIN SSS SHARE-----\nLabel: Mom\nQuorum: 2 of 3\nIndex: 0\n\n"), "{}", text);
+    assert!(text.ends_with("-----END SSS SHARE-----"), "{}", text);
+    assert!(text.lines().all(|line| line.len() <= 64 || line.starts_with("-----")), "{}", text);
+
+    assert_eq!(parse_share(&format!("\n  {}\n", text)).unwrap(), share);
+    let (read, body) = ShareHeader::split(&text);
+    assert_eq!((read.label.as_deref(), read.quorum), (Some("Mom"), Some((2, 3))));
+    assert_eq!(armor::decode(body).unwrap(), share);
+}
+
+#[test]
+fn damaged_blocks_are_refused() {
+    let share = [3u8, 1, 2, 250, 9, 9, 9];
+    let text = armor::encode(&share);
+    assert_eq!(armor::crc24(b""), 0xB7_04CE);
+
+    let damaged = text.replacen("AwEC", "AwED", 1);
+    assert_ne!(damaged, text);
+    let e = armor::decode(&damaged).unwrap_err();
+    assert!(matches!(&e, SssError::Checksum(m) if m.contains("CRC-24")), "{}", e);
+
+    let e = armor::decode(&text.replace("Index: 3", "Index: 4")).unwrap_err();
+    assert!(matches!(&e, SssError::Parse(m) if m.contains("Index header says 4")), "{}", e);
+    assert!(armor::decode(text.trim_end_matches("-----END SSS SHARE-----")).is_err());
+    assert!(ShareEncoding::Armor.encode_with_parity(&share, Some(4)).is_err());
+}
+
+#[test]
+fn cli_armored_share_files_combine() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--encoding", "armor", "--out-dir", "shares", "--share-label", "Mom"]);
+    sss.answer("Enter secret value", "wrapped up");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let first = std::fs::read_to_string(ws.path("shares/share-01-of-03.sss")).unwrap();
+    assert!(first.starts_with("-----BEGIN SSS SHARE-----\nLabel: Mom\nSet: "), "{}", first);
+
+    let entries = ["@shares/share-01-of-03.sss".to_string(), "@shares/share-03-of-03.sss".to_string()];
+    let outcome = ws.combine(&[], &entries, "pw");
+    assert_eq!(outcome.recovered(), Some("wrapped up"), "{}", outcome.output);
+    assert!(outcome.output.contains("share #1 is labelled 'Mom'"), "{}", outcome.output);
+}
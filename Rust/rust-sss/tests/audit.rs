@@ -0,0 +1,78 @@
+use serde_json::{json, Map, Value};
+
+use shamir_cli::audit::{self, AuditLog, GENESIS};
+use shamir_cli::SssError;
+
+fn params(threshold: u8) -> Map<String, Value> {
+    json!({ "threshold": threshold, "total": 3 }).as_object().unwrap().clone()
+}
+
+fn write_lines(path: &std::path::Path, lines: &[&str]) {
+    std::fs::write(path, lines.iter().map(|line| format!("{}\n", line)).collect::<String>()).unwrap();
+}
+
+#[test]
+fn records_chain_onto_each_other() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.log");
+    let log = AuditLog::new(&path);
+
+    let first = log.append("encrypt", params(2), vec!["0a1b2c3d".into()], true, "").unwrap();
+    assert_eq!(first.seq, 1);
+    assert_eq!(first.prev, GENESIS);
+    let second = log.append("decrypt", Map::new(), vec![], false, "wrong password").unwrap();
+    assert_eq!(second.prev, first.hash);
+
+    let chain = audit::verify(&path).unwrap();
+    assert_eq!(chain.records(), 2);
+    assert_eq!(chain.head(), second.hash);
+    assert_eq!(chain.records_after(&first.hash), Some(1));
+    assert_eq!(chain.records_after(GENESIS), Some(2));
+    assert_eq!(chain.records_after("00ff"), None);
+    assert_eq!(audit::share_id(b"share").len(), 8);
+}
+
+#[test]
+fn edited_removed_and_reordered_records_break_the_chain() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.log");
+    let log = AuditLog::new(&path);
+    for threshold in 2..=4 {
+        log.append("encrypt", params(threshold), vec![], true, "").unwrap();
+    }
+    let text = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let tampered = |lines: &[&str]| {
+        write_lines(&path, lines);
+        matches!(audit::verify(&path), Err(SssError::Policy(_)))
+    };
+
+    let edited = lines[1].replace(r#""threshold":3"#, r#""threshold":1"#);
+    assert!(tampered(&[lines[0], &edited, lines[2]]));
+    assert!(tampered(&[lines[0], lines[2]]));
+    assert!(tampered(&[lines[1], lines[0], lines[2]]));
+
+    // nothing is appended to a broken log
+    assert!(log.append("encrypt", params(2), vec![], true, "").is_err());
+    write_lines(&path, &lines);
+    assert_eq!(audit::verify(&path).unwrap().records(), 3);
+}
+
+#[test]
+fn concurrent_appends_keep_one_chain_in_a_private_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.log");
+    std::thread::scope(|scope| {
+        for threshold in 2..=9 {
+            let path = &path;
+            scope.spawn(move || AuditLog::new(path).append("encrypt", params(threshold), vec![], true, "").unwrap());
+        }
+    });
+    assert_eq!(audit::verify(&path).unwrap().records(), 8);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+}
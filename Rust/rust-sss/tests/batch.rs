@@ -0,0 +1,70 @@
+use shamir_cli::batch::{self, SUMMARY_FILE};
+use shamir_cli::format::ShareEncoding;
+use shamir_cli::input;
+use shamir_cli::pipeline::{combine_secret, secret_text};
+use shamir_cli::SssError;
+
+const CSV: &str = "label,secret,threshold,total,encoding\r\n\
+stripe live,\"sk_live_1,2\"\"3\",3,5,\r\n\
+github-deploy,ghp_abc,2,3,bech32\r\n";
+
+#[test]
+fn csv_and_json_rows_parse_alike() {
+    let rows = batch::parse_csv(CSV).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].secret, "sk_live_1,2\"3");
+    assert_eq!((rows[0].threshold, rows[0].total, rows[0].encoding), (3, 5, None));
+    assert_eq!(rows[0].dir_name(), "stripe-live");
+    assert_eq!(rows[1].encoding, Some(ShareEncoding::Bech32));
+
+    let json = r#"[{ "label": "stripe live", "secret": "sk_live_1,2\"3", "threshold": 3, "total": 5 },
+                   { "label": "github-deploy", "secret": "ghp_abc", "threshold": 2, "total": 3, "encoding": "bech32" }]"#;
+    assert_eq!(batch::parse_json(json).unwrap(), rows);
+
+    let devices = batch::parse_csv("label,secret,threshold,total\r\nCON,a,1,1\r\ncom1,b,1,1\r\ncomet,c,1,1\r\n").unwrap();
+    let dirs: Vec<String> = devices.iter().map(|row| row.dir_name()).collect();
+    assert_eq!(dirs, ["con-", "com1-", "comet"]);
+
+    assert!(matches!(batch::parse_csv("label,secret\nonly-one-field\n"), Err(SssError::Parse(_))));
+    assert!(matches!(batch::parse_csv("label,secret,threshold,total\na,\"open,2,3\n"), Err(SssError::Parse(_))));
+}
+
+#[test]
+fn bad_rows_stop_the_batch_before_anything_is_written() {
+    let dir = tempfile::tempdir().unwrap();
+    for csv in [
+        "label,secret,threshold,total\na,x,3,2\n",
+        "label,secret,threshold,total\na,x,2,3\nA,y,2,3\n",
+        "label,secret,threshold,total\na,,2,3\n",
+    ] {
+        let rows = batch::parse_csv(csv).unwrap();
+        let result = batch::split_all(&rows, dir.path(), "pw", 1_000, &mut rand::rngs::OsRng);
+        assert!(matches!(result, Err(SssError::Policy(_))), "{}", csv);
+    }
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn every_row_is_split_into_its_own_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let rows = batch::parse_csv(CSV).unwrap();
+    let (summary, shares) = batch::split_all(&rows, dir.path(), "pw", 1_000, &mut rand::rngs::OsRng).unwrap();
+    assert_eq!(shares.len(), 8);
+    assert_eq!(summary.sets[1].dir, "github-deploy");
+    assert!(dir.path().join(SUMMARY_FILE).exists());
+
+    for (set, secret) in summary.sets.iter().zip(["sk_live_1,2\"3", "ghp_abc"]) {
+        let quorum: Vec<Vec<u8>> = (1..=set.threshold)
+            .map(|n| {
+                let file = dir.path().join(&set.dir).join(format!("share-0{}-of-0{}.sss", n, set.total));
+                input::parse_share(&input::read_share_file(file).unwrap()).unwrap()
+            })
+            .collect();
+        let recovered = combine_secret(&quorum, "pw").unwrap();
+        assert_eq!(secret_text(&recovered).unwrap().as_str(), secret);
+    }
+    assert!(matches!(
+        batch::split_all(&rows, dir.path(), "pw", 1_000, &mut rand::rngs::OsRng),
+        Err(SssError::Policy(_))
+    ));
+}
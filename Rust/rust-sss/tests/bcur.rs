@@ -0,0 +1,50 @@
+use shamir_cli::bcur::{self, PartsDecoder, ShareParts};
+use shamir_cli::format::ShareEncoding;
+use shamir_cli::input::parse_share;
+use shamir_cli::scan::{Capture, Scanned};
+use shamir_cli::secret::SecretType;
+
+#[test]
+fn seeds_and_bytes_are_read_as_secrets() {
+    let entropy = [0x5au8; 16];
+    let seed = bcur::encode_seed(&entropy);
+    assert!(seed.starts_with("ur:crypto-seed/"));
+    let secret = SecretType::from_input(&seed.to_uppercase()).unwrap();
+    assert!(matches!(&secret, SecretType::Mnemonic(e) if e == &entropy));
+
+    let secret = SecretType::from_input(&bcur::encode_bytes(b"raw")).unwrap();
+    assert!(matches!(&secret, SecretType::Bytes(b) if b == b"raw"));
+
+    let share = ShareEncoding::Ur.encode(&[1, 2, 3]).unwrap();
+    assert!(share.starts_with("ur:crypto-sss/"));
+    assert!(SecretType::from_input(&share).is_err());
+    assert!(parse_share(&seed).is_err());
+    assert!(ShareEncoding::Ur.encode_with_parity(&[1, 2, 3], Some(4)).is_err());
+}
+
+#[test]
+fn a_multi_part_share_is_rebuilt_from_its_parts() {
+    let share: Vec<u8> = (1..=120u8).collect();
+    let mut parts = ShareParts::new(&share, 30).unwrap();
+    let count = parts.fragment_count();
+    assert!(count > 1);
+
+    let first = parts.next_part().unwrap();
+    assert_eq!(bcur::part_count(&first), Some(count));
+    assert!(bcur::decode_share(&first).is_err());
+    assert_eq!(bcur::part_count(&bcur::encode_share(&share)), None);
+
+    let mut decoder = PartsDecoder::default();
+    let mut capture = Capture::new(Some(1));
+    decoder.receive(&first).unwrap();
+    assert_eq!(capture.offer(&first), Scanned::Partial { have: 1, of: count });
+    let mut outcome = Scanned::Partial { have: 1, of: count };
+    while let Scanned::Partial { .. } = outcome {
+        let part = parts.next_part().unwrap();
+        decoder.receive(&part).unwrap();
+        outcome = capture.offer(&part);
+    }
+    assert_eq!(outcome, Scanned::Added(1));
+    assert_eq!(decoder.share().unwrap(), Some(share.clone()));
+    assert_eq!(capture.into_shares(), vec![share]);
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use shamir_cli::bench::{self, Plan};
+
+#[test]
+fn every_operation_is_timed_at_every_size() {
+    let plan = Plan { sizes: vec![16, 1024], rounds: 1_000, min_time: Duration::ZERO, ..Plan::default() };
+    let results = bench::run(&plan).unwrap();
+
+    let names: Vec<(&str, usize)> = results.iter().map(|m| (m.operation.as_str(), m.bytes)).collect();
+    assert_eq!(
+        names,
+        [
+            ("kdf", 0),
+            ("aead-seal", 16),
+            ("aead-open", 16),
+            ("split", 16),
+            ("reconstruct", 16),
+            ("aead-seal", 1024),
+            ("aead-open", 1024),
+            ("split", 1024),
+            ("reconstruct", 1024),
+        ]
+    );
+    for m in &results {
+        assert!(m.runs >= 1 && m.mean_us >= 0.0);
+        assert_eq!(m.mib_per_s.is_some(), m.bytes > 0, "{}", m.operation);
+    }
+}
+
+#[test]
+fn impossible_share_counts_are_reported_before_timing() {
+    let plan = Plan { sizes: vec![16], rounds: 1_000, threshold: 6, total: 5, min_time: Duration::ZERO };
+    assert!(bench::run(&plan).is_err());
+}
@@ -0,0 +1,52 @@
+use serde_json::{json, Map};
+
+use shamir_cli::ceremony::{self, Ceremony, Check};
+use shamir_cli::token::generate_key;
+use shamir_cli::SssError;
+
+fn ceremony() -> Ceremony {
+    let mut parameters = Map::new();
+    parameters.insert("threshold".into(), json!(2));
+    parameters.insert("set".into(), json!("ab12cd34"));
+    Ceremony {
+        operation: "encrypt".into(),
+        started_at: 1_900_000_000,
+        finished_at: 1_900_000_042,
+        parameters,
+        participants: vec!["Alice".into(), "Bob".into()],
+        shares: vec!["0a0b0c0d".into(), "01020304".into()],
+        checks: vec![Check { at: 1_900_000_010, subject: "rehearsal".into(), ok: true, detail: "verified 1/1".into() }],
+        ok: true,
+        detail: "completed".into(),
+        audit_record: Some((7, "ff".repeat(32))),
+    }
+}
+
+#[test]
+fn records_read_as_plain_text() {
+    let text = ceremony().render();
+    for line in [
+        "Operation:  encrypt",
+        "Started:    2030-03-17 17:46:40 UTC",
+        "Audit log:  record 7, hash ffff",
+        "  threshold    2",
+        "  Alice",
+        "    2. 01020304",
+        "  17:46:50  ok      rehearsal: verified 1/1",
+    ] {
+        assert!(text.contains(line), "missing {:?} in\n{}", line, text);
+    }
+}
+
+#[test]
+fn signed_records_catch_edits_and_other_signers() {
+    let key = generate_key();
+    let signed = ceremony().sign(&key);
+    assert_eq!(ceremony::verify(&signed, Some(&key.verifying_key())).unwrap(), key.verifying_key());
+
+    let edited = signed.replace("Bob", "Eve");
+    assert!(matches!(ceremony::verify(&edited, None), Err(SssError::Signing(_))));
+    let other = generate_key().verifying_key();
+    assert!(matches!(ceremony::verify(&signed, Some(&other)), Err(SssError::Signing(_))));
+    assert!(matches!(ceremony::verify(&ceremony().render(), None), Err(SssError::Signing(_))));
+}
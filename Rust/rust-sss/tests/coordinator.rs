@@ -0,0 +1,24 @@
+#![cfg(feature = "coordinator")]
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+use shamir_cli::coordinator::{seal, session_code, GuardianSession};
+
+#[test]
+fn a_share_sealed_on_the_phone_opens_only_for_its_session() {
+    let session = GuardianSession::generate();
+    let page = session.page();
+    assert!(!page.contains("{{"), "{}", page);
+    let start = page.find("atob(\"").unwrap() + 6;
+    let key = BASE64.decode(&page[start..start + page[start..].find('"').unwrap()]).unwrap();
+    assert_eq!(session_code(&key), session.code());
+
+    let mut rng = rand::rngs::OsRng;
+    let contribution = seal(&key, "[1, 2, 3]", Some("Ann".into()), &mut rng).unwrap();
+    assert_eq!(&*session.open(&contribution).unwrap(), "[1, 2, 3]");
+    assert_eq!(contribution.label.as_deref(), Some("Ann"));
+
+    let other = GuardianSession::generate();
+    assert!(other.open(&contribution).is_err());
+    assert_ne!(other.code(), session.code());
+}
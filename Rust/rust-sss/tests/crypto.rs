@@ -0,0 +1,110 @@
+use shamir_cli::crypto::{
+    encrypt_data, kdf_header, kdf_rounds, open_payload, MAX_PBKDF2_ROUNDS, PBKDF2_ROUNDS,
+};
+use shamir_cli::format::SetBinding;
+use shamir_cli::SssError;
+
+fn payload(header: Option<u32>, rounds: u32) -> Vec<u8> {
+    let (ciphertext, salt, nonce) = encrypt_data(b"kdf header", "pw", rounds).unwrap();
+    let mut data = header.map(|r| kdf_header(r).to_vec()).unwrap_or_default();
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    data
+}
+
+#[test]
+fn payloads_carry_their_kdf_rounds() {
+    let data = payload(Some(3_000), 3_000);
+    assert_eq!(kdf_rounds(&data).map(|(rounds, _)| rounds), Some(3_000));
+    assert_eq!(&open_payload(&data, "pw").unwrap()[..], b"kdf header");
+    assert!(matches!(open_payload(&data, "wrong"), Err(SssError::Aead)));
+
+    // sets split before the header existed still open with the old fixed cost
+    let legacy = payload(None, PBKDF2_ROUNDS);
+    assert_eq!(kdf_rounds(&legacy), None);
+    assert_eq!(&open_payload(&legacy, "pw").unwrap()[..], b"kdf header");
+
+    assert_eq!(kdf_rounds(&kdf_header(MAX_PBKDF2_ROUNDS + 1)), None);
+    assert_eq!(kdf_rounds(&kdf_header(0)), None);
+}
+
+#[test]
+fn seeded_encryption_is_reproducible() {
+    use rand::SeedableRng;
+    let seal = |seed| {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+        shamir_cli::crypto::encrypt_data_with_rng(b"golden", "pw", 1_000, &mut rng).unwrap()
+    };
+    assert_eq!(seal(7), seal(7));
+    assert_ne!(seal(7).1, seal(8).1);
+}
+
+#[test]
+fn pipeline_splits_and_combines_like_the_cli() {
+    use shamir_cli::pipeline::{combine_secret, split_secret};
+    use shamir_cli::secret::{Padding, SecretType};
+
+    let secret = SecretType::from_input("hex:00ff").unwrap();
+    let shares = split_secret(&secret, "pw", 2, 3, Padding::default(), 1_000, &mut rand::rngs::OsRng).unwrap();
+    match combine_secret(&shares[1..], "pw").unwrap() {
+        SecretType::Bytes(ref bytes) => assert_eq!(bytes, &[0x00, 0xff]),
+        _ => panic!("expected bytes"),
+    }
+    assert!(matches!(combine_secret(&shares[..1], "pw"), Err(SssError::Aead)));
+}
+
+#[test]
+fn a_decoy_password_recovers_only_the_decoy() {
+    use shamir_cli::pipeline::{combine_secret, split_secret, split_secret_with_decoy};
+    use shamir_cli::secret::{Padding, SecretType};
+
+    let real = SecretType::String("the real seed".into());
+    let decoy = SecretType::String("a small savings wallet".into());
+    let split = |decoy_password| {
+        let mut rng = rand::rngs::OsRng;
+        split_secret_with_decoy(&real, "pw", &decoy, decoy_password, 2, 3, Padding::default(), 1_000, &mut rng)
+    };
+    let shares = split("duress").unwrap();
+    assert!(matches!(&combine_secret(&shares[..2], "pw").unwrap(), SecretType::String(s) if s == "the real seed"));
+    let recovered = combine_secret(&shares[1..], "duress").unwrap();
+    assert!(matches!(&recovered, SecretType::String(s) if s == "a small savings wallet"));
+    assert!(matches!(combine_secret(&shares[..2], "neither"), Err(SssError::Aead)));
+    assert!(split("pw").is_err());
+
+    // every set has two slots, so one with a decoy is no longer than one without
+    let plain = split_secret(&real, "pw", 2, 3, Padding::default(), 1_000, &mut rand::rngs::OsRng).unwrap();
+    assert_eq!(shares[0].len(), plain[0].len());
+    assert!(matches!(&combine_secret(&plain[..2], "pw").unwrap(), SecretType::String(s) if s == "the real seed"));
+}
+
+#[test]
+fn sets_are_bound_to_their_id_and_threshold() {
+    use shamir_cli::pipeline::{combine_secret, split_secret};
+    use shamir_cli::secret::{Padding, SecretType};
+    use shamir_cli::shamir::Field;
+    use shamir_cli::timelock::Conditions;
+
+    let secret = SecretType::String("bound".into());
+    let split = || split_secret(&secret, "pw", 2, 3, Padding::default(), 1_000, &mut rand::rngs::OsRng).unwrap();
+    let (first, second) = (split(), split());
+    let payload = Field::Gf256.reconstruct(&first[..2]).unwrap();
+    let binding = Conditions::split(&payload).0.binding.unwrap();
+    assert_eq!((binding.threshold, binding.version), (2, 1));
+    let other = Conditions::split(&Field::Gf256.reconstruct(&second[1..]).unwrap()).0.binding.unwrap();
+    assert_ne!(binding.id, other.id);
+
+    // the same secret and password, but shares of two sets do not open each other
+    assert!(matches!(combine_secret(&[&first[0], &second[1]], "pw"), Err(SssError::Aead)));
+    for at in [4, 4 + 16] {
+        let mut edited = payload.clone();
+        edited[at] ^= 1;
+        assert!(matches!(open_payload(&edited, "pw"), Err(SssError::Aead)), "byte {}", at);
+    }
+
+    let mut newer = payload.clone();
+    newer[4 + 16 + 1] = 9;
+    let shares = Field::Gf256.split(&newer, 2, 2).unwrap();
+    assert!(matches!(combine_secret(&shares, "pw"), Err(SssError::Parse(_))));
+    assert_eq!(SetBinding::split(&binding.header()), Some((binding, &[][..])));
+}
@@ -0,0 +1,99 @@
+#![cfg(all(unix, feature = "daemon"))]
+
+use serde_json::{json, Value};
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use shamir_cli::daemon::{bind, call, read_frame, write_frame, Daemon, MAX_CONNECTIONS, MAX_FRAME};
+
+fn start() -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("sss.sock");
+    let listener = bind(&socket, false).unwrap();
+    std::thread::spawn(move || Daemon::new(1_000, &[]).serve(&listener));
+    (dir, socket)
+}
+
+fn shares(reply: &Value) -> Vec<Value> {
+    reply["shares"].as_array().unwrap().clone()
+}
+
+#[test]
+fn split_and_combine_with_the_password_in_each_request() {
+    let (_dir, socket) = start();
+    let split = call(&socket, &json!({ "op": "split", "secret": "hi", "password": "pw", "threshold": 2, "total": 3 }))
+        .unwrap();
+    assert_eq!(split["ok"], true, "{}", split);
+    let shares = shares(&split);
+    assert_eq!(shares.len(), 3);
+
+    let combine = call(&socket, &json!({ "op": "combine", "shares": shares[1..], "password": "pw" })).unwrap();
+    assert_eq!((combine["ok"].as_bool(), combine["secret"].as_str()), (Some(true), Some("hi")));
+
+    let wrong = call(&socket, &json!({ "op": "combine", "shares": shares[1..], "password": "no" })).unwrap();
+    assert_eq!((wrong["ok"].as_bool(), wrong["kind"].as_str()), (Some(false), Some("aead")));
+}
+
+#[test]
+fn unlock_remembers_the_password_across_connections() {
+    let (_dir, socket) = start();
+    let split = json!({ "op": "split", "secret": "hi", "threshold": 2, "total": 2 });
+    assert_eq!(call(&socket, &split).unwrap()["kind"], "unauthorized");
+
+    let unlock = call(&socket, &json!({ "op": "unlock", "password": "pw", "ttl_secs": 60 })).unwrap();
+    assert_eq!(unlock["ttl_secs"], 60);
+    assert_eq!(call(&socket, &json!({ "op": "ping" })).unwrap()["unlocked"], true);
+    let shares = shares(&call(&socket, &split).unwrap());
+    let combine = call(&socket, &json!({ "op": "combine", "shares": shares })).unwrap();
+    assert_eq!(combine["secret"], "hi");
+
+    call(&socket, &json!({ "op": "lock" })).unwrap();
+    assert_eq!(call(&socket, &json!({ "op": "combine", "shares": shares })).unwrap()["kind"], "unauthorized");
+}
+
+#[test]
+fn one_connection_carries_many_frames() {
+    let (_dir, socket) = start();
+    let mut stream = UnixStream::connect(&socket).unwrap();
+    for request in [r#"{"op":"ping"}"#, r#"{"op":"nope"}"#, "not json"] {
+        write_frame(&mut stream, request.as_bytes()).unwrap();
+        let reply: Value = serde_json::from_slice(&read_frame(&mut stream).unwrap().unwrap()).unwrap();
+        assert_eq!(reply["ok"].as_bool(), Some(request == r#"{"op":"ping"}"#), "{}", reply);
+    }
+
+    stream.write_all(&(MAX_FRAME + 1).to_be_bytes()).unwrap();
+    assert!(read_frame(&mut stream).unwrap().is_none());
+}
+
+#[test]
+fn bind_refuses_to_replace_anything_but_a_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sss.sock");
+    std::fs::write(&path, "keep me").unwrap();
+    assert!(bind(&path, false).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+}
+
+#[test]
+fn connections_over_the_limit_are_turned_away_until_one_ends() {
+    let (_dir, socket) = start();
+    let ping = json!({ "op": "ping" });
+    let mut held: Vec<UnixStream> = (0..MAX_CONNECTIONS).map(|_| UnixStream::connect(&socket).unwrap()).collect();
+    // a reply on the last one means the daemon has taken every one of them in
+    write_frame(&mut held[MAX_CONNECTIONS - 1], ping.to_string().as_bytes()).unwrap();
+    assert!(read_frame(&mut held[MAX_CONNECTIONS - 1]).unwrap().is_some());
+
+    // the refusal comes unasked, and the daemon hangs up after it
+    let mut turned_away = UnixStream::connect(&socket).unwrap();
+    let busy: Value = serde_json::from_slice(&read_frame(&mut turned_away).unwrap().unwrap()).unwrap();
+    assert_eq!((busy["ok"].as_bool(), busy["kind"].as_str()), (Some(false), Some("io")), "{}", busy);
+
+    held.pop();
+    // a turned-away call may fail outright, the daemon having hung up before it wrote
+    let freed = (0..50).any(|_| {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        call(&socket, &ping).is_ok_and(|reply| reply["ok"] == true)
+    });
+    assert!(freed, "the slot of a closed connection was never given back");
+}
@@ -0,0 +1,67 @@
+use zeroize::Zeroizing;
+
+use shamir_cli::doctor::{diagnose, Cause, Evidence};
+use shamir_cli::format::{ShareEncoding, ShareHeader};
+use shamir_cli::pipeline::split_secret;
+use shamir_cli::secret::{Padding, SecretType};
+use shamir_cli::SssError;
+
+fn split(password: &str) -> Vec<Vec<u8>> {
+    let secret = SecretType::String("in the attic".into());
+    split_secret(&secret, password, 2, 3, Padding::default(), 1_000, &mut rand::rngs::OsRng).unwrap()
+}
+
+fn evidence(shares: &[String], passwords: &[&str]) -> Evidence {
+    Evidence {
+        shares: shares.iter().enumerate().map(|(i, text)| (format!("share-{}.txt", i + 1), text.clone())).collect(),
+        passwords: passwords.iter().map(|p| Zeroizing::new(p.to_string())).collect(),
+        ..Evidence::default()
+    }
+}
+
+#[test]
+fn a_sound_set_is_found_among_strays_and_slips() {
+    let set = split("Attic pw");
+    let other = split("Attic pw");
+    let shares = [
+        ShareEncoding::Bech32.encode(&set[0]).unwrap(),
+        hex::encode(&other[1]),
+        format!("{:?}", set[2]),
+        ShareEncoding::Base64.encode(&set[0]).unwrap(),
+        "not a share at all".to_string(),
+    ];
+    let diagnosis = diagnose(&evidence(&shares, &["guess", "aTTIC PW"]));
+    assert_eq!(diagnosis.cause, Cause::Recoverable, "{:#?}", diagnosis);
+    assert_eq!(diagnosis.password, Some(2));
+    assert_eq!(diagnosis.quorum, ["share-1.txt", "share-3.txt"]);
+    let failed: Vec<&str> = diagnosis.findings.iter().filter(|f| !f.ok).map(|f| f.check.as_str()).collect();
+    assert_eq!(
+        failed,
+        ["membership of share-4.txt", "encoding of share-5.txt", "membership of share-2.txt", "password candidate 1"]
+    );
+    let caps = diagnosis.findings.iter().find(|f| f.check == "password candidate 2").unwrap();
+    assert_eq!(caps.detail, "opens the set with caps lock on");
+    diagnosis.result().unwrap();
+}
+
+#[test]
+fn the_first_failing_check_is_the_cause() {
+    let set = split("pw");
+    let header = ShareHeader { quorum: Some((2, 3)), ..Default::default() };
+    let lone = [header.prepend(&hex::encode(&set[1]))];
+    assert_eq!(diagnose(&evidence(&lone, &["pw"])).cause, Cause::TooFewShares);
+
+    let pair = [hex::encode(&set[0]), hex::encode(&set[1])];
+    let wrong = diagnose(&evidence(&pair, &["pw2", "Pw"]));
+    assert_eq!(wrong.cause, Cause::WrongPassword);
+    assert!(matches!(wrong.result(), Err(SssError::WrongPassword)));
+    assert_eq!(diagnose(&evidence(&pair, &[])).cause, Cause::NoPassword);
+
+    let mixed = [hex::encode(&set[0]), hex::encode(&split("pw")[1])];
+    let diagnosis = diagnose(&evidence(&mixed, &["pw"]));
+    assert_eq!(diagnosis.cause, Cause::MixedSets);
+    assert!(matches!(diagnosis.result(), Err(SssError::Share(_))));
+
+    assert_eq!(diagnose(&evidence(&["[0, 1, 2]".to_string()], &["pw"])).cause, Cause::UnreadableShares);
+    assert_eq!(diagnose(&evidence(&[], &["pw"])).cause, Cause::NoShares);
+}
@@ -0,0 +1,47 @@
+use shamir_cli::dotenv::{self, export_line};
+use shamir_cli::secret::{deserialize_secret, serialize_secret, SecretType};
+use shamir_cli::SssError;
+
+const ENV: &str = "# production\n\
+export DATABASE_URL=postgres://app:p4ss@db/app\n\
+API_KEY = 'it''s'\n\
+GREETING=\"line one\\nsaid \\\"hi\\\"\"\n\
+PEM=\"-----BEGIN KEY-----\n\
+abc\n\
+-----END KEY-----\"\n\
+EMPTY=\n\
+PORT=8080 # the default\n\
+PORT=9090\n";
+
+#[test]
+fn variables_are_read_the_way_dotenv_loaders_read_them() {
+    let variables = dotenv::parse(ENV).unwrap();
+    let value = |name: &str| variables.iter().find(|v| v.name == name).map(|v| v.value.as_str().to_string());
+    assert_eq!(variables.len(), 6);
+    assert_eq!(value("DATABASE_URL").as_deref(), Some("postgres://app:p4ss@db/app"));
+    assert_eq!(value("API_KEY").as_deref(), Some("it"));
+    assert_eq!(value("GREETING").as_deref(), Some("line one\nsaid \"hi\""));
+    assert_eq!(value("PEM").as_deref(), Some("-----BEGIN KEY-----\nabc\n-----END KEY-----"));
+    assert_eq!(value("EMPTY").as_deref(), Some(""));
+    assert_eq!(value("PORT").as_deref(), Some("9090"));
+
+    for bad in ["NO_EQUALS\n", "1ABC=x\n", "OPEN=\"never closed\n"] {
+        assert!(matches!(dotenv::parse(bad), Err(SssError::Parse(_))), "{}", bad);
+    }
+}
+
+#[test]
+fn export_lines_quote_for_the_shell() {
+    let variables = dotenv::parse("A=\"it's\"\nB=plain\n").unwrap();
+    let selected = dotenv::select(&variables, &["B".into(), "A".into()]).unwrap();
+    assert_eq!(export_line(selected[0]).as_str(), "export B='plain'");
+    assert_eq!(export_line(selected[1]).as_str(), "export A='it'\\''s'");
+    assert!(matches!(dotenv::select(&variables, &["C".into()]), Err(SssError::Parse(e)) if e.contains("A, B")));
+}
+
+#[test]
+fn env_files_come_back_byte_for_byte() {
+    let text = "A=1\r\nB=2\n\n# trailing comment without a newline";
+    let bytes = serialize_secret(&SecretType::EnvFile(text.into()));
+    assert!(matches!(deserialize_secret(&bytes).unwrap(), SecretType::EnvFile(ref t) if t == text));
+}
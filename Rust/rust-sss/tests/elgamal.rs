@@ -0,0 +1,38 @@
+use shamir_cli::elgamal;
+use shamir_cli::SssError;
+
+#[test]
+fn any_quorum_of_partials_decrypts() {
+    let dealt = elgamal::keygen(3, 5).unwrap();
+    let ciphertext = elgamal::encrypt(&dealt.public_key, b"quarterly numbers").unwrap();
+    let partials: Vec<Vec<u8>> = dealt
+        .shares
+        .iter()
+        .map(|share| elgamal::partial_decrypt(share, &ciphertext).unwrap())
+        .collect();
+    assert!(partials.iter().all(|p| p.len() == elgamal::PARTIAL_LEN));
+
+    let quorum = [&partials[4], &partials[0], &partials[2]];
+    assert_eq!(elgamal::combine(&quorum, &ciphertext).unwrap().as_slice(), b"quarterly numbers");
+    assert_eq!(elgamal::combine(&partials, &ciphertext).unwrap().as_slice(), b"quarterly numbers");
+}
+
+#[test]
+fn too_few_or_foreign_partials_fail() {
+    let dealt = elgamal::keygen(2, 3).unwrap();
+    let other = elgamal::keygen(2, 3).unwrap();
+    let ciphertext = elgamal::encrypt(&dealt.public_key, b"x").unwrap();
+    let partial = |share: &[u8]| elgamal::partial_decrypt(share, &ciphertext).unwrap();
+
+    assert!(matches!(elgamal::combine(&[partial(&dealt.shares[0])], &ciphertext), Err(SssError::Aead)));
+    let mixed = [partial(&dealt.shares[0]), partial(&other.shares[1])];
+    assert!(matches!(elgamal::combine(&mixed, &ciphertext), Err(SssError::Aead)));
+    let repeated = [partial(&dealt.shares[0]), partial(&dealt.shares[0])];
+    assert!(matches!(elgamal::combine(&repeated, &ciphertext), Err(SssError::Share(_))));
+
+    let mut tampered = ciphertext.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    let partials = [partial(&dealt.shares[0]), partial(&dealt.shares[1])];
+    assert!(elgamal::combine(&partials, &tampered).is_err());
+    assert!(elgamal::combine(&partials, b"not a ciphertext").is_err());
+}
@@ -0,0 +1,243 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::format::{share_conflict, ShareEncoding, ShareHeader};
+use shamir_cli::input::{parse_share, share_entries, InputError};
+use support::{shares_in, Workspace};
+
+const ENCODINGS: [ShareEncoding; 7] = [
+    ShareEncoding::List,
+    ShareEncoding::Hex,
+    ShareEncoding::Base64,
+    ShareEncoding::Base58,
+    ShareEncoding::Bech32,
+    ShareEncoding::Ur,
+    ShareEncoding::Armor,
+];
+
+#[test]
+fn every_encoding_is_detected_on_input() {
+    let share: Vec<u8> = (0..=255u8).rev().step_by(3).collect();
+    for encoding in ENCODINGS {
+        let text = encoding.encode(&share).unwrap();
+        assert_eq!(parse_share(&text).unwrap(), share, "{:?}: {}", encoding, text);
+    }
+    for encoding in [ShareEncoding::Hex, ShareEncoding::Bech32] {
+        let shouted = format!("  {}\n", encoding.encode(&share).unwrap().to_uppercase());
+        assert_eq!(parse_share(&shouted).unwrap(), share, "{}", shouted);
+    }
+    assert!(ShareEncoding::Bech32.encode(&share).unwrap().starts_with("sss1"));
+    assert!(ShareEncoding::Bech32.encode(&[7u8; 700]).is_err());
+}
+
+#[test]
+fn share_files_saved_with_windows_line_endings_parse() {
+    let dir = tempfile::tempdir().unwrap();
+    let share: Vec<u8> = (0..200u8).collect();
+    let header = ShareHeader {
+        label: Some("vault B".into()),
+        note: Some("kept in the safe".into()),
+        quorum: Some((2, 3)),
+        ..ShareHeader::default()
+    };
+    for encoding in ENCODINGS {
+        let text = header.prepend(&encoding.encode(&share).unwrap()).replace('\n', "\r\n") + "\r\n";
+        let path = dir.path().join(format!("{:?}.txt", encoding));
+        std::fs::write(&path, format!("\u{feff}{}", text)).unwrap();
+        let parsed = shamir_cli::input::parse_share_details(&shamir_cli::input::read_share_file(&path).unwrap()).unwrap();
+        assert_eq!(parsed.share, share, "{:?}: {:?}", encoding, text);
+        assert_eq!(parsed.header, header, "{:?}", encoding);
+        // pasted rather than read from a file, the text is not normalized first
+        assert_eq!(parse_share(&text).unwrap(), share, "{:?}", encoding);
+    }
+}
+
+#[test]
+fn piped_shares_split_into_one_entry_per_share() {
+    let armored = shamir_cli::armor::encode(&[1, 2, 3, 4]);
+    let text = format!("Set: 0a1b\nQuorum: 2 of 3\n[1, 2, 3]\n\n\n[2, 4, 6]\nLabel: Mom\n{}\n", armored);
+    let entries = share_entries(&text);
+    assert_eq!(entries.len(), 3, "{:?}", entries);
+    assert_eq!(entries[0], "Set: 0a1b\nQuorum: 2 of 3\n[1, 2, 3]\n");
+    assert_eq!(entries[1], "[2, 4, 6]\n");
+    assert!(entries[2].starts_with("Label: Mom\n-----BEGIN") && entries[2].trim_end().ends_with("-----"));
+    assert_eq!(parse_share(&entries[2]).unwrap(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn bech32_catches_a_mistyped_character() {
+    let text = ShareEncoding::Bech32.encode(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    let mut typo = text.into_bytes();
+    let last = typo.len() - 3;
+    typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+    let typo = String::from_utf8(typo).unwrap();
+    assert!(matches!(parse_share(&typo), Err(InputError::Encoding { encoding: "bech32", .. })), "{}", typo);
+    assert!(matches!(parse_share("base58:0OIl"), Err(InputError::Encoding { encoding: "base58", .. })));
+    assert!(matches!(parse_share("12g4"), Err(InputError::UnexpectedChar { ch: 'g', .. })));
+    assert!(parse_share("A🌀").is_err());
+}
+
+#[test]
+fn cli_split_in_bech32_combines() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--encoding", "bech32"]);
+    sss.answer("Enter secret value", "short and checked");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let shares: Vec<String> = outcome
+        .output
+        .lines()
+        .filter(|l| l.starts_with("Share ID"))
+        .filter_map(|l| l.rsplit(' ').next().map(str::to_string))
+        .collect();
+    assert!(shares.iter().all(|s| s.starts_with("sss1")), "{}", outcome.output);
+
+    let outcome = ws.combine(&[], &shares[1..], "pw");
+    assert_eq!(outcome.recovered(), Some("short and checked"), "{}", outcome.output);
+}
+
+#[test]
+fn cli_parity_repairs_mistyped_characters() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--encoding", "hex", "--parity", "8"]);
+    sss.answer("Enter secret value", "smudged but fine");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let mut shares: Vec<String> = outcome
+        .output
+        .lines()
+        .filter(|l| l.starts_with("Share ID"))
+        .filter_map(|l| l.rsplit(' ').next().map(str::to_string))
+        .collect();
+    assert!(shares.iter().all(|s| s.starts_with("rs8:")), "{}", outcome.output);
+
+    let damaged: String = shares[0]
+        .char_indices()
+        .map(|(i, c)| if [10, 21, 40].contains(&i) { if c == '0' { '8' } else { '0' } } else { c })
+        .collect();
+    shares[0] = damaged;
+    let outcome = ws.combine(&[], &shares[..2], "pw");
+    assert_eq!(outcome.recovered(), Some("smudged but fine"), "{}", outcome.output);
+    assert!(outcome.output.contains("Corrected 3 damaged byte(s) in share #1"), "{}", outcome.output);
+
+    let outcome = ws.sss(&["encrypt", "--encoding", "bech32", "--parity", "4"]).finish();
+    assert_eq!(outcome.code, 2, "{}", outcome.output);
+}
+
+#[test]
+fn labels_ride_above_any_encoding() {
+    let header = ShareHeader {
+        label: Some("Safe deposit box".into()),
+        note: Some("Bank on\nMain St".into()),
+        ..Default::default()
+    };
+    let share = [3u8, 1, 2, 250];
+    for encoding in [ShareEncoding::List, ShareEncoding::Hex, ShareEncoding::Base64, ShareEncoding::Bech32] {
+        let text = header.prepend(&encoding.encode(&share).unwrap());
+        assert!(text.starts_with("Label: Safe deposit box\nNote: Bank on Main St\n"), "{}", text);
+        assert_eq!(parse_share(&text).unwrap(), share);
+        let (read, body) = ShareHeader::split(&text);
+        assert_eq!(read.label.as_deref(), Some("Safe deposit box"));
+        assert_eq!(read.note.as_deref(), Some("Bank on Main St"));
+        assert!(!body.contains("Label"));
+    }
+    assert!(ShareHeader::split("base64:AQID").0.is_empty());
+}
+
+#[test]
+fn cli_labels_are_shown_when_shares_are_read() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&[
+        "encrypt", "--out-dir", "shares", "--share-label", "Mom", "--share-label", "Lawyer", "--share-note",
+        "Blue folder",
+    ]);
+    sss.answer("Enter secret value", "traceable");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let first = std::fs::read_to_string(ws.path("shares/share-01-of-03.sss")).unwrap();
+    assert!(first.starts_with("Label: Mom\nNote: Blue folder\nSet: "), "{}", first);
+    assert!(first.contains("\nQuorum: 2 of 3\n"), "{}", first);
+    assert!(std::fs::read_to_string(ws.path("shares/manifest.json")).unwrap().contains("\"Lawyer\""));
+
+    let entries = ["@shares/share-01-of-03.sss".to_string(), "@shares/share-03-of-03.sss".to_string()];
+    let outcome = ws.combine(&[], &entries, "pw");
+    assert_eq!(outcome.recovered(), Some("traceable"), "{}", outcome.output);
+    assert!(outcome.output.contains("share #1 is labelled 'Mom' (Blue folder)"), "{}", outcome.output);
+
+    let outcome = ws.sss(&["manifest", "verify", "shares"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+
+    let mut sss = ws.sss(&["decrypt"]);
+    sss.answer("Share #1", "@shares/share-02-of-03.sss");
+    sss.answer("Share #2", "");
+    let outcome = sss.finish();
+    assert!(outcome.output.contains("only 1 of the required 2 shares"), "{}", outcome.output);
+}
+
+#[test]
+fn conflicting_shares_are_named() {
+    let entered = vec![(vec![1, 10, 20], Some("aaaa".to_string())), (vec![2, 11, 21], None)];
+    assert_eq!(share_conflict(&entered, &[3, 12, 22], Some("AAAA")), None);
+    assert_eq!(share_conflict(&entered, &[2, 11, 21], None).unwrap(), "it is the same share as share #2");
+    assert!(share_conflict(&entered, &[1, 99, 99], None).unwrap().contains("index 1 like share #1"));
+    assert!(share_conflict(&entered, &[3, 12, 22], Some("bbbb")).unwrap().contains("set bbbb but share #1"));
+    assert!(share_conflict(&entered, &[3, 12, 22, 32], None).unwrap().contains("4 bytes but share #1 is 3"));
+}
+
+#[test]
+fn share_size_counts_the_conditions_in_front_of_the_payload() {
+    let ws = Workspace::new();
+    let dealer = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+    let locked = ["--not-before", "2030-01-01"];
+    for conditions in [&[][..], &locked, &[&locked[..], &["--dead-mans-switch", dealer]].concat()] {
+        let args = [&["--share-size", "256"], conditions].concat();
+        let shares = ws.split(&args, "sized", "pw", 3, 2);
+        for share in &shares {
+            assert_eq!(share.split(',').count(), 256, "{:?}: {}", conditions, share);
+        }
+        if conditions.is_empty() {
+            let outcome = ws.combine(&[], &shares[..2], "pw");
+            assert_eq!(outcome.recovered(), Some("sized"), "{}", outcome.output);
+        }
+    }
+}
+
+#[test]
+fn a_decoy_set_is_the_size_of_one_without() {
+    let ws = Workspace::new();
+    let plain = ws.split(&["--share-size", "256"], "real", "pw", 3, 2);
+
+    let mut sss = ws.sss(&["encrypt", "--decoy", "--share-size", "256"]);
+    sss.answer("Enter secret value", "real");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Enter the decoy secret", "decoy");
+    sss.answer("Enter decoy password", "duress");
+    sss.answer("Confirm decoy password", "duress");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let decoyed = shares_in(&outcome.output);
+    assert_eq!(decoyed[0].split(',').count(), plain[0].split(',').count());
+
+    let outcome = ws.combine(&[], &decoyed[1..], "duress");
+    assert_eq!(outcome.recovered(), Some("decoy"), "{}", outcome.output);
+}
@@ -0,0 +1,100 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use shamir_cli::envelope::{self, Header, HEADER_LEN};
+use shamir_cli::SssError;
+
+fn sealed(key: &envelope::Dek, data: &[u8], chunk_len: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    assert_eq!(envelope::seal(key, &mut &data[..], &mut out, chunk_len, &mut OsRng).unwrap(), data.len() as u64);
+    out
+}
+
+fn opened(key: &envelope::Dek, file: &[u8]) -> Result<Vec<u8>, SssError> {
+    let mut out = Vec::new();
+    envelope::open(key, &mut &file[..], &mut out).map(|_| out)
+}
+
+#[test]
+fn data_of_any_length_round_trips_in_chunks() {
+    let key = envelope::generate_key(&mut OsRng);
+    for len in [0, 1, 15, 16, 17, 64, 1000] {
+        let mut data = vec![0u8; len];
+        OsRng.fill_bytes(&mut data);
+        let file = sealed(&key, &data, 16);
+        assert_eq!(file.len(), HEADER_LEN + len + 16 * len.div_ceil(16).max(1), "len {}", len);
+        assert_eq!(Header::read(&mut &file[..]).unwrap().key_id, envelope::key_id(&key));
+        assert_eq!(opened(&key, &file).unwrap(), data, "len {}", len);
+    }
+}
+
+#[test]
+fn truncated_reordered_and_altered_files_fail() {
+    let key = envelope::generate_key(&mut OsRng);
+    let file = sealed(&key, &[7u8; 48], 16);
+    let chunk = 16 + 16;
+
+    // dropping the final chunk leaves a chunk that was not sealed as the last one
+    assert!(matches!(opened(&key, &file[..file.len() - chunk]), Err(SssError::Aead)));
+    assert!(matches!(opened(&key, &file[..HEADER_LEN]), Err(SssError::Aead)));
+    assert!(matches!(opened(&key, &file[..file.len() - 1]), Err(SssError::Aead)));
+
+    let mut swapped = file[..HEADER_LEN].to_vec();
+    swapped.extend_from_slice(&file[HEADER_LEN + chunk..HEADER_LEN + 2 * chunk]);
+    swapped.extend_from_slice(&file[HEADER_LEN..HEADER_LEN + chunk]);
+    swapped.extend_from_slice(&file[HEADER_LEN + 2 * chunk..]);
+    assert!(matches!(opened(&key, &swapped), Err(SssError::Aead)));
+
+    let mut altered = file.clone();
+    altered[HEADER_LEN + chunk + 3] ^= 1;
+    assert!(matches!(opened(&key, &altered), Err(SssError::Aead)));
+
+    // the chunk length is part of the associated data
+    let mut resized = file.clone();
+    resized[HEADER_LEN - 1] ^= 1;
+    assert!(opened(&key, &resized).is_err());
+}
+
+#[test]
+fn the_wrong_key_is_named_before_decrypting() {
+    let key = envelope::generate_key(&mut OsRng);
+    let file = sealed(&key, b"quarterly numbers", 1024);
+    let other = envelope::generate_key(&mut OsRng);
+    let err = opened(&other, &file).unwrap_err();
+    assert!(matches!(err, SssError::Share(ref msg) if msg.contains(&hex::encode(envelope::key_id(&key)))), "{}", err);
+    assert!(matches!(Header::read(&mut &b"sse2"[..]), Err(SssError::Parse(_))));
+}
+
+#[test]
+fn key_shares_are_checked_against_the_key_id() {
+    let key = envelope::generate_key(&mut OsRng);
+    let id = envelope::key_id(&key);
+    let shares = envelope::split_key(&key, 2, 3, &mut OsRng).unwrap();
+    assert!(shares.iter().all(|share| share.len() == 33));
+    assert_eq!(*envelope::combine_key(&shares[1..], &id).unwrap(), *key);
+    assert!(matches!(envelope::combine_key(&shares[..1], &id), Err(SssError::Share(_))));
+
+    let stray = envelope::split_key(&envelope::generate_key(&mut OsRng), 2, 3, &mut OsRng).unwrap();
+    assert!(matches!(envelope::combine_key(&[&shares[0], &stray[1]], &id), Err(SssError::Share(_))));
+}
+
+#[test]
+fn reshare_keeps_the_file_and_rotate_replaces_the_key() {
+    let key = envelope::generate_key(&mut OsRng);
+    let id = envelope::key_id(&key);
+    let file = sealed(&key, &[3u8; 100], 32);
+    let shares = envelope::split_key(&key, 2, 3, &mut OsRng).unwrap();
+
+    let reissued = envelope::reshare(&shares[..2], &id, 3, 5, &mut OsRng).unwrap();
+    assert_eq!(reissued.len(), 5);
+    assert_eq!(*envelope::combine_key(&reissued[2..], &id).unwrap(), *key);
+    assert!(envelope::reshare(&shares[..2], &id, 6, 5, &mut OsRng).is_err());
+
+    let mut rotated = Vec::new();
+    let new_key = envelope::rotate(&key, &mut &file[..], &mut rotated, &mut OsRng).unwrap();
+    assert_ne!(*new_key, *key);
+    assert_eq!(rotated.len(), file.len());
+    assert_eq!(opened(&new_key, &rotated).unwrap(), vec![3u8; 100]);
+    assert!(matches!(opened(&key, &rotated), Err(SssError::Share(_))));
+    assert!(envelope::rotate(&new_key, &mut &file[..], &mut Vec::new(), &mut OsRng).is_err());
+}
@@ -0,0 +1,74 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::input::InputError;
+use shamir_cli::SssError;
+use support::Workspace;
+
+#[test]
+fn exit_codes_follow_the_documented_contract() {
+    let cases = [
+        (SssError::Input(InputError::Empty), 2),
+        (SssError::InvalidArgument("x".into()), 2),
+        (SssError::Parse("x".into()), 3),
+        (SssError::Checksum("x".into()), 3),
+        (SssError::Aead, 4),
+        (SssError::WrongPassword, 4),
+        (SssError::Unauthorized("x".into()), 4),
+        (SssError::InsufficientShares("x".into()), 5),
+        (SssError::Share("x".into()), 6),
+    ];
+    for (error, code) in cases {
+        assert_eq!(error.exit_code(), code, "{}", error);
+    }
+
+    let outcome = Workspace::new().sss(&["--help"]).finish();
+    assert!(outcome.output.contains("Exit codes:") && outcome.output.contains("5   not enough shares"), "{}", outcome.output);
+}
+
+#[test]
+fn bad_arguments_exit_with_code_2() {
+    let ws = Workspace::new();
+    std::fs::write(ws.path("secret.txt"), "hush\n").unwrap();
+    std::fs::write(ws.path("password.txt"), "pw\n").unwrap();
+    let given = ["encrypt", "--secret-file", "secret.txt", "--password-file", "password.txt", "--no-cards"];
+    for (bad, message) in [
+        (&["--share-size", "50", "--total", "3", "--threshold", "2"][..], "share size must be larger than"),
+        (&["--total", "3", "--threshold", "5"][..], "Only 3 shares will be made"),
+        (&["--total", "3", "--threshold", "2", "--parity", "3"][..], "parity must be an even number"),
+    ] {
+        let args: Vec<&str> = given.iter().chain(bad).copied().collect();
+        let outcome = ws.sss(&args).finish();
+        assert_eq!(outcome.code, 2, "{:?}: {}", bad, outcome.output);
+        assert!(outcome.output.contains(&format!("invalid argument: {}", message)), "{:?}: {}", bad, outcome.output);
+    }
+}
+
+#[test]
+fn quiet_mode_prints_only_the_shares_and_the_secret() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["--quiet", "encrypt", "--share-label", "Mom"]);
+    sss.answer("Enter secret value", "hush");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(!outcome.output.contains("Share ID") && !outcome.output.contains("Mom"), "{}", outcome.output);
+    let shares: Vec<&str> = outcome.output.lines().map(str::trim).filter(|l| l.starts_with('[')).collect();
+    assert_eq!(shares.len(), 3, "{}", outcome.output);
+
+    let entries: Vec<String> = shares[1..].iter().map(|s| s.to_string()).collect();
+    let outcome = ws.combine(&["-q"], &entries, "pw");
+    assert!(outcome.success(), "{}", outcome.output);
+    let after = &outcome.output[outcome.output.find("Enter encryption password: [hidden]").unwrap()..];
+    let printed: Vec<&str> = after.lines().skip(1).map(str::trim).filter(|l| !l.is_empty()).collect();
+    assert_eq!(printed, ["hush"], "{}", outcome.output);
+
+    let mut sss = ws.sss(&["-q", "decrypt", "--threshold", "2"]);
+    sss.answer("Share #1", &entries[0]);
+    sss.answer("Share #2", "");
+    assert_eq!(sss.finish().code, 5);
+}
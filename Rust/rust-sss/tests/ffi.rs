@@ -0,0 +1,59 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::CStr;
+use std::ptr;
+
+use shamir_cli::ffi::*;
+
+fn empty_shares() -> SssShares {
+    SssShares { shares: ptr::null_mut(), count: 0 }
+}
+
+fn empty_buffer() -> SssBuffer {
+    SssBuffer { data: ptr::null_mut(), len: 0 }
+}
+
+#[test]
+fn split_and_combine_round_trip_bytes() {
+    let secret = [0u8, 1, 2, 255, 0, 7];
+    let mut shares = empty_shares();
+    unsafe {
+        assert_eq!(sss_split(secret.as_ptr(), secret.len(), c"pw".as_ptr(), 2, 3, &mut shares), SSS_OK);
+        assert_eq!(shares.count, 3);
+
+        let quorum = std::slice::from_raw_parts(shares.shares.add(1), 2);
+        let mut out = empty_buffer();
+        assert_eq!(sss_combine(quorum.as_ptr(), quorum.len(), c"pw".as_ptr(), &mut out), SSS_OK);
+        assert_eq!(std::slice::from_raw_parts(out.data, out.len), secret);
+        assert!(sss_last_error().is_null());
+
+        sss_buffer_free(&mut out);
+        assert!(out.data.is_null());
+        sss_shares_free(&mut shares);
+        assert!(shares.shares.is_null() && shares.count == 0);
+        sss_shares_free(&mut shares);
+    }
+}
+
+#[test]
+fn failures_return_a_fixed_code_and_a_message() {
+    let mut shares = empty_shares();
+    unsafe {
+        assert_eq!(sss_split(b"x".as_ptr(), 1, c"pw".as_ptr(), 2, 2, &mut shares), SSS_OK);
+        let mut out = empty_buffer();
+        assert_eq!(sss_combine(shares.shares, shares.count, c"wrong".as_ptr(), &mut out), SSS_ERR_DECRYPT);
+        assert!(out.data.is_null());
+        assert!(!CStr::from_ptr(sss_last_error()).to_bytes().is_empty());
+
+        assert_eq!(sss_split(b"x".as_ptr(), 1, ptr::null(), 2, 2, &mut empty_shares()), SSS_ERR_INPUT);
+        assert!(CStr::from_ptr(sss_last_error()).to_str().unwrap().contains("password"));
+        assert_eq!(sss_split(b"x".as_ptr(), 1, c"pw".as_ptr(), 3, 2, &mut empty_shares()), SSS_ERR_INPUT);
+
+        let header = include_str!("../include/sss.h");
+        let codes = [("SSS_OK", SSS_OK), ("SSS_ERR_DECRYPT", SSS_ERR_DECRYPT), ("SSS_ERR_PANIC", SSS_ERR_PANIC)];
+        for (name, value) in codes {
+            assert!(header.contains(&format!("#define {} {}\n", name, value)), "{}", name);
+        }
+        sss_shares_free(&mut shares);
+    }
+}
@@ -0,0 +1,55 @@
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use shamir_cli::fountain::{is_frame, Decoder, Encoder, Frame};
+use shamir_cli::SssError;
+
+fn message() -> Vec<u8> {
+    (0..5_000u32).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+#[test]
+fn frames_rebuild_the_message_in_any_order_without_the_plain_fragments() {
+    let message = message();
+    let encoder = Encoder::new(&message, 200).unwrap();
+    assert_eq!(encoder.fragment_count(), 25);
+
+    // only mixed frames, shuffled, as a camera joining a loop late would see them
+    let count = encoder.fragment_count() as u32;
+    let mut seqs: Vec<u32> = (count + 1..=count * 4).collect();
+    seqs.shuffle(&mut rand_chacha::ChaCha20Rng::seed_from_u64(7));
+    let mut decoder: Option<Decoder> = None;
+    for seq in seqs {
+        let frame = Frame::parse(&encoder.frame(seq)).unwrap();
+        match &mut decoder {
+            Some(decoder) => decoder.receive(frame).unwrap(),
+            None => decoder = Some(Decoder::new(frame)),
+        }
+        if decoder.as_ref().unwrap().message().unwrap().is_some() {
+            break;
+        }
+    }
+    let rebuilt = decoder.unwrap().message().unwrap().expect("four loops' worth of frames suffice");
+    assert_eq!(rebuilt.as_slice(), message.as_slice());
+}
+
+#[test]
+fn frames_are_checked() {
+    let encoder = Encoder::new(b"short message", 5).unwrap();
+    let frame = encoder.frame(1);
+    assert!(is_frame(&frame) && frame.starts_with("SSSF:1/3:13:"));
+    for bad in ["SSSF:0/3:13:00000000:AA", "SSSF:1/3:13:000000:AABB", "SSSF:1/2:13:00000000:AABBCCDDEE", "SSSF:1"] {
+        assert!(matches!(Frame::parse(bad), Err(SssError::Parse(_))), "{}", bad);
+    }
+
+    // a damaged fragment is caught by the checksum once everything is in
+    let mut decoder = Decoder::new(Frame::parse(&frame.replace(":73686", ":73687")).unwrap());
+    for seq in 2..=3 {
+        decoder.receive(Frame::parse(&encoder.frame(seq)).unwrap()).unwrap();
+    }
+    assert!(matches!(decoder.message(), Err(SssError::Checksum(_))));
+
+    let other = Encoder::new(b"another message", 5).unwrap();
+    assert!(decoder.receive(Frame::parse(&other.frame(1)).unwrap()).is_err());
+    assert!(matches!(Encoder::new(b"", 5), Err(SssError::Parse(_))));
+}
@@ -0,0 +1,54 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::fragment;
+use support::Workspace;
+
+#[test]
+fn any_needed_fragments_rebuild_the_share() {
+    let share: Vec<u8> = (1..=77u8).collect();
+    let fragments = fragment::split(&share, 3, 5).unwrap();
+    assert!(fragments.iter().all(|f| f.data.len() == 26));
+    for a in 0..5 {
+        for b in a + 1..5 {
+            for c in b + 1..5 {
+                let some = [fragments[a].clone(), fragments[b].clone(), fragments[c].clone()];
+                assert_eq!(fragment::join(&some).unwrap(), share, "{} {} {}", a, b, c);
+            }
+        }
+    }
+    assert!(fragment::join(&fragments[..2]).is_err());
+    assert!(fragment::join(&[fragments[4].clone(), fragments[4].clone(), fragments[1].clone()]).is_err());
+
+    let mut damaged = fragments[3..].to_vec();
+    damaged.push(fragments[0].clone());
+    damaged[0].data[7] ^= 1;
+    assert!(matches!(fragment::join(&damaged), Err(shamir_cli::SssError::Checksum(_))));
+    assert!(fragment::split(&share, 4, 3).is_err());
+}
+
+#[test]
+fn cli_decrypt_gathers_fragments_from_a_manifest() {
+    let ws = Workspace::new();
+    let shares = ws.split(&[], "spread thin", "pw", 3, 2);
+    let files = ws.distribute(&shares);
+
+    let outcome = ws
+        .sss(&[
+            "fragment", "split", "share-1.txt", "--needed", "2", "--total", "3", "--to", "usb-a", "--to", "usb-b",
+            "--to", "usb-c",
+        ])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(ws.path("usb-c/share-1.fragments.json").exists());
+    std::fs::remove_dir_all(ws.path("usb-b")).unwrap();
+
+    let entries = vec!["@usb-c/share-1.fragments.json".to_string(), format!("@{}", files[2].display())];
+    let outcome = ws.combine(&[], &entries, "pw");
+    assert_eq!(outcome.recovered(), Some("spread thin"), "{}", outcome.output);
+
+    std::fs::remove_file(ws.path("usb-a/share-1.frag-1-of-3.json")).unwrap();
+    let outcome = ws.sss(&["fragment", "join", "usb-c/share-1.fragments.json"]).finish();
+    assert_eq!(outcome.code, 5, "{}", outcome.output);
+}
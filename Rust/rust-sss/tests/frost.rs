@@ -0,0 +1,55 @@
+#![cfg(feature = "frost")]
+
+use ed25519_dalek::Verifier;
+use shamir_cli::frost::{self, Suite};
+
+fn sign(dealt: &frost::Dealt, signers: &[usize], message: &[u8]) -> shamir_cli::Result<Vec<u8>> {
+    let rounds: Vec<_> = signers
+        .iter()
+        .map(|&i| frost::round1(&dealt.key_packages[i]).unwrap())
+        .collect();
+    let commitments: Vec<String> = rounds.iter().map(|(_, c)| c.clone()).collect();
+    let shares = signers
+        .iter()
+        .zip(&rounds)
+        .map(|(&i, (nonces, _))| frost::round2(&dealt.key_packages[i], nonces, &commitments, message))
+        .collect::<shamir_cli::Result<Vec<_>>>()?;
+    frost::aggregate(&dealt.public_package, &commitments, &shares, message)
+}
+
+#[test]
+fn imported_ed25519_key_signs_as_itself() {
+    let seed = [7u8; 32];
+    let dealt = frost::keygen(Suite::Ed25519, Some(&seed), 2, 3).unwrap();
+    let key = ed25519_dalek::SigningKey::from_bytes(&seed).verifying_key();
+    assert_eq!(dealt.group_key, key.to_bytes());
+    assert_eq!(frost::group_key(&dealt.public_package).unwrap(), dealt.group_key);
+
+    let signature = sign(&dealt, &[0, 2], b"release v1.2.3").unwrap();
+    let signature = ed25519_dalek::Signature::from_slice(&signature).unwrap();
+    key.verify(b"release v1.2.3", &signature).unwrap();
+    assert!(key.verify(b"release v1.2.4", &signature).is_err());
+}
+
+#[test]
+fn secp256k1_quorum_signs() {
+    let dealt = frost::keygen(Suite::Secp256k1, None, 3, 5).unwrap();
+    assert_eq!(dealt.group_key.len(), 33);
+    assert_eq!(sign(&dealt, &[4, 1, 3], b"tx").unwrap().len(), 65);
+    assert!(sign(&dealt, &[0, 1], b"tx").is_err());
+}
+
+#[test]
+fn messages_are_checked() {
+    let ed = frost::keygen(Suite::Ed25519, None, 2, 2).unwrap();
+    let k1 = frost::keygen(Suite::Secp256k1, None, 2, 2).unwrap();
+    let (nonces, commitments) = frost::round1(&ed.key_packages[0]).unwrap();
+    let (_, other) = frost::round1(&k1.key_packages[1]).unwrap();
+
+    // wrong kind, mixed curves, and a repeated signer
+    assert!(frost::round1(&ed.public_package).is_err());
+    assert!(frost::round2(&ed.key_packages[0], &nonces, &[commitments.clone(), other], b"m").is_err());
+    assert!(frost::round2(&ed.key_packages[0], &nonces, &[commitments.clone(), commitments], b"m").is_err());
+    assert!(frost::keygen(Suite::Ed25519, Some(&[1u8; 31]), 2, 3).is_err());
+    assert!(frost::keygen(Suite::Ed25519, None, 4, 3).is_err());
+}
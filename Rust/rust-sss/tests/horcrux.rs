@@ -0,0 +1,76 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::horcrux::{self, Horcrux};
+use support::Workspace;
+
+const DIARY: &[u8] = b"Tom Riddle's diary, page one. Nothing interesting happens here, probably.";
+
+fn reread(horcruxes: &[Horcrux]) -> Vec<Horcrux> {
+    horcruxes.iter().map(|h| Horcrux::parse(&h.to_bytes().unwrap()).unwrap()).collect()
+}
+
+#[test]
+fn split_and_bind_with_a_threshold() {
+    let horcruxes = horcrux::split(DIARY, "diary.txt", 1_700_000_000, 3, 5).unwrap();
+    assert_eq!(horcruxes[1].file_name(), "diary_2_of_5.horcrux");
+    assert!(horcruxes.iter().all(|h| h.body.len() == DIARY.len() && h.body != DIARY));
+
+    let text = String::from_utf8_lossy(&horcruxes[0].to_bytes().unwrap()).into_owned();
+    assert!(text.starts_with("# THIS FILE IS A HORCRUX."));
+    assert!(text.contains("\n-- HEADER --\n{\"originalFilename\":\"diary.txt\",\"timestamp\":1700000000,"));
+
+    let mut some: Vec<Horcrux> = reread(&horcruxes).into_iter().rev().step_by(2).collect();
+    let (name, data) = horcrux::bind(&mut some).unwrap();
+    assert_eq!((name.as_str(), data.as_slice()), ("diary.txt", DIARY));
+
+    let mut too_few = reread(&horcruxes[..2]);
+    assert!(matches!(horcrux::bind(&mut too_few), Err(shamir_cli::SssError::InsufficientShares(_))));
+    let mut twice = reread(&[&horcruxes[..2], &horcruxes[..1]].concat());
+    assert!(horcrux::bind(&mut twice).is_err());
+}
+
+#[test]
+fn every_horcrux_needed_deals_the_body_out() {
+    let horcruxes = horcrux::split(DIARY, "diary.txt", 1, 3, 3).unwrap();
+    let lengths: Vec<usize> = horcruxes.iter().map(|h| h.body.len()).collect();
+    assert_eq!(lengths.iter().sum::<usize>(), DIARY.len());
+    assert!(lengths.iter().all(|&l| l == DIARY.len().div_ceil(3) || l == DIARY.len() / 3));
+
+    let mut all = reread(&horcruxes);
+    all.swap(0, 2);
+    assert_eq!(horcrux::bind(&mut all).unwrap().1.as_slice(), DIARY);
+
+    let other = horcrux::split(DIARY, "diary.txt", 2, 3, 3).unwrap();
+    let mut mixed = reread(&[&horcruxes[..2], &other[2..]].concat());
+    assert!(horcrux::bind(&mut mixed).is_err());
+}
+
+#[test]
+fn parse_rejects_other_files() {
+    assert!(Horcrux::parse(b"just a text file\n").is_err());
+    assert!(Horcrux::parse(b"-- HEADER --\n{\"originalFilename\":\"x\"}\n-- BODY --\n").is_err());
+    let bytes = horcrux::split(b"x", "x", 0, 2, 2).unwrap()[0].to_bytes().unwrap();
+    let truncated = &bytes[..bytes.len() - b"-- BODY --\n".len() - 1];
+    assert!(Horcrux::parse(truncated).is_err());
+}
+
+#[test]
+fn cli_split_and_bind() {
+    let ws = Workspace::new();
+    std::fs::write(ws.path("diary.txt"), DIARY).unwrap();
+    let outcome = ws
+        .sss(&["horcrux", "split", "diary.txt", "--threshold", "2", "--total", "3", "--out-dir", "pieces"])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    std::fs::remove_file(ws.path("pieces/diary_2_of_3.horcrux")).unwrap();
+
+    let outcome = ws.sss(&["horcrux", "bind", "pieces", "--out", "back.txt"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert_eq!(std::fs::read(ws.path("back.txt")).unwrap(), DIARY);
+
+    std::fs::remove_file(ws.path("pieces/diary_3_of_3.horcrux")).unwrap();
+    let outcome = ws.sss(&["horcrux", "bind", "pieces/diary_1_of_3.horcrux"]).finish();
+    assert_eq!(outcome.code, 5, "{}", outcome.output);
+}
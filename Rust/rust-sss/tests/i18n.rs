@@ -0,0 +1,87 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::card::RecoveryCard;
+use shamir_cli::i18n::{self, Catalog, Problem};
+use shamir_cli::tr;
+use support::Workspace;
+
+#[test]
+fn catalogs_read_plain_fluent_messages() {
+    let catalog = Catalog::parse("nl", "# Nederlands\ncard-title = HERSTELKAART\ncard-keep = Bewaar\n  deze kaart\n").unwrap();
+    assert_eq!(catalog.get("card-title"), Some("HERSTELKAART"));
+    assert_eq!(catalog.get("card-keep"), Some("Bewaar\ndeze kaart"));
+
+    for broken in ["-brand = Shamir", "card-title = { $n ->\n *[one] kaart\n}", "card-title = a\ncard-title = b", "= a"] {
+        assert!(Catalog::parse("nl", broken).is_err(), "{}", broken);
+    }
+
+    let partial = Catalog::parse("nl", "card-share = kaart\ncard-tile = HERSTELKAART\n").unwrap();
+    let problems = partial.check();
+    assert!(problems.contains(&Problem::Unknown("card-tile".into())), "{:?}", problems);
+    assert!(problems.contains(&Problem::Missing("card-title".into())), "{:?}", problems);
+    assert!(problems.iter().any(|p| matches!(p, Problem::Placeables { id, .. } if id == "card-share")));
+
+    assert_eq!(i18n::candidates("de_AT.UTF-8"), ["de-AT", "de"]);
+    assert_eq!(i18n::candidates("C"), ["en"]);
+}
+
+#[test]
+fn built_in_translations_are_complete_and_used_on_cards() {
+    for language in Catalog::builtin_languages() {
+        let problems = Catalog::builtin(language).unwrap().check();
+        assert!(problems.is_empty(), "{}: {:?}", language, problems);
+    }
+
+    let card = RecoveryCard {
+        command: "sss decrypt --set 4f1c2a9e".into(),
+        format_version: 1,
+        set_fingerprint: "4f1c2a9e".into(),
+        share_index: 2,
+        threshold: 2,
+        total: 3,
+    };
+    assert_eq!(card.lines()[0], format!("{:<26}{:>14}", "SHAMIR RECOVERY CARD", "share #2"));
+    i18n::set_language("de_DE.UTF-8").unwrap();
+    let lines = card.lines();
+    assert!(lines[0].starts_with("WIEDERHERSTELLUNGSKARTE") && lines[0].ends_with("Anteil Nr. 2"), "{:?}", lines);
+    assert!(lines.contains(&"Quorum: beliebige 2 von 3 Anteilen".to_string()), "{:?}", lines);
+    assert!(card.to_text().lines().all(|line| line.chars().count() == 44), "{}", card.to_text());
+    assert_eq!(tr!("too-few-shares", given = 1, threshold = 2), "nur 1 der erforderlichen 2 Anteile wurden angegeben");
+    assert!(i18n::set_language("tlh").is_err());
+    i18n::set_language("en").unwrap();
+}
+
+#[test]
+fn cli_prompts_in_the_chosen_language() {
+    let ws = Workspace::new();
+    std::fs::create_dir(ws.path("locales")).unwrap();
+    std::fs::write(ws.path("locales/nl.ftl"), "prompt-share = Deel #{ $number } (leeg om te stoppen)\n").unwrap();
+
+    let mut sss = ws.sss(&["--lang", "de", "encrypt"]);
+    sss.answer("Geheimnis eingeben", "mehrsprachig");
+    sss.answer("Verschlüsselungspasswort eingeben", "pw");
+    sss.answer("Verschlüsselungspasswort bestätigen", "pw");
+    sss.answer("Gesamtzahl der Anteile", "3");
+    sss.answer("Mindestens benötigte Anteile", "2");
+    sss.confirm("Wiederherstellungskarten", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let shares = support::shares_in(&outcome.output);
+
+    let mut sss = ws.sss(&["--lang", "nl", "decrypt"]);
+    sss.answer("Deel #1", &shares[0]);
+    sss.answer("Deel #2", &shares[2]);
+    sss.answer("Deel #3", "");
+    sss.answer("Enter encryption password", "pw");
+    let outcome = sss.finish();
+    assert_eq!(outcome.recovered(), Some("mehrsprachig"), "{}", outcome.output);
+
+    let outcome = ws.sss(&["locale", "check", "locales/nl.ftl"]).finish();
+    assert_eq!(outcome.code, 3, "{}", outcome.output);
+    assert!(outcome.output.contains("card-title: not translated"), "{}", outcome.output);
+    let outcome = ws.sss(&["locale", "list"]).finish();
+    assert!(outcome.output.contains("installed") && outcome.output.contains("de "), "{}", outcome.output);
+    assert_eq!(ws.sss(&["--lang", "tlh", "locale", "list"]).finish().code, 2);
+}
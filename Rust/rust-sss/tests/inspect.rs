@@ -0,0 +1,62 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::format::{ShareEncoding, ShareHeader};
+use shamir_cli::inspect;
+use shamir_cli::shamir::Field;
+use support::Workspace;
+
+#[test]
+fn loose_share_is_described_without_a_set() {
+    let ws = Workspace::new();
+    let share: Vec<u8> = [2u8].iter().chain(&[7u8; 68]).copied().collect();
+    let header = ShareHeader { label: Some("Lawyer".into()), set: Some("0badc0de".into()), ..Default::default() };
+    std::fs::write(ws.path("old.txt"), header.prepend(&ShareEncoding::Bech32.encode(&share).unwrap())).unwrap();
+
+    let info = inspect::inspect(&ws.path("old.txt"), Field::Gf256).unwrap();
+    assert_eq!((info.index, info.len, info.encoding.as_str()), (2, 69, "bech32"));
+    assert_eq!(info.label.as_deref(), Some("Lawyer"));
+    assert_eq!(info.checksum.as_deref(), Some("bech32m checksum valid"));
+    assert_eq!(info.padded_secret_len, Some(16));
+    assert!(info.set.is_none());
+    assert_eq!(info.declared_set.as_deref(), Some("0badc0de"));
+
+    std::fs::write(ws.path("blank.txt"), "[0, 1, 2]").unwrap();
+    assert!(matches!(inspect::inspect(&ws.path("blank.txt"), Field::Gf256), Err(shamir_cli::SssError::Share(_))));
+}
+
+#[test]
+fn cli_inspects_a_box_of_shares() {
+    let ws = Workspace::new();
+    assert!(ws.sss(&["token", "keygen", "--out", "dealer.key"]).finish().success());
+    let mut sss = ws.sss(&[
+        "encrypt", "--out-dir", "shares", "--manifest-key", "dealer.key", "--share-label", "Mom", "--share-note",
+        "Blue folder",
+    ]);
+    sss.answer("Enter secret value", "audited");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    assert!(sss.finish().success());
+    std::fs::write(ws.path("junk.txt"), "not a share").unwrap();
+
+    let outcome = ws.sss(&["inspect", "shares/share-01-of-03.sss", "shares/share-02-of-03.sss"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains("'Mom' (Blue folder)"), "{}", outcome.output);
+    assert!(outcome.output.contains("any 2 of 3 shares"), "{}", outcome.output);
+    assert!(outcome.output.contains("lists this share; signature valid ("), "{}", outcome.output);
+
+    let outcome = ws
+        .sss(&["--output-format", "json", "inspect", "shares/share-03-of-03.sss", "junk.txt"])
+        .finish();
+    assert_eq!(outcome.code, 6, "{}", outcome.output);
+    let start = outcome.output.find('[').unwrap();
+    let end = outcome.output.rfind(']').unwrap();
+    let report: serde_json::Value = serde_json::from_str(&outcome.output[start..=end]).unwrap();
+    assert_eq!(report[0]["share"]["index"], 3);
+    assert_eq!(report[0]["share"]["set"]["threshold"], 2);
+    assert_eq!(report[1]["ok"], false);
+}
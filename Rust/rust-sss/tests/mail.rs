@@ -0,0 +1,54 @@
+#![cfg(feature = "smtp")]
+
+use lettre::transport::stub::StubTransport;
+
+use shamir_cli::mail::{message, plan, send_all, LogEntry};
+
+fn shares_and_cards() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for n in 1..=2 {
+        std::fs::create_dir_all(dir.path().join("age-shares")).unwrap();
+        let share = dir.path().join(format!("age-shares/share-{}.age", n));
+        std::fs::write(share, "-----BEGIN AGE ENCRYPTED FILE-----").unwrap();
+    }
+    std::fs::create_dir_all(dir.path().join("cards")).unwrap();
+    std::fs::write(dir.path().join("cards/card-002.txt"), "SHAMIR RECOVERY CARD").unwrap();
+    dir
+}
+
+#[test]
+fn each_custodian_gets_their_share_and_card() {
+    let dir = shares_and_cards();
+    let (shares, cards) = (dir.path().join("age-shares"), dir.path().join("cards"));
+    let to = ["alice@example.com".to_string(), "bob@example.com".to_string()];
+    assert!(plan(&shares, Some(&cards), &to[..1]).is_err());
+    assert!(plan(&cards, None, &to).is_err());
+
+    let deliveries = plan(&shares, Some(&cards), &to).unwrap();
+    assert_eq!(deliveries[0].index, 1);
+    assert!(deliveries[0].cards.is_empty());
+    assert_eq!(deliveries[1].cards, vec![cards.join("card-002.txt")]);
+
+    let mail = String::from_utf8(message("dealer@example.com", &deliveries[1]).unwrap().formatted()).unwrap();
+    assert!(mail.contains("To: bob@example.com"), "{}", mail);
+    assert!(mail.contains("filename=\"share-2.age\""), "{}", mail);
+    assert!(mail.contains("filename=\"card-002.txt\""), "{}", mail);
+    assert!(message("not an address", &deliveries[0]).is_err());
+}
+
+#[test]
+fn every_delivery_attempt_is_logged() {
+    let dir = shares_and_cards();
+    let to = ["alice@example.com".to_string(), "bob@example.com".to_string()];
+    let deliveries = plan(&dir.path().join("age-shares"), None, &to).unwrap();
+    let log = dir.path().join("distribution.log");
+
+    let sent = send_all(&StubTransport::new_ok(), "dealer@example.com", &deliveries, &log).unwrap();
+    assert!(sent.iter().all(|entry| entry.sent));
+    let failed = send_all(&StubTransport::new_error(), "dealer@example.com", &deliveries, &log).unwrap();
+    assert!(failed.iter().all(|entry| !entry.sent && entry.detail.contains("SMTP")));
+
+    let logged: Vec<LogEntry> =
+        std::fs::read_to_string(&log).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(logged, [sent, failed].concat());
+}
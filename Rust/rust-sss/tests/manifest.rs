@@ -0,0 +1,133 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::manifest::{self, Manifest};
+use support::Workspace;
+
+#[test]
+fn share_files_are_named_in_order() {
+    assert_eq!(manifest::share_file_name(1, 5), "share-01-of-05.sss");
+    assert_eq!(manifest::share_file_name(7, 120), "share-007-of-120.sss");
+}
+
+#[test]
+fn out_dir_writes_a_signed_manifest() {
+    let ws = Workspace::new();
+    let outcome = ws.sss(&["token", "keygen", "--out", "dealer.key"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let issuer = outcome.output.lines().find_map(|l| l.strip_prefix("Issuer key (give this to coordinators): "));
+    let issuer = issuer.expect("issuer key printed").trim().to_string();
+
+    let mut sss = ws.sss(&[
+        "encrypt", "--out-dir", "shares", "--manifest-key", "dealer.key",
+        "--custodian", "alice:DE", "--custodian", "bob:FR", "--custodian", "carol:US",
+    ]);
+    sss.answer("Enter secret value", "filed away");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(support::shares_in(&outcome.output).is_empty(), "{}", outcome.output);
+
+    let document = std::fs::read_to_string(ws.path("shares/manifest.json")).unwrap();
+    let parsed: Manifest = serde_json::from_str(&document).unwrap();
+    assert_eq!((parsed.threshold, parsed.total, parsed.field.as_str()), (2, 3, "gf256"));
+    assert_eq!(parsed.shares[1].file, "share-02-of-03.sss");
+    assert_eq!(parsed.shares[2].label.as_deref(), Some("carol"));
+
+    let outcome = ws.sss(&["manifest", "verify", "shares", "--signer", &issuer]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+
+    let entries = vec!["@shares/share-03-of-03.sss".to_string(), "@shares/share-01-of-03.sss".to_string()];
+    let outcome = ws.combine(&[], &entries, "pw");
+    assert_eq!(outcome.recovered(), Some("filed away"), "{}", outcome.output);
+
+    let mut tampered = parsed.clone();
+    tampered.threshold = 1;
+    std::fs::write(ws.path("shares/manifest.json"), serde_json::to_string(&tampered).unwrap()).unwrap();
+    assert_eq!(ws.sss(&["manifest", "verify", "shares"]).finish().code, 11);
+
+    std::fs::write(ws.path("shares/manifest.json"), &document).unwrap();
+    std::fs::write(ws.path("shares/share-02-of-03.sss"), "00ff\n").unwrap();
+    let outcome = ws.sss(&["manifest", "verify", "shares"]).finish();
+    assert_eq!(outcome.code, 3, "{}", outcome.output);
+    assert!(outcome.output.contains("FAILED   share #2"), "{}", outcome.output);
+}
+
+#[test]
+fn quorum_simulation_flags_risky_holders() {
+    use shamir_cli::policy::{holders, simulate};
+
+    let report = simulate(holders(["ann", "ben", "ann", "cat", "dan"]), 3, 50);
+    assert_eq!(report.total, 5);
+    assert_eq!(report.holders[0].shares, 2);
+    assert_eq!(
+        report.quorums,
+        [vec!["ann", "ben"], vec!["ann", "cat"], vec!["ann", "dan"], vec!["ben", "cat", "dan"]]
+    );
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+    let report = simulate(holders(["ann", "ann", "ben", "cat"]), 2, 1);
+    assert_eq!((report.quorums.len(), report.truncated), (1, true));
+    let report = simulate(holders(["ann", "ann", "ben"]), 2, 50);
+    assert_eq!((report.quorums, report.truncated), (vec![vec!["ann".to_string()]], false));
+    assert!(report.warnings.iter().any(|w| w.contains("ann holds 2 shares")), "{:?}", report.warnings);
+    assert!(report.warnings.iter().any(|w| w.starts_with("without ann's")), "{:?}", report.warnings);
+    assert!(simulate(holders(["a", "b"]), 2, 50).warnings[0].contains("every share is required"));
+}
+
+#[test]
+fn cli_quorum_report_from_a_share_directory() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&[
+        "encrypt", "--out-dir", "shares", "--share-label", "Mom", "--share-label", "Mom", "--share-label", "Lawyer",
+    ]);
+    sss.answer("Enter secret value", "planned");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    assert!(sss.finish().success());
+
+    let outcome = ws.sss(&["manifest", "quorum", "shares"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains("Mom (2 shares)"), "{}", outcome.output);
+    assert!(outcome.output.contains("Mom holds 2 shares and can recover the secret alone"), "{}", outcome.output);
+    assert_eq!(ws.sss(&["manifest", "quorum", "shares", "--strict"]).finish().code, 10);
+}
+
+#[test]
+fn pedersen_sets_publish_commitments_custodians_can_check() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--field", "pedersen", "--out-dir", "escrow", "--total", "3", "--threshold", "2"]);
+    sss.answer("Enter secret value", "escrowed for decades");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains("Pedersen commitments (publish"), "{}", outcome.output);
+
+    let parsed = manifest::read_manifest(&ws.path("escrow")).unwrap();
+    assert_eq!(parsed.field, "pedersen");
+    assert_eq!(parsed.commitments.as_ref().map(|c| c.threshold), Some(2));
+    let outcome = ws.sss(&["manifest", "verify", "escrow"]).finish();
+    assert!(outcome.success() && outcome.output.contains("Pedersen commitments"), "{}", outcome.output);
+    let outcome = ws.sss(&["manifest", "check-share", "escrow", "escrow/share-02-of-03.sss"]).finish();
+    assert!(outcome.output.contains("matches the set's Pedersen commitments"), "{}", outcome.output);
+
+    let entries = vec!["@escrow/share-01-of-03.sss".to_string(), "@escrow/share-03-of-03.sss".to_string()];
+    let outcome = ws.combine(&["--field", "pedersen"], &entries, "pw");
+    assert_eq!(outcome.recovered(), Some("escrowed for decades"), "{}", outcome.output);
+
+    // commitments altered after publishing no longer open for the custodian's share
+    let mut other = manifest::read_manifest(&ws.path("escrow")).unwrap();
+    other.commitments.as_mut().unwrap().chunks.reverse();
+    std::fs::write(ws.path("escrow/manifest.json"), serde_json::to_string(&other).unwrap()).unwrap();
+    let outcome = ws.sss(&["manifest", "check-share", "escrow", "escrow/share-02-of-03.sss"]).finish();
+    assert_eq!(outcome.code, 6, "{}", outcome.output);
+}
@@ -0,0 +1,77 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::crypto::{encrypt_data, PBKDF2_ROUNDS};
+use shamir_cli::legacy::{self, Saved};
+use shamir_cli::secret::{deserialize_secret, SecretType};
+use shamir_cli::shamir::Field;
+use support::Workspace;
+
+/// What the first versions printed: the plaintext zero-padded to 32 bytes, salt ‖ nonce ‖
+/// ciphertext split into byte lists, and the salt and nonce to save.
+fn old_split(secret: &str, password: &str, ciphertext_only: bool) -> (Vec<String>, String) {
+    let mut plaintext = vec![0u8];
+    plaintext.extend_from_slice(&(secret.len() as u32).to_be_bytes());
+    plaintext.extend_from_slice(secret.as_bytes());
+    plaintext.resize(32, 0);
+    let (ciphertext, salt, nonce) = encrypt_data(&plaintext, password, PBKDF2_ROUNDS).unwrap();
+    let payload = if ciphertext_only { ciphertext } else { [&salt[..], &nonce[..], &ciphertext].concat() };
+    let shares = Field::Gf256.split(&payload, 2, 3).unwrap().iter().map(|s| format!("{:?}", s)).collect();
+    let saved = format!("SAVE THESE VALUES FOR DECRYPTION:\nSalt: {}\nNonce: {}\n", hex::encode(salt), hex::encode(nonce));
+    (shares, saved)
+}
+
+#[test]
+fn old_payloads_open_with_or_without_their_salt_and_nonce() {
+    let (_, saved_text) = old_split("unused", "pw", false);
+    let saved = Saved::parse(&saved_text).unwrap();
+    assert!(Saved::parse("Salt: 00ff\nNonce: 00\n").is_err());
+    assert!(Saved::parse("Nonce: 00\n").is_err());
+
+    for ciphertext_only in [false, true] {
+        let (shares, saved_text) = old_split("in the old format", "pw", ciphertext_only);
+        let shares: Vec<Vec<u8>> = shares.iter().map(|s| shamir_cli::input::parse_share(s).unwrap()).collect();
+        let payload = Field::Gf256.reconstruct(&shares[1..]).unwrap();
+        assert!(legacy::is_legacy(&payload));
+        let saved = Saved::parse(&saved_text).unwrap();
+        let upgraded = legacy::upgrade(&legacy::open(&payload, Some(&saved), "pw").unwrap()).unwrap();
+        assert!(matches!(deserialize_secret(&upgraded).unwrap(), SecretType::String(ref s) if s == "in the old format"));
+        if !ciphertext_only {
+            assert!(legacy::open(&payload, None, "pw").is_ok());
+        }
+        assert!(legacy::open(&payload, Some(&saved), "wrong").is_err());
+    }
+    assert_ne!(saved.salt, [0; 16]);
+}
+
+#[test]
+fn cli_migrates_an_old_set_to_the_current_format() {
+    let ws = Workspace::new();
+    let (shares, saved) = old_split("from the first release", "old-pw", false);
+    std::fs::write(ws.path("saved.txt"), saved).unwrap();
+
+    let mut sss = ws.sss(&["migrate", "--saved", "saved.txt", "--total", "3", "--threshold", "2", "--out-dir", "new"]);
+    sss.answer("Share #1", &shares[2]);
+    sss.answer("Share #2", &shares[0]);
+    sss.answer("Share #3", "");
+    sss.answer("Enter the old set's encryption password", "old-pw");
+    sss.answer("Enter new encryption password", "new-pw");
+    sss.answer("Confirm new encryption password", "new-pw");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(!outcome.output.contains("from the first release"), "{}", outcome.output);
+
+    let text = std::fs::read_to_string(ws.path("new/share-01-of-03.sss")).unwrap();
+    assert!(text.contains("Set: ") && text.contains("Quorum: 2 of 3"), "{}", text);
+    let files: Vec<String> = (1..=2).map(|n| format!("@new/share-0{}-of-03.sss", n)).collect();
+    assert_eq!(ws.combine(&[], &files, "new-pw").recovered(), Some("from the first release"));
+
+    // shares in the current format are rotated, not migrated
+    let mut sss = ws.sss(&["migrate"]);
+    sss.answer("Share #1", &files[0]);
+    sss.answer("Share #2", &files[1]);
+    sss.answer("Share #3", "");
+    assert_eq!(sss.finish().code, 3);
+}
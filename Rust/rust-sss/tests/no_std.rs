@@ -0,0 +1,25 @@
+//! What the library does built without `std`: `cargo test --no-default-features --test no_std`.
+
+#![cfg(not(feature = "std"))]
+
+use rand::rngs::mock::StepRng;
+use shamir_cli::crypto::{encrypt_data_with_aad, kdf_header, open_payload};
+use shamir_cli::error::SssError;
+use shamir_cli::format::SetBinding;
+use shamir_cli::shamir;
+
+#[test]
+fn a_bound_payload_round_trips_through_shares() {
+    let mut rng = StepRng::new(7, 13);
+    let binding = SetBinding::new(2, &mut rng).header();
+    let (ciphertext, salt, nonce) = encrypt_data_with_aad(b"embedded", "pw", 1_000, &binding, &mut rng).unwrap();
+    let payload = [&binding[..], &kdf_header(1_000), &salt, &nonce, &ciphertext].concat();
+    let shares = shamir::split_with_rng(&payload, 2, 3, &mut rng).unwrap();
+    let payload = shamir::reconstruct(&shares[1..]).unwrap();
+    assert_eq!(&*open_payload(&payload, "pw").unwrap(), b"embedded");
+    assert!(matches!(open_payload(&payload, "wrong"), Err(SssError::Aead)));
+
+    let mut edited = payload.clone();
+    edited[4] ^= 1;
+    assert!(matches!(open_payload(&edited, "pw"), Err(SssError::Aead)), "the set ID is authenticated");
+}
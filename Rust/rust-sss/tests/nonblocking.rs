@@ -0,0 +1,50 @@
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use zeroize::Zeroizing;
+
+use shamir_cli::nonblocking::{self, INLINE_LIMIT};
+use shamir_cli::secret::{Padding, SecretType};
+use shamir_cli::shamir::Field;
+use shamir_cli::SssError;
+
+fn run<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+}
+
+fn pw(text: &str) -> Zeroizing<String> {
+    Zeroizing::new(text.to_string())
+}
+
+#[test]
+fn split_and_combine_secret() {
+    run(async {
+        let secret = SecretType::String("hello".into());
+        let shares = nonblocking::split_secret(secret, pw("pw"), 2, 3, Padding::default(), 1_000).await.unwrap();
+        let recovered = nonblocking::combine_secret(shares[1..].to_vec(), pw("pw")).await.unwrap();
+        assert!(matches!(&recovered, SecretType::String(s) if s == "hello"));
+        let wrong = nonblocking::combine_secret(shares[..2].to_vec(), pw("no")).await;
+        assert!(matches!(wrong, Err(SssError::Aead)));
+    });
+}
+
+#[test]
+fn seal_and_open_payload() {
+    run(async {
+        let payload = nonblocking::seal(Zeroizing::new(b"abc".to_vec()), pw("pw"), 1_000).await.unwrap();
+        let plaintext = nonblocking::open_payload(payload.to_vec(), pw("pw")).await.unwrap();
+        assert_eq!(&plaintext[..], b"abc");
+    });
+}
+
+#[test]
+fn share_math_on_small_and_large_payloads() {
+    run(async {
+        for len in [32, INLINE_LIMIT + 1] {
+            let secret: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let shares = nonblocking::split(Field::Gf256, Zeroizing::new(secret.clone()), 3, 4).await.unwrap();
+            let rebuilt = nonblocking::reconstruct(Field::Gf256, shares[1..].to_vec()).await.unwrap();
+            assert_eq!(*rebuilt, secret);
+        }
+    });
+}
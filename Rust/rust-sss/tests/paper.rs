@@ -0,0 +1,82 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::input::parse_share;
+use shamir_cli::paper::{self, ShareSheet};
+use support::Workspace;
+
+#[test]
+fn words_round_trip_at_every_length() {
+    for len in 1..80u8 {
+        // trailing zero bytes are the awkward case: the last word's padding can hold one too
+        let share: Vec<u8> = (0..len).map(|i| if i + 2 >= len { 0 } else { i.wrapping_mul(37) ^ len }).collect();
+        let words = paper::share_words(&share).join(" ");
+        assert_eq!(paper::parse_words(&words).unwrap().unwrap(), share, "{} bytes: {}", len, words);
+        assert_eq!(parse_share(&words.to_uppercase()).unwrap(), share);
+    }
+
+    let mut words = paper::share_words(b"\x03 a share").iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    words.swap(0, 1);
+    assert!(paper::parse_words(&words.join(" ")).unwrap().is_err());
+    assert!(paper::parse_words("abandon notaword").is_none());
+}
+
+#[test]
+fn civil_dates() {
+    assert_eq!(paper::civil_date(0), "1970-01-01");
+    assert_eq!(paper::civil_date(951_782_400), "2000-02-29");
+    assert_eq!(paper::civil_date(1_700_000_000), "2023-11-14");
+}
+
+#[test]
+fn page_has_the_share_three_ways() {
+    let share = [2u8, 0xde, 0xad, 0xbe, 0xef];
+    let html = ShareSheet {
+        share: &share,
+        set_fingerprint: "0123456789abcdef".into(),
+        threshold: 2,
+        total: 3,
+        created: "2024-05-01".into(),
+        custodian: Some("Aunt <Em>".into()),
+        command: "sss decrypt".into(),
+    }
+    .to_html()
+    .unwrap();
+    assert!(html.contains("Shamir share #2 of 3"));
+    assert!(html.contains("Aunt &lt;Em&gt;"));
+    assert!(html.contains("02de adbe ef"));
+    assert!(html.contains("<svg"));
+    for word in paper::share_words(&share) {
+        assert!(html.contains(&format!(">{}</li>", word)), "{}", word);
+    }
+}
+
+#[test]
+fn cli_pages_recover_from_typed_words() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--paper", "pages", "--custodian", "ann:UK", "--custodian", "ben:UK"]);
+    sss.answer("Enter secret value", "on paper");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+
+    let typed: Vec<String> = ["pages/share-001.html", "pages/share-002.html"]
+        .iter()
+        .map(|page| {
+            let html = std::fs::read_to_string(ws.path(page)).unwrap();
+            html.split("</li>")
+                .filter_map(|part| part.rsplit('>').next())
+                .filter(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_lowercase()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    assert!(std::fs::read_to_string(ws.path("pages/share-002.html")).unwrap().contains("<strong>ben</strong>"));
+
+    let outcome = ws.combine(&[], &typed, "pw");
+    assert_eq!(outcome.recovered(), Some("on paper"), "{}", outcome.output);
+}
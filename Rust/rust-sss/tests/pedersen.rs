@@ -0,0 +1,48 @@
+use proptest::prelude::*;
+
+use shamir_cli::crypto::seeded_rng;
+use shamir_cli::shamir::pedersen::{self, CHUNK_LEN};
+use shamir_cli::shamir::Field;
+use shamir_cli::SssError;
+
+#[test]
+fn every_share_matches_the_commitments_and_tampering_does_not() {
+    let secret = b"long-term escrow, longer than one 31-byte chunk of the field";
+    let shares = pedersen::split_with_rng(secret, 3, 5, &mut seeded_rng("pedersen")).unwrap();
+    let commitments = pedersen::commit(&shares, 3).unwrap();
+    assert_eq!(commitments.chunks.len(), (secret.len() + 4).div_ceil(CHUNK_LEN));
+    for share in &shares {
+        pedersen::verify_share(share, &commitments).unwrap();
+    }
+
+    // a changed value or blinding factor no longer opens the commitment
+    for position in [1, shares[0].len() - 1] {
+        let mut forged = shares[0].clone();
+        forged[position] ^= 1;
+        assert!(matches!(pedersen::verify_share(&forged, &commitments), Err(SssError::Share(_))));
+    }
+    // nor does a share of another split of the same secret
+    let other = pedersen::split_with_rng(secret, 3, 5, &mut seeded_rng("other")).unwrap();
+    assert!(pedersen::verify_share(&other[0], &commitments).is_err());
+    assert_eq!(pedersen::reconstruct(&[&other[0], &other[3], &other[4]]).unwrap(), secret);
+}
+
+#[test]
+fn the_same_secret_commits_differently_each_time() {
+    // the blinding polynomial hides the secret, so even C_0 differs between splits
+    let first = pedersen::commit(&pedersen::split_with_rng(b"same", 2, 2, &mut seeded_rng("a")).unwrap(), 2).unwrap();
+    let second = pedersen::commit(&pedersen::split_with_rng(b"same", 2, 2, &mut seeded_rng("b")).unwrap(), 2).unwrap();
+    assert_ne!(first.chunks[0][..66], second.chunks[0][..66]);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn any_quorum_rebuilds_the_payload(secret in proptest::collection::vec(any::<u8>(), 0..100), total in 2u8..6) {
+        let threshold = total - 1;
+        let shares = Field::Pedersen.split(&secret, threshold, total).unwrap();
+        prop_assert_eq!(Field::Pedersen.reconstruct(&shares[1..]).unwrap(), secret);
+        prop_assert_eq!("pedersen".parse::<Field>().unwrap(), Field::Pedersen);
+    }
+}
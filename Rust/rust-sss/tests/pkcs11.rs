@@ -0,0 +1,47 @@
+use shamir_cli::pkcs11::{hsm_header, is_hsm_payload, HsmPayload};
+use shamir_cli::format::SetBinding;
+use shamir_cli::timelock::Conditions;
+use shamir_cli::SssError;
+
+#[test]
+fn hsm_payloads_name_their_key_and_authenticate_the_header() {
+    let mut payload = hsm_header("backup-2024").unwrap();
+    let header_len = payload.len();
+    payload.extend_from_slice(&[7; 12]);
+    payload.extend_from_slice(b"ciphertext and tag");
+    assert!(is_hsm_payload(&payload));
+
+    let parsed = HsmPayload::parse(&payload).unwrap();
+    assert_eq!(parsed.label, "backup-2024");
+    assert_eq!(parsed.header, &payload[..header_len]);
+    assert_eq!(parsed.nonce, &[7; 12]);
+    assert_eq!(parsed.ciphertext, b"ciphertext and tag");
+
+    assert!(matches!(HsmPayload::parse(&payload[..header_len + 11]), Err(SssError::Parse(_))));
+    assert!(matches!(hsm_header(""), Err(SssError::Parse(_))));
+    assert!(matches!(hsm_header(&"k".repeat(256)), Err(SssError::Parse(_))));
+    assert!(!is_hsm_payload(&shamir_cli::crypto::kdf_header(100_000)));
+}
+
+#[test]
+fn a_set_binding_in_front_is_part_of_the_authenticated_header() {
+    let binding = SetBinding::new(3, &mut rand::rngs::OsRng).header();
+    let mut payload = [&binding[..], &hsm_header("backup-2024").unwrap()].concat();
+    let header_len = payload.len();
+    payload.extend_from_slice(&[7; 12]);
+    payload.extend_from_slice(b"ciphertext and tag");
+    assert!(is_hsm_payload(&payload));
+
+    let parsed = HsmPayload::parse(&payload).unwrap();
+    assert_eq!(parsed.label, "backup-2024");
+    assert_eq!(parsed.header, &payload[..header_len]);
+    assert_eq!(parsed.nonce, &[7; 12]);
+    assert_eq!(Conditions::split(&payload).0.binding.map(|b| b.threshold), Some(3));
+}
+
+#[cfg(feature = "pkcs11")]
+#[test]
+fn a_missing_module_is_a_store_error() {
+    let result = shamir_cli::pkcs11::Hsm::login(std::path::Path::new("/nonexistent/libpkcs11.so"), None, "1234");
+    assert!(matches!(result, Err(SssError::Store(_))));
+}
@@ -0,0 +1,99 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::plan::{self, Attribute, PlanFile};
+use shamir_cli::SssError;
+use support::Workspace;
+
+const PLAN: &str = r#"
+threshold = 2
+
+[rules]
+distinct = ["city"]
+
+[[custodian]]
+name = "alice"
+locations = ["boston-safe", "boston-bank"]
+
+[[custodian]]
+name = "bob"
+locations = ["boston-bank", "nyc-vault"]
+
+[[custodian]]
+name = "carol"
+
+[[location]]
+name = "boston-safe"
+city = "Boston"
+country = "US"
+
+[[location]]
+name = "boston-bank"
+city = "Boston"
+country = "US"
+
+[[location]]
+name = "nyc-vault"
+city = "New York"
+country = "US"
+
+[[location]]
+name = "zurich-box"
+city = "Zurich"
+country = "CH"
+"#;
+
+#[test]
+fn no_two_shares_land_in_one_city() {
+    let plan = PlanFile::parse(PLAN).unwrap().solve().unwrap();
+    let homes: Vec<&str> = plan.assignments.iter().map(|a| a.location.as_str()).collect();
+    assert_eq!(homes, ["boston-safe", "nyc-vault", "zurich-box"]);
+    assert_eq!(plan.assignments[2].note(), "zurich-box (Zurich, CH)");
+
+    let redundancy = plan::analyse(&plan, 10);
+    let cities = redundancy.iter().find(|r| r.attribute == Attribute::City).unwrap();
+    assert_eq!((cities.places, cities.tolerates), (3, 1));
+    assert_eq!(cities.fatal.len(), 3);
+    let countries = redundancy.iter().find(|r| r.attribute == Attribute::Country).unwrap();
+    assert_eq!((countries.places, countries.tolerates), (2, 0));
+    assert_eq!(countries.fatal, vec![vec!["US".to_string()]]);
+}
+
+#[test]
+fn impossible_rules_are_refused() {
+    let strict = PLAN.replace(r#"distinct = ["city"]"#, "distinct = [\"country\"]");
+    let e = PlanFile::parse(&strict).unwrap().solve().unwrap_err();
+    assert!(matches!(&e, SssError::Policy(m) if m.contains("span only 2")), "{}", e);
+
+    let survive = PLAN.replace(r#"distinct = ["city"]"#, "survive = 2");
+    assert!(matches!(PlanFile::parse(&survive).unwrap().solve(), Err(SssError::Policy(_))));
+
+    let unknown = PLAN.replace(r#""nyc-vault"]"#, r#""paris-vault"]"#);
+    assert!(matches!(PlanFile::parse(&unknown), Err(SssError::Parse(_))));
+    assert!(matches!(PlanFile::parse(&PLAN.replace("threshold = 2", "threshold = 4")), Err(SssError::Parse(_))));
+}
+
+#[test]
+fn cli_plan_splits_labelled_shares() {
+    let ws = Workspace::new();
+    std::fs::write(ws.path("plan.toml"), PLAN).unwrap();
+
+    let outcome = ws.sss(&["plan", "plan.toml", "--dry-run"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains("Any 1 of the 3 cities can be lost"), "{}", outcome.output);
+    assert!(outcome.output.contains("losing US together loses the secret"), "{}", outcome.output);
+
+    let mut sss = ws.sss(&["plan", "plan.toml", "--out-dir", "shares"]);
+    sss.answer("Enter secret value", "mapped out");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let text = std::fs::read_to_string(ws.path("shares/share-02-of-03.sss")).unwrap();
+    assert!(text.starts_with("Label: bob\nNote: nyc-vault (New York, US)\n"), "{}", text);
+
+    let files: Vec<String> = [1, 3].iter().map(|n| format!("@shares/share-0{}-of-03.sss", n)).collect();
+    assert_eq!(ws.combine(&[], &files, "pw").recovered(), Some("mapped out"));
+}
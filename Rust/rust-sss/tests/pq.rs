@@ -0,0 +1,64 @@
+#![cfg(all(unix, feature = "pq"))]
+
+mod support;
+
+use shamir_cli::pq::{self, Identity, Recipient};
+use support::{shares_in, Workspace};
+
+#[test]
+fn sealed_shares_open_only_with_their_identity() {
+    let identity = Identity::generate(&mut rand::rngs::OsRng);
+    let recipient = Recipient::parse(&identity.to_public().to_string()).unwrap();
+    let identity = Identity::parse(&format!("# a comment\n{}\n", identity.to_text().as_str())).unwrap();
+    assert_eq!(recipient.to_string(), identity.to_public().to_string());
+
+    let sealed = pq::seal(&recipient, b"1,2,3", &mut rand::rngs::OsRng).unwrap();
+    assert!(sealed.starts_with("-----BEGIN SSS PQ SHARE-----\n"), "{}", sealed);
+    assert_eq!(pq::open(&identity, &sealed).unwrap().as_slice(), b"1,2,3");
+    assert_ne!(sealed, pq::seal(&recipient, b"1,2,3", &mut rand::rngs::OsRng).unwrap());
+
+    let stranger = Identity::generate(&mut rand::rngs::OsRng);
+    assert!(pq::open(&stranger, &sealed).is_err());
+    let mut lines: Vec<String> = sealed.lines().map(String::from).collect();
+    lines[3] = lines[3].chars().rev().collect();
+    assert!(pq::open(&identity, &lines.join("\n")).is_err());
+    assert!(Recipient::parse("sss-pq1-AAAA").is_err());
+}
+
+#[test]
+fn cli_encrypts_shares_to_post_quantum_keys() {
+    let ws = Workspace::new();
+    let mut args = vec!["encrypt".to_string(), "--pq-out".to_string(), "vault".to_string()];
+    for n in 1..=3 {
+        let outcome = ws.sss(&["pq", "keygen", "--out", &format!("key-{}.txt", n)]).finish();
+        assert!(outcome.success(), "{}", outcome.output);
+        let public = outcome.output.lines().find(|l| l.trim().starts_with(pq::PUBLIC_PREFIX)).unwrap();
+        args.push("--pq-recipient".to_string());
+        args.push(public.trim().to_string());
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let mut sss = ws.sss(&args);
+    sss.answer("Enter secret value", "quantum safe");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(shares_in(&outcome.output).is_empty(), "shares were printed:\n{}", outcome.output);
+
+    let identity = Identity::read(&ws.path("key-3.txt")).unwrap();
+    let third = std::fs::read_to_string(ws.path("vault/share-3.pq")).unwrap();
+    let third = String::from_utf8(pq::open(&identity, &third).unwrap().to_vec()).unwrap();
+
+    let mut sss = ws.sss(&["decrypt", "--pq-identity", "key-2.txt"]);
+    sss.answer("Share #1", "@vault/share-2.pq");
+    sss.answer("Share #2", "@vault/share-3.pq");
+    sss.expect("not sealed to this identity");
+    sss.answer("Share #2", third.trim());
+    sss.answer("Share #3", "");
+    sss.answer("Enter encryption password", "pw");
+    let outcome = sss.finish();
+    assert_eq!(outcome.recovered(), Some("quantum safe"), "{}", outcome.output);
+}
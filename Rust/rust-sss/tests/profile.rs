@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use shamir_cli::format::ShareEncoding;
+use shamir_cli::profile::Config;
+use shamir_cli::SssError;
+
+const CONFIG: &str = r#"
+[defaults]
+kdf_rounds = 200000
+encoding = "hex"
+
+[profile.family]
+threshold = 2
+custodians = ["alice:US", "bob:DE", "carol:CH"]
+encoding = "bech32"
+
+[profile.office]
+threshold = 3
+total = 5
+out_dir = "/srv/shares"
+"#;
+
+#[test]
+fn profiles_lay_over_the_defaults() {
+    let config = Config::parse(CONFIG).unwrap();
+
+    let defaults = config.resolve(None).unwrap();
+    assert_eq!(defaults.kdf_rounds, Some(200_000));
+    assert_eq!(defaults.encoding, Some(ShareEncoding::Hex));
+    assert_eq!(defaults.threshold, None);
+
+    let family = config.resolve(Some("family")).unwrap();
+    assert_eq!(family.encoding, Some(ShareEncoding::Bech32));
+    assert_eq!(family.kdf_rounds, Some(200_000));
+    assert_eq!(family.custodians.len(), 3);
+    assert_eq!(family.custodians[1].jurisdiction, "DE");
+
+    let office = config.resolve(Some("office")).unwrap();
+    assert_eq!((office.threshold, office.total), (Some(3), Some(5)));
+    assert_eq!(office.out_dir, Some(PathBuf::from("/srv/shares")));
+
+    let unknown = config.resolve(Some("nobody"));
+    assert!(matches!(unknown, Err(SssError::InvalidArgument(e)) if e.contains("family, office")));
+}
+
+#[test]
+fn impossible_profiles_are_refused() {
+    for bad in [
+        "[profile.p]\nthreshold = 4\ntotal = 3",
+        "[profile.p]\nthreshold = 1",
+        "[profile.p]\ntotal = 2\ncustodians = [\"alice:US\", \"bob:DE\", \"carol:CH\"]",
+        "[profile.p]\nkdf_rounds = 10",
+    ] {
+        let config = Config::parse(bad).unwrap();
+        assert!(matches!(config.resolve(Some("p")), Err(SssError::Parse(_))), "{}", bad);
+    }
+    for malformed in ["[profile.p]\nencoding = \"morse\"", "[profile.p]\ncustodians = [\"alice\"]", "[profile.p]\nsize = 1"] {
+        assert!(matches!(Config::parse(malformed), Err(SssError::Parse(_))), "{}", malformed);
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::pipeline;
+use shamir_cli::progress::{self, Event};
+use shamir_cli::secret::{Padding, SecretType};
+use shamir_cli::shamir::Field;
+use shamir_cli::stream;
+use std::sync::{Arc, Mutex};
+use support::Workspace;
+
+#[test]
+fn events_follow_a_split_and_a_large_file() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    progress::set_reporter(Some(Box::new(move |event: &Event| sink.lock().unwrap().push(event.clone()))));
+
+    let secret = SecretType::String("progress".into());
+    let mut rng = rand::rngs::OsRng;
+    let shares = pipeline::split_secret(&secret, "pw", 2, 3, Padding::default(), 5_000, &mut rng).unwrap();
+    Field::Gf256.rehearse(&shares, 2, &Field::Gf256.reconstruct(&shares).unwrap(), 64).unwrap();
+    let key = stream::generate_content_key();
+    let mut sealed = Vec::new();
+    stream::encrypt_stream(&vec![7u8; 40 * stream::CHUNK_SIZE][..], &mut sealed, &key).unwrap();
+    progress::set_reporter(None);
+    assert!(!progress::enabled());
+
+    let events = seen.lock().unwrap().clone();
+    assert_eq!(events[0], Event::KdfStarted { rounds: 5_000 });
+    let kdf: Vec<&Event> = events.iter().filter(|e| matches!(e, Event::KdfProgress { .. })).collect();
+    assert_eq!(kdf.len(), 100);
+    assert_eq!(kdf.last().unwrap(), &&Event::KdfProgress { done: 5_000, rounds: 5_000 });
+    let generated: Vec<&Event> = events.iter().filter(|e| matches!(e, Event::ShareGenerated { .. })).collect();
+    assert_eq!(generated, [1, 2, 3].map(|index| Event::ShareGenerated { index, total: 3 }).iter().collect::<Vec<_>>());
+    assert!(events.contains(&Event::VerificationPassed { quorums: 3 }));
+    let streamed: Vec<u64> = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::StreamProgress { bytes } => Some(*bytes / stream::CHUNK_SIZE as u64),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(streamed, [16, 32, 40]);
+
+    // the key worked out round by round is the one PBKDF2 gives without a reporter
+    let recovered = pipeline::combine_secret(&shares[1..], "pw").unwrap();
+    assert!(matches!(&recovered, SecretType::String(s) if s == "progress"));
+}
+
+#[test]
+fn cli_writes_events_to_a_file_descriptor() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["--progress-fd", "1", "encrypt", "--kdf-rounds", "20000"]);
+    sss.answer("Enter secret value", "watched");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    for line in [
+        r#"{"event":"kdf-started","rounds":20000}"#,
+        r#"{"event":"kdf-progress","done":20000,"rounds":20000}"#,
+        r#"{"event":"share-generated","index":3,"total":3}"#,
+        r#"{"event":"verification-passed","quorums":3}"#,
+    ] {
+        assert!(outcome.output.contains(line), "no {} in:\n{}", line, outcome.output);
+    }
+    assert_eq!(ws.sss(&["--progress-fd", "99", "encrypt"]).finish().code, 1);
+}
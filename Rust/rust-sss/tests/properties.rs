@@ -0,0 +1,39 @@
+#![cfg(feature = "testing")]
+
+use proptest::prelude::*;
+use shamir_cli::testing::{
+    assert_below_threshold, assert_parsers_total, assert_round_trip, assert_secret_round_trip, byte_fields, params,
+    secret_inputs, secrets,
+};
+
+proptest! {
+    #[test]
+    fn split_then_combine_round_trips(
+        field in byte_fields(),
+        secret in secrets(96),
+        params in params(16),
+        order in proptest::collection::vec(any::<u8>(), 16),
+    ) {
+        assert_round_trip(field, &secret, params, &order)?;
+    }
+
+    #[test]
+    fn too_few_shares_rebuild_something_else(field in byte_fields(), secret in secrets(48), params in params(8)) {
+        assert_below_threshold(field, &secret, params)?;
+    }
+
+    #[test]
+    fn typed_secrets_survive_serialization(input in secret_inputs()) {
+        assert_secret_round_trip(&input)?;
+    }
+
+    #[test]
+    fn parsers_never_panic(text in "(Label: [a-z]{0,8}\n)?(rs[0-9]:)?(sss1|base64:|base58:|\\[)?[ -~]{0,120}") {
+        assert_parsers_total(&text);
+    }
+
+    #[test]
+    fn parsers_never_panic_on_arbitrary_text(text in "\\PC{0,200}") {
+        assert_parsers_total(&text);
+    }
+}
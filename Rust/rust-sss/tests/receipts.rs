@@ -0,0 +1,108 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::manifest::Manifest;
+use shamir_cli::receipt::{self, Receipt, ReceiptVerifier, Status};
+use support::Workspace;
+
+fn manifest_for(shares: &[&[u8]]) -> Manifest {
+    Manifest {
+        format_version: 1,
+        set_fingerprint: "0011223344556677".into(),
+        field: "gf256".into(),
+        threshold: 2,
+        total: shares.len() as u8,
+        created_at: 0,
+        shares: shares
+            .iter()
+            .map(|share| shamir_cli::manifest::ShareEntry {
+                index: share[0],
+                file: shamir_cli::manifest::share_file_name(share[0], shares.len() as u8),
+                fingerprint: shamir_cli::manifest::share_fingerprint(share),
+                label: None,
+            })
+            .collect(),
+        commitments: None,
+        signature: None,
+    }
+}
+
+#[test]
+fn receipts_account_for_shares_by_fingerprint() {
+    let shares: [&[u8]; 3] = [&[1, 10, 20], &[2, 11, 21], &[3, 12, 22]];
+    let manifest = manifest_for(&shares);
+    let key = shamir_cli::token::generate_key();
+
+    let mut alice = Receipt::new(shares[0], "alice", Some(manifest.set_fingerprint.clone()));
+    alice.sign(&key).unwrap();
+    let mut bob = Receipt::new(shares[1], "bob", None);
+    bob.seal(b"shared secret").unwrap();
+    let mut forged = Receipt::new(&[3, 0, 0], "carol", None);
+    forged.sign(&key).unwrap();
+
+    let verifier = ReceiptVerifier { hmac_key: Some(b"shared secret"), registry: None };
+    let statuses = receipt::account(&manifest, &[alice.clone(), bob.clone(), forged], &verifier);
+    assert!(matches!(&statuses[0].1, Status::Received { custodian, .. } if custodian == "alice"));
+    assert!(matches!(&statuses[1].1, Status::Received { custodian, .. } if custodian == "bob"));
+    assert!(matches!(&statuses[2].1, Status::Invalid(e) if e.contains("fingerprint")));
+
+    let mut edited = alice.clone();
+    edited.custodian = "mallory".into();
+    assert!(edited.verify(&verifier).is_err());
+    let wrong_key = ReceiptVerifier { hmac_key: Some(b"guess"), registry: None };
+    assert!(bob.verify(&wrong_key).is_err());
+    assert_eq!(receipt::account(&manifest, &[], &verifier)[0].1, Status::Missing);
+}
+
+#[test]
+fn custodians_sign_receipts_and_the_dealer_checks_them() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--out-dir", "shares", "--total", "3"]);
+    sss.answer("Enter secret value", "handed out");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    assert!(sss.finish().success());
+
+    let outcome = ws.sss(&["guardian", "keygen", "--out", "alice.key"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    std::fs::write(ws.path("hmac.key"), "dealer and bob\n").unwrap();
+    std::fs::create_dir(ws.path("receipts")).unwrap();
+
+    let outcome = ws
+        .sss(&[
+            "receipts", "sign", "shares/share-01-of-03.sss", "--custodian", "alice", "--key", "alice.key",
+            "--manifest", "shares", "--out", "receipts/alice.json",
+        ])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let outcome = ws
+        .sss(&[
+            "receipts", "sign", "shares/share-02-of-03.sss", "--custodian", "bob", "--hmac-key", "hmac.key",
+            "--out", "receipts/bob.json",
+        ])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+
+    let outcome = ws.sss(&["receipts", "verify", "shares", "receipts", "--hmac-key", "hmac.key"]).finish();
+    assert_eq!(outcome.code, 11, "{}", outcome.output);
+    assert!(outcome.output.contains("MISSING  share #3"), "{}", outcome.output);
+    assert!(outcome.output.contains("share #1 received by alice"), "{}", outcome.output);
+
+    let outcome = ws
+        .sss(&[
+            "receipts", "sign", "shares/share-03-of-03.sss", "--custodian", "carol", "--hmac-key", "hmac.key",
+            "--out", "receipts/carol.json",
+        ])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let outcome = ws.sss(&["receipts", "verify", "shares", "receipts", "--hmac-key", "hmac.key"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains("All 3 shares"), "{}", outcome.output);
+
+    let outcome = ws.sss(&["receipts", "verify", "shares", "receipts"]).finish();
+    assert_eq!(outcome.code, 11, "{}", outcome.output);
+    assert!(outcome.output.contains("needs --hmac-key"), "{}", outcome.output);
+}
@@ -0,0 +1,32 @@
+use proptest::prelude::*;
+use shamir_cli::reed_solomon::{decode, encode};
+
+#[test]
+fn clean_codewords_decode_unchanged() {
+    let data: Vec<u8> = (0..600u32).map(|i| (i * 7 % 251) as u8).collect();
+    let codeword = encode(&data, 8).unwrap();
+    assert_eq!(codeword.len(), 600 + 3 * 8);
+    assert_eq!(decode(&codeword, 8).unwrap(), (data, 0));
+    assert!(encode(b"x", 3).is_err());
+    assert!(decode(&codeword[..5], 8).is_err());
+}
+
+proptest! {
+    #[test]
+    fn corrects_up_to_half_the_parity(
+        data in proptest::collection::vec(any::<u8>(), 1..300),
+        half in 1usize..8,
+        hits in proptest::collection::vec((any::<prop::sample::Index>(), 1u8..=255), 0..8),
+    ) {
+        let parity = 2 * half;
+        let mut codeword = encode(&data, parity).unwrap();
+        let mut positions = std::collections::BTreeSet::new();
+        for (index, flip) in hits.iter().take(half) {
+            let position = index.index(codeword.len().min(255));
+            if positions.insert(position) {
+                codeword[position] ^= flip;
+            }
+        }
+        prop_assert_eq!(decode(&codeword, parity).unwrap(), (data, positions.len()));
+    }
+}
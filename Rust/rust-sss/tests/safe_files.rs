@@ -0,0 +1,100 @@
+#![cfg(unix)]
+
+mod support;
+
+use std::os::unix::fs::PermissionsExt;
+
+use shamir_cli::secure::{self, WritePolicy};
+use shamir_cli::SssError;
+use support::Workspace;
+
+fn mode(path: &std::path::Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn share_directories_are_private_and_never_written_over() {
+    let ws = Workspace::new();
+    // a refused split stops before offering recovery cards
+    let split = |extra: &[&str], writes: bool| {
+        let mut sss = ws.sss(&[&["encrypt", "--out-dir", "shares"], extra].concat());
+        sss.answer("Enter secret value", "kept to myself");
+        sss.answer("Enter encryption password", "pw");
+        sss.answer("Confirm encryption password", "pw");
+        sss.answer("Total number of shares", "3");
+        sss.answer("Minimum shares required", "2");
+        if writes {
+            sss.confirm("recovery cards", false);
+        }
+        sss.finish()
+    };
+    assert!(split(&[], true).success());
+    let first = std::fs::read_to_string(ws.path("shares/share-01-of-03.sss")).unwrap();
+
+    let outcome = split(&[], false);
+    assert_eq!(outcome.code, 10, "{}", outcome.output);
+    assert!(outcome.output.contains("already exists; not writing over it without --force"), "{}", outcome.output);
+    assert_eq!(std::fs::read_to_string(ws.path("shares/share-01-of-03.sss")).unwrap(), first);
+
+    let outcome = split(&["--force", "--atomic"], true);
+    assert!(outcome.success(), "{}", outcome.output);
+    assert_ne!(std::fs::read_to_string(ws.path("shares/share-01-of-03.sss")).unwrap(), first);
+    for entry in std::fs::read_dir(ws.path("shares")).unwrap() {
+        let path = entry.unwrap().path();
+        assert_ne!(path.extension().unwrap(), "partial");
+        assert_eq!(mode(&path), 0o600, "{}", path.display());
+    }
+}
+
+#[test]
+fn recovered_secrets_replace_a_file_only_when_forced() {
+    let ws = Workspace::new();
+    let shares = ws.split(&[], "overwritten", "pw", 3, 2);
+    let out = ws.path("secret.txt");
+    std::fs::write(&out, "old\n").unwrap();
+    std::fs::set_permissions(&out, std::fs::Permissions::from_mode(0o644)).unwrap();
+    let out_arg = out.display().to_string();
+
+    let outcome = ws.combine(&["--secret-out", &out_arg], &shares[..2], "pw");
+    assert_eq!(outcome.code, 10, "{}", outcome.output);
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "old\n");
+
+    let outcome = ws.combine(&["--force", "--secret-out", &out_arg], &shares[1..], "pw");
+    assert!(outcome.success(), "{}", outcome.output);
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "overwritten\n");
+    assert_eq!(mode(&out), 0o600);
+}
+
+#[test]
+fn atomic_writes_leave_nothing_behind_when_they_fail() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("secret.bin");
+    secure::set_write_policy(WritePolicy { force: false, atomic: true, shred: true });
+
+    let failed = secure::write_private_with(&target, |writer| {
+        std::io::Write::write_all(writer, b"half of a secret")?;
+        Err::<(), _>(SssError::Share("interrupted".into()))
+    });
+    assert!(matches!(failed, Err(SssError::Share(_))));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    secure::write_private(&target, b"whole").unwrap();
+    assert_eq!(std::fs::read(&target).unwrap(), b"whole");
+    assert_eq!(mode(&target), 0o600);
+    assert!(matches!(secure::write_private(&target, b"again"), Err(SssError::Policy(_))));
+    secure::set_write_policy(WritePolicy::default());
+}
+
+#[test]
+fn atomic_writes_fit_long_file_names() {
+    let dir = tempfile::tempdir().unwrap();
+    secure::set_write_policy(WritePolicy { force: false, atomic: true, shred: false });
+    for len in [200, 250, 255] {
+        let target = dir.path().join(format!("{}.sse", "é".repeat((len - 4) / 2)));
+        secure::write_private_with(&target, |writer| Ok(std::io::Write::write_all(writer, b"long")?)).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"long");
+        assert_eq!(mode(&target), 0o600);
+    }
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+    secure::set_write_policy(WritePolicy::default());
+}
@@ -0,0 +1,54 @@
+use shamir_cli::format::{ShareEncoding, ShareHeader};
+use shamir_cli::fountain::Encoder;
+use shamir_cli::pipeline;
+use shamir_cli::scan::{Capture, Scanned};
+use shamir_cli::secret::{Padding, SecretType};
+
+fn shares() -> Vec<Vec<u8>> {
+    let secret = SecretType::String("scanned".into());
+    pipeline::split_secret(&secret, "pw", 2, 3, Padding::default(), 1_000, &mut rand::rngs::OsRng).unwrap()
+}
+
+#[test]
+fn capture_waits_for_the_threshold_a_header_declares() {
+    let shares = shares();
+    let mut capture = Capture::new(None);
+    let header = ShareHeader { set: Some("abcd1234".into()), quorum: Some((2, 3)), ..Default::default() };
+    let first = header.prepend(&ShareEncoding::Bech32.encode(&shares[0]).unwrap().to_uppercase());
+
+    assert!(matches!(capture.offer("not a share"), Scanned::Rejected(_)));
+    assert_eq!(capture.offer(&first), Scanned::Added(shares[0][0]));
+    assert_eq!(capture.offer(&first), Scanned::Again(shares[0][0]));
+    assert_eq!((capture.threshold(), capture.set()), (Some(2), Some("abcd1234")));
+    assert!(!capture.complete());
+
+    assert_eq!(capture.offer(&hex::encode(&shares[2])), Scanned::Added(shares[2][0]));
+    assert!(capture.complete());
+    assert_eq!(capture.indexes(), vec![shares[0][0], shares[2][0]]);
+    let recovered = pipeline::combine_secret(&capture.into_shares(), "pw").unwrap();
+    assert!(matches!(&recovered, SecretType::String(s) if s == "scanned"));
+}
+
+#[test]
+fn capture_rejects_shares_of_another_split() {
+    let (ours, theirs) = (shares(), shares());
+    let mut capture = Capture::new(Some(2));
+    assert_eq!(capture.offer(&hex::encode(&ours[0])), Scanned::Added(ours[0][0]));
+    assert!(matches!(capture.offer(&hex::encode(&theirs[0])), Scanned::Rejected(_)));
+    assert!(!capture.complete());
+}
+
+#[test]
+fn capture_rebuilds_shares_shown_as_fountain_frames() {
+    let shares = shares();
+    let text = ShareHeader { quorum: Some((2, 3)), ..Default::default() }.prepend(&hex::encode(&shares[1]));
+    let encoder = Encoder::new(text.as_bytes(), 16).unwrap();
+    let mut capture = Capture::new(None);
+    let count = encoder.fragment_count() as u32;
+    for seq in 1..count {
+        assert_eq!(capture.offer(&encoder.frame(seq)), Scanned::Partial { have: seq as usize, of: count as usize });
+    }
+    assert_eq!(capture.offer(&encoder.frame(count)), Scanned::Added(shares[1][0]));
+    assert_eq!(capture.offer(&encoder.frame(count + 1)), Scanned::Again(shares[1][0]));
+    assert_eq!(capture.threshold(), Some(2));
+}
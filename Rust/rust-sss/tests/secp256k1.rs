@@ -0,0 +1,74 @@
+use proptest::prelude::*;
+use shamir_cli::shamir::{secp256k1, Field};
+
+const ORDER: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+fn unhex(s: &str) -> Vec<u8> {
+    hex::decode(s).unwrap()
+}
+
+#[test]
+fn shares_are_scalars_and_recover_the_key() {
+    let key = unhex("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35");
+    let shares = secp256k1::split(&key, 3, 5).unwrap();
+    assert!(shares.iter().all(|s| s.len() == secp256k1::SHARE_LEN));
+    assert_eq!(secp256k1::reconstruct(&shares[1..4]).unwrap(), key);
+    assert_eq!(secp256k1::reconstruct(&[&shares[4], &shares[0], &shares[2]]).unwrap(), key);
+}
+
+#[test]
+fn threshold_one_shares_are_the_secret() {
+    let key = unhex("0000000000000000000000000000000000000000000000000000000000000007");
+    for share in secp256k1::split(&key, 1, 3).unwrap() {
+        assert_eq!(&share[1..], key.as_slice());
+    }
+}
+
+#[test]
+fn public_key_of_one_is_the_generator() {
+    let one = unhex("0000000000000000000000000000000000000000000000000000000000000001");
+    assert_eq!(
+        hex::encode(secp256k1::public_key(&one).unwrap()),
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+    );
+}
+
+#[test]
+fn rejects_values_outside_the_field() {
+    assert!(secp256k1::split(&unhex(ORDER), 2, 3).is_err());
+    assert!(secp256k1::split(&[1u8; 31], 2, 3).is_err());
+    assert!(secp256k1::split(&[1u8; 32], 0, 3).is_err());
+    assert!(secp256k1::split(&[1u8; 32], 4, 3).is_err());
+
+    let mut bad = vec![1u8];
+    bad.extend(unhex(ORDER));
+    let good = secp256k1::split(&[1u8; 32], 2, 2).unwrap();
+    assert!(secp256k1::reconstruct(&[bad, good[1].clone()]).is_err());
+    assert!(secp256k1::reconstruct(&[vec![1, 2], vec![2, 3]]).is_err());
+    assert!(secp256k1::reconstruct(&[good[0].clone(), good[0].clone()]).is_err());
+}
+
+#[test]
+fn field_names_parse() {
+    assert_eq!("gf256".parse::<Field>().unwrap(), Field::Gf256);
+    assert_eq!("SECP256K1".parse::<Field>().unwrap(), Field::Secp256k1);
+    assert!("p256".parse::<Field>().is_err());
+}
+
+proptest! {
+    #[test]
+    fn any_quorum_recovers_the_scalar(
+        key in proptest::array::uniform32(any::<u8>()).prop_filter("below the order", |k| k[0] < 0xff),
+        total in 1u8..=12,
+        threshold_seed in any::<u8>(),
+    ) {
+        let threshold = threshold_seed % total + 1;
+        let shares = Field::Secp256k1.split(&key, threshold, total).unwrap();
+        let quorum = &shares[(total - threshold) as usize..];
+        prop_assert_eq!(Field::Secp256k1.reconstruct(quorum).unwrap(), key.to_vec());
+        if threshold > 1 {
+            let short = &shares[..threshold as usize - 1];
+            prop_assert_ne!(Field::Secp256k1.reconstruct(short).unwrap(), key.to_vec());
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use shamir_cli::otp::{self, Totp};
+use shamir_cli::secret::{deserialize_secret, serialize_secret, SecretKind, SecretType};
+
+fn round_trip(secret: &SecretType) -> SecretType {
+    deserialize_secret(&serialize_secret(secret)).unwrap()
+}
+
+#[test]
+fn codes_and_identifiers_are_not_read_as_numbers() {
+    assert!(matches!(SecretType::from_input("042").unwrap(), SecretType::String(ref s) if s == "042"));
+    assert!(matches!(SecretType::from_input("1.50").unwrap(), SecretType::String(ref s) if s == "1.50"));
+    assert!(matches!(SecretType::from_input("-17").unwrap(), SecretType::Int(-17)));
+    assert!(matches!(SecretType::from_input("2.5").unwrap(), SecretType::Float(f) if f == 2.5));
+
+    let uuid = SecretType::from_input("6F9619FF-8B86-D011-B42D-00C04FC964FF").unwrap();
+    assert!(matches!(uuid, SecretType::Uuid(ref u) if shamir_cli::secret::uuid_text(u) == "6f9619ff-8b86-d011-b42d-00c04fc964ff"));
+    assert!(matches!(round_trip(&uuid), SecretType::Uuid(u) if u[0] == 0x6f));
+    assert!(SecretType::from_input("uuid:6f9619ff8b86d011b42d00c04fc964ff").is_ok());
+    assert!(SecretType::from_input("uuid:6f9619ff-8b86").is_err());
+
+    assert!(matches!(SecretType::from_input("2024-02-29").unwrap(), SecretType::Date(ref d) if d == "2024-02-29"));
+    assert!(matches!(SecretType::from_input("2023-02-29").unwrap(), SecretType::String(_)));
+    assert!(SecretType::from_input("date:2023-02-29").is_err());
+    assert!(matches!(round_trip(&SecretType::date("1999-12-31").unwrap()), SecretType::Date(ref d) if d == "1999-12-31"));
+}
+
+#[test]
+fn totp_seeds_rebuild_their_otpauth_uri() {
+    assert_eq!(otp::base32_encode(b"12345678901234567890"), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    assert_eq!(&*otp::base32_decode("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap(), b"12345678901234567890");
+    assert!(otp::base32_decode("GEZ1").is_err());
+
+    let uri = "otpauth://totp/Example%20Co:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example%20Co&digits=8";
+    let secret = SecretType::from_input(uri).unwrap();
+    let SecretType::Totp(ref totp) = round_trip(&secret) else { panic!("not a TOTP seed") };
+    assert_eq!(totp.issuer.as_deref(), Some("Example Co"));
+    assert_eq!(totp.account.as_deref(), Some("alice@example.com"));
+    assert_eq!((totp.digits, totp.period, totp.algorithm.as_str()), (8, 30, "SHA1"));
+    assert_eq!(&*totp.uri(), uri);
+
+    let bare = SecretType::from_input("totp:JBSWY3DPEHPK3PXP").unwrap();
+    assert!(matches!(bare, SecretType::Totp(ref t) if *t.uri() == "otpauth://totp/?secret=JBSWY3DPEHPK3PXP"));
+    assert!(Totp::parse_uri("otpauth://hotp/x?secret=JBSWY3DPEHPK3PXP").is_err());
+    assert!(Totp::parse_uri("otpauth://totp/x?secret=JBSWY3DPEHPK3PXP&algorithm=MD5").is_err());
+}
+
+#[test]
+fn a_named_type_is_taken_as_given() {
+    let parse = |input: &str, kind: &str| SecretType::parse_as(input, kind.parse::<SecretKind>().unwrap());
+    assert!(matches!(parse("1234", "string").unwrap(), SecretType::String(ref s) if s == "1234"));
+    assert!(matches!(parse("json:{}", "string").unwrap(), SecretType::String(ref s) if s == "json:{}"));
+    assert!(matches!(parse("0042", "int").unwrap(), SecretType::Int(42)));
+    assert!(matches!(parse("12.5", "float").unwrap(), SecretType::Float(f) if f == 12.5));
+    assert!(matches!(parse("00ff", "hex").unwrap(), SecretType::Bytes(ref b) if b == &[0, 255]));
+    assert!(matches!(parse("JBSWY3DPEHPK3PXP", "totp").unwrap(), SecretType::Totp(_)));
+    assert!(matches!(parse("2024-02-29", "auto").unwrap(), SecretType::Date(_)));
+    assert!(parse("12.5", "int").is_err());
+    assert!(parse("2023-02-29", "date").is_err());
+    assert!("number".parse::<SecretKind>().is_err());
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "server")]
+
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use shamir_cli::server::{bind, serve, verify_shares, Api};
+use shamir_cli::token::{self, Ledger};
+
+const AUTH: Option<&str> = Some("Bearer s3cret");
+
+fn api() -> Api {
+    Api::new("s3cret", 1_000)
+}
+
+fn post(api: &Api, path: &str, body: Value) -> (u16, Value) {
+    api.handle("POST", path, AUTH, &body.to_string())
+}
+
+fn split(api: &Api) -> Vec<String> {
+    let (status, reply) = post(
+        api,
+        "/split",
+        json!({ "secret": "hello", "password": "pw", "threshold": 2, "total": 3, "encoding": "bech32" }),
+    );
+    assert_eq!(status, 200, "{}", reply);
+    assert_eq!(reply["kdf_rounds"], 1_000);
+    reply["shares"].as_array().unwrap().iter().map(|s| s.as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn split_then_combine_recovers_the_secret() {
+    let api = api();
+    let shares = split(&api);
+    assert_eq!(shares.len(), 3);
+    assert!(shares[0].starts_with("sss1"));
+
+    let (status, reply) = post(&api, "/combine", json!({ "shares": [shares[2], shares[0]], "password": "pw" }));
+    assert_eq!((status, reply["secret"].as_str()), (200, Some("hello")));
+
+    let (status, reply) = post(&api, "/combine", json!({ "shares": [shares[0], shares[1]], "password": "no" }));
+    assert_eq!((status, reply["kind"].as_str()), (422, Some("aead")));
+}
+
+#[test]
+fn endpoints_want_the_bearer_token() {
+    let api = api();
+    let body = json!({ "shares": ["01ff"], "password": "pw" }).to_string();
+    for auth in [None, Some("Bearer wrong"), Some("s3cret")] {
+        let (status, reply) = api.handle("POST", "/combine", auth, &body);
+        assert_eq!((status, reply["kind"].as_str()), (401, Some("unauthorized")));
+    }
+    assert_eq!(api.handle("GET", "/health", None, "").0, 200);
+    assert_eq!(api.handle("GET", "/split", AUTH, "").0, 405);
+    assert_eq!(api.handle("POST", "/nope", AUTH, "").0, 404);
+    assert_eq!(api.handle("POST", "/split", AUTH, "{").0, 400);
+}
+
+#[test]
+fn a_capability_token_opens_its_own_set_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = token::generate_key();
+    let api = api().with_capabilities(key.verifying_key(), Ledger::new(dir.path().join("spent")));
+    let (_, reply) = post(&api, "/split", json!({ "secret": "hello", "password": "pw", "threshold": 2, "total": 3 }));
+    let fingerprint = reply["set_fingerprint"].as_str().unwrap().to_string();
+    let ours = reply["shares"].as_array().unwrap().clone();
+    let theirs = split(&api);
+
+    let capability = token::issue(&key, "alice", &fingerprint, Duration::from_secs(600)).unwrap();
+    let auth = format!("Bearer {}", capability);
+    let combine = |shares: &[Value]| {
+        api.handle("POST", "/combine", Some(&auth), &json!({ "shares": shares, "password": "pw" }).to_string())
+    };
+    // shares of another set are refused without using the token up
+    let (status, reply) = combine(&[json!(theirs[0]), json!(theirs[1])]);
+    assert_eq!((status, reply["kind"].as_str()), (401, Some("unauthorized")), "{}", reply);
+    let (status, reply) = combine(&ours[..2]);
+    assert_eq!((status, reply["secret"].as_str()), (200, Some("hello")), "{}", reply);
+    let (status, reply) = combine(&ours[1..]);
+    assert_eq!(status, 401, "{}", reply);
+    assert!(reply["error"].as_str().unwrap().contains("already been used"), "{}", reply);
+
+    // the token is good for nothing but /combine
+    let (status, _) = api.handle("POST", "/inspect", Some(&auth), &json!({ "shares": [ours[0]] }).to_string());
+    assert_eq!(status, 401);
+}
+
+#[test]
+fn inspect_and_verify_describe_shares_without_the_secret() {
+    let api = api();
+    let shares = split(&api);
+
+    let (status, reply) = post(&api, "/inspect", json!({ "shares": [shares[1]] }));
+    assert_eq!(status, 200, "{}", reply);
+    assert_eq!(reply["shares"][0]["index"], 2);
+    assert_eq!(reply["shares"][0]["encoding"], "bech32");
+
+    let (status, reply) = post(&api, "/verify", json!({ "shares": shares, "threshold": 2, "password": "pw" }));
+    assert_eq!(status, 200, "{}", reply);
+    assert_eq!((reply["consistent"].as_bool(), reply["decrypts"].as_bool()), (Some(true), Some(true)));
+    assert!(reply.get("secret").is_none());
+
+    let (_, reply) = post(&api, "/verify", json!({ "shares": [shares[0], shares[1]], "password": "no" }));
+    assert_eq!(reply["decrypts"].as_bool(), Some(false));
+}
+
+#[test]
+fn verify_catches_a_share_from_another_split() {
+    let api = api();
+    let first = split(&api);
+    let second = split(&api);
+    let decode = |s: &String| shamir_cli::input::parse_share(s).unwrap();
+    let mixed = vec![decode(&first[0]), decode(&first[1]), decode(&second[2])];
+    let verdict = verify_shares(&mixed, Some(2), None).unwrap();
+    assert_eq!(verdict.consistent, Some(false));
+    assert!(verify_shares(&mixed[..1], Some(2), None).is_err());
+}
+
+#[test]
+fn plain_http_is_only_served_on_loopback() {
+    assert!(bind("0.0.0.0:0".parse().unwrap(), None).is_err());
+
+    let server = bind("127.0.0.1:0".parse().unwrap(), None).unwrap();
+    let addr = server.server_addr().to_ip().unwrap();
+    std::thread::scope(|scope| {
+        scope.spawn(|| serve(&server, &api(), 1));
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 200"), "{}", reply);
+        assert!(reply.ends_with(r#"{"status":"ok"}"#), "{}", reply);
+        server.unblock();
+    });
+}
@@ -0,0 +1,119 @@
+use proptest::prelude::*;
+use rand::RngCore;
+use shamir_cli::shamir::{quorum_count, quorums, reconstruct, split, split_with_rng, Field};
+
+/// Hands out 1, 2, 3, ... so split results can be checked against hand-computed vectors.
+struct Counter(u8);
+
+impl RngCore for Counter {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            self.0 = self.0.wrapping_add(1);
+            *byte = self.0;
+        }
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    hex::decode(s).unwrap()
+}
+
+#[test]
+fn split_matches_reference_vector() {
+    // "Hi", threshold 3: coefficients (1, 2) for 'H' and (3, 4) for 'i', field polynomial 0x11d
+    let shares = split_with_rng(b"Hi", 3, 4, &mut Counter(0)).unwrap();
+    let expected: Vec<Vec<u8>> = ["014b6e", "02427f", "034178", "046c25"].iter().map(|s| unhex(s)).collect();
+    assert_eq!(shares, expected);
+}
+
+#[test]
+fn reconstructs_sets_made_by_sss_rs() {
+    let shares: Vec<Vec<u8>> = [
+        "01d812ad2038e6c306ca0d2af40b8566ac32d09099ace0653ea72f31",
+        "021cce75c181ba908a619764f1fe957879b1ee4ee432b40fad6e0684",
+        "03b7afabbecb2f73efc4f73e6481797cbcef57aa04be220ff0bd46c7",
+        "043df5d9e30823ec200a6ce2fab0681e6f52fd9b435804042ffe3e5e",
+        "059694079c42b60f45af0cb86fcf841aaa0c447fa3d49204722d7e1d",
+    ]
+    .iter()
+    .map(|s| unhex(s))
+    .collect();
+    for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+        let picked: Vec<&Vec<u8>> = subset.iter().map(|&i| &shares[i]).collect();
+        assert_eq!(reconstruct(&picked).unwrap(), b"sss_rs compatibility vector");
+    }
+
+    let shares = [unhex("01021f8e72"), unhex("0306c28d8b")];
+    assert_eq!(reconstruct(&shares).unwrap(), [0, 255, 1, 128]);
+}
+
+#[test]
+fn rejects_bad_parameters_and_shares() {
+    assert!(split(b"x", 0, 3).is_err());
+    assert!(split(b"x", 4, 3).is_err());
+    assert!(reconstruct::<Vec<u8>>(&[]).is_err());
+    assert!(reconstruct(&[vec![1, 2], vec![1, 3]]).is_err());
+    assert!(reconstruct(&[vec![0, 2], vec![1, 3]]).is_err());
+    assert!(reconstruct(&[vec![1, 2], vec![2, 3, 4]]).is_err());
+}
+
+#[test]
+fn rehearsal_covers_quorums_and_catches_a_bad_share() {
+    assert_eq!(quorum_count(3, 5), 10);
+    assert_eq!(quorum_count(128, 255), u64::MAX);
+    let all = quorums(3, 5, 64, &mut rand::rngs::OsRng);
+    assert_eq!(all.len(), 10);
+    assert!(all.iter().all(|q| q.len() == 3 && q.windows(2).all(|w| w[0] < w[1])));
+    let sampled = quorums(10, 40, 25, &mut rand::rngs::OsRng);
+    assert_eq!(sampled.len(), 25);
+    assert!(sampled.iter().all(|q| q.len() == 10 && q.iter().all(|&i| i < 40)));
+
+    let secret = b"rehearse before shredding";
+    let mut shares = split(secret, 2, 4).unwrap();
+    assert_eq!(Field::Gf256.rehearse(&shares, 2, secret, 64).unwrap(), 6);
+    shares[2][5] ^= 0x40;
+    let error = Field::Gf256.rehearse(&shares, 2, secret, 64).unwrap_err().to_string();
+    assert!(error.contains("#3"), "{}", error);
+}
+
+proptest! {
+    #[test]
+    fn any_quorum_recovers_the_secret(
+        secret in proptest::collection::vec(any::<u8>(), 1..64),
+        total in 1u8..=20,
+        threshold_seed in any::<u8>(),
+        order in proptest::collection::vec(any::<u8>(), 20),
+    ) {
+        let threshold = threshold_seed % total + 1;
+        let shares = split(&secret, threshold, total).unwrap();
+        prop_assert_eq!(shares.len(), total as usize);
+        prop_assert!(shares.iter().all(|s| s.len() == secret.len() + 1));
+
+        let mut indices: Vec<usize> = (0..total as usize).collect();
+        indices.sort_by_key(|&i| order[i]);
+        for quorum in [threshold as usize, total as usize] {
+            let picked: Vec<&Vec<u8>> = indices[..quorum].iter().map(|&i| &shares[i]).collect();
+            prop_assert_eq!(reconstruct(&picked).unwrap(), secret.clone());
+        }
+    }
+
+    #[test]
+    fn fewer_than_threshold_shares_do_not_recover(
+        secret in proptest::collection::vec(any::<u8>(), 16..32),
+        threshold in 2u8..=8,
+    ) {
+        let shares = split(&secret, threshold, threshold).unwrap();
+        let short = &shares[..threshold as usize - 1];
+        prop_assert_ne!(reconstruct(short).unwrap(), secret);
+    }
+}
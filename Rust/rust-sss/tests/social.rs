@@ -0,0 +1,70 @@
+use ed25519_dalek::SigningKey;
+
+use shamir_cli::social::{answer, Guardian, RecoverySession, Registry};
+use shamir_cli::SssError;
+
+fn guardian(name: &str, key: &SigningKey) -> Guardian {
+    Guardian {
+        name: name.into(),
+        contact: None,
+        public_key: hex::encode(key.verifying_key().as_bytes()),
+    }
+}
+
+#[test]
+fn only_registered_guardians_answer_their_own_challenge() {
+    let (alice, bob, mallory) = (
+        SigningKey::from_bytes(&[1; 32]),
+        SigningKey::from_bytes(&[2; 32]),
+        SigningKey::from_bytes(&[3; 32]),
+    );
+    let mut registry = Registry::default();
+    registry.add(guardian("alice", &alice)).unwrap();
+    registry.add(guardian("bob", &bob)).unwrap();
+    assert!(registry.add(guardian("Alice", &mallory)).is_err());
+    assert!(registry.add(guardian("carol", &bob)).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let session = RecoverySession::generate(Some("ABCD".into()), &mut rand::rngs::OsRng);
+    let written = session.write(dir.path(), &registry).unwrap();
+    assert_eq!(written.len(), 2);
+    assert!(session.write(dir.path(), &registry).is_err());
+    let session = RecoverySession::read(dir.path()).unwrap();
+    let challenge = session.challenge(registry.find("alice").unwrap()).unwrap();
+    assert_eq!(challenge, written[0].1);
+    assert_eq!(challenge.set.as_deref(), Some("abcd"));
+
+    let reply = answer(&challenge, &alice, "[1, 2, 3]").unwrap();
+    assert_eq!(&session.open(&registry, &reply).unwrap()[..], "[1, 2, 3]");
+
+    // someone else's key, bob's name on alice's challenge, or a tampered share are refused
+    assert!(matches!(
+        answer(&challenge, &mallory, "[1, 2, 3]"),
+        Err(SssError::Unauthorized(_))
+    ));
+    let mut renamed = reply.clone();
+    renamed.guardian = "bob".into();
+    assert!(matches!(
+        session.open(&registry, &renamed),
+        Err(SssError::Unauthorized(_))
+    ));
+    let mut tampered = reply.clone();
+    *tampered.ciphertext.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        session.open(&registry, &tampered),
+        Err(SssError::Unauthorized(_))
+    ));
+
+    // an answer to another session's challenge does not count in this one
+    let other = RecoverySession::generate(None, &mut rand::rngs::OsRng);
+    let stale = answer(
+        &other.challenge(registry.find("alice").unwrap()).unwrap(),
+        &alice,
+        "[1, 2, 3]",
+    )
+    .unwrap();
+    assert!(matches!(
+        session.open(&registry, &stale),
+        Err(SssError::Unauthorized(_))
+    ));
+}
@@ -0,0 +1,105 @@
+use shamir_cli::source::Source;
+
+#[test]
+fn values_keep_all_but_one_trailing_line_break() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("password");
+    std::fs::write(&path, "  spaced pw \r\n").unwrap();
+    let source = Source::pick(None, None, Some(&path)).unwrap();
+    assert_eq!(&*source.read().unwrap(), "  spaced pw ");
+
+    std::fs::write(&path, "two\nlines\n\n").unwrap();
+    assert_eq!(&*source.read().unwrap(), "two\nlines\n");
+
+    std::fs::write(&path, "\n").unwrap();
+    assert!(source.read().is_err());
+}
+
+#[test]
+fn environment_variables_must_be_set() {
+    std::env::set_var("SSS_TEST_SOURCE_SECRET", "from the environment");
+    let source = Source::pick(Some("SSS_TEST_SOURCE_SECRET"), None, None).unwrap();
+    assert_eq!(&*source.read().unwrap(), "from the environment");
+
+    let missing = Source::Env("SSS_TEST_SOURCE_UNSET".into()).read().unwrap_err();
+    assert!(missing.to_string().contains("$SSS_TEST_SOURCE_UNSET is not set"), "{}", missing);
+    assert_eq!(Source::pick(None, None, None), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn descriptors_are_read_through_dev_fd() {
+    use std::os::fd::AsRawFd;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("secret");
+    std::fs::write(&path, "handed over\n").unwrap();
+    let file = std::fs::File::open(&path).unwrap();
+    assert_eq!(&*Source::Fd(file.as_raw_fd()).read().unwrap(), "handed over");
+}
+
+/// `sss` with the secret and password in the environment and `stdin` on standard input, as a
+/// script runs it.
+fn scripted(dir: &std::path::Path, args: &[&str], stdin: &str) -> std::process::Output {
+    use std::io::Write;
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_sss"))
+        .args(args)
+        .current_dir(dir)
+        .env("SSS_CONFIG_DIR", dir)
+        .env("SSS_LANG", "en")
+        .env("SSS_TEST_SECRET", "scripted secret")
+        .env("SSS_TEST_PASSWORD", "correct horse battery staple")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("run sss");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn scripted_encrypt(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let split = ["encrypt", "--secret-env", "SSS_TEST_SECRET", "--password-env", "SSS_TEST_PASSWORD"];
+    scripted(dir, &[&split[..], &["--total", "3", "--threshold", "2"], args].concat(), "")
+}
+
+#[test]
+fn a_script_splits_without_a_terminal() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = scripted_encrypt(dir.path(), &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().filter(|line| line.contains(": [")).count(), 3, "{}", stdout);
+    assert!(!dir.path().join("recovery-cards").exists(), "no cards unless asked for");
+
+    let output = scripted_encrypt(dir.path(), &["--cards", "cards"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.path().join("cards/card-003.txt").is_file());
+}
+
+#[test]
+fn a_script_recovers_with_json_on_stdout() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let json = |output: std::process::Output| -> serde_json::Value {
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).expect("stdout is one JSON document")
+    };
+    let split = json(scripted_encrypt(dir.path(), &["--output-format", "json", "--encoding", "hex"]));
+    let shares: Vec<&str> = split["shares"].as_array().unwrap().iter().map(|s| s["share"].as_str().unwrap()).collect();
+    assert!(shares[0].starts_with("Set: "), "shares keep their header lines: {}", shares[0]);
+
+    let decrypt = ["--output-format", "json", "decrypt", "--password-env", "SSS_TEST_PASSWORD"];
+    let piped = format!("{}\n\n{}\n", shares[0], shares[2]);
+    let recovered = json(scripted(dir.path(), &decrypt, &piped));
+    assert_eq!(recovered["secret"], "scripted secret");
+
+    std::fs::write(dir.path().join("share-2.txt"), shares[1]).unwrap();
+    std::fs::write(dir.path().join("share-3.txt"), shares[2]).unwrap();
+    let files = ["--share", "share-2.txt", "--share", "share-3.txt"];
+    let recovered = json(scripted(dir.path(), &[&decrypt[..], &files].concat(), ""));
+    assert_eq!(recovered["secret"], "scripted secret");
+
+    let output = scripted(dir.path(), &[&decrypt[..], &["--share", "share-2.txt"]].concat(), "");
+    assert_eq!(output.status.code(), Some(5), "{}", String::from_utf8_lossy(&output.stdout));
+}
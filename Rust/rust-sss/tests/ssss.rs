@@ -0,0 +1,58 @@
+use proptest::prelude::*;
+use shamir_cli::shamir::ssss;
+
+// From the ssss-split(1) manual: a (3,5) split of "my secret root password" at 184 bits.
+const MANUAL_SHARES: [&str; 5] = [
+    "1-1c41ef496eccfbeba439714085df8437236298da8dd824",
+    "2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665",
+    "3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309",
+    "4-468de7d6eb36674c9cf008c8e8fc8c566537ad6301eb9e",
+    "5-4756974923c0dce0a55f4774d09ca7a4865f64f56a4ee0",
+];
+
+fn parse(lines: &[&str]) -> Vec<Vec<u8>> {
+    lines.iter().map(|l| ssss::parse_share(l).unwrap()).collect()
+}
+
+#[test]
+fn combines_shares_from_the_ssss_manual() {
+    let picks = [[2, 4, 1], [0, 1, 3], [4, 3, 2]];
+    for pick in picks {
+        let shares = parse(&pick.map(|i| MANUAL_SHARES[i]));
+        assert_eq!(ssss::reconstruct(&shares, true).unwrap(), b"my secret root password");
+    }
+    let two = parse(&MANUAL_SHARES[..2]);
+    assert_ne!(ssss::reconstruct(&two, true).unwrap(), b"my secret root password");
+    assert_eq!(ssss::format_share(&parse(&MANUAL_SHARES[2..3])[0], 5), MANUAL_SHARES[2]);
+}
+
+#[test]
+fn tokens_and_bad_lines() {
+    let share = ssss::parse_share("backup-07-00ff").unwrap();
+    assert_eq!(share, vec![7, 0, 255]);
+    assert_eq!(ssss::format_share(&share, 12), "07-00ff");
+    for bad in ["00ff", "0-00ff", "1-0g", "1-"] {
+        assert!(ssss::parse_share(bad).is_err(), "{}", bad);
+    }
+    let largest = [0x5a; ssss::MAX_SECRET_LEN];
+    let shares = ssss::split(&largest, 3, 3, true).unwrap();
+    assert_eq!(ssss::reconstruct(&shares, true).unwrap(), largest);
+    assert!(ssss::split(&[7; 129], 2, 3, true).is_err());
+    assert!(ssss::split(b"secret", 1, 3, true).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn split_then_combine(
+        secret in proptest::collection::vec(any::<u8>(), 1..48),
+        threshold in 2u8..5,
+        extra in 0u8..3,
+        diffusion in any::<bool>(),
+    ) {
+        let shares = ssss::split(&secret, threshold, threshold + extra, diffusion).unwrap();
+        let quorum = &shares[extra as usize..];
+        prop_assert_eq!(ssss::reconstruct(quorum, diffusion).unwrap(), secret);
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use shamir_cli::format::{ShareEncoding, ShareHeader};
+use shamir_cli::input::parse_share;
+use shamir_cli::stego::{capacity, hide, is_image, reveal};
+use shamir_cli::SssError;
+
+fn cover(width: u32, height: u32, color: png::ColorType) -> Vec<u8> {
+    let samples = color.samples();
+    let pixels: Vec<u8> = (0..width as usize * height as usize * samples).map(|i| (i * 37 % 251) as u8).collect();
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pixels).unwrap();
+    writer.finish().unwrap();
+    out
+}
+
+#[test]
+fn a_hidden_share_comes_back_out() {
+    let share: Vec<u8> = (1..=60u8).collect();
+    let header = ShareHeader { label: Some("Gran".into()), ..Default::default() };
+    let text = header.prepend(&ShareEncoding::Bech32.encode(&share).unwrap());
+    for color in [png::ColorType::Rgb, png::ColorType::Rgba, png::ColorType::Grayscale] {
+        let cover = cover(64, 48, color);
+        let stego = hide(&cover, &text).unwrap();
+        let revealed = reveal(&stego).unwrap();
+        assert_eq!(revealed.as_str(), text, "{:?}", color);
+        assert_eq!(parse_share(&revealed).unwrap(), share);
+        assert_eq!(ShareHeader::split(&revealed).0, header);
+    }
+}
+
+#[test]
+fn small_covers_and_plain_pictures_are_refused() {
+    let small = cover(8, 8, png::ColorType::Rgba);
+    assert_eq!(capacity(&small).unwrap(), 8 * 8 * 3 / 8 - 8);
+    assert!(matches!(hide(&small, &"ab".repeat(30)), Err(SssError::Parse(_))));
+    assert!(matches!(reveal(&small), Err(SssError::Parse(_))));
+    assert!(matches!(reveal(b"not a png"), Err(SssError::Parse(_))));
+
+    assert!(is_image(Path::new("holiday.PNG")));
+    assert!(!is_image(Path::new("share.txt")));
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use shamir_cli::manifest::share_fingerprint;
+use shamir_cli::shamir;
+use shamir_cli::store::{FileStore, MemoryStore, ShareStore};
+use shamir_cli::SssError;
+
+fn exercise(store: &mut dyn ShareStore) {
+    let shares = shamir::split(b"stored away", 2, 3).unwrap();
+    let mut fingerprints: Vec<String> = shares.iter().map(|s| store.put_share(s).unwrap()).collect();
+    fingerprints.sort();
+    assert_eq!(store.list().unwrap(), fingerprints);
+    assert!(shares.iter().all(|s| fingerprints.contains(&share_fingerprint(s))));
+
+    let back: Vec<Vec<u8>> = fingerprints[..2].iter().map(|f| store.get_share(f).unwrap().unwrap()).collect();
+    assert_eq!(shamir::reconstruct(&back).unwrap(), b"stored away");
+
+    assert!(store.delete(&fingerprints[0]).unwrap());
+    assert!(!store.delete(&fingerprints[0]).unwrap());
+    assert_eq!(store.get(&fingerprints[0]).unwrap(), None);
+    assert_eq!(store.list().unwrap(), &fingerprints[1..]);
+
+    // a share put under someone else's fingerprint is caught on the way out
+    store.put(&fingerprints[1], b"not that share").unwrap();
+    assert!(matches!(store.get_share(&fingerprints[1]), Err(SssError::Checksum(_))));
+
+    store.stage("request-1", b"pending", Duration::from_secs(60)).unwrap();
+    assert_eq!(store.staged("request-1").unwrap().as_deref(), Some(&b"pending"[..]));
+    assert_eq!(store.staged("request-2").unwrap(), None);
+}
+
+#[test]
+fn the_memory_store_keeps_shares_by_fingerprint() {
+    exercise(&mut MemoryStore::new());
+
+    let mut store = MemoryStore::new();
+    store.stage("gone", b"x", Duration::ZERO).unwrap();
+    assert_eq!(store.staged("gone").unwrap(), None);
+}
+
+#[test]
+fn the_file_store_keeps_shares_across_reopening() {
+    let dir = tempfile::tempdir().unwrap();
+    exercise(&mut FileStore::open(dir.path()).unwrap());
+
+    let mut reopened = FileStore::open(dir.path()).unwrap();
+    assert_eq!(reopened.list().unwrap().len(), 2);
+    assert_eq!(reopened.staged("request-1").unwrap().as_deref(), Some(&b"pending"[..]));
+
+    assert!(matches!(reopened.put("../escape", b"x"), Err(SssError::Store(_))));
+    assert!(matches!(reopened.get(".hidden"), Err(SssError::Store(_))));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let fingerprint = &reopened.list().unwrap()[0];
+        let mode = std::fs::metadata(dir.path().join("shares").join(fingerprint)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,19 @@
+use shamir_cli::crypto::PBKDF2_ROUNDS;
+use shamir_cli::strength::{describe_duration, estimate};
+
+#[test]
+fn crack_time_accounts_for_the_kdf() {
+    let weak = estimate("password1", &[], PBKDF2_ROUNDS);
+    assert_eq!(weak.score, 0);
+    assert!(weak.warning.is_some());
+    assert_eq!(describe_duration(weak.crack_seconds), "less than a second");
+
+    let strong = estimate("correct horse battery staple quietly", &[], PBKDF2_ROUNDS);
+    assert_eq!(strong.score, 4);
+    assert_eq!(describe_duration(strong.crack_seconds), "more than a century");
+
+    let named = estimate("alice-bob-2024", &["alice", "bob"], PBKDF2_ROUNDS);
+    assert!(named.guesses < estimate("alice-bob-2024", &[], PBKDF2_ROUNDS).guesses);
+    assert_eq!(describe_duration(3.0 * 86_400.0), "about 3 days");
+    assert_eq!(describe_duration(60.0), "about 1 minute");
+}
@@ -0,0 +1,75 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::audit::share_id;
+use shamir_cli::format::{Parent, ShareHeader};
+use shamir_cli::subshare::{self, Entered, Nest};
+use support::Workspace;
+
+#[test]
+fn a_quorum_of_sub_shares_rebuilds_the_share_through_every_level() {
+    let share: Vec<u8> = (1..=40u8).collect();
+    let header = ShareHeader { label: Some("Alice".into()), quorum: Some((3, 5)), ..Default::default() };
+    let subshares = subshare::split(&share, &header, 2, 3, &mut rand::rngs::OsRng).unwrap();
+    assert_eq!(subshares.len(), 3);
+    let parent = Parent { share: share_id(&share), quorum: (2, 3) };
+    assert_eq!(subshares[0].1.parents, vec![parent.clone()]);
+    assert_eq!(ShareHeader::split(&subshares[0].1.prepend("00")).0, subshares[0].1);
+
+    let (second, second_header) = &subshares[1];
+    let nested = subshare::split(second, second_header, 2, 2, &mut rand::rngs::OsRng).unwrap();
+    assert_eq!(nested[0].1.parents.len(), 2);
+
+    let mut nest = Nest::default();
+    let (first, first_header) = subshares[0].clone();
+    assert!(matches!(nest.add(first, first_header).unwrap(), Entered::Pending { have: 1, need: 2, .. }));
+    let (inner, inner_header) = nested[0].clone();
+    assert!(matches!(nest.add(inner, inner_header).unwrap(), Entered::Pending { have: 1, need: 2, .. }));
+    let (inner, inner_header) = nested[1].clone();
+    assert_eq!(nest.add(inner, inner_header).unwrap(), Entered::Share(share.clone(), header.clone(), 4));
+    assert!(nest.pending().is_empty());
+
+    assert_eq!(nest.add(share.clone(), header.clone()).unwrap(), Entered::Share(share, header, 0));
+}
+
+#[test]
+fn sub_shares_of_another_share_are_refused() {
+    let share: Vec<u8> = (1..=40u8).collect();
+    let other: Vec<u8> = (2..=41u8).collect();
+    let ours = subshare::split(&share, &ShareHeader::default(), 2, 2, &mut rand::rngs::OsRng).unwrap();
+    let theirs = subshare::split(&other, &ShareHeader::default(), 2, 2, &mut rand::rngs::OsRng).unwrap();
+
+    let mut nest = Nest::default();
+    let (first, _) = ours[0].clone();
+    let (second, _) = theirs[1].clone();
+    nest.add(first.clone(), ours[0].1.clone()).unwrap();
+    assert!(nest.add(first, ours[0].1.clone()).is_err());
+    assert!(matches!(nest.add(second, ours[1].1.clone()), Err(shamir_cli::SssError::Share(_))));
+}
+
+#[test]
+fn cli_decrypt_takes_sub_shares_in_place_of_a_share() {
+    let ws = Workspace::new();
+    let shares = ws.split(&[], "split twice", "pw", 3, 2);
+    ws.distribute(&shares);
+
+    let outcome = ws
+        .sss(&[
+            "sub-split", "share-1.txt", "--threshold", "2", "--total", "3", "--encoding", "hex", "--out-dir", "devices",
+        ])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let text = std::fs::read_to_string(ws.path("devices/share-1-sub-02-of-03.sss")).unwrap();
+    assert!(text.starts_with("Parent: "), "{}", text);
+
+    let mut sss = ws.sss(&["decrypt"]);
+    sss.answer("Share #1", "@devices/share-1-sub-01-of-03.sss");
+    sss.answer("Share #1", "@devices/share-1-sub-03-of-03.sss");
+    sss.answer("Share #2", "@share-3.txt");
+    sss.answer("Share #3", "");
+    sss.answer("Enter encryption password", "pw");
+    let outcome = sss.finish();
+    assert_eq!(outcome.recovered(), Some("split twice"), "{}", outcome.output);
+    assert!(outcome.output.contains("Rebuilt share #1 from 2 sub-shares"), "{}", outcome.output);
+}
@@ -0,0 +1,202 @@
+//! Drives the `sss` binary through a pseudo-terminal, the way a person at the keyboard would.
+//! Any integration test can pull it in with `mod support;`.
+//!
+//! dialoguer discards keys typed before a prompt is drawn, so every answer waits for its
+//! prompt to appear first.
+
+#![allow(dead_code)]
+
+use expectrl::{Eof, Session, WaitStatus};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+/// Password prompts switch off echo with TCSAFLUSH just after drawing the prompt, which
+/// discards anything already typed; give them a moment before answering.
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// A running `sss` process plus everything it has printed so far.
+pub struct Sss {
+    session: Session,
+    output: String,
+}
+
+/// How a session ended.
+pub struct Outcome {
+    pub output: String,
+    pub code: i32,
+}
+
+impl Outcome {
+    pub fn success(&self) -> bool {
+        self.code == 0
+    }
+
+    /// The line after `Recovered secret:`, if there is one.
+    pub fn recovered(&self) -> Option<&str> {
+        let mut lines = self.output.lines();
+        lines.find(|l| l.trim() == "Recovered secret:")?;
+        lines.next().map(str::trim)
+    }
+}
+
+impl Sss {
+    pub fn spawn(dir: &Path, args: &[&str]) -> Sss {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_sss"));
+        // keep a `sss bench-kdf` calibration and the machine's locale out of the tests, and theirs in
+        command.args(args).current_dir(dir).env("SSS_CONFIG_DIR", dir).env("SSS_LANG", "en");
+        let mut session = Session::spawn(command).expect("spawn sss in a pty");
+        session.set_expect_timeout(Some(TIMEOUT));
+        Sss { session, output: String::new() }
+    }
+
+    /// Waits for `text` and returns everything printed since the previous expectation.
+    pub fn expect(&mut self, text: &str) -> String {
+        let found = self
+            .session
+            .expect(text)
+            .unwrap_or_else(|e| panic!("waiting for {:?}: {}\n--- output so far ---\n{}", text, e, self.output));
+        let chunk = String::from_utf8_lossy(found.as_bytes()).into_owned();
+        self.output.push_str(&chunk);
+        chunk
+    }
+
+    /// Answers a text or password prompt.
+    pub fn answer(&mut self, prompt: &str, answer: &str) {
+        self.expect(prompt);
+        std::thread::sleep(SETTLE);
+        self.send_line(answer);
+    }
+
+    /// Types a line without waiting for a prompt, for when one was already matched.
+    pub fn send_line(&mut self, line: &str) {
+        self.session.send_line(line).expect("write to pty");
+    }
+
+    /// Answers a yes/no prompt, which reads a single key.
+    pub fn confirm(&mut self, prompt: &str, yes: bool) {
+        self.expect(prompt);
+        self.session.send(if yes { "y" } else { "n" }).expect("write to pty");
+    }
+
+    /// Reads until the process exits and reports its exit code.
+    pub fn finish(mut self) -> Outcome {
+        if let Ok(found) = self.session.expect(Eof) {
+            self.output.push_str(&String::from_utf8_lossy(found.as_bytes()));
+        }
+        let code = match self.session.get_process().wait() {
+            Ok(WaitStatus::Exited(_, code)) => code,
+            other => panic!("sss did not exit normally: {:?}", other),
+        };
+        Outcome { output: strip_ansi(&self.output), code }
+    }
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if c != '\r' {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// The `[n, n, ...]` share lists printed by a split, in order.
+pub fn shares_in(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|l| l.starts_with("Share ID"))
+        .filter_map(|l| l.find('[').map(|start| l[start..].trim().to_string()))
+        .collect()
+}
+
+/// A scratch directory the binary runs in, so card and share files never leak between tests.
+pub struct Workspace {
+    pub dir: TempDir,
+}
+
+impl Workspace {
+    pub fn new() -> Workspace {
+        Workspace { dir: TempDir::new().expect("create temp dir") }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    pub fn sss(&self, args: &[&str]) -> Sss {
+        Sss::spawn(self.dir.path(), args)
+    }
+
+    /// Asks `sss` for completions the way the fish script does, for the last of `words`, and
+    /// returns each candidate with its description.
+    pub fn complete(&self, words: &[&str]) -> Vec<(String, String)> {
+        let output = Command::new(env!("CARGO_BIN_EXE_sss"))
+            .args([&["--", "sss"], words].concat())
+            .current_dir(self.dir.path())
+            .env("SSS_CONFIG_DIR", self.dir.path())
+            .env("SSS_COMPLETE", "fish")
+            .output()
+            .expect("run sss for completions");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout)
+            .expect("completions are UTF-8")
+            .lines()
+            .map(|line| {
+                let (value, help) = line.split_once('\t').unwrap_or((line, ""));
+                (value.to_string(), help.to_string())
+            })
+            .collect()
+    }
+
+    /// Runs a plain `encrypt` and returns the printed shares.
+    pub fn split(&self, args: &[&str], secret: &str, password: &str, total: u8, threshold: u8) -> Vec<String> {
+        let mut sss = self.sss(&[&["encrypt"], args].concat());
+        sss.answer("Enter secret value", secret);
+        sss.answer("Enter encryption password", password);
+        sss.answer("Confirm encryption password", password);
+        sss.answer("Total number of shares", &total.to_string());
+        sss.answer("Minimum shares required", &threshold.to_string());
+        sss.confirm("recovery cards", false);
+        let outcome = sss.finish();
+        assert!(outcome.success(), "split failed:\n{}", outcome.output);
+        let shares = shares_in(&outcome.output);
+        assert_eq!(shares.len(), total as usize, "split output:\n{}", outcome.output);
+        shares
+    }
+
+    /// Writes each share to `share-N.txt` the way a custodian would keep it.
+    pub fn distribute(&self, shares: &[String]) -> Vec<PathBuf> {
+        shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| {
+                let path = self.path(&format!("share-{}.txt", i + 1));
+                std::fs::write(&path, share).expect("write share file");
+                path
+            })
+            .collect()
+    }
+
+    /// Runs `decrypt`, pasting each entry (a share, or `@path`) in turn.
+    pub fn combine(&self, args: &[&str], entries: &[String], password: &str) -> Outcome {
+        let mut sss = self.sss(&[&["decrypt"], args].concat());
+        for (i, entry) in entries.iter().enumerate() {
+            sss.answer(&format!("Share #{}", i + 1), entry);
+        }
+        sss.answer(&format!("Share #{}", entries.len() + 1), "");
+        sss.answer("Enter encryption password", password);
+        sss.finish()
+    }
+}
@@ -0,0 +1,74 @@
+use ed25519_dalek::SigningKey;
+use std::time::Duration;
+
+use shamir_cli::pipeline::{combine_secret, seal_locked};
+use shamir_cli::secret::{pad, serialize_secret, Padding, SecretType};
+use shamir_cli::format::SetBinding;
+use shamir_cli::shamir::Field;
+use shamir_cli::switch::{Heartbeat, Release, Switch};
+use shamir_cli::timelock::{Conditions, TimeLock};
+use shamir_cli::{token, SssError};
+
+const SET: &str = "0123456789abcdef";
+
+#[test]
+fn a_switch_opens_on_approval_or_a_lapsed_heartbeat() {
+    let dealer = SigningKey::from_bytes(&[3; 32]);
+    let switch = Switch { dealer_key: dealer.verifying_key().to_bytes() };
+    let now = shamir_cli::timelock::system_now();
+
+    assert!(matches!(switch.check(SET, None, None, now), Err(SssError::Policy(_))));
+
+    let live = Heartbeat::sign(&dealer, SET, Duration::from_secs(86_400));
+    assert!(matches!(switch.check(SET, None, Some(&live), now), Err(SssError::Policy(_))));
+    let lapsed = Heartbeat::sign(&dealer, SET, Duration::ZERO);
+    assert_eq!(switch.check(SET, None, Some(&lapsed), now).unwrap(), Release::Lapsed(lapsed.expires_at));
+    assert!(matches!(switch.check("feedface", None, Some(&lapsed), now), Err(SssError::Unauthorized(_))));
+
+    let mut forged = lapsed.clone();
+    forged.expires_at -= 1;
+    assert!(matches!(switch.check(SET, None, Some(&forged), now), Err(SssError::Unauthorized(_))));
+    let stranger = Heartbeat::sign(&SigningKey::from_bytes(&[4; 32]), SET, Duration::ZERO);
+    assert!(matches!(switch.check(SET, None, Some(&stranger), now), Err(SssError::Unauthorized(_))));
+
+    let approval = token::issue(&dealer, "alice", SET, Duration::from_secs(3_600)).unwrap();
+    assert_eq!(switch.check(SET, Some(&approval), Some(&live), now).unwrap(), Release::Approved("alice".into()));
+    assert!(matches!(switch.check("feedface", Some(&approval), None, now), Err(SssError::Unauthorized(_))));
+}
+
+#[test]
+fn heartbeats_round_trip_through_a_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("heartbeat.json");
+    let dealer = SigningKey::from_bytes(&[3; 32]);
+    let heartbeat = Heartbeat::sign(&dealer, SET, Duration::from_secs(60));
+    heartbeat.write(&path).unwrap();
+    Heartbeat::sign(&dealer, SET, Duration::from_secs(120)).write(&path).unwrap();
+    let read = Heartbeat::read(&path).unwrap();
+    assert_eq!(read.expires_at, read.issued_at + 120);
+    read.verify(dealer.verifying_key().as_bytes(), SET).unwrap();
+}
+
+#[test]
+fn conditions_ride_in_front_of_the_payload() {
+    let conditions = Conditions {
+        time_lock: Some(TimeLock { not_before: 42, roughtime_key: None }),
+        switch: Some(Switch { dealer_key: SigningKey::from_bytes(&[3; 32]).verifying_key().to_bytes() }),
+        binding: Some(SetBinding { id: [9; 16], threshold: 2, version: 1 }),
+    };
+    let mut payload = conditions.header();
+    payload.extend_from_slice(b"kdf1");
+    let (parsed, header, rest) = Conditions::split(&payload);
+    assert_eq!(parsed, conditions);
+    assert_eq!(header, &conditions.header()[..]);
+    assert_eq!(rest, b"kdf1");
+    assert!(Conditions::split(b"kdf1").0.is_empty());
+
+    // the library has no approval or heartbeat to go on, so it leaves a switched set to `decrypt`
+    let mut rng = rand::rngs::OsRng;
+    let plaintext = pad(&serialize_secret(&SecretType::String("the will".into())), Padding::default()).unwrap();
+    let switched = Conditions { time_lock: None, ..conditions };
+    let payload = seal_locked(&plaintext, "pw", 1_000, &switched, &mut rng).unwrap();
+    let shares = Field::Gf256.split_with_rng(&payload, 2, 2, &mut rng).unwrap();
+    assert!(matches!(combine_secret(&shares, "pw"), Err(SssError::Policy(_))));
+}
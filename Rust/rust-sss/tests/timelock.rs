@@ -0,0 +1,96 @@
+use ed25519_dalek::{Signer, SigningKey};
+
+use shamir_cli::pipeline::{combine_secret, seal_locked};
+use shamir_cli::roughtime::{self, encode_message, leaf_hash, node_hash};
+use shamir_cli::secret::{pad, serialize_secret, Padding, SecretType};
+use shamir_cli::shamir::Field;
+use shamir_cli::timelock::{format_date, parse_date, Conditions, TimeLock};
+use shamir_cli::SssError;
+
+#[test]
+fn dates_parse_as_utc() {
+    assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+    assert_eq!(parse_date("2000-02-29T12:30:05Z").unwrap(), 951_827_405);
+    assert_eq!(format_date(951_827_405), "2000-02-29 12:30:05 UTC");
+    assert_eq!(format_date(parse_date("2100-12-31").unwrap()), "2100-12-31 00:00:00 UTC");
+    for bad in ["2001-02-29", "2030-13-01", "2030-01-01T10:00:00", "1969-12-31", "next year"] {
+        assert!(matches!(parse_date(bad), Err(SssError::InvalidArgument(_))), "{}", bad);
+    }
+}
+
+fn locked_shares(not_before: u64, roughtime_key: Option<[u8; 32]>) -> Vec<Vec<u8>> {
+    let mut rng = rand::rngs::OsRng;
+    let plaintext = pad(&serialize_secret(&SecretType::String("the will".into())), Padding::default()).unwrap();
+    let conditions = Conditions { time_lock: Some(TimeLock { not_before, roughtime_key }), switch: None, binding: None };
+    let payload = seal_locked(&plaintext, "pw", 1_000, &conditions, &mut rng).unwrap();
+    Field::Gf256.split_with_rng(&payload, 2, 2, &mut rng).unwrap()
+}
+
+#[test]
+fn a_locked_set_opens_only_after_its_date() {
+    let future = locked_shares(parse_date("2999-01-01").unwrap(), None);
+    assert!(matches!(combine_secret(&future, "pw"), Err(SssError::Policy(_))));
+    let payload = Field::Gf256.reconstruct(&future).unwrap();
+    assert_eq!(TimeLock::split(&payload).unwrap().0.not_before, parse_date("2999-01-01").unwrap());
+
+    let past = locked_shares(parse_date("2001-01-01").unwrap(), None);
+    assert!(matches!(&combine_secret(&past, "pw").unwrap(), SecretType::String(s) if s == "the will"));
+
+    // moving the date is caught by the ciphertext
+    let mut payload = Field::Gf256.reconstruct(&future).unwrap();
+    payload[4..12].copy_from_slice(&0u64.to_be_bytes());
+    assert!(matches!(shamir_cli::crypto::open_payload(&payload, "pw"), Err(SssError::Aead)));
+
+    let attested = locked_shares(0, Some([7; 32]));
+    assert!(matches!(combine_secret(&attested, "pw"), Err(SssError::Policy(_))));
+}
+
+/// A reply as a Roughtime server with `root` as its long-term key would send it, with the
+/// nonce as the second leaf of a two-leaf tree.
+fn reply(root: &SigningKey, nonce: &[u8; 64], midpoint: u64) -> Vec<u8> {
+    let online = SigningKey::from_bytes(&[9; 32]);
+    let (mint, maxt) = (0u64.to_le_bytes(), u64::MAX.to_le_bytes());
+    let dele = encode_message(&[
+        (roughtime::MINT, &mint),
+        (roughtime::MAXT, &maxt),
+        (roughtime::PUBK, online.verifying_key().as_bytes()),
+    ]);
+    let dele_sig = root.sign(&[&b"RoughTime v1 delegation signature--\0"[..], &dele].concat());
+    let cert = encode_message(&[(roughtime::SIG, &dele_sig.to_bytes()), (roughtime::DELE, &dele)]);
+
+    let sibling = leaf_hash(b"another client");
+    let tree_root = node_hash(&sibling, &leaf_hash(nonce));
+    let srep = encode_message(&[
+        (roughtime::RADI, &1_000_000u32.to_le_bytes()),
+        (roughtime::MIDP, &midpoint.to_le_bytes()),
+        (roughtime::ROOT, &tree_root),
+    ]);
+    let srep_sig = online.sign(&[&b"RoughTime v1 response signature\0"[..], &srep].concat());
+    encode_message(&[
+        (roughtime::SIG, &srep_sig.to_bytes()),
+        (roughtime::PATH, &sibling),
+        (roughtime::SREP, &srep),
+        (roughtime::CERT, &cert),
+        (roughtime::INDX, &1u32.to_le_bytes()),
+    ])
+}
+
+#[test]
+fn roughtime_replies_are_checked_against_the_server_key() {
+    let root = SigningKey::from_bytes(&[3; 32]);
+    let key = root.verifying_key().to_bytes();
+    let nonce = [5u8; 64];
+    assert_eq!(roughtime::request(&nonce).len(), 1024);
+
+    let reply = reply(&root, &nonce, 1_900_000_000_000_000);
+    let attested = roughtime::verify(&reply, &nonce, &key).unwrap();
+    assert_eq!(attested.earliest_secs(), 1_899_999_999);
+
+    assert!(matches!(roughtime::verify(&reply, &[6u8; 64], &key), Err(SssError::Store(_))));
+    let impostor = SigningKey::from_bytes(&[4; 32]).verifying_key().to_bytes();
+    assert!(matches!(roughtime::verify(&reply, &nonce, &impostor), Err(SssError::Store(_))));
+    assert!(matches!(roughtime::verify(&reply[..100], &nonce, &key), Err(SssError::Store(_))));
+
+    assert_eq!(roughtime::parse_key(&hex::encode(key)).unwrap(), key);
+    assert!(roughtime::parse_key("short").is_err());
+}
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use shamir_cli::tpm::{is_sealed, parse_pcrs, read, DEFAULT_PCRS};
+use shamir_cli::SssError;
+
+#[test]
+fn pcr_selections_are_checked_before_the_tpm_sees_them() {
+    assert_eq!(parse_pcrs(DEFAULT_PCRS).unwrap(), "sha256:0,2,4,7");
+    assert_eq!(parse_pcrs(" SHA1:7 ").unwrap(), "sha1:7");
+    for bad in ["", "sha256", "sha256:", "md5:0", "sha256:0,24", "sha256:0,,1", "0,2,4,7"] {
+        assert!(matches!(parse_pcrs(bad), Err(SssError::InvalidArgument(_))), "{}", bad);
+    }
+}
+
+#[test]
+fn sealed_files_are_recognised_by_name_and_checked_on_read() {
+    assert!(is_sealed(Path::new("laptop.tpm.json")));
+    assert!(!is_sealed(Path::new("share.fragments.json")));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.tpm.json");
+    std::fs::write(&path, r#"{"index": 1}"#).unwrap();
+    assert!(matches!(read(&path), Err(SssError::Parse(_))));
+}
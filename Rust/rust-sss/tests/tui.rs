@@ -0,0 +1,95 @@
+#![cfg(feature = "tui")]
+
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Terminal;
+use shamir_cli::format::ShareHeader;
+use shamir_cli::tui::{Ceremony, Entry, Stage, Status, Step};
+use shamir_cli::Result;
+
+fn check(entered: &str) -> Result<(Entry, Option<u8>)> {
+    let good = entered.starts_with("good");
+    let entry = Entry {
+        subject: format!("Share {}", entered),
+        status: if good { Status::Accepted } else { Status::Rejected },
+        index: good.then_some(entered.len() as u8),
+        id: None,
+        header: ShareHeader { label: good.then(|| "Mom".to_string()), ..Default::default() },
+        notes: if good { Vec::new() } else { vec!["Share rejected: not a share".into()] },
+    };
+    Ok((entry, good.then_some(2)))
+}
+
+fn press(ceremony: &mut Ceremony, keys: &str) -> Step {
+    let mut step = Step::Continue;
+    for c in keys.chars() {
+        step = ceremony.key(KeyEvent::from(KeyCode::Char(c)), &mut check).unwrap();
+    }
+    step
+}
+
+fn enter(ceremony: &mut Ceremony) -> Step {
+    ceremony.key(KeyEvent::from(KeyCode::Enter), &mut check).unwrap()
+}
+
+fn screen(ceremony: &mut Ceremony) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|frame| ceremony.draw(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let width = buffer.area.width as usize;
+    let symbols: Vec<&str> = buffer.content.iter().map(|cell| cell.symbol()).collect();
+    symbols.chunks(width).map(|row| row.concat()).collect::<Vec<_>>().join("\n")
+}
+
+#[test]
+fn shares_are_masked_and_checked_as_they_come_in() {
+    let mut ceremony = Ceremony::new(None);
+    press(&mut ceremony, "junk");
+    enter(&mut ceremony);
+    assert_eq!(ceremony.entries()[0].status, Status::Rejected);
+    let shown = screen(&mut ceremony);
+    assert!(shown.contains("✗ Share junk"), "{}", shown);
+    assert!(shown.contains("Share rejected: not a share"), "{}", shown);
+
+    press(&mut ceremony, "good-1");
+    let shown = screen(&mut ceremony);
+    assert!(shown.contains("••••••") && !shown.contains("good-1"), "{}", shown);
+    enter(&mut ceremony);
+    assert!(screen(&mut ceremony).contains("1 of 2 shares"));
+
+    enter(&mut ceremony);
+    assert_eq!(ceremony.stage(), Stage::Shares);
+    assert!(screen(&mut ceremony).contains("1 more share(s) are needed"));
+
+    press(&mut ceremony, "good-22");
+    enter(&mut ceremony);
+    let shown = screen(&mut ceremony);
+    assert!(shown.contains("2 of 2 shares") && shown.contains("(index 7) Mom"), "{}", shown);
+}
+
+#[test]
+fn recovery_waits_for_confirmation() {
+    let mut ceremony = Ceremony::new(Some(2));
+    for share in ["good-1", "good-22"] {
+        press(&mut ceremony, share);
+        enter(&mut ceremony);
+    }
+    enter(&mut ceremony);
+    assert_eq!(ceremony.stage(), Stage::Password);
+    press(&mut ceremony, "hunter2");
+    assert!(!screen(&mut ceremony).contains("hunter2"));
+    enter(&mut ceremony);
+
+    let shown = screen(&mut ceremony);
+    assert!(shown.contains("Recover the secret from these 2 shares?"), "{}", shown);
+    assert_eq!(press(&mut ceremony, "n"), Step::Continue);
+    assert_eq!(ceremony.stage(), Stage::Password);
+    enter(&mut ceremony);
+    match press(&mut ceremony, "y") {
+        Step::Recover(password) => assert_eq!(password.as_str(), "hunter2"),
+        step => panic!("{:?}", step),
+    }
+
+    let mut ceremony = Ceremony::new(Some(2));
+    assert_eq!(ceremony.key(KeyEvent::from(KeyCode::Esc), &mut check).unwrap(), Step::Cancel);
+}
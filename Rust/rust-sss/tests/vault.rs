@@ -0,0 +1,98 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::shamir::vault::{self, InitOutput};
+use support::Workspace;
+
+// 3-of-3 unseal keys made with a line-by-line port of Vault's shamir.Split (mult, Horner
+// evaluation, y bytes followed by x) with the x coordinates Vault would have picked at random.
+const SECRET: &str = "eb74add2213401985d530fb223f8146be679cfdea30b6b4d05d6a867eda7fab0";
+const KEYS: [&str; 3] = [
+    "XHu8NR0xNvksXFOqRB2iVMjy2zcqwMajP/IotmeHejsq",
+    "NwH+C1l4iGbE0C9TTGmW3cohX4CUHZEDMBLfQNdMNmgH",
+    "u7i6bjOynihTMU9sjClB0nBVbp9v0bh8QimKhXKEVwzD",
+];
+
+fn init_json(keys: &[String], threshold: u8) -> String {
+    serde_json::json!({
+        "unseal_keys_b64": keys,
+        "unseal_keys_hex": [],
+        "unseal_shares": keys.len(),
+        "unseal_threshold": threshold,
+        "root_token": "hvs.not-a-real-token",
+    })
+    .to_string()
+}
+
+#[test]
+fn vault_unseal_keys_reconstruct() {
+    let shares: Vec<Vec<u8>> = KEYS.iter().map(|k| vault::parse_unseal_key(k).unwrap()).collect();
+    assert_eq!(shares[0][0], 0x2a);
+    assert_eq!(hex::encode(vault::reconstruct(&shares).unwrap()), SECRET);
+    assert_ne!(hex::encode(vault::reconstruct(&shares[..2]).unwrap()), SECRET);
+
+    let as_hex = hex::encode(base64::Engine::decode(&base64::engine::general_purpose::STANDARD, KEYS[1]).unwrap());
+    assert_eq!(vault::parse_unseal_key(&as_hex).unwrap(), shares[1]);
+    assert_eq!(vault::unseal_key(&shares[2]), KEYS[2]);
+}
+
+#[test]
+fn resplit_keeps_the_key_and_catches_mistyped_keys() {
+    let keys: Vec<String> = KEYS.iter().map(|k| k.to_string()).collect();
+    let init: InitOutput = serde_json::from_str(&init_json(&keys, 3)).unwrap();
+    let shares = vault::resplit(&init, 2, 5).unwrap();
+    assert_eq!(shares.len(), 5);
+    assert_eq!(hex::encode(vault::reconstruct(&shares[3..]).unwrap()), SECRET);
+
+    let output = InitOutput::from_shares(&shares, 2);
+    let document = serde_json::to_string(&output).unwrap();
+    assert!(!document.contains("root_token"));
+    let reread: InitOutput = serde_json::from_str(&document).unwrap();
+    assert_eq!(reread.shares().unwrap(), (shares.clone(), 2));
+
+    let mut mistyped = reread.unseal_keys_b64.clone();
+    mistyped[3] = vault::unseal_key(&{
+        let mut share = shares[3].clone();
+        share[5] ^= 1;
+        share
+    });
+    let init: InitOutput = serde_json::from_str(&init_json(&mistyped, 2)).unwrap();
+    assert!(matches!(vault::resplit(&init, 2, 3), Err(shamir_cli::SssError::Share(_))));
+}
+
+#[test]
+fn cli_vault_field_and_resplit() {
+    let ws = Workspace::new();
+    let mut sss = ws.sss(&["encrypt", "--field", "vault"]);
+    sss.answer("Enter secret value", "unseal me");
+    sss.answer("Enter encryption password", "pw");
+    sss.answer("Confirm encryption password", "pw");
+    sss.answer("Total number of shares", "3");
+    sss.answer("Minimum shares required", "2");
+    sss.confirm("recovery cards", false);
+    let outcome = sss.finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let keys: Vec<String> = outcome
+        .output
+        .lines()
+        .filter(|l| l.starts_with("Share ID"))
+        .filter_map(|l| l.rsplit(' ').next().map(str::to_string))
+        .collect();
+    assert_eq!(keys.len(), 3, "{}", outcome.output);
+    assert!(vault::parse_unseal_key(&keys[0]).is_ok(), "{}", keys[0]);
+
+    let outcome = ws.combine(&["--field", "vault"], &keys[1..], "pw");
+    assert_eq!(outcome.recovered(), Some("unseal me"), "{}", outcome.output);
+
+    let original: Vec<String> = KEYS.iter().map(|k| k.to_string()).collect();
+    std::fs::write(ws.path("init.json"), init_json(&original, 3)).unwrap();
+    let outcome = ws
+        .sss(&["vault", "resplit", "--init", "init.json", "--threshold", "2", "--total", "4", "--out", "new.json"])
+        .finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    let new: InitOutput = serde_json::from_str(&std::fs::read_to_string(ws.path("new.json")).unwrap()).unwrap();
+    let (shares, threshold) = new.shares().unwrap();
+    assert_eq!((shares.len(), threshold), (4, 2));
+    assert_eq!(hex::encode(vault::reconstruct(&shares[1..3]).unwrap()), SECRET);
+}
@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+mod support;
+
+use shamir_cli::vectors::{self, VectorFile};
+use support::Workspace;
+
+fn published() -> VectorFile {
+    serde_json::from_str(include_str!("data/vectors.json")).unwrap()
+}
+
+#[test]
+fn published_vectors_are_still_generated_and_verify() {
+    let published = published();
+    assert_eq!(vectors::generate().unwrap().vectors, published.vectors, "the share format changed");
+    for vector in &published.vectors {
+        vectors::verify(vector).unwrap_or_else(|e| panic!("{}: {}", vector.name, e));
+    }
+
+    let mut tampered = published.vectors[1].clone();
+    tampered.shares[0].replace_range(4..6, "00");
+    assert!(vectors::verify(&tampered).is_err());
+    let mut tampered = published.vectors[0].clone();
+    tampered.password.push('!');
+    assert!(vectors::verify(&tampered).is_err());
+
+    // vectors published before sets were bound still open
+    let unbound: VectorFile = serde_json::from_str(include_str!("data/vectors-unbound.json")).unwrap();
+    for vector in &unbound.vectors {
+        vectors::verify(vector).unwrap_or_else(|e| panic!("unbound {}: {}", vector.name, e));
+    }
+
+    // and so do those published before every GF(256) set had two slots
+    let one_slot: VectorFile = serde_json::from_str(include_str!("data/vectors-one-slot.json")).unwrap();
+    for vector in &one_slot.vectors {
+        vectors::verify(vector).unwrap_or_else(|e| panic!("one slot {}: {}", vector.name, e));
+    }
+}
+
+#[test]
+fn cli_reproduces_a_vector_and_verifies_files() {
+    let vector = published().vectors.remove(0);
+    let ws = Workspace::new();
+    let shares = ws.split(
+        &["--deterministic-seed", &vector.name, "--kdf-rounds", "1000", "--padding", &vector.padding],
+        &vector.secret,
+        &vector.password,
+        vector.total,
+        vector.threshold,
+    );
+    let expected: Vec<String> = vector.shares.iter().map(|s| format!("{:?}", hex::decode(s).unwrap())).collect();
+    assert_eq!(shares, expected);
+
+    assert!(ws.sss(&["test-vectors", "generate", "--out", "vectors.json"]).finish().success());
+    let outcome = ws.sss(&["test-vectors", "verify", "vectors.json"]).finish();
+    assert!(outcome.success(), "{}", outcome.output);
+    assert!(outcome.output.contains("ok      vault-2-of-4"), "{}", outcome.output);
+
+    let text = std::fs::read_to_string(ws.path("vectors.json")).unwrap();
+    std::fs::write(ws.path("vectors.json"), text.replace("password for int-5-of-5", "guess")).unwrap();
+    let outcome = ws.sss(&["test-vectors", "verify", "vectors.json"]).finish();
+    assert_eq!(outcome.code, 6, "{}", outcome.output);
+    assert!(outcome.output.contains("FAILED  int-5-of-5"), "{}", outcome.output);
+}
@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use shamir_cli::yubikey::{is_wrapped, read, wrap};
+use shamir_cli::SssError;
+
+#[test]
+fn only_the_two_otp_slots_are_accepted() {
+    for slot in [0, 3] {
+        assert!(matches!(wrap(&[1, 2, 3], slot), Err(SssError::Parse(_))), "{}", slot);
+    }
+}
+
+#[test]
+fn wrapped_files_are_recognised_by_name_and_checked_on_read() {
+    assert!(is_wrapped(Path::new("keychain.yubikey.json")));
+    assert!(!is_wrapped(Path::new("laptop.tpm.json")));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.yubikey.json");
+    std::fs::write(&path, r#"{"index": 1}"#).unwrap();
+    assert!(matches!(read(&path), Err(SssError::Parse(_))));
+    std::fs::write(
+        &path,
+        r#"{"format_version": 1, "index": 1, "id": "00000000", "slot": 2, "serial": null,
+            "challenge": "00", "nonce": "0011", "ciphertext": "00"}"#,
+    )
+    .unwrap();
+    assert!(matches!(read(&path), Err(SssError::Parse(_))));
+}